@@ -0,0 +1,200 @@
+//! Benchmarks for the DAO hot paths, run with `cargo bench --features bench`.
+//!
+//! Each run creates an isolated schema in the database at `DATABASE_URL`, applies the migrations
+//! to it and drops it once the benchmarks complete. Alongside criterion's throughput report, the
+//! p99 latency of the individual DAO calls is printed for each benchmark.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::types::Uuid;
+use sqlx::{Executor, PgPool};
+use tokio::runtime::Runtime;
+use question_answer::fixtures;
+use question_answer::models::EntityId;
+use question_answer::persistence::{migrations, AnswerDao, AnswerDaoImpl, QuestionDao, QuestionDaoImpl};
+
+/// The number of concurrent tasks used when benchmarking likes.
+const CONCURRENCY: usize = 32;
+
+/// A connection pool whose connections use an isolated, freshly migrated schema.
+struct BenchDb {
+    pool: PgPool,
+    admin: PgPool,
+    schema: String,
+}
+
+impl BenchDb {
+    async fn setup() -> Self {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set to run the benchmarks");
+        let admin = PgPool::connect(&url).await.expect("database should be reachable");
+        let schema = format!("bench_{}", Uuid::new_v4().simple());
+        admin.execute(format!("CREATE SCHEMA {schema}").as_str()).await.expect("schema should be created");
+        let search_path = format!("SET search_path TO {schema}, public");
+        let pool = PgPoolOptions::new()
+            .max_connections(CONCURRENCY as u32)
+            .after_connect(move |conn, _| {
+                let search_path = search_path.clone();
+                Box::pin(async move { conn.execute(search_path.as_str()).await.map(|_| ()) })
+            })
+            .connect(&url)
+            .await
+            .expect("database should be reachable");
+        migrations::run(&pool).await.expect("migrations should be applied");
+        Self { pool, admin, schema }
+    }
+
+    async fn truncate(&self) {
+        self.pool.execute("TRUNCATE questions CASCADE").await.expect("tables should be truncated");
+    }
+
+    async fn teardown(self) {
+        self.pool.close().await;
+        self.admin.execute(format!("DROP SCHEMA {} CASCADE", self.schema).as_str()).await.expect("schema should be dropped");
+    }
+}
+
+/// Records the latency of individual DAO calls so their p99 can be reported.
+#[derive(Default)]
+struct Latencies(Mutex<Vec<Duration>>);
+
+impl Latencies {
+    fn record(&self, latency: Duration) {
+        self.0.lock().unwrap().push(latency);
+    }
+
+    /// Prints the p99 latency of the calls recorded so far and resets the recorder.
+    fn report(&self, name: &str) {
+        let mut latencies = std::mem::take(&mut *self.0.lock().unwrap());
+        if latencies.is_empty() {
+            return;
+        }
+        latencies.sort();
+        let p99 = latencies[(latencies.len() * 99 / 100).min(latencies.len() - 1)];
+        println!("{name}: p99 latency {p99:?} over {} calls", latencies.len());
+    }
+}
+
+/// Times a single call, recording its latency.
+async fn timed<T>(latencies: &Latencies, call: impl std::future::Future<Output = T>) -> Duration {
+    let start = Instant::now();
+    call.await;
+    let elapsed = start.elapsed();
+    latencies.record(elapsed);
+    elapsed
+}
+
+fn bench_dao(c: &mut Criterion) {
+    let rt = Runtime::new().expect("runtime should be created");
+    let db = rt.block_on(BenchDb::setup());
+    let question_dao = Arc::new(QuestionDaoImpl::new(db.pool.clone()));
+    let answer_dao = AnswerDaoImpl::new(db.pool.clone());
+    let latencies = Arc::new(Latencies::default());
+
+    let mut group = c.benchmark_group("questions");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("create_question", |b| {
+        b.to_async(&rt).iter_custom(|iters| {
+            let (question_dao, latencies) = (&question_dao, &latencies);
+            async move {
+                let mut total = Duration::ZERO;
+                for _ in 0..iters {
+                    total += timed(latencies, question_dao.create_question(fixtures::question().build())).await;
+                }
+                total
+            }
+        })
+    });
+    latencies.report("questions/create_question");
+
+    let question_id = rt.block_on(fixtures::seed_questions(question_dao.as_ref(), 1))[0];
+    group.bench_function("get_question", |b| {
+        b.to_async(&rt).iter_custom(|iters| {
+            let (question_dao, latencies) = (&question_dao, &latencies);
+            async move {
+                let mut total = Duration::ZERO;
+                for _ in 0..iters {
+                    total += timed(latencies, question_dao.get_question(EntityId::new(question_id.to_string()))).await;
+                }
+                total
+            }
+        })
+    });
+    latencies.report("questions/get_question");
+
+    rt.block_on(db.truncate());
+    let mut seeded = 0;
+    for rows in [1_000, 10_000] {
+        rt.block_on(fixtures::seed_questions(question_dao.as_ref(), rows - seeded));
+        seeded = rows;
+        group.throughput(Throughput::Elements(rows as u64));
+        group.bench_with_input(BenchmarkId::new("get_questions", rows), &rows, |b, _| {
+            b.to_async(&rt).iter_custom(|iters| {
+                let (question_dao, latencies) = (&question_dao, &latencies);
+                async move {
+                    let mut total = Duration::ZERO;
+                    for _ in 0..iters {
+                        total += timed(latencies, question_dao.get_questions()).await;
+                    }
+                    total
+                }
+            })
+        });
+        latencies.report(&format!("questions/get_questions/{rows}"));
+    }
+
+    let question_id = rt.block_on(fixtures::seed_questions(question_dao.as_ref(), 1))[0];
+    group.throughput(Throughput::Elements(CONCURRENCY as u64));
+    group.bench_function(BenchmarkId::new("increment_question_likes", CONCURRENCY), |b| {
+        b.to_async(&rt).iter_custom(|iters| {
+            let (question_dao, latencies) = (question_dao.clone(), latencies.clone());
+            async move {
+                let start = Instant::now();
+                for _ in 0..iters {
+                    let tasks: Vec<_> = (0..CONCURRENCY)
+                        .map(|_| {
+                            let (question_dao, latencies) = (question_dao.clone(), latencies.clone());
+                            tokio::spawn(async move {
+                                timed(&latencies, question_dao.increment_question_likes(EntityId::new(question_id.to_string()))).await;
+                            })
+                        })
+                        .collect();
+                    for task in tasks {
+                        task.await.expect("task should complete");
+                    }
+                }
+                start.elapsed()
+            }
+        })
+    });
+    latencies.report(&format!("questions/increment_question_likes/{CONCURRENCY}"));
+    group.finish();
+
+    let mut group = c.benchmark_group("answers");
+    let (question_id, _) = rt.block_on(fixtures::seed_question_with_answers(question_dao.as_ref(), &answer_dao, 500));
+    group.throughput(Throughput::Elements(500));
+    group.bench_function("get_answers", |b| {
+        b.to_async(&rt).iter_custom(|iters| {
+            let (answer_dao, latencies) = (&answer_dao, &latencies);
+            async move {
+                let mut total = Duration::ZERO;
+                for _ in 0..iters {
+                    total += timed(latencies, answer_dao.get_answers(EntityId::new(question_id.to_string()))).await;
+                }
+                total
+            }
+        })
+    });
+    latencies.report("answers/get_answers/500");
+    group.finish();
+
+    rt.block_on(db.teardown());
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(20).measurement_time(Duration::from_secs(5));
+    targets = bench_dao
+}
+criterion_main!(benches);
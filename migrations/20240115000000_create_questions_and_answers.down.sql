@@ -0,0 +1,3 @@
+-- Drops the questions and answers tables.
+DROP TABLE IF EXISTS answers;
+DROP TABLE IF EXISTS questions;
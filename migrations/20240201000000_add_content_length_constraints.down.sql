@@ -0,0 +1,7 @@
+-- Removes the content length constraints.
+ALTER TABLE answers
+    DROP CONSTRAINT IF EXISTS answers_answer_length;
+
+ALTER TABLE questions
+    DROP CONSTRAINT IF EXISTS questions_question_length,
+    DROP CONSTRAINT IF EXISTS questions_title_length;
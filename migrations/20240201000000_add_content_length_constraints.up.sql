@@ -0,0 +1,7 @@
+-- Ensures the database agrees with the default `ContentLimits` used by the DAOs.
+ALTER TABLE questions
+    ADD CONSTRAINT questions_title_length CHECK (char_length(title) <= 300),
+    ADD CONSTRAINT questions_question_length CHECK (char_length(question) <= 30000);
+
+ALTER TABLE answers
+    ADD CONSTRAINT answers_answer_length CHECK (char_length(answer) <= 30000);
@@ -0,0 +1,4 @@
+-- Dissociates answers from their authors and drops the users table.
+ALTER TABLE answers DROP COLUMN IF EXISTS author_id;
+
+DROP TABLE IF EXISTS users;
@@ -0,0 +1,10 @@
+-- Creates the users table and associates answers with their (optional) authors.
+CREATE TABLE IF NOT EXISTS users (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    username TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+ALTER TABLE answers ADD COLUMN author_id UUID NULL REFERENCES users (id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS answers_author_id_idx ON answers (author_id);
@@ -0,0 +1,2 @@
+-- Drops the idempotency keys table.
+DROP TABLE IF EXISTS idempotency_keys;
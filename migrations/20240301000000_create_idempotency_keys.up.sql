@@ -0,0 +1,9 @@
+-- Stores the idempotency keys supplied when creating questions, so retried requests are not duplicated.
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    key TEXT PRIMARY KEY,
+    entity_id UUID NOT NULL,
+    request_hash TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idempotency_keys_created_at_idx ON idempotency_keys (created_at);
@@ -0,0 +1,2 @@
+-- Drops the subscriptions table.
+DROP TABLE IF EXISTS subscriptions;
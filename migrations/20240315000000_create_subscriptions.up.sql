@@ -0,0 +1,9 @@
+-- Records which questions a user follows and when they last fetched updates for them.
+CREATE TABLE IF NOT EXISTS subscriptions (
+    user_token TEXT NOT NULL,
+    question_id UUID NOT NULL REFERENCES questions (id) ON DELETE CASCADE,
+    last_seen_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (user_token, question_id)
+);
+
+CREATE INDEX IF NOT EXISTS subscriptions_question_id_idx ON subscriptions (question_id);
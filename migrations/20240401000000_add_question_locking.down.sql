@@ -0,0 +1,4 @@
+-- Removes the edit and lock timestamps from questions.
+ALTER TABLE questions
+    DROP COLUMN IF EXISTS locked_at,
+    DROP COLUMN IF EXISTS updated_at;
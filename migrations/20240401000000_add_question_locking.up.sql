@@ -0,0 +1,4 @@
+-- Tracks when questions were last edited and when they were locked against edits.
+ALTER TABLE questions
+    ADD COLUMN updated_at TIMESTAMPTZ NULL,
+    ADD COLUMN locked_at TIMESTAMPTZ NULL;
@@ -0,0 +1,4 @@
+-- Removes the sequential secondary ids, along with their sequences.
+ALTER TABLE answers DROP COLUMN IF EXISTS serial;
+
+ALTER TABLE questions DROP COLUMN IF EXISTS serial;
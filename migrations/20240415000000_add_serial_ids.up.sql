@@ -0,0 +1,4 @@
+-- Adds sequential secondary ids to questions and answers for legacy clients that use numeric ids.
+ALTER TABLE questions ADD COLUMN serial BIGSERIAL NOT NULL UNIQUE;
+
+ALTER TABLE answers ADD COLUMN serial BIGSERIAL NOT NULL UNIQUE;
@@ -0,0 +1,4 @@
+-- Removes answer drafts, deleting any that were never published.
+DELETE FROM answers WHERE NOT published;
+
+ALTER TABLE answers DROP COLUMN IF EXISTS published;
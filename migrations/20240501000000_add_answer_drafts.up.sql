@@ -0,0 +1,4 @@
+-- Allows answers to be saved as drafts, which are hidden from listings until published.
+ALTER TABLE answers ADD COLUMN published BOOLEAN NOT NULL DEFAULT true;
+
+CREATE INDEX IF NOT EXISTS answers_drafts_idx ON answers (question_id, author_id) WHERE NOT published;
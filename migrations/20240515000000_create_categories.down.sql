@@ -0,0 +1,4 @@
+-- Drops the category tree, uncategorizing every question.
+ALTER TABLE questions DROP COLUMN IF EXISTS category_id;
+
+DROP TABLE IF EXISTS categories;
@@ -0,0 +1,16 @@
+-- Creates the category tree questions can be filed under.
+CREATE TABLE IF NOT EXISTS categories (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL CHECK (char_length(name) BETWEEN 1 AND 300),
+    parent_id UUID NULL REFERENCES categories (id) ON DELETE RESTRICT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK (parent_id <> id)
+);
+
+-- Sibling names are unique, top level categories are treated as siblings of each other
+CREATE UNIQUE INDEX IF NOT EXISTS categories_sibling_name_idx
+    ON categories (COALESCE(parent_id, '00000000-0000-0000-0000-000000000000'::uuid), name);
+
+ALTER TABLE questions ADD COLUMN category_id UUID NULL REFERENCES categories (id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS questions_category_id_idx ON questions (category_id);
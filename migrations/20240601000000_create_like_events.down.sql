@@ -0,0 +1,4 @@
+-- Drops the like audit log.
+DROP TABLE IF EXISTS like_events;
+
+DROP FUNCTION IF EXISTS like_events_append_only();
@@ -0,0 +1,22 @@
+-- Records every change to a like counter, so it is known when likes happened and not just how many there are.
+CREATE TABLE IF NOT EXISTS like_events (
+    id BIGSERIAL PRIMARY KEY,
+    entity_type TEXT NOT NULL CHECK (entity_type IN ('question', 'answer')),
+    -- Not a foreign key, events outlive the questions and answers they refer to
+    entity_id UUID NOT NULL,
+    delta INTEGER NOT NULL,
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    source_token TEXT NULL
+);
+
+CREATE INDEX IF NOT EXISTS like_events_entity_id_idx ON like_events (entity_id, occurred_at);
+
+CREATE OR REPLACE FUNCTION like_events_append_only() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'like_events is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER like_events_append_only
+    BEFORE UPDATE OR DELETE ON like_events
+    FOR EACH STATEMENT EXECUTE FUNCTION like_events_append_only();
@@ -0,0 +1,2 @@
+-- Removes the external ids of questions.
+ALTER TABLE questions DROP COLUMN IF EXISTS external_id;
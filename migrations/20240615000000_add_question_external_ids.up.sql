@@ -0,0 +1,2 @@
+-- Identifies questions synced from an external system by the id they have there.
+ALTER TABLE questions ADD COLUMN external_id TEXT NULL UNIQUE;
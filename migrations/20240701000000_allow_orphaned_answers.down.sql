@@ -0,0 +1,4 @@
+-- Requires every answer to belong to a question again, deleting any orphaned answers.
+DELETE FROM answers WHERE question_id IS NULL;
+
+ALTER TABLE answers ALTER COLUMN question_id SET NOT NULL;
@@ -0,0 +1,2 @@
+-- Allows answers to outlive their question when it is deleted with the orphan policy.
+ALTER TABLE answers ALTER COLUMN question_id DROP NOT NULL;
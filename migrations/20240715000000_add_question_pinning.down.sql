@@ -0,0 +1,2 @@
+-- Removes question pinning.
+ALTER TABLE questions DROP COLUMN IF EXISTS pinned_at;
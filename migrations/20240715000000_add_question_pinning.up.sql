@@ -0,0 +1,4 @@
+-- Allows questions to be pinned to the top of listings.
+ALTER TABLE questions ADD COLUMN pinned_at TIMESTAMPTZ NULL;
+
+CREATE INDEX IF NOT EXISTS questions_pinned_at_idx ON questions (pinned_at) WHERE pinned_at IS NOT NULL;
@@ -0,0 +1,12 @@
+-- Narrows like counters back to integers, clamping any that no longer fit.
+ALTER TABLE like_events DISABLE TRIGGER like_events_append_only;
+
+UPDATE like_events SET delta = GREATEST(LEAST(delta, 2147483647), -2147483648) WHERE delta NOT BETWEEN -2147483648 AND 2147483647;
+
+ALTER TABLE like_events ENABLE TRIGGER like_events_append_only;
+
+ALTER TABLE like_events ALTER COLUMN delta TYPE INTEGER;
+
+ALTER TABLE answers ALTER COLUMN likes TYPE INTEGER USING LEAST(likes, 2147483647);
+
+ALTER TABLE questions ALTER COLUMN likes TYPE INTEGER USING LEAST(likes, 2147483647);
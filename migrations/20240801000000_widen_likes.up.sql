@@ -0,0 +1,6 @@
+-- Widens like counters, and the changes recorded to them, so that they cannot overflow.
+ALTER TABLE questions ALTER COLUMN likes TYPE BIGINT;
+
+ALTER TABLE answers ALTER COLUMN likes TYPE BIGINT;
+
+ALTER TABLE like_events ALTER COLUMN delta TYPE BIGINT;
@@ -0,0 +1,4 @@
+-- Removes the normalized titles of questions.
+DROP INDEX IF EXISTS questions_title_normalized_idx;
+
+ALTER TABLE questions DROP COLUMN IF EXISTS title_normalized;
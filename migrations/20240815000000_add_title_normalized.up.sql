@@ -0,0 +1,8 @@
+-- Stores the normalized title of each question, maintained by the data access objects, so that titles can be
+-- compared regardless of case, accents and compatibility characters using an index.
+ALTER TABLE questions ADD COLUMN title_normalized TEXT NULL;
+
+-- Existing titles are only lowercased, they are fully normalized the next time they are written
+UPDATE questions SET title_normalized = lower(title);
+
+CREATE INDEX IF NOT EXISTS questions_title_normalized_idx ON questions (title_normalized);
@@ -0,0 +1,6 @@
+-- Drops the votes on answers.
+DROP TABLE IF EXISTS answer_votes;
+
+ALTER TABLE answers DROP COLUMN IF EXISTS unhelpful_count;
+
+ALTER TABLE answers DROP COLUMN IF EXISTS helpful_count;
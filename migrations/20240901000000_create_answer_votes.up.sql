@@ -0,0 +1,13 @@
+-- Counts the helpful and unhelpful votes on each answer, separately from its likes.
+ALTER TABLE answers ADD COLUMN helpful_count BIGINT NOT NULL DEFAULT 0;
+
+ALTER TABLE answers ADD COLUMN unhelpful_count BIGINT NOT NULL DEFAULT 0;
+
+-- Stores the current vote of each user on an answer, so a user votes at most once per answer.
+CREATE TABLE IF NOT EXISTS answer_votes (
+    answer_id UUID NOT NULL REFERENCES answers (id) ON DELETE CASCADE,
+    user_token TEXT NOT NULL,
+    vote TEXT NOT NULL CHECK (vote IN ('helpful', 'unhelpful')),
+    voted_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (answer_id, user_token)
+);
@@ -0,0 +1,4 @@
+-- Drops the view counts of questions.
+DROP TABLE IF EXISTS question_views;
+
+ALTER TABLE questions DROP COLUMN IF EXISTS views;
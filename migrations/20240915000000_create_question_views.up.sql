@@ -0,0 +1,10 @@
+-- Counts the views of each question.
+ALTER TABLE questions ADD COLUMN IF NOT EXISTS views INTEGER NOT NULL DEFAULT 0;
+
+-- Stores when each client last had a view of a question counted, so repeated views within a window count once.
+CREATE TABLE IF NOT EXISTS question_views (
+    question_id UUID NOT NULL REFERENCES questions (id) ON DELETE CASCADE,
+    client_token TEXT NOT NULL,
+    viewed_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (question_id, client_token)
+);
@@ -0,0 +1,10 @@
+-- Drops the character and word counts of question and answer content.
+DROP INDEX IF EXISTS questions_word_count_idx;
+
+ALTER TABLE answers DROP COLUMN IF EXISTS word_count;
+
+ALTER TABLE answers DROP COLUMN IF EXISTS char_count;
+
+ALTER TABLE questions DROP COLUMN IF EXISTS word_count;
+
+ALTER TABLE questions DROP COLUMN IF EXISTS char_count;
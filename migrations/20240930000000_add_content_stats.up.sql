@@ -0,0 +1,11 @@
+-- Stores the character and word counts of question and answer content, maintained by the data access objects.
+-- Rows that existed before this migration keep zero counts until they are recomputed.
+ALTER TABLE questions ADD COLUMN char_count INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE questions ADD COLUMN word_count INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE answers ADD COLUMN char_count INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE answers ADD COLUMN word_count INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS questions_word_count_idx ON questions (word_count);
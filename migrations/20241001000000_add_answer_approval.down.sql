@@ -0,0 +1,4 @@
+-- Drops the moderation of answers.
+DROP TABLE IF EXISTS answer_rejections;
+
+ALTER TABLE answers DROP COLUMN IF EXISTS approved_at;
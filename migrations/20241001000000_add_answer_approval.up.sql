@@ -0,0 +1,15 @@
+-- Records when a moderator approved an answer, for communities that review answers before showing them.
+ALTER TABLE answers ADD COLUMN approved_at TIMESTAMPTZ NULL;
+
+-- Answers published before moderation existed were already visible, so they count as approved.
+UPDATE answers SET approved_at = created_at WHERE published;
+
+-- Keeps a record of the answers moderators rejected, which are deleted from answers.
+CREATE TABLE IF NOT EXISTS answer_rejections (
+    answer_id UUID PRIMARY KEY,
+    question_id UUID NULL,
+    author_id UUID NULL,
+    answer TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    rejected_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
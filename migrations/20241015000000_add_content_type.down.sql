@@ -0,0 +1,4 @@
+-- Drops the content type of questions and answers.
+ALTER TABLE answers DROP COLUMN IF EXISTS content_type;
+
+ALTER TABLE questions DROP COLUMN IF EXISTS content_type;
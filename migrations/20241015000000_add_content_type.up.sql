@@ -0,0 +1,7 @@
+-- Stores whether question and answer content is plain text or markdown, so clients know how to render it.
+-- Rows that existed before this migration are plain text.
+ALTER TABLE questions ADD COLUMN content_type TEXT NOT NULL DEFAULT 'text'
+    CONSTRAINT questions_content_type_check CHECK (content_type IN ('text', 'markdown'));
+
+ALTER TABLE answers ADD COLUMN content_type TEXT NOT NULL DEFAULT 'text'
+    CONSTRAINT answers_content_type_check CHECK (content_type IN ('text', 'markdown'));
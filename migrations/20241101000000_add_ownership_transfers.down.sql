@@ -0,0 +1,6 @@
+-- Drops the record of ownership transfers and the authors of questions.
+DROP TABLE IF EXISTS ownership_transfers;
+
+DROP INDEX IF EXISTS questions_author_id_idx;
+
+ALTER TABLE questions DROP COLUMN IF EXISTS author_id;
@@ -0,0 +1,17 @@
+-- Associates questions with their (optional) authors, and records every change of the author of a question or
+-- an answer.
+ALTER TABLE questions ADD COLUMN author_id UUID NULL REFERENCES users (id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS questions_author_id_idx ON questions (author_id);
+
+CREATE TABLE IF NOT EXISTS ownership_transfers (
+    id BIGSERIAL PRIMARY KEY,
+    entity_type TEXT NOT NULL CHECK (entity_type IN ('question', 'answer')),
+    -- Not foreign keys, transfers outlive the content and the users they refer to
+    entity_id UUID NOT NULL,
+    previous_author_id UUID NULL,
+    new_author_id UUID NOT NULL,
+    transferred_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS ownership_transfers_entity_id_idx ON ownership_transfers (entity_id, transferred_at);
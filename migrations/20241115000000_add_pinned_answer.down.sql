@@ -0,0 +1,2 @@
+-- Drops the pinned answers of questions.
+ALTER TABLE questions DROP COLUMN IF EXISTS pinned_answer_id;
@@ -0,0 +1,2 @@
+-- Lets the author of a question pin one of its answers above the others. Deleting the answer unpins it.
+ALTER TABLE questions ADD COLUMN pinned_answer_id UUID NULL REFERENCES answers (id) ON DELETE SET NULL;
@@ -0,0 +1,4 @@
+-- Drops the tags and the questions labelled with them.
+DROP TABLE IF EXISTS question_tags;
+
+DROP TABLE IF EXISTS tags;
@@ -0,0 +1,21 @@
+-- Creates the tags questions can be labelled with. Usage counts are computed from the junction table rather than
+-- stored, so they cannot drift when questions are deleted.
+CREATE TABLE IF NOT EXISTS tags (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL CHECK (char_length(name) BETWEEN 1 AND 50),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Names are unique regardless of case, and the pattern index serves prefix searches
+CREATE UNIQUE INDEX IF NOT EXISTS tags_name_idx ON tags (lower(name));
+
+CREATE INDEX IF NOT EXISTS tags_name_prefix_idx ON tags (lower(name) text_pattern_ops);
+
+CREATE TABLE IF NOT EXISTS question_tags (
+    question_id UUID NOT NULL REFERENCES questions (id) ON DELETE CASCADE,
+    tag_id UUID NOT NULL REFERENCES tags (id) ON DELETE CASCADE,
+    tagged_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (question_id, tag_id)
+);
+
+CREATE INDEX IF NOT EXISTS question_tags_tag_id_idx ON question_tags (tag_id);
@@ -0,0 +1,2 @@
+-- Drops the moderation flags raised by content policies.
+DROP TABLE IF EXISTS moderation_flags;
@@ -0,0 +1,14 @@
+-- Creates the flags a content policy raises on questions and answers it lets through for a moderator to review.
+-- Each flag concerns exactly one question or answer and is removed along with it.
+CREATE TABLE IF NOT EXISTS moderation_flags (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    question_id UUID REFERENCES questions (id) ON DELETE CASCADE,
+    answer_id UUID REFERENCES answers (id) ON DELETE CASCADE,
+    reason TEXT NOT NULL CHECK (char_length(reason) > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK (num_nonnulls(question_id, answer_id) = 1)
+);
+
+CREATE INDEX IF NOT EXISTS moderation_flags_question_id_idx ON moderation_flags (question_id);
+
+CREATE INDEX IF NOT EXISTS moderation_flags_answer_id_idx ON moderation_flags (answer_id);
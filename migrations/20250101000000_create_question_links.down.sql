@@ -0,0 +1,2 @@
+-- Drops the links between questions.
+DROP TABLE IF EXISTS question_links;
@@ -0,0 +1,15 @@
+-- Creates the links moderators draw between questions. Related links hold in both directions, so a pair of
+-- questions can be related only once whichever way round it was linked.
+CREATE TABLE IF NOT EXISTS question_links (
+    from_id UUID NOT NULL REFERENCES questions (id) ON DELETE CASCADE,
+    to_id UUID NOT NULL REFERENCES questions (id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('related', 'duplicate_of', 'follow_up')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (from_id, to_id, kind),
+    CHECK (from_id <> to_id)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS question_links_related_idx
+    ON question_links (LEAST(from_id, to_id), GREATEST(from_id, to_id)) WHERE kind = 'related';
+
+CREATE INDEX IF NOT EXISTS question_links_to_id_idx ON question_links (to_id);
@@ -0,0 +1,6 @@
+-- Drops the revisions of questions and the trigger recording them.
+DROP TRIGGER IF EXISTS question_revisions_record ON questions;
+
+DROP FUNCTION IF EXISTS question_revisions_record();
+
+DROP TABLE IF EXISTS question_revisions;
@@ -0,0 +1,35 @@
+-- Records the content a question had before each edit, so that it can be read as it was at any past time.
+-- Revisions are written by a trigger, so every statement editing a question is recorded.
+CREATE TABLE IF NOT EXISTS question_revisions (
+    id BIGSERIAL PRIMARY KEY,
+    question_id UUID NOT NULL REFERENCES questions (id) ON DELETE CASCADE,
+    title TEXT NOT NULL,
+    question TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    char_count INTEGER NOT NULL,
+    word_count INTEGER NOT NULL,
+    -- When the replaced content had last been updated, NULL if it was the content the question was created with
+    updated_at TIMESTAMPTZ NULL,
+    -- When the content was replaced
+    revised_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS question_revisions_question_id_idx ON question_revisions (question_id, revised_at);
+
+CREATE OR REPLACE FUNCTION question_revisions_record() RETURNS trigger AS $$
+BEGIN
+    INSERT INTO question_revisions (question_id, title, question, content_type, char_count, word_count, updated_at, revised_at)
+    VALUES (
+        OLD.id, OLD.title, OLD.question, OLD.content_type, OLD.char_count, OLD.word_count, OLD.updated_at,
+        -- Edits set the update time from the clock of the data access object, other statements are timed now
+        CASE WHEN NEW.updated_at IS DISTINCT FROM OLD.updated_at THEN NEW.updated_at ELSE now() END
+    );
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER question_revisions_record
+    AFTER UPDATE OF title, question, content_type ON questions
+    FOR EACH ROW
+    WHEN ((OLD.title, OLD.question, OLD.content_type) IS DISTINCT FROM (NEW.title, NEW.question, NEW.content_type))
+    EXECUTE FUNCTION question_revisions_record();
@@ -0,0 +1,4 @@
+-- Drops the author tokens of questions and answers, along with their indexes.
+ALTER TABLE answers DROP COLUMN IF EXISTS author_token;
+
+ALTER TABLE questions DROP COLUMN IF EXISTS author_token;
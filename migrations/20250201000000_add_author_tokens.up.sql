@@ -0,0 +1,9 @@
+-- Stores the client generated token identifying the anonymous author of questions and answers, so that authors
+-- without an account can list their own posts. Existing rows have no token.
+ALTER TABLE questions ADD COLUMN author_token TEXT;
+
+ALTER TABLE answers ADD COLUMN author_token TEXT;
+
+CREATE INDEX questions_author_token_idx ON questions (author_token, created_at DESC) WHERE author_token IS NOT NULL;
+
+CREATE INDEX answers_author_token_idx ON answers (author_token, created_at DESC) WHERE author_token IS NOT NULL;
@@ -0,0 +1,6 @@
+-- Drops the bounties of questions, along with their index and constraints.
+ALTER TABLE questions DROP CONSTRAINT IF EXISTS questions_bounty_complete;
+
+ALTER TABLE questions DROP COLUMN IF EXISTS bounty_expires_at;
+
+ALTER TABLE questions DROP COLUMN IF EXISTS bounty_amount;
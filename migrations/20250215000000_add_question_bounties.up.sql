@@ -0,0 +1,10 @@
+-- Stores the bounty attached to a question to attract answers, in points, along with the time it expires. Both are
+-- set together, and cleared together once the bounty expires or is withdrawn.
+ALTER TABLE questions ADD COLUMN bounty_amount INT CHECK (bounty_amount BETWEEN 1 AND 500);
+
+ALTER TABLE questions ADD COLUMN bounty_expires_at TIMESTAMPTZ;
+
+ALTER TABLE questions ADD CONSTRAINT questions_bounty_complete
+    CHECK ((bounty_amount IS NULL) = (bounty_expires_at IS NULL));
+
+CREATE INDEX questions_bounty_expires_at_idx ON questions (bounty_expires_at) WHERE bounty_expires_at IS NOT NULL;
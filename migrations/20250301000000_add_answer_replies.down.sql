@@ -0,0 +1,2 @@
+-- Drops the parents of replies, turning them into answers to the question.
+ALTER TABLE answers DROP COLUMN IF EXISTS parent_answer_id;
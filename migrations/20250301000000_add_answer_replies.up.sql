@@ -0,0 +1,6 @@
+-- Stores the answer an answer replies to, for replies one level deep below the answers to a question. Replies are
+-- deleted along with the answer they reply to.
+ALTER TABLE answers ADD COLUMN parent_answer_id UUID REFERENCES answers (id) ON DELETE CASCADE
+    CHECK (parent_answer_id <> id);
+
+CREATE INDEX answers_parent_answer_id_idx ON answers (parent_answer_id) WHERE parent_answer_id IS NOT NULL;
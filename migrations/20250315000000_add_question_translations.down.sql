@@ -0,0 +1,2 @@
+-- Drops the translations of questions.
+DROP TABLE IF EXISTS question_translations;
@@ -0,0 +1,10 @@
+-- Stores translations of the title and content of questions, one per question and BCP 47 language tag. Translations
+-- are removed along with their question.
+CREATE TABLE IF NOT EXISTS question_translations (
+    question_id UUID NOT NULL REFERENCES questions (id) ON DELETE CASCADE,
+    lang TEXT NOT NULL,
+    title TEXT NOT NULL,
+    question TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (question_id, lang)
+);
@@ -0,0 +1,6 @@
+-- Drops the archive of answers.
+DROP TABLE IF EXISTS answers_archive;
+
+ALTER TABLE questions DROP COLUMN IF EXISTS archived_answer_count;
+
+ALTER TABLE questions DROP COLUMN IF EXISTS last_activity_at;
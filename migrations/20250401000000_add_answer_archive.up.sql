@@ -0,0 +1,31 @@
+-- Records when a question was last answered or liked, so that the answers of long inactive questions can be archived.
+-- Questions without any activity since they were asked have no time recorded, and count as active when created.
+ALTER TABLE questions ADD COLUMN last_activity_at TIMESTAMPTZ NULL;
+
+-- Existing questions were last active when their latest answer was posted.
+UPDATE questions SET last_activity_at = (SELECT max(created_at) FROM answers WHERE answers.question_id = questions.id);
+
+-- The number of answers moved to answers_archive, so readers can tell that a question had more answers.
+ALTER TABLE questions ADD COLUMN archived_answer_count BIGINT NOT NULL DEFAULT 0;
+
+-- Keeps the answers archived from inactive questions, which are deleted from answers. Archived answers are removed
+-- along with their question.
+CREATE TABLE IF NOT EXISTS answers_archive (
+    answer_id UUID PRIMARY KEY,
+    serial BIGINT NOT NULL,
+    question_id UUID NOT NULL REFERENCES questions (id) ON DELETE CASCADE,
+    parent_answer_id UUID NULL,
+    author_id UUID NULL,
+    author_token TEXT NULL,
+    answer TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    likes BIGINT NOT NULL,
+    helpful_count BIGINT NOT NULL,
+    unhelpful_count BIGINT NOT NULL,
+    published BOOLEAN NOT NULL,
+    approved_at TIMESTAMPTZ NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    archived_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS answers_archive_question_id_idx ON answers_archive (question_id);
@@ -0,0 +1,2 @@
+-- Drops the likes of each user.
+DROP TABLE IF EXISTS likes;
@@ -0,0 +1,12 @@
+-- Stores whether each user likes each question and answer, so that a user likes an entity at most once and a
+-- replayed like or unlike changes nothing.
+CREATE TABLE IF NOT EXISTS likes (
+    entity_type TEXT NOT NULL CHECK (entity_type IN ('question', 'answer')),
+    -- Not a foreign key, as it refers to either a question or an answer
+    entity_id UUID NOT NULL,
+    user_token TEXT NOT NULL,
+    liked BOOLEAN NOT NULL,
+    -- When the user last changed the like, by the client's clock, so that older changes are not applied over it
+    client_ts TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (entity_type, entity_id, user_token)
+);
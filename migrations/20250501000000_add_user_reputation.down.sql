@@ -0,0 +1,4 @@
+-- Drops the reputation of users.
+DROP INDEX IF EXISTS users_reputation_idx;
+
+ALTER TABLE users DROP COLUMN IF EXISTS reputation;
@@ -0,0 +1,5 @@
+-- Stores the reputation users gain as their questions and answers are liked and their answers accepted. It is kept
+-- up to date in the transaction of each event, so the leaderboard is read without aggregating every like.
+ALTER TABLE users ADD COLUMN reputation INT NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS users_reputation_idx ON users (reputation DESC, username);
@@ -0,0 +1,8 @@
+-- Drops the tombstones of answers, leaving their placeholder content.
+ALTER TABLE answers_archive DROP COLUMN IF EXISTS deleted_reason;
+
+ALTER TABLE answers_archive DROP COLUMN IF EXISTS deleted_at;
+
+ALTER TABLE answers DROP COLUMN IF EXISTS deleted_reason;
+
+ALTER TABLE answers DROP COLUMN IF EXISTS deleted_at;
@@ -0,0 +1,9 @@
+-- Records when a moderator replaced an answer with a tombstone, and why. Tombstoned answers keep their row, so the
+-- replies to them stay in their thread.
+ALTER TABLE answers ADD COLUMN deleted_at TIMESTAMPTZ NULL;
+
+ALTER TABLE answers ADD COLUMN deleted_reason TEXT NULL;
+
+ALTER TABLE answers_archive ADD COLUMN deleted_at TIMESTAMPTZ NULL;
+
+ALTER TABLE answers_archive ADD COLUMN deleted_reason TEXT NULL;
@@ -0,0 +1,6 @@
+-- Drops the archived votes, flags and acceptances of archived answers.
+ALTER TABLE questions DROP COLUMN IF EXISTS archived_pinned_answer_id;
+
+DROP TABLE IF EXISTS moderation_flags_archive;
+
+DROP TABLE IF EXISTS answer_votes_archive;
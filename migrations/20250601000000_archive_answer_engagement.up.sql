@@ -0,0 +1,22 @@
+-- Keeps the votes and moderation flags of archived answers, which were otherwise deleted along with the answers.
+-- They are removed along with their archived answer.
+CREATE TABLE IF NOT EXISTS answer_votes_archive (
+    answer_id UUID NOT NULL REFERENCES answers_archive (answer_id) ON DELETE CASCADE,
+    user_token TEXT NOT NULL,
+    vote TEXT NOT NULL CHECK (vote IN ('helpful', 'unhelpful')),
+    voted_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (answer_id, user_token)
+);
+
+CREATE TABLE IF NOT EXISTS moderation_flags_archive (
+    id UUID PRIMARY KEY,
+    answer_id UUID NOT NULL REFERENCES answers_archive (answer_id) ON DELETE CASCADE,
+    reason TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS moderation_flags_archive_answer_id_idx ON moderation_flags_archive (answer_id);
+
+-- The accepted answer of a question once it has been archived, as archiving it clears pinned_answer_id. At most one
+-- of the two is set, pinning or unpinning an answer clears it.
+ALTER TABLE questions ADD COLUMN archived_pinned_answer_id UUID NULL REFERENCES answers_archive (answer_id) ON DELETE SET NULL;
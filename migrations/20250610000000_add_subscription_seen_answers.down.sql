@@ -0,0 +1,2 @@
+-- Drops the answers seen by subscribers, leaving last_seen_at as the only record of what was reported.
+DROP TABLE IF EXISTS subscription_seen_answers;
@@ -0,0 +1,18 @@
+-- Records the answers reported to each subscriber. Answers can become visible after later answers were reported,
+-- once a moderator approves them or their transaction commits late, so no watermark on their creation time tells
+-- which were seen. The answers seen are removed along with the answer or the subscription.
+CREATE TABLE IF NOT EXISTS subscription_seen_answers (
+    user_token TEXT NOT NULL,
+    question_id UUID NOT NULL,
+    answer_id UUID NOT NULL REFERENCES answers (id) ON DELETE CASCADE,
+    PRIMARY KEY (user_token, answer_id),
+    FOREIGN KEY (user_token, question_id) REFERENCES subscriptions (user_token, question_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS subscription_seen_answers_subscription_idx ON subscription_seen_answers (user_token, question_id);
+
+-- The answers created before the last fetch were all reported, or predate the subscription.
+INSERT INTO subscription_seen_answers (user_token, question_id, answer_id)
+SELECT subscriptions.user_token, subscriptions.question_id, answers.id
+FROM subscriptions JOIN answers ON answers.question_id = subscriptions.question_id
+WHERE answers.created_at <= subscriptions.last_seen_at;
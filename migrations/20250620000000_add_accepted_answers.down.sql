@@ -0,0 +1,2 @@
+-- Drops the accepted answers of questions.
+ALTER TABLE questions DROP COLUMN IF EXISTS accepted_answer_id;
@@ -0,0 +1,3 @@
+-- The answer the author of a question accepted, which earns its author reputation. It is distinct from the pinned
+-- answer, which only changes the order answers are listed in. Deleting the accepted answer clears it.
+ALTER TABLE questions ADD COLUMN accepted_answer_id UUID NULL REFERENCES answers (id) ON DELETE SET NULL;
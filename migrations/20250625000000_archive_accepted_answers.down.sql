@@ -0,0 +1,4 @@
+-- Restores the archived pinned answers of questions in place of their archived accepted answers.
+ALTER TABLE questions ADD COLUMN archived_pinned_answer_id UUID NULL REFERENCES answers_archive (answer_id) ON DELETE SET NULL;
+
+ALTER TABLE questions DROP COLUMN IF EXISTS archived_accepted_answer_id;
@@ -0,0 +1,6 @@
+-- Keeps the accepted answer of a question once it has been archived, as archiving it clears accepted_answer_id. At
+-- most one of the two is set, accepting or unaccepting an answer clears it. The archived pinned answer it replaces was
+-- only kept as a stand-in for acceptance, a pinned answer is unpinned when archived.
+ALTER TABLE questions ADD COLUMN archived_accepted_answer_id UUID NULL REFERENCES answers_archive (answer_id) ON DELETE SET NULL;
+
+ALTER TABLE questions DROP COLUMN IF EXISTS archived_pinned_answer_id;
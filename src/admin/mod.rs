@@ -0,0 +1,318 @@
+//! Contains `QaAdmin`, a facade over the data access objects for operational tooling such as a CLI.
+//!
+//! Every operation returns a report that can be displayed to a person as is.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use chrono::Duration;
+use sqlx::PgPool;
+use sqlx::types::Uuid;
+use crate::clock::{Clock, SystemClock};
+use crate::models::dto::{EngagementRecord, QuestionDetailResponse};
+use crate::models::{
+    Answer, DbError, EntityId, ImportRecord, ImportVerdict, LikableEntity, MergeReport, PurgeReport, RecountReport, Totals,
+};
+use crate::persistence::migrations::{self, MigrationError};
+use crate::persistence::schema::SchemaName;
+use crate::persistence::{AdminDao, AdminDaoImpl, AnswerDao, AnswerDaoImpl, QuestionDao, QuestionDaoImpl, StatsDao, StatsDaoImpl};
+
+#[cfg(test)]
+mod test;
+
+/// The errors returned by `QaAdmin` operations.
+#[derive(Debug)]
+pub enum AdminError {
+    /// A data access object failed
+    Db(DbError),
+    /// The migrations could not be applied
+    Migration(MigrationError),
+    /// The database could not be connected to
+    Connect(sqlx::Error),
+    /// A file could not be read or written
+    Io(std::io::Error),
+    /// An export could not be serialized
+    Serialize(serde_json::Error),
+    /// An import could not be parsed
+    Parse(serde_json::Error),
+}
+
+impl Display for AdminError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdminError::Db(e) => write!(f, "{e}"),
+            AdminError::Migration(e) => write!(f, "{e}"),
+            AdminError::Connect(e) => write!(f, "Error connecting to database: {e}"),
+            AdminError::Io(e) => write!(f, "Error accessing file: {e}"),
+            AdminError::Serialize(e) => write!(f, "Error serializing export: {e}"),
+            AdminError::Parse(e) => write!(f, "Error parsing import: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for AdminError {}
+
+/// The result of exporting every question along with its answers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportReport {
+    /// The file the export was written to
+    pub path: PathBuf,
+    /// The number of questions exported
+    pub questions: usize,
+    /// The number of answers exported
+    pub answers: usize,
+}
+
+impl Display for ExportReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Exported {} questions and {} answers to {}", self.questions, self.answers, self.path.display())
+    }
+}
+
+/// Whether an import restores the engagement counters of the exported content or starts them from zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EngagementMode {
+    /// Likes and views are restored from the engagement stream, if one is given
+    #[default]
+    Preserve,
+    /// Likes and views start from zero, such as when refreshing a staging database
+    Zero,
+}
+
+/// The result of importing exported content, and optionally its engagement.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// Whether this is the report of a dry run, in which nothing was written
+    pub dry_run: bool,
+    /// The number of questions imported, or that would be in a dry run
+    pub questions: u64,
+    /// The number of answers imported, or that would be in a dry run
+    pub answers: u64,
+    /// The number of questions and answers whose engagement was restored, always zero in a dry run
+    pub engagement_restored: u64,
+    /// The entities of the engagement stream that do not exist in the database, in the order they were given
+    pub missing: Vec<(LikableEntity, Uuid)>,
+    /// The verdict on each question and answer of the content, in the order they were given
+    pub records: Vec<ImportRecord>,
+}
+
+impl Display for ImportReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.dry_run {
+            let invalid = self.records.iter().filter(|record| matches!(record.verdict, ImportVerdict::Invalid(_))).count();
+            return write!(
+                f,
+                "Would import {} questions and {} answers, with {} invalid records",
+                self.questions, self.answers, invalid
+            );
+        }
+        write!(
+            f,
+            "Imported {} questions and {} answers, restored the engagement of {} and skipped {} missing",
+            self.questions, self.answers, self.engagement_restored, self.missing.len()
+        )
+    }
+}
+
+/// The result of rebuilding the indexes of the crate's tables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReindexReport {
+    /// The tables that were reindexed
+    pub tables: Vec<&'static str>,
+}
+
+impl Display for ReindexReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Reindexed {} tables: {}", self.tables.len(), self.tables.join(", "))
+    }
+}
+
+/// High level administrative operations, for use from a binary without knowledge of the individual DAOs.
+pub struct QaAdmin {
+    question_dao: QuestionDaoImpl,
+    answer_dao: AnswerDaoImpl,
+    stats_dao: StatsDaoImpl,
+    admin_dao: AdminDaoImpl,
+    clock: Arc<dyn Clock>,
+}
+
+impl QaAdmin {
+    /// Creates the facade over an existing pool, whose database is assumed to be migrated, using the system clock.
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            question_dao: QuestionDaoImpl::new(pool.clone()),
+            answer_dao: AnswerDaoImpl::new(pool.clone()),
+            stats_dao: StatsDaoImpl::new(pool.clone()),
+            admin_dao: AdminDaoImpl::new(pool),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Places the tables the facade reads and writes in `schema`, see `QuestionDaoImpl::with_schema`.
+    pub fn with_schema(mut self, schema: SchemaName) -> Self {
+        self.question_dao = self.question_dao.with_schema(schema.clone());
+        self.answer_dao = self.answer_dao.with_schema(schema.clone());
+        self.stats_dao = self.stats_dao.with_schema(schema.clone());
+        self.admin_dao = self.admin_dao.with_schema(schema);
+        self
+    }
+
+    /// Sets the `Clock` the age of deleted content is measured with.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Connects to the database at `url` and applies any pending migrations.
+    pub async fn connect(url: &str) -> Result<Self, AdminError> {
+        let pool = PgPool::connect(url).await.map_err(AdminError::Connect)?;
+        migrations::run(&pool).await.map_err(AdminError::Migration)?;
+        Ok(Self::new(pool))
+    }
+
+    /// Counts the rows in each table.
+    pub async fn stats(&self) -> Result<Totals, AdminError> {
+        self.stats_dao.get_totals().await.map_err(AdminError::Db)
+    }
+
+    /// Merges the duplicate question into the target question, see `AdminDao::merge_questions`.
+    pub async fn merge(&self, duplicate_id: EntityId, target_id: EntityId) -> Result<MergeReport, AdminError> {
+        self.admin_dao.merge_questions(duplicate_id, target_id).await.map_err(AdminError::Db)
+    }
+
+    /// Writes every question along with its answers to `path` as a JSON array, in the same
+    /// representation returned to clients.
+    ///
+    /// The export is deterministic, so that exports of an unchanged database are byte identical and a change
+    /// only alters the lines of the fields it touches. Questions, and the answers of each question, are ordered
+    /// by creation time and then id, fields are written in the order of the response types, timestamps are
+    /// written with a fixed precision and the file ends with a newline.
+    pub async fn export(&self, path: impl AsRef<Path>) -> Result<ExportReport, AdminError> {
+        let mut questions = self.question_dao.get_questions().await.map_err(AdminError::Db)?;
+        let mut answers = self.answer_dao.get_all_answers().await.map_err(AdminError::Db)?;
+        // Listings put pinned questions first, which would reorder the export whenever a question is pinned
+        questions.sort_by_key(|question| (question.created_at(), question.id()));
+        answers.sort_by_key(|answer| (answer.created_at(), answer.id()));
+        let question_count = questions.len();
+        let mut answer_count = 0;
+        let mut answers_by_question: HashMap<Uuid, Vec<Answer>> = HashMap::new();
+        // Orphaned answers have no question to be exported with
+        for answer in answers {
+            if let Some(question_id) = answer.question_id() {
+                answers_by_question.entry(question_id).or_default().push(answer);
+                answer_count += 1;
+            }
+        }
+        let details: Vec<QuestionDetailResponse> = questions.into_iter()
+            .map(|question| {
+                let answers = answers_by_question.remove(&question.id()).unwrap_or_default();
+                QuestionDetailResponse::new(question, answers)
+            })
+            .collect();
+        let mut json = serde_json::to_vec_pretty(&details).map_err(AdminError::Serialize)?;
+        json.push(b'\n');
+        fs::write(path.as_ref(), json).map_err(AdminError::Io)?;
+        Ok(ExportReport { path: path.as_ref().to_path_buf(), questions: question_count, answers: answer_count })
+    }
+
+    /// Writes the likes and views of every question and answer exported by `export` to `path`, one
+    /// `EngagementRecord` per line, so that they can be imported along with the content or left out.
+    ///
+    /// Like the content, the stream is deterministic: questions come first, followed by the answers, each ordered
+    /// by creation time and then id.
+    pub async fn export_engagement(&self, path: impl AsRef<Path>) -> Result<ExportReport, AdminError> {
+        let mut questions = self.question_dao.get_questions().await.map_err(AdminError::Db)?;
+        let mut answers = self.answer_dao.get_all_answers().await.map_err(AdminError::Db)?;
+        questions.sort_by_key(|question| (question.created_at(), question.id()));
+        // Orphaned answers are not part of the exported content
+        answers.retain(|answer| answer.question_id().is_some());
+        answers.sort_by_key(|answer| (answer.created_at(), answer.id()));
+        let mut ndjson = Vec::new();
+        let records = questions.iter().map(EngagementRecord::from).chain(answers.iter().map(EngagementRecord::from));
+        for record in records {
+            serde_json::to_writer(&mut ndjson, &record).map_err(AdminError::Serialize)?;
+            ndjson.push(b'\n');
+        }
+        fs::write(path.as_ref(), ndjson).map_err(AdminError::Io)?;
+        Ok(ExportReport { path: path.as_ref().to_path_buf(), questions: questions.len(), answers: answers.len() })
+    }
+
+    /// Imports content written by `export`, keeping its ids, see `AdminDao::import_content`. With
+    /// `EngagementMode::Preserve` the likes and views are then restored from the `engagement` stream written by
+    /// `export_engagement`, if one is given, while with `EngagementMode::Zero` they are left at zero.
+    ///
+    /// Engagement of entities that do not exist in the database, such as content removed from the export, is not
+    /// an error but listed in the report.
+    ///
+    /// A dry run reads and checks both files and reports the verdict on every record of the content, including
+    /// all invalid ones, without writing anything. The engagement is parsed but not restored.
+    pub async fn import_all(
+        &self,
+        content: impl AsRef<Path>,
+        engagement: Option<&Path>,
+        mode: EngagementMode,
+        dry_run: bool,
+    ) -> Result<ImportReport, AdminError> {
+        let content: Vec<QuestionDetailResponse> = serde_json::from_slice(&fs::read(content).map_err(AdminError::Io)?)
+            .map_err(AdminError::Parse)?;
+        let records = self.admin_dao.import_content(content, dry_run).await.map_err(AdminError::Db)?;
+        let inserted = |entity| {
+            records.iter().filter(|record| record.entity == entity && record.verdict == ImportVerdict::Insert).count() as u64
+        };
+        let mut report = ImportReport {
+            dry_run,
+            questions: inserted(LikableEntity::Question),
+            answers: inserted(LikableEntity::Answer),
+            records,
+            ..ImportReport::default()
+        };
+        let Some(engagement) = engagement.filter(|_| mode == EngagementMode::Preserve) else {
+            return Ok(report);
+        };
+        let (mut question_likes, mut question_views, mut answer_likes) = (Vec::new(), Vec::new(), Vec::new());
+        for line in fs::read_to_string(engagement).map_err(AdminError::Io)?.lines().filter(|line| !line.trim().is_empty()) {
+            let record: EngagementRecord = serde_json::from_str(line).map_err(AdminError::Parse)?;
+            match record.entity {
+                LikableEntity::Question => {
+                    question_views.push((EntityId::new(record.id.clone()), record.views.unwrap_or_default()));
+                    question_likes.push((EntityId::new(record.id), record.likes));
+                }
+                LikableEntity::Answer => answer_likes.push((EntityId::new(record.id), record.likes)),
+            }
+        }
+        if dry_run {
+            return Ok(report);
+        }
+        // The views of a question are missing exactly when its likes are, so only the likes are reported
+        self.admin_dao.bulk_set_question_views(question_views).await.map_err(AdminError::Db)?;
+        let restored = self.admin_dao.bulk_set_question_likes(question_likes).await.map_err(AdminError::Db)?;
+        report.engagement_restored += restored.updated;
+        report.missing.extend(restored.missing.into_iter().map(|id| (LikableEntity::Question, id)));
+        let restored = self.admin_dao.bulk_set_answer_likes(answer_likes).await.map_err(AdminError::Db)?;
+        report.engagement_restored += restored.updated;
+        report.missing.extend(restored.missing.into_iter().map(|id| (LikableEntity::Answer, id)));
+        Ok(report)
+    }
+
+    /// Rebuilds the indexes of every table.
+    pub async fn reindex(&self) -> Result<ReindexReport, AdminError> {
+        let tables = self.admin_dao.reindex().await.map_err(AdminError::Db)?;
+        Ok(ReindexReport { tables })
+    }
+
+    /// Deletes for good the answers tombstoned longer than `older_than` ago, keeping those replies still refer to,
+    /// see `AdminDao::purge_deleted_answers`. A negative age is rejected with `DbError::Validation`.
+    pub async fn purge_deleted(&self, older_than: Duration) -> Result<PurgeReport, AdminError> {
+        if older_than < Duration::zero() {
+            return Err(AdminError::Db(DbError::validation(String::from("the age of purged answers cannot be negative"))));
+        }
+        self.admin_dao.purge_deleted_answers(self.clock.now() - older_than).await.map_err(AdminError::Db)
+    }
+
+    /// Corrects the likes of every question and answer from the like audit log, see `AdminDao::recount_likes`.
+    pub async fn recount_likes(&self) -> Result<RecountReport, AdminError> {
+        self.admin_dao.recount_likes().await.map_err(AdminError::Db)
+    }
+}
@@ -0,0 +1,387 @@
+use std::sync::Arc;
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use sqlx::types::Uuid;
+use crate::admin::QaAdmin;
+use crate::clock::{Clock, FixedClock};
+use crate::fixtures;
+use crate::models::{DbError, EntityId, ImportVerdict, LikableEntity, Totals};
+use crate::models::dto::QuestionDetailResponse;
+use crate::persistence::{AnswerDao, AnswerDaoImpl, QuestionDao, QuestionDaoImpl, SubscriptionDao, SubscriptionDaoImpl};
+use super::{AdminError, EngagementMode, ImportReport};
+
+/// Seeds two questions, with two and one answers, and a subscription to each.
+async fn seed(pool: &PgPool) -> (Uuid, Uuid) {
+    let question_dao = QuestionDaoImpl::new(pool.clone());
+    let answer_dao = AnswerDaoImpl::new(pool.clone());
+    let subscription_dao = SubscriptionDaoImpl::new(pool.clone());
+    let (first, _) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 2).await;
+    let (second, _) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 1).await;
+    for question_id in [first, second] {
+        subscription_dao.subscribe("ops", EntityId::uuid(question_id)).await.expect("subscription should be created successfully");
+        question_dao.increment_question_likes(EntityId::uuid(question_id)).await.expect("question should be liked successfully");
+    }
+    (first, second)
+}
+
+#[sqlx::test]
+async fn stats_should_count_every_table(pool: PgPool) {
+    seed(&pool).await;
+    let admin = QaAdmin::new(pool);
+    let res = admin.stats().await;
+    println!("{:?}", res);
+    let totals = res.unwrap();
+    assert_eq!(totals, Totals { questions: 2, answers: 3, users: 0, subscriptions: 2 });
+    assert!(totals.to_string().contains("answers:       3"));
+}
+
+#[sqlx::test]
+async fn merge_should_move_answers_subscriptions_and_likes(pool: PgPool) {
+    let (duplicate, target) = seed(&pool).await;
+    let subscription_dao = SubscriptionDaoImpl::new(pool.clone());
+    subscription_dao.subscribe("other", EntityId::uuid(duplicate)).await.expect("subscription should be created successfully");
+    let admin = QaAdmin::new(pool.clone());
+    let res = admin.merge(EntityId::uuid(duplicate), EntityId::uuid(target)).await;
+    println!("{:?}", res);
+    let report = res.unwrap();
+    assert_eq!((report.answers_moved, report.subscriptions_moved, report.likes_added), (2, 1, 1));
+    assert!(report.to_string().contains("moved 2 answers and 1 subscriptions"));
+    let question = QuestionDaoImpl::new(pool.clone()).get_question(EntityId::uuid(target)).await.unwrap();
+    assert_eq!(question.likes(), 2);
+    let answers = AnswerDaoImpl::new(pool.clone()).get_answers(EntityId::uuid(target)).await.unwrap();
+    assert_eq!(answers.len(), 3);
+    assert_eq!(admin.stats().await.unwrap(), Totals { questions: 1, answers: 3, users: 0, subscriptions: 2 });
+    // The answers seen on the duplicate stay seen, only the answer to the target is new to its subscriber
+    let updates = subscription_dao.get_updates("other").await.unwrap();
+    assert_eq!(updates.iter().map(|update| update.new_answers()).collect::<Vec<_>>(), [1]);
+    let res = admin.merge(EntityId::uuid(target), EntityId::uuid(target)).await;
+    let Err(AdminError::Db(DbError::Validation { .. })) = res else { panic!("Error should be `Validation` variant") };
+    let res = admin.merge(EntityId::uuid(duplicate), EntityId::uuid(target)).await;
+    let Err(AdminError::Db(DbError::NotFound { .. })) = res else { panic!("Error should be `NotFound` variant") };
+}
+
+#[sqlx::test]
+async fn export_should_write_every_question_with_its_answers(pool: PgPool) {
+    let (first, second) = seed(&pool).await;
+    let admin = QaAdmin::new(pool);
+    let path = std::env::temp_dir().join(format!("qa_export_{}.json", Uuid::new_v4()));
+    let res = admin.export(&path).await;
+    println!("{:?}", res);
+    let report = res.unwrap();
+    assert_eq!((report.questions, report.answers), (2, 3));
+    let exported: Vec<QuestionDetailResponse> = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let answer_count = |id: Uuid| exported.iter().find(|q| q.question.id == id.to_string()).map(|q| q.answers.len());
+    assert_eq!(answer_count(first), Some(2));
+    assert_eq!(answer_count(second), Some(1));
+}
+
+#[sqlx::test]
+async fn export_should_be_byte_identical_for_unchanged_data(pool: PgPool) {
+    let (first, second) = seed(&pool).await;
+    // Pinning the later question would move it first in listings, but not in the export
+    QuestionDaoImpl::new(pool.clone()).pin_question(EntityId::uuid(second)).await.expect("question should be pinned successfully");
+    // The time remaining on a bounty changes between exports, so only the bounty as stored is exported
+    QuestionDaoImpl::new(pool.clone()).set_bounty(EntityId::uuid(first), 50, Duration::days(1)).await.expect("bounty should be set successfully");
+    let admin = QaAdmin::new(pool.clone());
+    let export = |name: &str| {
+        let path = std::env::temp_dir().join(format!("qa_export_{name}_{}.json", Uuid::new_v4()));
+        let admin = &admin;
+        async move {
+            admin.export(&path).await.expect("export should succeed");
+            let contents = std::fs::read_to_string(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            contents
+        }
+    };
+    let before = export("before").await;
+    assert_eq!(before, export("again").await);
+    assert!(before.contains("\"bounty\"") && !before.contains("remaining_seconds"), "{before}");
+    let exported: Vec<QuestionDetailResponse> = serde_json::from_str(&before).unwrap();
+    let ids: Vec<String> = exported.iter().map(|q| q.question.id.clone()).collect();
+    assert_eq!(ids, [first.to_string(), second.to_string()]);
+    // A changed like count should only change the line of that count
+    QuestionDaoImpl::new(pool).increment_question_likes(EntityId::uuid(first)).await.expect("question should be liked successfully");
+    let after = export("after").await;
+    assert_eq!(before.lines().count(), after.lines().count());
+    let changed: Vec<(&str, &str)> = before.lines().zip(after.lines()).filter(|(before, after)| before != after).collect();
+    assert_eq!(changed.len(), 1);
+    assert_eq!((changed[0].0.trim(), changed[0].1.trim()), ("\"likes\": 1,", "\"likes\": 2,"));
+}
+
+/// Exports the content and the engagement of the database to temporary files, then empties it.
+async fn export_and_truncate(pool: &PgPool) -> (std::path::PathBuf, std::path::PathBuf) {
+    let admin = QaAdmin::new(pool.clone());
+    let content = std::env::temp_dir().join(format!("qa_content_{}.json", Uuid::new_v4()));
+    let engagement = std::env::temp_dir().join(format!("qa_engagement_{}.ndjson", Uuid::new_v4()));
+    admin.export(&content).await.expect("content should be exported successfully");
+    let res = admin.export_engagement(&engagement).await;
+    println!("{:?}", res);
+    assert_eq!(res.unwrap().questions, 2);
+    sqlx::query("TRUNCATE questions CASCADE").execute(pool).await.unwrap();
+    (content, engagement)
+}
+
+/// The likes and views of every question and the likes of every answer, ordered by id.
+async fn engagement(pool: &PgPool) -> Vec<(Uuid, i64, Option<i32>)> {
+    sqlx::query_as(
+        "SELECT id, likes, views FROM questions UNION ALL SELECT id, likes, NULL FROM answers ORDER BY id"
+    )
+        .fetch_all(pool)
+        .await
+        .unwrap()
+}
+
+#[sqlx::test]
+async fn import_all_should_preserve_engagement(pool: PgPool) {
+    let (first, _) = seed(&pool).await;
+    let question_dao = QuestionDaoImpl::new(pool.clone());
+    let answer_dao = AnswerDaoImpl::new(pool.clone());
+    question_dao.record_view(EntityId::uuid(first), "viewer").await.expect("view should be recorded successfully");
+    let answer_id = answer_dao.get_answers(EntityId::uuid(first)).await.unwrap()[0].id();
+    for _ in 0..2 {
+        answer_dao.increment_answer_likes(EntityId::uuid(answer_id)).await.expect("answer should be liked successfully");
+    }
+    let other_answer_id = answer_dao.get_answers(EntityId::uuid(first)).await.unwrap()[1].id();
+    question_dao.pin_answer(EntityId::uuid(first), EntityId::uuid(answer_id)).await.expect("answer should be pinned successfully");
+    question_dao.accept_answer(EntityId::uuid(first), EntityId::uuid(other_answer_id)).await.expect("answer should be accepted successfully");
+    let before = engagement(&pool).await;
+    let (content, engagement_path) = export_and_truncate(&pool).await;
+    assert!(engagement(&pool).await.is_empty());
+    let res = QaAdmin::new(pool.clone()).import_all(&content, Some(&engagement_path), EngagementMode::Preserve, false).await;
+    println!("{:?}", res);
+    let report = res.unwrap();
+    assert!(report.records.iter().all(|record| record.verdict == ImportVerdict::Insert));
+    assert_eq!(report, ImportReport { questions: 2, answers: 3, engagement_restored: 5, records: report.records.clone(), ..ImportReport::default() });
+    assert_eq!(engagement(&pool).await, before);
+    // The pinned and accepted answers are restored separately
+    let question = question_dao.get_question(EntityId::uuid(first)).await.unwrap();
+    assert_eq!((question.pinned_answer_id(), question.accepted_answer_id()), (Some(answer_id), Some(other_answer_id)));
+    std::fs::remove_file(content).unwrap();
+    std::fs::remove_file(engagement_path).unwrap();
+}
+
+#[sqlx::test]
+async fn import_all_should_zero_engagement(pool: PgPool) {
+    let (first, _) = seed(&pool).await;
+    QuestionDaoImpl::new(pool.clone()).record_view(EntityId::uuid(first), "viewer").await.expect("view should be recorded successfully");
+    let (content, engagement_path) = export_and_truncate(&pool).await;
+    let res = QaAdmin::new(pool.clone()).import_all(&content, Some(&engagement_path), EngagementMode::Zero, false).await;
+    println!("{:?}", res);
+    let report = res.unwrap();
+    assert!(report.records.iter().all(|record| record.verdict == ImportVerdict::Insert));
+    assert_eq!(report, ImportReport { questions: 2, answers: 3, engagement_restored: 0, records: report.records.clone(), ..ImportReport::default() });
+    let imported = engagement(&pool).await;
+    assert_eq!(imported.len(), 5);
+    assert!(imported.iter().all(|(_, likes, views)| *likes == 0 && views.unwrap_or_default() == 0));
+    std::fs::remove_file(content).unwrap();
+    std::fs::remove_file(engagement_path).unwrap();
+}
+
+#[sqlx::test]
+async fn import_all_should_report_engagement_of_missing_content(pool: PgPool) {
+    let (first, second) = seed(&pool).await;
+    let second_answer = AnswerDaoImpl::new(pool.clone()).get_answers(EntityId::uuid(second)).await.unwrap()[0].id();
+    let (content, engagement_path) = export_and_truncate(&pool).await;
+    // Leave the second question, and so its answer, out of the content
+    let mut exported: Vec<QuestionDetailResponse> = serde_json::from_slice(&std::fs::read(&content).unwrap()).unwrap();
+    exported.retain(|detail| detail.question.id == first.to_string());
+    std::fs::write(&content, serde_json::to_vec(&exported).unwrap()).unwrap();
+    let res = QaAdmin::new(pool.clone()).import_all(&content, Some(&engagement_path), EngagementMode::Preserve, false).await;
+    println!("{:?}", res);
+    let report = res.unwrap();
+    assert_eq!((report.questions, report.answers, report.engagement_restored), (1, 2, 3));
+    assert_eq!(report.missing, [(LikableEntity::Question, second), (LikableEntity::Answer, second_answer)]);
+    assert_eq!(report.to_string(), "Imported 1 questions and 2 answers, restored the engagement of 3 and skipped 2 missing");
+    let question = QuestionDaoImpl::new(pool).get_question(EntityId::uuid(first)).await.unwrap();
+    assert_eq!(question.likes(), 1);
+    std::fs::remove_file(content).unwrap();
+    std::fs::remove_file(engagement_path).unwrap();
+}
+
+/// Exports the content of the database to a temporary file, returning the path and the exported content.
+async fn export_content(pool: &PgPool) -> (std::path::PathBuf, Vec<QuestionDetailResponse>) {
+    let path = std::env::temp_dir().join(format!("qa_content_{}.json", Uuid::new_v4()));
+    QaAdmin::new(pool.clone()).export(&path).await.expect("content should be exported successfully");
+    let exported = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    (path, exported)
+}
+
+#[sqlx::test]
+async fn import_all_dry_run_should_write_nothing_and_match_a_real_run(pool: PgPool) {
+    let (first, second) = seed(&pool).await;
+    let (content, mut exported) = export_content(&pool).await;
+    // The first question is kept so its records are skipped, while the second is removed to be imported again
+    sqlx::query("DELETE FROM questions WHERE id = $1").bind(second).execute(&pool).await.unwrap();
+    let mut extra = exported.iter().find(|detail| detail.question.id == first.to_string()).unwrap().clone();
+    extra.question.id = Uuid::new_v4().to_string();
+    extra.answers.clear();
+    exported.extend([extra.clone(), extra]);
+    std::fs::write(&content, serde_json::to_vec(&exported).unwrap()).unwrap();
+    let admin = QaAdmin::new(pool.clone());
+    let before = admin.stats().await.unwrap();
+
+    let res = admin.import_all(&content, None, EngagementMode::Preserve, true).await;
+    println!("{:?}", res);
+    let dry_run = res.unwrap();
+    assert_eq!(admin.stats().await.unwrap(), before);
+    let verdicts: Vec<_> = dry_run.records.iter().map(|record| (record.entity, record.verdict.clone())).collect();
+    assert_eq!(verdicts, [
+        (LikableEntity::Question, ImportVerdict::Skip),
+        (LikableEntity::Answer, ImportVerdict::Skip),
+        (LikableEntity::Answer, ImportVerdict::Skip),
+        (LikableEntity::Question, ImportVerdict::Insert),
+        (LikableEntity::Answer, ImportVerdict::Insert),
+        (LikableEntity::Question, ImportVerdict::Insert),
+        (LikableEntity::Question, ImportVerdict::Skip),
+    ]);
+    assert_eq!(dry_run.to_string(), "Would import 2 questions and 1 answers, with 0 invalid records");
+
+    let res = admin.import_all(&content, None, EngagementMode::Preserve, false).await;
+    println!("{:?}", res);
+    let real_run = res.unwrap();
+    assert_eq!(real_run, ImportReport { dry_run: false, ..dry_run });
+    assert_eq!(admin.stats().await.unwrap().questions, before.questions + 2);
+    std::fs::remove_file(content).unwrap();
+}
+
+#[sqlx::test]
+async fn import_all_dry_run_should_report_every_invalid_record(pool: PgPool) {
+    seed(&pool).await;
+    let (content, mut exported) = export_content(&pool).await;
+    sqlx::query("TRUNCATE questions CASCADE").execute(&pool).await.unwrap();
+    exported[0].question.id = String::from("not a uuid");
+    exported[1].answers[0].created_at = String::from("yesterday");
+    std::fs::write(&content, serde_json::to_vec(&exported).unwrap()).unwrap();
+    let admin = QaAdmin::new(pool.clone());
+
+    let res = admin.import_all(&content, None, EngagementMode::Preserve, true).await;
+    println!("{:?}", res);
+    let report = res.unwrap();
+    let invalid: Vec<_> = report.records.iter().filter(|record| matches!(record.verdict, ImportVerdict::Invalid(_))).collect();
+    // The answers of the invalid question are invalid along with it
+    assert_eq!(invalid.len(), 1 + exported[0].answers.len() + 1);
+    assert_eq!(invalid[0].id, "not a uuid");
+    assert_eq!(report.questions, 1);
+
+    // A real run stops at the first invalid record without importing anything
+    let res = admin.import_all(&content, None, EngagementMode::Preserve, false).await;
+    println!("{:?}", res);
+    let Err(AdminError::Db(DbError::InvalidId { .. })) = res else { panic!("Error should be `InvalidId` variant") };
+    assert_eq!(admin.stats().await.unwrap().questions, 0);
+    std::fs::remove_file(content).unwrap();
+}
+
+#[sqlx::test]
+async fn import_all_should_reject_truncated_answers(pool: PgPool) {
+    seed(&pool).await;
+    let (content, mut exported) = export_content(&pool).await;
+    sqlx::query("TRUNCATE questions CASCADE").execute(&pool).await.unwrap();
+    // A preview would be imported as the whole answer
+    exported[0].answers[0].truncated = true;
+    std::fs::write(&content, serde_json::to_vec(&exported).unwrap()).unwrap();
+    let admin = QaAdmin::new(pool.clone());
+
+    let res = admin.import_all(&content, None, EngagementMode::Preserve, true).await;
+    println!("{:?}", res);
+    let report = res.unwrap();
+    let invalid: Vec<_> = report.records.iter().filter(|record| matches!(record.verdict, ImportVerdict::Invalid(_))).collect();
+    assert_eq!(invalid.len(), 1);
+    assert_eq!(invalid[0].id, exported[0].answers[0].id);
+    std::fs::remove_file(content).unwrap();
+}
+
+#[sqlx::test]
+async fn reindex_should_report_every_table(pool: PgPool) {
+    seed(&pool).await;
+    let admin = QaAdmin::new(pool);
+    let res = admin.reindex().await;
+    println!("{:?}", res);
+    let report = res.unwrap();
+    assert!(report.tables.contains(&"questions") && report.tables.contains(&"answers"));
+    assert!(report.to_string().starts_with("Reindexed 22 tables"));
+}
+
+#[sqlx::test]
+async fn purge_deleted_should_keep_recent_tombstones_and_those_with_replies(pool: PgPool) {
+    let clock = Arc::new(FixedClock::new(Utc::now() - Duration::days(10)));
+    let question_dao = QuestionDaoImpl::new(pool.clone());
+    let answer_dao = AnswerDaoImpl::new(pool.clone()).with_clock(clock.clone());
+    let question_id = question_dao.create_question(fixtures::question().build()).await.unwrap().id();
+    let create = |parent: Option<Uuid>| {
+        let answer = fixtures::answer(question_id);
+        let answer = match parent {
+            Some(parent) => answer.parent_answer_id(parent),
+            None => answer,
+        };
+        answer_dao.create_answer(answer.build())
+    };
+    let tombstone = |answer_id: Uuid| answer_dao.delete_answer_with_tombstone(EntityId::uuid(answer_id), None);
+    let alone = create(None).await.unwrap().id();
+    let with_reply = create(None).await.unwrap().id();
+    let reply = create(Some(with_reply)).await.unwrap().id();
+    let with_deleted_reply = create(None).await.unwrap().id();
+    let deleted_reply = create(Some(with_deleted_reply)).await.unwrap().id();
+    let live = create(None).await.unwrap().id();
+    for answer_id in [alone, with_reply, with_deleted_reply, deleted_reply] {
+        tombstone(answer_id).await.expect("answer should be tombstoned successfully");
+    }
+    clock.advance(Duration::days(9));
+    let recent = create(None).await.unwrap().id();
+    tombstone(recent).await.expect("answer should be tombstoned successfully");
+    clock.advance(Duration::days(1));
+
+    let admin = QaAdmin::new(pool.clone()).with_clock(clock.clone());
+    let res = admin.purge_deleted(Duration::days(7)).await;
+    println!("{:?}", res);
+    let report = res.unwrap();
+    assert_eq!((report.before, report.answers, report.kept_with_replies), (clock.now() - Duration::days(7), 3, 1));
+    assert!(report.to_string().starts_with("Purged 3 answers deleted before"));
+    assert!(report.to_string().ends_with("kept 1 with replies"));
+    let mut remaining: Vec<Uuid> = answer_dao.get_all_answers().await.unwrap().iter().map(|answer| answer.id()).collect();
+    let mut expected = vec![with_reply, reply, live, recent];
+    remaining.sort();
+    expected.sort();
+    assert_eq!(remaining, expected);
+    // Nothing else is old enough to be purged
+    let report = admin.purge_deleted(Duration::days(7)).await.unwrap();
+    assert_eq!((report.answers, report.kept_with_replies), (0, 1));
+    let res = admin.purge_deleted(Duration::days(-1)).await;
+    let Err(AdminError::Db(DbError::Validation { .. })) = res else { panic!("Error should be `Validation` variant") };
+}
+
+#[sqlx::test]
+async fn recount_likes_should_correct_counters_from_the_like_events(pool: PgPool) {
+    let (first, second) = seed(&pool).await;
+    let question_dao = QuestionDaoImpl::new(pool.clone());
+    let answer_dao = AnswerDaoImpl::new(pool.clone());
+    let legacy = question_dao.create_question(fixtures::question().build()).await.unwrap().id();
+    let answer_id = answer_dao.get_answers(EntityId::uuid(first)).await.unwrap()[0].id();
+    for _ in 0..2 {
+        answer_dao.increment_answer_likes(EntityId::uuid(answer_id)).await.expect("answer should be liked successfully");
+    }
+    // The counters drift from the log, while the likes of the legacy question predate it
+    for (table, id, likes) in [("questions", first, 7), ("questions", legacy, 3), ("answers", answer_id, 0)] {
+        sqlx::query(&format!("UPDATE {table} SET likes = $2 WHERE id = $1")).bind(id).bind(likes as i64).execute(&pool).await.unwrap();
+    }
+
+    let admin = QaAdmin::new(pool.clone());
+    let res = admin.recount_likes().await;
+    println!("{:?}", res);
+    let report = res.unwrap();
+    assert_eq!((report.questions, report.answers), (1, 1));
+    assert_eq!(report.to_string(), "Recounted likes, corrected 1 questions and 1 answers");
+    for (question_id, likes) in [(first, 1), (second, 1), (legacy, 3)] {
+        assert_eq!(question_dao.get_question(EntityId::uuid(question_id)).await.unwrap().likes(), likes);
+    }
+    let answer = answer_dao.get_answer(EntityId::uuid(answer_id)).await.unwrap();
+    assert_eq!(answer.likes(), 2);
+    let report = admin.recount_likes().await.unwrap();
+    assert_eq!((report.questions, report.answers), (0, 0));
+}
+
+#[tokio::test]
+async fn connect_should_fail_for_invalid_urls() {
+    let res = QaAdmin::connect("not a database url").await;
+    let Err(AdminError::Connect(_)) = res else { panic!("Error should be `Connect` variant") };
+}
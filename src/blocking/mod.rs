@@ -0,0 +1,224 @@
+//! Contains `QuestionClient` and `AnswerClient`, synchronous wrappers over the data access objects for programs
+//! without an async runtime of their own, available with the `blocking` feature.
+//!
+//! Each client owns a small single threaded runtime, created when the client connects and shut down when it is
+//! dropped, and blocks the calling thread on it for every operation. The clients must not be used from within an
+//! async runtime, where they panic instead of stalling it; async code should use the data access objects directly.
+//!
+//! # Example
+//! ```no_run
+//! use question_answer::blocking::QuestionClient;
+//! use question_answer::persistence::pool::PoolConfig;
+//!
+//! let client = QuestionClient::connect("postgres://localhost/qa", PoolConfig::default()).unwrap();
+//! for question in client.get_questions().unwrap() {
+//!     println!("{}", question.title());
+//! }
+//! ```
+
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+use sqlx::postgres::PgConnectOptions;
+use sqlx::types::Uuid;
+use tokio::runtime::{Builder, Handle, Runtime};
+use crate::models::diff::DiffHunk;
+use crate::models::period::Period;
+use crate::models::*;
+use crate::persistence::pool::{self, PoolConfig};
+use crate::persistence::{AnswerDao, AnswerDaoImpl, QuestionDao, QuestionDaoImpl};
+
+#[cfg(test)]
+mod test;
+
+/// The runtime owned by a blocking client, which refuses to block from within another runtime.
+#[derive(Debug)]
+struct BlockingRuntime {
+    runtime: Option<Runtime>,
+    client: &'static str,
+}
+
+impl BlockingRuntime {
+    /// Creates the runtime of the client named `client`.
+    fn new(client: &'static str) -> Result<Self, DbError> {
+        Self::ensure_not_async(client);
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| DbError::connection(sqlx::Error::Io(e)))?;
+        Ok(Self { runtime: Some(runtime), client })
+    }
+
+    /// Runs `future` to completion on the runtime, blocking the calling thread.
+    ///
+    /// # Panics
+    /// If called from within an async runtime.
+    fn block_on<F: std::future::Future>(&self, future: F) -> F::Output {
+        Self::ensure_not_async(self.client);
+        self.runtime.as_ref().expect("the runtime is only taken on drop").block_on(future)
+    }
+
+    /// Panics with an explanation if the calling thread is running an async runtime, since blocking it on another
+    /// runtime would stall every task it drives.
+    fn ensure_not_async(client: &str) {
+        if Handle::try_current().is_ok() {
+            panic!(
+                "`{client}` cannot be used from within an async runtime, since blocking would stall the runtime; \
+                use the data access objects of `question_answer::persistence` and `.await` them instead"
+            );
+        }
+    }
+}
+
+impl Drop for BlockingRuntime {
+    fn drop(&mut self) {
+        // Shutting down in the background rather than waiting for tasks is also allowed within an async runtime
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+/// Generates synchronous wrappers for methods of the data access object held by a client.
+macro_rules! blocking_methods {
+    ($trait:literal, $($name:ident($($arg:ident: $ty:ty),*) -> $ret:ty;)*) => {
+        $(
+            #[doc = concat!("Synchronous version of `", $trait, "::", stringify!($name), "`.")]
+            ///
+            /// # Panics
+            /// If called from within an async runtime.
+            pub fn $name(&self, $($arg: $ty),*) -> Result<$ret, DbError> {
+                self.runtime.block_on(self.dao.$name($($arg),*))
+            }
+        )*
+    };
+}
+
+/// A synchronous client for questions, wrapping a `QuestionDaoImpl`.
+pub struct QuestionClient {
+    // Declared before the runtime, so the pool is dropped while its runtime is still running
+    dao: QuestionDaoImpl,
+    runtime: BlockingRuntime,
+}
+
+impl QuestionClient {
+    /// Creates a client for the database at `url`, see `persistence::pool::connect`.
+    ///
+    /// # Panics
+    /// If called from within an async runtime.
+    pub fn connect(url: &str, config: PoolConfig) -> Result<Self, DbError> {
+        let runtime = BlockingRuntime::new("QuestionClient")?;
+        let pool = runtime.block_on(pool::connect(url, config))?;
+        Ok(Self { dao: QuestionDaoImpl::new(pool), runtime })
+    }
+
+    /// Creates a client for the database described by `options`, see `persistence::pool::connect_with`.
+    ///
+    /// # Panics
+    /// If called from within an async runtime.
+    pub fn connect_with(options: PgConnectOptions, config: PoolConfig) -> Result<Self, DbError> {
+        let runtime = BlockingRuntime::new("QuestionClient")?;
+        let pool = runtime.block_on(pool::connect_with(options, config))?;
+        Ok(Self { dao: QuestionDaoImpl::new(pool), runtime })
+    }
+
+    blocking_methods! {
+        "QuestionDao",
+        create_question(new_question: NewQuestion) -> Question;
+        get_question(question_id: EntityId) -> Question;
+        get_question_as_of(question_id: EntityId, at: DateTime<Utc>) -> Question;
+        get_revision_diff(question_id: EntityId, from_rev: u32, to_rev: u32) -> Vec<DiffHunk>;
+        get_questions() -> Vec<Question>;
+        get_questions_projected(fields: QuestionFields, page: PageRequest) -> Page<QuestionPartial>;
+        get_questions_shorter_than(max_words: i32) -> Vec<Question>;
+        get_questions_in_period(period: Period, tz: Tz) -> Vec<Question>;
+        get_questions_by_like_range(min: i32, max: Option<i32>, created_after: Option<DateTime<Utc>>) -> Vec<Question>;
+        get_questions_exceeding_like_rate(likes_per_hour: f64, window: Duration) -> Vec<QuestionLikeRate>;
+        get_question_header(question_id: EntityId) -> QuestionHeader;
+        get_question_detail_consistent(question_id: EntityId) -> QuestionDetail;
+        get_question_detail_for_author(question_id: EntityId, author_token: &str) -> QuestionDetail;
+        get_question_detail_with(question_id: EntityId, options: DetailOptions) -> QuestionDetail;
+        get_questions_by_token(author_token: &str) -> Vec<Question>;
+        search_questions(query: &str, ranking: SearchRankingConfig, limit: u32) -> Vec<RankedQuestion>;
+        get_activity_feed(limit: u32, before: Option<ActivityCursor>) -> Vec<ActivityItem>;
+        update_question(question_id: EntityId, update: UpdateQuestion) -> Question;
+        lock_question(question_id: EntityId) -> Question;
+        unlock_question(question_id: EntityId) -> Question;
+        pin_question(question_id: EntityId) -> Question;
+        unpin_question(question_id: EntityId) -> Question;
+        pin_answer(question_id: EntityId, answer_id: EntityId) -> Question;
+        unpin_answer(question_id: EntityId) -> Question;
+        accept_answer(question_id: EntityId, answer_id: EntityId) -> Question;
+        unaccept_answer(question_id: EntityId) -> Question;
+        set_bounty(question_id: EntityId, amount: i32, duration: Duration) -> Question;
+        clear_bounty(question_id: EntityId) -> Question;
+        upsert_translation(question_id: EntityId, lang: &str, content: NewTranslation) -> QuestionTranslation;
+        get_translation(question_id: EntityId, lang: &str) -> Question;
+        link_questions(from_id: EntityId, to_id: EntityId, kind: LinkKind) -> ();
+        unlink_questions(from_id: EntityId, to_id: EntityId, kind: LinkKind) -> bool;
+        get_linked_questions(question_id: EntityId) -> Vec<(LinkKind, Question)>;
+        delete_question(question_id: EntityId, force: bool) -> Uuid;
+        delete_question_with_policy(question_id: EntityId, policy: DeletePolicy) -> Uuid;
+        increment_question_likes(question_id: EntityId) -> i64;
+        record_view(question_id: EntityId, client_token: &str) -> ViewOutcome;
+        create_question_idempotent(new_question: NewQuestion, key: &str) -> CreateOutcome;
+        upsert_question_by_external_id(new_question: NewQuestion) -> UpsertOutcome;
+        purge_idempotency_keys(ttl: Duration) -> u64;
+        expire_bounties() -> Vec<Uuid>;
+        delete_questions_batched(ids: Vec<EntityId>, batch_size: usize, force: bool, progress: Option<&mut dyn FnMut(BatchProgress)>) -> u64;
+    }
+}
+
+/// A synchronous client for answers, wrapping an `AnswerDaoImpl`.
+pub struct AnswerClient {
+    // Declared before the runtime, so the pool is dropped while its runtime is still running
+    dao: AnswerDaoImpl,
+    runtime: BlockingRuntime,
+}
+
+impl AnswerClient {
+    /// Creates a client for the database at `url`, see `persistence::pool::connect`.
+    ///
+    /// # Panics
+    /// If called from within an async runtime.
+    pub fn connect(url: &str, config: PoolConfig) -> Result<Self, DbError> {
+        let runtime = BlockingRuntime::new("AnswerClient")?;
+        let pool = runtime.block_on(pool::connect(url, config))?;
+        Ok(Self { dao: AnswerDaoImpl::new(pool), runtime })
+    }
+
+    /// Creates a client for the database described by `options`, see `persistence::pool::connect_with`.
+    ///
+    /// # Panics
+    /// If called from within an async runtime.
+    pub fn connect_with(options: PgConnectOptions, config: PoolConfig) -> Result<Self, DbError> {
+        let runtime = BlockingRuntime::new("AnswerClient")?;
+        let pool = runtime.block_on(pool::connect_with(options, config))?;
+        Ok(Self { dao: AnswerDaoImpl::new(pool), runtime })
+    }
+
+    blocking_methods! {
+        "AnswerDao",
+        create_answer(new_answer: NewAnswer) -> Answer;
+        create_answer_draft(new_answer: NewAnswer) -> Answer;
+        publish_answer(answer_id: EntityId) -> Answer;
+        get_drafts(question_id: EntityId, author_id: EntityId) -> Vec<Answer>;
+        get_answer(answer_id: EntityId) -> Answer;
+        get_answers(question_id: EntityId) -> Vec<Answer>;
+        get_answer_threads(question_id: EntityId) -> Vec<AnswerThread>;
+        get_answers_sorted(question_id: EntityId, sort: AnswerSort, limit: u32) -> Vec<Answer>;
+        get_answers_paged(question_id: EntityId, limit: u32, page_token: Option<&str>) -> AnswerPage;
+        get_answers_with_authors(question_id: EntityId) -> Vec<AnswerWithAuthor>;
+        get_answers_by_token(author_token: &str) -> Vec<Answer>;
+        search_answers(question_id: EntityId, term: &str) -> Vec<Answer>;
+        search_all_answers(term: &str, limit: u32) -> Vec<AnswerWithQuestion>;
+        get_all_answers() -> Vec<Answer>;
+        get_all_answers_with_question(page: PageRequest) -> Page<AnswerWithQuestion>;
+        delete_answer(answer_id: EntityId) -> Uuid;
+        delete_answer_with_tombstone(answer_id: EntityId, reason: Option<String>) -> Answer;
+        increment_answer_likes(answer_id: EntityId) -> i64;
+        vote_answer(answer_id: EntityId, vote: AnswerVote, user_token: &str) -> VoteOutcome;
+        approve_answer(answer_id: EntityId) -> Answer;
+        reject_answer(answer_id: EntityId) -> Uuid;
+        archive_stale_answers(inactive_for: Duration, batch: usize) -> ArchiveReport;
+    }
+}
@@ -0,0 +1,72 @@
+use std::thread;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use crate::fixtures;
+use crate::models::{DbError, EntityId};
+use crate::persistence::pool::PoolConfig;
+use super::{AnswerClient, QuestionClient};
+
+// The test database is only set up by the async harness, so the clients are used from a thread without a runtime
+
+#[sqlx::test]
+async fn question_client_should_work_without_a_runtime(_: PgPoolOptions, options: PgConnectOptions) {
+    thread::spawn(move || {
+        let client = QuestionClient::connect_with(options, PoolConfig::default()).expect("client should connect successfully");
+        let question = client.create_question(fixtures::question().title("Blocking?").build()).expect("question should be created successfully");
+        assert_eq!(client.increment_question_likes(EntityId::uuid(question.id())).unwrap(), 1);
+        let res = client.get_questions();
+        println!("{:?}", res);
+        let questions = res.unwrap();
+        assert_eq!(questions.len(), 1);
+        assert_eq!((questions[0].title(), questions[0].likes()), ("Blocking?", 1));
+        client.delete_question(EntityId::uuid(question.id()), false).expect("question should be deleted successfully");
+        let res = client.get_question(EntityId::uuid(question.id()));
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+    })
+        .join()
+        .expect("blocking client should not panic");
+}
+
+#[sqlx::test]
+async fn answer_client_should_work_without_a_runtime(_: PgPoolOptions, options: PgConnectOptions) {
+    thread::spawn(move || {
+        let questions = QuestionClient::connect_with(options.clone(), PoolConfig::default()).expect("client should connect successfully");
+        let answers = AnswerClient::connect_with(options, PoolConfig::default()).expect("client should connect successfully");
+        let question_id = questions.create_question(fixtures::question().build()).expect("question should be created successfully").id();
+        let answer = answers.create_answer(fixtures::answer(question_id).answer("Block on it").build()).expect("answer should be created successfully");
+        let res = answers.get_answers(EntityId::uuid(question_id));
+        println!("{:?}", res);
+        let listed: Vec<_> = res.unwrap().iter().map(|answer| answer.id()).collect();
+        assert_eq!(listed, [answer.id()]);
+        assert_eq!(answers.get_answer(EntityId::uuid(answer.id())).unwrap().answer(), "Block on it");
+    })
+        .join()
+        .expect("blocking clients should not panic");
+}
+
+#[test]
+fn connect_should_report_connection_errors() {
+    let config = PoolConfig { acquire_timeout: std::time::Duration::from_secs(1), ..PoolConfig::default() };
+    let res = QuestionClient::connect("postgres://postgres@localhost:1/qa", config);
+    let Err(DbError::Unavailable { .. }) = res else { panic!("Error should be `Unavailable` variant") };
+}
+
+#[tokio::test]
+#[should_panic(expected = "`QuestionClient` cannot be used from within an async runtime")]
+async fn question_client_should_panic_when_created_within_a_runtime() {
+    let _ = QuestionClient::connect("postgres://postgres@localhost/qa", PoolConfig { lazy: true, ..PoolConfig::default() });
+}
+
+#[test]
+#[should_panic(expected = "`AnswerClient` cannot be used from within an async runtime")]
+fn answer_client_should_panic_when_used_within_a_runtime() {
+    let client = AnswerClient::connect("postgres://postgres@localhost/qa", PoolConfig { lazy: true, ..PoolConfig::default() })
+        .expect("lazy client should be created without connecting");
+    // The client may still be dropped within a runtime, but not used
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+        .block_on(async move {
+            let _ = client.get_all_answers();
+        });
+}
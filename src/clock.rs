@@ -0,0 +1,74 @@
+//! Contains the `Clock` trait used to obtain the current time, allowing time sensitive logic
+//! to be tested deterministically.
+
+use std::sync::Mutex;
+use chrono::{DateTime, Duration, Utc};
+
+/// A source of the current time.
+pub trait Clock: Send + Sync {
+    /// Gets the current time.
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// A `Clock` reading the system time, used by default.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A `Clock` that always reads the same time until it is explicitly moved.
+#[derive(Debug)]
+pub struct FixedClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl FixedClock {
+    /// Creates a clock that reads `now` until it is changed.
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self { now: Mutex::new(now) }
+    }
+
+    /// Sets the time read by the clock.
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    /// Moves the time read by the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap()
+    }
+}
+
+/// A `Clock` that moves forward by a fixed step every time it is read, so consecutive
+/// readings are distinct and strictly increasing.
+#[derive(Debug)]
+pub struct SteppingClock {
+    next: Mutex<DateTime<Utc>>,
+    step: Duration,
+}
+
+impl SteppingClock {
+    /// Creates a clock whose first reading is `start`.
+    pub fn new(start: DateTime<Utc>, step: Duration) -> Self {
+        Self { next: Mutex::new(start), step }
+    }
+}
+
+impl Clock for SteppingClock {
+    fn now(&self) -> DateTime<Utc> {
+        let mut next = self.next.lock().unwrap();
+        let now = *next;
+        *next += self.step;
+        now
+    }
+}
@@ -0,0 +1,206 @@
+//! Contains builders and seeding helpers for test data, available with the `testing` feature.
+//!
+//! # Example
+//! ```no_run
+//! # async fn example(pool: sqlx::PgPool) {
+//! use question_answer::fixtures;
+//! use question_answer::persistence::{AnswerDaoImpl, QuestionDao, QuestionDaoImpl};
+//!
+//! let question_dao = QuestionDaoImpl::new(pool.clone());
+//! let answer_dao = AnswerDaoImpl::new(pool);
+//! // Create a question with randomized content
+//! let question = question_dao.create_question(fixtures::question().build()).await.unwrap();
+//! // Or seed a question along with some answers
+//! let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 3).await;
+//! # }
+//! ```
+
+use sqlx::types::Uuid;
+use crate::models::{ContentType, NewAnswer, NewQuestion};
+use crate::persistence::{AnswerDao, QuestionDao};
+
+/// Creates a `QuestionFixture` with a randomized title and content.
+///
+/// # Example
+/// ```no_run
+/// use question_answer::fixtures;
+///
+/// let new_question = fixtures::question().title("How do I use fixtures?").build();
+/// assert_eq!(new_question.title, "How do I use fixtures?");
+/// ```
+pub fn question() -> QuestionFixture {
+    let suffix = Uuid::new_v4().simple();
+    QuestionFixture {
+        title: format!("Test Question {suffix}"),
+        question: format!("Hello this question is a test {suffix}"),
+        external_id: None,
+        content_type: None,
+        author_token: None,
+    }
+}
+
+/// Creates an `AnswerFixture` for the question with id `question_id`, with randomized content.
+///
+/// # Example
+/// ```no_run
+/// use question_answer::fixtures;
+/// use sqlx::types::Uuid;
+///
+/// let new_answer = fixtures::answer(Uuid::new_v4()).answer("Use the builders").build();
+/// assert_eq!(new_answer.answer, "Use the builders");
+/// ```
+pub fn answer(question_id: Uuid) -> AnswerFixture {
+    AnswerFixture {
+        question_id,
+        answer: format!("Test answer {}", Uuid::new_v4().simple()),
+        author_id: None,
+        content_type: None,
+        author_token: None,
+        parent_answer_id: None,
+    }
+}
+
+/// A builder for `NewQuestion`s used in tests.
+#[derive(Debug, Clone)]
+pub struct QuestionFixture {
+    title: String,
+    question: String,
+    external_id: Option<String>,
+    content_type: Option<ContentType>,
+    author_token: Option<String>,
+}
+
+impl QuestionFixture {
+    /// Overrides the title of the question.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Overrides the content of the question.
+    pub fn question(mut self, question: impl Into<String>) -> Self {
+        self.question = question.into();
+        self
+    }
+
+    /// Sets the id of the question in the external system it is synced from.
+    pub fn external_id(mut self, external_id: impl Into<String>) -> Self {
+        self.external_id = Some(external_id.into());
+        self
+    }
+
+    /// Sets whether the content of the question is plain text or markdown.
+    pub fn content_type(mut self, content_type: ContentType) -> Self {
+        self.content_type = Some(content_type);
+        self
+    }
+
+    /// Sets the token identifying the anonymous author of the question.
+    pub fn author_token(mut self, author_token: impl Into<String>) -> Self {
+        self.author_token = Some(author_token.into());
+        self
+    }
+
+    /// Builds the `NewQuestion`.
+    pub fn build(self) -> NewQuestion {
+        NewQuestion {
+            title: self.title,
+            question: self.question,
+            external_id: self.external_id,
+            content_type: self.content_type,
+            author_token: self.author_token,
+        }
+    }
+}
+
+/// A builder for `NewAnswer`s used in tests.
+#[derive(Debug, Clone)]
+pub struct AnswerFixture {
+    question_id: Uuid,
+    answer: String,
+    author_id: Option<Uuid>,
+    content_type: Option<ContentType>,
+    author_token: Option<String>,
+    parent_answer_id: Option<Uuid>,
+}
+
+impl AnswerFixture {
+    /// Overrides the content of the answer.
+    pub fn answer(mut self, answer: impl Into<String>) -> Self {
+        self.answer = answer.into();
+        self
+    }
+
+    /// Sets the id of the user authoring the answer.
+    pub fn author_id(mut self, author_id: Uuid) -> Self {
+        self.author_id = Some(author_id);
+        self
+    }
+
+    /// Sets whether the content of the answer is plain text or markdown.
+    pub fn content_type(mut self, content_type: ContentType) -> Self {
+        self.content_type = Some(content_type);
+        self
+    }
+
+    /// Sets the token identifying the anonymous author of the answer.
+    pub fn author_token(mut self, author_token: impl Into<String>) -> Self {
+        self.author_token = Some(author_token.into());
+        self
+    }
+
+    /// Sets the id of the answer the answer replies to.
+    pub fn parent_answer_id(mut self, parent_answer_id: Uuid) -> Self {
+        self.parent_answer_id = Some(parent_answer_id);
+        self
+    }
+
+    /// Builds the `NewAnswer`.
+    pub fn build(self) -> NewAnswer {
+        NewAnswer {
+            question_id: self.question_id.to_string(),
+            answer: self.answer,
+            author_id: self.author_id.map(|id| id.to_string()),
+            content_type: self.content_type,
+            author_token: self.author_token,
+            parent_answer_id: self.parent_answer_id.map(|id| id.to_string()),
+        }
+    }
+}
+
+/// Creates `n` questions with randomized content, returning their ids in creation order.
+///
+/// # Panics
+/// If any of the questions cannot be created.
+pub async fn seed_questions<Q: QuestionDao>(question_dao: &Q, n: usize) -> Vec<Uuid> {
+    let mut question_ids = Vec::with_capacity(n);
+    for _ in 0..n {
+        let question_id = question_dao.create_question(question().build())
+            .await
+            .expect("question should be created successfully")
+            .id();
+        question_ids.push(question_id);
+    }
+    question_ids
+}
+
+/// Creates a question with randomized content and `n` answers to it, returning the id of the
+/// question and the ids of the answers in creation order.
+///
+/// # Panics
+/// If the question or any of the answers cannot be created.
+pub async fn seed_question_with_answers<Q: QuestionDao, A: AnswerDao>(question_dao: &Q, answer_dao: &A, n: usize) -> (Uuid, Vec<Uuid>) {
+    let question_id = question_dao.create_question(question().build())
+        .await
+        .expect("question should be created successfully")
+        .id();
+    let mut answer_ids = Vec::with_capacity(n);
+    for _ in 0..n {
+        let answer_id = answer_dao.create_answer(answer(question_id).build())
+            .await
+            .expect("answer should be created successfully")
+            .id();
+        answer_ids.push(answer_id);
+    }
+    (question_id, answer_ids)
+}
@@ -0,0 +1,5 @@
+//! Contains importers bringing content from other question and answer sites into the database.
+
+pub mod stackexchange;
+#[cfg(test)]
+mod test;
@@ -0,0 +1,285 @@
+//! Contains the importer for the `Posts.xml` files of the public Stack Exchange data dumps.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::BufRead;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use sqlx::types::Uuid;
+use crate::models::{DbError, NewAnswer, NewQuestion};
+use crate::persistence::{AnswerDao, QuestionDao};
+
+/// The `PostTypeId` of questions.
+const QUESTION_POST_TYPE: &str = "1";
+/// The `PostTypeId` of answers.
+const ANSWER_POST_TYPE: &str = "2";
+
+/// Why a post was not imported.
+#[derive(Debug)]
+pub enum SkipReason {
+    /// The answer's question was not imported, because it is missing from the dump, was skipped, or comes after the limit
+    Orphaned,
+    /// The row lacks a required attribute or an attribute could not be read
+    Malformed(String),
+    /// The data access object rejected the post's content
+    Rejected(DbError),
+}
+
+/// A post that was not imported.
+#[derive(Debug)]
+pub struct SkippedPost {
+    /// The `Id` of the post in the dump, `None` if the row has no readable id
+    pub post_id: Option<String>,
+    /// Why the post was not imported
+    pub reason: SkipReason,
+}
+
+/// The result of importing a `Posts.xml` file.
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    /// The number of questions created
+    pub questions_created: usize,
+    /// The number of answers created
+    pub answers_created: usize,
+    /// The number of posts that are neither questions nor answers, such as tag wikis, which are ignored
+    pub ignored: usize,
+    /// The posts that were not imported, in the order they appear in the dump
+    pub skipped: Vec<SkippedPost>,
+    /// The ids of the questions created, keyed by the `Id` of their post in the dump
+    pub question_ids: HashMap<String, Uuid>,
+}
+
+impl Display for ImportReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Imported {} questions and {} answers, skipped {} posts and ignored {}",
+            self.questions_created, self.answers_created, self.skipped.len(), self.ignored
+        )
+    }
+}
+
+/// The errors that stop an import.
+#[derive(Debug)]
+pub enum ImportError {
+    /// The file is not well formed XML
+    Xml(quick_xml::Error),
+    /// The database failed for a reason other than the content of a post
+    Db(DbError),
+}
+
+impl Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::Xml(e) => write!(f, "Error reading posts: {e}"),
+            ImportError::Db(e) => write!(f, "Error importing posts: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+/// Imports the questions and answers of a Stack Exchange `Posts.xml` file, streaming it row by row.
+///
+/// Questions are created from rows with `PostTypeId="1"`, and answers from rows with `PostTypeId="2"`, attached to
+/// the question created for their `ParentId`. The HTML of bodies is reduced to plain text. Posts are created with
+/// the current time rather than their original dates, and other post types are ignored.
+///
+/// # Parameters
+/// `reader`: The contents of the `Posts.xml` file
+/// `question_dao`: The `QuestionDao` the questions are created with
+/// `answer_dao`: The `AnswerDao` the answers are created with
+/// `limit`: The maximum number of posts to create, questions and answers combined, or `None` to import every post
+///
+/// # Returns
+/// A `Result<ImportReport, ImportError>`, `Ok(ImportReport)` counting the created posts and listing any skipped
+/// ones. Answers whose question was not imported, malformed rows and posts rejected with `DbError::Validation` are
+/// skipped rather than stopping the import. Malformed XML stops the import with `Err(ImportError::Xml)`, and any other
+/// database error with `Err(ImportError::Db)`, keeping the posts created so far.
+pub async fn import_posts<Q: QuestionDao, A: AnswerDao>(
+    reader: impl BufRead,
+    question_dao: &Q,
+    answer_dao: &A,
+    limit: Option<usize>,
+) -> Result<ImportReport, ImportError> {
+    let mut reader = Reader::from_reader(reader);
+    let mut buf = Vec::new();
+    let mut report = ImportReport::default();
+    while limit.is_none_or(|limit| report.questions_created + report.answers_created < limit) {
+        let row = match reader.read_event_into(&mut buf).map_err(ImportError::Xml)? {
+            Event::Eof => break,
+            Event::Start(element) | Event::Empty(element) if element.name().as_ref() == b"row" => Post::parse(&element),
+            _ => {
+                buf.clear();
+                continue;
+            }
+        };
+        buf.clear();
+        let post = match row {
+            Ok(post) => post,
+            Err((post_id, reason)) => {
+                report.skipped.push(SkippedPost { post_id, reason: SkipReason::Malformed(reason) });
+                continue;
+            }
+        };
+        match post.post_type.as_deref() {
+            Some(QUESTION_POST_TYPE) => import_question(post, question_dao, &mut report).await?,
+            Some(ANSWER_POST_TYPE) => import_answer(post, answer_dao, &mut report).await?,
+            _ => report.ignored += 1,
+        }
+    }
+    Ok(report)
+}
+
+/// The attributes of a row used by the import.
+#[derive(Debug, Default)]
+struct Post {
+    id: String,
+    post_type: Option<String>,
+    parent_id: Option<String>,
+    title: Option<String>,
+    body: Option<String>,
+}
+
+impl Post {
+    /// Reads the attributes of a `row` element, returning the post's id, if readable, and the reason on failure.
+    fn parse(element: &BytesStart) -> Result<Self, (Option<String>, String)> {
+        let mut id = None;
+        let mut post = Post::default();
+        for attribute in element.attributes() {
+            let attribute = attribute.map_err(|e| (id.clone(), e.to_string()))?;
+            let value = attribute.unescape_value().map_err(|e| (id.clone(), e.to_string()))?.into_owned();
+            match attribute.key.as_ref() {
+                b"Id" => id = Some(value),
+                b"PostTypeId" => post.post_type = Some(value),
+                b"ParentId" => post.parent_id = Some(value),
+                b"Title" => post.title = Some(value),
+                b"Body" => post.body = Some(value),
+                _ => {}
+            }
+        }
+        post.id = id.ok_or_else(|| (None, String::from("missing attribute `Id`")))?;
+        Ok(post)
+    }
+
+    /// Takes a required attribute, reporting the post as malformed if it is missing.
+    fn require(&mut self, attribute: &str, value: fn(&mut Post) -> &mut Option<String>) -> Result<String, SkippedPost> {
+        value(self).take().ok_or_else(|| SkippedPost {
+            post_id: Some(self.id.clone()),
+            reason: SkipReason::Malformed(format!("missing attribute `{attribute}`")),
+        })
+    }
+}
+
+/// Sorts the outcome of creating a post into the report, stopping the import for errors not caused by its content.
+fn record<T>(post_id: &str, res: Result<T, DbError>, report: &mut ImportReport) -> Result<Option<T>, ImportError> {
+    match res {
+        Ok(created) => Ok(Some(created)),
+        Err(e @ DbError::Validation { .. }) => {
+            report.skipped.push(SkippedPost { post_id: Some(post_id.to_string()), reason: SkipReason::Rejected(e) });
+            Ok(None)
+        }
+        Err(e) => Err(ImportError::Db(e)),
+    }
+}
+
+/// Creates the question of a post, remembering its id for the answers that follow.
+async fn import_question<Q: QuestionDao>(mut post: Post, question_dao: &Q, report: &mut ImportReport) -> Result<(), ImportError> {
+    let fields = post.require("Title", |post| &mut post.title).and_then(|title| Ok((title, post.require("Body", |post| &mut post.body)?)));
+    let (title, body) = match fields {
+        Ok(fields) => fields,
+        Err(skipped) => {
+            report.skipped.push(skipped);
+            return Ok(());
+        }
+    };
+    let new_question = NewQuestion { title: title.trim().to_string(), question: strip_html(&body), external_id: None, content_type: None, author_token: None };
+    if let Some(question) = record(&post.id, question_dao.create_question(new_question).await, report)? {
+        report.questions_created += 1;
+        report.question_ids.insert(post.id, question.id());
+    }
+    Ok(())
+}
+
+/// Creates the answer of a post, attached to the question created for its parent.
+async fn import_answer<A: AnswerDao>(mut post: Post, answer_dao: &A, report: &mut ImportReport) -> Result<(), ImportError> {
+    let fields = post.require("ParentId", |post| &mut post.parent_id).and_then(|parent| Ok((parent, post.require("Body", |post| &mut post.body)?)));
+    let (parent_id, body) = match fields {
+        Ok(fields) => fields,
+        Err(skipped) => {
+            report.skipped.push(skipped);
+            return Ok(());
+        }
+    };
+    let Some(question_id) = report.question_ids.get(&parent_id) else {
+        report.skipped.push(SkippedPost { post_id: Some(post.id), reason: SkipReason::Orphaned });
+        return Ok(());
+    };
+    let new_answer = NewAnswer { question_id: question_id.to_string(), answer: strip_html(&body), author_id: None, content_type: None, author_token: None, parent_answer_id: None };
+    if record(&post.id, answer_dao.create_answer(new_answer).await, report)?.is_some() {
+        report.answers_created += 1;
+    }
+    Ok(())
+}
+
+/// Reduces the HTML of a post body to plain text, removing tags, keeping line breaks between blocks and decoding
+/// character references.
+pub fn strip_html(html: &str) -> String {
+    // Closing these elements starts a new line in the rendered text
+    const BREAKS: [&str; 9] = ["br", "br/", "/p", "/pre", "/li", "/blockquote", "/h1", "/h2", "/h3"];
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&decode_entities(&rest[..start]));
+        let Some(end) = rest[start..].find('>') else {
+            // An unterminated tag is text
+            rest = &rest[start..];
+            break;
+        };
+        let tag = rest[start + 1..start + end].trim().to_ascii_lowercase();
+        let name = tag.split_whitespace().next().unwrap_or_default();
+        if BREAKS.contains(&name) {
+            text.push('\n');
+        }
+        rest = &rest[start + end + 1..];
+    }
+    text.push_str(&decode_entities(rest));
+    // Collapse the blank lines left between blocks
+    text.lines().map(str::trim_end).filter(|line| !line.is_empty()).collect::<Vec<_>>().join("\n").trim().to_string()
+}
+
+/// Decodes the named character references common in post bodies, and numeric character references.
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest.find(';').map(|end| (&rest[1..end], end));
+        let character = entity.and_then(|(name, _)| match name {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => name.strip_prefix("#x").or_else(|| name.strip_prefix("#X"))
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| name.strip_prefix('#').map(str::parse))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        });
+        match (character, entity) {
+            (Some(character), Some((_, end))) => {
+                decoded.push(character);
+                rest = &rest[end + 1..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
@@ -0,0 +1,66 @@
+mod stackexchange_tests {
+    use sqlx::PgPool;
+    use crate::interop::stackexchange::{import_posts, strip_html, ImportError, SkipReason};
+    use crate::models::{ContentLimits, DbError, EntityId};
+    use crate::persistence::{AnswerDao, AnswerDaoImpl, QuestionDao, QuestionDaoImpl};
+
+    const POSTS: &[u8] = include_bytes!("testdata/Posts.xml");
+
+    #[sqlx::test]
+    async fn import_posts_should_create_questions_and_answers(pool: PgPool) -> Result<(), DbError> {
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_limits(ContentLimits { max_title: 60, ..Default::default() });
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let report = import_posts(POSTS, &question_dao, &answer_dao, None).await.expect("posts should be imported");
+        println!("{}", report);
+        assert_eq!(report.questions_created, 2);
+        assert_eq!(report.answers_created, 3);
+        assert_eq!(report.ignored, 1);
+        let skipped = report.skipped.iter()
+            .map(|skipped| (skipped.post_id.as_deref(), &skipped.reason))
+            .collect::<Vec<_>>();
+        assert!(matches!(skipped[..], [
+            (Some("5"), SkipReason::Orphaned),
+            (Some("6"), SkipReason::Malformed(_)),
+            (Some("7"), SkipReason::Rejected(DbError::Validation { .. })),
+        ]));
+        // The question and its answers should be mapped from the first rows of the dump
+        let question_id = EntityId::new(report.question_ids["1"].to_string());
+        let question = question_dao.get_question(question_id.clone()).await?;
+        assert_eq!(question.title(), "How do I reverse a string?");
+        assert_eq!(question.question(), "I have a String and want it backwards & in place.\nIs there a method?");
+        let answers = answer_dao.get_answers(question_id).await?;
+        assert_eq!(answers.len(), 2);
+        assert!(answers.iter().any(|answer| answer.answer() == "Use s.chars().rev().collect()."));
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn import_posts_should_stop_at_limit(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let report = import_posts(POSTS, &question_dao, &answer_dao, Some(3)).await.expect("posts should be imported");
+        println!("{}", report);
+        assert_eq!(report.questions_created, 1);
+        assert_eq!(report.answers_created, 2);
+        assert!(report.skipped.is_empty());
+        assert_eq!(question_dao.get_questions().await.expect("questions should be read").len(), 1);
+    }
+
+    #[sqlx::test]
+    async fn import_posts_should_fail_on_malformed_xml(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let xml = br#"<posts><row Id="1" PostTypeId="1" Title="Kept" Body="&lt;p&gt;Created before the error&lt;/p&gt;" /></post>"#;
+        let res = import_posts(&xml[..], &question_dao, &answer_dao, None).await;
+        println!("{:?}", res);
+        let Err(ImportError::Xml(_)) = res else { panic!("Error should be `Xml` variant") };
+        // Posts read before the error should be kept
+        assert_eq!(question_dao.get_questions().await.expect("questions should be read").len(), 1);
+    }
+
+    #[test]
+    fn strip_html_should_keep_text_and_line_breaks() {
+        assert_eq!(strip_html("<p>One &lt;tag&gt;</p>\n\n<p>Two<br/>Three &#233;&#x21;</p>"), "One <tag>\nTwo\nThree é!");
+        assert_eq!(strip_html("a < b && c"), "a < b && c");
+    }
+}
@@ -0,0 +1,17 @@
+//! Persistence for a question and answer service: the models, and data access objects backed by Postgres.
+//!
+//! Most code only needs the `persistence::prelude`, which brings the data access traits, their
+//! implementations and the model types into scope.
+#![deny(missing_docs)]
+pub mod admin;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod clock;
+#[cfg(feature = "stackexchange")]
+pub mod interop;
+pub mod models;
+pub mod persistence;
+#[cfg(any(test, feature = "testing"))]
+pub mod fixtures;
+#[cfg(any(test, feature = "testing"))]
+pub mod query_counter;
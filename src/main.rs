@@ -1,6 +1,3 @@
-pub mod models;
-pub mod persistence;
-
 fn main() {
     println!("Hello, world!");
 }
@@ -0,0 +1,29 @@
+//! Contains the length statistics stored alongside the content of questions and answers, so that short posts
+//! can be found without reading their content.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The length of a piece of content as a reader perceives it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ContentStats {
+    /// The number of characters, counting each grapheme cluster, such as an emoji sequence, once
+    pub char_count: i32,
+    /// The number of words, split at Unicode word boundaries. Scripts written without spaces, such as Chinese
+    /// and Japanese, count each ideograph as a word
+    pub word_count: i32,
+}
+
+impl ContentStats {
+    /// Computes the statistics of `text`.
+    pub fn of(text: &str) -> Self {
+        Self {
+            char_count: saturating_count(text.graphemes(true)),
+            word_count: saturating_count(text.unicode_words()),
+        }
+    }
+}
+
+/// Counts the items of `iter`, saturating at the largest count the database columns can hold.
+fn saturating_count<T>(iter: impl Iterator<Item = T>) -> i32 {
+    i32::try_from(iter.count()).unwrap_or(i32::MAX)
+}
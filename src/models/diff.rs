@@ -0,0 +1,139 @@
+//! Contains `diff_revisions`, which compares two revisions of a question's content line by line, so that every
+//! client shows the same changes.
+
+use serde::Serialize;
+
+/// The number of unchanged lines kept around each change.
+pub const CONTEXT_LINES: usize = 3;
+
+/// A line of a `DiffHunk`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "text", rename_all = "snake_case")]
+pub enum DiffLine {
+    /// A line both revisions share, shown around changes
+    Context(String),
+    /// A line only the new revision has
+    Added(String),
+    /// A line only the old revision has
+    Removed(String),
+}
+
+/// A run of changed lines along with up to `CONTEXT_LINES` unchanged lines on either side, as in a unified diff.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiffHunk {
+    /// The line of the old revision the hunk starts at, counting from one. For a hunk only adding lines to an empty
+    /// revision this is zero
+    pub old_start: usize,
+    /// The number of lines of the old revision the hunk covers, its context and removed lines
+    pub old_lines: usize,
+    /// The line of the new revision the hunk starts at, counting from one. For a hunk only removing every line this
+    /// is zero
+    pub new_start: usize,
+    /// The number of lines of the new revision the hunk covers, its context and added lines
+    pub new_lines: usize,
+    /// The lines of the hunk in order
+    pub lines: Vec<DiffLine>,
+}
+
+/// A step of the edit turning the old lines into the new ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    Keep,
+    Remove,
+    Add,
+}
+
+/// Compares two revisions of some content line by line, using a longest common subsequence so that the fewest lines
+/// are reported as changed. Differences in line endings, and in a trailing newline, are not changes.
+///
+/// # Returns
+/// The hunks of changed lines in order, empty if the revisions have the same lines.
+pub fn diff_revisions(old: &str, new: &str) -> Vec<DiffHunk> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let edits = edits(&old, &new);
+    // Index of the first and one past the last edit of each hunk, merging changes whose context would overlap
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for (i, _) in edits.iter().enumerate().filter(|(_, edit)| **edit != Edit::Keep) {
+        let start = i.saturating_sub(CONTEXT_LINES);
+        let end = (i + 1 + CONTEXT_LINES).min(edits.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+    // The lines of each revision consumed before each edit
+    let mut positions = Vec::with_capacity(edits.len() + 1);
+    let (mut o, mut n) = (0, 0);
+    for edit in &edits {
+        positions.push((o, n));
+        match edit {
+            Edit::Keep => (o, n) = (o + 1, n + 1),
+            Edit::Remove => o += 1,
+            Edit::Add => n += 1,
+        }
+    }
+    positions.push((o, n));
+    ranges
+        .into_iter()
+        .map(|(start, end)| {
+            let (old_from, new_from) = positions[start];
+            let (old_to, new_to) = positions[end];
+            let lines = edits[start..end]
+                .iter()
+                .zip(&positions[start..end])
+                .map(|(edit, &(o, n))| match edit {
+                    Edit::Keep => DiffLine::Context(old[o].to_string()),
+                    Edit::Remove => DiffLine::Removed(old[o].to_string()),
+                    Edit::Add => DiffLine::Added(new[n].to_string()),
+                })
+                .collect();
+            DiffHunk {
+                old_start: if old_to > old_from { old_from + 1 } else { old_from },
+                old_lines: old_to - old_from,
+                new_start: if new_to > new_from { new_from + 1 } else { new_from },
+                new_lines: new_to - new_from,
+                lines,
+            }
+        })
+        .collect()
+}
+
+/// The shortest edit turning `old` into `new`. Lines shared at the start and end are kept without being compared
+/// further, so the quadratic table only covers the changed middle of the revisions.
+fn edits(old: &[&str], new: &[&str]) -> Vec<Edit> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..].iter().rev().zip(new[prefix..].iter().rev()).take_while(|(a, b)| a == b).count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+    // lengths[i][j] is the length of the longest common subsequence of old_middle[i..] and new_middle[j..]
+    let width = new_middle.len() + 1;
+    let mut lengths = vec![0u32; (old_middle.len() + 1) * width];
+    for i in (0..old_middle.len()).rev() {
+        for j in (0..new_middle.len()).rev() {
+            lengths[i * width + j] = if old_middle[i] == new_middle[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+    let mut edits = vec![Edit::Keep; prefix];
+    let (mut i, mut j) = (0, 0);
+    while i < old_middle.len() && j < new_middle.len() {
+        if old_middle[i] == new_middle[j] {
+            edits.push(Edit::Keep);
+            (i, j) = (i + 1, j + 1);
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            edits.push(Edit::Remove);
+            i += 1;
+        } else {
+            edits.push(Edit::Add);
+            j += 1;
+        }
+    }
+    edits.extend(std::iter::repeat_n(Edit::Remove, old_middle.len() - i));
+    edits.extend(std::iter::repeat_n(Edit::Add, new_middle.len() - j));
+    edits.extend(std::iter::repeat_n(Edit::Keep, suffix));
+    edits
+}
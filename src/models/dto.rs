@@ -0,0 +1,981 @@
+//! Contains the request and response representations of the models, decoupling what clients
+//! see from how entities are persisted.
+
+use std::borrow::Cow;
+use std::io::{self, Write};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::de::{DeserializeOwned, Error};
+use serde::{Deserialize, Serialize};
+use super::{Answer, AnswerBodyMode, ContentLimits, ContentType, DbError, LikableEntity, LikeTarget, NewAnswer, NewQuestion, Question, QuestionDetail, QuestionHeader, UpdateQuestion, ValidationReason, ANSWER_PREVIEW_CHARS};
+
+/// Formats a timestamp as RFC 3339 with a fixed precision in UTC.
+fn format_timestamp(timestamp: DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+/// The representation of a `Question` returned to clients.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuestionResponse {
+    /// The unique id of the question
+    pub id: String,
+    /// The sequential secondary id of the question, for legacy clients
+    pub serial: Option<i64>,
+    /// The title of the question
+    pub title: String,
+    /// The content of the question
+    pub question: String,
+    /// The number of likes the question has received
+    pub likes: i64,
+    /// The number of times the question has been viewed
+    pub views: i32,
+    /// The RFC 3339 timestamp the question was created
+    pub created_at: String,
+    /// The RFC 3339 timestamp the question was last updated, if ever
+    pub updated_at: Option<String>,
+    /// The RFC 3339 timestamp the question was pinned, if it is pinned
+    pub pinned_at: Option<String>,
+    /// The unique id of the answer pinned above the others, if any
+    pub pinned_answer_id: Option<String>,
+    /// The unique id of the answer accepted by the author of the question, if it is still listed
+    #[serde(default)]
+    pub accepted_answer_id: Option<String>,
+    /// Whether the content is plain text or markdown
+    pub content_type: ContentType,
+    /// Whether the caller authored it, only present when the caller identified themselves with an author token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_mine: Option<bool>,
+    /// The bounty offered for answering the question, only present while it has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bounty: Option<BountyResponse>,
+    /// The language of the title and content, only present when they are a translation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    /// Whether the title and content are a translation rather than the original, only present when they are
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_translation: bool,
+}
+
+impl QuestionResponse {
+    /// Creates the response for a question as of `now`, leaving out a bounty that has expired without having been
+    /// cleared yet and reporting the time remaining on an unexpired one.
+    ///
+    /// `now` should come from the same `Clock` as the data access objects, see `QuestionDaoImpl::with_clock`.
+    pub fn at(question: Question, now: DateTime<Utc>) -> Self {
+        let bounty = BountyResponse::of(&question, now);
+        Self { bounty, ..Self::from(question) }
+    }
+}
+
+/// Creates the response without reading the time, so that it is the same whenever it is created: the bounty is
+/// reported as stored, without its remaining time, see `QuestionResponse::at`.
+impl From<Question> for QuestionResponse {
+    fn from(question: Question) -> Self {
+        let bounty = BountyResponse::stored(&question);
+        Self {
+            id: question.id.to_string(),
+            serial: question.serial,
+            title: question.title,
+            question: question.question,
+            likes: question.likes,
+            views: question.views,
+            created_at: format_timestamp(question.created_at),
+            updated_at: question.updated_at.map(format_timestamp),
+            pinned_at: question.pinned_at.map(format_timestamp),
+            pinned_answer_id: question.pinned_answer_id.map(|id| id.to_string()),
+            accepted_answer_id: question.accepted_answer_id.map(|id| id.to_string()),
+            content_type: question.content_type,
+            is_mine: question.is_mine,
+            bounty,
+            is_translation: question.lang.is_some(),
+            lang: question.lang,
+        }
+    }
+}
+
+/// The representation of the bounty of a question returned to clients.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BountyResponse {
+    /// The points offered for answering the question
+    pub amount: i32,
+    /// The RFC 3339 timestamp the bounty expires
+    pub expires_at: String,
+    /// The number of whole seconds remaining until the bounty expires, only present when the response was created
+    /// as of a given time, see `QuestionResponse::at`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remaining_seconds: Option<i64>,
+}
+
+impl BountyResponse {
+    /// Creates the response for the bounty of `question` as of `now`, `None` if it has no bounty or it has expired.
+    pub fn of(question: &Question, now: DateTime<Utc>) -> Option<Self> {
+        let remaining = question.bounty_remaining(now)?;
+        Some(Self { remaining_seconds: Some(remaining.num_seconds()), ..Self::stored(question)? })
+    }
+
+    /// Creates the response for the bounty of `question` as stored, without its remaining time, `None` if it has no
+    /// bounty.
+    pub fn stored(question: &Question) -> Option<Self> {
+        Some(Self {
+            amount: question.bounty_amount?,
+            expires_at: format_timestamp(question.bounty_expires_at?),
+            remaining_seconds: None,
+        })
+    }
+}
+
+/// The representation of an `Answer` returned to clients.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnswerResponse {
+    /// The unique id of the answer
+    pub id: String,
+    /// The sequential secondary id of the answer, for legacy clients
+    pub serial: Option<i64>,
+    /// The unique id of the question the answer responds to, `None` if the question was deleted
+    pub question_id: Option<String>,
+    /// The content of the answer, left out when the response carries no answer bodies
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub answer: String,
+    /// The number of likes the answer has received
+    pub likes: i64,
+    /// The RFC 3339 timestamp the answer was created
+    pub created_at: String,
+    /// Whether the content is plain text or markdown
+    pub content_type: ContentType,
+    /// Whether the caller authored it, only present when the caller identified themselves with an author token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_mine: Option<bool>,
+    /// The unique id of the answer it replies to, only present for replies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_answer_id: Option<String>,
+    /// Whether a moderator replaced the answer with a tombstone, whose content is `DELETED_CONTENT`, only present
+    /// for tombstones
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_deleted: bool,
+    /// Why the answer was tombstoned, only present for tombstones with a reason
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_reason: Option<String>,
+    /// Whether the content is shortened or left out, see `AnswerBodyMode`, only present when it is
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+impl AnswerResponse {
+    /// Creates the response for an answer carrying as much of its content as `mode` keeps. Previews are cut at a
+    /// character boundary, so multi-byte characters are never split.
+    pub fn with_body(answer: Answer, mode: AnswerBodyMode) -> Self {
+        let mut response = Self::from(answer);
+        match mode {
+            AnswerBodyMode::Full => {}
+            AnswerBodyMode::Preview => {
+                if let Some((end, _)) = response.answer.char_indices().nth(ANSWER_PREVIEW_CHARS) {
+                    response.answer.truncate(end);
+                    response.truncated = true;
+                }
+            }
+            AnswerBodyMode::None => {
+                response.answer.clear();
+                response.truncated = true;
+            }
+        }
+        response
+    }
+}
+
+impl From<Answer> for AnswerResponse {
+    fn from(answer: Answer) -> Self {
+        Self {
+            id: answer.id.to_string(),
+            serial: answer.serial,
+            question_id: answer.question_id.map(|id| id.to_string()),
+            answer: answer.answer,
+            likes: answer.likes,
+            created_at: format_timestamp(answer.created_at),
+            content_type: answer.content_type,
+            is_mine: answer.is_mine,
+            parent_answer_id: answer.parent_answer_id.map(|id| id.to_string()),
+            is_deleted: answer.deleted_at.is_some(),
+            deleted_reason: answer.deleted_reason,
+            truncated: false,
+        }
+    }
+}
+
+/// The representation of a question along with its answers returned to clients.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuestionDetailResponse {
+    /// The question itself
+    #[serde(flatten)]
+    pub question: QuestionResponse,
+    /// The answers to the question
+    pub answers: Vec<AnswerResponse>,
+}
+
+impl QuestionDetailResponse {
+    /// Creates the response for a question and its answers.
+    pub fn new(question: Question, answers: Vec<Answer>) -> Self {
+        Self::with_answer_bodies(question, answers, AnswerBodyMode::Full)
+    }
+
+    /// Creates the response for a question and its answers, each carrying as much of its content as `mode` keeps,
+    /// see `AnswerResponse::with_body`.
+    pub fn with_answer_bodies(question: Question, answers: Vec<Answer>, mode: AnswerBodyMode) -> Self {
+        Self {
+            question: question.into(),
+            answers: answers.into_iter().map(|answer| AnswerResponse::with_body(answer, mode)).collect(),
+        }
+    }
+
+    /// Creates the response for the detail of a question read by `QuestionDao::get_question_detail_with`, each
+    /// embedded answer carrying as much of its content as `mode` keeps.
+    pub fn from_detail(detail: QuestionDetail, mode: AnswerBodyMode) -> Self {
+        let (header, answers) = detail.into_parts();
+        Self::with_answer_bodies(header.question, answers, mode)
+    }
+}
+
+/// The engagement counters of a question or an answer, exported separately from the content so that they can be
+/// restored or left out independently of it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EngagementRecord {
+    /// The kind of entity the counters belong to
+    pub entity: LikableEntity,
+    /// The unique id of the entity
+    pub id: String,
+    /// The number of likes the entity has received
+    pub likes: i64,
+    /// The number of times the entity has been viewed, `None` for answers whose views are not counted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub views: Option<i32>,
+}
+
+impl From<&Question> for EngagementRecord {
+    fn from(question: &Question) -> Self {
+        Self { entity: LikableEntity::Question, id: question.id.to_string(), likes: question.likes, views: Some(question.views) }
+    }
+}
+
+impl From<&Answer> for EngagementRecord {
+    fn from(answer: &Answer) -> Self {
+        Self { entity: LikableEntity::Answer, id: answer.id.to_string(), likes: answer.likes, views: None }
+    }
+}
+
+/// The representation of a `QuestionHeader` returned to clients.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuestionHeaderResponse {
+    /// The unique id of the question
+    pub id: String,
+    /// The sequential secondary id of the question, for legacy clients
+    pub serial: Option<i64>,
+    /// The title of the question
+    pub title: String,
+    /// The content of the question
+    pub question: String,
+    /// The number of likes the question has received
+    pub likes: i64,
+    /// The number of times the question has been viewed
+    pub views: i32,
+    /// The number of published answers to the question
+    pub answer_count: i64,
+    /// The unique id of the answer pinned above the others, if any, which is not necessarily the accepted answer
+    pub pinned_answer_id: Option<String>,
+    /// Whether an answer to the question was accepted by its author, whether or not it has since been archived
+    pub has_accepted_answer: bool,
+    /// The unique id of the accepted answer, if any
+    pub accepted_answer_id: Option<String>,
+    /// Whether the content is plain text or markdown
+    pub content_type: ContentType,
+}
+
+impl From<QuestionHeader> for QuestionHeaderResponse {
+    fn from(header: QuestionHeader) -> Self {
+        let (has_accepted_answer, accepted_answer_id) = (header.has_accepted_answer(), header.accepted_answer_id());
+        let question = header.question;
+        Self {
+            id: question.id.to_string(),
+            serial: question.serial,
+            title: question.title,
+            question: question.question,
+            likes: question.likes,
+            views: question.views,
+            answer_count: header.answer_count,
+            pinned_answer_id: question.pinned_answer_id.map(|id| id.to_string()),
+            has_accepted_answer,
+            accepted_answer_id: accepted_answer_id.map(|id| id.to_string()),
+            content_type: question.content_type,
+        }
+    }
+}
+
+/// How fields a request type does not have are handled when parsing a request body.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InputMode {
+    /// Unknown fields are ignored, so existing clients keep working
+    #[default]
+    Lenient,
+    /// Unknown fields are rejected, naming the field and the expected ones
+    Strict,
+}
+
+/// A request type whose fields are known, so strict parsing can reject any others.
+pub trait RequestFields {
+    /// The names of the fields of the request type
+    const FIELDS: &'static [&'static str];
+}
+
+impl RequestFields for NewQuestion {
+    const FIELDS: &'static [&'static str] = &["title", "question", "external_id", "content_type", "author_token"];
+}
+
+impl RequestFields for NewAnswer {
+    const FIELDS: &'static [&'static str] = &["question_id", "answer", "author_id", "content_type", "author_token", "parent_answer_id"];
+}
+
+impl RequestFields for UpdateQuestion {
+    const FIELDS: &'static [&'static str] = &["title", "question"];
+}
+
+impl RequestFields for LikeTarget {
+    const FIELDS: &'static [&'static str] = &["entity", "id"];
+}
+
+/// Parses a JSON request body, rejecting unknown fields in `InputMode::Strict`.
+///
+/// # Parameters
+/// `body`: The JSON request body
+/// `mode`: Whether unknown fields are ignored or rejected
+///
+/// # Returns
+/// A `Result<T, serde_json::Error>`, `Ok(T)` if the body is valid for the mode, otherwise `Err(serde_json::Error)`
+/// describing the unknown or missing field.
+pub fn parse_request<T: DeserializeOwned + RequestFields>(body: &str, mode: InputMode) -> Result<T, serde_json::Error> {
+    if mode == InputMode::Lenient {
+        return serde_json::from_str(body);
+    }
+    from_value(serde_json::from_str(body)?, mode)
+}
+
+/// Converts a parsed JSON request body, rejecting unknown fields in `InputMode::Strict`.
+fn from_value<T: DeserializeOwned + RequestFields>(value: serde_json::Value, mode: InputMode) -> Result<T, serde_json::Error> {
+    if mode == InputMode::Strict {
+        if let Some(field) = value.as_object().and_then(|object| object.keys().find(|key| !T::FIELDS.contains(&key.as_str()))) {
+            return Err(serde_json::Error::unknown_field(field, T::FIELDS));
+        }
+    }
+    serde_json::from_value(value)
+}
+
+/// Parses an RFC 7396 JSON Merge Patch of a question into the `UpdateQuestion` it describes.
+///
+/// Fields present in the patch are changed and absent fields are left unchanged. Setting a field to `null` would
+/// remove it, which is rejected since the title and the content of a question are both required.
+///
+/// # Parameters
+/// `body`: The JSON Merge Patch request body
+/// `mode`: Whether unknown fields are ignored or rejected
+///
+/// # Returns
+/// A `Result<UpdateQuestion, serde_json::Error>`, `Ok(UpdateQuestion)` if the patch is valid for the mode, otherwise
+/// `Err(serde_json::Error)` if the patch is not an object, removes a field or has an unknown or mistyped field.
+pub fn parse_question_patch(body: &str, mode: InputMode) -> Result<UpdateQuestion, serde_json::Error> {
+    let value: serde_json::Value = serde_json::from_str(body)?;
+    let Some(patch) = value.as_object() else {
+        return Err(serde_json::Error::custom("a merge patch of a question must be a JSON object"));
+    };
+    if let Some(field) = UpdateQuestion::FIELDS.iter().find(|field| patch.get(**field).is_some_and(serde_json::Value::is_null)) {
+        return Err(serde_json::Error::custom(format!("field `{field}` cannot be removed, a question requires it")));
+    }
+    from_value(value, mode)
+}
+
+/// The maximum nesting depth of arrays and objects in a request body. Request bodies are flat objects, so the limit
+/// only rejects bodies built to exhaust the parser.
+pub const MAX_JSON_DEPTH: usize = 32;
+
+/// Why a request body was rejected, as reported to clients in a 400 response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BodyErrorCode {
+    /// The body exceeds the size limit of the route
+    BodyTooLarge,
+    /// The body is not valid UTF-8
+    InvalidUtf8,
+    /// Arrays or objects are nested deeper than `MAX_JSON_DEPTH`
+    NestingTooDeep,
+    /// The body is not valid JSON
+    MalformedJson,
+    /// The body ends before the JSON value does
+    TruncatedJson,
+    /// The body is valid JSON but not a valid request, such as a mistyped, missing or unknown field
+    InvalidRequest,
+}
+
+/// The structured body of a 400 response to a request body that could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BodyError {
+    /// Why the body was rejected
+    pub code: BodyErrorCode,
+    /// The JSON path of the failure, such as `$.title`, or `$` for the body as a whole
+    pub path: String,
+    /// A description of the failure
+    pub message: String,
+}
+
+impl BodyError {
+    fn new(code: BodyErrorCode, path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { code, path: path.into(), message: message.into() }
+    }
+
+    /// Converts a serde error for `body`, locating the failure by the position serde reports.
+    fn from_serde(body: &str, e: serde_json::Error) -> Self {
+        let code = match e.classify() {
+            serde_json::error::Category::Eof => BodyErrorCode::TruncatedJson,
+            serde_json::error::Category::Syntax | serde_json::error::Category::Io => BodyErrorCode::MalformedJson,
+            serde_json::error::Category::Data => BodyErrorCode::InvalidRequest,
+        };
+        let path = if e.line() == 0 { String::from("$") } else { scan_json(body, Some(offset_of(body, e.line(), e.column()))).path };
+        Self::new(code, path, e.to_string())
+    }
+}
+
+impl std::fmt::Display for BodyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at {}", self.message, self.path)
+    }
+}
+
+impl std::error::Error for BodyError {}
+
+/// Parses a raw JSON request body as `parse_request` does, without panicking or allocating beyond `limit` on
+/// hostile input. The size of the body is checked first, then its encoding and its nesting depth, before serde
+/// reads it.
+///
+/// The size of a body should also be checked against its `Content-Length` with `BodyLimits` before it is read, this
+/// check only covers bodies sent without one.
+///
+/// # Parameters
+/// `body`: The raw request body
+/// `mode`: Whether unknown fields are ignored or rejected
+/// `limit`: The maximum size of the body in bytes, from the `BodyLimits` of the route
+///
+/// # Returns
+/// A `Result<T, BodyError>`, `Ok(T)` if the body is a valid request, otherwise `Err(BodyError)` with the code and
+/// the JSON path of the failure.
+pub fn parse_body<T: DeserializeOwned + RequestFields>(body: &[u8], mode: InputMode, limit: usize) -> Result<T, BodyError> {
+    let body = check_body(body, limit)?;
+    // Serde would also accept the fields of a request as an array, which no client should send
+    if body.trim_start().starts_with(|c: char| c != '{') {
+        return Err(BodyError::new(BodyErrorCode::InvalidRequest, "$", "a request body must be a JSON object"));
+    }
+    if mode == InputMode::Strict {
+        let value: serde_json::Value = serde_json::from_str(body).map_err(|e| BodyError::from_serde(body, e))?;
+        check_unknown_fields::<T>(&value)?;
+    }
+    serde_json::from_str(body).map_err(|e| BodyError::from_serde(body, e))
+}
+
+/// Parses a raw JSON Merge Patch of a question as `parse_question_patch` does, with the checks of `parse_body`.
+///
+/// # Returns
+/// A `Result<UpdateQuestion, BodyError>`, `Ok(UpdateQuestion)` if the patch is valid for the mode, otherwise
+/// `Err(BodyError)` with the code and the JSON path of the failure.
+pub fn parse_question_patch_body(body: &[u8], mode: InputMode, limit: usize) -> Result<UpdateQuestion, BodyError> {
+    let body = check_body(body, limit)?;
+    let value: serde_json::Value = serde_json::from_str(body).map_err(|e| BodyError::from_serde(body, e))?;
+    let Some(patch) = value.as_object() else {
+        return Err(BodyError::new(BodyErrorCode::InvalidRequest, "$", "a merge patch of a question must be a JSON object"));
+    };
+    if let Some(field) = UpdateQuestion::FIELDS.iter().find(|field| patch.get(**field).is_some_and(serde_json::Value::is_null)) {
+        return Err(BodyError::new(BodyErrorCode::InvalidRequest, format!("$.{field}"), format!("field `{field}` cannot be removed, a question requires it")));
+    }
+    if mode == InputMode::Strict {
+        check_unknown_fields::<UpdateQuestion>(&value)?;
+    }
+    serde_json::from_str(body).map_err(|e| BodyError::from_serde(body, e))
+}
+
+/// Checks the size, encoding and nesting depth of a raw request body, returning it as text.
+fn check_body(body: &[u8], limit: usize) -> Result<&str, BodyError> {
+    if body.len() > limit {
+        return Err(BodyError::new(BodyErrorCode::BodyTooLarge, "$", format!("the body exceeds the limit of {limit} bytes")));
+    }
+    let body = std::str::from_utf8(body).map_err(|e| {
+        // The path is read from the valid prefix, which ends at a character boundary
+        let prefix = std::str::from_utf8(&body[..e.valid_up_to()]).unwrap_or_default();
+        BodyError::new(BodyErrorCode::InvalidUtf8, scan_json(prefix, None).path, format!("the body is not valid UTF-8 after byte {}", e.valid_up_to()))
+    })?;
+    let scan = scan_json(body, None);
+    if scan.too_deep {
+        return Err(BodyError::new(BodyErrorCode::NestingTooDeep, scan.path, format!("the body nests arrays or objects deeper than {MAX_JSON_DEPTH} levels")));
+    }
+    Ok(body)
+}
+
+/// Rejects the first field of an object not among the fields of `T`.
+fn check_unknown_fields<T: RequestFields>(value: &serde_json::Value) -> Result<(), BodyError> {
+    if let Some(field) = value.as_object().and_then(|object| object.keys().find(|key| !T::FIELDS.contains(&key.as_str()))) {
+        let message = serde_json::Error::unknown_field(field, T::FIELDS).to_string();
+        return Err(BodyError::new(BodyErrorCode::InvalidRequest, format!("$.{field}"), message));
+    }
+    Ok(())
+}
+
+/// Converts a 1-based line and column, as serde reports them, into a byte offset of `body`.
+fn offset_of(body: &str, line: usize, column: usize) -> usize {
+    let line_start = body.split_inclusive('\n').take(line.saturating_sub(1)).map(str::len).sum::<usize>();
+    line_start.saturating_add(column).min(body.len())
+}
+
+/// The outcome of scanning the structure of a JSON text.
+struct JsonScan {
+    /// The JSON path where the scan stopped
+    path: String,
+    /// Whether the scan stopped because the nesting exceeded `MAX_JSON_DEPTH`
+    too_deep: bool,
+}
+
+/// An array or object the scan is inside of, along with the key or index of the current element.
+enum Container {
+    Array(usize),
+    Object(Option<String>),
+}
+
+/// Scans the structure of a JSON text up to byte `until`, or to its end, without building its values, returning
+/// the JSON path at that position. Stops early once the nesting exceeds `MAX_JSON_DEPTH`. Malformed text is scanned
+/// as far as its brackets and strings allow, as serde reports the actual error.
+fn scan_json(text: &str, until: Option<usize>) -> JsonScan {
+    let bytes = &text.as_bytes()[..until.unwrap_or(text.len()).min(text.len())];
+    let mut stack: Vec<Container> = Vec::new();
+    // Whether the next string inside an object is a key rather than a value
+    let mut expect_key = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                let start = i + 1;
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                if expect_key {
+                    if let Some(Container::Object(key)) = stack.last_mut() {
+                        let raw = text.get(start..i.min(bytes.len())).unwrap_or_default();
+                        *key = Some(serde_json::from_str(&format!("\"{raw}\"")).unwrap_or_else(|_| raw.to_string()));
+                    }
+                    expect_key = false;
+                }
+            }
+            b'{' => {
+                stack.push(Container::Object(None));
+                expect_key = true;
+            }
+            b'[' => {
+                stack.push(Container::Array(0));
+            }
+            b'}' | b']' => {
+                stack.pop();
+                expect_key = false;
+            }
+            b',' => match stack.last_mut() {
+                Some(Container::Array(index)) => *index += 1,
+                Some(Container::Object(_)) => expect_key = true,
+                None => {}
+            },
+            _ => {}
+        }
+        if stack.len() > MAX_JSON_DEPTH {
+            // The path locates the array or object exceeding the limit
+            return JsonScan { path: json_path(&stack[..MAX_JSON_DEPTH]), too_deep: true };
+        }
+        i += 1;
+    }
+    JsonScan { path: json_path(&stack), too_deep: false }
+}
+
+/// Formats the JSON path of the current element of the innermost container.
+fn json_path(stack: &[Container]) -> String {
+    let mut path = String::from("$");
+    for container in stack {
+        match container {
+            Container::Array(index) => path.push_str(&format!("[{index}]")),
+            Container::Object(Some(key)) => path.push_str(&format!(".{key}")),
+            Container::Object(None) => {}
+        }
+    }
+    path
+}
+
+/// The bytes allowed in a request body besides its content, for field names, punctuation and short fields such as
+/// the content type.
+const BODY_OVERHEAD_BYTES: usize = 4096;
+
+/// The bytes a single character can take in a JSON string, written as an escaped surrogate pair.
+const MAX_ESCAPED_CHAR_BYTES: usize = 12;
+
+/// The maximum sizes, in bytes, of request bodies creating or editing content, checked before a body is read
+/// in full so that oversized bodies are rejected without allocating them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BodyLimits {
+    /// The maximum size of a body creating or editing a question
+    pub question: usize,
+    /// The maximum size of a body creating an answer
+    pub answer: usize,
+}
+
+impl BodyLimits {
+    /// Checks the size of a body creating or editing a question, such as its `Content-Length`.
+    ///
+    /// # Returns
+    /// A `Result<(), DbError>`, `Ok(())` if the body is within the limit, otherwise
+    /// `ValidationReason::LimitExceeded` carrying the limit in bytes.
+    pub fn check_question(&self, size: usize) -> Result<(), DbError> {
+        check_body_size(size, self.question)
+    }
+
+    /// Checks the size of a body creating an answer, see `check_question`.
+    pub fn check_answer(&self, size: usize) -> Result<(), DbError> {
+        check_body_size(size, self.answer)
+    }
+}
+
+/// Derives the body limits from the content limits, allowing every character of the longest valid content to be
+/// escaped, so that no body within the `ContentLimits` is rejected for its size.
+impl From<ContentLimits> for BodyLimits {
+    fn from(limits: ContentLimits) -> Self {
+        let bytes = |chars: usize| chars.saturating_mul(MAX_ESCAPED_CHAR_BYTES).saturating_add(BODY_OVERHEAD_BYTES);
+        Self {
+            question: bytes(limits.max_title.saturating_add(limits.max_question)),
+            answer: bytes(limits.max_answer),
+        }
+    }
+}
+
+impl Default for BodyLimits {
+    fn default() -> Self {
+        Self::from(ContentLimits::default())
+    }
+}
+
+/// Rejects a body of `size` bytes exceeding `limit` bytes.
+fn check_body_size(size: usize, limit: usize) -> Result<(), DbError> {
+    if size > limit {
+        return Err(ValidationReason::LimitExceeded { limit: u32::try_from(limit).unwrap_or(u32::MAX) }.into());
+    }
+    Ok(())
+}
+
+/// How counts are represented in JSON responses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CountFormat {
+    /// Counts are always JSON numbers
+    #[default]
+    Number,
+    /// Counts larger than JavaScript can represent exactly are JSON strings, smaller ones remain numbers
+    StringWhenUnsafe,
+}
+
+/// The largest magnitude a JSON number can have while remaining exact as a JavaScript number, 2^53 - 1.
+pub const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// Serializes a response to JSON, formatting its integers as `format` describes.
+///
+/// # Parameters
+/// `value`: The response being serialized
+/// `format`: Whether integers beyond `MAX_SAFE_INTEGER` are written as numbers or strings
+///
+/// # Returns
+/// A `Result<serde_json::Value, serde_json::Error>`, `Ok(serde_json::Value)` in the successful case,
+/// otherwise `Err(serde_json::Error)` if `value` cannot be serialized.
+pub fn to_json<T: Serialize>(value: &T, format: CountFormat) -> Result<serde_json::Value, serde_json::Error> {
+    let mut json = serde_json::to_value(value)?;
+    if format == CountFormat::StringWhenUnsafe {
+        stringify_unsafe_integers(&mut json);
+    }
+    Ok(json)
+}
+
+/// Replaces every integer in `json` beyond `MAX_SAFE_INTEGER` with its decimal string.
+fn stringify_unsafe_integers(json: &mut serde_json::Value) {
+    match json {
+        serde_json::Value::Number(number) => {
+            let unsafe_integer = number.as_i64().map(|n| n.unsigned_abs() > MAX_SAFE_INTEGER)
+                .or_else(|| number.as_u64().map(|n| n > MAX_SAFE_INTEGER))
+                .unwrap_or(false);
+            if unsafe_integer {
+                *json = serde_json::Value::String(number.to_string());
+            }
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(stringify_unsafe_integers),
+        serde_json::Value::Object(fields) => fields.values_mut().for_each(stringify_unsafe_integers),
+        _ => {}
+    }
+}
+
+/// The representations a listing of responses can be returned in, chosen from the `Accept` header of a request.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ListFormat {
+    /// A JSON array
+    #[default]
+    Json,
+    /// Comma separated values with a header row, downloaded as a file
+    Csv,
+    /// Newline delimited JSON, one response per line
+    Ndjson,
+}
+
+impl ListFormat {
+    /// The media types of the formats, in order of preference when a request accepts several equally.
+    pub const MEDIA_TYPES: [&'static str; 3] = ["application/json", "text/csv", "application/x-ndjson"];
+
+    /// The media type of the format, for the `Content-Type` header of the response.
+    pub fn media_type(&self) -> &'static str {
+        match self {
+            ListFormat::Json => "application/json",
+            ListFormat::Csv => "text/csv",
+            ListFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    /// The `Content-Disposition` header of the response, naming the file `name` is downloaded as. Only CSV is
+    /// downloaded, so the other formats have none.
+    pub fn content_disposition(&self, name: &str) -> Option<String> {
+        match self {
+            ListFormat::Csv => Some(format!("attachment; filename=\"{name}.csv\"")),
+            ListFormat::Json | ListFormat::Ndjson => None,
+        }
+    }
+
+    /// Chooses the format of a response from the `Accept` header of the request.
+    ///
+    /// The supported media type with the highest quality wins, ties going to the earliest in `MEDIA_TYPES`.
+    /// Wildcards match every supported type they cover, and a missing or empty header accepts JSON.
+    ///
+    /// # Parameters
+    /// `accept`: The value of the `Accept` header, if the request has one
+    ///
+    /// # Returns
+    /// A `Result<ListFormat, DbError>`, `Ok(ListFormat)` in the successful case, otherwise `Err(DbError::Validation)`
+    /// listing the supported media types if none of them are acceptable.
+    pub fn negotiate(accept: Option<&str>) -> Result<ListFormat, DbError> {
+        let accept = match accept.map(str::trim) {
+            None | Some("") => return Ok(ListFormat::Json),
+            Some(accept) => accept,
+        };
+        let ranges: Vec<(String, f32)> = accept
+            .split(',')
+            .map(|range| {
+                let mut params = range.split(';').map(str::trim);
+                let media_range = params.next().unwrap_or_default().to_ascii_lowercase();
+                let quality = params
+                    .filter_map(|param| param.strip_prefix("q="))
+                    .find_map(|q| q.parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                (media_range, quality)
+            })
+            .collect();
+        let mut best: Option<(ListFormat, f32)> = None;
+        for format in [ListFormat::Json, ListFormat::Csv, ListFormat::Ndjson] {
+            // The most specific range matching the format decides its quality, so `text/csv;q=0, */*` excludes CSV
+            let media_type = format.media_type();
+            let (kind, _) = media_type.split_once('/').expect("media types have a subtype");
+            let quality = [media_type.to_string(), format!("{kind}/*"), String::from("*/*")]
+                .iter()
+                .find_map(|candidate| ranges.iter().find(|(range, _)| range == candidate).map(|&(_, q)| q));
+            if let Some(quality) = quality.filter(|&q| q > 0.0) {
+                if best.is_none_or(|(_, q)| quality > q) {
+                    best = Some((format, quality));
+                }
+            }
+        }
+        best.map(|(format, _)| format).ok_or_else(|| {
+            DbError::validation(format!("unsupported media type `{accept}`, expected one of: {}", ListFormat::MEDIA_TYPES.join(", ")))
+        })
+    }
+}
+
+/// The header row of a CSV listing of questions.
+pub const QUESTION_CSV_HEADER: [&str; 11] = [
+    "id", "serial", "title", "question", "likes", "views", "created_at", "updated_at", "pinned_at", "pinned_answer_id", "content_type",
+];
+
+/// Quotes a CSV field if it contains a separator, a quote or a line break, doubling its quotes.
+fn csv_field(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+/// Writes one CSV record terminated by CRLF.
+fn write_csv_record<W: Write>(writer: &mut W, fields: &[&str]) -> io::Result<()> {
+    let record: Vec<_> = fields.iter().map(|field| csv_field(field)).collect();
+    write!(writer, "{}\r\n", record.join(","))
+}
+
+/// Writes questions as CSV, with a header row followed by one row per question. Fields are quoted as RFC 4180
+/// describes, and missing values are left empty.
+///
+/// Rows are written as `questions` yields them, so a listing can be written as it is read rather than buffered.
+///
+/// # Parameters
+/// `writer`: Where the CSV is written
+/// `questions`: The questions listed
+///
+/// # Returns
+/// A `Result<(), io::Error>`, `Ok(())` in the successful case, otherwise `Err(io::Error)` if writing fails.
+pub fn write_questions_csv<W, I>(mut writer: W, questions: I) -> io::Result<()>
+where
+    W: Write,
+    I: IntoIterator<Item = QuestionResponse>,
+{
+    write_csv_record(&mut writer, &QUESTION_CSV_HEADER)?;
+    for question in questions {
+        let serial = question.serial.map(|serial| serial.to_string()).unwrap_or_default();
+        write_csv_record(&mut writer, &[
+            &question.id,
+            &serial,
+            &question.title,
+            &question.question,
+            &question.likes.to_string(),
+            &question.views.to_string(),
+            &question.created_at,
+            question.updated_at.as_deref().unwrap_or_default(),
+            question.pinned_at.as_deref().unwrap_or_default(),
+            question.pinned_answer_id.as_deref().unwrap_or_default(),
+            question.content_type.as_str(),
+        ])?;
+    }
+    writer.flush()
+}
+
+/// Writes responses as newline delimited JSON, one compact JSON object per line, formatting integers as `format`
+/// describes.
+///
+/// Lines are written as `values` yields them, so a listing can be written as it is read rather than buffered.
+///
+/// # Parameters
+/// `writer`: Where the lines are written
+/// `values`: The responses listed
+/// `format`: Whether integers beyond `MAX_SAFE_INTEGER` are written as numbers or strings
+///
+/// # Returns
+/// A `Result<(), io::Error>`, `Ok(())` in the successful case, otherwise `Err(io::Error)` if a response cannot be
+/// serialized or writing fails.
+pub fn write_ndjson<W, I, T>(mut writer: W, values: I, format: CountFormat) -> io::Result<()>
+where
+    W: Write,
+    I: IntoIterator<Item = T>,
+    T: Serialize,
+{
+    for value in values {
+        serde_json::to_writer(&mut writer, &to_json(&value, format)?)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()
+}
+
+/// How the fields of responses are named in JSON.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FieldCase {
+    /// `snake_case`, as the responses declare their fields
+    #[default]
+    Snake,
+    /// `camelCase`, as JavaScript clients expect
+    Camel,
+}
+
+impl FieldCase {
+    /// Renames every field of the objects in `json` to this case, leaving the values themselves unchanged.
+    pub fn apply(&self, json: &mut serde_json::Value) {
+        if *self == FieldCase::Snake {
+            return;
+        }
+        match json {
+            serde_json::Value::Array(values) => values.iter_mut().for_each(|value| self.apply(value)),
+            serde_json::Value::Object(fields) => {
+                *fields = std::mem::take(fields)
+                    .into_iter()
+                    .map(|(field, mut value)| {
+                        self.apply(&mut value);
+                        (camel_case(&field), value)
+                    })
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+
+    /// Serializes a response to JSON, naming its fields in this case and formatting its integers as `format`
+    /// describes, see `to_json`.
+    pub fn to_json<T: Serialize>(&self, value: &T, format: CountFormat) -> Result<serde_json::Value, serde_json::Error> {
+        let mut json = to_json(value, format)?;
+        self.apply(&mut json);
+        Ok(json)
+    }
+}
+
+/// Converts a `snake_case` field name to `camelCase`.
+fn camel_case(field: &str) -> String {
+    let mut words = field.split('_');
+    let mut camel = String::from(words.next().unwrap_or_default());
+    for word in words {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            camel.extend(first.to_uppercase());
+            camel.push_str(chars.as_str());
+        }
+    }
+    camel
+}
+
+/// Defines a module of wrappers serializing the responses with their fields named in one case.
+macro_rules! cased_responses {
+    ($(#[$doc:meta])* $module:ident, $case:ident) => {
+        $(#[$doc])*
+        pub mod $module {
+            use serde::{Serialize, Serializer};
+            use super::FieldCase;
+
+            /// Serializes the wrapped response with its fields named in the case of this module.
+            #[derive(Debug, Clone, Copy)]
+            pub struct Json<'a, T>(pub &'a T);
+
+            impl<'a, T> From<&'a T> for Json<'a, T> {
+                fn from(response: &'a T) -> Self {
+                    Self(response)
+                }
+            }
+
+            impl<T: Serialize> Serialize for Json<'_, T> {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    if FieldCase::$case == FieldCase::Snake {
+                        return self.0.serialize(serializer);
+                    }
+                    let mut json = serde_json::to_value(self.0).map_err(serde::ser::Error::custom)?;
+                    FieldCase::$case.apply(&mut json);
+                    json.serialize(serializer)
+                }
+            }
+
+            /// A `QuestionResponse` serialized in the case of this module.
+            pub type QuestionResponse<'a> = Json<'a, super::QuestionResponse>;
+            /// An `AnswerResponse` serialized in the case of this module.
+            pub type AnswerResponse<'a> = Json<'a, super::AnswerResponse>;
+            /// A `QuestionDetailResponse` serialized in the case of this module.
+            pub type QuestionDetailResponse<'a> = Json<'a, super::QuestionDetailResponse>;
+            /// A `QuestionHeaderResponse` serialized in the case of this module.
+            pub type QuestionHeaderResponse<'a> = Json<'a, super::QuestionHeaderResponse>;
+        }
+    };
+}
+
+cased_responses!(
+    /// Contains the responses serialized with `snake_case` fields, as they are declared.
+    snake, Snake
+);
+
+cased_responses!(
+    /// Contains the responses serialized with `camelCase` fields, such as `createdAt` and `questionId`.
+    camel, Camel
+);
@@ -0,0 +1,176 @@
+//! Contains `LegacyDbError`, the variants `DbError` had before it was classified independently of the database
+//! backend, for callers that still construct or match on them.
+//!
+//! A `LegacyDbError` converts into the `DbError` of the same classification, and a `DbError` converts back into the
+//! legacy variant it would have been reported as. The driver error a legacy variant wraps is recovered from the
+//! `source` of the `DbError` where it is a `sqlx::Error`, so callers can migrate one match at a time.
+//!
+//! # Example
+//! ```
+//! #![allow(deprecated)]
+//! use question_answer::models::DbError;
+//! use question_answer::models::legacy::LegacyDbError;
+//!
+//! let error = DbError::validation("title is too long");
+//! let LegacyDbError::Validation(message) = LegacyDbError::from(error) else { unreachable!() };
+//! assert_eq!(message, "title is too long");
+//! ```
+
+#![allow(deprecated)]
+
+use chrono::Duration;
+use sqlx::error::Error;
+use sqlx::types::Uuid;
+use super::{BoxError, ConflictReason, DbError, InternalReason, UnavailableReason, ValidationReason};
+
+/// The errors returned by the data access objects before `DbError` was classified independently of the database
+/// backend, see the module documentation.
+#[deprecated(note = "match on the variants and reasons of `DbError` instead")]
+#[derive(Debug)]
+pub enum LegacyDbError {
+    /// An entity could not be inserted
+    Creation(Error),
+    /// The requested entity does not exist
+    NotFound(Error),
+    /// An id could not be parsed as a `Uuid`
+    InvalidUuid(&'static str),
+    /// The database could not be reached or queried
+    Access(Error),
+    /// A connection to the database could not be established
+    Connection(Error),
+    /// A row could not be decoded into a model
+    FromRow(Error),
+    /// An entity could not be deleted
+    Deletion(Error),
+    /// An entity could not be updated
+    Update(Error),
+    /// A transaction could not be committed
+    Commit(Error),
+    /// A batch operation failed part way through
+    PartialBatch {
+        /// The number of rows affected by the batches that were committed
+        completed: u64,
+        /// The error that stopped the operation
+        error: Box<LegacyDbError>,
+    },
+    /// The input was rejected before reaching the database
+    Validation(String),
+    /// The operation conflicts with the current state of the database
+    Conflict(String),
+    /// The content was rejected by the `ContentPolicy` of the data access object, for the given reason
+    PolicyViolation(String),
+    /// The question with the given id is locked
+    Locked(Uuid),
+    /// The answer with the given id is a reply, which cannot be replied to
+    NestedReply(Uuid),
+    /// The operation would exceed a configured limit
+    LimitExceeded {
+        /// The limit that would be exceeded
+        limit: u32,
+    },
+    /// The author has created as many entities as their `CreationQuota` allows within the last hour
+    QuotaExceeded {
+        /// How long until the author can create another entity
+        retry_after: Duration,
+    },
+    /// The entity cannot be deleted while other entities depend on it
+    HasDependents {
+        /// The number of dependent entities
+        count: i64,
+    },
+    /// The database schema is older than the schema the crate was built against
+    SchemaMismatch {
+        /// The version of the latest migration the crate was built with
+        expected: i64,
+        /// The version of the latest migration applied to the database, zero if none were
+        found: i64,
+        /// The names of the migrations that have not been applied, oldest first
+        missing: Vec<String>,
+    },
+}
+
+impl From<LegacyDbError> for DbError {
+    fn from(error: LegacyDbError) -> Self {
+        match error {
+            LegacyDbError::Creation(e) => DbError::creation(e),
+            // A legacy `NotFound` is not found whatever driver error it wraps
+            LegacyDbError::NotFound(e) => DbError::NotFound { entity: "entity", id: None, source: Some(Box::new(e)) },
+            LegacyDbError::InvalidUuid(reason) => DbError::invalid_id(reason),
+            LegacyDbError::Access(e) => DbError::from(e),
+            LegacyDbError::Connection(e) => DbError::connection(e),
+            LegacyDbError::FromRow(e) => DbError::from_row(e),
+            LegacyDbError::Deletion(e) => DbError::deletion(e),
+            LegacyDbError::Update(e) => DbError::update(e),
+            LegacyDbError::Commit(e) => DbError::commit(e),
+            LegacyDbError::PartialBatch { completed, error } => DbError::from(*error).stopped_after(completed),
+            LegacyDbError::Validation(message) => DbError::validation(message),
+            LegacyDbError::Conflict(message) => DbError::conflict(message),
+            LegacyDbError::PolicyViolation(reason) => ValidationReason::PolicyViolation(reason).into(),
+            LegacyDbError::Locked(id) => ConflictReason::Locked(id).into(),
+            LegacyDbError::NestedReply(id) => ValidationReason::NestedReply(id).into(),
+            LegacyDbError::LimitExceeded { limit } => ValidationReason::LimitExceeded { limit }.into(),
+            LegacyDbError::QuotaExceeded { retry_after } => UnavailableReason::QuotaExceeded { retry_after }.into(),
+            LegacyDbError::HasDependents { count } => ConflictReason::HasDependents { count }.into(),
+            LegacyDbError::SchemaMismatch { expected, found, missing } => {
+                InternalReason::SchemaMismatch { expected, found, missing }.into()
+            }
+        }
+    }
+}
+
+impl From<DbError> for LegacyDbError {
+    fn from(mut error: DbError) -> Self {
+        if let Some(completed) = error.take_partial_batch() {
+            return LegacyDbError::PartialBatch { completed, error: Box::new(LegacyDbError::from(error)) };
+        }
+        let message = error.to_string();
+        match error {
+            DbError::NotFound { source, .. } => LegacyDbError::NotFound(driver_error(source, || Error::RowNotFound)),
+            DbError::InvalidId { reason, .. } => LegacyDbError::InvalidUuid(reason),
+            DbError::Conflict { reason, source } => match reason {
+                ConflictReason::State(message) => LegacyDbError::Conflict(message),
+                ConflictReason::Locked(id) => LegacyDbError::Locked(id),
+                ConflictReason::HasDependents { count } => LegacyDbError::HasDependents { count },
+                ConflictReason::Constraint => LegacyDbError::Access(driver_error(source, || Error::Protocol(message))),
+            },
+            DbError::Validation { reason, source } => match reason {
+                ValidationReason::Input(message) => LegacyDbError::Validation(message),
+                ValidationReason::PolicyViolation(reason) => LegacyDbError::PolicyViolation(reason),
+                ValidationReason::NestedReply(id) => LegacyDbError::NestedReply(id),
+                ValidationReason::LimitExceeded { limit } => LegacyDbError::LimitExceeded { limit },
+                ValidationReason::Constraint => LegacyDbError::Access(driver_error(source, || Error::Protocol(message))),
+            },
+            DbError::Unavailable { reason, source } => match reason {
+                UnavailableReason::QuotaExceeded { retry_after } => LegacyDbError::QuotaExceeded { retry_after },
+                UnavailableReason::Transient | UnavailableReason::Unreachable => {
+                    LegacyDbError::Connection(driver_error(source, || Error::Protocol(message)))
+                }
+            },
+            DbError::Internal { reason, source } => {
+                let e = || Error::Protocol(message);
+                match reason {
+                    InternalReason::Query => LegacyDbError::Access(driver_error(source, e)),
+                    InternalReason::Connect => LegacyDbError::Connection(driver_error(source, e)),
+                    InternalReason::Create => LegacyDbError::Creation(driver_error(source, e)),
+                    InternalReason::Decode => LegacyDbError::FromRow(driver_error(source, e)),
+                    InternalReason::Update => LegacyDbError::Update(driver_error(source, e)),
+                    InternalReason::Delete => LegacyDbError::Deletion(driver_error(source, e)),
+                    InternalReason::Commit => LegacyDbError::Commit(driver_error(source, e)),
+                    InternalReason::SchemaMismatch { expected, found, missing } => {
+                        LegacyDbError::SchemaMismatch { expected, found, missing }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Recovers the driver error a legacy variant wraps from the `source` of a `DbError`. A source of another backend is
+/// wrapped as `sqlx::Error::AnyDriverError`, and a missing source is made by `otherwise`.
+fn driver_error(source: Option<BoxError>, otherwise: impl FnOnce() -> Error) -> Error {
+    match source.map(|source| source.downcast::<Error>()) {
+        Some(Ok(e)) => *e,
+        Some(Err(source)) => Error::AnyDriverError(source),
+        None => otherwise(),
+    }
+}
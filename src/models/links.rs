@@ -0,0 +1,191 @@
+//! Contains `PageLinks`, the links to the pages around a page of a listing, and `CursorSigner`, which makes the
+//! cursors of keyset paged listings opaque tokens that clients can pass back but not forge.
+//!
+//! A handler builds the `PageLinks` of the page it returns from the path and query of the request, and reports them
+//! as an RFC 8288 `Link` header, along with an `X-Total-Count` header for offset paged listings. The link to the next
+//! page of a keyset paged listing carries a token signed with HMAC-SHA256, so a token that was altered, made with
+//! another key or is older than the signer's maximum age is rejected with `DbError::Validation`, which a handler maps
+//! to a 400, before it reaches a query.
+
+use std::sync::Arc;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use crate::clock::{Clock, SystemClock};
+use super::{DbError, Page, PageRequest};
+
+/// How long a signed cursor is accepted after it was issued, unless configured otherwise.
+pub const DEFAULT_CURSOR_MAX_AGE_HOURS: i64 = 24;
+
+/// The query parameter holding the signed cursor of a keyset paged listing.
+pub const CURSOR_PARAM: &str = "cursor";
+
+/// The query parameter holding the offset of an offset paged listing.
+pub const OFFSET_PARAM: &str = "offset";
+
+/// The query parameter holding the page size of an offset paged listing.
+pub const LIMIT_PARAM: &str = "limit";
+
+/// The signed content of a token.
+#[derive(Serialize, Deserialize)]
+struct Payload<C> {
+    cursor: C,
+    issued_at: DateTime<Utc>,
+}
+
+/// Signs the cursors of keyset paged listings into URL-safe tokens and verifies the tokens clients pass back.
+///
+/// A token is the URL-safe base64 of the cursor and the time it was issued, followed by a `.` and the URL-safe base64
+/// of their HMAC-SHA256, without padding.
+pub struct CursorSigner {
+    key: Vec<u8>,
+    max_age: Duration,
+    clock: Arc<dyn Clock>,
+}
+
+impl CursorSigner {
+    /// Creates a signer with the secret `key`, accepting tokens for `DEFAULT_CURSOR_MAX_AGE_HOURS` using the system
+    /// clock. Every instance serving a listing must share the key.
+    pub fn new(key: impl Into<Vec<u8>>) -> Self {
+        Self { key: key.into(), max_age: Duration::hours(DEFAULT_CURSOR_MAX_AGE_HOURS), clock: Arc::new(SystemClock) }
+    }
+
+    /// Sets how long a token is accepted after it was issued.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Sets the `Clock` used to issue tokens and check their age.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Signs a cursor into a token, issued now.
+    ///
+    /// # Parameters
+    /// `cursor`: The position in the listing, such as the creation time of the last item of a page
+    ///
+    /// # Returns
+    /// The URL-safe token, which `verify` turns back into the cursor.
+    pub fn sign<C: Serialize>(&self, cursor: &C) -> String {
+        let payload = Payload { cursor, issued_at: self.clock.now() };
+        let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&payload).expect("cursor should serialize to JSON"));
+        let signature = URL_SAFE_NO_PAD.encode(self.mac(&payload).finalize().into_bytes());
+        format!("{payload}.{signature}")
+    }
+
+    /// Verifies a token made by `sign`, returning its cursor.
+    ///
+    /// # Parameters
+    /// `token`: The token, as passed back by the client
+    ///
+    /// # Returns
+    /// A `Result<C, DbError>`, `Ok(C)` with the cursor if the token was signed with the key and is no older than the
+    /// maximum age, otherwise `Err(DbError::Validation)` describing whether it is malformed, forged or expired.
+    pub fn verify<C: DeserializeOwned>(&self, token: &str) -> Result<C, DbError> {
+        let malformed = || DbError::validation(String::from("cursor is malformed"));
+        let (payload, signature) = token.split_once('.').ok_or_else(malformed)?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| malformed())?;
+        // The signature is checked before the payload is decoded, so nothing a client made up is parsed
+        self.mac(payload)
+            .verify_slice(&signature)
+            .map_err(|_| DbError::validation(String::from("cursor signature is invalid")))?;
+        let payload = URL_SAFE_NO_PAD.decode(payload).map_err(|_| malformed())?;
+        let payload: Payload<C> = serde_json::from_slice(&payload).map_err(|_| malformed())?;
+        if self.clock.now() - payload.issued_at > self.max_age {
+            return Err(DbError::validation(String::from("cursor has expired")));
+        }
+        Ok(payload.cursor)
+    }
+
+    /// The HMAC of an encoded payload.
+    fn mac(&self, payload: &str) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC should accept a key of any length");
+        mac.update(payload.as_bytes());
+        mac
+    }
+}
+
+/// The links to the pages around a page of a listing, relative to the path of the request the page was listed for.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PageLinks {
+    /// The path and query of the next page, `None` on the last page
+    pub next: Option<String>,
+    /// The path and query of the previous page, `None` on the first page and for keyset paged listings
+    pub prev: Option<String>,
+    /// The number of items of the listing, only known for offset paged listings
+    pub total_count: Option<u64>,
+}
+
+impl PageLinks {
+    /// Links the pages around a page of an offset paged listing.
+    ///
+    /// # Parameters
+    /// `target`: The path and query of the request, whose other query parameters the links keep
+    /// `request`: The `PageRequest` the page was listed with
+    /// `page`: The page
+    /// `total_count`: The number of items of the listing
+    ///
+    /// # Returns
+    /// The `PageLinks`, with a next link if the page has items after it and a previous link unless it is the first.
+    pub fn offset<T>(target: &str, request: PageRequest, page: &Page<T>, total_count: u64) -> Self {
+        let link = |offset: u32| {
+            with_query_params(target, &[(OFFSET_PARAM, offset.to_string()), (LIMIT_PARAM, request.limit.to_string())])
+        };
+        Self {
+            next: page.has_more.then(|| link(request.offset + page.items.len() as u32)),
+            prev: (request.offset > 0).then(|| link(request.offset.saturating_sub(request.limit))),
+            total_count: Some(total_count),
+        }
+    }
+
+    /// Links the page after a page of a keyset paged listing, whose position is passed as a signed cursor.
+    ///
+    /// # Parameters
+    /// `target`: The path and query of the request, whose other query parameters the link keeps
+    /// `page`: The page
+    /// `signer`: The `CursorSigner` signing the cursor of the next page
+    /// `cursor`: The cursor after an item, from which the next page is listed
+    ///
+    /// # Returns
+    /// The `PageLinks`, with a next link if the page has items after it.
+    pub fn keyset<T, C: Serialize>(target: &str, page: &Page<T>, signer: &CursorSigner, cursor: impl Fn(&T) -> C) -> Self {
+        let next = page.items.last().filter(|_| page.has_more).map(|last| {
+            with_query_params(target, &[(CURSOR_PARAM, signer.sign(&cursor(last)))])
+        });
+        Self { next, prev: None, total_count: None }
+    }
+
+    /// The value of the RFC 8288 `Link` header, `None` if there is no page to link to.
+    pub fn link_header(&self) -> Option<String> {
+        let links: Vec<String> = [("next", &self.next), ("prev", &self.prev)]
+            .into_iter()
+            .filter_map(|(rel, link)| link.as_ref().map(|link| format!("<{link}>; rel=\"{rel}\"")))
+            .collect();
+        (!links.is_empty()).then(|| links.join(", "))
+    }
+
+    /// The value of the `X-Total-Count` header, `None` for keyset paged listings.
+    pub fn total_count_header(&self) -> Option<String> {
+        self.total_count.map(|count| count.to_string())
+    }
+}
+
+/// Sets query parameters of a path and query, replacing any parameters of the same names and keeping the others in
+/// order. The values must be URL-safe, as numbers and signed cursors are.
+fn with_query_params(target: &str, params: &[(&str, String)]) -> String {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let kept = query.split('&').filter(|pair| {
+        let name = pair.split_once('=').map_or(*pair, |(name, _)| name);
+        !pair.is_empty() && params.iter().all(|(param, _)| *param != name)
+    });
+    let set = params.iter().map(|(name, value)| format!("{name}={value}"));
+    let query: Vec<String> = kept.map(String::from).chain(set).collect();
+    format!("{path}?{}", query.join("&"))
+}
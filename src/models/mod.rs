@@ -1,31 +1,190 @@
 //! Contains the structs that model the databases.
 
+use std::collections::HashMap;
 use std::fmt::Display;
 use std::convert::TryInto;
-use serde::{Serialize, Deserialize};
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
 use sqlx::types::Uuid;
 use sqlx::FromRow;
 // use sqlx::uuid
 use sqlx::error::Error;
-use chrono::{DateTime, Utc};
-use serde_json::ser::Formatter;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 
+pub mod content_stats;
+pub mod diff;
+pub mod dto;
+pub mod legacy;
+pub mod links;
+pub mod normalize;
+pub mod period;
+pub mod policy;
+#[cfg(feature = "render")]
+pub mod render;
+#[cfg(test)]
+mod row_compat;
+#[cfg(test)]
+mod test;
 
+/// The model types needed to use the data access objects.
 pub mod prelude {
-    pub use super::*;
+    pub use super::{
+        ActivityCursor, ActivityItem, AnonymizeReport, Answer, AnswerBodyMode, AnswerPage, AnswerSort, AnswerThread, AnswerVote,
+        AnswerWithAuthor, AnswerWithQuestion, ArchiveReport, BatchProgress, BoxError, BulkUpdate, Category, CategoryNode,
+        ConflictReason, ContentLimits, ContentType, CreateOutcome, CreationQuota, DailyActivity, DbError, DbErrorContext,
+        DbErrorKind, DeletePolicy, DetailOptions, EntityId, EntityIdKind, ImportRecord, ImportVerdict, InternalReason,
+        LatencyStats, LikableEntity, LikeAction, LikeBatchReport, LikeEvent, LikeOp, LikeOutcome, LikeTarget, LinkKind,
+        MergeReport, ModerationMode, NewAnswer, NewCategory, NewQuestion, NewTranslation, Page, PageRequest, PartialBatch,
+        PurgeReport, Question, QuestionDetail, QuestionFields, QuestionHeader, QuestionLikeRate, QuestionPartial,
+        QuestionTranslation, QuestionUpdate, RankComponents, RankedQuestion, RecountReport, ReputationConfig, RetagReport,
+        SearchRankingConfig, Tag, TagAcceptance, TagStats, TagSuggestion, Totals, TransferReport, UnavailableReason,
+        UpdateQuestion, UpsertOutcome, UserReputation, ValidationReason, ViewOutcome, VoteOutcome, ANSWER_PREVIEW_CHARS,
+        DEFAULT_ANSWER_LIMIT, DEFAULT_EMBEDDED_ANSWERS, DEFAULT_MAX_PINNED, DEFAULT_VIEW_WINDOW_MINUTES, DELETED_CONTENT,
+        MAX_AUTHOR_TOKEN_LENGTH, MAX_BOUNTY, MAX_DELETED_REASON_LENGTH, MAX_LIKE_RATE_WINDOW_DAYS, MAX_PAGE_SIZE,
+        MAX_TAG_LENGTH, MIN_AUTHOR_TOKEN_LENGTH, MIN_BOUNTY, SUPPORTED_LANGUAGES,
+    };
 }
 
 /// A new question received from a request.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct NewQuestion {
     /// The title of the new question
+    #[serde(deserialize_with = "trimmed")]
     pub title: String,
     /// The content of the new question
+    #[serde(deserialize_with = "trimmed")]
     pub question: String,
+    /// The id of the question in the external system it is synced from, if any
+    #[serde(default)]
+    pub external_id: Option<String>,
+    /// Whether the content is plain text or markdown, `None` for plain text
+    #[serde(default)]
+    pub content_type: Option<ContentType>,
+    /// The client generated token identifying an anonymous author, which is never returned to clients
+    #[serde(default)]
+    pub author_token: Option<String>,
+}
+
+impl NewQuestion {
+    /// Ensures the title and content of the new question are within the given `ContentLimits`, and that the
+    /// author token, if any, is well formed.
+    pub fn validate(&self, limits: &ContentLimits) -> Result<(), DbError> {
+        check_length("title", &self.title, limits.max_title)?;
+        check_length("question", &self.question, limits.max_question)?;
+        self.content_type.as_ref().map_or(Ok(()), ContentType::validate)?;
+        self.author_token.as_deref().map_or(Ok(()), validate_author_token)
+    }
+}
+
+/// A partial edit of a question's content, fields that are `None` are left unchanged.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UpdateQuestion {
+    /// The new title of the question
+    pub title: Option<String>,
+    /// The new content of the question
+    pub question: Option<String>,
+}
+
+impl UpdateQuestion {
+    /// Ensures the edit changes something and that any new content is within the given `ContentLimits`.
+    pub fn validate(&self, limits: &ContentLimits) -> Result<(), DbError> {
+        if self.title.is_none() && self.question.is_none() {
+            return Err(DbError::validation(String::from("an update must change the title or the question")));
+        }
+        if let Some(title) = &self.title {
+            check_length("title", title, limits.max_title)?;
+        }
+        if let Some(question) = &self.question {
+            check_length("question", question, limits.max_question)?;
+        }
+        Ok(())
+    }
+}
+
+/// The BCP 47 language tags questions can be translated into, in their canonical case.
+pub const SUPPORTED_LANGUAGES: [&str; 20] = [
+    "ar", "de", "en", "en-GB", "en-US", "es", "fr", "hi", "it", "ja", "ko", "nl", "pl", "pt", "pt-BR", "ru", "tr", "uk",
+    "zh-Hans", "zh-Hant",
+];
+
+/// Checks a BCP 47 language tag against `SUPPORTED_LANGUAGES`, ignoring case as the standard does.
+///
+/// # Returns
+/// A `Result<&'static str, DbError>`, `Ok(&str)` with the tag in its canonical case if it is supported, otherwise
+/// `Err(DbError::Validation)`.
+pub fn normalize_language(lang: &str) -> Result<&'static str, DbError> {
+    SUPPORTED_LANGUAGES
+        .iter()
+        .find(|supported| supported.eq_ignore_ascii_case(lang))
+        .copied()
+        .ok_or_else(|| DbError::validation(format!("unsupported language `{lang}`, expected one of: {}", SUPPORTED_LANGUAGES.join(", "))))
+}
+
+/// The translated content of a question.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewTranslation {
+    /// The translated title of the question
+    pub title: String,
+    /// The translated content of the question
+    pub question: String,
+}
+
+impl NewTranslation {
+    /// Ensures the translated title and content are within the given `ContentLimits`.
+    pub fn validate(&self, limits: &ContentLimits) -> Result<(), DbError> {
+        check_length("title", &self.title, limits.max_title)?;
+        check_length("question", &self.question, limits.max_question)
+    }
+}
+
+/// A translation of a question into another language, as stored in `question_translations`.
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow)]
+pub struct QuestionTranslation {
+    /// The unique id of the translated question
+    question_id: Uuid,
+    /// The BCP 47 language tag of the translation
+    lang: String,
+    /// The translated title
+    title: String,
+    /// The translated content
+    question: String,
+    /// The timestamp the translation was last written
+    updated_at: DateTime<Utc>,
+}
+
+impl QuestionTranslation {
+    /// Creates a translation read along with its question.
+    pub(crate) fn new(question_id: Uuid, lang: String, title: String, question: String, updated_at: DateTime<Utc>) -> Self {
+        Self { question_id, lang, title, question, updated_at }
+    }
+
+    /// The unique id of the translated question.
+    pub fn question_id(&self) -> Uuid {
+        self.question_id
+    }
+
+    /// The BCP 47 language tag of the translation.
+    pub fn lang(&self) -> &str {
+        &self.lang
+    }
+
+    /// The translated title.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// The translated content.
+    pub fn question(&self) -> &str {
+        &self.question
+    }
+
+    /// The timestamp the translation was last written.
+    pub fn updated_at(&self) -> DateTime<Utc> {
+        self.updated_at
+    }
 }
 
 /// A question that has been successfully persisted in the database.
-#[derive(Debug, Serialize, FromRow)]
+#[derive(Debug, Clone, Serialize, FromRow)]
 pub struct Question {
     /// The unique id of the question
     id: Uuid,
@@ -34,36 +193,297 @@ pub struct Question {
     /// The content of the question
     question: String,
     /// The number of likes the question has received
-    likes: i32,
+    likes: i64,
     /// The timestamp as a string the question was created
     created_at: DateTime<Utc>,
     // tags: Vec<Option<>>
+    // The following fields are optional columns, they default when absent from a row
+    // so that binaries remain compatible with schemas that do not have them yet.
+    /// The timestamp the question was last updated, if ever
+    #[sqlx(default)]
+    updated_at: Option<DateTime<Utc>>,
+    /// The url friendly slug of the question
+    #[sqlx(default)]
+    slug: Option<String>,
+    /// The moderation status of the question
+    #[sqlx(default)]
+    status: Option<String>,
+    /// The number of times the question has been viewed
+    #[sqlx(default)]
+    views: i32,
+    /// The timestamp the question was locked against edits, if it is locked
+    #[sqlx(default)]
+    locked_at: Option<DateTime<Utc>>,
+    /// The timestamp the question was pinned to the top of listings, if it is pinned
+    #[sqlx(default)]
+    pinned_at: Option<DateTime<Utc>>,
+    /// The sequential secondary id of the question
+    #[sqlx(default)]
+    serial: Option<i64>,
+    /// The id of the question in the external system it is synced from
+    #[sqlx(default)]
+    external_id: Option<String>,
+    /// The id of the category the question belongs to, if it has been categorized
+    #[sqlx(default)]
+    category_id: Option<Uuid>,
+    /// The number of characters in the content of the question
+    #[sqlx(default)]
+    char_count: i32,
+    /// The number of words in the content of the question
+    #[sqlx(default)]
+    word_count: i32,
+    /// Whether the content of the question is plain text or markdown
+    #[sqlx(default)]
+    content_type: ContentType,
+    /// The unique id of the user who authored the question, `None` if it has not been attributed
+    #[sqlx(default)]
+    author_id: Option<Uuid>,
+    /// The unique id of the answer pinned above the others by the author of the question, if any
+    #[sqlx(default)]
+    pinned_answer_id: Option<Uuid>,
+    /// The unique id of the answer accepted by the author of the question, if any
+    #[sqlx(default)]
+    accepted_answer_id: Option<Uuid>,
+    /// The unique id of the accepted answer once it has been archived, which clears `accepted_answer_id`
+    #[sqlx(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    archived_accepted_answer_id: Option<Uuid>,
+    /// Whether the question was created with the author token of the caller, `None` unless a token was given
+    #[sqlx(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    is_mine: Option<bool>,
+    /// The points offered for answering the question, if it has a bounty
+    #[sqlx(default)]
+    bounty_amount: Option<i32>,
+    /// The timestamp the bounty of the question expires, if it has a bounty
+    #[sqlx(default)]
+    bounty_expires_at: Option<DateTime<Utc>>,
+    /// The language of the translation the title and content were replaced with, `None` for the original
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lang: Option<String>,
 }
 
 impl Question {
-    pub fn new(id: Uuid, title: String, question: String, likes: i32, created_at: DateTime<Utc>) -> Self {
+    /// Creates a question, with the optional fields left unset.
+    pub fn new(id: Uuid, title: String, question: String, likes: i64, created_at: DateTime<Utc>) -> Self {
         Self {
             id,
             title,
             question,
             likes,
-            created_at
+            created_at,
+            updated_at: None,
+            slug: None,
+            status: None,
+            views: 0,
+            locked_at: None,
+            pinned_at: None,
+            serial: None,
+            external_id: None,
+            category_id: None,
+            char_count: 0,
+            word_count: 0,
+            content_type: ContentType::default(),
+            author_id: None,
+            pinned_answer_id: None,
+            accepted_answer_id: None,
+            archived_accepted_answer_id: None,
+            is_mine: None,
+            bounty_amount: None,
+            bounty_expires_at: None,
+            lang: None,
         }
     }
-    pub fn builder() -> QuestionBuilder {
+    /// Replaces the content of the question, and the fields derived from it, with the content of an earlier
+    /// revision, leaving the other fields as they are.
+    pub(crate) fn with_revision(mut self, revision: QuestionRevision) -> Self {
+        self.title = revision.title;
+        self.question = revision.question;
+        self.content_type = revision.content_type;
+        self.char_count = revision.char_count;
+        self.word_count = revision.word_count;
+        self.updated_at = revision.updated_at;
+        self
+    }
+
+    /// Replaces the title and content of the question with a translation, leaving the other fields as they are.
+    pub(crate) fn with_translation(mut self, translation: QuestionTranslation) -> Self {
+        self.title = translation.title;
+        self.question = translation.question;
+        self.lang = Some(translation.lang);
+        self
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn builder() -> QuestionBuilder {
         QuestionBuilder::new()
     }
 
+    /// The unique id of the question.
     pub fn id(&self) -> Uuid {
         self.id
     }
+
+    /// The title of the question.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// The content of the question.
+    pub fn question(&self) -> &str {
+        &self.question
+    }
+
+    /// The number of likes the question has received.
+    pub fn likes(&self) -> i64 {
+        self.likes
+    }
+
+    /// The timestamp the question was created.
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    /// The timestamp the question was last updated, if ever.
+    pub fn updated_at(&self) -> Option<DateTime<Utc>> {
+        self.updated_at
+    }
+
+    /// The url friendly slug of the question, if it has one.
+    pub fn slug(&self) -> Option<&str> {
+        self.slug.as_deref()
+    }
+
+    /// The moderation status of the question, if it has one.
+    pub fn status(&self) -> Option<&str> {
+        self.status.as_deref()
+    }
+
+    /// The number of times the question has been viewed.
+    pub fn views(&self) -> i32 {
+        self.views
+    }
+
+    /// The timestamp the question was locked against edits, if it is locked.
+    pub fn locked_at(&self) -> Option<DateTime<Utc>> {
+        self.locked_at
+    }
+
+    /// The timestamp the question was pinned to the top of listings, if it is pinned.
+    pub fn pinned_at(&self) -> Option<DateTime<Utc>> {
+        self.pinned_at
+    }
+
+    /// Whether the question is pinned to the top of listings.
+    pub fn is_pinned(&self) -> bool {
+        self.pinned_at.is_some()
+    }
+
+    /// The sequential secondary id of the question, if the schema has one.
+    pub fn serial(&self) -> Option<i64> {
+        self.serial
+    }
+
+    /// The id of the question in the external system it is synced from, if any.
+    pub fn external_id(&self) -> Option<&str> {
+        self.external_id.as_deref()
+    }
+
+    /// The id of the category the question belongs to, if it has been categorized.
+    pub fn category_id(&self) -> Option<Uuid> {
+        self.category_id
+    }
+
+    /// The number of characters in the content of the question, see `ContentStats`.
+    pub fn char_count(&self) -> i32 {
+        self.char_count
+    }
+
+    /// The number of words in the content of the question, see `ContentStats`.
+    pub fn word_count(&self) -> i32 {
+        self.word_count
+    }
+
+    /// Whether the content of the question is plain text or markdown.
+    pub fn content_type(&self) -> ContentType {
+        self.content_type.clone()
+    }
+
+    /// The unique id of the user who authored the question, `None` if it has not been attributed.
+    pub fn author_id(&self) -> Option<Uuid> {
+        self.author_id
+    }
+
+    /// The unique id of the answer pinned above the others, if any.
+    pub fn pinned_answer_id(&self) -> Option<Uuid> {
+        self.pinned_answer_id
+    }
+
+    /// The unique id of the answer accepted by the author of the question, if any.
+    pub fn accepted_answer_id(&self) -> Option<Uuid> {
+        self.accepted_answer_id
+    }
+
+    /// The unique id of the accepted answer, if it has been archived along with the other answers of the question.
+    pub fn archived_accepted_answer_id(&self) -> Option<Uuid> {
+        self.archived_accepted_answer_id
+    }
+
+    /// Whether an answer to the question was accepted, whether or not it has since been archived.
+    pub fn has_accepted_answer(&self) -> bool {
+        self.accepted_answer_id.is_some() || self.archived_accepted_answer_id.is_some()
+    }
+
+    /// Whether the question was created with the caller's author token, `None` unless the caller gave a token.
+    pub fn is_mine(&self) -> Option<bool> {
+        self.is_mine
+    }
+
+    /// The points offered for answering the question, `None` if it has no bounty. A bounty may have expired
+    /// without having been cleared yet, see `bounty_remaining`.
+    pub fn bounty_amount(&self) -> Option<i32> {
+        self.bounty_amount
+    }
+
+    /// The timestamp the bounty of the question expires, `None` if it has no bounty.
+    pub fn bounty_expires_at(&self) -> Option<DateTime<Utc>> {
+        self.bounty_expires_at
+    }
+
+    /// The time remaining on the bounty of the question as of `now`, `None` if it has no bounty or it has expired.
+    pub fn bounty_remaining(&self, now: DateTime<Utc>) -> Option<Duration> {
+        self.bounty_expires_at.map(|expires_at| expires_at - now).filter(|remaining| *remaining > Duration::zero())
+    }
+
+    /// The language of the translation the title and content were read in, `None` if they are the original.
+    pub fn lang(&self) -> Option<&str> {
+        self.lang.as_deref()
+    }
+
+    /// Whether the title and content are a translation rather than the original, see `QuestionDao::get_translation`.
+    pub fn is_translation(&self) -> bool {
+        self.lang.is_some()
+    }
+
+    /// Renders the content of the question to HTML according to its `ContentType`, see `render::render_html`.
+    #[cfg(feature = "render")]
+    pub fn render_html(&self) -> String {
+        render::render_html(&self.question, self.content_type.clone())
+    }
+
+    /// Whether the question is locked, in which case its content cannot be edited but it can still be answered.
+    pub fn is_locked(&self) -> bool {
+        self.locked_at.is_some()
+    }
 }
 
-pub struct QuestionBuilder {
+#[allow(dead_code)]
+pub(crate) struct QuestionBuilder {
     id: Option<Uuid>,
     title: Option<String>,
     question: Option<String>,
-    likes: Option<i32>,
+    likes: Option<i64>,
     created_at: Option<DateTime<Utc>>,
 }
 
@@ -85,7 +505,134 @@ pub struct NewAnswer {
     /// The id of the question the new answer is responding to
     pub question_id: String,
     /// The content of the new answer
+    #[serde(deserialize_with = "trimmed")]
     pub answer: String,
+    /// The id of the user authoring the new answer, `None` for anonymous answers
+    #[serde(default)]
+    pub author_id: Option<String>,
+    /// Whether the content is plain text or markdown, `None` for plain text
+    #[serde(default)]
+    pub content_type: Option<ContentType>,
+    /// The client generated token identifying an anonymous author, which is never returned to clients
+    #[serde(default)]
+    pub author_token: Option<String>,
+    /// The id of the answer the new answer replies to, `None` for answers to the question itself. Replies cannot
+    /// be replied to
+    #[serde(default)]
+    pub parent_answer_id: Option<String>,
+}
+
+impl NewAnswer {
+    /// Ensures the content of the new answer is within the given `ContentLimits`, and that the author token, if
+    /// any, is well formed.
+    pub fn validate(&self, limits: &ContentLimits) -> Result<(), DbError> {
+        check_length("answer", &self.answer, limits.max_answer)?;
+        self.content_type.as_ref().map_or(Ok(()), ContentType::validate)?;
+        self.author_token.as_deref().map_or(Ok(()), validate_author_token)
+    }
+}
+
+/// The minimum number of characters in an author token.
+pub const MIN_AUTHOR_TOKEN_LENGTH: usize = 16;
+
+/// The maximum number of characters in an author token.
+pub const MAX_AUTHOR_TOKEN_LENGTH: usize = 64;
+
+/// Checks that `token` is a well formed author token, made of between `MIN_AUTHOR_TOKEN_LENGTH` and
+/// `MAX_AUTHOR_TOKEN_LENGTH` url safe characters: ASCII letters, digits, `-` and `_`.
+pub fn validate_author_token(token: &str) -> Result<(), DbError> {
+    if !(MIN_AUTHOR_TOKEN_LENGTH..=MAX_AUTHOR_TOKEN_LENGTH).contains(&token.len()) {
+        return Err(DbError::validation(format!(
+            "author token must be between {MIN_AUTHOR_TOKEN_LENGTH} and {MAX_AUTHOR_TOKEN_LENGTH} characters, got {}",
+            token.len()
+        )));
+    }
+    if !token.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_') {
+        return Err(DbError::validation(String::from("author token must only contain letters, digits, `-` and `_`")));
+    }
+    Ok(())
+}
+
+/// The fewest points a bounty can offer.
+pub const MIN_BOUNTY: i32 = 1;
+
+/// The most points a bounty can offer, matching the `CHECK` constraint in the database schema.
+pub const MAX_BOUNTY: i32 = 500;
+
+/// Checks that a bounty offers between `MIN_BOUNTY` and `MAX_BOUNTY` points and lasts a positive `duration`.
+pub fn validate_bounty(amount: i32, duration: Duration) -> Result<(), DbError> {
+    if !(MIN_BOUNTY..=MAX_BOUNTY).contains(&amount) {
+        return Err(DbError::validation(format!("bounty must be between {MIN_BOUNTY} and {MAX_BOUNTY} points, got {amount}")));
+    }
+    if duration <= Duration::zero() {
+        return Err(DbError::validation(String::from("bounty duration must be positive")));
+    }
+    Ok(())
+}
+
+/// The maximum number of characters allowed in the content of questions and answers.
+/// The defaults match the `CHECK` constraints in the database schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentLimits {
+    /// The maximum number of characters in a question title
+    pub max_title: usize,
+    /// The maximum number of characters in the content of a question
+    pub max_question: usize,
+    /// The maximum number of characters in the content of an answer
+    pub max_answer: usize,
+}
+
+impl Default for ContentLimits {
+    fn default() -> Self {
+        Self {
+            max_title: 300,
+            max_question: 30_000,
+            max_answer: 30_000,
+        }
+    }
+}
+
+/// The maximum number of questions and answers an anonymous author can create within an hour, identified by their
+/// author token. Creations without an author token are not limited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CreationQuota {
+    /// The maximum number of questions created with the same author token within an hour
+    pub max_questions_per_hour: u32,
+    /// The maximum number of answers created with the same author token within an hour
+    pub max_answers_per_hour: u32,
+}
+
+/// The reputation the author of a question or an answer gains when it is liked, or when the answer is accepted by the
+/// author of its question. Unliking and unaccepting take the reputation back, and content without an author changes no
+/// reputation. Pinning an answer earns nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReputationConfig {
+    /// The reputation gained each time a question is liked
+    pub question_liked: i32,
+    /// The reputation gained each time an answer is liked
+    pub answer_liked: i32,
+    /// The reputation gained when an answer is accepted
+    pub answer_accepted: i32,
+}
+
+impl Default for ReputationConfig {
+    fn default() -> Self {
+        Self { question_liked: 5, answer_liked: 10, answer_accepted: 15 }
+    }
+}
+
+/// Deserializes a string without its surrounding whitespace.
+fn trimmed<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Ok(String::deserialize(deserializer)?.trim().to_string())
+}
+
+/// Checks that `value` has at most `max` characters.
+pub(crate) fn check_length(field: &str, value: &str, max: usize) -> Result<(), DbError> {
+    let len = value.chars().count();
+    if len > max {
+        return Err(DbError::validation(format!("{field} must be at most {max} characters, got {len}")));
+    }
+    Ok(())
 }
 
 /// An answer that has been successfully persisted in the database.
@@ -93,62 +640,2147 @@ pub struct NewAnswer {
 pub struct Answer {
     /// The unique id of the answer
     id: Uuid,
-    /// The unique id of the associated question
-    question_id: Uuid,
-    /// The content of the answer
+    /// The unique id of the associated question, `None` once the question is deleted with `DeletePolicy::Orphan`
+    question_id: Option<Uuid>,
+    /// The content of the answer, empty when the query left it out, see `AnswerBodyMode::None`
+    #[sqlx(default)]
     answer: String,
     /// The number of likes the answer has received
-    likes: i32,
+    likes: i64,
     /// The timestamp the answer was created at as a string
-    created_at: DateTime<Utc>
+    created_at: DateTime<Utc>,
+    /// The unique id of the user who authored the answer, `None` for anonymous answers
+    #[sqlx(default)]
+    author_id: Option<Uuid>,
+    /// The sequential secondary id of the answer
+    #[sqlx(default)]
+    serial: Option<i64>,
+    /// Whether the answer is visible in listings, `None` for schemas without drafts
+    #[sqlx(default)]
+    published: Option<bool>,
+    /// The number of users who voted the answer helpful
+    #[sqlx(default)]
+    helpful_count: i64,
+    /// The number of users who voted the answer unhelpful
+    #[sqlx(default)]
+    unhelpful_count: i64,
+    /// The number of characters in the content of the answer
+    #[sqlx(default)]
+    char_count: i32,
+    /// The number of words in the content of the answer
+    #[sqlx(default)]
+    word_count: i32,
+    /// The timestamp a moderator approved the answer, `None` if it has not been approved
+    #[sqlx(default)]
+    approved_at: Option<DateTime<Utc>>,
+    /// Whether the content of the answer is plain text or markdown
+    #[sqlx(default)]
+    content_type: ContentType,
+    /// Whether the answer was created with the author token of the caller, `None` unless a token was given
+    #[sqlx(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    is_mine: Option<bool>,
+    /// The unique id of the answer this answer replies to, `None` for answers to the question itself
+    #[sqlx(default)]
+    parent_answer_id: Option<Uuid>,
+    /// The timestamp a moderator replaced the answer with a tombstone, `None` unless it is tombstoned
+    #[sqlx(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deleted_at: Option<DateTime<Utc>>,
+    /// Why the answer was tombstoned, if a reason was given
+    #[sqlx(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deleted_reason: Option<String>,
 }
 
-/// A struct that acts as a wrapper for all entity ID's in the models module.
-pub struct EntityId {
-    id: String,
+impl Answer {
+    /// The unique id of the answer.
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// The unique id of the associated question, `None` once the question is deleted with `DeletePolicy::Orphan`.
+    pub fn question_id(&self) -> Option<Uuid> {
+        self.question_id
+    }
+
+    /// The content of the answer.
+    pub fn answer(&self) -> &str {
+        &self.answer
+    }
+
+    /// The number of likes the answer has received.
+    pub fn likes(&self) -> i64 {
+        self.likes
+    }
+
+    /// The timestamp the answer was created.
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    /// The unique id of the user who authored the answer, `None` for anonymous answers.
+    pub fn author_id(&self) -> Option<Uuid> {
+        self.author_id
+    }
+
+    /// The sequential secondary id of the answer, if the schema has one.
+    pub fn serial(&self) -> Option<i64> {
+        self.serial
+    }
+
+    /// Whether the answer has been published, rather than being a draft only visible to its author.
+    pub fn is_published(&self) -> bool {
+        self.published.unwrap_or(true)
+    }
+
+    /// The number of users who voted the answer helpful.
+    pub fn helpful_count(&self) -> i64 {
+        self.helpful_count
+    }
+
+    /// The number of users who voted the answer unhelpful.
+    pub fn unhelpful_count(&self) -> i64 {
+        self.unhelpful_count
+    }
+
+    /// The score of the answer, the number of helpful votes minus the number of unhelpful votes.
+    pub fn score(&self) -> i64 {
+        self.helpful_count.saturating_sub(self.unhelpful_count)
+    }
+
+    /// The number of characters in the content of the answer, see `ContentStats`.
+    pub fn char_count(&self) -> i32 {
+        self.char_count
+    }
+
+    /// The number of words in the content of the answer, see `ContentStats`.
+    pub fn word_count(&self) -> i32 {
+        self.word_count
+    }
+
+    /// The timestamp the answer was approved, `None` if it has not been approved. Answers published while
+    /// moderation is off are approved when they are published.
+    pub fn approved_at(&self) -> Option<DateTime<Utc>> {
+        self.approved_at
+    }
+
+    /// Whether the content of the answer is plain text or markdown.
+    pub fn content_type(&self) -> ContentType {
+        self.content_type.clone()
+    }
+
+    /// Whether the answer was created with the caller's author token, `None` unless the caller gave a token.
+    pub fn is_mine(&self) -> Option<bool> {
+        self.is_mine
+    }
+
+    /// The unique id of the answer this answer replies to, `None` for answers to the question itself.
+    pub fn parent_answer_id(&self) -> Option<Uuid> {
+        self.parent_answer_id
+    }
+
+    /// Whether a moderator replaced the answer with a tombstone, see `AnswerDao::delete_answer_with_tombstone`.
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    /// The timestamp the answer was tombstoned, `None` unless it is tombstoned.
+    pub fn deleted_at(&self) -> Option<DateTime<Utc>> {
+        self.deleted_at
+    }
+
+    /// Why the answer was tombstoned, if a reason was given.
+    pub fn deleted_reason(&self) -> Option<&str> {
+        self.deleted_reason.as_deref()
+    }
+
+    /// Renders the content of the answer to HTML according to its `ContentType`, see `render::render_html`.
+    #[cfg(feature = "render")]
+    pub fn render_html(&self) -> String {
+        render::render_html(&self.answer, self.content_type.clone())
+    }
 }
 
-impl EntityId {
-    pub fn new(id: String) -> Self {
-        Self { id }
+/// The default number of answers returned by a sorted listing.
+pub const DEFAULT_ANSWER_LIMIT: u32 = 50;
+
+/// The orderings available when listing the answers to a question.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnswerSort {
+    /// The most liked answers first, ties broken by the oldest first
+    #[default]
+    MostLiked,
+    /// The most recently created answers first
+    Newest,
+    /// The least recently created answers first
+    Oldest,
+    /// The highest scoring answers first, ties broken by the oldest first
+    Score,
+}
+
+impl AnswerSort {
+    /// The names accepted when parsing an `AnswerSort`.
+    pub const VARIANTS: [&'static str; 4] = ["most_liked", "newest", "oldest", "score"];
+}
+
+impl std::str::FromStr for AnswerSort {
+    type Err = DbError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "most_liked" => Ok(AnswerSort::MostLiked),
+            "newest" => Ok(AnswerSort::Newest),
+            "oldest" => Ok(AnswerSort::Oldest),
+            "score" => Ok(AnswerSort::Score),
+            _ => Err(DbError::validation(format!("invalid sort `{s}`, expected one of: {}", AnswerSort::VARIANTS.join(", ")))),
+        }
     }
 }
 
-impl TryInto<Uuid> for EntityId {
-    type Error = &'static str;
-    fn try_into(self) -> Result<Uuid, Self::Error> {
-        Uuid::parse_str(self.id.as_str()).map_err(|_| "unable to parse as uuid")
+/// An answer joined with the username of its author.
+#[derive(Debug, Serialize, FromRow)]
+pub struct AnswerWithAuthor {
+    /// The answer itself
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    answer: Answer,
+    /// The username of the author, `None` for anonymous answers. Serialized under an `author` object.
+    #[serde(rename = "author", serialize_with = "serialize_author")]
+    author_username: Option<String>,
+}
+
+impl AnswerWithAuthor {
+    /// The answer.
+    pub fn answer(&self) -> &Answer {
+        &self.answer
+    }
+
+    /// The username of the author, `None` for anonymous answers.
+    pub fn author_username(&self) -> Option<&str> {
+        self.author_username.as_deref()
     }
 }
 
-#[derive(Debug)]
-pub enum DbError {
-    Creation(Error),
-    NotFound(Error),
-    InvalidUuid(&'static str),
-    Access(Error),
-    FromRow(Error),
-    Deletion(Error),
-    Update(Error),
-    Commit(Error),
+/// An item of the feed of recent activity, which interleaves new questions and new answers. Serialized with the
+/// kind of the item as `type` and the item itself as `item`.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", content = "item", rename_all = "snake_case")]
+pub enum ActivityItem {
+    /// A question was posted
+    QuestionPosted(Question),
+    /// An answer was posted to the question with the given title
+    AnswerPosted {
+        /// The answer that was posted
+        answer: Answer,
+        /// The title of the question the answer was posted to
+        question_title: String,
+    },
 }
 
-impl Display for DbError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl ActivityItem {
+    /// The timestamp the question or the answer was posted.
+    pub fn created_at(&self) -> DateTime<Utc> {
+        match self {
+            ActivityItem::QuestionPosted(question) => question.created_at,
+            ActivityItem::AnswerPosted { answer, .. } => answer.created_at,
+        }
+    }
+
+    /// The position of the item in the feed, from which the feed continues after it.
+    pub fn cursor(&self) -> ActivityCursor {
         match self {
-            DbError::Creation(e) => write!(f, "Creation error occurred: {e}"),
-            DbError::NotFound(e) => write!(f, "Entity not found in database: {e}"),
-            DbError::InvalidUuid(s) => write!(f, "Invalid Uuid error: {s}"),
-            DbError::Access(e) => write!(f, "Error when accessing database: {e}"),
-            DbError::FromRow(e) => write!(f, "Error when converting entity from database row: {e}"),
-            DbError::Deletion(e) => write!(f, "Error deleting from database: {e}"),
-            DbError::Update(e) => write!(f, "Error updating database: {e}"),
-            DbError::Commit(e) => write!(f, "Error committing to database: {e}")
+            ActivityItem::QuestionPosted(question) => {
+                ActivityCursor { created_at: question.created_at, is_question: true, id: question.id }
+            }
+            ActivityItem::AnswerPosted { answer, .. } => {
+                ActivityCursor { created_at: answer.created_at, is_question: false, id: answer.id }
+            }
+        }
+    }
+}
+
+/// The position of an item in the feed of recent activity, which is ordered by the time the items were posted, newest
+/// first, then answers before questions, then by id. Items posted at the same time are told apart by their kind and
+/// id, so a page ending among them continues with the rest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActivityCursor {
+    /// The timestamp the item was posted
+    pub created_at: DateTime<Utc>,
+    /// Whether the item is a question rather than an answer
+    pub is_question: bool,
+    /// The unique id of the question or the answer
+    pub id: Uuid,
+}
+
+impl From<Question> for ActivityItem {
+    fn from(question: Question) -> Self {
+        ActivityItem::QuestionPosted(question)
+    }
+}
+
+/// Fails with the answer when its question is no longer visible, as the feed never shows an answer without a title.
+impl TryFrom<AnswerWithQuestion> for ActivityItem {
+    type Error = Answer;
+
+    fn try_from(answer: AnswerWithQuestion) -> Result<Self, Answer> {
+        match answer.question_title {
+            Some(question_title) => Ok(ActivityItem::AnswerPosted { answer: answer.answer, question_title }),
+            None => Err(answer.answer),
+        }
+    }
+}
+
+/// A question along with the number of its published answers, everything needed to render its header.
+#[derive(Debug, FromRow)]
+pub struct QuestionHeader {
+    /// The question itself
+    #[sqlx(flatten)]
+    question: Question,
+    /// The number of published answers to the question, replies included
+    answer_count: i64,
+    /// The number of published answers to the question itself, leaving out replies
+    #[sqlx(default)]
+    top_level_answer_count: i64,
+}
+
+impl QuestionHeader {
+    /// The question.
+    pub fn question(&self) -> &Question {
+        &self.question
+    }
+
+    /// The number of published answers to the question, replies included.
+    pub fn answer_count(&self) -> i64 {
+        self.answer_count
+    }
+
+    /// The number of published answers to the question itself, leaving out replies.
+    pub fn top_level_answer_count(&self) -> i64 {
+        self.top_level_answer_count
+    }
+
+    /// The unique id of the accepted answer, whether or not it has since been archived, if any.
+    pub fn accepted_answer_id(&self) -> Option<Uuid> {
+        self.question.accepted_answer_id.or(self.question.archived_accepted_answer_id)
+    }
+
+    /// Whether an answer to the question was accepted, as counted by `TagDao::get_acceptance_rate_by_tag`.
+    pub fn has_accepted_answer(&self) -> bool {
+        self.question.has_accepted_answer()
+    }
+}
+
+/// The weights blending text relevance with engagement when ranking the questions matching a search. Each
+/// engagement signal is damped as `ln(1 + count)`, so a popular question is lifted without drowning out relevance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchRankingConfig {
+    /// The weight of how well the question's title and content match the search, as ranked by `ts_rank`
+    pub text_weight: f64,
+    /// The weight of the question's likes
+    pub likes_weight: f64,
+    /// The weight of the question's visible answers
+    pub answers_weight: f64,
+    /// The weight of the question's views
+    pub views_weight: f64,
+    /// Whether each result reports the components of its score, see `RankedQuestion::components`
+    pub debug_explain: bool,
+}
+
+impl SearchRankingConfig {
+    /// Checks that every weight is finite and not negative.
+    pub fn validate(&self) -> Result<(), DbError> {
+        for (name, weight) in [
+            ("text", self.text_weight),
+            ("likes", self.likes_weight),
+            ("answers", self.answers_weight),
+            ("views", self.views_weight),
+        ] {
+            if !weight.is_finite() || weight < 0.0 {
+                return Err(DbError::validation(format!("the {name} weight must be a finite number of at least zero, got {weight}")));
+            }
         }
+        Ok(())
+    }
+}
+
+impl Default for SearchRankingConfig {
+    fn default() -> Self {
+        Self { text_weight: 1.0, likes_weight: 0.25, answers_weight: 0.25, views_weight: 0.0, debug_explain: false }
+    }
+}
+
+/// The components of the score of a `RankedQuestion`, before they are weighted.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct RankComponents {
+    /// How well the question matches the search, as ranked by `ts_rank`
+    pub text_rank: f64,
+    /// `ln(1 + likes)`
+    pub likes: f64,
+    /// `ln(1 + answers)`, counting the visible answers
+    pub answers: f64,
+    /// `ln(1 + views)`
+    pub views: f64,
+}
+
+/// A question matching a search, along with the score it was ranked by.
+#[derive(Debug, Serialize)]
+pub struct RankedQuestion {
+    /// The question itself
+    pub question: Question,
+    /// The weighted sum of the components of the score, higher ranking first
+    pub score: f64,
+    /// The unweighted components of the score, only reported with `SearchRankingConfig::debug_explain`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub components: Option<RankComponents>,
+}
+
+/// A question liked faster than the rate a moderation sweep looks for, along with the rate it was liked at.
+#[derive(Debug, Serialize)]
+pub struct QuestionLikeRate {
+    /// The question itself
+    pub question: Question,
+    /// The likes per hour, over the window of the sweep or, when estimated, over the lifetime of the question
+    pub likes_per_hour: f64,
+    /// Whether the question has no recorded like events, so the rate is its likes divided by its age
+    pub estimated: bool,
+}
+
+/// The longest window the like rate of questions can be measured over.
+pub const MAX_LIKE_RATE_WINDOW_DAYS: i64 = 30;
+
+/// Checks that a range of like counts is not negative and that `max`, when given, is not below `min`.
+pub fn validate_like_range(min: i32, max: Option<i32>) -> Result<(), DbError> {
+    if min < 0 {
+        return Err(DbError::validation(format!("minimum likes must not be negative, got {min}")));
+    }
+    match max {
+        Some(max) if max < min => Err(DbError::validation(format!("maximum likes {max} is below the minimum {min}"))),
+        _ => Ok(()),
     }
 }
 
-impl std::error::Error for DbError {}
+/// Checks that a like rate is a finite, non-negative number of likes per hour, measured over a positive `window` of at
+/// most `MAX_LIKE_RATE_WINDOW_DAYS` days.
+pub fn validate_like_rate(likes_per_hour: f64, window: Duration) -> Result<(), DbError> {
+    if !likes_per_hour.is_finite() || likes_per_hour < 0.0 {
+        return Err(DbError::validation(format!("likes per hour must be a non-negative number, got {likes_per_hour}")));
+    }
+    if window <= Duration::zero() || window > Duration::days(MAX_LIKE_RATE_WINDOW_DAYS) {
+        return Err(DbError::validation(format!(
+            "like rate window must be positive and at most {MAX_LIKE_RATE_WINDOW_DAYS} days"
+        )));
+    }
+    Ok(())
+}
+
+/// The largest number of items a page of a listing can hold.
+pub const MAX_PAGE_SIZE: u32 = 100;
+
+/// The page of a listing requested by a client, as an offset into the listing and the number of items.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageRequest {
+    /// The number of items of the listing before the page
+    pub offset: u32,
+    /// The maximum number of items on the page, at most `MAX_PAGE_SIZE`
+    pub limit: u32,
+}
+
+impl PageRequest {
+    /// Checks that the page holds at least one and at most `MAX_PAGE_SIZE` items.
+    pub fn validate(&self) -> Result<(), DbError> {
+        if self.limit == 0 || self.limit > MAX_PAGE_SIZE {
+            return Err(DbError::validation(format!("page limit must be between 1 and {MAX_PAGE_SIZE}, got {}", self.limit)));
+        }
+        Ok(())
+    }
+}
+
+/// The number of answers the detail of a question embeds by default when fetched with `DetailOptions`.
+pub const DEFAULT_EMBEDDED_ANSWERS: u32 = 20;
+
+/// The number of characters of its content an answer embedded with `AnswerBodyMode::Preview` keeps.
+pub const ANSWER_PREVIEW_CHARS: usize = 100;
+
+/// How much of their content the answers embedded in the detail of a question carry, so that clients on metered
+/// connections can leave the bodies out.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnswerBodyMode {
+    /// The whole content
+    #[default]
+    Full,
+    /// The first `ANSWER_PREVIEW_CHARS` characters of the content
+    Preview,
+    /// No content, which is not read from the database at all
+    None,
+}
+
+impl AnswerBodyMode {
+    /// The names accepted when parsing an `AnswerBodyMode`, such as from an `answer_bodies` query parameter.
+    pub const VARIANTS: [&'static str; 3] = ["full", "preview", "none"];
+}
+
+impl std::str::FromStr for AnswerBodyMode {
+    type Err = DbError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "full" => Ok(AnswerBodyMode::Full),
+            "preview" => Ok(AnswerBodyMode::Preview),
+            "none" => Ok(AnswerBodyMode::None),
+            _ => Err(DbError::validation(format!(
+                "invalid answer bodies `{s}`, expected one of: {}",
+                AnswerBodyMode::VARIANTS.join(", ")
+            ))),
+        }
+    }
+}
+
+/// Which of its answers the detail of a question embeds, so that questions with many answers are not read in full.
+/// Clients page through the rest of the answers separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DetailOptions {
+    /// The maximum number of answers embedded, at most `MAX_PAGE_SIZE`, zero embedding none
+    pub max_embedded_answers: u32,
+    /// The order the answers are ranked in, of which the first `max_embedded_answers` are embedded
+    pub sort: AnswerSort,
+    /// Whether the detail reports the total number of published answers, see `QuestionDetail::total_answers`
+    pub include_total: bool,
+    /// Whether the detail includes every translation of the question, see `QuestionDetail::translations`
+    pub include_translations: bool,
+    /// Whether the detail reports the number of answers archived from the question, see
+    /// `QuestionDetail::archived_answers`
+    pub include_archived: bool,
+    /// How much of their content the embedded answers carry, see `QuestionDetailResponse::with_answer_bodies`
+    pub answer_bodies: AnswerBodyMode,
+}
+
+impl DetailOptions {
+    /// Checks that at most `MAX_PAGE_SIZE` answers are embedded.
+    pub fn validate(&self) -> Result<(), DbError> {
+        if self.max_embedded_answers > MAX_PAGE_SIZE {
+            return Err(DbError::validation(format!(
+                "at most {MAX_PAGE_SIZE} answers can be embedded, got {}",
+                self.max_embedded_answers
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for DetailOptions {
+    fn default() -> Self {
+        Self {
+            max_embedded_answers: DEFAULT_EMBEDDED_ANSWERS,
+            sort: AnswerSort::default(),
+            include_total: true,
+            include_translations: false,
+            include_archived: false,
+            answer_bodies: AnswerBodyMode::default(),
+        }
+    }
+}
+
+/// A page of a listing, along with whether more items follow it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Page<T> {
+    /// The items on the page, in the order of the listing
+    pub items: Vec<T>,
+    /// Whether the listing has items after the page
+    pub has_more: bool,
+}
+
+/// A page of the answers to a question ordered by their likes as of a snapshot, see `AnswerDao::get_answers_paged`.
+#[derive(Debug, Serialize)]
+pub struct AnswerPage {
+    /// The answers on the page, the most liked as of the snapshot first
+    pub answers: Vec<Answer>,
+    /// The opaque token of the next page, `None` on the last page
+    pub page_token: Option<String>,
+}
+
+/// The columns of a question selected by a projected listing, combined with `|`. The id is always selected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct QuestionFields(u8);
+
+impl QuestionFields {
+    /// Only the id
+    pub const NONE: QuestionFields = QuestionFields(0);
+    /// The title
+    pub const TITLE: QuestionFields = QuestionFields(1);
+    /// The content, which can be large
+    pub const BODY: QuestionFields = QuestionFields(1 << 1);
+    /// The number of likes
+    pub const LIKES: QuestionFields = QuestionFields(1 << 2);
+    /// The creation time
+    pub const CREATED_AT: QuestionFields = QuestionFields(1 << 3);
+    /// Every field
+    pub const ALL: QuestionFields = QuestionFields(0b1111);
+
+    /// Whether every field of `other` is selected.
+    pub fn contains(&self, other: QuestionFields) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for QuestionFields {
+    type Output = QuestionFields;
+
+    fn bitor(self, rhs: QuestionFields) -> QuestionFields {
+        QuestionFields(self.0 | rhs.0)
+    }
+}
+
+/// A question with only the fields selected by `QuestionFields`, the others being `None`.
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow)]
+pub struct QuestionPartial {
+    /// The unique id of the question
+    id: Uuid,
+    /// The title of the question, if selected
+    #[sqlx(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    /// The content of the question, if selected
+    #[sqlx(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    question: Option<String>,
+    /// The number of likes the question has received, if selected
+    #[sqlx(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    likes: Option<i64>,
+    /// The time the question was created, if selected
+    #[sqlx(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    created_at: Option<DateTime<Utc>>,
+}
+
+impl QuestionPartial {
+    /// The unique id of the question.
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// The title of the question, `None` unless selected.
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// The content of the question, `None` unless selected.
+    pub fn question(&self) -> Option<&str> {
+        self.question.as_deref()
+    }
+
+    /// The number of likes the question has received, `None` unless selected.
+    pub fn likes(&self) -> Option<i64> {
+        self.likes
+    }
+
+    /// The time the question was created, `None` unless selected.
+    pub fn created_at(&self) -> Option<DateTime<Utc>> {
+        self.created_at
+    }
+}
+
+/// The question with every field of `QuestionFields::ALL` selected.
+impl From<Question> for QuestionPartial {
+    fn from(question: Question) -> Self {
+        Self {
+            id: question.id,
+            title: Some(question.title),
+            question: Some(question.question),
+            likes: Some(question.likes),
+            created_at: Some(question.created_at),
+        }
+    }
+}
+
+/// An answer to a question along with the replies to it, which cannot be replied to in turn.
+#[derive(Debug, Serialize)]
+pub struct AnswerThread {
+    /// The answer to the question
+    #[serde(flatten)]
+    answer: Answer,
+    /// The replies to the answer, oldest first
+    replies: Vec<Answer>,
+}
+
+impl AnswerThread {
+    /// Assembles threads from a listing of answers and replies, keeping the answers in the order of the listing
+    /// and the replies in the order they were created. Replies whose parent is not in the listing are left out.
+    pub(crate) fn assemble(answers: Vec<Answer>) -> Vec<AnswerThread> {
+        let (top_level, mut replies): (Vec<Answer>, Vec<Answer>) =
+            answers.into_iter().partition(|answer| answer.parent_answer_id.is_none());
+        replies.sort_by_key(|reply| (reply.created_at, reply.id));
+        let mut threads: Vec<AnswerThread> = top_level.into_iter().map(|answer| AnswerThread { answer, replies: Vec::new() }).collect();
+        let positions: HashMap<Uuid, usize> = threads.iter().enumerate().map(|(i, thread)| (thread.answer.id, i)).collect();
+        for reply in replies {
+            if let Some(&i) = reply.parent_answer_id.and_then(|parent| positions.get(&parent)) {
+                threads[i].replies.push(reply);
+            }
+        }
+        threads
+    }
+
+    /// The answer to the question.
+    pub fn answer(&self) -> &Answer {
+        &self.answer
+    }
+
+    /// The replies to the answer, oldest first.
+    pub fn replies(&self) -> &[Answer] {
+        &self.replies
+    }
+
+    /// Consumes the thread, returning the answer and its replies.
+    pub fn into_parts(self) -> (Answer, Vec<Answer>) {
+        (self.answer, self.replies)
+    }
+}
+
+/// A question along with its header and its published answers, everything needed to render its detail page.
+#[derive(Debug)]
+pub struct QuestionDetail {
+    /// The question and the number of its published answers
+    header: QuestionHeader,
+    /// The published answers to the question
+    answers: Vec<Answer>,
+    /// Whether the header and the answers were read from one snapshot of the database
+    snapshot: bool,
+    /// The total number of published answers, when the answers embedded may be fewer
+    total_answers: Option<i64>,
+    /// The translations of the question, when requested
+    translations: Vec<QuestionTranslation>,
+    /// The number of answers archived from the question, when requested
+    archived_answers: Option<i64>,
+}
+
+impl QuestionDetail {
+    /// Creates the detail of a question from its header and answers.
+    pub(crate) fn new(header: QuestionHeader, answers: Vec<Answer>, snapshot: bool) -> Self {
+        Self { header, answers, snapshot, total_answers: None, translations: Vec::new(), archived_answers: None }
+    }
+
+    /// Includes the translations of the question.
+    pub(crate) fn with_translations(mut self, translations: Vec<QuestionTranslation>) -> Self {
+        self.translations = translations;
+        self
+    }
+
+    /// Reports the total number of published answers alongside the answers embedded.
+    pub(crate) fn with_total_answers(mut self, total_answers: i64) -> Self {
+        self.total_answers = Some(total_answers);
+        self
+    }
+
+    /// Reports the number of answers archived from the question.
+    pub(crate) fn with_archived_answers(mut self, archived_answers: i64) -> Self {
+        self.archived_answers = Some(archived_answers);
+        self
+    }
+
+    /// The question and the number of its published answers.
+    pub fn header(&self) -> &QuestionHeader {
+        &self.header
+    }
+
+    /// The published answers to the question.
+    pub fn answers(&self) -> &[Answer] {
+        &self.answers
+    }
+
+    /// The total number of published answers, including those not embedded, if the detail was fetched with
+    /// `DetailOptions::include_total`.
+    pub fn total_answers(&self) -> Option<i64> {
+        self.total_answers
+    }
+
+    /// The number of answers moved to the archive by `AnswerDao::archive_stale_answers`, which are no longer listed,
+    /// if the detail was fetched with `DetailOptions::include_archived`.
+    pub fn archived_answers(&self) -> Option<i64> {
+        self.archived_answers
+    }
+
+    /// The translations of the question ordered by language, empty unless the detail was fetched with
+    /// `DetailOptions::include_translations`.
+    pub fn translations(&self) -> &[QuestionTranslation] {
+        &self.translations
+    }
+
+    /// Whether the header and the answers were read from one snapshot, so the answer count matches the answers.
+    /// When `false` each read saw the database as it was at the time, and concurrent writes may make them disagree.
+    pub fn is_snapshot(&self) -> bool {
+        self.snapshot
+    }
+
+    /// Splits the detail into the header and the answers.
+    pub fn into_parts(self) -> (QuestionHeader, Vec<Answer>) {
+        (self.header, self.answers)
+    }
+}
+
+/// An answer joined with the title of the question it answers.
+#[derive(Debug, Serialize, FromRow)]
+pub struct AnswerWithQuestion {
+    /// The answer itself
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    answer: Answer,
+    /// The title of the question the answer belongs to, `None` once the question is no longer visible
+    question_title: Option<String>,
+}
+
+impl AnswerWithQuestion {
+    /// The answer.
+    pub fn answer(&self) -> &Answer {
+        &self.answer
+    }
+
+    /// The unique id of the question the answer belongs to, `None` once the question is deleted with
+    /// `DeletePolicy::Orphan`.
+    pub fn question_id(&self) -> Option<Uuid> {
+        self.answer.question_id
+    }
+
+    /// The title of the question the answer belongs to, `None` once the question is no longer visible.
+    pub fn question_title(&self) -> Option<&str> {
+        self.question_title.as_deref()
+    }
+}
+
+/// Serializes an optional author username as `{ "username": ... }`, or `null` for anonymous content.
+fn serialize_author<S: serde::Serializer>(username: &Option<String>, serializer: S) -> Result<S::Ok, S::Error> {
+    #[derive(Serialize)]
+    struct Author<'a> {
+        username: &'a str,
+    }
+    username.as_deref().map(|username| Author { username }).serialize(serializer)
+}
+
+/// The content a question had before an edit, as recorded in `question_revisions`.
+#[derive(Debug)]
+pub(crate) struct QuestionRevision {
+    pub(crate) title: String,
+    pub(crate) question: String,
+    pub(crate) content_type: ContentType,
+    pub(crate) char_count: i32,
+    pub(crate) word_count: i32,
+    pub(crate) updated_at: Option<DateTime<Utc>>,
+}
+
+/// The answers a subscribed question has received since its subscriber last fetched updates.
+#[derive(Debug, Serialize, FromRow)]
+pub struct QuestionUpdate {
+    /// The unique id of the subscribed question
+    question_id: Uuid,
+    /// The number of answers that became visible since the last fetch
+    new_answers: i64,
+    /// The timestamp of the most recent new answer
+    latest_answer_at: DateTime<Utc>,
+}
+
+impl QuestionUpdate {
+    /// The unique id of the subscribed question.
+    pub fn question_id(&self) -> Uuid {
+        self.question_id
+    }
+
+    /// The number of answers that became visible since the last fetch.
+    pub fn new_answers(&self) -> i64 {
+        self.new_answers
+    }
+
+    /// The timestamp of the most recent new answer.
+    pub fn latest_answer_at(&self) -> DateTime<Utc> {
+        self.latest_answer_at
+    }
+}
+
+/// The number of questions and answers created on a calendar day (in UTC).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, FromRow)]
+pub struct DailyActivity {
+    /// The calendar day
+    pub date: NaiveDate,
+    /// The number of questions created on the day
+    pub questions: i64,
+    /// The number of answers created on the day
+    pub answers: i64,
+}
+
+/// A change to the like counter of a question or an answer, as recorded in the audit log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromRow)]
+pub struct LikeEvent {
+    /// The sequential id of the event
+    id: i64,
+    /// The kind of entity that was liked, either `question` or `answer`
+    entity_type: String,
+    /// The id of the question or answer that was liked
+    entity_id: Uuid,
+    /// The change to the like counter, negative when likes were removed
+    delta: i64,
+    /// The timestamp the change happened
+    occurred_at: DateTime<Utc>,
+    /// The token of the user who made the change, if known
+    source_token: Option<String>,
+}
+
+impl LikeEvent {
+    /// The sequential id of the event.
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    /// The kind of entity that was liked, either `question` or `answer`.
+    pub fn entity_type(&self) -> &str {
+        &self.entity_type
+    }
+
+    /// The id of the question or answer that was liked.
+    pub fn entity_id(&self) -> Uuid {
+        self.entity_id
+    }
+
+    /// The change to the like counter, negative when likes were removed.
+    pub fn delta(&self) -> i64 {
+        self.delta
+    }
+
+    /// The timestamp the change happened.
+    pub fn occurred_at(&self) -> DateTime<Utc> {
+        self.occurred_at
+    }
+
+    /// The token of the user who made the change, if known.
+    pub fn source_token(&self) -> Option<&str> {
+        self.source_token.as_deref()
+    }
+}
+
+/// A new category received from a request.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NewCategory {
+    /// The name of the new category, unique among its siblings
+    pub name: String,
+    /// The id of the parent category, `None` for a top level category
+    #[serde(default)]
+    pub parent_id: Option<String>,
+}
+
+impl NewCategory {
+    /// Ensures the name of the new category is not blank and within the length of a question title.
+    pub fn validate(&self, limits: &ContentLimits) -> Result<(), DbError> {
+        if self.name.trim().is_empty() {
+            return Err(DbError::validation(String::from("name must not be blank")));
+        }
+        check_length("name", &self.name, limits.max_title)
+    }
+}
+
+/// A category questions can be filed under, forming a tree through its parent.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromRow)]
+pub struct Category {
+    /// The unique id of the category
+    id: Uuid,
+    /// The name of the category
+    name: String,
+    /// The id of the parent category, `None` for a top level category
+    parent_id: Option<Uuid>,
+    /// The timestamp the category was created
+    created_at: DateTime<Utc>,
+}
+
+impl Category {
+    /// The unique id of the category.
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// The name of the category.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The id of the parent category, `None` for a top level category.
+    pub fn parent_id(&self) -> Option<Uuid> {
+        self.parent_id
+    }
+
+    /// The timestamp the category was created.
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+}
+
+/// The maximum number of characters in the name of a tag.
+pub const MAX_TAG_LENGTH: usize = 50;
+
+/// A tag questions can be labelled with. Names are unique regardless of case, keeping the spelling of their first use.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromRow)]
+pub struct Tag {
+    /// The unique id of the tag
+    id: Uuid,
+    /// The name of the tag
+    name: String,
+    /// The timestamp the tag was first used
+    created_at: DateTime<Utc>,
+}
+
+impl Tag {
+    /// The unique id of the tag.
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// The name of the tag.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The timestamp the tag was first used.
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+}
+
+/// A tag suggested for a prefix, along with the number of questions using it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromRow)]
+pub struct TagSuggestion {
+    /// The name of the tag
+    pub name: String,
+    /// The number of questions labelled with the tag
+    pub usage_count: i64,
+}
+
+/// The usage of a tag.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromRow)]
+pub struct TagStats {
+    /// The name of the tag
+    pub name: String,
+    /// The number of questions labelled with the tag
+    pub usage_count: i64,
+    /// The timestamp a question was last labelled with the tag, `None` if no question currently is
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// How often the questions labelled with a tag have an answer accepted by their author.
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow)]
+pub struct TagAcceptance {
+    /// The name of the tag
+    pub name: String,
+    /// The number of questions labelled with the tag
+    pub question_count: i64,
+    /// The number of those questions with an accepted answer
+    pub accepted_count: i64,
+    /// The share of the questions with an accepted answer, between zero and one
+    pub acceptance_rate: f64,
+}
+
+/// A user along with their reputation, see `ReputationConfig`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, FromRow)]
+pub struct UserReputation {
+    /// The unique id of the user
+    pub id: Uuid,
+    /// The name of the user
+    pub username: String,
+    /// The reputation of the user
+    pub reputation: i32,
+}
+
+/// The result of renaming a tag, merging it into the tag with the new name if there is one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetagReport {
+    /// The number of questions labelled with the renamed tag, which are now labelled with the new name
+    pub questions_affected: u64,
+    /// The number of those questions that were already labelled with the new name
+    pub already_tagged: u64,
+}
+
+impl Display for RetagReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Retagged {} questions, {} of which already had the new tag", self.questions_affected, self.already_tagged)
+    }
+}
+
+/// A category along with its subcategories.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CategoryNode {
+    /// The category
+    #[serde(flatten)]
+    pub category: Category,
+    /// The subcategories, in the order they were listed
+    pub children: Vec<CategoryNode>,
+}
+
+impl CategoryNode {
+    /// Builds the trees rooted at the top level categories from a flat list of categories, keeping the
+    /// relative order of siblings. Categories whose parent is not in the list are omitted.
+    pub fn build_tree(categories: Vec<Category>) -> Vec<CategoryNode> {
+        let mut children: HashMap<Option<Uuid>, Vec<Category>> = HashMap::new();
+        for category in categories {
+            children.entry(category.parent_id).or_default().push(category);
+        }
+        Self::build_children(None, &mut children)
+    }
+
+    fn build_children(parent_id: Option<Uuid>, children: &mut HashMap<Option<Uuid>, Vec<Category>>) -> Vec<CategoryNode> {
+        children.remove(&parent_id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|category| {
+                let children = Self::build_children(Some(category.id), children);
+                CategoryNode { category, children }
+            })
+            .collect()
+    }
+}
+
+/// The parsed form of an `EntityId`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntityIdKind {
+    /// The primary key of an entity
+    Uuid(Uuid),
+    /// The secondary, sequential key of an entity, used by legacy clients
+    Serial(i64),
+}
+
+/// A struct that acts as a wrapper for all entity ID's in the models module.
+///
+/// An `EntityId` is either a uuid or a serial number. Ids received as strings are parsed as a uuid first,
+/// in any of the formats accepted by `Uuid::parse_str`: hyphenated (`67e55044-10b1-426f-9247-bb680e5fe0c8`),
+/// simple (`67e5504410b1426f9247bb680e5fe0c8`), urn (`urn:uuid:67e55044-10b1-426f-9247-bb680e5fe0c8`) or braced
+/// (`{67e55044-10b1-426f-9247-bb680e5fe0c8}`), with hex digits in either case. Otherwise a string of decimal
+/// digits that fits in an `i64` is a serial number. No signs or surrounding whitespace are permitted.
+///
+/// Callers that know which kind of id they have should use `EntityId::uuid` or `EntityId::serial`, so that
+/// an id of the wrong kind is never silently accepted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityId {
+    id: String,
+    kind: Option<EntityIdKind>,
+}
+
+impl EntityId {
+    /// Wraps an id received from a request, parsing it as a uuid or a serial number.
+    pub fn new(id: String) -> Self {
+        let kind = Uuid::parse_str(&id)
+            .map(EntityIdKind::Uuid)
+            .ok()
+            .or_else(|| parse_serial(&id).map(EntityIdKind::Serial));
+        Self { id, kind }
+    }
+
+    /// Creates the id of an entity from its uuid.
+    pub fn uuid(id: Uuid) -> Self {
+        Self { id: id.to_string(), kind: Some(EntityIdKind::Uuid(id)) }
+    }
+
+    /// Creates the id of an entity from its serial number.
+    pub fn serial(id: i64) -> Self {
+        Self { id: id.to_string(), kind: Some(EntityIdKind::Serial(id)) }
+    }
+
+    /// The id as it was received.
+    pub fn as_str(&self) -> &str {
+        &self.id
+    }
+
+    /// The kind of id, or an error if it is neither a uuid nor a serial number.
+    pub fn kind(&self) -> Result<EntityIdKind, &'static str> {
+        self.kind.ok_or("unable to parse as uuid or serial")
+    }
+}
+
+/// Parses a serial number, which must consist of decimal digits only.
+fn parse_serial(id: &str) -> Option<i64> {
+    if id.is_empty() || !id.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    id.parse().ok()
+}
+
+impl TryFrom<&EntityId> for Uuid {
+    type Error = &'static str;
+    fn try_from(id: &EntityId) -> Result<Uuid, Self::Error> {
+        match id.kind {
+            Some(EntityIdKind::Uuid(uuid)) => Ok(uuid),
+            Some(EntityIdKind::Serial(_)) => Err("expected a uuid, got a serial"),
+            None => Err("unable to parse as uuid"),
+        }
+    }
+}
+
+impl TryInto<Uuid> for EntityId {
+    type Error = &'static str;
+    fn try_into(self) -> Result<Uuid, Self::Error> {
+        Uuid::try_from(&self)
+    }
+}
+
+/// Progress of a batched operation, reported after each batch is committed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchProgress {
+    /// The zero based index of the batch that was just committed
+    pub batch: usize,
+    /// The total number of batches the operation was split into
+    pub total_batches: usize,
+    /// The number of rows affected by the batch that was just committed
+    pub affected_in_batch: u64,
+    /// The number of rows affected by all batches committed so far
+    pub affected_total: u64,
+}
+
+/// The result of a bulk update that reports the ids it could not find, rather than failing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BulkUpdate {
+    /// The number of entities updated
+    pub updated: u64,
+    /// The ids that matched no entity, in the order they were given
+    pub missing: Vec<Uuid>,
+}
+
+/// What importing a record of exported content does, or would do in a dry run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportVerdict {
+    /// The record is inserted
+    Insert,
+    /// A record with the same id already exists, or comes earlier in the content, so the record is skipped
+    Skip,
+    /// The record cannot be imported for the given reason, which stops a real import
+    Invalid(String),
+}
+
+/// The verdict on a single question or answer of exported content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportRecord {
+    /// Whether the record is a question or an answer
+    pub entity: LikableEntity,
+    /// The id of the record as given in the content
+    pub id: String,
+    /// What importing the record does
+    pub verdict: ImportVerdict,
+}
+
+/// The number of rows in each of the crate's tables.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, FromRow)]
+pub struct Totals {
+    /// The number of questions
+    pub questions: i64,
+    /// The number of answers
+    pub answers: i64,
+    /// The number of users
+    pub users: i64,
+    /// The number of subscriptions
+    pub subscriptions: i64,
+}
+
+impl Display for Totals {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "questions:     {}", self.questions)?;
+        writeln!(f, "answers:       {}", self.answers)?;
+        writeln!(f, "users:         {}", self.users)?;
+        write!(f, "subscriptions: {}", self.subscriptions)
+    }
+}
+
+/// How long questions waited for their first answer. The durations are `None` when no question was answered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct LatencyStats {
+    /// The number of questions that have been answered
+    pub answered: i64,
+    /// The number of questions that are still unanswered
+    pub unanswered: i64,
+    /// The shortest wait for a first answer, serialized as seconds
+    #[serde(serialize_with = "as_seconds")]
+    pub min: Option<Duration>,
+    /// The median wait for a first answer, serialized as seconds
+    #[serde(serialize_with = "as_seconds")]
+    pub median: Option<Duration>,
+    /// The 90th percentile wait for a first answer, serialized as seconds
+    #[serde(serialize_with = "as_seconds")]
+    pub p90: Option<Duration>,
+}
+
+/// Serializes a duration as a fractional number of seconds.
+fn as_seconds<S: Serializer>(duration: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error> {
+    duration.map(|d| d.num_microseconds().unwrap_or(i64::MAX) as f64 / 1_000_000.0).serialize(serializer)
+}
+
+/// The result of merging a duplicate question into another question.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MergeReport {
+    /// The id of the duplicate question, which no longer exists
+    pub duplicate_id: Uuid,
+    /// The id of the question the duplicate was merged into
+    pub target_id: Uuid,
+    /// The number of answers moved to the target question
+    pub answers_moved: u64,
+    /// The number of subscriptions moved to the target question, excluding users already subscribed to it
+    pub subscriptions_moved: u64,
+    /// The number of likes the duplicate question had, which were added to the target question
+    pub likes_added: i64,
+}
+
+impl Display for MergeReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Merged question {} into {}: moved {} answers and {} subscriptions, added {} likes",
+            self.duplicate_id, self.target_id, self.answers_moved, self.subscriptions_moved, self.likes_added
+        )
+    }
+}
+
+/// The number of questions and answers transferred from one author to another.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransferReport {
+    /// The number of questions transferred to the new author
+    pub questions: u64,
+    /// The number of answers transferred to the new author
+    pub answers: u64,
+}
+
+impl Display for TransferReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Transferred {} questions and {} answers", self.questions, self.answers)
+    }
+}
+
+/// The content that replaces answers scrubbed when their author is anonymized, and tombstoned answers.
+pub const DELETED_CONTENT: &str = "[deleted]";
+
+/// The maximum number of characters in the reason an answer was tombstoned.
+pub const MAX_DELETED_REASON_LENGTH: usize = 500;
+
+/// The number of rows affected in each table when an author is anonymized.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AnonymizeReport {
+    /// The number of answers dissociated from the author, and scrubbed if requested
+    pub answers: u64,
+    /// The number of user rows deleted, zero if the author had already been removed
+    pub users: u64,
+}
+
+/// The answers moved to the archive from questions without recent activity.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArchiveReport {
+    /// The number of answers archived from each question, in the order the questions were archived
+    pub questions: Vec<(Uuid, u64)>,
+    /// The number of transactions the answers were moved in
+    pub batches: u32,
+}
+
+impl ArchiveReport {
+    /// The total number of answers archived.
+    pub fn answers(&self) -> u64 {
+        self.questions.iter().map(|(_, archived)| archived).sum()
+    }
+}
+
+impl Display for ArchiveReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Archived {} answers from {} questions in {} batches",
+            self.answers(), self.questions.len(), self.batches
+        )
+    }
+}
+
+/// The tombstoned answers deleted for good, see `AdminDao::purge_deleted_answers`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PurgeReport {
+    /// The answers tombstoned before this time were purged
+    pub before: DateTime<Utc>,
+    /// The number of answers deleted
+    pub answers: u64,
+    /// The number of answers tombstoned before the time that were kept, as replies to them remain
+    pub kept_with_replies: u64,
+}
+
+impl Display for PurgeReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Purged {} answers deleted before {}, kept {} with replies",
+            self.answers, self.before.to_rfc3339(), self.kept_with_replies
+        )
+    }
+}
+
+/// The like counters corrected from the like audit log, see `AdminDao::recount_likes`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RecountReport {
+    /// The number of questions whose likes were corrected
+    pub questions: u64,
+    /// The number of answers whose likes were corrected
+    pub answers: u64,
+}
+
+impl Display for RecountReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Recounted likes, corrected {} questions and {} answers", self.questions, self.answers)
+    }
+}
+
+/// The default maximum number of questions that can be pinned at once.
+pub const DEFAULT_MAX_PINNED: u32 = 5;
+
+/// The default number of minutes within which repeated views of a question by the same client count once.
+pub const DEFAULT_VIEW_WINDOW_MINUTES: i64 = 30;
+
+/// What happens to the answers of a question when the question is deleted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DeletePolicy {
+    /// The question cannot be deleted while it has answers
+    Restrict,
+    /// The answers are deleted along with the question
+    #[default]
+    Cascade,
+    /// The answers are kept, without a question
+    Orphan,
+}
+
+/// Whether new answers are shown immediately or held back for review.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ModerationMode {
+    /// Answers are shown as soon as they are published
+    #[default]
+    Off,
+    /// Answers are shown once they were published the given number of minutes ago, or earlier if approved
+    DelayMinutes(u32),
+    /// Answers are shown once a moderator approves them
+    Manual,
+}
+
+/// The format of question and answer content, telling clients how to render it.
+///
+/// Formats added by later versions are read as `Unknown` rather than failing the read, so that older binaries keep
+/// working against a newer database. They are stored and serialized under their own name, but cannot be written.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum ContentType {
+    /// Plain text, rendered as is
+    #[default]
+    Text,
+    /// Markdown, rendered with its formatting and code blocks
+    Markdown,
+    /// A format this version does not know, holding the name it is stored under
+    Unknown(String),
+}
+
+impl ContentType {
+    /// The names of the content types this version knows.
+    pub const VARIANTS: [&'static str; 2] = ["text", "markdown"];
+
+    /// The name the content type is stored under.
+    pub fn as_str(&self) -> &str {
+        match self {
+            ContentType::Text => "text",
+            ContentType::Markdown => "markdown",
+            ContentType::Unknown(name) => name,
+        }
+    }
+
+    /// Ensures the content type is one this version knows, so that an `Unknown` one is never written.
+    pub fn validate(&self) -> Result<(), DbError> {
+        match self {
+            ContentType::Unknown(name) => Err(DbError::validation(format!(
+                "unsupported content type `{name}`, expected one of: {}",
+                ContentType::VARIANTS.join(", ")
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl From<&str> for ContentType {
+    fn from(name: &str) -> Self {
+        match name {
+            "text" => ContentType::Text,
+            "markdown" => ContentType::Markdown,
+            name => ContentType::Unknown(name.to_string()),
+        }
+    }
+}
+
+impl Serialize for ContentType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for ContentType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(ContentType::from(String::deserialize(deserializer)?.as_str()))
+    }
+}
+
+impl sqlx::Type<sqlx::Postgres> for ContentType {
+    fn type_info() -> sqlx::postgres::PgTypeInfo {
+        <str as sqlx::Type<sqlx::Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &sqlx::postgres::PgTypeInfo) -> bool {
+        <str as sqlx::Type<sqlx::Postgres>>::compatible(ty)
+    }
+}
+
+impl sqlx::Encode<'_, sqlx::Postgres> for ContentType {
+    fn encode_by_ref(&self, buf: &mut sqlx::postgres::PgArgumentBuffer) -> sqlx::encode::IsNull {
+        <&str as sqlx::Encode<sqlx::Postgres>>::encode(self.as_str(), buf)
+    }
+}
+
+impl<'r> sqlx::Decode<'r, sqlx::Postgres> for ContentType {
+    fn decode(value: sqlx::postgres::PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+        Ok(ContentType::from(<&str as sqlx::Decode<sqlx::Postgres>>::decode(value)?))
+    }
+}
+
+/// The kinds of manual link between two questions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+pub enum LinkKind {
+    /// The questions are related, which holds in both directions
+    Related,
+    /// The linking question duplicates the linked question
+    DuplicateOf,
+    /// The linking question follows up on the linked question
+    FollowUp,
+}
+
+impl LinkKind {
+    /// Whether the link holds in both directions, so that it is listed from either question.
+    pub fn is_symmetric(&self) -> bool {
+        matches!(self, LinkKind::Related)
+    }
+}
+
+/// The outcome of an idempotent creation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CreateOutcome {
+    /// The entity was newly created with the given id
+    Created(Uuid),
+    /// The entity had already been created by an earlier request with the same key
+    AlreadyCreated(Uuid),
+}
+
+impl CreateOutcome {
+    /// The id of the entity, whether or not it was newly created.
+    pub fn id(&self) -> Uuid {
+        match self {
+            CreateOutcome::Created(id) | CreateOutcome::AlreadyCreated(id) => *id,
+        }
+    }
+}
+
+/// The outcome of an upsert keyed by an external id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpsertOutcome {
+    /// The entity was newly created with the given id
+    Created(Uuid),
+    /// The existing entity with the given id was updated with new content
+    Updated(Uuid),
+    /// The existing entity with the given id already had the same content
+    Unchanged(Uuid),
+}
+
+impl UpsertOutcome {
+    /// The id of the entity, whether or not it was changed.
+    pub fn id(&self) -> Uuid {
+        match self {
+            UpsertOutcome::Created(id) | UpsertOutcome::Updated(id) | UpsertOutcome::Unchanged(id) => *id,
+        }
+    }
+}
+
+/// A user's vote on whether an answer was helpful.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnswerVote {
+    /// The answer was helpful
+    Helpful,
+    /// The answer was not helpful
+    Unhelpful,
+}
+
+impl AnswerVote {
+    /// The name the vote is stored under.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnswerVote::Helpful => "helpful",
+            AnswerVote::Unhelpful => "unhelpful",
+        }
+    }
+}
+
+/// The outcome of a user voting on an answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoteOutcome {
+    /// The user had not voted on the answer before, the vote was counted
+    Recorded,
+    /// The user had cast the opposite vote, which was replaced by the new vote
+    Switched,
+    /// The user had already cast the same vote, nothing changed
+    Unchanged,
+}
+
+/// The outcome of a client viewing a question.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewOutcome {
+    /// The client had not viewed the question within the view window, the view was counted
+    Counted,
+    /// The client had already viewed the question within the view window, nothing changed
+    Repeated,
+}
+
+/// The kinds of entity that can be liked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LikableEntity {
+    /// A question
+    Question,
+    /// An answer
+    Answer,
+}
+
+/// A like received from a request, naming the kind of entity and its id.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LikeTarget {
+    /// The kind of entity being liked
+    pub entity: LikableEntity,
+    /// The id of the entity being liked
+    pub id: String,
+}
+
+/// Whether a `LikeOp` likes an entity or takes a like back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LikeAction {
+    /// The user likes the entity
+    Like,
+    /// The user no longer likes the entity
+    Unlike,
+}
+
+/// A like or unlike made by a user while offline, synced later as part of a batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LikeOp {
+    /// The kind of entity being liked
+    pub entity: LikableEntity,
+    /// The id of the entity being liked
+    pub id: EntityId,
+    /// Whether the entity is liked or unliked
+    pub op: LikeAction,
+    /// When the user made the change, by the client's clock. Of the changes a user makes to an entity, the latest
+    /// is kept, so replayed or late changes do not undo newer ones
+    pub client_ts: DateTime<Utc>,
+}
+
+/// The outcome of a single `LikeOp` of a batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LikeOutcome {
+    /// The like was added or taken back, changing the like counter of the entity
+    Applied,
+    /// The user's like was already in the requested state, or a later change was already applied, nothing changed
+    AlreadyApplied,
+    /// The entity does not exist, for example because it was deleted after the like was made
+    EntityMissing,
+    /// The operation cannot be applied for the given reason
+    Invalid(String),
+}
+
+/// The outcomes of a batch of likes, in the order of its operations.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LikeBatchReport {
+    /// The outcome of each operation, at the same index as the operation
+    pub outcomes: Vec<LikeOutcome>,
+}
+
+impl LikeBatchReport {
+    /// The number of operations that changed a like counter.
+    pub fn applied(&self) -> usize {
+        self.outcomes.iter().filter(|outcome| **outcome == LikeOutcome::Applied).count()
+    }
+}
+
+/// A boxed error of any backend, kept as the `source` of a `DbError`.
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// The errors returned by the data access objects, classified independently of the database backend. The error the
+/// backend reported, if any, is kept as the `source` of the variant, so that callers can decide how to respond to an
+/// error without depending on the backend's error type.
+#[derive(Debug)]
+pub enum DbError {
+    /// The requested entity does not exist
+    NotFound {
+        /// The kind of the entity, such as `"question"`, or `"entity"` if the operation did not name it
+        entity: &'static str,
+        /// The id of the entity, if known
+        id: Option<String>,
+        /// The error reported by the backend, if any
+        source: Option<BoxError>,
+    },
+    /// An id could not be parsed
+    InvalidId {
+        /// Why the id could not be parsed
+        reason: &'static str,
+        /// The error reported while parsing the id, if any
+        source: Option<BoxError>,
+    },
+    /// The operation conflicts with the current state of the database
+    Conflict {
+        /// What the operation conflicts with
+        reason: ConflictReason,
+        /// The error reported by the backend, if any
+        source: Option<BoxError>,
+    },
+    /// The input was rejected, before or by the database
+    Validation {
+        /// Why the input was rejected
+        reason: ValidationReason,
+        /// The error reported by the backend, if any
+        source: Option<BoxError>,
+    },
+    /// The database or the caller's allowance is temporarily unavailable, retrying later may succeed
+    Unavailable {
+        /// What is unavailable
+        reason: UnavailableReason,
+        /// The error reported by the backend, if any
+        source: Option<BoxError>,
+    },
+    /// Any other failure, the details of which are available through `source`
+    Internal {
+        /// The operation that failed
+        reason: InternalReason,
+        /// The error reported by the backend, if any
+        source: Option<BoxError>,
+    },
+}
+
+/// Why an operation conflicts with the current state of the database, see `DbError::Conflict`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConflictReason {
+    /// The conflict described by the message, such as a reused idempotency key
+    State(String),
+    /// The question with the given id is locked
+    Locked(Uuid),
+    /// The entity cannot be deleted while other entities depend on it
+    HasDependents {
+        /// The number of dependent entities
+        count: i64,
+    },
+    /// A unique, foreign key or exclusion constraint of the database was violated, see `source`
+    Constraint,
+}
+
+/// Why the input of an operation was rejected, see `DbError::Validation`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationReason {
+    /// The input is invalid as described by the message
+    Input(String),
+    /// The content was rejected by the `ContentPolicy` of the data access object, for the given reason
+    PolicyViolation(String),
+    /// The answer with the given id is a reply, which cannot be replied to
+    NestedReply(Uuid),
+    /// The operation would exceed a configured limit
+    LimitExceeded {
+        /// The limit that would be exceeded
+        limit: u32,
+    },
+    /// A value was rejected by the database, such as by a check constraint or as invalid input syntax, see `source`
+    Constraint,
+}
+
+/// What is unavailable to an operation, see `DbError::Unavailable`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnavailableReason {
+    /// The database timed out, lost the connection, failed to serialize the transaction or is shutting down or out
+    /// of connections, so retrying the operation shortly is likely to succeed
+    Transient,
+    /// The database cannot be reached, such as because the pool was closed, until it is reconfigured
+    Unreachable,
+    /// The author has created as many entities as their `CreationQuota` allows within the last hour
+    QuotaExceeded {
+        /// How long until the author can create another entity
+        retry_after: Duration,
+    },
+}
+
+/// The operation that failed, see `DbError::Internal`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InternalReason {
+    /// The database could not be queried
+    Query,
+    /// A connection to the database could not be established
+    Connect,
+    /// An entity could not be inserted
+    Create,
+    /// A row could not be decoded into a model
+    Decode,
+    /// An entity could not be updated
+    Update,
+    /// An entity could not be deleted
+    Delete,
+    /// A transaction could not be committed
+    Commit,
+    /// The database schema is older than the schema the crate was built against
+    SchemaMismatch {
+        /// The version of the latest migration the crate was built with
+        expected: i64,
+        /// The version of the latest migration applied to the database, zero if none were
+        found: i64,
+        /// The names of the migrations that have not been applied, oldest first
+        missing: Vec<String>,
+    },
+}
+
+/// The progress of a batch operation that failed part way through, kept as the `source` of the `DbError` that
+/// stopped it, see `DbError::stopped_after`.
+#[derive(Debug)]
+pub struct PartialBatch {
+    /// The number of rows affected by the batches that were committed
+    pub completed: u64,
+    /// The error reported by the backend for the batch that failed, if any
+    source: Option<BoxError>,
+}
+
+impl Display for PartialBatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Batch operation stopped after {} rows were affected", self.completed)
+    }
+}
+
+impl std::error::Error for PartialBatch {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source.as_deref().map(|e| e as &(dyn std::error::Error + 'static))
+    }
+}
+
+/// A backend-neutral classification of a `DbError`, so that callers can decide how to respond to an error, such as
+/// which status to report, without matching on the reasons of the variants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DbErrorKind {
+    /// The requested entity does not exist
+    NotFound,
+    /// An id could not be parsed
+    InvalidId,
+    /// The operation conflicts with the current state of the database, such as a duplicate or a missing reference
+    Conflict,
+    /// The input was rejected, before or by the database
+    Validation,
+    /// The database or the caller's allowance is temporarily unavailable, retrying later may succeed
+    Unavailable,
+    /// Any other failure, the details of which are available through `source`
+    Internal,
+}
+
+impl DbError {
+    /// A `DbError::NotFound` for the entity of the given kind and id, such as `DbError::not_found("question", id)`.
+    pub fn not_found(entity: &'static str, id: impl ToString) -> Self {
+        DbError::NotFound { entity, id: Some(id.to_string()), source: None }
+    }
+
+    /// A `DbError::InvalidId` for an id that could not be parsed for the given reason.
+    pub fn invalid_id(reason: &'static str) -> Self {
+        DbError::InvalidId { reason, source: None }
+    }
+
+    /// A `DbError::Validation` for input that is invalid as described by `message`.
+    pub fn validation(message: impl Into<String>) -> Self {
+        ValidationReason::Input(message.into()).into()
+    }
+
+    /// A `DbError::Conflict` for a conflict with the current state of the database described by `message`.
+    pub fn conflict(message: impl Into<String>) -> Self {
+        ConflictReason::State(message.into()).into()
+    }
+
+    /// Keeps `source` as the cause of the error, replacing any cause it had.
+    pub fn caused_by(mut self, source: impl Into<BoxError>) -> Self {
+        *self.source_mut() = Some(source.into());
+        self
+    }
+
+    /// Reports that the error stopped a batch operation after `completed` rows were affected by the batches that
+    /// were committed, keeping the variant and reason of the error.
+    pub fn stopped_after(mut self, completed: u64) -> Self {
+        let source = self.source_mut();
+        let batch = PartialBatch { completed, source: source.take() };
+        *source = Some(Box::new(batch));
+        self
+    }
+
+    /// The progress of the batch operation the error stopped, if it stopped one, see `stopped_after`.
+    pub fn partial_batch(&self) -> Option<&PartialBatch> {
+        self.source_ref().and_then(|source| source.downcast_ref())
+    }
+
+    /// Separates the progress of the batch operation the error stopped from the error, restoring the cause of the
+    /// batch that failed as its `source`.
+    pub(crate) fn take_partial_batch(&mut self) -> Option<u64> {
+        let source = self.source_mut();
+        match source.take().map(|source| source.downcast::<PartialBatch>()) {
+            Some(Ok(batch)) => {
+                *source = batch.source;
+                Some(batch.completed)
+            }
+            Some(Err(other)) => {
+                *source = Some(other);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Attaches the kind and id of the entity the error concerns, so its message can name the entity.
+    ///
+    /// # Parameters
+    /// `entity`: The kind of the entity, such as `"question"`
+    /// `id`: The id of the entity, as given by the caller
+    pub fn with_context(self, entity: &'static str, id: impl ToString) -> DbErrorContext {
+        DbErrorContext { entity, id: Some(id.to_string()), error: self }
+    }
+
+    /// The raw details of the error reported by the backend, which are kept out of the `Display` message since they
+    /// name tables, constraints and SQLSTATE codes. Intended for logs that are not shown to clients.
+    ///
+    /// # Returns
+    /// An `Option<String>`, `Some(String)` describing the error reported by the backend, otherwise `None`.
+    pub fn debug_details(&self) -> Option<String> {
+        match self.partial_batch() {
+            Some(batch) => batch.source.as_ref().map(|e| e.to_string()),
+            None => self.source_ref().map(|e| e.to_string()),
+        }
+    }
+
+    /// Whether the error is likely to be temporary, so that retrying the operation later, or serving a previously
+    /// read value in the meantime, is reasonable. Timeouts, lost connections, serialization failures, deadlocks
+    /// and a database that is shutting down or out of connections are transient, while invalid input, missing
+    /// entities, constraint violations and exceeded quotas are not.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, DbError::Unavailable { reason: UnavailableReason::Transient, .. })
+    }
+
+    /// The backend-neutral classification of the error, which is its variant.
+    pub fn kind(&self) -> DbErrorKind {
+        match self {
+            DbError::NotFound { .. } => DbErrorKind::NotFound,
+            DbError::InvalidId { .. } => DbErrorKind::InvalidId,
+            DbError::Conflict { .. } => DbErrorKind::Conflict,
+            DbError::Validation { .. } => DbErrorKind::Validation,
+            DbError::Unavailable { .. } => DbErrorKind::Unavailable,
+            DbError::Internal { .. } => DbErrorKind::Internal,
+        }
+    }
+
+    /// Classifies a driver error, reporting any error that is not classified otherwise as a failure of the
+    /// `otherwise` operation. A missing row is not found, whichever operation read it, and the driver error is kept
+    /// as the `source`.
+    pub(crate) fn classify(e: Error, otherwise: InternalReason) -> Self {
+        let code = e.as_database_error().and_then(|db_err| db_err.code()).map(|code| code.into_owned());
+        let error: DbError = match (&e, code.as_deref()) {
+            (Error::RowNotFound, _) => DbError::NotFound { entity: "entity", id: None, source: None },
+            (Error::PoolTimedOut | Error::Io(_), _) => UnavailableReason::Transient.into(),
+            (Error::PoolClosed | Error::Tls(_), _) => UnavailableReason::Unreachable.into(),
+            (
+                Error::ColumnDecode { .. } | Error::ColumnNotFound(_) | Error::ColumnIndexOutOfBounds { .. } | Error::Decode(_),
+                _,
+            ) => InternalReason::Decode.into(),
+            // Unique, foreign key and exclusion violations
+            (Error::Database(_), Some("23505" | "23503" | "23P01")) => ConflictReason::Constraint.into(),
+            // Other integrity constraint violations and data exceptions, such as invalid input syntax
+            (Error::Database(_), Some(code)) if code.starts_with("23") || code.starts_with("22") => ValidationReason::Constraint.into(),
+            (Error::Database(_), code) if DbError::transient_code(code) => UnavailableReason::Transient.into(),
+            _ => otherwise.into(),
+        };
+        error.caused_by(e)
+    }
+
+    /// Classifies a driver error raised while inserting an entity.
+    pub(crate) fn creation(e: Error) -> Self {
+        DbError::classify(e, InternalReason::Create)
+    }
+
+    /// Classifies a driver error raised while updating an entity.
+    pub(crate) fn update(e: Error) -> Self {
+        DbError::classify(e, InternalReason::Update)
+    }
+
+    /// Classifies a driver error raised while deleting an entity.
+    pub(crate) fn deletion(e: Error) -> Self {
+        DbError::classify(e, InternalReason::Delete)
+    }
+
+    /// Classifies a driver error raised while committing a transaction.
+    pub(crate) fn commit(e: Error) -> Self {
+        DbError::classify(e, InternalReason::Commit)
+    }
+
+    /// Classifies a driver error raised while decoding a row.
+    pub(crate) fn from_row(e: Error) -> Self {
+        DbError::classify(e, InternalReason::Decode)
+    }
+
+    /// Classifies a driver error raised while connecting to the database.
+    pub(crate) fn connection(e: Error) -> Self {
+        DbError::classify(e, InternalReason::Connect)
+    }
+
+    /// Whether a SQLSTATE code denotes a transient failure, see `is_transient`.
+    fn transient_code(code: Option<&str>) -> bool {
+        // Connection exceptions, serialization failures, deadlocks, lock and statement timeouts, and shutdowns
+        code.is_some_and(|code| {
+            code.starts_with("08") || matches!(code, "40001" | "40P01" | "55P03" | "57014" | "57P01" | "57P02" | "57P03" | "53300")
+        })
+    }
+
+    /// The cause of the error, whichever the variant.
+    fn source_ref(&self) -> Option<&BoxError> {
+        match self {
+            DbError::NotFound { source, .. }
+            | DbError::InvalidId { source, .. }
+            | DbError::Conflict { source, .. }
+            | DbError::Validation { source, .. }
+            | DbError::Unavailable { source, .. }
+            | DbError::Internal { source, .. } => source.as_ref(),
+        }
+    }
+
+    /// The cause of the error, whichever the variant.
+    fn source_mut(&mut self) -> &mut Option<BoxError> {
+        match self {
+            DbError::NotFound { source, .. }
+            | DbError::InvalidId { source, .. }
+            | DbError::Conflict { source, .. }
+            | DbError::Validation { source, .. }
+            | DbError::Unavailable { source, .. }
+            | DbError::Internal { source, .. } => source,
+        }
+    }
+
+    /// Writes the message of the error, naming the entity it concerns as `subject`.
+    fn write_message(&self, f: &mut std::fmt::Formatter<'_>, subject: &str) -> std::fmt::Result {
+        if let Some(batch) = self.partial_batch() {
+            write!(f, "{batch}: ")?;
+        }
+        match self {
+            DbError::NotFound { .. } => write!(f, "The {subject} was not found"),
+            DbError::InvalidId { reason, .. } => write!(f, "The id of the {subject} is invalid: {reason}"),
+            DbError::Conflict { reason, .. } => match reason {
+                ConflictReason::State(s) => write!(f, "Conflict error: {s}"),
+                ConflictReason::Locked(id) => write!(f, "Question {id} is locked"),
+                ConflictReason::HasDependents { count } => write!(f, "Cannot delete the {subject} while it has {count} dependents"),
+                ConflictReason::Constraint => write!(f, "The {subject} conflicts with the data in the database"),
+            },
+            DbError::Validation { reason, .. } => match reason {
+                ValidationReason::Input(s) => write!(f, "Validation error: {s}"),
+                ValidationReason::PolicyViolation(s) => write!(f, "The {subject} violates the content policy: {s}"),
+                ValidationReason::NestedReply(id) => write!(f, "Answer {id} is a reply and cannot be replied to"),
+                ValidationReason::LimitExceeded { limit } => write!(f, "The limit of {limit} has been reached"),
+                ValidationReason::Constraint => write!(f, "The {subject} has a value the database does not accept"),
+            },
+            DbError::Unavailable { reason, .. } => match reason {
+                UnavailableReason::Transient => write!(f, "The database is temporarily unavailable"),
+                UnavailableReason::Unreachable => write!(f, "The database could not be connected to"),
+                UnavailableReason::QuotaExceeded { retry_after } => {
+                    write!(f, "The creation quota has been exceeded, retry after {} seconds", retry_after.num_seconds().max(1))
+                }
+            },
+            DbError::Internal { reason, .. } => match reason {
+                InternalReason::Query => write!(f, "The database could not be accessed"),
+                InternalReason::Connect => write!(f, "The database could not be connected to"),
+                InternalReason::Create => write!(f, "The {subject} could not be created"),
+                InternalReason::Decode => write!(f, "The {subject} could not be read from the database"),
+                InternalReason::Update => write!(f, "The {subject} could not be updated"),
+                InternalReason::Delete => write!(f, "The {subject} could not be deleted"),
+                InternalReason::Commit => write!(f, "The changes to the {subject} could not be committed"),
+                InternalReason::SchemaMismatch { expected, found, missing } => write!(
+                    f,
+                    "The database schema is at version {found} but version {expected} is expected, missing migrations: {}",
+                    missing.join(", ")
+                ),
+            },
+        }
+    }
+}
+
+impl From<ConflictReason> for DbError {
+    fn from(reason: ConflictReason) -> Self {
+        DbError::Conflict { reason, source: None }
+    }
+}
+
+impl From<ValidationReason> for DbError {
+    fn from(reason: ValidationReason) -> Self {
+        DbError::Validation { reason, source: None }
+    }
+}
+
+impl From<UnavailableReason> for DbError {
+    fn from(reason: UnavailableReason) -> Self {
+        DbError::Unavailable { reason, source: None }
+    }
+}
+
+impl From<InternalReason> for DbError {
+    fn from(reason: InternalReason) -> Self {
+        DbError::Internal { reason, source: None }
+    }
+}
+
+/// Classifies a driver error, for errors not raised by a specific operation. This is the only place driver errors
+/// are classified: missing rows are `NotFound`, constraint violations `Conflict` or `Validation`, failures to reach
+/// the database `Unavailable` and any other error `Internal`, each keeping the driver error as its `source`.
+impl From<Error> for DbError {
+    fn from(e: Error) -> Self {
+        DbError::classify(e, InternalReason::Query)
+    }
+}
+
+/// Describes the error without the details of the error reported by the backend, which are available through
+/// `source` and `debug_details`. A `NotFound` error names the entity it concerns.
+impl Display for DbError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DbError::NotFound { entity, id: Some(id), .. } => self.write_message(f, &format!("{entity} {id}")),
+            DbError::NotFound { entity, id: None, .. } => self.write_message(f, entity),
+            _ => self.write_message(f, "entity"),
+        }
+    }
+}
+
+impl std::error::Error for DbError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source_ref().map(|e| e.as_ref() as &(dyn std::error::Error + 'static))
+    }
+}
+
+/// A `DbError` along with the entity it concerns, whose message names the entity.
+#[derive(Debug)]
+pub struct DbErrorContext {
+    /// The kind of the entity, such as `"question"`
+    pub entity: &'static str,
+    /// The id of the entity, if known
+    pub id: Option<String>,
+    /// The error itself
+    pub error: DbError,
+}
+
+impl Display for DbErrorContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.id {
+            Some(id) => self.error.write_message(f, &format!("{} {id}", self.entity)),
+            None => self.error.write_message(f, self.entity),
+        }
+    }
+}
+
+impl std::error::Error for DbErrorContext {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
 
 
 
@@ -0,0 +1,29 @@
+//! Contains the normalization applied to text before it is compared, so that strings a reader would consider
+//! the same, such as "Café", "cafe" and "ｃａｆｅ", compare equal.
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// Normalizes `text` for comparison. Compatibility characters such as fullwidth letters are replaced by their
+/// canonical equivalents (NFKC), the text is case folded and, if `strip_accents` is set, accents are removed.
+///
+/// # Parameters
+/// `text`: The text being normalized
+/// `strip_accents`: Whether accented letters are replaced by their unaccented letters
+///
+/// # Returns
+/// The normalized text, in NFC.
+pub fn normalize(text: &str, strip_accents: bool) -> String {
+    // Lowercasing can produce combining marks, so the result is recomposed afterwards
+    let folded = text.nfkc().flat_map(char::to_lowercase);
+    if strip_accents {
+        folded.collect::<String>().nfd().filter(|c| !is_combining_mark(*c)).nfc().collect()
+    } else {
+        folded.nfc().collect()
+    }
+}
+
+/// Normalizes a question title for the `title_normalized` column, stripping accents.
+pub fn normalize_title(title: &str) -> String {
+    normalize(title, true)
+}
@@ -0,0 +1,51 @@
+//! Contains the calendar periods questions can be filtered by, and the computation of their boundaries in the
+//! time zone of a community rather than in UTC.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// A calendar period containing the current time, as seen in a community's time zone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    /// The current local day
+    Today,
+    /// The current local week, starting on Monday
+    ThisWeek,
+    /// The current local month
+    ThisMonth,
+}
+
+impl Period {
+    /// Computes the boundaries of the period containing `now` in `tz`, as the first instant of the period and the
+    /// first instant of the next one, so that an instant `t` is in the period if `start <= t < end`.
+    ///
+    /// Days are not assumed to last 24 hours, so a day shortened or lengthened by a daylight saving transition
+    /// covers exactly its local hours.
+    pub fn bounds(&self, now: DateTime<Utc>, tz: Tz) -> (DateTime<Utc>, DateTime<Utc>) {
+        let today = now.with_timezone(&tz).date_naive();
+        let (first, next) = match self {
+            Period::Today => (today, today + Duration::days(1)),
+            Period::ThisWeek => {
+                let monday = today - Duration::days(i64::from(today.weekday().num_days_from_monday()));
+                (monday, monday + Duration::days(7))
+            }
+            Period::ThisMonth => {
+                let first = today.with_day(1).expect("every month has a first day");
+                let next = first.checked_add_months(chrono::Months::new(1)).expect("the next month is in range");
+                (first, next)
+            }
+        };
+        (start_of_day(first, tz), start_of_day(next, tz))
+    }
+}
+
+/// The first instant of a local day. Where a daylight saving transition skips midnight, the day starts when the
+/// clocks resume, and where midnight occurs twice, it starts at the first one.
+fn start_of_day(date: NaiveDate, tz: Tz) -> DateTime<Utc> {
+    let midnight = date.and_hms_opt(0, 0, 0).expect("midnight is a valid time");
+    // Transitions move the clocks by whole minutes, so the first local minute that exists ends the gap
+    (0..24 * 60)
+        .find_map(|minute| tz.from_local_datetime(&(midnight + Duration::minutes(minute))).earliest())
+        .expect("a daylight saving transition is shorter than a day")
+        .with_timezone(&Utc)
+}
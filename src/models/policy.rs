@@ -0,0 +1,86 @@
+//! Contains the hook deployments use to apply their own content rules to questions and answers, rejecting or
+//! flagging content for moderation as it is created or edited.
+
+use std::collections::HashSet;
+use unicode_segmentation::UnicodeSegmentation;
+use super::normalize::normalize;
+
+/// The kinds of content a `ContentPolicy` checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContentKind {
+    /// The title of a question
+    Title,
+    /// The content of a question
+    Question,
+    /// The content of an answer
+    Answer,
+}
+
+/// The decision of a `ContentPolicy` about a piece of content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyDecision {
+    /// The content is stored as is
+    Allow,
+    /// The content is not stored, the reason being reported as `ValidationReason::PolicyViolation`
+    Reject(String),
+    /// The content is stored along with a moderation flag giving the reason
+    Flag(String),
+}
+
+/// Rules content must follow, checked after the content is validated and before it is stored.
+///
+/// Policies are called while the request creating or editing the content waits, so they should not block.
+pub trait ContentPolicy: Send + Sync {
+    /// Decides whether `text` is allowed, rejected or flagged for moderation.
+    ///
+    /// # Parameters
+    /// `kind`: The kind of content `text` is
+    /// `text`: The content being checked
+    fn check(&self, kind: ContentKind, text: &str) -> PolicyDecision;
+}
+
+/// A reference `ContentPolicy` rejecting, or flagging, content containing any word of a deny list.
+///
+/// Words are compared whole, ignoring case and accents, so a denied word does not match within a longer word.
+#[derive(Debug, Clone)]
+pub struct DenyListPolicy {
+    words: HashSet<String>,
+    flag: bool,
+}
+
+impl DenyListPolicy {
+    /// Creates a policy rejecting content containing any of `words`.
+    pub fn new<I, S>(words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self {
+            words: words.into_iter().map(|word| normalize(word.as_ref().trim(), true)).filter(|word| !word.is_empty()).collect(),
+            flag: false,
+        }
+    }
+
+    /// Flags content containing a denied word for moderation instead of rejecting it.
+    pub fn flagging(mut self) -> Self {
+        self.flag = true;
+        self
+    }
+}
+
+impl ContentPolicy for DenyListPolicy {
+    fn check(&self, kind: ContentKind, text: &str) -> PolicyDecision {
+        let denied = text.unicode_words().map(|word| normalize(word, true)).find(|word| self.words.contains(word));
+        match denied {
+            None => PolicyDecision::Allow,
+            Some(word) => {
+                let reason = format!("{} contains the denied word `{word}`", match kind {
+                    ContentKind::Title => "title",
+                    ContentKind::Question => "question",
+                    ContentKind::Answer => "answer",
+                });
+                if self.flag { PolicyDecision::Flag(reason) } else { PolicyDecision::Reject(reason) }
+            }
+        }
+    }
+}
@@ -0,0 +1,46 @@
+//! Contains the rendering of question and answer content to HTML, available with the `render` feature.
+
+use pulldown_cmark::escape::escape_html;
+use pulldown_cmark::{html, Event, Options, Parser, Tag};
+use super::ContentType;
+
+/// The URL schemes links and images may not use, since following them runs code in the reader's browser.
+const UNSAFE_SCHEMES: [&str; 3] = ["javascript:", "vbscript:", "data:"];
+
+/// Renders content to HTML according to its `ContentType`.
+///
+/// Plain text is escaped entirely and rendered as one paragraph, keeping its line breaks. Markdown is rendered with
+/// its formatting, fenced code blocks, tables and strikethrough. Raw HTML within markdown is escaped and shown as
+/// text, and links and images with a script or data URL lose their destination. Content of an `Unknown` type is
+/// rendered as plain text, since escaping it entirely is always safe.
+pub fn render_html(content: &str, content_type: ContentType) -> String {
+    let mut rendered = String::with_capacity(content.len() * 3 / 2);
+    match content_type {
+        ContentType::Text | ContentType::Unknown(_) => {
+            rendered.push_str("<p>");
+            for (i, line) in content.lines().enumerate() {
+                if i > 0 {
+                    rendered.push_str("<br />\n");
+                }
+                escape_html(&mut rendered, line).expect("writing to a String cannot fail");
+            }
+            rendered.push_str("</p>\n");
+        }
+        ContentType::Markdown => {
+            let events = Parser::new_ext(content, Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH).map(|event| match event {
+                Event::Html(raw) => Event::Text(raw),
+                Event::Start(Tag::Link(kind, url, title)) if is_unsafe_url(&url) => Event::Start(Tag::Link(kind, "".into(), title)),
+                Event::Start(Tag::Image(kind, url, title)) if is_unsafe_url(&url) => Event::Start(Tag::Image(kind, "".into(), title)),
+                event => event,
+            });
+            html::push_html(&mut rendered, events);
+        }
+    }
+    rendered
+}
+
+/// Whether a link or image destination uses one of the `UNSAFE_SCHEMES`, ignoring case and surrounding whitespace.
+fn is_unsafe_url(url: &str) -> bool {
+    let url = url.trim_start().to_ascii_lowercase();
+    UNSAFE_SCHEMES.iter().any(|scheme| url.starts_with(scheme))
+}
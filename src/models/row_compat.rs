@@ -0,0 +1,52 @@
+//! Tests ensuring rows decode into the models regardless of which optional columns are present.
+
+use sqlx::{FromRow, PgPool};
+use sqlx::types::Uuid;
+use super::Question;
+
+#[sqlx::test]
+async fn question_should_decode_without_optional_columns(pool: PgPool) {
+    let row = sqlx::query("SELECT gen_random_uuid() AS id, 'Test Question' AS title, 'Hello this question is a test' AS question, 3::bigint AS likes, now() AS created_at")
+        .fetch_one(&pool)
+        .await
+        .expect("row should be selected successfully");
+    let question = Question::from_row(&row);
+    println!("{:?}", question);
+    assert!(question.is_ok());
+    let question = question.unwrap();
+    assert_eq!(question.likes(), 3);
+    assert!(question.updated_at().is_none());
+    assert!(question.slug().is_none());
+    assert!(question.status().is_none());
+    assert_eq!(question.views(), 0);
+}
+
+#[sqlx::test]
+async fn question_should_decode_with_optional_and_unknown_columns(pool: PgPool) {
+    let id = Uuid::new_v4();
+    let row = sqlx::query("SELECT $1 AS id, 'Test Question' AS title, 'Hello this question is a test' AS question, 3::bigint AS likes, now() AS created_at, now() AS updated_at, 'test-question' AS slug, 'open' AS status, 7 AS views, 'ignored' AS unknown_column")
+        .bind(id)
+        .fetch_one(&pool)
+        .await
+        .expect("row should be selected successfully");
+    let question = Question::from_row(&row);
+    println!("{:?}", question);
+    assert!(question.is_ok());
+    let question = question.unwrap();
+    assert_eq!(question.id(), id);
+    assert!(question.updated_at().is_some());
+    assert_eq!(question.slug(), Some("test-question"));
+    assert_eq!(question.status(), Some("open"));
+    assert_eq!(question.views(), 7);
+}
+
+#[sqlx::test]
+async fn question_should_fail_to_decode_without_required_columns(pool: PgPool) {
+    let row = sqlx::query("SELECT gen_random_uuid() AS id, 'Test Question' AS title")
+        .fetch_one(&pool)
+        .await
+        .expect("row should be selected successfully");
+    let question = Question::from_row(&row);
+    println!("{:?}", question);
+    assert!(question.is_err());
+}
@@ -0,0 +1,1305 @@
+use proptest::prelude::*;
+use sqlx::types::Uuid;
+use super::{EntityId, EntityIdKind};
+
+/// Formats `uuid` in one of the textual formats accepted by `EntityId`.
+fn format_uuid(uuid: Uuid, format: u8, uppercase: bool) -> String {
+    let mut buf = Uuid::encode_buffer();
+    let formatted = match (format % 4, uppercase) {
+        (0, false) => uuid.hyphenated().encode_lower(&mut buf),
+        (0, true) => uuid.hyphenated().encode_upper(&mut buf),
+        (1, false) => uuid.simple().encode_lower(&mut buf),
+        (1, true) => uuid.simple().encode_upper(&mut buf),
+        (2, false) => uuid.urn().encode_lower(&mut buf),
+        (2, true) => uuid.urn().encode_upper(&mut buf),
+        (_, false) => uuid.braced().encode_lower(&mut buf),
+        (_, true) => uuid.braced().encode_upper(&mut buf),
+    };
+    formatted.to_string()
+}
+
+proptest! {
+    #[test]
+    fn entity_id_should_parse_valid_uuids_in_all_formats(bits in any::<u128>(), format in any::<u8>(), uppercase in any::<bool>()) {
+        let uuid = Uuid::from_u128(bits);
+        let entity_id = EntityId::new(format_uuid(uuid, format, uppercase));
+        prop_assert_eq!(Uuid::try_from(&entity_id), Ok(uuid));
+        // Parsing does not consume the id, so it can be parsed again
+        prop_assert_eq!(Uuid::try_from(&entity_id), Ok(uuid));
+    }
+
+    #[test]
+    fn entity_id_should_round_trip(bits in any::<u128>(), format in any::<u8>(), uppercase in any::<bool>()) {
+        let uuid = Uuid::from_u128(bits);
+        let entity_id = EntityId::new(format_uuid(uuid, format, uppercase));
+        let parsed: Uuid = entity_id.clone().try_into().unwrap();
+        let round_tripped = EntityId::new(parsed.to_string());
+        prop_assert_eq!(Uuid::try_from(&round_tripped), Ok(uuid));
+        // The canonical form is always hyphenated lowercase
+        prop_assert_eq!(round_tripped.as_str(), uuid.hyphenated().to_string());
+    }
+
+    #[test]
+    fn entity_id_should_agree_with_uuid_parsing_for_arbitrary_strings(s in any::<String>()) {
+        let entity_id = EntityId::new(s.clone());
+        prop_assert_eq!(Uuid::try_from(&entity_id).ok(), Uuid::parse_str(&s).ok());
+    }
+
+    #[test]
+    fn entity_id_should_reject_corrupted_digits(bits in any::<u128>(), position in 0usize..36, corruption in "[g-zG-Z_ !#%.]") {
+        let mut id = Uuid::from_u128(bits).hyphenated().to_string();
+        // Skip over the hyphens so a hex digit is always corrupted
+        let position = if [8, 13, 18, 23].contains(&position) { position + 1 } else { position };
+        id.replace_range(position..position + 1, &corruption);
+        prop_assert!(Uuid::try_from(&EntityId::new(id)).is_err());
+    }
+
+    #[test]
+    fn entity_id_should_reject_truncated_and_padded_uuids(bits in any::<u128>(), position in 0usize..36, pad in prop::option::of("[0-9a-f]")) {
+        let mut id = Uuid::from_u128(bits).hyphenated().to_string();
+        match pad {
+            Some(digit) => id.insert_str(position, &digit),
+            None => { id.remove(position); },
+        }
+        prop_assert!(Uuid::try_from(&EntityId::new(id)).is_err());
+    }
+
+    #[test]
+    fn entity_id_should_reject_surrounding_whitespace(bits in any::<u128>(), whitespace in "[ \t\n]{1,3}") {
+        let id = format!("{whitespace}{}", Uuid::from_u128(bits));
+        prop_assert!(Uuid::try_from(&EntityId::new(id)).is_err());
+    }
+
+    #[test]
+    fn entity_id_should_parse_non_negative_integers_as_serials(serial in 0i64..) {
+        let entity_id = EntityId::new(serial.to_string());
+        prop_assert_eq!(entity_id.kind(), Ok(EntityIdKind::Serial(serial)));
+        prop_assert_eq!(&entity_id, &EntityId::serial(serial));
+        prop_assert!(Uuid::try_from(&entity_id).is_err());
+    }
+}
+
+#[test]
+fn entity_id_should_prefer_uuid_for_all_digit_uuids() {
+    // 32 decimal digits are a valid simple uuid, and too large for a serial anyway
+    let digits = "12345678901234567890123456789012";
+    assert_eq!(EntityId::new(String::from(digits)).kind(), Ok(EntityIdKind::Uuid(Uuid::parse_str(digits).unwrap())));
+}
+
+#[test]
+fn entity_id_should_reject_malformed_serials() {
+    for id in ["", "-1", "+1", " 1", "1 ", "1.0", "0x1f", "9223372036854775808"] {
+        assert!(EntityId::new(String::from(id)).kind().is_err(), "{id:?} should not parse");
+    }
+}
+
+#[test]
+fn entity_id_constructors_should_fix_the_kind() {
+    let uuid = Uuid::new_v4();
+    assert_eq!(EntityId::uuid(uuid).kind(), Ok(EntityIdKind::Uuid(uuid)));
+    assert_eq!(EntityId::uuid(uuid), EntityId::new(uuid.to_string()));
+    assert_eq!(Uuid::try_from(&EntityId::uuid(uuid)), Ok(uuid));
+    assert_eq!(EntityId::serial(123).kind(), Ok(EntityIdKind::Serial(123)));
+    assert_eq!(EntityId::serial(123).as_str(), "123");
+    // A serial is never mistaken for a uuid
+    assert!(Uuid::try_from(&EntityId::serial(123)).is_err());
+}
+
+mod dto_tests {
+    use chrono::{Duration, TimeZone, Utc};
+    use serde_json::json;
+    use sqlx::types::Uuid;
+    use crate::models::dto::{
+        camel, parse_body, parse_question_patch, parse_question_patch_body, parse_request, snake, to_json, write_ndjson, write_questions_csv, AnswerResponse, BodyErrorCode, BodyLimits, BountyResponse,
+        CountFormat, EngagementRecord, FieldCase, InputMode, ListFormat, QuestionDetailResponse, QuestionHeaderResponse, QuestionResponse,
+        MAX_JSON_DEPTH, MAX_SAFE_INTEGER, QUESTION_CSV_HEADER,
+    };
+    use crate::models::{
+        Answer, AnswerBodyMode, ContentLimits, ContentType, DbError, LikableEntity, LikeTarget, NewAnswer, NewQuestion, Question,
+        QuestionHeader, UpdateQuestion, ValidationReason, ANSWER_PREVIEW_CHARS, DELETED_CONTENT, MAX_AUTHOR_TOKEN_LENGTH, MIN_AUTHOR_TOKEN_LENGTH,
+    };
+
+    const QUESTION_ID: &str = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+    const ANSWER_ID: &str = "a1a2a3a4-b1b2-c1c2-d1d2-d3d4d5d6d7d8";
+
+    fn sample_question() -> Question {
+        Question::new(
+            Uuid::parse_str(QUESTION_ID).unwrap(),
+            String::from("Test Question"),
+            String::from("Hello this question is a test"),
+            3,
+            Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap(),
+        )
+    }
+
+    fn sample_answer() -> Answer {
+        Answer {
+            id: Uuid::parse_str(ANSWER_ID).unwrap(),
+            question_id: Some(Uuid::parse_str(QUESTION_ID).unwrap()),
+            answer: String::from("Test answer"),
+            likes: 1,
+            created_at: Utc.with_ymd_and_hms(2024, 1, 15, 13, 30, 0).unwrap(),
+            author_id: Some(Uuid::new_v4()),
+            serial: Some(12),
+            published: Some(true),
+            helpful_count: 0,
+            unhelpful_count: 0,
+            char_count: 0,
+            word_count: 0,
+            approved_at: None,
+            content_type: ContentType::Text,
+            is_mine: None,
+            parent_answer_id: None,
+            deleted_at: None,
+            deleted_reason: None,
+        }
+    }
+
+    #[test]
+    fn question_response_should_convert_from_question() {
+        let response = QuestionResponse::from(sample_question());
+        assert_eq!(response.id, QUESTION_ID);
+        assert_eq!(response.title, "Test Question");
+        assert_eq!(response.likes, 3);
+        assert_eq!(response.created_at, "2024-01-15T12:00:00.000000Z");
+        assert_eq!(response.updated_at, None);
+    }
+
+    #[test]
+    fn question_response_should_serialize_to_expected_shape() {
+        let json = serde_json::to_value(QuestionResponse::from(sample_question())).unwrap();
+        assert_eq!(json, json!({
+            "id": QUESTION_ID,
+            "serial": null,
+            "title": "Test Question",
+            "question": "Hello this question is a test",
+            "likes": 3,
+            "views": 0,
+            "created_at": "2024-01-15T12:00:00.000000Z",
+            "updated_at": null,
+            "pinned_at": null,
+            "pinned_answer_id": null,
+            "accepted_answer_id": null,
+            "content_type": "text",
+        }));
+    }
+
+    #[test]
+    fn question_response_should_expose_the_bounty_with_its_remaining_time() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap();
+        let mut question = sample_question();
+        question.bounty_amount = Some(50);
+        question.bounty_expires_at = Some(now + Duration::hours(2));
+        assert_eq!(question.bounty_remaining(now), Some(Duration::hours(2)));
+        let bounty = BountyResponse::of(&question, now).expect("bounty should not have expired");
+        assert_eq!(serde_json::to_value(bounty).unwrap(), json!({
+            "amount": 50,
+            "expires_at": "2024-01-15T14:00:00.000000Z",
+            "remaining_seconds": 7200,
+        }));
+        assert_eq!(QuestionResponse::at(question.clone(), now).bounty.and_then(|bounty| bounty.remaining_seconds), Some(7200));
+        // An expired bounty that has not been cleared yet is left out
+        assert_eq!(question.bounty_remaining(now + Duration::hours(2)), None);
+        assert_eq!(BountyResponse::of(&question, now + Duration::hours(3)), None);
+        let json = serde_json::to_value(QuestionResponse::at(question.clone(), now + Duration::hours(3))).unwrap();
+        assert!(json.get("bounty").is_none(), "{json}");
+    }
+
+    #[test]
+    fn question_response_should_not_depend_on_when_it_is_created() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap();
+        let mut question = sample_question();
+        question.bounty_amount = Some(50);
+        question.bounty_expires_at = Some(now + Duration::hours(2));
+        // Without a time the bounty is reported as stored, so exports are the same whenever they are made
+        assert_eq!(serde_json::to_value(QuestionResponse::from(question)).unwrap()["bounty"], json!({
+            "amount": 50,
+            "expires_at": "2024-01-15T14:00:00.000000Z",
+        }));
+    }
+
+    #[test]
+    fn answer_response_should_convert_from_answer() {
+        let response = AnswerResponse::from(sample_answer());
+        assert_eq!(response.id, ANSWER_ID);
+        assert_eq!(response.question_id.as_deref(), Some(QUESTION_ID));
+        assert_eq!(response.created_at, "2024-01-15T13:30:00.000000Z");
+    }
+
+    #[test]
+    fn answer_response_should_serialize_to_expected_shape() {
+        // The author is internal and must not leak into the response
+        let json = serde_json::to_value(AnswerResponse::from(sample_answer())).unwrap();
+        assert_eq!(json, json!({
+            "id": ANSWER_ID,
+            "serial": 12,
+            "question_id": QUESTION_ID,
+            "answer": "Test answer",
+            "likes": 1,
+            "created_at": "2024-01-15T13:30:00.000000Z",
+            "content_type": "text",
+        }));
+    }
+
+    #[test]
+    fn answer_response_should_keep_the_body_the_mode_asks_for() {
+        let with_content = |content: &str| {
+            let mut answer = sample_answer();
+            answer.answer = String::from(content);
+            answer
+        };
+        let full = AnswerResponse::with_body(with_content("Test answer"), AnswerBodyMode::Full);
+        assert_eq!(full, AnswerResponse::from(sample_answer()));
+        let preview = AnswerResponse::with_body(with_content("Test answer"), AnswerBodyMode::Preview);
+        assert_eq!((preview.answer.as_str(), preview.truncated), ("Test answer", false));
+
+        // Four bytes per character, the preview ends on a character boundary rather than a byte count
+        let emoji = "🦀".repeat(ANSWER_PREVIEW_CHARS + 1);
+        let preview = AnswerResponse::with_body(with_content(&emoji), AnswerBodyMode::Preview);
+        assert_eq!(preview.answer, "🦀".repeat(ANSWER_PREVIEW_CHARS));
+        assert!(preview.truncated);
+        let exact = "é".repeat(ANSWER_PREVIEW_CHARS);
+        let preview = AnswerResponse::with_body(with_content(&exact), AnswerBodyMode::Preview);
+        assert_eq!((preview.answer.as_str(), preview.truncated), (exact.as_str(), false));
+
+        let json = serde_json::to_value(AnswerResponse::with_body(sample_answer(), AnswerBodyMode::None)).unwrap();
+        assert_eq!(json, json!({
+            "id": ANSWER_ID,
+            "serial": 12,
+            "question_id": QUESTION_ID,
+            "likes": 1,
+            "created_at": "2024-01-15T13:30:00.000000Z",
+            "content_type": "text",
+            "truncated": true,
+        }));
+        let parsed: AnswerResponse = serde_json::from_value(json).unwrap();
+        assert!(parsed.answer.is_empty() && parsed.truncated);
+    }
+
+    #[test]
+    fn answer_body_mode_should_parse_query_values() {
+        let modes: Vec<AnswerBodyMode> = AnswerBodyMode::VARIANTS.iter().map(|mode| mode.parse().unwrap()).collect();
+        assert_eq!(modes, [AnswerBodyMode::Full, AnswerBodyMode::Preview, AnswerBodyMode::None]);
+        assert_eq!(AnswerBodyMode::default(), AnswerBodyMode::Full);
+        let res = "Preview".parse::<AnswerBodyMode>();
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+    }
+
+    #[test]
+    fn answer_response_should_serialize_tombstones() {
+        let mut answer = sample_answer();
+        answer.answer = String::from(DELETED_CONTENT);
+        answer.likes = 0;
+        answer.deleted_at = Some(Utc.with_ymd_and_hms(2024, 1, 16, 9, 0, 0).unwrap());
+        answer.deleted_reason = Some(String::from("off topic"));
+        let json = serde_json::to_value(AnswerResponse::from(answer)).unwrap();
+        assert_eq!(json, json!({
+            "id": ANSWER_ID,
+            "serial": 12,
+            "question_id": QUESTION_ID,
+            "answer": "[deleted]",
+            "likes": 0,
+            "created_at": "2024-01-15T13:30:00.000000Z",
+            "content_type": "text",
+            "is_deleted": true,
+            "deleted_reason": "off topic",
+        }));
+        let parsed: AnswerResponse = serde_json::from_value(json).unwrap();
+        assert!(parsed.is_deleted);
+    }
+
+    #[test]
+    fn question_header_response_should_serialize_to_expected_shape() {
+        let header = QuestionHeader { question: sample_question(), answer_count: 2, top_level_answer_count: 1 };
+        let json = serde_json::to_value(QuestionHeaderResponse::from(header)).unwrap();
+        assert_eq!(json, json!({
+            "id": QUESTION_ID,
+            "serial": null,
+            "title": "Test Question",
+            "question": "Hello this question is a test",
+            "likes": 3,
+            "views": 0,
+            "answer_count": 2,
+            "pinned_answer_id": null,
+            "has_accepted_answer": false,
+            "accepted_answer_id": null,
+            "content_type": "text",
+        }));
+        // An archived accepted answer is still accepted
+        let mut question = sample_question();
+        question.archived_accepted_answer_id = Some(Uuid::parse_str(ANSWER_ID).unwrap());
+        let header = QuestionHeader { question, answer_count: 0, top_level_answer_count: 0 };
+        let response = QuestionHeaderResponse::from(header);
+        assert!(response.has_accepted_answer);
+        assert_eq!(response.accepted_answer_id.as_deref(), Some(ANSWER_ID));
+        // A pinned answer is reported as pinned, not as accepted
+        let mut question = sample_question();
+        question.pinned_answer_id = Some(Uuid::parse_str(ANSWER_ID).unwrap());
+        let response = QuestionHeaderResponse::from(QuestionHeader { question, answer_count: 1, top_level_answer_count: 1 });
+        assert_eq!(response.pinned_answer_id.as_deref(), Some(ANSWER_ID));
+        assert_eq!((response.has_accepted_answer, response.accepted_answer_id), (false, None));
+    }
+
+    #[test]
+    fn engagement_records_should_serialize_one_line_per_entity() {
+        let question = serde_json::to_string(&EngagementRecord::from(&sample_question())).unwrap();
+        assert_eq!(question, format!(r#"{{"entity":"question","id":"{QUESTION_ID}","likes":3,"views":0}}"#));
+        // Answer views are not counted, so they are left out rather than written as zero
+        let answer = serde_json::to_string(&EngagementRecord::from(&sample_answer())).unwrap();
+        assert_eq!(answer, format!(r#"{{"entity":"answer","id":"{ANSWER_ID}","likes":1}}"#));
+        let parsed: EngagementRecord = serde_json::from_str(&answer).unwrap();
+        assert_eq!((parsed.entity, parsed.views), (LikableEntity::Answer, None));
+    }
+
+    #[test]
+    fn question_detail_response_should_serialize_to_expected_shape() {
+        let json = serde_json::to_value(QuestionDetailResponse::new(sample_question(), vec![sample_answer()])).unwrap();
+        assert_eq!(json, json!({
+            "id": QUESTION_ID,
+            "serial": null,
+            "title": "Test Question",
+            "question": "Hello this question is a test",
+            "likes": 3,
+            "views": 0,
+            "created_at": "2024-01-15T12:00:00.000000Z",
+            "updated_at": null,
+            "pinned_at": null,
+            "pinned_answer_id": null,
+            "accepted_answer_id": null,
+            "content_type": "text",
+            "answers": [{
+                "id": ANSWER_ID,
+                "serial": 12,
+                "question_id": QUESTION_ID,
+                "answer": "Test answer",
+                "likes": 1,
+                "created_at": "2024-01-15T13:30:00.000000Z",
+                "content_type": "text",
+            }],
+        }));
+    }
+
+    #[test]
+    fn to_json_should_stringify_unsafe_counts_only_when_requested() {
+        let mut response = QuestionResponse::from(sample_question());
+        response.likes = i64::MAX;
+        response.views = 7;
+        let json = to_json(&response, CountFormat::Number).unwrap();
+        assert_eq!(json["likes"], json!(i64::MAX));
+        let json = to_json(&response, CountFormat::StringWhenUnsafe).unwrap();
+        assert_eq!(json["likes"], json!("9223372036854775807"));
+        assert_eq!(json["views"], json!(7));
+        // Counts JavaScript can still represent exactly remain numbers
+        response.likes = MAX_SAFE_INTEGER as i64;
+        let json = to_json(&response, CountFormat::StringWhenUnsafe).unwrap();
+        assert_eq!(json["likes"], json!(MAX_SAFE_INTEGER));
+        // Nested responses are formatted too
+        let mut answer = AnswerResponse::from(sample_answer());
+        answer.likes = MAX_SAFE_INTEGER as i64 + 1;
+        let json = to_json(&vec![answer], CountFormat::StringWhenUnsafe).unwrap();
+        assert_eq!(json[0]["likes"], json!("9007199254740992"));
+    }
+
+    /// Parses a golden file from `src/models/testdata`.
+    fn golden(contents: &str) -> serde_json::Value {
+        serde_json::from_str(contents).expect("golden file should be valid JSON")
+    }
+
+    /// A question response with every optional field present.
+    fn full_question_response() -> QuestionResponse {
+        let mut question = sample_question();
+        question.serial = Some(7);
+        question.updated_at = Some(Utc.with_ymd_and_hms(2024, 1, 16, 8, 15, 0).unwrap());
+        question.pinned_at = Some(Utc.with_ymd_and_hms(2024, 1, 17, 9, 0, 0).unwrap());
+        question.pinned_answer_id = Some(Uuid::parse_str(ANSWER_ID).unwrap());
+        question.accepted_answer_id = Some(Uuid::parse_str(ANSWER_ID).unwrap());
+        question.is_mine = Some(true);
+        QuestionResponse::from(question)
+    }
+
+    /// A question detail response with an answer lacking its optional fields and one having them.
+    fn full_question_detail_response() -> QuestionDetailResponse {
+        let mut orphaned = sample_answer();
+        orphaned.question_id = None;
+        orphaned.is_mine = Some(false);
+        QuestionDetailResponse::new(sample_question(), vec![sample_answer(), orphaned])
+    }
+
+    #[test]
+    fn question_response_should_match_golden_files_in_both_cases() {
+        let response = full_question_response();
+        let snake = serde_json::to_value(snake::QuestionResponse::from(&response)).unwrap();
+        assert_eq!(snake, golden(include_str!("testdata/question_response_snake.json")));
+        // The snake case wrapper serializes exactly as the response itself
+        assert_eq!(snake, serde_json::to_value(&response).unwrap());
+        let camel = serde_json::to_value(camel::QuestionResponse::from(&response)).unwrap();
+        assert_eq!(camel, golden(include_str!("testdata/question_response_camel.json")));
+    }
+
+    #[test]
+    fn question_detail_response_should_match_golden_files_in_both_cases() {
+        let response = full_question_detail_response();
+        let snake = serde_json::to_value(snake::QuestionDetailResponse::from(&response)).unwrap();
+        assert_eq!(snake, golden(include_str!("testdata/question_detail_snake.json")));
+        // Nested answers are renamed along with the question
+        let camel = serde_json::to_value(camel::QuestionDetailResponse::from(&response)).unwrap();
+        assert_eq!(camel, golden(include_str!("testdata/question_detail_camel.json")));
+        let answer = serde_json::to_value(camel::AnswerResponse::from(&response.answers[1])).unwrap();
+        assert_eq!(answer, golden(include_str!("testdata/question_detail_camel.json"))["answers"][1]);
+    }
+
+    #[test]
+    fn field_case_should_rename_fields_chosen_at_runtime() {
+        let response = full_question_detail_response();
+        let json = FieldCase::default().to_json(&response, CountFormat::Number).unwrap();
+        assert_eq!(json, golden(include_str!("testdata/question_detail_snake.json")));
+        let mut json = FieldCase::Camel.to_json(&response, CountFormat::StringWhenUnsafe).unwrap();
+        assert_eq!(json, golden(include_str!("testdata/question_detail_camel.json")));
+        // Only field names are renamed, never the values
+        json["title"] = json!("snake_case_title");
+        FieldCase::Camel.apply(&mut json);
+        assert_eq!(json["title"], json!("snake_case_title"));
+    }
+
+    #[test]
+    fn parse_request_should_reject_unknown_fields_only_in_strict_mode() {
+        let body = r#"{"title": "Title", "question": "Question", "tags": ["rust"]}"#;
+        let question: NewQuestion = parse_request(body, InputMode::Lenient).expect("lenient mode should ignore unknown fields");
+        assert_eq!(question.title, "Title");
+        let res = parse_request::<NewQuestion>(body, InputMode::Strict);
+        println!("{:?}", res);
+        assert_eq!(res.unwrap_err().to_string(), "unknown field `tags`, expected one of `title`, `question`, `external_id`, `content_type`, `author_token`");
+
+        let body = r#"{"question_id": "1", "answer": "Answer", "author": "someone"}"#;
+        assert!(parse_request::<NewAnswer>(body, InputMode::Lenient).is_ok());
+        let err = parse_request::<NewAnswer>(body, InputMode::Strict).unwrap_err().to_string();
+        assert!(err.contains("`author`") && err.contains("`author_id`"), "{err}");
+    }
+
+    #[test]
+    fn parse_body_should_reject_hostile_bodies_with_codes() {
+        let limit = BodyLimits::default().question;
+        let deep = format!("{}{}", "[".repeat(1000), "]".repeat(1000));
+        let deep_field = format!(r#"{{"title": {}1{}, "question": "Question"}}"#, "[".repeat(1000), "]".repeat(1000));
+        let deep_path = format!("${}", "[0]".repeat(MAX_JSON_DEPTH));
+        let deep_field_path = format!("$.title{}", "[0]".repeat(MAX_JSON_DEPTH - 1));
+        let cases: [(&[u8], BodyErrorCode, &str); 10] = [
+            (b"{\"title\": \"Ti\xfftle\", \"question\": \"Question\"}", BodyErrorCode::InvalidUtf8, "$.title"),
+            (b"\xc3\x28", BodyErrorCode::InvalidUtf8, "$"),
+            (deep.as_bytes(), BodyErrorCode::NestingTooDeep, &deep_path),
+            (deep_field.as_bytes(), BodyErrorCode::NestingTooDeep, &deep_field_path),
+            (br#"{"title": "Title", "question": "Quest"#, BodyErrorCode::TruncatedJson, "$.question"),
+            (b"", BodyErrorCode::TruncatedJson, "$"),
+            (br#"{"title": "Title", "question" "Question"}"#, BodyErrorCode::MalformedJson, "$.question"),
+            (br#"["Title", "Question"]"#, BodyErrorCode::InvalidRequest, "$"),
+            (br#"{"title": 1, "question": "Question"}"#, BodyErrorCode::InvalidRequest, "$.title"),
+            (br#"{"title": "Title"}"#, BodyErrorCode::InvalidRequest, "$"),
+        ];
+        for (body, code, path) in cases {
+            let res = parse_body::<NewQuestion>(body, InputMode::Lenient, limit);
+            println!("{:?}", res);
+            let err = res.unwrap_err();
+            assert_eq!((err.code, err.path.as_str()), (code, path), "{err}");
+        }
+
+        let res = parse_body::<NewQuestion>(&vec![b' '; limit + 1], InputMode::Lenient, limit);
+        assert_eq!(res.unwrap_err().code, BodyErrorCode::BodyTooLarge);
+        let res = parse_body::<NewQuestion>(br#"{"title": "Title", "question": "Question", "tags": []}"#, InputMode::Strict, limit);
+        let err = res.unwrap_err();
+        assert_eq!((err.code, err.path.as_str()), (BodyErrorCode::InvalidRequest, "$.tags"));
+        let nested = format!(r#"{{"title": "Title", "question": "Question", "tags": {}{}}}"#, "[".repeat(MAX_JSON_DEPTH - 1), "]".repeat(MAX_JSON_DEPTH - 1));
+        let question: NewQuestion = parse_body(nested.as_bytes(), InputMode::Lenient, limit).expect("nesting within the limit should be parsed");
+        assert_eq!(question.title, "Title");
+    }
+
+    #[test]
+    fn body_errors_should_serialize_as_structured_bodies() {
+        let res = parse_question_patch_body(br#"{"title": null}"#, InputMode::Strict, BodyLimits::default().question);
+        println!("{:?}", res);
+        let body = serde_json::to_value(res.unwrap_err()).unwrap();
+        assert_eq!(body, json!({
+            "code": "invalid_request",
+            "path": "$.title",
+            "message": "field `title` cannot be removed, a question requires it",
+        }));
+        let update = parse_question_patch_body(br#"{"title": "New title"}"#, InputMode::Strict, BodyLimits::default().question)
+            .expect("title patch should be parsed");
+        assert_eq!(update.title.as_deref(), Some("New title"));
+    }
+
+    #[test]
+    fn author_tokens_should_be_validated() {
+        let question = |token: &str| NewQuestion {
+            title: String::from("Title"),
+            question: String::from("Question"),
+            external_id: None,
+            content_type: None,
+            author_token: Some(token.to_string()),
+        };
+        let limits = ContentLimits::default();
+        assert!(question(&"a".repeat(MIN_AUTHOR_TOKEN_LENGTH)).validate(&limits).is_ok());
+        assert!(question(&"Z9-_".repeat(MAX_AUTHOR_TOKEN_LENGTH / 4)).validate(&limits).is_ok());
+        for token in ["a".repeat(MIN_AUTHOR_TOKEN_LENGTH - 1), "a".repeat(MAX_AUTHOR_TOKEN_LENGTH + 1), format!("{}=", "a".repeat(20)), "é".repeat(10)] {
+            let res = question(&token).validate(&limits);
+            println!("{:?}", res);
+            let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        }
+        let answer = NewAnswer {
+            question_id: String::from("1"),
+            answer: String::from("Answer"),
+            author_id: None,
+            content_type: None,
+            author_token: Some(String::from("short")),
+            parent_answer_id: None,
+        };
+        let Err(DbError::Validation { .. }) = answer.validate(&limits) else { panic!("Error should be `Validation` variant") };
+    }
+
+    #[test]
+    fn parse_question_patch_should_only_change_present_fields() {
+        let update = parse_question_patch(r#"{"title": "New title"}"#, InputMode::Strict).expect("title patch should be parsed");
+        assert_eq!((update.title.as_deref(), update.question.as_deref()), (Some("New title"), None));
+        let update = parse_question_patch(r#"{"title": "New title", "question": "New question"}"#, InputMode::Strict)
+            .expect("patch of both fields should be parsed");
+        assert_eq!((update.title.as_deref(), update.question.as_deref()), (Some("New title"), Some("New question")));
+        // An empty patch parses, and is rejected as an update that changes nothing
+        let update = parse_question_patch("{}", InputMode::Strict).expect("empty patch should be parsed");
+        assert!(update.validate(&Default::default()).is_err());
+    }
+
+    #[test]
+    fn parse_question_patch_should_reject_removing_required_fields() {
+        for mode in [InputMode::Lenient, InputMode::Strict] {
+            let res = parse_question_patch(r#"{"title": "New title", "question": null}"#, mode);
+            println!("{:?}", res);
+            assert_eq!(res.unwrap_err().to_string(), "field `question` cannot be removed, a question requires it");
+        }
+        let res = parse_question_patch(r#"["title"]"#, InputMode::Lenient);
+        assert_eq!(res.unwrap_err().to_string(), "a merge patch of a question must be a JSON object");
+        assert!(parse_question_patch(r#"{"title": 7}"#, InputMode::Lenient).is_err());
+    }
+
+    #[test]
+    fn parse_question_patch_should_reject_unknown_fields_only_in_strict_mode() {
+        let body = r#"{"title": "New title", "likes": 100}"#;
+        let update: UpdateQuestion = parse_question_patch(body, InputMode::Lenient).expect("lenient mode should ignore unknown fields");
+        assert_eq!(update.title.as_deref(), Some("New title"));
+        let res = parse_question_patch(body, InputMode::Strict);
+        println!("{:?}", res);
+        assert_eq!(res.unwrap_err().to_string(), "unknown field `likes`, expected `title` or `question`");
+    }
+
+    #[test]
+    fn parse_request_should_name_valid_entities_of_a_like() {
+        let body = format!(r#"{{"entity": "answer", "id": "{ANSWER_ID}"}}"#);
+        let target: LikeTarget = parse_request(&body, InputMode::Strict).expect("like should be parsed");
+        assert_eq!(target, LikeTarget { entity: LikableEntity::Answer, id: String::from(ANSWER_ID) });
+        let res = parse_request::<LikeTarget>(r#"{"entity": "comment", "id": "1"}"#, InputMode::Lenient);
+        println!("{:?}", res);
+        assert!(res.unwrap_err().to_string().starts_with("unknown variant `comment`, expected `question` or `answer`"));
+    }
+
+    #[test]
+    fn parse_request_should_name_missing_fields() {
+        for mode in [InputMode::Lenient, InputMode::Strict] {
+            let res = parse_request::<NewQuestion>(r#"{"titel": "Title", "question": "Question"}"#, mode);
+            println!("{:?}", res);
+            let err = res.unwrap_err().to_string();
+            let expected = match mode {
+                InputMode::Lenient => "missing field `title`",
+                InputMode::Strict => "unknown field `titel`, expected one of `title`, `question`, `external_id`, `content_type`",
+            };
+            assert!(err.starts_with(expected), "{err}");
+        }
+        let res = parse_request::<NewQuestion>(r#"{"question": "Question"}"#, InputMode::Strict);
+        assert_eq!(res.unwrap_err().to_string(), "missing field `title`");
+    }
+
+    #[test]
+    fn new_answer_should_accept_known_content_types_only() {
+        let answer: NewAnswer = serde_json::from_value(json!({ "question_id": QUESTION_ID, "answer": "answer", "content_type": "markdown" })).unwrap();
+        assert_eq!(answer.content_type, Some(ContentType::Markdown));
+        let question: NewQuestion = parse_request(r#"{"title": "Title", "question": "Question"}"#, InputMode::Strict).unwrap();
+        assert_eq!(question.content_type, None);
+        let res = parse_request::<NewAnswer>(&format!(r#"{{"question_id": "{QUESTION_ID}", "answer": "answer", "content_type": "html"}}"#), InputMode::Lenient);
+        println!("{:?}", res);
+        let answer = res.expect("unknown content types should parse");
+        assert_eq!(answer.content_type, Some(ContentType::Unknown(String::from("html"))));
+        let res = answer.validate(&ContentLimits::default());
+        assert_eq!(res.unwrap_err().to_string(), "Validation error: unsupported content type `html`, expected one of: text, markdown");
+    }
+
+    #[test]
+    fn new_question_and_new_answer_should_trim_content() {
+        let question: NewQuestion = serde_json::from_value(json!({ "title": "  title  ", "question": "\n question\t" })).unwrap();
+        assert_eq!(question.title, "title");
+        assert_eq!(question.question, "question");
+        let answer: NewAnswer = serde_json::from_value(json!({ "question_id": QUESTION_ID, "answer": " answer " })).unwrap();
+        assert_eq!(answer.answer, "answer");
+        assert_eq!(answer.author_id, None);
+    }
+
+    #[test]
+    fn list_format_should_negotiate_the_accept_header() {
+        assert_eq!(ListFormat::negotiate(None).unwrap(), ListFormat::Json);
+        assert_eq!(ListFormat::negotiate(Some(" ")).unwrap(), ListFormat::Json);
+        assert_eq!(ListFormat::negotiate(Some("*/*")).unwrap(), ListFormat::Json);
+        assert_eq!(ListFormat::negotiate(Some("Text/CSV")).unwrap(), ListFormat::Csv);
+        assert_eq!(ListFormat::negotiate(Some("application/x-ndjson")).unwrap(), ListFormat::Ndjson);
+        assert_eq!(ListFormat::negotiate(Some("text/*")).unwrap(), ListFormat::Csv);
+        assert_eq!(ListFormat::negotiate(Some("application/json;q=0.5, text/csv")).unwrap(), ListFormat::Csv);
+        assert_eq!(ListFormat::negotiate(Some("application/json;q=0, */*;q=0.1")).unwrap(), ListFormat::Csv);
+        assert_eq!(ListFormat::negotiate(Some("text/html, application/*;q=0.8")).unwrap(), ListFormat::Json);
+        for accept in ["text/html", "application/xml, text/csv;q=0", "image/*"] {
+            let res = ListFormat::negotiate(Some(accept));
+            println!("{:?}", res);
+            let Err(DbError::Validation { reason: ValidationReason::Input(message), .. }) = res else { panic!("Error should be `Validation` variant") };
+            assert!(ListFormat::MEDIA_TYPES.iter().all(|media_type| message.contains(media_type)));
+        }
+        assert_eq!(ListFormat::Csv.content_disposition("questions").unwrap(), "attachment; filename=\"questions.csv\"");
+        assert_eq!(ListFormat::Ndjson.content_disposition("questions"), None);
+    }
+
+    #[test]
+    fn write_questions_csv_should_quote_fields() {
+        let mut question = QuestionResponse::from(sample_question());
+        question.title = String::from("Commas, \"quotes\" and more");
+        question.question = String::from("Two\nlines");
+        let mut csv = Vec::new();
+        write_questions_csv(&mut csv, [question]).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(
+            csv,
+            format!(
+                "{}\r\n{QUESTION_ID},,\"Commas, \"\"quotes\"\" and more\",\"Two\nlines\",3,0,2024-01-15T12:00:00.000000Z,,,,text\r\n",
+                QUESTION_CSV_HEADER.join(","),
+            )
+        );
+    }
+
+    #[test]
+    fn write_ndjson_should_write_one_object_per_line() {
+        let questions = [QuestionResponse::from(sample_question()), QuestionResponse { likes: 1 << 60, ..QuestionResponse::from(sample_question()) }];
+        let mut ndjson = Vec::new();
+        write_ndjson(&mut ndjson, questions.clone(), CountFormat::StringWhenUnsafe).unwrap();
+        let ndjson = String::from_utf8(ndjson).unwrap();
+        assert!(ndjson.ends_with('\n'));
+        let lines: Vec<serde_json::Value> = ndjson.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], serde_json::to_value(&questions[0]).unwrap());
+        assert_eq!(lines[1]["likes"], json!((1u64 << 60).to_string()));
+    }
+
+    #[test]
+    fn body_limits_should_reject_bodies_over_the_limit() {
+        let limits = BodyLimits { question: 100, answer: 50 };
+        assert!(limits.check_question(100).is_ok());
+        let Err(DbError::Validation { reason: ValidationReason::LimitExceeded { limit: 100 }, .. }) = limits.check_question(101) else { panic!("Error should be `LimitExceeded` reason") };
+        assert!(limits.check_answer(50).is_ok());
+        let Err(DbError::Validation { reason: ValidationReason::LimitExceeded { limit: 50 }, .. }) = limits.check_answer(51) else { panic!("Error should be `LimitExceeded` reason") };
+    }
+
+    #[test]
+    fn body_limits_should_admit_the_longest_valid_content() {
+        let content = ContentLimits { max_title: 10, max_question: 20, max_answer: 20 };
+        let limits = BodyLimits::from(content);
+        assert!(limits.question > limits.answer);
+        // Every character escaped as a surrogate pair, the longest encoding of valid content
+        let answer = "\\ud83d\\ude00".repeat(content.max_answer);
+        let body = format!(r#"{{"question_id": "{QUESTION_ID}", "answer": "{answer}", "content_type": "markdown"}}"#);
+        assert!(limits.check_answer(body.len()).is_ok());
+        let parsed: NewAnswer = parse_request(&body, InputMode::Strict).unwrap();
+        assert!(parsed.validate(&content).is_ok());
+        assert_eq!(BodyLimits::default(), BodyLimits::from(ContentLimits::default()));
+    }
+}
+
+mod links_tests {
+    use std::sync::Arc;
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use crate::clock::FixedClock;
+    use crate::models::links::{CursorSigner, PageLinks};
+    use crate::models::{DbError, Page, PageRequest, ValidationReason};
+
+    fn signer(clock: Arc<FixedClock>) -> CursorSigner {
+        CursorSigner::new("secret").with_clock(clock).with_max_age(Duration::hours(1))
+    }
+
+    fn clock() -> Arc<FixedClock> {
+        Arc::new(FixedClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()))
+    }
+
+    #[test]
+    fn cursor_signer_should_round_trip_url_safe_tokens() {
+        let signer = signer(clock());
+        let cursor = Utc.with_ymd_and_hms(2023, 12, 31, 23, 59, 59).unwrap();
+        let token = signer.sign(&cursor);
+        assert!(token.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')), "{}", token);
+        assert_eq!(signer.verify::<DateTime<Utc>>(&token).unwrap(), cursor);
+        let token = signer.sign(&("a/b?c&d", 7));
+        assert_eq!(signer.verify::<(String, i64)>(&token).unwrap(), (String::from("a/b?c&d"), 7));
+    }
+
+    #[test]
+    fn cursor_signer_should_reject_tampered_tokens() {
+        let signer = signer(clock());
+        let token = signer.sign(&10_i64);
+        let (payload, signature) = token.split_once('.').unwrap();
+
+        // A payload changed to another cursor keeps the signature of the original
+        let forged = String::from_utf8(URL_SAFE_NO_PAD.decode(payload).unwrap()).unwrap().replace("10", "11");
+        let res = signer.verify::<i64>(&format!("{}.{}", URL_SAFE_NO_PAD.encode(forged), signature));
+        println!("{:?}", res);
+        let Err(DbError::Validation { reason: ValidationReason::Input(message), .. }) = res else { panic!("Error should be `Validation` variant") };
+        assert_eq!(message, "cursor signature is invalid");
+
+        // Every single character changed
+        for i in 0..token.len() {
+            let mut tampered = token.clone().into_bytes();
+            tampered[i] = if tampered[i] == b'A' { b'B' } else { b'A' };
+            let tampered = String::from_utf8(tampered).unwrap();
+            let Err(DbError::Validation { .. }) = signer.verify::<i64>(&tampered) else { panic!("Error should be `Validation` variant") };
+        }
+
+        // Signed with another key, truncated or not a token
+        let other = CursorSigner::new("other secret").sign(&10_i64);
+        let Err(DbError::Validation { .. }) = signer.verify::<i64>(&other) else { panic!("Error should be `Validation` variant") };
+        for malformed in [payload, "", ".", "not a token", &token[..token.len() - 1]] {
+            let res = signer.verify::<i64>(malformed);
+            println!("{:?}", res);
+            let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        }
+    }
+
+    #[test]
+    fn cursor_signer_should_reject_expired_tokens() {
+        let clock = clock();
+        let signer = signer(clock.clone());
+        let token = signer.sign(&10_i64);
+        clock.advance(Duration::hours(1));
+        assert_eq!(signer.verify::<i64>(&token).unwrap(), 10);
+        clock.advance(Duration::seconds(1));
+        let res = signer.verify::<i64>(&token);
+        println!("{:?}", res);
+        let Err(DbError::Validation { reason: ValidationReason::Input(message), .. }) = res else { panic!("Error should be `Validation` variant") };
+        assert_eq!(message, "cursor has expired");
+    }
+
+    #[test]
+    fn page_links_should_link_offset_pages_keeping_other_parameters() {
+        let page = Page { items: vec![1, 2], has_more: true };
+        let links = PageLinks::offset("/questions?sort=new&offset=2&limit=2", PageRequest { offset: 2, limit: 2 }, &page, 5);
+        assert_eq!(links.next.as_deref(), Some("/questions?sort=new&offset=4&limit=2"));
+        assert_eq!(links.prev.as_deref(), Some("/questions?sort=new&offset=0&limit=2"));
+        assert_eq!(
+            links.link_header().as_deref(),
+            Some(r#"</questions?sort=new&offset=4&limit=2>; rel="next", </questions?sort=new&offset=0&limit=2>; rel="prev""#)
+        );
+        assert_eq!(links.total_count_header().as_deref(), Some("5"));
+
+        let first = PageLinks::offset("/questions", PageRequest { offset: 0, limit: 2 }, &Page { items: vec![1], has_more: false }, 1);
+        assert_eq!(first, PageLinks { next: None, prev: None, total_count: Some(1) });
+        assert_eq!(first.link_header(), None);
+    }
+
+    #[test]
+    fn page_links_should_link_the_next_keyset_page_with_a_signed_cursor() {
+        let signer = signer(clock());
+        let links = PageLinks::keyset("/feed?limit=2&cursor=old", &Page { items: vec![3_i64, 4], has_more: true }, &signer, |item| *item);
+        let next = links.next.as_deref().unwrap();
+        let token = next.strip_prefix("/feed?limit=2&cursor=").unwrap();
+        assert_eq!(signer.verify::<i64>(token).unwrap(), 4);
+        assert_eq!(links.link_header(), Some(format!("<{next}>; rel=\"next\"")));
+        assert_eq!(links.total_count_header(), None);
+
+        let last = PageLinks::keyset("/feed", &Page { items: vec![5_i64], has_more: false }, &signer, |item| *item);
+        assert_eq!(last, PageLinks::default());
+    }
+}
+
+mod normalize_tests {
+    use crate::models::normalize::{normalize, normalize_title};
+
+    #[test]
+    fn normalize_should_fold_case_and_compatibility_characters() {
+        assert_eq!(normalize("Ｑuestion", false), "question");
+        assert_eq!(normalize("MiXeD CaSe", false), "mixed case");
+        assert_eq!(normalize("ＡＢＣ１２３", false), "abc123");
+        // Accents are kept unless stripped, in composed form regardless of the input form
+        assert_eq!(normalize("Cafe\u{301}", false), "caf\u{e9}");
+        assert_eq!(normalize("CAF\u{c9}", false), "caf\u{e9}");
+    }
+
+    #[test]
+    fn normalize_should_strip_accents_only_when_requested() {
+        assert_eq!(normalize("Café", true), "cafe");
+        assert_eq!(normalize("Cafe\u{301}", true), "cafe");
+        assert_eq!(normalize("Ｃａｆé Crème Brûlée", true), "cafe creme brulee");
+        assert_ne!(normalize("Café", false), normalize("cafe", false));
+    }
+
+    #[test]
+    fn normalize_title_should_match_normalized_counterparts() {
+        for title in ["Café question", "CAFE QUESTION", "ｃａｆｅ ｑｕｅｓｔｉｏｎ", "Cafe\u{301} Question"] {
+            assert_eq!(normalize_title(title), "cafe question", "{title:?}");
+        }
+    }
+}
+
+mod error_tests {
+    use std::error::Error as _;
+    use std::borrow::Cow;
+    use chrono::Duration;
+    use sqlx::error::DatabaseError;
+    use sqlx::types::Uuid;
+    #[allow(deprecated)]
+    use crate::models::legacy::LegacyDbError;
+    use crate::models::{ConflictReason, DbError, DbErrorKind, InternalReason, UnavailableReason, ValidationReason};
+
+    /// A database error like the ones sqlx returns, whose message names internals that must not be displayed.
+    fn sqlx_error() -> sqlx::Error {
+        sqlx::Error::Protocol(String::from("relation \"questions\" violates constraint \"questions_pkey\" (SQLSTATE 23505)"))
+    }
+
+    /// A database error with an SQLSTATE code, as the driver reports constraint violations.
+    #[derive(Debug)]
+    struct CodedError(&'static str);
+
+    impl std::fmt::Display for CodedError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "SQLSTATE {}", self.0)
+        }
+    }
+
+    impl std::error::Error for CodedError {}
+
+    impl DatabaseError for CodedError {
+        fn message(&self) -> &str {
+            self.0
+        }
+
+        fn code(&self) -> Option<Cow<'_, str>> {
+            Some(Cow::Borrowed(self.0))
+        }
+
+        fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+            self
+        }
+
+        fn kind(&self) -> sqlx::error::ErrorKind {
+            sqlx::error::ErrorKind::Other
+        }
+    }
+
+    fn coded(code: &'static str) -> sqlx::Error {
+        sqlx::Error::Database(Box::new(CodedError(code)))
+    }
+
+    fn io() -> sqlx::Error {
+        sqlx::Error::Io(std::io::Error::from(std::io::ErrorKind::ConnectionReset))
+    }
+
+    #[test]
+    fn display_should_describe_each_variant_without_database_details() {
+        let id = Uuid::parse_str("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap();
+        let cases = [
+            (DbError::creation(sqlx_error()), "The entity could not be created"),
+            (DbError::from(sqlx::Error::RowNotFound), "The entity was not found"),
+            (DbError::not_found("question", id), "The question 67e55044-10b1-426f-9247-bb680e5fe0c8 was not found"),
+            (DbError::invalid_id("invalid character"), "The id of the entity is invalid: invalid character"),
+            (DbError::from(sqlx_error()), "The database could not be accessed"),
+            (DbError::connection(sqlx_error()), "The database could not be connected to"),
+            (DbError::from_row(sqlx_error()), "The entity could not be read from the database"),
+            (DbError::deletion(sqlx_error()), "The entity could not be deleted"),
+            (DbError::update(sqlx_error()), "The entity could not be updated"),
+            (DbError::commit(sqlx_error()), "The changes to the entity could not be committed"),
+            (
+                DbError::deletion(sqlx_error()).stopped_after(3),
+                "Batch operation stopped after 3 rows were affected: The entity could not be deleted",
+            ),
+            (DbError::validation("title is too long"), "Validation error: title is too long"),
+            (DbError::conflict("key reused"), "Conflict error: key reused"),
+            (DbError::from(coded("23505")), "The entity conflicts with the data in the database"),
+            (DbError::from(coded("23514")), "The entity has a value the database does not accept"),
+            (DbError::from(sqlx::Error::PoolTimedOut), "The database is temporarily unavailable"),
+            (ValidationReason::PolicyViolation(String::from("no spam")).into(), "The entity violates the content policy: no spam"),
+            (ConflictReason::Locked(id).into(), "Question 67e55044-10b1-426f-9247-bb680e5fe0c8 is locked"),
+            (ValidationReason::NestedReply(id).into(), "Answer 67e55044-10b1-426f-9247-bb680e5fe0c8 is a reply and cannot be replied to"),
+            (ValidationReason::LimitExceeded { limit: 5 }.into(), "The limit of 5 has been reached"),
+            (
+                UnavailableReason::QuotaExceeded { retry_after: Duration::seconds(90) }.into(),
+                "The creation quota has been exceeded, retry after 90 seconds",
+            ),
+            (ConflictReason::HasDependents { count: 2 }.into(), "Cannot delete the entity while it has 2 dependents"),
+            (
+                InternalReason::SchemaMismatch { expected: 2, found: 1, missing: vec![String::from("2_add_column")] }.into(),
+                "The database schema is at version 1 but version 2 is expected, missing migrations: 2_add_column",
+            ),
+        ];
+        for (error, expected) in cases {
+            let error: DbError = error;
+            assert_eq!(error.to_string(), expected);
+            assert!(!error.to_string().contains("SQLSTATE"));
+        }
+    }
+
+    #[test]
+    fn display_with_context_should_name_the_entity() {
+        let id = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+        let error = DbError::from(sqlx::Error::RowNotFound).with_context("question", id);
+        assert_eq!(error.to_string(), format!("The question {id} was not found"));
+        let error = DbError::from(ConflictReason::HasDependents { count: 4 }).with_context("category", 7);
+        assert_eq!(error.to_string(), "Cannot delete the category 7 while it has 4 dependents");
+        let error = DbError::validation("answer is empty").with_context("answer", 1);
+        assert_eq!(error.to_string(), "Validation error: answer is empty");
+    }
+
+    #[test]
+    fn source_should_keep_the_database_details() {
+        let error = DbError::update(sqlx_error());
+        assert_eq!(error.source().map(|e| e.to_string()), Some(sqlx_error().to_string()));
+        assert_eq!(error.debug_details(), Some(sqlx_error().to_string()));
+        // The details of a batch are those of the error that stopped it
+        let error = error.stopped_after(1);
+        assert_eq!(error.partial_batch().map(|batch| batch.completed), Some(1));
+        assert_eq!(error.source().and_then(|e| e.source()).map(|e| e.to_string()), Some(sqlx_error().to_string()));
+        assert_eq!(error.debug_details(), Some(sqlx_error().to_string()));
+        // Errors raised before reaching the database have no details
+        let error = DbError::validation("title is too long");
+        assert!(error.source().is_none() && error.debug_details().is_none());
+        let error = DbError::commit(sqlx_error()).with_context("question", 1);
+        assert!(error.source().and_then(|e| e.source()).is_some());
+    }
+
+    #[test]
+    fn from_should_classify_driver_errors_and_keep_them_as_source() {
+        let error = DbError::from(sqlx::Error::RowNotFound);
+        let DbError::NotFound { entity: "entity", id: None, .. } = error else { panic!("Error should be `NotFound` variant") };
+        assert!(error.source().and_then(|e| e.downcast_ref::<sqlx::Error>()).is_some_and(|e| matches!(e, sqlx::Error::RowNotFound)));
+        // A missing row is not found whichever operation raised it
+        let DbError::NotFound { .. } = DbError::deletion(sqlx::Error::RowNotFound) else { panic!("Error should be `NotFound` variant") };
+
+        for code in ["23505", "23503"] {
+            let error = DbError::from(coded(code));
+            let DbError::Conflict { reason: ConflictReason::Constraint, .. } = error else { panic!("Error should be `Conflict` variant") };
+            let source = error.source().and_then(|e| e.downcast_ref::<sqlx::Error>());
+            assert!(source.and_then(|e| e.as_database_error()).and_then(|e| e.code()).is_some_and(|c| c == code));
+        }
+        let error = DbError::creation(coded("23514"));
+        let DbError::Validation { reason: ValidationReason::Constraint, .. } = error else { panic!("Error should be `Validation` variant") };
+
+        let error = DbError::from(io());
+        let DbError::Unavailable { reason: UnavailableReason::Transient, .. } = error else { panic!("Error should be `Unavailable` variant") };
+        assert!(error.source().and_then(|e| e.downcast_ref::<sqlx::Error>()).is_some_and(|e| matches!(e, sqlx::Error::Io(_))));
+        let error = DbError::from(sqlx::Error::PoolClosed);
+        let DbError::Unavailable { reason: UnavailableReason::Unreachable, .. } = error else { panic!("Error should be `Unavailable` variant") };
+
+        let error = DbError::update(sqlx_error());
+        let DbError::Internal { reason: InternalReason::Update, .. } = error else { panic!("Error should be `Internal` variant") };
+    }
+
+    #[test]
+    fn kind_should_classify_errors_independently_of_the_backend() {
+        assert_eq!(DbError::from(sqlx::Error::RowNotFound).kind(), DbErrorKind::NotFound);
+        assert_eq!(DbError::from(io()).kind(), DbErrorKind::Unavailable);
+        assert_eq!(DbError::from(sqlx::Error::ColumnNotFound(String::from("likes"))).kind(), DbErrorKind::Internal);
+        assert_eq!(DbError::from(coded("23505")).kind(), DbErrorKind::Conflict);
+        assert_eq!(DbError::from(coded("22001")).kind(), DbErrorKind::Validation);
+        assert_eq!(DbError::invalid_id("not a uuid").kind(), DbErrorKind::InvalidId);
+        assert_eq!(DbError::from(ConflictReason::Locked(Uuid::nil())).kind(), DbErrorKind::Conflict);
+        assert_eq!(DbError::from(UnavailableReason::QuotaExceeded { retry_after: Duration::seconds(1) }).kind(), DbErrorKind::Unavailable);
+        assert_eq!(DbError::validation("").stopped_after(1).kind(), DbErrorKind::Validation);
+    }
+
+    #[test]
+    fn is_transient_should_classify_errors_worth_retrying() {
+        assert!(DbError::from(sqlx::Error::PoolTimedOut).is_transient());
+        assert!(DbError::connection(io()).is_transient());
+        assert!(DbError::from(coded("40001")).is_transient());
+        assert!(DbError::deletion(io()).stopped_after(1).is_transient());
+        assert!(!DbError::from(sqlx::Error::RowNotFound).is_transient());
+        assert!(!DbError::creation(sqlx_error()).is_transient());
+        assert!(!DbError::from(sqlx::Error::PoolClosed).is_transient());
+        assert!(!DbError::validation("title is too long").is_transient());
+        assert!(!DbError::from(ConflictReason::Locked(Uuid::nil())).is_transient());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn legacy_conversions_should_round_trip_the_classification() {
+        let LegacyDbError::NotFound(sqlx::Error::RowNotFound) = LegacyDbError::from(DbError::not_found("question", 1)) else {
+            panic!("Error should be `NotFound` variant")
+        };
+        let LegacyDbError::Update(sqlx::Error::Protocol(_)) = LegacyDbError::from(DbError::update(sqlx_error())) else {
+            panic!("Error should be `Update` variant")
+        };
+        let LegacyDbError::Connection(sqlx::Error::Io(_)) = LegacyDbError::from(DbError::from(io())) else {
+            panic!("Error should be `Connection` variant")
+        };
+        let LegacyDbError::PartialBatch { completed: 2, error } = LegacyDbError::from(DbError::deletion(sqlx_error()).stopped_after(2)) else {
+            panic!("Error should be `PartialBatch` variant")
+        };
+        let LegacyDbError::Deletion(_) = *error else { panic!("Error should be `Deletion` variant") };
+
+        let error = DbError::from(LegacyDbError::Locked(Uuid::nil()));
+        let DbError::Conflict { reason: ConflictReason::Locked(_), .. } = error else { panic!("Error should be `Conflict` variant") };
+        let error = DbError::from(LegacyDbError::NotFound(sqlx_error()));
+        let DbError::NotFound { .. } = error else { panic!("Error should be `NotFound` variant") };
+        let error = DbError::from(LegacyDbError::PartialBatch { completed: 4, error: Box::new(LegacyDbError::Commit(sqlx_error())) });
+        let DbError::Internal { reason: InternalReason::Commit, .. } = error else { panic!("Error should be `Internal` variant") };
+        assert_eq!(error.partial_batch().map(|batch| batch.completed), Some(4));
+    }
+}
+
+mod content_stats_tests {
+    use crate::models::content_stats::ContentStats;
+
+    #[test]
+    fn content_stats_should_count_ascii_words_and_characters() {
+        assert_eq!(ContentStats::of("How do I sort a Vec<i32>?"), ContentStats { char_count: 25, word_count: 7 });
+        assert_eq!(ContentStats::of("  spaced   out  "), ContentStats { char_count: 16, word_count: 2 });
+        assert_eq!(ContentStats::of(""), ContentStats::default());
+    }
+
+    #[test]
+    fn content_stats_should_count_emoji_as_single_characters_but_not_words() {
+        // A family emoji is several code points joined into one grapheme
+        let stats = ContentStats::of("Thanks \u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467} \u{1F44D}\u{1F3FD}");
+        assert_eq!(stats, ContentStats { char_count: 10, word_count: 1 });
+    }
+
+    #[test]
+    fn content_stats_should_split_cjk_text_without_whitespace() {
+        // Each ideograph and kana is a word, since the text has no spaces to split at
+        assert_eq!(ContentStats::of("东京很大"), ContentStats { char_count: 4, word_count: 4 });
+        assert_eq!(ContentStats::of("東京に行きます"), ContentStats { char_count: 7, word_count: 7 });
+        assert_eq!(ContentStats::of("Rust 编程"), ContentStats { char_count: 7, word_count: 3 });
+    }
+}
+
+mod content_type_tests {
+    use crate::models::{ContentType, DbError};
+
+    #[test]
+    fn content_type_should_round_trip_through_serde() {
+        for (content_type, json) in [(ContentType::Text, "\"text\""), (ContentType::Markdown, "\"markdown\"")] {
+            assert_eq!(serde_json::to_string(&content_type).unwrap(), json);
+            assert_eq!(serde_json::from_str::<ContentType>(json).unwrap(), content_type);
+            assert!(content_type.validate().is_ok());
+        }
+    }
+
+    #[test]
+    fn unknown_content_type_should_serialize_transparently_but_fail_validation() {
+        let content_type: ContentType = serde_json::from_str("\"html\"").unwrap();
+        assert_eq!(content_type, ContentType::Unknown(String::from("html")));
+        assert_eq!(serde_json::to_string(&content_type).unwrap(), "\"html\"");
+        let res = content_type.validate();
+        println!("{:?}", res);
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+    }
+}
+
+mod diff_tests {
+    use crate::models::diff::{diff_revisions, DiffHunk, DiffLine};
+
+    fn context(text: &str) -> DiffLine {
+        DiffLine::Context(String::from(text))
+    }
+
+    fn added(text: &str) -> DiffLine {
+        DiffLine::Added(String::from(text))
+    }
+
+    fn removed(text: &str) -> DiffLine {
+        DiffLine::Removed(String::from(text))
+    }
+
+    #[test]
+    fn diff_revisions_should_be_empty_for_the_same_lines() {
+        assert!(diff_revisions("", "").is_empty());
+        assert!(diff_revisions("one\ntwo", "one\ntwo").is_empty());
+        // Line endings are not content
+        assert!(diff_revisions("one\r\ntwo\n", "one\ntwo").is_empty());
+    }
+
+    #[test]
+    fn diff_revisions_should_report_inserted_lines() {
+        let hunks = diff_revisions("", "one\ntwo");
+        assert_eq!(hunks, vec![DiffHunk { old_start: 0, old_lines: 0, new_start: 1, new_lines: 2, lines: vec![added("one"), added("two")] }]);
+
+        let hunks = diff_revisions("1\n2\n3\n4\n5\n6\n7\n8", "1\n2\n3\n4\nnew\n5\n6\n7\n8");
+        let lines = vec![context("2"), context("3"), context("4"), added("new"), context("5"), context("6"), context("7")];
+        assert_eq!(hunks, vec![DiffHunk { old_start: 2, old_lines: 6, new_start: 2, new_lines: 7, lines }]);
+    }
+
+    #[test]
+    fn diff_revisions_should_report_deleted_lines() {
+        let hunks = diff_revisions("one\ntwo", "");
+        assert_eq!(hunks, vec![DiffHunk { old_start: 1, old_lines: 2, new_start: 0, new_lines: 0, lines: vec![removed("one"), removed("two")] }]);
+
+        let hunks = diff_revisions("keep\ndrop\nkeep too", "keep\nkeep too");
+        let lines = vec![context("keep"), removed("drop"), context("keep too")];
+        assert_eq!(hunks, vec![DiffHunk { old_start: 1, old_lines: 3, new_start: 1, new_lines: 2, lines }]);
+    }
+
+    #[test]
+    fn diff_revisions_should_split_distant_changes_into_hunks() {
+        let old: Vec<String> = (1..=20).map(|n| n.to_string()).collect();
+        let mut new = old.clone();
+        new[1] = String::from("two");
+        new[17] = String::from("eighteen");
+        let hunks = diff_revisions(&old.join("\n"), &new.join("\n"));
+        assert_eq!(hunks.len(), 2);
+        assert_eq!((hunks[0].old_start, hunks[0].old_lines, hunks[0].new_start, hunks[0].new_lines), (1, 5, 1, 5));
+        assert_eq!(hunks[0].lines, vec![context("1"), removed("2"), added("two"), context("3"), context("4"), context("5")]);
+        assert_eq!((hunks[1].old_start, hunks[1].old_lines, hunks[1].new_start, hunks[1].new_lines), (15, 6, 15, 6));
+        // Changes closer than twice the context share a hunk
+        new[8] = String::from("nine");
+        new[12] = String::from("thirteen");
+        assert_eq!(diff_revisions(&old.join("\n"), &new.join("\n")).len(), 1);
+    }
+
+    #[test]
+    fn diff_revisions_should_keep_the_longest_common_lines() {
+        let hunks = diff_revisions("a\nb\nc\nd", "b\nx\nd\na");
+        let lines: Vec<DiffLine> = hunks.into_iter().flat_map(|hunk| hunk.lines).collect();
+        let kept = lines.iter().filter(|line| matches!(line, DiffLine::Context(_))).count();
+        assert_eq!(kept, 2);
+        assert_eq!(lines.iter().filter(|line| matches!(line, DiffLine::Removed(_))).count(), 2);
+        assert_eq!(lines.iter().filter(|line| matches!(line, DiffLine::Added(_))).count(), 2);
+    }
+
+    #[test]
+    fn diff_hunks_should_serialize_tagged_lines() {
+        let json = serde_json::to_value(diff_revisions("old", "new")).unwrap();
+        assert_eq!(json, serde_json::json!([{
+            "old_start": 1, "old_lines": 1, "new_start": 1, "new_lines": 1,
+            "lines": [{"kind": "removed", "text": "old"}, {"kind": "added", "text": "new"}],
+        }]));
+    }
+}
+
+mod policy_tests {
+    use crate::models::policy::{ContentKind, ContentPolicy, DenyListPolicy, PolicyDecision};
+
+    #[test]
+    fn deny_list_policy_should_match_whole_words_regardless_of_case_and_accents() {
+        let policy = DenyListPolicy::new(["Spam", " crème ", ""]);
+        assert_eq!(policy.check(ContentKind::Title, "Nothing to see"), PolicyDecision::Allow);
+        assert_eq!(policy.check(ContentKind::Title, "Spammers and antispam"), PolicyDecision::Allow);
+        assert_eq!(
+            policy.check(ContentKind::Question, "Buy SPAM, now!"),
+            PolicyDecision::Reject(String::from("question contains the denied word `spam`"))
+        );
+        assert_eq!(
+            policy.check(ContentKind::Answer, "Try the CREME"),
+            PolicyDecision::Reject(String::from("answer contains the denied word `creme`"))
+        );
+    }
+
+    #[test]
+    fn deny_list_policy_should_flag_when_flagging() {
+        let policy = DenyListPolicy::new(["spam"]).flagging();
+        assert_eq!(policy.check(ContentKind::Answer, "spam"), PolicyDecision::Flag(String::from("answer contains the denied word `spam`")));
+        assert_eq!(policy.check(ContentKind::Answer, "ham"), PolicyDecision::Allow);
+    }
+}
+
+mod period_tests {
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use chrono_tz::America::{New_York, Santiago};
+    use chrono_tz::Asia::Tokyo;
+    use crate::models::period::Period;
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32, s: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, s).unwrap()
+    }
+
+    #[test]
+    fn today_should_span_23_hours_when_clocks_spring_forward() {
+        let (start, end) = Period::Today.bounds(utc(2024, 3, 10, 12, 0, 0), New_York);
+        assert_eq!((start, end), (utc(2024, 3, 10, 5, 0, 0), utc(2024, 3, 11, 4, 0, 0)));
+        assert_eq!(end - start, Duration::hours(23));
+    }
+
+    #[test]
+    fn today_should_span_25_hours_when_clocks_fall_back() {
+        let (start, end) = Period::Today.bounds(utc(2024, 11, 3, 12, 0, 0), New_York);
+        assert_eq!((start, end), (utc(2024, 11, 3, 4, 0, 0), utc(2024, 11, 4, 5, 0, 0)));
+        assert_eq!(end - start, Duration::hours(25));
+    }
+
+    #[test]
+    fn today_should_change_at_local_midnight() {
+        // Midnight in New York is 05:00 UTC in the winter
+        let (start, _) = Period::Today.bounds(utc(2024, 3, 10, 4, 59, 59), New_York);
+        assert_eq!(start, utc(2024, 3, 9, 5, 0, 0));
+        let (start, _) = Period::Today.bounds(utc(2024, 3, 10, 5, 0, 0), New_York);
+        assert_eq!(start, utc(2024, 3, 10, 5, 0, 0));
+        // Ahead of UTC, the local day starts on the previous UTC day
+        let (start, end) = Period::Today.bounds(utc(2024, 3, 10, 16, 0, 0), Tokyo);
+        assert_eq!((start, end), (utc(2024, 3, 10, 15, 0, 0), utc(2024, 3, 11, 15, 0, 0)));
+    }
+
+    #[test]
+    fn today_should_start_when_clocks_resume_if_midnight_is_skipped() {
+        // Santiago moves from 00:00 to 01:00 on 2023-09-03, so the day starts at 01:00 local time
+        let (start, end) = Period::Today.bounds(utc(2023, 9, 3, 12, 0, 0), Santiago);
+        assert_eq!((start, end), (utc(2023, 9, 3, 4, 0, 0), utc(2023, 9, 4, 3, 0, 0)));
+        assert_eq!(end - start, Duration::hours(23));
+    }
+
+    #[test]
+    fn this_week_should_start_on_monday_across_a_transition() {
+        // Sunday 2024-03-10 belongs to the week starting Monday 2024-03-04, before the clocks spring forward
+        let (start, end) = Period::ThisWeek.bounds(utc(2024, 3, 10, 12, 0, 0), New_York);
+        assert_eq!((start, end), (utc(2024, 3, 4, 5, 0, 0), utc(2024, 3, 11, 4, 0, 0)));
+        assert_eq!(end - start, Duration::hours(7 * 24 - 1));
+    }
+
+    #[test]
+    fn this_month_should_end_at_the_first_of_the_next_month() {
+        let (start, end) = Period::ThisMonth.bounds(utc(2024, 11, 30, 12, 0, 0), New_York);
+        assert_eq!((start, end), (utc(2024, 11, 1, 4, 0, 0), utc(2024, 12, 1, 5, 0, 0)));
+        let (start, end) = Period::ThisMonth.bounds(utc(2024, 12, 31, 12, 0, 0), New_York);
+        assert_eq!((start, end), (utc(2024, 12, 1, 5, 0, 0), utc(2025, 1, 1, 5, 0, 0)));
+    }
+}
+
+#[cfg(feature = "render")]
+mod render_tests {
+    use crate::models::ContentType;
+    use crate::models::render::render_html;
+
+    #[test]
+    fn render_html_should_render_markdown_with_code_blocks() {
+        let answer = "Use `sort`:\n\n```rust\nlet mut v = vec![3, 1];\nv.sort();\n```\n\n<script>alert(1)</script>\n\n[docs](javascript:alert(1))";
+        assert_eq!(
+            render_html(answer, ContentType::Markdown),
+            "<p>Use <code>sort</code>:</p>\n\
+            <pre><code class=\"language-rust\">let mut v = vec![3, 1];\nv.sort();\n</code></pre>\n\
+            &lt;script&gt;alert(1)&lt;/script&gt;\n\
+            <p><a href=\"\">docs</a></p>\n"
+        );
+    }
+
+    #[test]
+    fn render_html_should_escape_plain_text() {
+        let answer = "Use <b>`sort`</b> & **stop**\nthen print";
+        assert_eq!(
+            render_html(answer, ContentType::Text),
+            "<p>Use &lt;b&gt;`sort`&lt;/b&gt; &amp; **stop**<br />\nthen print</p>\n"
+        );
+    }
+}
@@ -0,0 +1,436 @@
+//! Contains `CachedQuestionDao`, a `QuestionDao` decorator keeping the questions it reads in memory, so that repeated
+//! reads of a question skip the database and, if configured to, still succeed while the database is briefly
+//! unavailable.
+//!
+//! Only `get_question` is served from the cache, every other call is forwarded to the wrapped data access object.
+//! Changes made through the decorator remove the question from the cache, while changes made elsewhere are seen once
+//! the cached question is older than `CacheConfig::ttl`. A read racing a change made through the decorator is returned
+//! but not cached, so the question it read before the change is not served afterwards.
+//!
+//! The cache holds at most `CacheConfig::capacity` questions. Questions too old to ever be served again are dropped
+//! as new ones are cached, and once it is full the question cached the longest ago makes room for the new one.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+use sqlx::types::Uuid;
+use crate::clock::{Clock, SystemClock};
+use crate::models::*;
+use crate::models::diff::DiffHunk;
+use crate::models::period::Period;
+use super::QuestionDao;
+
+/// The oldest a cached question is served when reading it again fails, unless configured otherwise.
+pub const DEFAULT_MAX_STALENESS_MINUTES: i64 = 10;
+
+/// The number of questions cached at most, unless configured otherwise.
+pub const DEFAULT_CACHE_CAPACITY: usize = 10_000;
+
+/// Options controlling how long cached questions are served.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheConfig {
+    /// How long a cached question is served without reading it again
+    pub ttl: Duration,
+    /// Serves a cached question older than `ttl` when reading it again fails with a transient error, see
+    /// `DbError::is_transient`
+    pub serve_stale_on_error: bool,
+    /// The oldest a cached question may be to be served when reading it again fails, older questions are never served
+    pub max_staleness: Duration,
+    /// The number of questions cached at most, a capacity of zero caches nothing
+    pub capacity: usize,
+}
+
+impl CacheConfig {
+    /// The oldest a cached question may be to be served in any way, after which it is dropped.
+    fn retention(&self) -> Duration {
+        match self.serve_stale_on_error {
+            true => self.ttl.max(self.max_staleness),
+            false => self.ttl,
+        }
+    }
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::seconds(30),
+            serve_stale_on_error: false,
+            max_staleness: Duration::minutes(DEFAULT_MAX_STALENESS_MINUTES),
+            capacity: DEFAULT_CACHE_CAPACITY,
+        }
+    }
+}
+
+/// A value read through a `CachedQuestionDao`, along with whether it may be out of date.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheOutcome<T> {
+    /// The value, read from the database or the cache
+    pub value: T,
+    /// Whether the value was served from the cache because reading it again failed
+    pub stale: bool,
+}
+
+/// A question in the cache, along with when it was read.
+struct Entry {
+    question: Question,
+    cached_at: DateTime<Utc>,
+}
+
+/// The cached questions by id, along with the ids of their serials, so either kind of `EntityId` finds them.
+#[derive(Default)]
+struct Entries {
+    questions: HashMap<Uuid, Entry>,
+    serials: HashMap<i64, Uuid>,
+    /// The questions in the order they were cached, the longest ago first. Questions removed or cached again since
+    /// are left in place and skipped, as their time no longer matches their entry
+    order: VecDeque<(Uuid, DateTime<Utc>)>,
+    /// Incremented by every invalidation, so a read can tell whether one happened while it was reading
+    generation: u64,
+}
+
+impl Entries {
+    fn id(&self, question_id: &EntityId) -> Option<Uuid> {
+        match question_id.kind() {
+            Ok(EntityIdKind::Uuid(id)) => Some(id),
+            Ok(EntityIdKind::Serial(serial)) => self.serials.get(&serial).copied(),
+            Err(_) => None,
+        }
+    }
+
+    fn remove(&mut self, id: Uuid) {
+        if let Some(entry) = self.questions.remove(&id) {
+            if let Some(serial) = entry.question.serial() {
+                self.serials.remove(&serial);
+            }
+        }
+    }
+
+    /// Caches a question read at `cached_at`, first dropping the questions cached before `expired_before` and, if the
+    /// cache is still full, the questions cached the longest ago.
+    fn insert(&mut self, question: Question, cached_at: DateTime<Utc>, expired_before: DateTime<Utc>, capacity: usize) {
+        if capacity == 0 {
+            return;
+        }
+        self.remove(question.id());
+        while let Some(&(id, at)) = self.order.front() {
+            if at >= expired_before && self.questions.len() < capacity {
+                break;
+            }
+            self.order.pop_front();
+            if self.questions.get(&id).is_some_and(|entry| entry.cached_at == at) {
+                self.remove(id);
+            }
+        }
+        // Skipped positions pile up when questions are removed or cached again without the cache filling up
+        if self.order.len() >= capacity.saturating_mul(2) {
+            let questions = &self.questions;
+            self.order.retain(|(id, at)| questions.get(id).is_some_and(|entry| entry.cached_at == *at));
+        }
+        if let Some(serial) = question.serial() {
+            self.serials.insert(serial, question.id());
+        }
+        self.order.push_back((question.id(), cached_at));
+        self.questions.insert(question.id(), Entry { question, cached_at });
+    }
+}
+
+/// A `QuestionDao` serving the questions read with `get_question` from memory, see the module documentation.
+pub struct CachedQuestionDao<D> {
+    inner: D,
+    config: CacheConfig,
+    clock: Arc<dyn Clock>,
+    entries: Mutex<Entries>,
+}
+
+impl<D: QuestionDao> CachedQuestionDao<D> {
+    /// Wraps `inner` with an empty cache, using the system clock and the default `CacheConfig`.
+    pub fn new(inner: D) -> Self {
+        Self { inner, config: CacheConfig::default(), clock: Arc::new(SystemClock), entries: Mutex::new(Entries::default()) }
+    }
+
+    /// Sets the `CacheConfig` deciding how long cached questions are served.
+    pub fn with_config(mut self, config: CacheConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Sets the `Clock` used to age cached questions.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// The wrapped data access object, whose calls bypass the cache.
+    pub fn inner(&self) -> &D {
+        &self.inner
+    }
+
+    /// Removes a question from the cache, so it is read again by the next call. A read in progress is not cached.
+    pub fn invalidate(&self, question_id: &EntityId) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.generation += 1;
+        if let Some(id) = entries.id(question_id) {
+            entries.remove(id);
+        }
+    }
+
+    /// The number of questions cached, including those too old to be served that have not been dropped yet.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().questions.len()
+    }
+
+    /// Whether no question is cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets a question like `get_question`, serving it from the cache while it is younger than `CacheConfig::ttl`.
+    /// With `CacheConfig::serve_stale_on_error`, a question cached no longer than `CacheConfig::max_staleness` ago is
+    /// served as stale when reading it again fails with a transient error.
+    ///
+    /// # Parameters
+    /// `question_id`: The `EntityId` of the `Question`
+    ///
+    /// # Returns
+    /// A `Result<CacheOutcome<Question>, DbError>`, `Ok(CacheOutcome)` with the question and whether it is stale in
+    /// the successful case, otherwise the `Err(DbError)` reading it failed with, including when no question was cached
+    /// or it is too old to be served.
+    pub async fn get_question_cached(&self, question_id: EntityId) -> Result<CacheOutcome<Question>, DbError> {
+        self.read(question_id, self.config.serve_stale_on_error).await
+    }
+
+    /// The question cached no longer than `max_age` ago, if any. A question too old to ever be served is dropped.
+    fn cached(&self, question_id: &EntityId, max_age: Duration) -> Option<Question> {
+        let mut entries = self.entries.lock().unwrap();
+        let id = entries.id(question_id)?;
+        let age = self.clock.now() - entries.questions.get(&id)?.cached_at;
+        if age > self.config.retention() {
+            entries.remove(id);
+            return None;
+        }
+        (age <= max_age).then(|| entries.questions[&id].question.clone())
+    }
+
+    async fn read(&self, question_id: EntityId, serve_stale: bool) -> Result<CacheOutcome<Question>, DbError> {
+        if let Some(question) = self.cached(&question_id, self.config.ttl) {
+            return Ok(CacheOutcome { value: question, stale: false });
+        }
+        let cached_at = self.clock.now();
+        let generation = self.entries.lock().unwrap().generation;
+        match self.inner.get_question(question_id.clone()).await {
+            Ok(question) => {
+                let mut entries = self.entries.lock().unwrap();
+                // The question may have changed since it was read, so it is only cached if nothing was invalidated
+                if entries.generation == generation {
+                    let expired_before = self.clock.now() - self.config.retention();
+                    entries.insert(question.clone(), cached_at, expired_before, self.config.capacity);
+                }
+                Ok(CacheOutcome { value: question, stale: false })
+            }
+            Err(e) if serve_stale && e.is_transient() => match self.cached(&question_id, self.config.max_staleness) {
+                Some(question) => Ok(CacheOutcome { value: question, stale: true }),
+                None => Err(e),
+            },
+            Err(e) => {
+                if e.kind() == DbErrorKind::NotFound {
+                    self.invalidate(&question_id);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Removes the question from the cache once `result` is known, whether the change succeeded or not.
+    fn invalidating<T>(&self, question_id: &EntityId, result: Result<T, DbError>) -> Result<T, DbError> {
+        self.invalidate(question_id);
+        result
+    }
+}
+
+impl<D: QuestionDao> QuestionDao for CachedQuestionDao<D> {
+    async fn create_question(&self, new_question: NewQuestion) -> Result<Question, DbError> {
+        self.inner.create_question(new_question).await
+    }
+
+    /// Serves fresh questions from the cache, but never stale ones, see `get_question_cached`.
+    async fn get_question(&self, question_id: EntityId) -> Result<Question, DbError> {
+        self.read(question_id, false).await.map(|outcome| outcome.value)
+    }
+
+    async fn get_question_as_of(&self, question_id: EntityId, at: DateTime<Utc>) -> Result<Question, DbError> {
+        self.inner.get_question_as_of(question_id, at).await
+    }
+
+    async fn get_revision_diff(&self, question_id: EntityId, from_rev: u32, to_rev: u32) -> Result<Vec<DiffHunk>, DbError> {
+        self.inner.get_revision_diff(question_id, from_rev, to_rev).await
+    }
+
+    async fn get_questions(&self) -> Result<Vec<Question>, DbError> {
+        self.inner.get_questions().await
+    }
+
+    async fn get_questions_projected(&self, fields: QuestionFields, page: PageRequest) -> Result<Page<QuestionPartial>, DbError> {
+        self.inner.get_questions_projected(fields, page).await
+    }
+
+    async fn get_questions_shorter_than(&self, max_words: i32) -> Result<Vec<Question>, DbError> {
+        self.inner.get_questions_shorter_than(max_words).await
+    }
+
+    async fn get_questions_in_period(&self, period: Period, tz: Tz) -> Result<Vec<Question>, DbError> {
+        self.inner.get_questions_in_period(period, tz).await
+    }
+
+    async fn get_questions_by_like_range(&self, min: i32, max: Option<i32>, created_after: Option<DateTime<Utc>>) -> Result<Vec<Question>, DbError> {
+        self.inner.get_questions_by_like_range(min, max, created_after).await
+    }
+
+    async fn get_questions_exceeding_like_rate(&self, likes_per_hour: f64, window: Duration) -> Result<Vec<QuestionLikeRate>, DbError> {
+        self.inner.get_questions_exceeding_like_rate(likes_per_hour, window).await
+    }
+
+    async fn get_question_header(&self, question_id: EntityId) -> Result<QuestionHeader, DbError> {
+        self.inner.get_question_header(question_id).await
+    }
+
+    async fn get_question_detail_consistent(&self, question_id: EntityId) -> Result<QuestionDetail, DbError> {
+        self.inner.get_question_detail_consistent(question_id).await
+    }
+
+    async fn get_question_detail_for_author(&self, question_id: EntityId, author_token: &str) -> Result<QuestionDetail, DbError> {
+        self.inner.get_question_detail_for_author(question_id, author_token).await
+    }
+
+    async fn get_question_detail_with(&self, question_id: EntityId, options: DetailOptions) -> Result<QuestionDetail, DbError> {
+        self.inner.get_question_detail_with(question_id, options).await
+    }
+
+    async fn get_questions_by_token(&self, author_token: &str) -> Result<Vec<Question>, DbError> {
+        self.inner.get_questions_by_token(author_token).await
+    }
+
+    async fn search_questions(&self, query: &str, ranking: SearchRankingConfig, limit: u32) -> Result<Vec<RankedQuestion>, DbError> {
+        self.inner.search_questions(query, ranking, limit).await
+    }
+
+    async fn get_activity_feed(&self, limit: u32, before: Option<ActivityCursor>) -> Result<Vec<ActivityItem>, DbError> {
+        self.inner.get_activity_feed(limit, before).await
+    }
+
+    async fn update_question(&self, question_id: EntityId, update: UpdateQuestion) -> Result<Question, DbError> {
+        self.invalidating(&question_id, self.inner.update_question(question_id.clone(), update).await)
+    }
+
+    async fn lock_question(&self, question_id: EntityId) -> Result<Question, DbError> {
+        self.invalidating(&question_id, self.inner.lock_question(question_id.clone()).await)
+    }
+
+    async fn unlock_question(&self, question_id: EntityId) -> Result<Question, DbError> {
+        self.invalidating(&question_id, self.inner.unlock_question(question_id.clone()).await)
+    }
+
+    async fn pin_question(&self, question_id: EntityId) -> Result<Question, DbError> {
+        self.invalidating(&question_id, self.inner.pin_question(question_id.clone()).await)
+    }
+
+    async fn unpin_question(&self, question_id: EntityId) -> Result<Question, DbError> {
+        self.invalidating(&question_id, self.inner.unpin_question(question_id.clone()).await)
+    }
+
+    async fn pin_answer(&self, question_id: EntityId, answer_id: EntityId) -> Result<Question, DbError> {
+        self.invalidating(&question_id, self.inner.pin_answer(question_id.clone(), answer_id).await)
+    }
+
+    async fn unpin_answer(&self, question_id: EntityId) -> Result<Question, DbError> {
+        self.invalidating(&question_id, self.inner.unpin_answer(question_id.clone()).await)
+    }
+
+    async fn accept_answer(&self, question_id: EntityId, answer_id: EntityId) -> Result<Question, DbError> {
+        self.invalidating(&question_id, self.inner.accept_answer(question_id.clone(), answer_id).await)
+    }
+
+    async fn unaccept_answer(&self, question_id: EntityId) -> Result<Question, DbError> {
+        self.invalidating(&question_id, self.inner.unaccept_answer(question_id.clone()).await)
+    }
+
+    async fn set_bounty(&self, question_id: EntityId, amount: i32, duration: Duration) -> Result<Question, DbError> {
+        self.invalidating(&question_id, self.inner.set_bounty(question_id.clone(), amount, duration).await)
+    }
+
+    async fn clear_bounty(&self, question_id: EntityId) -> Result<Question, DbError> {
+        self.invalidating(&question_id, self.inner.clear_bounty(question_id.clone()).await)
+    }
+
+    async fn upsert_translation(&self, question_id: EntityId, lang: &str, content: NewTranslation) -> Result<QuestionTranslation, DbError> {
+        self.inner.upsert_translation(question_id, lang, content).await
+    }
+
+    async fn get_translation(&self, question_id: EntityId, lang: &str) -> Result<Question, DbError> {
+        self.inner.get_translation(question_id, lang).await
+    }
+
+    async fn link_questions(&self, from_id: EntityId, to_id: EntityId, kind: LinkKind) -> Result<(), DbError> {
+        self.inner.link_questions(from_id, to_id, kind).await
+    }
+
+    async fn unlink_questions(&self, from_id: EntityId, to_id: EntityId, kind: LinkKind) -> Result<bool, DbError> {
+        self.inner.unlink_questions(from_id, to_id, kind).await
+    }
+
+    async fn get_linked_questions(&self, question_id: EntityId) -> Result<Vec<(LinkKind, Question)>, DbError> {
+        self.inner.get_linked_questions(question_id).await
+    }
+
+    async fn delete_question(&self, question_id: EntityId, force: bool) -> Result<Uuid, DbError> {
+        self.invalidating(&question_id, self.inner.delete_question(question_id.clone(), force).await)
+    }
+
+    async fn delete_question_with_policy(&self, question_id: EntityId, policy: DeletePolicy) -> Result<Uuid, DbError> {
+        self.invalidating(&question_id, self.inner.delete_question_with_policy(question_id.clone(), policy).await)
+    }
+
+    async fn increment_question_likes(&self, question_id: EntityId) -> Result<i64, DbError> {
+        self.invalidating(&question_id, self.inner.increment_question_likes(question_id.clone()).await)
+    }
+
+    async fn record_view(&self, question_id: EntityId, client_token: &str) -> Result<ViewOutcome, DbError> {
+        self.invalidating(&question_id, self.inner.record_view(question_id.clone(), client_token).await)
+    }
+
+    async fn create_question_idempotent(&self, new_question: NewQuestion, key: &str) -> Result<CreateOutcome, DbError> {
+        self.inner.create_question_idempotent(new_question, key).await
+    }
+
+    async fn upsert_question_by_external_id(&self, new_question: NewQuestion) -> Result<UpsertOutcome, DbError> {
+        let outcome = self.inner.upsert_question_by_external_id(new_question).await?;
+        self.invalidate(&EntityId::uuid(outcome.id()));
+        Ok(outcome)
+    }
+
+    async fn purge_idempotency_keys(&self, ttl: Duration) -> Result<u64, DbError> {
+        self.inner.purge_idempotency_keys(ttl).await
+    }
+
+    async fn expire_bounties(&self) -> Result<Vec<Uuid>, DbError> {
+        let expired = self.inner.expire_bounties().await?;
+        for id in &expired {
+            self.invalidate(&EntityId::uuid(*id));
+        }
+        Ok(expired)
+    }
+
+    async fn delete_questions_batched(
+        &self,
+        ids: Vec<EntityId>,
+        batch_size: usize,
+        force: bool,
+        progress: Option<&mut dyn FnMut(BatchProgress)>
+    ) -> Result<u64, DbError> {
+        let result = self.inner.delete_questions_batched(ids.clone(), batch_size, force, progress).await;
+        for id in &ids {
+            self.invalidate(id);
+        }
+        result
+    }
+}
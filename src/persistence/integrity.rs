@@ -0,0 +1,268 @@
+//! Contains checks for rows violating the invariants the data access objects maintain, such as after manual
+//! changes to the database, and the repair of the violations that can be fixed safely.
+
+use std::fmt::Display;
+use sqlx::{Connection, PgPool};
+use sqlx::types::Uuid;
+use crate::models::{DbError, DeletePolicy};
+use super::like_entity_type;
+use super::lock::{self, LockOutcome, MaintenanceLock};
+use super::schema::{SchemaName, Tables};
+
+/// The rows found violating the invariants of the schema, by category. Each list is ordered by id.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct IntegrityReport {
+    /// The answers whose `question_id` refers to a question that does not exist, possible if the foreign key
+    /// was ever dropped
+    pub dangling_answers: Vec<Uuid>,
+    /// The questions whose pinned or accepted answer is an answer to another question, or to none
+    pub foreign_answer_pointers: Vec<Uuid>,
+    /// The questions with fewer than zero likes
+    pub negative_question_likes: Vec<Uuid>,
+    /// The answers with fewer than zero likes
+    pub negative_answer_likes: Vec<Uuid>,
+    /// The questions last updated before they were created
+    pub updated_before_created: Vec<Uuid>,
+}
+
+impl IntegrityReport {
+    /// Whether no violations were found.
+    pub fn is_clean(&self) -> bool {
+        self.dangling_answers.is_empty()
+            && self.foreign_answer_pointers.is_empty()
+            && self.negative_question_likes.is_empty()
+            && self.negative_answer_likes.is_empty()
+            && self.updated_before_created.is_empty()
+    }
+}
+
+impl Display for IntegrityReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Found {} dangling answers, {} questions pinning or accepting answers to other questions, {} questions and {} \
+            answers with negative likes, and {} questions updated before they were created",
+            self.dangling_answers.len(),
+            self.foreign_answer_pointers.len(),
+            self.negative_question_likes.len(),
+            self.negative_answer_likes.len(),
+            self.updated_before_created.len()
+        )
+    }
+}
+
+/// The result of repairing the violations of an `IntegrityReport`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RepairReport {
+    /// The number of dangling answers deleted, with `DeletePolicy::Cascade`
+    pub answers_deleted: u64,
+    /// The number of dangling answers kept without a question, with `DeletePolicy::Orphan`
+    pub answers_orphaned: u64,
+    /// The dangling answers left unchanged, with `DeletePolicy::Restrict`
+    pub answers_left: Vec<Uuid>,
+    /// The number of questions whose pinned or accepted answer to another question was cleared
+    pub pointers_cleared: u64,
+    /// The number of questions and answers whose negative likes were reset to zero
+    pub likes_reset: u64,
+    /// The number of questions whose last update was moved to the time they were created
+    pub timestamps_fixed: u64,
+}
+
+impl Display for RepairReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Deleted {} and orphaned {} dangling answers, left {}, cleared the answer pointers of {} questions, reset {} like \
+            counters and fixed {} timestamps",
+            self.answers_deleted,
+            self.answers_orphaned,
+            self.answers_left.len(),
+            self.pointers_cleared,
+            self.likes_reset,
+            self.timestamps_fixed
+        )
+    }
+}
+
+/// The condition of a question whose answer pointer `column` refers to an answer of another question, or of none.
+fn foreign_answer(column: &str) -> String {
+    format!(
+        "EXISTS (SELECT 1 FROM answers WHERE answers.id = questions.{column} \
+            AND answers.question_id IS DISTINCT FROM questions.id)"
+    )
+}
+
+/// Checks the database for rows violating the invariants of the schema, reading every category from one snapshot.
+///
+/// # Parameters
+/// `pool`: The pool of the database to check
+///
+/// # Returns
+/// A `Result<IntegrityReport, DbError>`, `Ok(IntegrityReport)` listing the offending rows by category, which is
+/// clean if none were found, otherwise `Err(DbError)`.
+pub async fn check_integrity(pool: &PgPool) -> Result<IntegrityReport, DbError> {
+    check(pool, &Tables::default()).await
+}
+
+/// Checks the tables of `schema` like `check_integrity`, see `schema::SchemaName`.
+pub async fn check_integrity_in_schema(pool: &PgPool, schema: &SchemaName) -> Result<IntegrityReport, DbError> {
+    check(pool, &Tables::in_schema(schema.clone())).await
+}
+
+/// Checks the `tables` for rows violating the invariants of the schema, see `check_integrity`.
+async fn check(pool: &PgPool, tables: &Tables) -> Result<IntegrityReport, DbError> {
+    let mut conn = pool.acquire().await.map_err(DbError::from)?;
+    let mut tx = conn.begin().await.map_err(DbError::from)?;
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+        .execute(&mut *tx)
+        .await
+        .map_err(DbError::from)?;
+    let mut report = IntegrityReport::default();
+    let foreign_answer_pointers = format!(
+        "SELECT id FROM questions WHERE {} OR {} ORDER BY id",
+        foreign_answer("pinned_answer_id"),
+        foreign_answer("accepted_answer_id")
+    );
+    for (ids, query) in [
+        (
+            &mut report.dangling_answers,
+            "SELECT id FROM answers WHERE question_id IS NOT NULL \
+                AND NOT EXISTS (SELECT 1 FROM questions WHERE questions.id = answers.question_id) ORDER BY id",
+        ),
+        (&mut report.foreign_answer_pointers, foreign_answer_pointers.as_str()),
+        (&mut report.negative_question_likes, "SELECT id FROM questions WHERE likes < 0 ORDER BY id"),
+        (&mut report.negative_answer_likes, "SELECT id FROM answers WHERE likes < 0 ORDER BY id"),
+        (&mut report.updated_before_created, "SELECT id FROM questions WHERE updated_at < created_at ORDER BY id"),
+    ] {
+        *ids = sqlx::query_scalar(&tables.sql(query)).fetch_all(&mut *tx).await.map_err(DbError::from)?;
+    }
+    tx.commit().await.map_err(DbError::commit)?;
+    Ok(report)
+}
+
+/// Repairs the violations listed in `report` in one transaction. Every row is checked again before it is changed,
+/// so rows fixed since the report was made are left alone.
+///
+/// Pinned and accepted answers to other questions are cleared, leaving the reputation of their authors as it is.
+/// Negative likes are reset to zero, recording the change in the like audit log, and questions updated before they
+/// were created are marked as updated when they were created. Dangling answers are handled with `policy`: deleted
+/// with `DeletePolicy::Cascade`, kept without a question with `DeletePolicy::Orphan`, or left unchanged for manual
+/// repair with `DeletePolicy::Restrict`.
+///
+/// # Parameters
+/// `pool`: The pool of the database to repair
+/// `report`: The `IntegrityReport` of the violations to repair
+/// `policy`: The `DeletePolicy` dangling answers are handled with
+///
+/// # Returns
+/// A `Result<RepairReport, DbError>`, `Ok(RepairReport)` counting the rows repaired, otherwise `Err(DbError)` and
+/// nothing is changed.
+pub async fn repair(pool: &PgPool, report: &IntegrityReport, policy: DeletePolicy) -> Result<RepairReport, DbError> {
+    repair_tables(pool, &Tables::default(), report, policy).await
+}
+
+/// Repairs the violations listed in `report` like `repair`, in the tables of `schema` it was checked in with
+/// `check_integrity_in_schema`.
+pub async fn repair_in_schema(
+    pool: &PgPool,
+    schema: &SchemaName,
+    report: &IntegrityReport,
+    policy: DeletePolicy,
+) -> Result<RepairReport, DbError> {
+    repair_tables(pool, &Tables::in_schema(schema.clone()), report, policy).await
+}
+
+/// Repairs the violations listed in `report` in the `tables`, see `repair`.
+async fn repair_tables(
+    pool: &PgPool,
+    tables: &Tables,
+    report: &IntegrityReport,
+    policy: DeletePolicy,
+) -> Result<RepairReport, DbError> {
+    const DANGLING: &str = "id = ANY($1) AND question_id IS NOT NULL \
+        AND NOT EXISTS (SELECT 1 FROM questions WHERE questions.id = answers.question_id)";
+    let mut repaired = RepairReport::default();
+    let mut tx = pool.begin().await.map_err(DbError::from)?;
+    match policy {
+        DeletePolicy::Cascade => {
+            repaired.answers_deleted = sqlx::query(&tables.sql(&format!("DELETE FROM answers WHERE {DANGLING}")))
+                .bind(&report.dangling_answers)
+                .execute(&mut *tx)
+                .await
+                .map_err(DbError::update)?
+                .rows_affected();
+        }
+        DeletePolicy::Orphan => {
+            let orphan = tables.sql(&format!("UPDATE answers SET question_id = NULL WHERE {DANGLING}")).into_owned();
+            repaired.answers_orphaned = sqlx::query(&orphan)
+                .bind(&report.dangling_answers)
+                .execute(&mut *tx)
+                .await
+                .map_err(DbError::update)?
+                .rows_affected();
+        }
+        DeletePolicy::Restrict => repaired.answers_left = report.dangling_answers.clone(),
+    }
+    // Each pointer is only cleared if it still refers to an answer of another question
+    let (pinned, accepted) = (foreign_answer("pinned_answer_id"), foreign_answer("accepted_answer_id"));
+    repaired.pointers_cleared = sqlx::query(&tables.sql(&format!(
+        "UPDATE questions SET \
+            pinned_answer_id = CASE WHEN {pinned} THEN NULL ELSE pinned_answer_id END, \
+            accepted_answer_id = CASE WHEN {accepted} THEN NULL ELSE accepted_answer_id END \
+        WHERE id = ANY($1) AND ({pinned} OR {accepted})"
+    )))
+        .bind(&report.foreign_answer_pointers)
+        .execute(&mut *tx)
+        .await
+        .map_err(DbError::update)?
+        .rows_affected();
+    for (table, ids) in [("questions", &report.negative_question_likes), ("answers", &report.negative_answer_likes)] {
+        let reset: i64 = sqlx::query_scalar(&tables.sql(&format!(
+            "WITH previous AS ( \
+                SELECT id, likes FROM {table} WHERE id = ANY($1) AND likes < 0 FOR UPDATE \
+            ), updated AS ( \
+                UPDATE {table} SET likes = 0 FROM previous WHERE {table}.id = previous.id \
+                RETURNING {table}.id, -previous.likes AS delta \
+            ), recorded AS ( \
+                INSERT INTO like_events (entity_type, entity_id, delta) SELECT $2, id, delta FROM updated \
+            ) \
+            SELECT COUNT(*) FROM updated"
+        )))
+            .bind(ids)
+            .bind(like_entity_type(table))
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(DbError::update)?;
+        repaired.likes_reset += reset as u64;
+    }
+    repaired.timestamps_fixed = sqlx::query(&tables.sql(
+        "UPDATE questions SET updated_at = created_at WHERE id = ANY($1) AND updated_at < created_at"
+    ))
+        .bind(&report.updated_before_created)
+        .execute(&mut *tx)
+        .await
+        .map_err(DbError::update)?
+        .rows_affected();
+    tx.commit().await.map_err(DbError::commit)?;
+    Ok(repaired)
+}
+
+/// Repairs the violations listed in `report` like `repair`, unless another instance is already repairing, see
+/// `lock::with_advisory_lock`.
+pub async fn repair_guarded(pool: &PgPool, report: &IntegrityReport, policy: DeletePolicy) -> Result<LockOutcome<RepairReport>, DbError> {
+    lock::with_advisory_lock(pool, MaintenanceLock::RepairIntegrity, || repair(pool, report, policy)).await
+}
+
+/// Repairs the violations listed in `report` like `repair_in_schema`, unless another instance is already repairing,
+/// in any schema, see `lock::with_advisory_lock`.
+pub async fn repair_guarded_in_schema(
+    pool: &PgPool,
+    schema: &SchemaName,
+    report: &IntegrityReport,
+    policy: DeletePolicy,
+) -> Result<LockOutcome<RepairReport>, DbError> {
+    lock::with_advisory_lock(pool, MaintenanceLock::RepairIntegrity, || {
+        repair_in_schema(pool, schema, report, policy)
+    })
+    .await
+}
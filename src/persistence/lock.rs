@@ -0,0 +1,104 @@
+//! Contains a guard ensuring that maintenance operations run from only one instance of an application at a time,
+//! using Postgres advisory locks.
+
+use std::future::Future;
+use sqlx::{Connection, PgPool};
+use crate::models::DbError;
+
+/// The first half of the key of every advisory lock taken by the crate, so that they cannot collide with the
+/// advisory locks of other applications sharing the database.
+const NAMESPACE: i32 = 0x5141_4e53;
+
+/// The maintenance operations guarded by an advisory lock, each with its own lock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MaintenanceLock {
+    /// Purging expired idempotency keys
+    PurgeIdempotencyKeys,
+    /// Recomputing the character and word counts of content
+    RecomputeContentStats,
+    /// Rebuilding the indexes of the crate's tables
+    Reindex,
+    /// Repairing rows violating the invariants of the schema
+    RepairIntegrity,
+}
+
+impl MaintenanceLock {
+    /// The second half of the key of the lock, unique within the crate's `NAMESPACE`.
+    pub fn key(&self) -> i32 {
+        match self {
+            MaintenanceLock::PurgeIdempotencyKeys => 1,
+            MaintenanceLock::RecomputeContentStats => 2,
+            MaintenanceLock::Reindex => 3,
+            MaintenanceLock::RepairIntegrity => 4,
+        }
+    }
+}
+
+/// The outcome of an operation guarded by `with_advisory_lock`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockOutcome<T> {
+    /// The lock was acquired and the operation ran, returning `T`
+    Ran(T),
+    /// Another session held the lock, so the operation did not run
+    SkippedBecauseLocked,
+}
+
+impl<T> LockOutcome<T> {
+    /// The result of the operation, `None` if it was skipped.
+    pub fn ran(self) -> Option<T> {
+        match self {
+            LockOutcome::Ran(value) => Some(value),
+            LockOutcome::SkippedBecauseLocked => None,
+        }
+    }
+
+    /// Whether the operation was skipped because another session held the lock.
+    pub fn is_skipped(&self) -> bool {
+        matches!(self, LockOutcome::SkippedBecauseLocked)
+    }
+}
+
+/// Runs `f` while holding the advisory lock of `lock`, unless another session holds it already, in which case
+/// `f` is not run. Only one guarded run of an operation can therefore be in progress across every instance
+/// connected to the database.
+///
+/// The lock is held by a connection taken out of the pool for the duration of the run, and released when `f`
+/// completes, whether or not it succeeds. The connection is then closed, so that even if the release fails, or the
+/// run is cancelled before it completes, ending the session releases the lock rather than a pooled connection
+/// keeping it.
+///
+/// # Parameters
+/// `pool`: The pool of the database the lock is taken in
+/// `lock`: The `MaintenanceLock` of the operation
+/// `f`: Creates the future of the operation, called only if the lock is acquired
+///
+/// # Returns
+/// A `Result<LockOutcome<T>, DbError>`, `Ok(LockOutcome::Ran(T))` if the operation ran and succeeded, or
+/// `Ok(LockOutcome::SkippedBecauseLocked)` if the lock was held elsewhere. The error of a failed operation is
+/// returned as is, and failing to take the lock is returned as `Err(DbError)`.
+pub async fn with_advisory_lock<T, F, Fut>(pool: &PgPool, lock: MaintenanceLock, f: F) -> Result<LockOutcome<T>, DbError>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<T, DbError>>,
+{
+    let mut conn = pool.acquire().await.map_err(DbError::from)?.detach();
+    let acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_lock($1, $2)")
+        .bind(NAMESPACE)
+        .bind(lock.key())
+        .fetch_one(&mut conn)
+        .await
+        .map_err(DbError::from)?;
+    if !acquired {
+        let _ = conn.close().await;
+        return Ok(LockOutcome::SkippedBecauseLocked);
+    }
+    let res = f().await;
+    // Closing the session releases the lock even if unlocking fails, so neither error is reported over `res`
+    let _ = sqlx::query("SELECT pg_advisory_unlock($1, $2)")
+        .bind(NAMESPACE)
+        .bind(lock.key())
+        .execute(&mut conn)
+        .await;
+    let _ = conn.close().await;
+    res.map(LockOutcome::Ran)
+}
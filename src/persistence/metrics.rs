@@ -0,0 +1,133 @@
+//! Contains `DaoMetrics`, a `QueryObserver` counting and timing the calls made to the data access objects it is set
+//! on, by method and outcome, so that they can be scraped in the Prometheus text exposition format.
+//!
+//! One `DaoMetrics` may be shared by several data access objects, whose series are distinguished by the `entity`
+//! label. A call's outcome is `ok` if it succeeded, and the classification of its error otherwise, see `DbErrorKind`.
+//!
+//! # Example
+//! ```no_run
+//! # fn example(pool: sqlx::PgPool) {
+//! use std::sync::Arc;
+//! use question_answer::persistence::{AnswerDaoImpl, QuestionDaoImpl};
+//! use question_answer::persistence::metrics::DaoMetrics;
+//!
+//! let metrics = Arc::new(DaoMetrics::default());
+//! let question_dao = QuestionDaoImpl::new(pool.clone()).with_observer(metrics.clone());
+//! let answer_dao = AnswerDaoImpl::new(pool).with_observer(metrics.clone());
+//! print!("{}", metrics.prometheus_export());
+//! # }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use crate::models::DbErrorKind;
+use super::observer::{DaoEvent, EntityKind, QueryObserver};
+use super::pool::escape_label_value;
+
+/// The upper bounds, in seconds, of the buckets the durations of calls are counted in.
+pub const LATENCY_BUCKETS: [f64; 12] = [0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0];
+
+/// The labels of a series, ordered so that the export is stable.
+type SeriesKey = (&'static str, &'static str, &'static str);
+
+/// The calls observed for one entity, method and outcome.
+#[derive(Debug, Default)]
+struct Series {
+    /// The number of calls by bucket, not cumulative, the calls slower than every bucket are only in `count`
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+/// A `QueryObserver` collecting call counters and latency histograms, see the module documentation.
+#[derive(Debug, Default)]
+pub struct DaoMetrics {
+    series: Mutex<BTreeMap<SeriesKey, Series>>,
+}
+
+impl DaoMetrics {
+    /// The number of calls observed so far for a method and outcome, across entities.
+    pub fn calls(&self, method: &str, outcome: &str) -> u64 {
+        let series = self.series.lock().unwrap_or_else(|e| e.into_inner());
+        series.iter().filter(|((_, m, o), _)| *m == method && *o == outcome).map(|(_, series)| series.count).sum()
+    }
+
+    /// Renders the calls observed so far in the Prometheus text exposition format, as the counter
+    /// `qa_dao_calls_total` and the histogram `qa_dao_call_duration_seconds`, both labelled with `entity`, `method`
+    /// and `outcome`, whose values are escaped.
+    ///
+    /// # Returns
+    /// The exposition text, ending with a newline, with only the metadata of both metrics if no call was observed.
+    pub fn prometheus_export(&self) -> String {
+        let series = self.series.lock().unwrap_or_else(|e| e.into_inner());
+        let labels: Vec<String> = series
+            .keys()
+            .map(|(entity, method, outcome)| {
+                format!(
+                    "entity=\"{}\",method=\"{}\",outcome=\"{}\"",
+                    escape_label_value(entity),
+                    escape_label_value(method),
+                    escape_label_value(outcome),
+                )
+            })
+            .collect();
+        let mut text = String::from(
+            "# HELP qa_dao_calls_total The completed calls to the data access objects, by method and outcome.\n\
+            # TYPE qa_dao_calls_total counter\n",
+        );
+        for (labels, series) in labels.iter().zip(series.values()) {
+            let _ = writeln!(text, "qa_dao_calls_total{{{labels}}} {}", series.count);
+        }
+        text.push_str(
+            "# HELP qa_dao_call_duration_seconds How long the calls to the data access objects took, by method and outcome.\n\
+            # TYPE qa_dao_call_duration_seconds histogram\n",
+        );
+        for (labels, series) in labels.iter().zip(series.values()) {
+            let mut cumulative = 0;
+            for (bound, calls) in LATENCY_BUCKETS.iter().zip(series.buckets) {
+                cumulative += calls;
+                let _ = writeln!(text, "qa_dao_call_duration_seconds_bucket{{{labels},le=\"{bound}\"}} {cumulative}");
+            }
+            let _ = writeln!(text, "qa_dao_call_duration_seconds_bucket{{{labels},le=\"+Inf\"}} {}", series.count);
+            let _ = writeln!(text, "qa_dao_call_duration_seconds_sum{{{labels}}} {}", series.sum);
+            let _ = writeln!(text, "qa_dao_call_duration_seconds_count{{{labels}}} {}", series.count);
+        }
+        text
+    }
+}
+
+impl QueryObserver for DaoMetrics {
+    fn on_complete(&self, event: DaoEvent) {
+        let key = (entity_label(event.entity), event.method, outcome_label(event.error));
+        let seconds = event.duration.as_secs_f64();
+        let mut series = self.series.lock().unwrap_or_else(|e| e.into_inner());
+        let series = series.entry(key).or_default();
+        if let Some(bucket) = LATENCY_BUCKETS.iter().position(|bound| seconds <= *bound) {
+            series.buckets[bucket] += 1;
+        }
+        series.count += 1;
+        series.sum += seconds;
+    }
+}
+
+/// The value of the `entity` label.
+fn entity_label(entity: EntityKind) -> &'static str {
+    match entity {
+        EntityKind::Question => "question",
+        EntityKind::Answer => "answer",
+    }
+}
+
+/// The value of the `outcome` label, matching the serialized `DbErrorKind` of a failed call.
+fn outcome_label(error: Option<DbErrorKind>) -> &'static str {
+    match error {
+        None => "ok",
+        Some(DbErrorKind::NotFound) => "not_found",
+        Some(DbErrorKind::InvalidId) => "invalid_id",
+        Some(DbErrorKind::Conflict) => "conflict",
+        Some(DbErrorKind::Validation) => "validation",
+        Some(DbErrorKind::Unavailable) => "unavailable",
+        Some(DbErrorKind::Internal) => "internal",
+    }
+}
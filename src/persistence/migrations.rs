@@ -0,0 +1,126 @@
+//! Contains the embedded, reversible schema migrations and helpers for applying them.
+
+use std::fmt::Display;
+use sqlx::migrate::{MigrateError, Migrator};
+use sqlx::{ConnectOptions, Connection, Error, PgPool};
+use super::schema::SchemaName;
+
+/// The crate's migrations, embedded from the `migrations` directory at compile time.
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+/// Options guarding the destructive `reset`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResetOptions {
+    /// Must be set for `reset` to drop anything
+    pub allow_destructive: bool,
+    /// Allows resetting a database whose name contains neither `dev` nor `test`
+    pub force: bool,
+}
+
+/// The errors returned when applying or resetting migrations.
+#[derive(Debug)]
+pub enum MigrationError {
+    /// A destructive operation was attempted without `ResetOptions::allow_destructive`
+    DestructiveNotAllowed,
+    /// The named database does not look disposable and the reset was not forced
+    ProtectedDatabase(String),
+    /// The database could not be reached or queried
+    Access(Error),
+    /// The migrations could not be applied or reverted
+    Migrate(MigrateError),
+}
+
+impl Display for MigrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MigrationError::DestructiveNotAllowed => write!(f, "Destructive migration operations have not been allowed"),
+            MigrationError::ProtectedDatabase(name) => write!(f, "Refusing to reset database `{name}`, its name contains neither `dev` nor `test`"),
+            MigrationError::Access(e) => write!(f, "Error when accessing database: {e}"),
+            MigrationError::Migrate(e) => write!(f, "Error applying migrations: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+/// Applies any migrations that have not yet been applied to the database.
+pub async fn run(pool: &PgPool) -> Result<(), MigrationError> {
+    MIGRATOR.run(pool).await.map_err(MigrationError::Migrate)
+}
+
+/// Applies any migrations that have not yet been applied to `schema`, creating the schema if it does not exist.
+/// The schema keeps its own migration history, so it is migrated independently of the `public` schema and of
+/// any other schema. The functions the migrations install in the schema, such as those of triggers, are set to
+/// find their tables there whatever the search path of the statements firing them.
+///
+/// The migrations are applied on a connection of their own, closed once they are, so the search path they are
+/// applied with never reaches a connection of the pool.
+///
+/// # Parameters
+/// `pool`: The pool of the database containing the schema
+/// `schema`: The `SchemaName` of the schema the tables are created in
+///
+/// # Returns
+/// A `Result<(), MigrationError>`, `Ok(())` once the schema is up to date.
+pub async fn run_in_schema(pool: &PgPool, schema: &SchemaName) -> Result<(), MigrationError> {
+    let mut conn = pool.connect_options().connect().await.map_err(MigrationError::Access)?;
+    // The name is a validated identifier, so it can be written into the statements
+    sqlx::query(&format!("CREATE SCHEMA IF NOT EXISTS {schema}"))
+        .execute(&mut conn)
+        .await
+        .map_err(MigrationError::Access)?;
+    sqlx::query(&format!("SET search_path TO {schema}, public"))
+        .execute(&mut conn)
+        .await
+        .map_err(MigrationError::Access)?;
+    MIGRATOR.run(&mut conn).await.map_err(MigrationError::Migrate)?;
+    let functions: Vec<(String, String)> = sqlx::query_as(
+        "SELECT proname::text, pg_get_function_identity_arguments(pg_proc.oid) FROM pg_proc \
+        JOIN pg_namespace ON pg_namespace.oid = pronamespace WHERE nspname = $1 AND prokind = 'f'"
+    )
+        .bind(schema.as_str())
+        .fetch_all(&mut conn)
+        .await
+        .map_err(MigrationError::Access)?;
+    for (name, arguments) in functions {
+        sqlx::query(&format!("ALTER FUNCTION {schema}.{name}({arguments}) SET search_path = {schema}"))
+            .execute(&mut conn)
+            .await
+            .map_err(MigrationError::Access)?;
+    }
+    conn.close().await.map_err(MigrationError::Access)
+}
+
+/// Reverts every migration, dropping all of the crate's tables along with their data, and then
+/// re-applies them, leaving an empty, up to date schema. Intended for local development only.
+///
+/// # Parameters
+/// `pool`: The pool of the database to reset
+/// `options`: `ResetOptions` that must allow destructive operations, and must force the reset of
+/// databases whose name contains neither `dev` nor `test`
+///
+/// # Returns
+/// A `Result<(), MigrationError>`, `Ok(())` once the schema has been recreated. If the options do not permit
+/// the reset nothing is dropped and `Err(MigrationError::DestructiveNotAllowed)` or
+/// `Err(MigrationError::ProtectedDatabase)` is returned.
+pub async fn reset(pool: &PgPool, options: ResetOptions) -> Result<(), MigrationError> {
+    if !options.allow_destructive {
+        return Err(MigrationError::DestructiveNotAllowed);
+    }
+    let name: String = sqlx::query_scalar("SELECT current_database()")
+        .fetch_one(pool)
+        .await
+        .map_err(MigrationError::Access)?;
+    if !options.force && !is_disposable(&name) {
+        return Err(MigrationError::ProtectedDatabase(name));
+    }
+    // A target version of zero reverts every applied migration
+    MIGRATOR.undo(pool, 0).await.map_err(MigrationError::Migrate)?;
+    run(pool).await
+}
+
+/// Whether a database's name marks it as safe to reset without forcing.
+pub(crate) fn is_disposable(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.contains("dev") || name.contains("test")
+}
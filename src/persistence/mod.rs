@@ -1,22 +1,58 @@
 //! Contains the trait needed for implementing a database access object as well
 //! as implementations.
 
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
-use sqlx::PgPool;
+use std::sync::Arc;
+use sqlx::{Connection, Executor, PgPool, Postgres, Transaction};
 use sqlx::postgres::PgRow;
 use sqlx::Row;
 use sqlx::types::Uuid;
-use sqlx::FromRow;
-use crate::models::prelude::*;
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+use crate::clock::{Clock, SystemClock};
+use crate::models::*;
+use crate::models::content_stats::ContentStats;
+use crate::models::diff::{diff_revisions, DiffHunk};
+use crate::models::dto::{AnswerResponse, QuestionDetailResponse, QuestionResponse};
+use crate::models::normalize::normalize_title;
+use crate::models::period::Period;
+use crate::models::policy::{ContentKind, ContentPolicy, PolicyDecision};
+use self::lock::{LockOutcome, MaintenanceLock};
+use self::observer::{observed_dao, EntityKind, QueryObserver};
+use self::pool_monitor::AcquireWaits;
+use self::schema::{SchemaName, Tables, TABLES};
+use self::scoped::Source;
 
+pub mod cache;
+pub mod integrity;
+pub mod lock;
+pub mod metrics;
+pub mod migrations;
+pub mod observer;
+pub mod paginate;
+pub mod pool;
+pub mod pool_monitor;
+pub mod schema;
+pub mod scoped;
 #[cfg(test)]
 mod test;
 
+/// The data access traits and their implementations, along with the model types they use.
 pub mod prelude {
-    pub use super::*;
+    pub use sqlx::PgPool;
+    pub use crate::clock::{Clock, SystemClock};
+    pub use crate::models::prelude::*;
+    pub use super::{
+        like_entity, AdminDao, AdminDaoImpl, AnswerDao, AnswerDaoImpl, AuthorDao, AuthorDaoImpl, CategoryDao, CategoryDaoImpl,
+        LikeDao, LikeDaoImpl, QuestionDao, QuestionDaoImpl, StatsDao, StatsDaoImpl, SubscriptionDao, SubscriptionDaoImpl,
+        TagDao, TagDaoImpl,
+    };
+    pub use super::scoped::ScopedDao;
 }
 
 /// The interface for any database access object that will interact with the the questions database.
+#[allow(async_fn_in_trait)]
 pub trait QuestionDao {
     /// # Required Method
     /// Creates a new question and inserts it into the database.
@@ -25,9 +61,14 @@ pub trait QuestionDao {
     /// `new_question`: The content of the new question to be created and inserted into the database
     ///
     /// # Returns
-    /// A `Result<Uuid, DbError>`, if the question was created successfully a `Ok(Uuid)` will be returned
-    /// where the `Uuid` represents the id of the newly created question, otherwise `Err(DbError)` will be returned.
-    async fn create_question(&self, new_question: NewQuestion) -> Result<Uuid, DbError>;
+    /// A `Result<Question, DbError>`, if the question was created successfully a `Ok(Question)` will be returned
+    /// containing the question as persisted, including its generated id, initial likes and creation time,
+    /// otherwise `Err(DbError)` will be returned.
+    /// Content exceeding the configured `ContentLimits` is rejected with `Err(DbError::Validation)`, and content
+    /// rejected by the configured `ContentPolicy` with `ValidationReason::PolicyViolation`. An author token that
+    /// created as many questions within the last hour as the configured `CreationQuota` allows is rejected with
+    /// `UnavailableReason::QuotaExceeded`.
+    async fn create_question(&self, new_question: NewQuestion) -> Result<Question, DbError>;
 
     /// # Required Method
     /// Gets a question from the database if present.
@@ -40,22 +81,440 @@ pub trait QuestionDao {
     async fn get_question(&self, question_id: EntityId) -> Result<Question, DbError>;
 
     /// # Required Method
-    /// Gets a `Vec` of all questions in the database
+    /// Gets a question as it was at a past time, with the title and content it had then. Only the content is
+    /// historical: likes, views, pins, locks and the other fields are the current values of the question.
+    ///
+    /// # Parameters
+    /// `question_id`: The `EntityId` of the `Question` being read
+    /// `at`: The time the content is read as of. The content of an edit made exactly at `at` is included
+    ///
+    /// # Returns
+    /// A `Result<Question, DbError>`, in the success case `Ok(Question)` with the content as of `at`. A missing
+    /// question, or one that was created after `at`, is reported as `Err(DbError::NotFound)`, otherwise
+    /// `Err(DbError)`.
+    async fn get_question_as_of(&self, question_id: EntityId, at: DateTime<Utc>) -> Result<Question, DbError>;
+
+    /// # Required Method
+    /// Compares the content of two revisions of a question line by line. Revision 1 is the content the question was
+    /// created with, each edit adds the next revision, and the last revision is the current content.
+    ///
+    /// # Parameters
+    /// `question_id`: The `EntityId` of the `Question` being compared
+    /// `from_rev`: The revision compared from
+    /// `to_rev`: The revision compared to, which may come before `from_rev` to compare in reverse
+    ///
+    /// # Returns
+    /// A `Result<Vec<DiffHunk>, DbError>`, in the success case `Ok(Vec<DiffHunk>)` with the changes turning the
+    /// content of `from_rev` into that of `to_rev`, empty if they have the same lines. A missing question is reported
+    /// as `Err(DbError::NotFound)` and a revision the question does not have as `Err(DbError::Validation)`, otherwise
+    /// `Err(DbError)`.
+    async fn get_revision_diff(&self, question_id: EntityId, from_rev: u32, to_rev: u32) -> Result<Vec<DiffHunk>, DbError>;
+
+    /// # Required Method
+    /// Gets a `Vec` of all questions in the database, pinned questions first with the most recently pinned
+    /// first, followed by the other questions oldest first.
     ///
     /// # Returns
     /// A `Result<Vec<Question>>, DbError>`, in the success case `Ok(Vec<Question>)`, otherwise `Err(DbError)`.
     async fn get_questions(&self, ) -> Result<Vec<Question>, DbError>;
 
     /// # Required Method
-    /// Deletes a question from the database.
+    /// Gets a page of the questions in the order of `get_questions`, reading only the columns selected by `fields`
+    /// so that listings needing only titles do not transfer the content of every question.
+    ///
+    /// # Parameters
+    /// `fields`: The fields of the questions to read, the id is always read
+    /// `page`: The page of the listing to get
+    ///
+    /// # Returns
+    /// A `Result<Page<QuestionPartial>, DbError>`, in the success case `Ok(Page<QuestionPartial>)` whose questions have
+    /// the fields not selected set to `None`. A page limit of zero or above `MAX_PAGE_SIZE` is rejected with
+    /// `Err(DbError::Validation)`, otherwise `Err(DbError)`.
+    async fn get_questions_projected(&self, fields: QuestionFields, page: PageRequest) -> Result<Page<QuestionPartial>, DbError>;
+
+    /// # Required Method
+    /// Gets the questions whose content is shorter than `max_words` words, for reviewing low effort posts.
+    ///
+    /// # Parameters
+    /// `max_words`: The number of words a question's content must be below to be returned
+    ///
+    /// # Returns
+    /// A `Result<Vec<Question>, DbError>`, in the success case `Ok(Vec<Question>)` ordered shortest first and
+    /// then oldest first, otherwise `Err(DbError)`.
+    async fn get_questions_shorter_than(&self, max_words: i32) -> Result<Vec<Question>, DbError>;
+
+    /// # Required Method
+    /// Gets the questions created during the current day, week or month in a community's time zone.
+    ///
+    /// # Parameters
+    /// `period`: The `Period` containing the current time
+    /// `tz`: The time zone the period is measured in, whose daylight saving transitions are respected
+    ///
+    /// # Returns
+    /// A `Result<Vec<Question>, DbError>`, in the success case `Ok(Vec<Question>)` ordered oldest first,
+    /// otherwise `Err(DbError)`.
+    async fn get_questions_in_period(&self, period: Period, tz: Tz) -> Result<Vec<Question>, DbError>;
+
+    /// # Required Method
+    /// Gets the questions whose like count is within a range, for moderators sweeping heavily liked content.
+    ///
+    /// # Parameters
+    /// `min`: The fewest likes a returned question has, which must not be negative
+    /// `max`: The most likes a returned question has, which must not be below `min`, or `None` for no upper bound
+    /// `created_after`: Only returns questions created strictly after this time when given
+    ///
+    /// # Returns
+    /// A `Result<Vec<Question>, DbError>`, in the success case `Ok(Vec<Question>)` ordered most liked first and then
+    /// oldest first. An invalid range is rejected with `Err(DbError::Validation)`, otherwise `Err(DbError)`.
+    async fn get_questions_by_like_range(&self, min: i32, max: Option<i32>, created_after: Option<DateTime<Utc>>) -> Result<Vec<Question>, DbError>;
+
+    /// # Required Method
+    /// Gets the questions liked faster than `likes_per_hour` over the last `window`, for moderators sweeping content
+    /// with suspicious like velocity. The rate is the net change recorded in the like events of the question during the
+    /// window, divided by its length. Questions without any like events, such as those liked before the events were
+    /// recorded, are estimated from their likes divided by their age instead.
+    ///
+    /// # Parameters
+    /// `likes_per_hour`: The rate a returned question is liked faster than, which must not be negative
+    /// `window`: How far back like events are counted, which must be positive and at most `MAX_LIKE_RATE_WINDOW_DAYS`
+    ///
+    /// # Returns
+    /// A `Result<Vec<QuestionLikeRate>, DbError>`, in the success case `Ok(Vec<QuestionLikeRate>)` ordered fastest
+    /// first and then oldest first. An invalid rate or window is rejected with `Err(DbError::Validation)`, otherwise
+    /// `Err(DbError)`.
+    async fn get_questions_exceeding_like_rate(&self, likes_per_hour: f64, window: Duration) -> Result<Vec<QuestionLikeRate>, DbError>;
+
+    /// # Required Method
+    /// Gets a question along with the number of its published answers, in a single query.
+    ///
+    /// # Parameters
+    /// `question_id`: The `EntityId` of the `Question` being queried
+    ///
+    /// # Returns
+    /// A `Result<QuestionHeader, DbError>`, `Ok(QuestionHeader)` in the successful case, `Err(DbError::NotFound)`
+    /// if the question does not exist, otherwise `Err(DbError)`.
+    async fn get_question_header(&self, question_id: EntityId) -> Result<QuestionHeader, DbError>;
+
+    /// # Required Method
+    /// Gets a question along with the number of its published answers and the answers themselves, read from one
+    /// snapshot of the database so that the answer count always matches the answers, even under concurrent writes.
+    ///
+    /// # Parameters
+    /// `question_id`: The `EntityId` of the `Question` being queried
+    ///
+    /// # Returns
+    /// A `Result<QuestionDetail, DbError>`, `Ok(QuestionDetail)` in the successful case, `Err(DbError::NotFound)`
+    /// if the question does not exist, otherwise `Err(DbError)`. Implementations that cannot read from a snapshot
+    /// fall back to separate reads, which `QuestionDetail::is_snapshot` reports.
+    async fn get_question_detail_consistent(&self, question_id: EntityId) -> Result<QuestionDetail, DbError>;
+
+    /// # Required Method
+    /// Gets the detail of a question like `get_question_detail_consistent`, reporting through `is_mine` whether the
+    /// question and each of its answers were created with the caller's author token.
+    ///
+    /// # Parameters
+    /// `question_id`: The `EntityId` of the `Question` being queried
+    /// `author_token`: The author token identifying the caller
+    ///
+    /// # Returns
+    /// A `Result<QuestionDetail, DbError>`, `Ok(QuestionDetail)` in the successful case, `Err(DbError::Validation)`
+    /// if the token is malformed and `Err(DbError::NotFound)` if the question does not exist, otherwise `Err(DbError)`.
+    async fn get_question_detail_for_author(&self, question_id: EntityId, author_token: &str) -> Result<QuestionDetail, DbError>;
+
+    /// # Required Method
+    /// Gets the detail of a question like `get_question_detail_consistent`, embedding only the first answers in the
+    /// order of `options`, with the pinned answer first, so questions with many answers are not read in full.
+    ///
+    /// # Parameters
+    /// `question_id`: The `EntityId` of the `Question` being queried
+    /// `options`: How many answers are embedded, in which order, and whether the total is reported
+    ///
+    /// # Returns
+    /// A `Result<QuestionDetail, DbError>`, `Ok(QuestionDetail)` in the successful case, `Err(DbError::Validation)`
+    /// if the options are invalid and `Err(DbError::NotFound)` if the question does not exist, otherwise `Err(DbError)`.
+    async fn get_question_detail_with(&self, question_id: EntityId, options: DetailOptions) -> Result<QuestionDetail, DbError>;
+
+    /// # Required Method
+    /// Gets the questions created with an author token, newest first, so anonymous authors can list their posts.
+    ///
+    /// # Parameters
+    /// `author_token`: The author token identifying the caller
+    ///
+    /// # Returns
+    /// A `Result<Vec<Question>, DbError>`, in the success case `Ok(Vec<Question>)`, each with `is_mine` set, which is
+    /// empty if no question has the token. A malformed token is rejected with `Err(DbError::Validation)`, otherwise
+    /// `Err(DbError)`.
+    async fn get_questions_by_token(&self, author_token: &str) -> Result<Vec<Question>, DbError>;
+
+    /// # Required Method
+    /// Searches the titles and content of the questions with full-text search, ranking the matches by a blend of
+    /// how well they match and how much engagement they have, so that a popular question outranks a duplicate that
+    /// matches as well. The score of a question is `ts_rank * text_weight + ln(1 + likes) * likes_weight +
+    /// ln(1 + answers) * answers_weight + ln(1 + views) * views_weight`, counting the visible answers.
+    ///
+    /// # Parameters
+    /// `query`: The words searched for, stemmed as English, every one of which a question must contain
+    /// `ranking`: The `SearchRankingConfig` weighting the components of the score
+    /// `limit`: The maximum number of questions returned, at least one
+    ///
+    /// # Returns
+    /// A `Result<Vec<RankedQuestion>, DbError>`, in the success case `Ok(Vec<RankedQuestion>)` ordered by score,
+    /// highest first. A blank query, invalid weights or a limit of zero are rejected with `Err(DbError::Validation)`,
+    /// otherwise `Err(DbError)`.
+    async fn search_questions(&self, query: &str, ranking: SearchRankingConfig, limit: u32) -> Result<Vec<RankedQuestion>, DbError>;
+
+    /// # Required Method
+    /// Gets the feed of recent activity, interleaving the questions and the answers posted newest first. Answers are
+    /// listed once published and visible under the moderation mode, along with the title of their question, while
+    /// answers without a question and tombstoned answers are left out.
+    ///
+    /// # Parameters
+    /// `limit`: The maximum number of items, at most `MAX_PAGE_SIZE`
+    /// `before`: Only items after this position in the feed are listed, the `ActivityItem::cursor` of the last item of
+    /// the previous page to continue the feed, or `None` for the most recent items
+    ///
+    /// # Returns
+    /// A `Result<Vec<ActivityItem>, DbError>`, in the success case `Ok(Vec<ActivityItem>)` ordered as described by
+    /// `ActivityCursor`. A limit of zero or above `MAX_PAGE_SIZE` is rejected with `Err(DbError::Validation)`,
+    /// otherwise `Err(DbError)`.
+    async fn get_activity_feed(&self, limit: u32, before: Option<ActivityCursor>) -> Result<Vec<ActivityItem>, DbError>;
+
+    /// # Required Method
+    /// Edits the title and/or content of a question that is not locked.
+    ///
+    /// # Parameters
+    /// `question_id`: The `EntityId` of the `Question` being edited
+    /// `update`: The `UpdateQuestion` containing the fields to change
+    ///
+    /// # Returns
+    /// A `Result<Question, DbError>`, in the success case `Ok(Question)` containing the edited question. An empty
+    /// update or content exceeding the configured `ContentLimits` is rejected with `Err(DbError::Validation)`,
+    /// content rejected by the configured `ContentPolicy` with `ValidationReason::PolicyViolation`, a locked
+    /// question with `ConflictReason::Locked`, otherwise `Err(DbError)`.
+    async fn update_question(&self, question_id: EntityId, update: UpdateQuestion) -> Result<Question, DbError>;
+
+    /// # Required Method
+    /// Locks a question, preventing edits to its content while still allowing it to be answered.
+    /// Locking an already locked question keeps the time it was first locked.
+    ///
+    /// # Parameters
+    /// `question_id`: The `EntityId` of the `Question` being locked
+    ///
+    /// # Returns
+    /// A `Result<Question, DbError>`, in the success case `Ok(Question)` containing the locked question,
+    /// otherwise `Err(DbError)`.
+    async fn lock_question(&self, question_id: EntityId) -> Result<Question, DbError>;
+
+    /// # Required Method
+    /// Unlocks a question, allowing its content to be edited again.
+    ///
+    /// # Parameters
+    /// `question_id`: The `EntityId` of the `Question` being unlocked
+    ///
+    /// # Returns
+    /// A `Result<Question, DbError>`, in the success case `Ok(Question)` containing the unlocked question,
+    /// otherwise `Err(DbError)`.
+    async fn unlock_question(&self, question_id: EntityId) -> Result<Question, DbError>;
+
+    /// # Required Method
+    /// Pins a question to the top of listings. Pinning a question that is already pinned leaves it unchanged.
+    ///
+    /// # Parameters
+    /// `question_id`: The `EntityId` of the `Question` being pinned
+    ///
+    /// # Returns
+    /// A `Result<Question, DbError>`, in the success case `Ok(Question)` containing the pinned question. If the
+    /// configured maximum number of questions are already pinned `ValidationReason::LimitExceeded` is returned,
+    /// otherwise `Err(DbError)`.
+    async fn pin_question(&self, question_id: EntityId) -> Result<Question, DbError>;
+
+    /// # Required Method
+    /// Unpins a question, returning it to its normal place in listings.
+    ///
+    /// # Parameters
+    /// `question_id`: The `EntityId` of the `Question` being unpinned
+    ///
+    /// # Returns
+    /// A `Result<Question, DbError>`, in the success case `Ok(Question)` containing the unpinned question,
+    /// otherwise `Err(DbError)`.
+    async fn unpin_question(&self, question_id: EntityId) -> Result<Question, DbError>;
+
+    /// # Required Method
+    /// Pins one of the answers to a question, on behalf of the question's author, so that it is listed before the
+    /// other answers in every order. Pinning an answer replaces any answer pinned before, and deleting the pinned
+    /// answer unpins it.
+    ///
+    /// # Parameters
+    /// `question_id`: The `EntityId` of the `Question` whose answer is being pinned
+    /// `answer_id`: The `EntityId` of the `Answer` being pinned
+    ///
+    /// # Returns
+    /// A `Result<Question, DbError>`, in the success case `Ok(Question)` containing the question with its pinned
+    /// answer. If the answer does not exist `Err(DbError::NotFound)` is returned, if it belongs to another question
+    /// `Err(DbError::Validation)`, otherwise `Err(DbError)`.
+    async fn pin_answer(&self, question_id: EntityId, answer_id: EntityId) -> Result<Question, DbError>;
+
+    /// # Required Method
+    /// Unpins the pinned answer of a question, returning the answers to their normal order. Unpinning a question
+    /// without a pinned answer leaves it unchanged.
+    ///
+    /// # Parameters
+    /// `question_id`: The `EntityId` of the `Question` whose answer is being unpinned
+    ///
+    /// # Returns
+    /// A `Result<Question, DbError>`, in the success case `Ok(Question)` containing the question without a pinned
+    /// answer, otherwise `Err(DbError)`.
+    async fn unpin_answer(&self, question_id: EntityId) -> Result<Question, DbError>;
+
+    /// # Required Method
+    /// Accepts one of the answers to a question, on behalf of the question's author, rewarding the author of the answer
+    /// with `ReputationConfig::answer_accepted`. Accepting an answer replaces any answer accepted before, moving the
+    /// reward to the new answer, and accepting the accepted answer again changes nothing. Acceptance is independent
+    /// of pinning.
+    ///
+    /// # Parameters
+    /// `question_id`: The `EntityId` of the `Question` whose answer is being accepted
+    /// `answer_id`: The `EntityId` of the `Answer` being accepted
+    ///
+    /// # Returns
+    /// A `Result<Question, DbError>`, in the success case `Ok(Question)` containing the question with its accepted
+    /// answer. If the answer does not exist `Err(DbError::NotFound)` is returned, if it belongs to another question
+    /// `Err(DbError::Validation)`, otherwise `Err(DbError)`.
+    async fn accept_answer(&self, question_id: EntityId, answer_id: EntityId) -> Result<Question, DbError>;
+
+    /// # Required Method
+    /// Withdraws the acceptance of the accepted answer of a question, taking back the reputation it earned. Unaccepting
+    /// a question without an accepted answer leaves it unchanged.
+    ///
+    /// # Parameters
+    /// `question_id`: The `EntityId` of the `Question` whose answer is being unaccepted
+    ///
+    /// # Returns
+    /// A `Result<Question, DbError>`, in the success case `Ok(Question)` containing the question without an accepted
+    /// answer, otherwise `Err(DbError)`.
+    async fn unaccept_answer(&self, question_id: EntityId) -> Result<Question, DbError>;
+
+    /// # Required Method
+    /// Attaches a bounty to a question, offering `amount` points for answering it until `duration` from now.
+    /// Setting a bounty on a question that already has one replaces it.
+    ///
+    /// # Parameters
+    /// `question_id`: The `EntityId` of the `Question` the bounty is attached to
+    /// `amount`: The points offered, between `MIN_BOUNTY` and `MAX_BOUNTY`
+    /// `duration`: How long the bounty lasts
+    ///
+    /// # Returns
+    /// A `Result<Question, DbError>`, in the success case `Ok(Question)` containing the question with its bounty.
+    /// An amount out of bounds or a duration that is not positive is rejected with `Err(DbError::Validation)`,
+    /// otherwise `Err(DbError)`. Locked questions accept bounties, since locking freezes edits but not answering, and
+    /// questions have no closed status that would stop them from being answered.
+    async fn set_bounty(&self, question_id: EntityId, amount: i32, duration: Duration) -> Result<Question, DbError>;
+
+    /// # Required Method
+    /// Withdraws the bounty of a question. Clearing a question without a bounty leaves it unchanged.
+    ///
+    /// # Parameters
+    /// `question_id`: The `EntityId` of the `Question` whose bounty is withdrawn
+    ///
+    /// # Returns
+    /// A `Result<Question, DbError>`, in the success case `Ok(Question)` containing the question without a bounty,
+    /// otherwise `Err(DbError)`.
+    async fn clear_bounty(&self, question_id: EntityId) -> Result<Question, DbError>;
+
+    /// # Required Method
+    /// Translates the title and content of a question into a language, replacing any earlier translation into it.
+    ///
+    /// # Parameters
+    /// `question_id`: The `EntityId` of the `Question` being translated
+    /// `lang`: The BCP 47 language tag of the translation, one of `SUPPORTED_LANGUAGES` in any case
+    /// `content`: The translated title and content
+    ///
+    /// # Returns
+    /// A `Result<QuestionTranslation, DbError>`, in the success case `Ok(QuestionTranslation)` with the language in
+    /// its canonical case. An unsupported language or content beyond the `ContentLimits` is rejected with
+    /// `Err(DbError::Validation)`, a locked question with `ConflictReason::Locked` and a missing question with
+    /// `Err(DbError::NotFound)`, otherwise `Err(DbError)`.
+    async fn upsert_translation(&self, question_id: EntityId, lang: &str, content: NewTranslation) -> Result<QuestionTranslation, DbError>;
+
+    /// # Required Method
+    /// Gets a question with its title and content in a language, falling back to the original title and content
+    /// when the question has not been translated into it, which `Question::is_translation` reports.
+    ///
+    /// # Parameters
+    /// `question_id`: The `EntityId` of the `Question` being read
+    /// `lang`: The BCP 47 language tag of the translation, one of `SUPPORTED_LANGUAGES` in any case
+    ///
+    /// # Returns
+    /// A `Result<Question, DbError>`, in the success case `Ok(Question)`. An unsupported language is rejected with
+    /// `Err(DbError::Validation)` and a missing question with `Err(DbError::NotFound)`, otherwise `Err(DbError)`.
+    async fn get_translation(&self, question_id: EntityId, lang: &str) -> Result<Question, DbError>;
+
+    /// # Required Method
+    /// Links a question to another question. `LinkKind::Related` links hold in both directions, so relating two
+    /// questions either way round is the same link.
+    ///
+    /// # Parameters
+    /// `from_id`: The `EntityId` of the linking question
+    /// `to_id`: The `EntityId` of the linked question
+    /// `kind`: The `LinkKind` of the link
+    ///
+    /// # Returns
+    /// A `Result<(), DbError>`, `Ok(())` if the link was created. Linking a question to itself is rejected with
+    /// `Err(DbError::Validation)`, an existing identical link with `Err(DbError::Conflict)` and a missing question
+    /// with `Err(DbError::NotFound)`, otherwise `Err(DbError)`.
+    async fn link_questions(&self, from_id: EntityId, to_id: EntityId, kind: LinkKind) -> Result<(), DbError>;
+
+    /// # Required Method
+    /// Removes a link between two questions, either way round for `LinkKind::Related`.
+    ///
+    /// # Parameters
+    /// `from_id`: The `EntityId` of the linking question
+    /// `to_id`: The `EntityId` of the linked question
+    /// `kind`: The `LinkKind` of the link
+    ///
+    /// # Returns
+    /// A `Result<bool, DbError>`, `Ok(true)` if the link was removed or `Ok(false)` if there was no such link,
+    /// otherwise `Err(DbError)`.
+    async fn unlink_questions(&self, from_id: EntityId, to_id: EntityId, kind: LinkKind) -> Result<bool, DbError>;
+
+    /// # Required Method
+    /// Gets the questions a question links to, along with the kind of each link. Related questions are listed
+    /// whichever way round they were linked, while other kinds of link are only listed from the linking question.
+    ///
+    /// # Parameters
+    /// `question_id`: The `EntityId` of the `Question` whose links are listed
+    ///
+    /// # Returns
+    /// A `Result<Vec<(LinkKind, Question)>, DbError>`, in the success case `Ok(Vec<(LinkKind, Question)>)` in the
+    /// order the links were created, empty if the question has none. A missing question is reported as
+    /// `Err(DbError::NotFound)`, otherwise `Err(DbError)`.
+    async fn get_linked_questions(&self, question_id: EntityId) -> Result<Vec<(LinkKind, Question)>, DbError>;
+
+    /// # Required Method
+    /// Deletes a question from the database, handling its answers with the data access object's `DeletePolicy`.
     ///
     /// # Parameters
     /// `question_id` the `EntityId` of the `Question` to be deleted.
+    /// `force`: Must be set to delete a locked question
     ///
     /// # Returns
-    /// A `Result<Uuid, DbError>`, if the question is successfully deleted then a `Ok(Uuid)` will be returned,
-    /// otherwise an `Err(DbError)` is returned.
-    async fn delete_question(&self, question_id: EntityId) -> Result<Uuid, DbError>;
+    /// A `Result<Uuid, DbError>`, if the question is successfully deleted then a `Ok(Uuid)` will be returned.
+    /// If the question is locked and `force` is not set `ConflictReason::Locked` is returned, otherwise an
+    /// `Err(DbError)` is returned.
+    async fn delete_question(&self, question_id: EntityId, force: bool) -> Result<Uuid, DbError>;
+
+    /// # Required Method
+    /// Deletes a question from the database, handling its answers with the given `DeletePolicy`.
+    ///
+    /// # Parameters
+    /// `question_id` the `EntityId` of the `Question` to be deleted.
+    /// `policy`: What happens to the answers of the question
+    ///
+    /// # Returns
+    /// A `Result<Uuid, DbError>`, `Ok(Uuid)` if the question is deleted. With `DeletePolicy::Restrict` a question
+    /// that has answers is rejected with `ConflictReason::HasDependents` carrying the number of answers. A locked
+    /// question is rejected with `ConflictReason::Locked`, otherwise `Err(DbError)` is returned.
+    async fn delete_question_with_policy(&self, question_id: EntityId, policy: DeletePolicy) -> Result<Uuid, DbError>;
 
     /// # Required Method
     /// Increments the number of likes associated with a particular question
@@ -64,12 +523,103 @@ pub trait QuestionDao {
     /// `question_id`, the `EntityId` of the `Question` being queried
     ///
     /// # Returns
-    /// A `Result<(), DbError>`, `Ok(())` in the successful case and `Err(DbError)` in the
-    /// unsuccessful case.
-    async fn increment_question_likes(&self, question_id: EntityId) -> Result<(), DbError>;
+    /// A `Result<i64, DbError>`, `Ok(i64)` containing the new number of likes in the successful case and
+    /// `Err(DbError)` in the unsuccessful case.
+    async fn increment_question_likes(&self, question_id: EntityId) -> Result<i64, DbError>;
+
+    /// # Required Method
+    /// Records a client viewing a question, counting the view only if the client's last counted view of the
+    /// question is older than the view window, so that refreshing a page does not inflate the count.
+    ///
+    /// # Parameters
+    /// `question_id`: The `EntityId` of the question being viewed
+    /// `client_token`: An opaque token identifying the client, such as a session id
+    ///
+    /// # Returns
+    /// A `Result<ViewOutcome, DbError>`, `Ok(ViewOutcome::Counted)` if the view was counted,
+    /// `Ok(ViewOutcome::Repeated)` if the client already viewed the question within the window, and
+    /// `Err(DbError::NotFound)` if the question does not exist.
+    async fn record_view(&self, question_id: EntityId, client_token: &str) -> Result<ViewOutcome, DbError>;
+
+    /// # Required Method
+    /// Creates a new question at most once per idempotency key. Retrying with the same key and the same
+    /// content returns the id of the question created by the first request rather than creating a duplicate.
+    ///
+    /// # Parameters
+    /// `new_question`: The content of the new question to be created and inserted into the database
+    /// `key`: The idempotency key supplied by the client
+    ///
+    /// # Returns
+    /// A `Result<CreateOutcome, DbError>`, `Ok(CreateOutcome::Created)` if the question was created,
+    /// `Ok(CreateOutcome::AlreadyCreated)` if the key was already used for the same content, and
+    /// `Err(DbError::Conflict)` if the key was already used for different content. Creating a question once the
+    /// author token reached its `CreationQuota` is rejected with `UnavailableReason::QuotaExceeded`, while retries
+    /// of a request that already created its question are still answered.
+    async fn create_question_idempotent(&self, new_question: NewQuestion, key: &str) -> Result<CreateOutcome, DbError>;
+
+    /// # Required Method
+    /// Creates or updates a question synced from an external system, keyed by its `external_id`. Questions without
+    /// an `external_id` are always created, the same as with `create_question`.
+    ///
+    /// # Parameters
+    /// `new_question`: The content of the question along with its id in the external system
+    ///
+    /// # Returns
+    /// A `Result<UpsertOutcome, DbError>`, `Ok(UpsertOutcome::Created)` if no question had the external id,
+    /// `Ok(UpsertOutcome::Updated)` if its title or content changed and `Ok(UpsertOutcome::Unchanged)` otherwise.
+    /// Changing a locked question is rejected with `ConflictReason::Locked`, and creating a question once the
+    /// author token reached its `CreationQuota` with `UnavailableReason::QuotaExceeded`. Updates are not limited by
+    /// the quota.
+    async fn upsert_question_by_external_id(&self, new_question: NewQuestion) -> Result<UpsertOutcome, DbError>;
+
+    /// # Required Method
+    /// Deletes idempotency keys older than the given time to live, after which retries using them
+    /// will create new questions.
+    ///
+    /// # Parameters
+    /// `ttl`: The time to live of an idempotency key
+    ///
+    /// # Returns
+    /// A `Result<u64, DbError>`, `Ok(u64)` with the number of keys purged, otherwise `Err(DbError)`.
+    async fn purge_idempotency_keys(&self, ttl: Duration) -> Result<u64, DbError>;
+
+    /// # Required Method
+    /// Clears the bounties that have expired, intended to be run periodically for maintenance.
+    ///
+    /// # Returns
+    /// A `Result<Vec<Uuid>, DbError>`, `Ok(Vec<Uuid>)` with the ids of the questions whose bounty was cleared, in no
+    /// particular order, otherwise `Err(DbError)`.
+    async fn expire_bounties(&self) -> Result<Vec<Uuid>, DbError>;
+
+    /// # Required Method
+    /// Deletes many questions in batches, handling their answers with the data access object's `DeletePolicy` the
+    /// same as `delete_question`. Each batch is deleted in its own short transaction and the runtime is yielded to
+    /// between batches, so that large deletions do not hold locks on the tables for long periods of time.
+    ///
+    /// # Parameters
+    /// `ids`: The `EntityId`s of the questions to be deleted
+    /// `batch_size`: The maximum number of questions deleted per transaction, a `batch_size` of zero is treated as one
+    /// `force`: Must be set to delete locked questions
+    /// `progress`: An optional callback invoked with a `BatchProgress` after each batch is committed
+    ///
+    /// # Returns
+    /// A `Result<u64, DbError>`, `Ok(u64)` with the number of questions deleted in the successful case. If any of
+    /// the ids are invalid `Err(DbError::InvalidId)`, or for serial ids that match no question `Err(DbError::NotFound)`,
+    /// is returned before anything is deleted. If a batch fails, including because it holds a locked question and
+    /// `force` is not set, or because its questions have answers and the policy is `DeletePolicy::Restrict`, no further
+    /// batches are attempted and the error of the failed batch is returned, whose `DbError::partial_batch` reports
+    /// the number of questions deleted by the batches that were committed so the caller can resume.
+    async fn delete_questions_batched(
+        &self,
+        ids: Vec<EntityId>,
+        batch_size: usize,
+        force: bool,
+        progress: Option<&mut dyn FnMut(BatchProgress)>
+    ) -> Result<u64, DbError>;
 }
 
 /// The interface for any database access object that will interact with the answers database.
+#[allow(async_fn_in_trait)]
 pub trait AnswerDao {
     /// # Required Method
     /// Creates a new answer for a particular question and inserts it into the database.
@@ -78,9 +628,49 @@ pub trait AnswerDao {
     /// `new_answer`: The `NewAnswer` containing the content of the answer to be inserted into the database
     ///
     /// # Returns
-    /// A `Result<Uuid, DbError>`, if the answer was created successfully a `Ok(Uuid)` will be returned
-    /// where the `Uuid` represents the id of the newly created answer, otherwise `Err(DbError)` will be returned.
-    async fn create_answer(&self, new_answer: NewAnswer) -> Result<Uuid, DbError>;
+    /// A `Result<Answer, DbError>`, if the answer was created successfully a `Ok(Answer)` will be returned
+    /// containing the answer as persisted, including its generated id, initial likes and creation time,
+    /// otherwise `Err(DbError)` will be returned.
+    /// Content exceeding the configured `ContentLimits` is rejected with `Err(DbError::Validation)`, and content
+    /// rejected by the configured `ContentPolicy` with `ValidationReason::PolicyViolation`. An author token that
+    /// created as many answers within the last hour as the configured `CreationQuota` allows is rejected with
+    /// `UnavailableReason::QuotaExceeded`.
+    async fn create_answer(&self, new_answer: NewAnswer) -> Result<Answer, DbError>;
+
+    /// # Required Method
+    /// Saves a new answer as a draft, which is excluded from listings and counts until it is published.
+    ///
+    /// # Parameters
+    /// `new_answer`: The `NewAnswer` containing the content of the draft
+    ///
+    /// # Returns
+    /// A `Result<Answer, DbError>`, `Ok(Answer)` containing the unpublished draft as persisted, otherwise
+    /// `Err(DbError)`, validated the same as `create_answer`.
+    async fn create_answer_draft(&self, new_answer: NewAnswer) -> Result<Answer, DbError>;
+
+    /// # Required Method
+    /// Publishes a draft answer, making it visible in listings. Its creation time is set to the time of
+    /// publication, so subscribers are notified of it as a new answer.
+    ///
+    /// # Parameters
+    /// `answer_id`: The `EntityId` of the answer to publish
+    ///
+    /// # Returns
+    /// A `Result<Answer, DbError>`, `Ok(Answer)` containing the published answer. Publishing an answer that is
+    /// already published succeeds without changing it. If the answer does not exist `Err(DbError::NotFound)`
+    /// is returned, otherwise `Err(DbError)`.
+    async fn publish_answer(&self, answer_id: EntityId) -> Result<Answer, DbError>;
+
+    /// # Required Method
+    /// Gets the unpublished drafts an author has written for a particular question, oldest first.
+    ///
+    /// # Parameters
+    /// `question_id`: The id of the `Question` the drafts answer
+    /// `author_id`: The id of the user who wrote the drafts
+    ///
+    /// # Returns
+    /// A `Result<Vec<Answer>, DbError>`, in the success case `Ok(Vec<Answer>)`, otherwise `Err(DbError)`.
+    async fn get_drafts(&self, question_id: EntityId, author_id: EntityId) -> Result<Vec<Answer>, DbError>;
 
     /// # Required Method
     /// Gets an answer from the database if present
@@ -92,250 +682,4666 @@ pub trait AnswerDao {
     /// A `Result<Answer, DbError>`, a `Ok(Question)` if the query is successful, otherwise `Err(DbError)`.
     async fn get_answer(&self, answer_id: EntityId) -> Result<Answer, DbError>;
 
-    /// # Required Method
-    /// Gets a `Vec` of all answers in the database associated with a particular question.
-    ///
-    /// # Parameters
-    /// `question_id`: The id of the `Question` whose answers are to be returned.
-    ///
-    /// # Returns
-    /// A `Result<Vec<Answer>>, DbError>`, in the success case `Ok(Vec<Answer>)`, otherwise `Err(DbError)`.
-    async fn get_answers(&self, question_id: EntityId) -> Result<Vec<Answer>, DbError>;
+    /// # Required Method
+    /// Gets a `Vec` of all answers in the database associated with a particular question.
+    ///
+    /// # Parameters
+    /// `question_id`: The id of the `Question` whose answers are to be returned.
+    ///
+    /// # Returns
+    /// A `Result<Vec<Answer>>, DbError>`, in the success case `Ok(Vec<Answer>)` in the default `AnswerSort` order,
+    /// otherwise `Err(DbError)`. Rows that cannot be decoded are reported with `InternalReason::Decode`. Drafts, and
+    /// answers held back by the implementation's `ModerationMode`, are excluded from this and the other listings.
+    async fn get_answers(&self, question_id: EntityId) -> Result<Vec<Answer>, DbError>;
+
+    /// # Required Method
+    /// Gets the answers to a particular question with the replies to each nested below it, read with one query.
+    ///
+    /// # Parameters
+    /// `question_id`: The id of the `Question` whose answers are to be returned
+    ///
+    /// # Returns
+    /// A `Result<Vec<AnswerThread>, DbError>`, in the success case `Ok(Vec<AnswerThread>)` with the answers to the
+    /// question in the default `AnswerSort` order, each with its replies oldest first, otherwise `Err(DbError)`.
+    /// Replies are listed like answers, so drafts and replies held back by moderation are excluded.
+    async fn get_answer_threads(&self, question_id: EntityId) -> Result<Vec<AnswerThread>, DbError>;
+
+    /// # Required Method
+    /// Gets the answers to a particular question in the given order, up to a limit.
+    ///
+    /// # Parameters
+    /// `question_id`: The id of the `Question` whose answers are to be returned.
+    /// `sort`: The order the answers are returned in
+    /// `limit`: The maximum number of answers returned, at least one
+    ///
+    /// # Returns
+    /// A `Result<Vec<Answer>>, DbError>`, in the success case `Ok(Vec<Answer>)`, which is empty if the question
+    /// has no answers. If the question does not exist `Err(DbError::NotFound)` is returned, otherwise `Err(DbError)`.
+    async fn get_answers_sorted(&self, question_id: EntityId, sort: AnswerSort, limit: u32) -> Result<Vec<Answer>, DbError>;
+
+    /// # Required Method
+    /// Gets a page of the published answers to a question, the most liked first, ties ordered oldest first. Every
+    /// page is ordered by the likes the answers had when the first page was read, which the page token carries
+    /// along with the position of the last answer read, so likes changing while a client pages through the answers
+    /// never make an answer appear twice or be skipped. Answers created after the first page are left out until the
+    /// client starts over, and answers deleted in the meantime are not listed. The pinned answer is not moved first.
+    ///
+    /// # Parameters
+    /// `question_id`: The id of the `Question` whose answers are returned
+    /// `limit`: The maximum number of answers on the page, between one and `MAX_PAGE_SIZE`
+    /// `page_token`: The `AnswerPage::page_token` of the previous page, `None` for the first page
+    ///
+    /// # Returns
+    /// A `Result<AnswerPage, DbError>`, in the success case `Ok(AnswerPage)`. An invalid limit or a malformed page
+    /// token is rejected with `Err(DbError::Validation)` and a missing question with `Err(DbError::NotFound)`,
+    /// otherwise `Err(DbError)`.
+    async fn get_answers_paged(&self, question_id: EntityId, limit: u32, page_token: Option<&str>) -> Result<AnswerPage, DbError>;
+
+    /// # Required Method
+    /// Gets a `Vec` of all answers associated with a particular question, along with the username
+    /// of each answer's author. Anonymous answers are included with no username.
+    ///
+    /// # Parameters
+    /// `question_id`: The id of the `Question` whose answers are to be returned.
+    ///
+    /// # Returns
+    /// A `Result<Vec<AnswerWithAuthor>>, DbError>`, in the success case `Ok(Vec<AnswerWithAuthor>)`,
+    /// otherwise `Err(DbError)`.
+    async fn get_answers_with_authors(&self, question_id: EntityId) -> Result<Vec<AnswerWithAuthor>, DbError>;
+
+    /// # Required Method
+    /// Gets the answers created with an author token, including drafts, newest first, so anonymous authors can
+    /// list their posts.
+    ///
+    /// # Parameters
+    /// `author_token`: The author token identifying the caller
+    ///
+    /// # Returns
+    /// A `Result<Vec<Answer>, DbError>`, in the success case `Ok(Vec<Answer>)`, each with `is_mine` set, which is
+    /// empty if no answer has the token. A malformed token is rejected with `Err(DbError::Validation)`, otherwise
+    /// `Err(DbError)`.
+    async fn get_answers_by_token(&self, author_token: &str) -> Result<Vec<Answer>, DbError>;
+
+    /// # Required Method
+    /// Searches the published answers of a particular question for those containing a term, ignoring case.
+    ///
+    /// # Parameters
+    /// `question_id`: The id of the `Question` whose answers are searched
+    /// `term`: The text searched for, matched literally
+    ///
+    /// # Returns
+    /// A `Result<Vec<Answer>>, DbError>`, in the success case `Ok(Vec<Answer>)` ordered by likes, which is empty if
+    /// no answer matches. An empty term is rejected with `Err(DbError::Validation)` and a missing question with
+    /// `Err(DbError::NotFound)`, otherwise `Err(DbError)`.
+    async fn search_answers(&self, question_id: EntityId, term: &str) -> Result<Vec<Answer>, DbError>;
+
+    /// # Required Method
+    /// Searches the published answers of every question for those containing a term, ignoring case, along with the
+    /// title of each answer's question.
+    ///
+    /// # Parameters
+    /// `term`: The text searched for, matched literally
+    /// `limit`: The maximum number of answers returned, at least one
+    ///
+    /// # Returns
+    /// A `Result<Vec<AnswerWithQuestion>>, DbError>`, in the success case `Ok(Vec<AnswerWithQuestion>)` ordered by
+    /// likes. An empty term or a limit of zero is rejected with `Err(DbError::Validation)`, otherwise `Err(DbError)`.
+    async fn search_all_answers(&self, term: &str, limit: u32) -> Result<Vec<AnswerWithQuestion>, DbError>;
+
+    /// # Required Method
+    /// Gets a `Vec` of all answers in the database
+    ///
+    /// # Returns
+    /// A `Result<Vec<Answer>>, DbError>`, in the success case `Ok(Vec<Answer>)` in the default `AnswerSort` order,
+    /// otherwise `Err(DbError)`. Rows that cannot be decoded are reported with `InternalReason::Decode`.
+    async fn get_all_answers(&self) -> Result<Vec<Answer>, DbError>;
+
+    /// # Required Method
+    /// Gets a page of all answers, most liked first like `get_all_answers`, each along with the title of the question
+    /// it belongs to, read in a single query.
+    ///
+    /// # Parameters
+    /// `page`: The page of the listing to get
+    ///
+    /// # Returns
+    /// A `Result<Page<AnswerWithQuestion>, DbError>`, in the success case `Ok(Page<AnswerWithQuestion>)` where answers
+    /// whose question is no longer visible are kept with a title of `None`. A page limit of zero or above
+    /// `MAX_PAGE_SIZE` is rejected with `Err(DbError::Validation)`, otherwise `Err(DbError)`.
+    async fn get_all_answers_with_question(&self, page: PageRequest) -> Result<Page<AnswerWithQuestion>, DbError>;
+
+    /// # Required Method
+    /// Deletes an answer from the database.
+    ///
+    /// # Parameters
+    /// `answer_id`: The `EntityId` of the `Answer` to be deleted.
+    ///
+    /// # Returns
+    /// A `Result<Uuid, DbError>`, if the answer is successfully deleted then a `Ok(Uuid)` will be returned,
+    /// otherwise an `Err(DbError)` is returned.
+    async fn delete_answer(&self, answer_id: EntityId) -> Result<Uuid, DbError>;
+
+    /// # Required Method
+    /// Replaces an answer with a tombstone instead of deleting it, so that the replies to it stay in their thread.
+    /// The content is replaced by `DELETED_CONTENT` and the likes are cleared, while the answer keeps its place in
+    /// listings marked as deleted, see `Answer::is_deleted`. Tombstoned answers can no longer be liked or voted on,
+    /// and can still be deleted with `delete_answer`.
+    ///
+    /// # Parameters
+    /// `answer_id`: The `EntityId` of the `Answer` being tombstoned
+    /// `reason`: Why the answer was removed, at most `MAX_DELETED_REASON_LENGTH` characters, shown with the tombstone
+    ///
+    /// # Returns
+    /// A `Result<Answer, DbError>`, `Ok(Answer)` containing the tombstone in the successful case,
+    /// `Err(DbError::NotFound)` if the answer does not exist, `Err(DbError::Conflict)` if it is already tombstoned and
+    /// `Err(DbError::Validation)` for an overlong reason, otherwise `Err(DbError)`.
+    async fn delete_answer_with_tombstone(&self, answer_id: EntityId, reason: Option<String>) -> Result<Answer, DbError>;
+
+    /// # Required Method
+    /// Increments the number of likes associated with a particular answer.
+    ///
+    /// # Parameters
+    /// `answer_id`: The `EntityId` of the `Answer` being queried
+    ///
+    /// # Returns
+    /// A `Result<i64, DbError>`, `Ok(i64)` containing the new number of likes in the successful case and
+    /// `Err(DbError)` in the unsuccessful case. Drafts cannot be liked and are rejected with `Err(DbError::Conflict)`.
+    async fn increment_answer_likes(&self, answer_id: EntityId) -> Result<i64, DbError>;
+
+    /// # Required Method
+    /// Records a user's vote on whether an answer was helpful. Each user has at most one vote per answer, so
+    /// voting the opposite way moves the user's vote from one counter to the other.
+    ///
+    /// # Parameters
+    /// `answer_id`: The `EntityId` of the `Answer` being voted on
+    /// `vote`: The `AnswerVote` being cast
+    /// `user_token`: The token identifying the voting user
+    ///
+    /// # Returns
+    /// A `Result<VoteOutcome, DbError>`, `Ok(VoteOutcome)` describing how the user's vote changed in the successful
+    /// case, `Err(DbError::NotFound)` if the answer does not exist and `Err(DbError::Conflict)` for drafts, otherwise
+    /// `Err(DbError)`.
+    async fn vote_answer(&self, answer_id: EntityId, vote: AnswerVote, user_token: &str) -> Result<VoteOutcome, DbError>;
+
+    /// # Required Method
+    /// Approves an answer held back by moderation, making it visible in listings. Approving an answer that is
+    /// already approved succeeds without changing it.
+    ///
+    /// # Parameters
+    /// `answer_id`: The `EntityId` of the `Answer` being approved
+    ///
+    /// # Returns
+    /// A `Result<Answer, DbError>`, `Ok(Answer)` containing the approved answer in the successful case,
+    /// `Err(DbError::NotFound)` if the answer does not exist and `Err(DbError::Conflict)` for drafts, otherwise
+    /// `Err(DbError)`.
+    async fn approve_answer(&self, answer_id: EntityId) -> Result<Answer, DbError>;
+
+    /// # Required Method
+    /// Rejects an answer, deleting it and keeping a copy of it in the `answer_rejections` audit table.
+    ///
+    /// # Parameters
+    /// `answer_id`: The `EntityId` of the `Answer` being rejected
+    ///
+    /// # Returns
+    /// A `Result<Uuid, DbError>`, `Ok(Uuid)` containing the id of the rejected answer in the successful case,
+    /// `Err(DbError::NotFound)` if the answer does not exist, otherwise `Err(DbError)`.
+    async fn reject_answer(&self, answer_id: EntityId) -> Result<Uuid, DbError>;
+
+    /// # Required Method
+    /// Moves every answer of the questions that were neither answered nor liked within `inactive_for` to the
+    /// `answers_archive` table, adding the number moved to the archived count of each question, see
+    /// `DetailOptions::include_archived`. Their votes and moderation flags are archived with them, and an accepted
+    /// answer is kept as `Question::archived_accepted_answer_id`, while a pinned answer is unpinned. Questions are archived `batch` at a time, each batch in its
+    /// own transaction, until none are left, so archiving again before any further activity archives nothing.
+    ///
+    /// # Parameters
+    /// `inactive_for`: How long a question must have been inactive for its answers to be archived
+    /// `batch`: The number of questions archived per transaction, at least one
+    ///
+    /// # Returns
+    /// A `Result<ArchiveReport, DbError>`, `Ok(ArchiveReport)` with the answers archived from each question in the
+    /// successful case, `Err(DbError::Validation)` if `inactive_for` is negative or `batch` is zero, otherwise
+    /// `Err(DbError)`. Batches committed before an error remain archived.
+    async fn archive_stale_answers(&self, inactive_for: Duration, batch: usize) -> Result<ArchiveReport, DbError>;
+}
+
+/// Likes a question or an answer, dispatching to the data access object of the target's kind of entity.
+///
+/// # Parameters
+/// `question_dao`: The `QuestionDao` questions are liked with
+/// `answer_dao`: The `AnswerDao` answers are liked with
+/// `target`: The `LikeTarget` naming the kind of entity and its id
+///
+/// # Returns
+/// A `Result<i64, DbError>`, `Ok(i64)` containing the new number of likes of the entity in the successful case,
+/// `Err(DbError::NotFound)` if the entity does not exist, otherwise `Err(DbError)`.
+pub async fn like_entity(question_dao: &impl QuestionDao, answer_dao: &impl AnswerDao, target: LikeTarget) -> Result<i64, DbError> {
+    let id = EntityId::new(target.id);
+    match target.entity {
+        LikableEntity::Question => question_dao.increment_question_likes(id).await,
+        LikableEntity::Answer => answer_dao.increment_answer_likes(id).await,
+    }
+}
+
+/// Maps errors from writing content, classifying check constraint, string length and numeric range
+/// violations as `DbError::Validation` with a stable message. Any other error is mapped with `otherwise`.
+fn content_error(e: sqlx::Error, otherwise: fn(sqlx::Error) -> DbError) -> DbError {
+    // The database's own message names the table and constraint, so a stable message is used per SQLSTATE code
+    let message = match e.as_database_error().and_then(|db_err| db_err.code()).as_deref() {
+        Some("23514") => "a value is not allowed by the content rules",
+        Some("22001") => "a value is longer than allowed",
+        Some("22003") => "a number is out of range",
+        _ => return otherwise(e),
+    };
+    DbError::validation(message).caused_by(e)
+}
+
+/// Maps errors from inserting new content, classifying check constraint and string length
+/// violations as `DbError::Validation` rather than failures to create the content.
+fn creation_error(e: sqlx::Error) -> DbError {
+    content_error(e, DbError::creation)
+}
+
+/// Maps errors from editing existing content, classifying check constraint and string length
+/// violations as `DbError::Validation` rather than failures to update the content.
+fn edit_error(e: sqlx::Error) -> DbError {
+    content_error(e, DbError::update)
+}
+
+/// Maps errors from reading a row that must exist, reporting a missing row as `DbError::NotFound` naming the
+/// entity of kind `entity` with the given id. Any other error is classified as `From<sqlx::Error>` does.
+fn missing(entity: &'static str, id: impl ToString) -> impl FnOnce(sqlx::Error) -> DbError {
+    move |e| match e {
+        sqlx::Error::RowNotFound => DbError::not_found(entity, id).caused_by(e),
+        e => DbError::from(e),
+    }
+}
+
+/// The kind of the entities stored in `table`, as named by `DbError::NotFound`.
+fn entity_of(table: &str) -> &'static str {
+    match table {
+        "questions" => "question",
+        "answers" => "answer",
+        "categories" => "category",
+        "tags" => "tag",
+        "users" => "user",
+        _ => "entity",
+    }
+}
+
+/// The interface for any database access object that will interact with the subscriptions database.
+#[allow(async_fn_in_trait)]
+pub trait SubscriptionDao {
+    /// # Required Method
+    /// Subscribes a user to a question, subscribing to an already subscribed question has no effect.
+    ///
+    /// # Parameters
+    /// `user_token`: The token identifying the subscribing user
+    /// `question_id`: The `EntityId` of the `Question` being subscribed to
+    ///
+    /// # Returns
+    /// A `Result<(), DbError>`, `Ok(())` in the successful case, `Err(DbError::NotFound)` if the question
+    /// does not exist, otherwise `Err(DbError)`.
+    async fn subscribe(&self, user_token: &str, question_id: EntityId) -> Result<(), DbError>;
+
+    /// # Required Method
+    /// Unsubscribes a user from a question, unsubscribing from a question that is not subscribed to has no effect.
+    ///
+    /// # Parameters
+    /// `user_token`: The token identifying the subscribed user
+    /// `question_id`: The `EntityId` of the `Question` being unsubscribed from
+    ///
+    /// # Returns
+    /// A `Result<(), DbError>`, `Ok(())` in the successful case and `Err(DbError)` in the
+    /// unsuccessful case.
+    async fn unsubscribe(&self, user_token: &str, question_id: EntityId) -> Result<(), DbError>;
+
+    /// # Required Method
+    /// Gets the updates for every question a user is subscribed to that has visible answers not reported yet, and
+    /// marks those answers as seen. Answers are reported once they become visible, whenever they were created.
+    ///
+    /// # Parameters
+    /// `user_token`: The token identifying the subscribed user
+    ///
+    /// # Returns
+    /// A `Result<Vec<QuestionUpdate>, DbError>`, in the success case `Ok(Vec<QuestionUpdate>)` ordered by the most
+    /// recent answer, otherwise `Err(DbError)`.
+    async fn get_updates(&self, user_token: &str) -> Result<Vec<QuestionUpdate>, DbError>;
+}
+
+/// The interface for any database access object that computes statistics across the questions and answers databases.
+#[allow(async_fn_in_trait)]
+pub trait StatsDao {
+    /// # Required Method
+    /// Gets the number of questions and answers created on each of the last `days` calendar days (in UTC),
+    /// including today. Days without any activity are included with zero counts.
+    ///
+    /// # Parameters
+    /// `days`: The number of days to report, at least one
+    ///
+    /// # Returns
+    /// A `Result<Vec<DailyActivity>, DbError>`, in the success case `Ok(Vec<DailyActivity>)` with one entry per day
+    /// ordered from the oldest day, otherwise `Err(DbError)`.
+    async fn get_daily_activity(&self, days: u32) -> Result<Vec<DailyActivity>, DbError>;
+
+    /// # Required Method
+    /// Counts the rows in each of the crate's tables, in a single snapshot. Draft answers are not counted.
+    ///
+    /// # Returns
+    /// A `Result<Totals, DbError>`, in the success case `Ok(Totals)`, otherwise `Err(DbError)`.
+    async fn get_totals(&self) -> Result<Totals, DbError>;
+
+    /// # Required Method
+    /// Measures how long questions waited for their first published answer.
+    ///
+    /// # Parameters
+    /// `since`: Only questions created at or after this time are measured
+    ///
+    /// # Returns
+    /// A `Result<LatencyStats, DbError>`, in the success case `Ok(LatencyStats)` with the minimum, median and
+    /// 90th percentile waits of the answered questions and the number still unanswered, otherwise `Err(DbError)`.
+    async fn get_time_to_first_answer(&self, since: DateTime<Utc>) -> Result<LatencyStats, DbError>;
+}
+
+/// The interface for administrative operations that application code should not perform, such as
+/// setting like counts directly when importing content from another system.
+#[allow(async_fn_in_trait)]
+pub trait AdminDao {
+    /// # Required Method
+    /// Sets the number of likes of a question.
+    ///
+    /// # Parameters
+    /// `question_id`: The `EntityId` of the `Question` being updated
+    /// `likes`: The new number of likes, which cannot be negative
+    ///
+    /// # Returns
+    /// A `Result<(), DbError>`, `Ok(())` in the successful case. A negative `likes` is rejected with
+    /// `Err(DbError::Validation)` and a missing question with `Err(DbError::NotFound)`, otherwise `Err(DbError)`.
+    async fn set_question_likes(&self, question_id: EntityId, likes: i64) -> Result<(), DbError>;
+
+    /// # Required Method
+    /// Sets the number of likes of an answer.
+    ///
+    /// # Parameters
+    /// `answer_id`: The `EntityId` of the `Answer` being updated
+    /// `likes`: The new number of likes, which cannot be negative
+    ///
+    /// # Returns
+    /// A `Result<(), DbError>`, `Ok(())` in the successful case. A negative `likes` is rejected with
+    /// `Err(DbError::Validation)` and a missing answer with `Err(DbError::NotFound)`, otherwise `Err(DbError)`.
+    async fn set_answer_likes(&self, answer_id: EntityId, likes: i64) -> Result<(), DbError>;
+
+    /// # Required Method
+    /// Sets the number of likes of many questions in a single statement.
+    ///
+    /// # Parameters
+    /// `pairs`: The `EntityId` of each `Question` paired with its new number of likes
+    ///
+    /// # Returns
+    /// A `Result<BulkUpdate, DbError>`, in the success case `Ok(BulkUpdate)` reporting the number of questions
+    /// updated and the uuids that matched no question. Invalid ids, serial ids that match no question, negative
+    /// likes and duplicate ids are rejected before anything is updated, otherwise `Err(DbError)`.
+    async fn bulk_set_question_likes(&self, pairs: Vec<(EntityId, i64)>) -> Result<BulkUpdate, DbError>;
+
+    /// # Required Method
+    /// Sets the number of likes of many answers in a single statement.
+    ///
+    /// # Parameters
+    /// `pairs`: The `EntityId` of each `Answer` paired with its new number of likes
+    ///
+    /// # Returns
+    /// A `Result<BulkUpdate, DbError>`, in the success case `Ok(BulkUpdate)` reporting the number of answers
+    /// updated and the uuids that matched no answer. Invalid ids, serial ids that match no answer, negative
+    /// likes and duplicate ids are rejected before anything is updated, otherwise `Err(DbError)`.
+    async fn bulk_set_answer_likes(&self, pairs: Vec<(EntityId, i64)>) -> Result<BulkUpdate, DbError>;
+
+    /// # Required Method
+    /// Sets the number of views of many questions in a single statement.
+    ///
+    /// # Parameters
+    /// `pairs`: The `EntityId` of each `Question` paired with its new number of views
+    ///
+    /// # Returns
+    /// A `Result<BulkUpdate, DbError>`, in the success case `Ok(BulkUpdate)` reporting the number of questions
+    /// updated and the uuids that matched no question. Invalid ids, serial ids that match no question, negative
+    /// views and duplicate ids are rejected before anything is updated, otherwise `Err(DbError)`.
+    async fn bulk_set_question_views(&self, pairs: Vec<(EntityId, i32)>) -> Result<BulkUpdate, DbError>;
+
+    /// # Required Method
+    /// Inserts exported questions along with their answers in one transaction, keeping their ids, content and
+    /// timestamps so that they can be matched with exported engagement. Engagement is not imported: the likes and
+    /// views are left at zero, to be restored separately if wanted. Serial ids are assigned anew and answers are
+    /// imported as published and approved. Questions and answers whose id already exists are skipped.
+    ///
+    /// A dry run performs the same checks and lookups inside a read only transaction that is always rolled back,
+    /// so nothing is inserted, and reports every invalid record rather than stopping at the first.
+    ///
+    /// # Parameters
+    /// `content`: The exported questions, each with its answers
+    /// `dry_run`: Whether to only report what the import would do
+    ///
+    /// # Returns
+    /// A `Result<Vec<ImportRecord>, DbError>`, in the success case `Ok(Vec<ImportRecord>)` with the verdict on each
+    /// question followed by those on its answers, in the order they were given. Outside of a dry run, ids that are
+    /// not uuids are rejected with `Err(DbError::InvalidId)` and malformed timestamps or content that is too long
+    /// with `Err(DbError::Validation)`, without importing anything, otherwise `Err(DbError)`.
+    async fn import_content(&self, content: Vec<QuestionDetailResponse>, dry_run: bool) -> Result<Vec<ImportRecord>, DbError>;
+
+    /// # Required Method
+    /// Erases a user, in a single transaction, without breaking the threads they contributed to. Their answers
+    /// are kept but dissociated from them, with their content replaced by `DELETED_CONTENT` if `scrub_content` is set,
+    /// and the user row is deleted last. Calling this again for the same user succeeds without affecting anything.
+    ///
+    /// # Parameters
+    /// `author_id`: The `EntityId` of the user being erased
+    /// `scrub_content`: Whether to replace the content of the user's answers
+    ///
+    /// # Returns
+    /// A `Result<AnonymizeReport, DbError>`, in the success case `Ok(AnonymizeReport)` with the number of rows
+    /// affected in each table, otherwise `Err(DbError)`.
+    async fn anonymize_author(&self, author_id: EntityId, scrub_content: bool) -> Result<AnonymizeReport, DbError>;
+
+    /// # Required Method
+    /// Attributes a question to another user, such as a ghost-written question to its real author, recording the
+    /// transfer in the ownership audit log in the same transaction.
+    ///
+    /// # Parameters
+    /// `question_id`: The `EntityId` of the `Question` being transferred
+    /// `new_author`: The `EntityId` of the user the question is attributed to
+    ///
+    /// # Returns
+    /// A `Result<(), DbError>`, `Ok(())` in the successful case. If the question or the user does not exist
+    /// `Err(DbError::NotFound)` is returned and nothing changes, otherwise `Err(DbError)`.
+    async fn transfer_question_author(&self, question_id: EntityId, new_author: EntityId) -> Result<(), DbError>;
+
+    /// # Required Method
+    /// Attributes an answer to another user, recording the transfer in the ownership audit log in the same
+    /// transaction.
+    ///
+    /// # Parameters
+    /// `answer_id`: The `EntityId` of the `Answer` being transferred
+    /// `new_author`: The `EntityId` of the user the answer is attributed to
+    ///
+    /// # Returns
+    /// A `Result<(), DbError>`, `Ok(())` in the successful case. If the answer or the user does not exist
+    /// `Err(DbError::NotFound)` is returned and nothing changes, otherwise `Err(DbError)`.
+    async fn transfer_answer_author(&self, answer_id: EntityId, new_author: EntityId) -> Result<(), DbError>;
+
+    /// # Required Method
+    /// Transfers every question and answer of one user to another in a single transaction, such as when merging
+    /// accounts, recording each transfer in the ownership audit log. The old user is kept.
+    ///
+    /// # Parameters
+    /// `old_author`: The `EntityId` of the user whose content is transferred
+    /// `new_author`: The `EntityId` of the user the content is attributed to
+    ///
+    /// # Returns
+    /// A `Result<TransferReport, DbError>`, in the success case `Ok(TransferReport)` with the number of questions
+    /// and answers transferred. Transferring to the same user is rejected with `Err(DbError::Validation)`, and a
+    /// new author that does not exist with `Err(DbError::NotFound)`, otherwise `Err(DbError)`.
+    async fn transfer_all_content(&self, old_author: EntityId, new_author: EntityId) -> Result<TransferReport, DbError>;
+
+    /// # Required Method
+    /// Merges a duplicate question into another question, in a single transaction. The answers, subscriptions and
+    /// likes of the duplicate are moved to the target question and the duplicate is then deleted.
+    ///
+    /// # Parameters
+    /// `duplicate_id`: The `EntityId` of the `Question` being merged and deleted
+    /// `target_id`: The `EntityId` of the `Question` being merged into
+    ///
+    /// # Returns
+    /// A `Result<MergeReport, DbError>`, in the success case `Ok(MergeReport)`. Merging a question into itself is
+    /// rejected with `Err(DbError::Validation)` and a missing question with `Err(DbError::NotFound)`,
+    /// otherwise `Err(DbError)`.
+    async fn merge_questions(&self, duplicate_id: EntityId, target_id: EntityId) -> Result<MergeReport, DbError>;
+
+    /// # Required Method
+    /// Rebuilds the indexes of the crate's tables.
+    ///
+    /// # Returns
+    /// A `Result<Vec<&'static str>, DbError>`, in the success case `Ok(Vec<&'static str>)` with the tables that were
+    /// reindexed, otherwise `Err(DbError)`.
+    async fn reindex(&self) -> Result<Vec<&'static str>, DbError>;
+
+    /// # Required Method
+    /// Recomputes the character and word counts of every question and answer, such as for rows created before the
+    /// counts were maintained. Rows are updated in batches, so that no statement locks a whole table.
+    ///
+    /// # Returns
+    /// A `Result<u64, DbError>`, in the success case `Ok(u64)` with the number of rows whose counts changed,
+    /// otherwise `Err(DbError)`.
+    async fn recompute_content_stats(&self) -> Result<u64, DbError>;
+
+    /// # Required Method
+    /// Gets the recorded changes to the like counter of a question or an answer, oldest first.
+    ///
+    /// # Parameters
+    /// `entity_id`: The uuid of the question or answer, events are kept after it is deleted
+    /// `since`: Only events that occurred at or after this time are returned
+    ///
+    /// # Returns
+    /// A `Result<Vec<LikeEvent>, DbError>`, in the success case `Ok(Vec<LikeEvent>)`, which is empty if nothing
+    /// was recorded, otherwise `Err(DbError)`.
+    async fn get_like_events(&self, entity_id: EntityId, since: DateTime<Utc>) -> Result<Vec<LikeEvent>, DbError>;
+
+    /// # Required Method
+    /// Deletes for good the answers tombstoned before a time, in a single transaction. Deleting an answer deletes
+    /// its replies, so a tombstone is kept while replies to it remain, unless they are purged along with it.
+    /// Archived answers are left to the archive.
+    ///
+    /// # Parameters
+    /// `before`: Only answers tombstoned before this time are deleted
+    ///
+    /// # Returns
+    /// A `Result<PurgeReport, DbError>`, in the success case `Ok(PurgeReport)` with the number of answers deleted
+    /// and kept, otherwise `Err(DbError)`.
+    async fn purge_deleted_answers(&self, before: DateTime<Utc>) -> Result<PurgeReport, DbError>;
+
+    /// # Required Method
+    /// Sets the likes of every question and answer to the sum of the changes recorded in the like audit log, in a
+    /// single transaction, correcting counters that drifted from it. Counters without any recorded change are left
+    /// as they are, as they may predate the log.
+    ///
+    /// # Returns
+    /// A `Result<RecountReport, DbError>`, in the success case `Ok(RecountReport)` with the number of counters
+    /// corrected, otherwise `Err(DbError)`.
+    async fn recount_likes(&self) -> Result<RecountReport, DbError>;
+}
+
+/// The interface for any database access object that will interact with the category tree.
+#[allow(async_fn_in_trait)]
+pub trait CategoryDao {
+    /// # Required Method
+    /// Creates a new category, either at the top level or under an existing parent.
+    ///
+    /// # Parameters
+    /// `new_category`: The `NewCategory` containing the name and parent of the category
+    ///
+    /// # Returns
+    /// A `Result<Category, DbError>`, `Ok(Category)` containing the category as persisted. If the parent does not
+    /// exist `Err(DbError::NotFound)` is returned, and if a sibling already has the same name `Err(DbError::Conflict)`,
+    /// otherwise `Err(DbError)`.
+    async fn create_category(&self, new_category: NewCategory) -> Result<Category, DbError>;
+
+    /// # Required Method
+    /// Moves a category, along with its subcategories, under a new parent or to the top level.
+    ///
+    /// # Parameters
+    /// `category_id`: The `EntityId` of the category to move
+    /// `parent_id`: The `EntityId` of the new parent, `None` to move the category to the top level
+    ///
+    /// # Returns
+    /// A `Result<Category, DbError>`, `Ok(Category)` containing the moved category. Moving a category under itself
+    /// or one of its descendants would create a cycle and is rejected with `Err(DbError::Conflict)`. If either
+    /// category does not exist `Err(DbError::NotFound)` is returned, otherwise `Err(DbError)`.
+    async fn set_category_parent(&self, category_id: EntityId, parent_id: Option<EntityId>) -> Result<Category, DbError>;
+
+    /// # Required Method
+    /// Gets every category, arranged as trees rooted at the top level categories with siblings ordered by name.
+    ///
+    /// # Returns
+    /// A `Result<Vec<CategoryNode>, DbError>`, in the success case `Ok(Vec<CategoryNode>)`, otherwise `Err(DbError)`.
+    async fn get_category_tree(&self) -> Result<Vec<CategoryNode>, DbError>;
+
+    /// # Required Method
+    /// Files a question under a category, replacing any category it was filed under before.
+    ///
+    /// # Parameters
+    /// `question_id`: The `EntityId` of the question to categorize
+    /// `category_id`: The `EntityId` of the category, `None` to uncategorize the question
+    ///
+    /// # Returns
+    /// A `Result<Question, DbError>`, `Ok(Question)` containing the updated question. If the question or the
+    /// category does not exist `Err(DbError::NotFound)` is returned, otherwise `Err(DbError)`.
+    async fn assign_category(&self, question_id: EntityId, category_id: Option<EntityId>) -> Result<Question, DbError>;
+
+    /// # Required Method
+    /// Gets the questions filed under a category, pinned questions first and then oldest first.
+    ///
+    /// # Parameters
+    /// `category_id`: The `EntityId` of the category
+    /// `include_descendants`: Whether questions filed under any of the category's subcategories are included
+    ///
+    /// # Returns
+    /// A `Result<Vec<Question>, DbError>`, in the success case `Ok(Vec<Question>)`. If the category does not exist
+    /// `Err(DbError::NotFound)` is returned, otherwise `Err(DbError)`.
+    async fn get_questions_in_category(&self, category_id: EntityId, include_descendants: bool) -> Result<Vec<Question>, DbError>;
+}
+
+/// The interface for any database access object that will interact with the tags database.
+#[allow(async_fn_in_trait)]
+pub trait TagDao {
+    /// # Required Method
+    /// Labels a question with a tag, creating the tag on its first use. Tags are matched regardless of case and
+    /// surrounding whitespace, and labelling a question with a tag it already has leaves it unchanged.
+    ///
+    /// # Parameters
+    /// `question_id`: The `EntityId` of the question to label
+    /// `name`: The name of the tag, at most `MAX_TAG_LENGTH` characters
+    ///
+    /// # Returns
+    /// A `Result<Tag, DbError>`, `Ok(Tag)` containing the tag. A blank or overlong name is rejected with
+    /// `Err(DbError::Validation)`, and a missing question with `Err(DbError::NotFound)`, otherwise `Err(DbError)`.
+    async fn tag_question(&self, question_id: EntityId, name: &str) -> Result<Tag, DbError>;
+
+    /// # Required Method
+    /// Removes a tag from a question. The tag itself is kept, with its usage count reduced.
+    ///
+    /// # Parameters
+    /// `question_id`: The `EntityId` of the question
+    /// `name`: The name of the tag, matched regardless of case and surrounding whitespace
+    ///
+    /// # Returns
+    /// A `Result<bool, DbError>`, `Ok(true)` if the question had the tag and `Ok(false)` if it did not,
+    /// otherwise `Err(DbError)`.
+    async fn untag_question(&self, question_id: EntityId, name: &str) -> Result<bool, DbError>;
+
+    /// # Required Method
+    /// Suggests the tags whose names start with a prefix, regardless of case, for autocompletion. The most used
+    /// tags are suggested first, ties ordered by name. Wildcards in the prefix are matched literally.
+    ///
+    /// # Parameters
+    /// `prefix`: The start of the tag names, which must not be blank
+    /// `limit`: The maximum number of tags suggested, at least one
+    ///
+    /// # Returns
+    /// A `Result<Vec<TagSuggestion>, DbError>`, in the success case `Ok(Vec<TagSuggestion>)`. A blank prefix, which
+    /// would match every tag, or a limit of zero are rejected with `Err(DbError::Validation)`, otherwise `Err(DbError)`.
+    async fn suggest_tags(&self, prefix: &str, limit: u32) -> Result<Vec<TagSuggestion>, DbError>;
+
+    /// # Required Method
+    /// Gets the usage of every tag, the most used first and ties ordered by name.
+    ///
+    /// # Returns
+    /// A `Result<Vec<TagStats>, DbError>`, in the success case `Ok(Vec<TagStats>)`, otherwise `Err(DbError)`.
+    async fn get_tag_stats(&self) -> Result<Vec<TagStats>, DbError>;
+
+    /// # Required Method
+    /// Gets the acceptance rate of every tag, the share of the questions labelled with it whose author accepted an
+    /// answer, computed in a single statement, including accepted answers since archived. Pinned answers are not counted, as pinning is not acceptance. Questions
+    /// count toward each of their tags. The highest rates come first, ties ordered by the number of questions and then by name.
+    ///
+    /// # Parameters
+    /// `min_questions`: The fewest questions a tag must label to be included, so sparse tags do not crowd the top
+    ///
+    /// # Returns
+    /// A `Result<Vec<TagAcceptance>, DbError>`, in the success case `Ok(Vec<TagAcceptance>)`, otherwise `Err(DbError)`.
+    async fn get_acceptance_rate_by_tag(&self, min_questions: i64) -> Result<Vec<TagAcceptance>, DbError>;
+
+    /// # Required Method
+    /// Renames a tag on every question labelled with it, in a single transaction. If a tag already has the new
+    /// name, the renamed tag is merged into it and removed, questions labelled with both keeping a single label.
+    /// Names are matched regardless of case, so renaming a tag to a name differing only in case changes nothing.
+    ///
+    /// # Parameters
+    /// `from`: The name of the tag being renamed
+    /// `to`: The new name, at most `MAX_TAG_LENGTH` characters
+    ///
+    /// # Returns
+    /// A `Result<RetagReport, DbError>`, `Ok(RetagReport)` counting the questions affected. A blank or overlong name
+    /// is rejected with `Err(DbError::Validation)`, and a missing tag with `Err(DbError::NotFound)`, otherwise
+    /// `Err(DbError)`.
+    async fn rename_tag(&self, from: &str, to: &str) -> Result<RetagReport, DbError>;
+}
+
+/// The interface for any database access object that will interact with the likes of each user.
+#[allow(async_fn_in_trait)]
+pub trait LikeDao {
+    /// # Required Method
+    /// Applies a batch of likes and unlikes made by a user, such as those a client queued while offline, in order and
+    /// within a single transaction. Whether the user likes each entity is stored, so an operation only changes a like
+    /// counter when it changes whether the user likes the entity, and replaying a batch changes nothing. Of the
+    /// operations on an entity, the one with the latest `client_ts` wins, so an operation older than one already
+    /// applied is reported as already applied.
+    ///
+    /// # Parameters
+    /// `user_token`: The token of the user who made the operations
+    /// `ops`: The operations, in the order they were made
+    ///
+    /// # Returns
+    /// A `Result<LikeBatchReport, DbError>`, in the success case `Ok(LikeBatchReport)` with the outcome of each
+    /// operation. Operations on missing entities, on draft answers or with malformed ids are reported in their
+    /// outcome without failing the batch, otherwise `Err(DbError)`, in which case no operation is applied.
+    async fn apply_like_batch(&self, user_token: &str, ops: Vec<LikeOp>) -> Result<LikeBatchReport, DbError>;
+}
+
+/// The interface for any database access object that will interact with the authors of questions and answers.
+#[allow(async_fn_in_trait)]
+pub trait AuthorDao {
+    /// # Required Method
+    /// Gets the users with the most reputation, see `ReputationConfig`, ties ordered by username.
+    ///
+    /// # Parameters
+    /// `limit`: The maximum number of users returned, at least one
+    ///
+    /// # Returns
+    /// A `Result<Vec<UserReputation>, DbError>`, in the success case `Ok(Vec<UserReputation>)`. A limit of zero is
+    /// rejected with `Err(DbError::Validation)`, otherwise `Err(DbError)`.
+    async fn get_top_users_by_reputation(&self, limit: u32) -> Result<Vec<UserReputation>, DbError>;
+}
+
+/// Resolves an `EntityId` to the uuid of a row in `table`, which must be one of the crate's tables.
+/// Uuids are returned as is, while serial ids are looked up by the `serial` column, so a serial id
+/// that matches no row is reported as `DbError::NotFound`.
+async fn resolve_id<'c, E: Executor<'c, Database = Postgres>>(executor: E, tables: &Tables, table: &str, id: EntityId) -> Result<Uuid, DbError> {
+    match id.kind().map_err(DbError::invalid_id)? {
+        EntityIdKind::Uuid(id) => Ok(id),
+        EntityIdKind::Serial(serial) => sqlx::query_scalar(&tables.sql(&format!("SELECT id FROM {table} WHERE serial = $1")))
+            .bind(serial)
+            .fetch_one(executor)
+            .await
+            .map_err(missing(entity_of(table), serial)),
+    }
+}
+
+/// Gets the time a question was locked, if it is, and locks its row for the rest of the transaction.
+async fn lock_state(tx: &mut Transaction<'_, Postgres>, tables: &Tables, question_id: Uuid) -> Result<Option<DateTime<Utc>>, DbError> {
+    sqlx::query_scalar(&tables.sql("SELECT locked_at FROM questions WHERE id = $1 FOR UPDATE"))
+        .bind(question_id)
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(missing("question", question_id))
+}
+
+/// Adds `delta` to the reputation of the author of the question or answer `id` in `table`, which must be `questions`
+/// or `answers`, within the transaction of the event earning it. Content without an author changes nothing.
+async fn adjust_reputation(tx: &mut Transaction<'_, Postgres>, tables: &Tables, table: &str, id: Uuid, delta: i32) -> Result<(), DbError> {
+    if delta == 0 {
+        return Ok(());
+    }
+    sqlx::query(&tables.sql(&format!(
+        "UPDATE users SET reputation = reputation + $1 FROM {table} WHERE {table}.id = $2 AND users.id = {table}.author_id"
+    )))
+        .bind(delta)
+        .bind(id)
+        .execute(&mut **tx)
+        .await
+        .map_err(DbError::update)?;
+    Ok(())
+}
+
+/// Gets the answer accepted on a question, if any, and locks its row for the rest of the transaction, so the
+/// acceptance cannot move concurrently.
+async fn accepted_answer(tx: &mut Transaction<'_, Postgres>, tables: &Tables, question_id: Uuid) -> Result<Option<Uuid>, DbError> {
+    sqlx::query_scalar(&tables.sql("SELECT accepted_answer_id FROM questions WHERE id = $1 FOR UPDATE"))
+        .bind(question_id)
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(missing("question", question_id))
+}
+
+/// Clears the acceptance of the archived accepted answer of a question, if it has one, taking the reputation `weight`
+/// it earned back from its author, as accepting or unaccepting an answer replaces it. The question must be locked by
+/// `accepted_answer`.
+async fn clear_archived_acceptance(tx: &mut Transaction<'_, Postgres>, tables: &Tables, question_id: Uuid, weight: i32) -> Result<(), DbError> {
+    sqlx::query(&tables.sql(
+        "WITH cleared AS ( \
+            UPDATE questions SET archived_accepted_answer_id = NULL FROM questions AS previous \
+            WHERE questions.id = $1 AND previous.id = $1 AND previous.archived_accepted_answer_id IS NOT NULL \
+            RETURNING previous.archived_accepted_answer_id AS answer_id \
+        ) \
+        UPDATE users SET reputation = reputation - $2 \
+        FROM cleared JOIN answers_archive ON answers_archive.answer_id = cleared.answer_id \
+        WHERE users.id = answers_archive.author_id"
+    ))
+        .bind(question_id)
+        .bind(weight)
+        .execute(&mut **tx)
+        .await
+        .map_err(DbError::update)?;
+    Ok(())
+}
+
+/// Checks that an answer belongs to a question, locking the answer for the rest of the transaction so it cannot be
+/// moved to another question or deleted before the question points at it. A missing answer is reported as
+/// `DbError::NotFound`, and an answer to another question as `DbError::Validation`.
+async fn check_answer_of(tx: &mut Transaction<'_, Postgres>, tables: &Tables, question_id: Uuid, answer_id: Uuid) -> Result<(), DbError> {
+    let answer_question_id: Option<Uuid> = sqlx::query_scalar(&tables.sql("SELECT question_id FROM answers WHERE id = $1 FOR SHARE"))
+        .bind(answer_id)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(DbError::from)?
+        .ok_or_else(|| DbError::not_found("answer", answer_id))?;
+    if answer_question_id != Some(question_id) {
+        return Err(DbError::validation(format!("answer {answer_id} does not belong to question {question_id}")));
+    }
+    Ok(())
+}
+
+/// Checks `texts` against `policy`, if there is one. The first rejection is returned as
+/// `ValidationReason::PolicyViolation`, otherwise the reason of the first flag is returned, if any were raised.
+fn check_policy(policy: Option<&dyn ContentPolicy>, texts: &[(ContentKind, &str)]) -> Result<Option<String>, DbError> {
+    let Some(policy) = policy else {
+        return Ok(None);
+    };
+    let mut flag = None;
+    for &(kind, text) in texts {
+        match policy.check(kind, text) {
+            PolicyDecision::Allow => {}
+            PolicyDecision::Reject(reason) => return Err(ValidationReason::PolicyViolation(reason).into()),
+            PolicyDecision::Flag(reason) => {
+                flag.get_or_insert(reason);
+            }
+        }
+    }
+    Ok(flag)
+}
+
+/// Records a moderation flag raised by a content policy on a question or an answer, in the transaction storing
+/// the entity so that neither is kept without the other.
+async fn insert_flag(
+    tx: &mut Transaction<'_, Postgres>,
+    tables: &Tables,
+    entity: LikableEntity,
+    id: Uuid,
+    reason: String,
+    now: DateTime<Utc>,
+) -> Result<(), DbError> {
+    let column = match entity {
+        LikableEntity::Question => "question_id",
+        LikableEntity::Answer => "answer_id",
+    };
+    sqlx::query(&tables.sql(&format!("INSERT INTO moderation_flags ({column}, reason, created_at) VALUES ($1, $2, $3)")))
+        .bind(id)
+        .bind(reason)
+        .bind(now)
+        .execute(&mut **tx)
+        .await
+        .map(|_| ())
+        .map_err(creation_error)
+}
+
+/// The first half of the key of the advisory locks serializing the creations of each author token, distinct from
+/// the namespace of the maintenance locks.
+const AUTHOR_LOCK_NAMESPACE: i32 = 0x5141_4154;
+
+/// Rejects the creation of a row of `table` with the author token `token` if the author created `max` or more rows
+/// of it within the hour before `now`. Creations without a token, or without a quota, are not limited. Concurrent
+/// creations with the same token are serialized until the transaction ends, so they cannot all pass the check.
+///
+/// # Returns
+/// A `Result<(), DbError>`, `Ok(())` if the row can be created, otherwise `UnavailableReason::QuotaExceeded`
+/// carrying the time until enough of the author's rows leave the window to create another.
+async fn check_quota(
+    tx: &mut Transaction<'_, Postgres>,
+    tables: &Tables,
+    table: &str,
+    token: Option<&str>,
+    max: Option<u32>,
+    now: DateTime<Utc>,
+) -> Result<(), DbError> {
+    let (Some(token), Some(max)) = (token, max) else {
+        return Ok(());
+    };
+    let window = Duration::hours(1);
+    if max == 0 {
+        return Err(UnavailableReason::QuotaExceeded { retry_after: window }.into());
+    }
+    sqlx::query("SELECT pg_advisory_xact_lock($1, hashtext($2))")
+        .bind(AUTHOR_LOCK_NAMESPACE)
+        .bind(token)
+        .execute(&mut **tx)
+        .await
+        .map_err(DbError::from)?;
+    // The creation time of the `max`th most recent row, once it leaves the window the author is under the quota again
+    let oldest_counted: Option<DateTime<Utc>> = sqlx::query_scalar(&tables.sql(&format!(
+        "SELECT created_at FROM {table} WHERE author_token = $1 AND created_at > $2 ORDER BY created_at DESC OFFSET $3 LIMIT 1"
+    )))
+        .bind(token)
+        .bind(now - window)
+        .bind(i64::from(max) - 1)
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(DbError::from)?;
+    match oldest_counted {
+        Some(created_at) => Err(UnavailableReason::QuotaExceeded { retry_after: created_at + window - now }.into()),
+        None => Ok(()),
+    }
+}
+
+/// The leading `ORDER BY` expression placing pinned questions before the others, the most recently pinned first.
+const PINNED_FIRST: &str = "pinned_at DESC NULLS LAST";
+
+/// The column read for each of the `QuestionFields` by a projected listing.
+const QUESTION_FIELD_COLUMNS: [(QuestionFields, &str); 4] = [
+    (QuestionFields::TITLE, "title"),
+    (QuestionFields::BODY, "question"),
+    (QuestionFields::LIKES, "likes"),
+    (QuestionFields::CREATED_AT, "created_at"),
+];
+
+/// The columns of `answers` read into an `Answer` when its content is left out, see `AnswerBodyMode::None`.
+const ANSWER_COLUMNS_WITHOUT_BODY: &str = "answers.id, answers.question_id, answers.likes, answers.created_at, \
+    answers.author_id, answers.serial, answers.published, answers.helpful_count, answers.unhelpful_count, \
+    answers.char_count, answers.word_count, answers.approved_at, answers.content_type, answers.parent_answer_id, \
+    answers.deleted_at, answers.deleted_reason";
+
+/// The number of rows updated per statement when recomputing content statistics.
+const CONTENT_STATS_BATCH_SIZE: i64 = 500;
+
+/// The condition a published answer must also meet to be listed and counted under `mode`, where `now` is the
+/// placeholder bound to the current time.
+fn answer_visibility(mode: ModerationMode, now: &str) -> String {
+    match mode {
+        ModerationMode::Off => String::from("TRUE"),
+        ModerationMode::DelayMinutes(minutes) => format!(
+            "(answers.approved_at IS NOT NULL OR answers.created_at + {minutes} * interval '1 minute' <= {now})"
+        ),
+        ModerationMode::Manual => String::from("answers.approved_at IS NOT NULL"),
+    }
+}
+
+/// The query of a question along with the number of its visible answers, in total and to the question itself,
+/// binding the question's id to `$1` and the current time to `$2`. With `for_author` the caller's author token is
+/// bound to `$3`, and the question reports whether it has that token.
+fn question_header_query(moderation: ModerationMode, for_author: bool) -> String {
+    let visible = format!("answers.question_id = questions.id AND answers.published AND {}", answer_visibility(moderation, "$2"));
+    format!(
+        "SELECT questions.*, \
+            (SELECT COUNT(*) FROM answers WHERE {visible}) AS answer_count, \
+            (SELECT COUNT(*) FROM answers WHERE {visible} AND answers.parent_answer_id IS NULL) AS top_level_answer_count{} \
+        FROM questions WHERE id = $1",
+        ownership("questions", for_author)
+    )
+}
+
+/// The query of the visible answers in the default order, binding the id of their question, or `NULL` for the answers
+/// to every question, to `$1` and the current time to `$2`. The answers to a single question start with its pinned
+/// answer. With `for_author` the caller's author token is bound to `$3`, and each answer reports whether it has it.
+fn answer_listing_query(moderation: ModerationMode, for_author: bool) -> String {
+    format!(
+        "SELECT *{} FROM answers WHERE published AND {} AND ($1::uuid IS NULL OR question_id = $1) ORDER BY {}, {}",
+        ownership("answers", for_author),
+        answer_visibility(moderation, "$2"),
+        pinned_answer_first("$1"),
+        answer_order(AnswerSort::default())
+    )
+}
+
+/// The query of the answers to the question whose id is bound to `$1` that the detail of the question embeds, see
+/// `DetailOptions`, binding the current time to `$2` and the caller's author token to `$3` with `for_author`. The
+/// limit is applied by the database, so answers that are not embedded are never read.
+fn embedded_answers_query(moderation: ModerationMode, for_author: bool, options: DetailOptions) -> String {
+    // Leaving the content out of the statement keeps the database from sending it at all
+    let columns = match options.answer_bodies {
+        AnswerBodyMode::None => ANSWER_COLUMNS_WITHOUT_BODY,
+        AnswerBodyMode::Full | AnswerBodyMode::Preview => "*",
+    };
+    format!(
+        "SELECT {columns}{} FROM answers WHERE published AND {} AND question_id = $1 ORDER BY {}, {} LIMIT {}",
+        ownership("answers", for_author),
+        answer_visibility(moderation, "$2"),
+        pinned_answer_first("$1"),
+        answer_order(options.sort),
+        options.max_embedded_answers
+    )
+}
+
+/// The `is_mine` column of rows of `table`, comparing their author token with the one bound to `$3`, or nothing
+/// unless `for_author`, leaving ownership unknown and the queries usable on schemas without author tokens.
+fn ownership(table: &str, for_author: bool) -> String {
+    if for_author {
+        format!(", {table}.author_token IS NOT DISTINCT FROM $3 AS is_mine")
+    } else {
+        String::new()
+    }
+}
+
+/// The `ORDER BY` term listing the pinned answer of the question whose id is bound to `question_param` first, which
+/// leaves the order unchanged if the question has no pinned answer or the parameter is `NULL`.
+fn pinned_answer_first(question_param: &str) -> String {
+    format!("answers.id = (SELECT pinned_answer_id FROM questions WHERE questions.id = {question_param}) DESC NULLS LAST")
+}
+
+/// The position of `AnswerDao::get_answers_paged` in the answers to a question: the snapshot the likes are read
+/// as of, and the likes as of the snapshot, creation time and id of the last answer read.
+struct AnswerPageToken {
+    snapshot: DateTime<Utc>,
+    likes: i64,
+    created_at: DateTime<Utc>,
+    id: Uuid,
+}
+
+impl AnswerPageToken {
+    /// Encodes the token as `snapshot.likes.created_at.id`, with the times in microseconds since the epoch, which is
+    /// the precision the database stores them with.
+    fn encode(&self) -> String {
+        format!("{}.{}.{}.{}", self.snapshot.timestamp_micros(), self.likes, self.created_at.timestamp_micros(), self.id.simple())
+    }
+
+    /// Decodes a token made by `encode`, rejecting anything else with `DbError::Validation`.
+    fn decode(token: &str) -> Result<Self, DbError> {
+        let malformed = || DbError::validation(String::from("page token is malformed"));
+        let mut parts = token.split('.');
+        let mut next = || parts.next().ok_or_else(malformed);
+        let time = |micros: &str| {
+            micros.parse().ok().and_then(chrono::NaiveDateTime::from_timestamp_micros).map(|time| time.and_utc()).ok_or_else(malformed)
+        };
+        let decoded = Self {
+            snapshot: time(next()?)?,
+            likes: next()?.parse().map_err(|_| malformed())?,
+            created_at: time(next()?)?,
+            id: Uuid::try_parse(next()?).map_err(|_| malformed())?,
+        };
+        if parts.next().is_some() {
+            return Err(malformed());
+        }
+        Ok(decoded)
+    }
+}
+
+/// The `ORDER BY` clause for listing answers in the given order.
+fn answer_order(sort: AnswerSort) -> &'static str {
+    match sort {
+        AnswerSort::MostLiked => "likes DESC, created_at, id",
+        AnswerSort::Newest => "created_at DESC, id DESC",
+        AnswerSort::Oldest => "created_at, id",
+        AnswerSort::Score => "helpful_count - unhelpful_count DESC, created_at, id",
+    }
+}
+
+/// A `QuestionDao` backed by a Postgres connection pool, or by the transaction of a `ScopedDao`.
+pub struct QuestionDaoImpl {
+    source: Source,
+    tables: Tables,
+    limits: ContentLimits,
+    clock: Arc<dyn Clock>,
+    delete_policy: DeletePolicy,
+    max_pinned: u32,
+    view_window: Duration,
+    moderation: ModerationMode,
+    policy: Option<Arc<dyn ContentPolicy>>,
+    quota: Option<CreationQuota>,
+    reputation: ReputationConfig,
+    observer: Option<Arc<dyn QueryObserver>>,
+}
+
+impl QuestionDaoImpl {
+    /// Creates the data access object, using the system clock, the default `ContentLimits`, the default
+    /// `DeletePolicy`, allowing up to `DEFAULT_MAX_PINNED` pinned questions, counting views once every
+    /// `DEFAULT_VIEW_WINDOW_MINUTES` per client, with moderation off and without a `ContentPolicy`.
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            source: Source::Pool { pool, waits: None },
+            tables: Tables::default(),
+            limits: ContentLimits::default(),
+            clock: Arc::new(SystemClock),
+            delete_policy: DeletePolicy::default(),
+            max_pinned: DEFAULT_MAX_PINNED,
+            view_window: Duration::minutes(DEFAULT_VIEW_WINDOW_MINUTES),
+            moderation: ModerationMode::default(),
+            policy: None,
+            quota: None,
+            reputation: ReputationConfig::default(),
+            observer: None,
+        }
+    }
+
+    /// Sets the `Clock` used to timestamp new questions and compute time based cutoffs.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Sets the `ContentLimits` new questions are validated against.
+    pub fn with_limits(mut self, limits: ContentLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Sets the `DeletePolicy` used by `delete_question`.
+    pub fn with_delete_policy(mut self, policy: DeletePolicy) -> Self {
+        self.delete_policy = policy;
+        self
+    }
+
+    /// Sets the maximum number of questions that can be pinned at once.
+    pub fn with_max_pinned(mut self, max_pinned: u32) -> Self {
+        self.max_pinned = max_pinned;
+        self
+    }
+
+    /// Sets the window within which repeated views of a question by the same client count once.
+    pub fn with_view_window(mut self, view_window: Duration) -> Self {
+        self.view_window = view_window;
+        self
+    }
+
+    /// Places the tables the data access object reads and writes in `schema`, which should have been migrated with
+    /// `migrations::run_in_schema`.
+    pub fn with_schema(mut self, schema: SchemaName) -> Self {
+        self.tables = Tables::in_schema(schema);
+        self
+    }
+
+    /// Records how long each call waited to acquire a connection from the pool in `waits`, such as those reported by
+    /// the `PoolMonitor` of the pool, see `PoolMonitor::acquire_waits`.
+    pub fn with_acquire_waits(mut self, waits: Arc<AcquireWaits>) -> Self {
+        if let Source::Pool { waits: current, .. } = &mut self.source {
+            *current = Some(waits);
+        }
+        self
+    }
+
+    /// Fails every connection the data access object acquires with `error`, so tests of failure paths get the same
+    /// error on every sqlx version, without closing the pool.
+    #[cfg(test)]
+    pub(crate) fn with_failing_source(mut self, error: fn() -> sqlx::Error) -> Self {
+        self.source = Source::Failing(error);
+        self
+    }
+
+    /// Sets the `QueryObserver` notified of every call, see `observer`.
+    pub fn with_observer(mut self, observer: Arc<dyn QueryObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Sets the `ModerationMode` deciding which answers are counted, which should match the mode of the
+    /// `AnswerDaoImpl` listing them.
+    pub fn with_moderation(mut self, moderation: ModerationMode) -> Self {
+        self.moderation = moderation;
+        self
+    }
+
+    /// Sets the `ContentPolicy` the titles and content of new and edited questions are checked against once
+    /// validated. Rejected questions are reported as `ValidationReason::PolicyViolation`, while flagged questions
+    /// are stored along with a moderation flag.
+    pub fn with_policy(mut self, policy: Arc<dyn ContentPolicy>) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Sets the `CreationQuota` limiting the questions created with each author token, of which only
+    /// `max_questions_per_hour` applies. Quotas are not enforced by default.
+    pub fn with_quota(mut self, quota: CreationQuota) -> Self {
+        self.quota = Some(quota);
+        self
+    }
+
+    /// Sets the `ReputationConfig` deciding the reputation authors gain as their questions are liked and their
+    /// answers accepted.
+    pub fn with_reputation(mut self, reputation: ReputationConfig) -> Self {
+        self.reputation = reputation;
+        self
+    }
+
+    /// Checks the title and content of a question against the `ContentPolicy`, see `check_policy`. Empty text,
+    /// left unchanged by an update, is not checked.
+    fn check_policy(&self, title: &str, question: &str) -> Result<Option<String>, DbError> {
+        let texts = [(ContentKind::Title, title), (ContentKind::Question, question)];
+        let texts: Vec<_> = texts.into_iter().filter(|(_, text)| !text.is_empty()).collect();
+        check_policy(self.policy.as_deref(), &texts)
+    }
+
+    /// Purges expired idempotency keys like `QuestionDao::purge_idempotency_keys`, unless another instance is
+    /// already purging them, see `lock::with_advisory_lock`. The lock outlives any transaction, so calls on the
+    /// transaction of a `ScopedDao` are rejected with `Err(DbError::Validation)`.
+    pub async fn purge_idempotency_keys_guarded(&self, ttl: Duration) -> Result<LockOutcome<u64>, DbError> {
+        let Source::Pool { pool, .. } = &self.source else {
+            return Err(DbError::validation(String::from("maintenance cannot be guarded within a scope")));
+        };
+        lock::with_advisory_lock(pool, MaintenanceLock::PurgeIdempotencyKeys, || self.purge_idempotency_keys(ttl)).await
+    }
+
+    /// Reads the header of a question and then its answers on one transaction, awaiting `between_reads` in between so
+    /// that tests can interleave concurrent writes. With `snapshot` the transaction is read only and `REPEATABLE READ`,
+    /// so both reads see the same snapshot, otherwise each read sees the changes committed before it started. With
+    /// `embed` only the answers it selects are read, otherwise all of them.
+    async fn read_question_detail(
+        &self,
+        question_id: Uuid,
+        snapshot: bool,
+        author_token: Option<&str>,
+        embed: Option<DetailOptions>,
+        between_reads: impl std::future::Future<Output = ()>,
+    ) -> Result<QuestionDetail, DbError> {
+        let now = self.clock.now();
+        let mut conn = self.source.acquire().await.map_err(DbError::from)?;
+        let mut tx = conn.begin().await.map_err(DbError::from)?;
+        if snapshot {
+            // Must be the first statement of the transaction, the snapshot is taken by the first read
+            sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+                .execute(&mut *tx)
+                .await
+                .map_err(DbError::from)?;
+        }
+        // The author token is only bound when the queries compare it, see `ownership`
+        let header_query = question_header_query(self.moderation, author_token.is_some());
+        let header_query = self.tables.sql(&header_query);
+        let header = sqlx::query_as::<_, QuestionHeader>(&header_query).bind(question_id).bind(now);
+        let header = match author_token {
+            Some(token) => header.bind(token),
+            None => header,
+        };
+        let header = header
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(missing("question", question_id))?;
+        between_reads.await;
+        let answers_query = match embed {
+            Some(options) => embedded_answers_query(self.moderation, author_token.is_some(), options),
+            None => answer_listing_query(self.moderation, author_token.is_some()),
+        };
+        let answers_query = self.tables.sql(&answers_query);
+        let answers = sqlx::query_as::<_, Answer>(&answers_query).bind(question_id).bind(now);
+        let answers = match author_token {
+            Some(token) => answers.bind(token),
+            None => answers,
+        };
+        let answers = answers
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(DbError::from)?;
+        let translations = match embed {
+            Some(options) if options.include_translations => {
+                sqlx::query_as::<_, QuestionTranslation>(&self.tables.sql("SELECT * FROM question_translations WHERE question_id = $1 ORDER BY lang"))
+                    .bind(question_id)
+                    .fetch_all(&mut *tx)
+                    .await
+                    .map_err(DbError::from)?
+            }
+            _ => Vec::new(),
+        };
+        let archived_answers = match embed {
+            Some(options) if options.include_archived => Some(
+                sqlx::query_scalar::<_, i64>(&self.tables.sql("SELECT archived_answer_count FROM questions WHERE id = $1"))
+                    .bind(question_id)
+                    .fetch_one(&mut *tx)
+                    .await
+                    .map_err(missing("question", question_id))?,
+            ),
+            _ => None,
+        };
+        tx.commit().await.map_err(DbError::commit)?;
+        // The header counts every published answer, however many are embedded
+        let total_answers = header.answer_count();
+        let mut detail = QuestionDetail::new(header, answers, snapshot).with_translations(translations);
+        if let Some(archived_answers) = archived_answers {
+            detail = detail.with_archived_answers(archived_answers);
+        }
+        match embed {
+            Some(options) if options.include_total => Ok(detail.with_total_answers(total_answers)),
+            _ => Ok(detail),
+        }
+    }
+
+    /// Deletes a question, handling its answers with `policy`. Locked questions are only deleted when forced.
+    async fn delete_question_as(&self, question_id: EntityId, policy: DeletePolicy, force: bool) -> Result<Uuid, DbError> {
+        // Attempt to parse entity id
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        let mut conn = self.source.acquire().await.map_err(DbError::from)?;
+        let mut tx = conn.begin().await.map_err(DbError::from)?;
+        // Ensure that a record with the given id exists, and that it may be deleted. The row lock also blocks
+        // answers from being created until the deletion commits, so the answers handled below are complete
+        if lock_state(&mut tx, &self.tables, question_id).await?.is_some() && !force {
+            return Err(ConflictReason::Locked(question_id).into());
+        }
+        match policy {
+            DeletePolicy::Restrict => {
+                let count: i64 = sqlx::query_scalar(&self.tables.sql("SELECT COUNT(*) FROM answers WHERE question_id = $1"))
+                    .bind(question_id)
+                    .fetch_one(&mut *tx)
+                    .await
+                    .map_err(DbError::from)?;
+                if count > 0 {
+                    return Err(ConflictReason::HasDependents { count }.into());
+                }
+            }
+            // The answers are deleted by the database when the question is
+            DeletePolicy::Cascade => {}
+            DeletePolicy::Orphan => {
+                sqlx::query(&self.tables.sql("UPDATE answers SET question_id = NULL WHERE question_id = $1"))
+                    .bind(question_id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(DbError::update)?;
+            }
+        }
+        // Now attempt to delete the record, and commit the changes if successful
+        let id = sqlx::query(&self.tables.sql("DELETE FROM questions WHERE id = $1 RETURNING id"))
+            .bind(question_id)
+            .try_map(|row: PgRow| row.try_get("id"))
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(DbError::deletion)?;
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(id)
+    }
+
+    /// Deletes a single batch of questions within one transaction, handling their answers with `policy` the same as
+    /// `delete_question_as`, returning the number of questions deleted. Locked questions are only deleted when forced.
+    async fn delete_question_batch(&self, ids: &[Uuid], policy: DeletePolicy, force: bool) -> Result<u64, DbError> {
+        let mut conn = self.source.acquire().await.map_err(DbError::from)?;
+        let mut tx = conn.begin().await.map_err(DbError::from)?;
+        // Lock the rows in a consistent order, which also blocks answers from being created until the batch commits
+        let locked: Option<Uuid> = sqlx::query_scalar(&self.tables.sql(
+            "SELECT id FROM (SELECT id, locked_at FROM questions WHERE id = ANY($1) ORDER BY id FOR UPDATE) batch \
+            WHERE locked_at IS NOT NULL LIMIT 1"
+        ))
+            .bind(ids)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(DbError::from)?;
+        if let (Some(question_id), false) = (locked, force) {
+            return Err(ConflictReason::Locked(question_id).into());
+        }
+        match policy {
+            DeletePolicy::Restrict => {
+                let count: i64 = sqlx::query_scalar(&self.tables.sql("SELECT COUNT(*) FROM answers WHERE question_id = ANY($1)"))
+                    .bind(ids)
+                    .fetch_one(&mut *tx)
+                    .await
+                    .map_err(DbError::from)?;
+                if count > 0 {
+                    return Err(ConflictReason::HasDependents { count }.into());
+                }
+            }
+            // The answers are deleted by the database when the questions are
+            DeletePolicy::Cascade => {}
+            DeletePolicy::Orphan => {
+                sqlx::query(&self.tables.sql("UPDATE answers SET question_id = NULL WHERE question_id = ANY($1)"))
+                    .bind(ids)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(DbError::update)?;
+            }
+        }
+        let deleted = sqlx::query(&self.tables.sql("DELETE FROM questions WHERE id = ANY($1)"))
+            .bind(ids)
+            .execute(&mut *tx)
+            .await
+            .map_err(DbError::deletion)?
+            .rows_affected();
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(deleted)
+    }
+}
+
+observed_dao! {
+    QuestionDao for QuestionDaoImpl via question_calls as EntityKind::Question;
+    create_question(new_question: NewQuestion) -> Question;
+    get_question(question_id: EntityId) -> Question, id: question_id;
+    get_question_as_of(question_id: EntityId, at: DateTime<Utc>) -> Question, id: question_id;
+    get_revision_diff(question_id: EntityId, from_rev: u32, to_rev: u32) -> Vec<DiffHunk>, id: question_id;
+    get_questions() -> Vec<Question>;
+    get_questions_projected(fields: QuestionFields, page: PageRequest) -> Page<QuestionPartial>;
+    get_questions_shorter_than(max_words: i32) -> Vec<Question>;
+    get_questions_in_period(period: Period, tz: Tz) -> Vec<Question>;
+    get_questions_by_like_range(min: i32, max: Option<i32>, created_after: Option<DateTime<Utc>>) -> Vec<Question>;
+    get_questions_exceeding_like_rate(likes_per_hour: f64, window: Duration) -> Vec<QuestionLikeRate>;
+    get_question_header(question_id: EntityId) -> QuestionHeader, id: question_id;
+    get_question_detail_consistent(question_id: EntityId) -> QuestionDetail, id: question_id;
+    get_question_detail_for_author(question_id: EntityId, author_token: &str) -> QuestionDetail, id: question_id;
+    get_question_detail_with(question_id: EntityId, options: DetailOptions) -> QuestionDetail, id: question_id;
+    get_questions_by_token(author_token: &str) -> Vec<Question>;
+    search_questions(query: &str, ranking: SearchRankingConfig, limit: u32) -> Vec<RankedQuestion>;
+    get_activity_feed(limit: u32, before: Option<ActivityCursor>) -> Vec<ActivityItem>;
+    update_question(question_id: EntityId, update: UpdateQuestion) -> Question, id: question_id;
+    lock_question(question_id: EntityId) -> Question, id: question_id;
+    unlock_question(question_id: EntityId) -> Question, id: question_id;
+    pin_question(question_id: EntityId) -> Question, id: question_id;
+    unpin_question(question_id: EntityId) -> Question, id: question_id;
+    pin_answer(question_id: EntityId, answer_id: EntityId) -> Question, id: question_id;
+    unpin_answer(question_id: EntityId) -> Question, id: question_id;
+    accept_answer(question_id: EntityId, answer_id: EntityId) -> Question, id: question_id;
+    unaccept_answer(question_id: EntityId) -> Question, id: question_id;
+    set_bounty(question_id: EntityId, amount: i32, duration: Duration) -> Question, id: question_id;
+    clear_bounty(question_id: EntityId) -> Question, id: question_id;
+    upsert_translation(question_id: EntityId, lang: &str, content: NewTranslation) -> QuestionTranslation, id: question_id;
+    get_translation(question_id: EntityId, lang: &str) -> Question, id: question_id;
+    link_questions(from_id: EntityId, to_id: EntityId, kind: LinkKind) -> (), id: from_id;
+    unlink_questions(from_id: EntityId, to_id: EntityId, kind: LinkKind) -> bool, id: from_id;
+    get_linked_questions(question_id: EntityId) -> Vec<(LinkKind, Question)>, id: question_id;
+    delete_question(question_id: EntityId, force: bool) -> Uuid, id: question_id;
+    delete_question_with_policy(question_id: EntityId, policy: DeletePolicy) -> Uuid, id: question_id;
+    increment_question_likes(question_id: EntityId) -> i64, id: question_id;
+    record_view(question_id: EntityId, client_token: &str) -> ViewOutcome, id: question_id;
+    create_question_idempotent(new_question: NewQuestion, key: &str) -> CreateOutcome;
+    upsert_question_by_external_id(new_question: NewQuestion) -> UpsertOutcome;
+    purge_idempotency_keys(ttl: Duration) -> u64;
+    expire_bounties() -> Vec<Uuid>;
+    delete_questions_batched(ids: Vec<EntityId>, batch_size: usize, force: bool, progress: Option<&mut dyn FnMut(BatchProgress)>) -> u64;
+}
+
+impl question_calls::Calls for QuestionDaoImpl {
+    async fn create_question(&self, new_question: NewQuestion) -> Result<Question, DbError> {
+        // Validate before touching the database
+        new_question.validate(&self.limits)?;
+        let flag = self.check_policy(&new_question.title, &new_question.question)?;
+        let title_normalized = normalize_title(&new_question.title);
+        let stats = ContentStats::of(&new_question.question);
+        let now = self.clock.now();
+        let mut conn = self.source.acquire().await.map_err(creation_error)?;
+        let mut tx = conn.begin().await.map_err(creation_error)?;
+        let max = self.quota.map(|quota| quota.max_questions_per_hour);
+        check_quota(&mut tx, &self.tables, "questions", new_question.author_token.as_deref(), max, now).await?;
+        let question = sqlx::query_as::<_, Question>(&self.tables.sql(
+            "INSERT INTO questions (title, question, created_at, external_id, title_normalized, char_count, word_count, content_type, author_token) \
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) RETURNING *"
+        ))
+            .bind(new_question.title)
+            .bind(new_question.question)
+            .bind(now)
+            .bind(new_question.external_id)
+            .bind(title_normalized)
+            .bind(stats.char_count)
+            .bind(stats.word_count)
+            .bind(new_question.content_type.unwrap_or_default())
+            .bind(new_question.author_token)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(creation_error)?;
+        if let Some(reason) = flag {
+            insert_flag(&mut tx, &self.tables, LikableEntity::Question, question.id(), reason, now).await?;
+        }
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(question)
+    }
+
+    async fn get_question(&self, question_id: EntityId) -> Result<Question, DbError> {
+        // Attempt to parse entity id
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        sqlx::query_as::<_, Question>(&self.tables.sql("SELECT * FROM questions WHERE id = $1"))
+            .bind(question_id)
+            .fetch_one(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(missing("question", question_id))
+    }
+
+    async fn get_question_as_of(&self, question_id: EntityId, at: DateTime<Utc>) -> Result<Question, DbError> {
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        // The content as of `at` is the content replaced by the first edit after it, or the current content
+        sqlx::query(&self.tables.sql(
+            "SELECT questions.*, revision.title AS revision_title, revision.question AS revision_question, \
+                revision.content_type AS revision_content_type, revision.char_count AS revision_char_count, \
+                revision.word_count AS revision_word_count, revision.updated_at AS revision_updated_at \
+            FROM questions LEFT JOIN LATERAL ( \
+                SELECT * FROM question_revisions WHERE question_id = questions.id AND revised_at > $2 \
+                ORDER BY revised_at, id LIMIT 1 \
+            ) revision ON true \
+            WHERE questions.id = $1 AND questions.created_at <= $2"
+        ))
+            .bind(question_id)
+            .bind(at)
+            .try_map(|row: PgRow| {
+                let question: Question = sqlx::FromRow::from_row(&row)?;
+                let Some(title) = row.try_get("revision_title")? else {
+                    return Ok(question);
+                };
+                Ok(question.with_revision(QuestionRevision {
+                    title,
+                    question: row.try_get("revision_question")?,
+                    content_type: row.try_get("revision_content_type")?,
+                    char_count: row.try_get("revision_char_count")?,
+                    word_count: row.try_get("revision_word_count")?,
+                    updated_at: row.try_get("revision_updated_at")?,
+                }))
+            })
+            .fetch_one(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(missing("question", question_id))
+    }
+
+    async fn get_revision_diff(&self, question_id: EntityId, from_rev: u32, to_rev: u32) -> Result<Vec<DiffHunk>, DbError> {
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        // Revisions are numbered in the order they were replaced, followed by the current content. The last revision
+        // is always read, so that a missing revision can be told apart from a missing question
+        let revisions: Vec<(i64, i64, String)> = sqlx::query_as(&self.tables.sql(
+            "WITH history AS ( \
+                SELECT question, revised_at, id FROM question_revisions WHERE question_id = $1 \
+                UNION ALL \
+                SELECT question, 'infinity'::timestamptz, NULL FROM questions WHERE id = $1 \
+            ), numbered AS ( \
+                SELECT question, ROW_NUMBER() OVER (ORDER BY revised_at, id NULLS LAST) AS rev, COUNT(*) OVER () AS total \
+                FROM history \
+            ) \
+            SELECT rev, total, question FROM numbered WHERE rev IN ($2, $3) OR rev = total"
+        ))
+            .bind(question_id)
+            .bind(i64::from(from_rev))
+            .bind(i64::from(to_rev))
+            .fetch_all(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(DbError::from)?;
+        // The current content is always a revision, so no rows means no question
+        let &(_, total, _) = revisions.first().ok_or_else(|| DbError::not_found("question", question_id))?;
+        let content = |rev: u32| {
+            revisions.iter()
+                .find(|(n, _, _)| *n == i64::from(rev))
+                .map(|(_, _, question)| question.as_str())
+                .ok_or_else(|| DbError::validation(format!("revision {rev} does not exist, the question has revisions 1 to {total}")))
+        };
+        Ok(diff_revisions(content(from_rev)?, content(to_rev)?))
+    }
+
+    async fn get_questions(&self) -> Result<Vec<Question>, DbError> {
+        sqlx::query_as::<_, Question>(&self.tables.sql(&format!("SELECT * FROM questions ORDER BY {PINNED_FIRST}, created_at, id")))
+            .fetch_all(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(DbError::from)
+    }
+
+    async fn get_questions_projected(&self, fields: QuestionFields, page: PageRequest) -> Result<Page<QuestionPartial>, DbError> {
+        page.validate()?;
+        // Only trusted column names are interpolated, never input
+        let columns: Vec<&str> = std::iter::once("id")
+            .chain(QUESTION_FIELD_COLUMNS.iter().filter(|(field, _)| fields.contains(*field)).map(|(_, column)| *column))
+            .collect();
+        // One more question than requested is read to tell whether more follow the page
+        let mut items = sqlx::query_as::<_, QuestionPartial>(&self.tables.sql(&format!(
+            "SELECT {} FROM questions ORDER BY {PINNED_FIRST}, created_at, id LIMIT $1 OFFSET $2",
+            columns.join(", ")
+        )))
+            .bind(i64::from(page.limit) + 1)
+            .bind(i64::from(page.offset))
+            .fetch_all(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(DbError::from)?;
+        let has_more = items.len() > page.limit as usize;
+        items.truncate(page.limit as usize);
+        Ok(Page { items, has_more })
+    }
+
+    async fn get_questions_shorter_than(&self, max_words: i32) -> Result<Vec<Question>, DbError> {
+        sqlx::query_as::<_, Question>(&self.tables.sql("SELECT * FROM questions WHERE word_count < $1 ORDER BY word_count, created_at, id"))
+            .bind(max_words)
+            .fetch_all(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(DbError::from)
+    }
+
+    async fn get_questions_in_period(&self, period: Period, tz: Tz) -> Result<Vec<Question>, DbError> {
+        let (start, end) = period.bounds(self.clock.now(), tz);
+        sqlx::query_as::<_, Question>(&self.tables.sql("SELECT * FROM questions WHERE created_at >= $1 AND created_at < $2 ORDER BY created_at, id"))
+            .bind(start)
+            .bind(end)
+            .fetch_all(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(DbError::from)
+    }
+
+    async fn get_questions_by_like_range(&self, min: i32, max: Option<i32>, created_after: Option<DateTime<Utc>>) -> Result<Vec<Question>, DbError> {
+        validate_like_range(min, max)?;
+        sqlx::query_as::<_, Question>(&self.tables.sql(
+            "SELECT * FROM questions \
+            WHERE likes >= $1 AND ($2::int IS NULL OR likes <= $2) AND ($3::timestamptz IS NULL OR created_at > $3) \
+            ORDER BY likes DESC, created_at, id"
+        ))
+            .bind(min)
+            .bind(max)
+            .bind(created_after)
+            .fetch_all(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(DbError::from)
+    }
+
+    async fn get_questions_exceeding_like_rate(&self, likes_per_hour: f64, window: Duration) -> Result<Vec<QuestionLikeRate>, DbError> {
+        validate_like_rate(likes_per_hour, window)?;
+        let now = self.clock.now();
+        let window_hours = window.num_milliseconds() as f64 / 3_600_000.0;
+        // The age of a question without events is at least a second, so a question created just now has a rate
+        sqlx::query(&self.tables.sql(
+            "WITH rates AS ( \
+                SELECT questions.*, recorded.question_id IS NULL AS estimated, \
+                    CASE WHEN recorded.question_id IS NULL \
+                        THEN questions.likes::float8 \
+                            / (GREATEST(EXTRACT(EPOCH FROM $2 - questions.created_at), 1) / 3600)::float8 \
+                        ELSE COALESCE(recent.delta, 0)::float8 / $4 \
+                    END AS likes_per_hour \
+                FROM questions \
+                LEFT JOIN ( \
+                    SELECT DISTINCT entity_id AS question_id FROM like_events WHERE entity_type = 'question' \
+                ) recorded ON recorded.question_id = questions.id \
+                LEFT JOIN ( \
+                    SELECT entity_id, SUM(delta) AS delta FROM like_events \
+                    WHERE entity_type = 'question' AND occurred_at > $3 AND occurred_at <= $2 \
+                    GROUP BY entity_id \
+                ) recent ON recent.entity_id = questions.id \
+            ) \
+            SELECT * FROM rates WHERE likes_per_hour > $1 ORDER BY likes_per_hour DESC, created_at, id"
+        ))
+            .bind(likes_per_hour)
+            .bind(now)
+            .bind(now - window)
+            .bind(window_hours)
+            .try_map(|row: PgRow| {
+                Ok(QuestionLikeRate {
+                    question: sqlx::FromRow::from_row(&row)?,
+                    likes_per_hour: row.try_get("likes_per_hour")?,
+                    estimated: row.try_get("estimated")?,
+                })
+            })
+            .fetch_all(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(DbError::from)
+    }
+
+    async fn get_question_header(&self, question_id: EntityId) -> Result<QuestionHeader, DbError> {
+        // Attempt to parse entity id
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        sqlx::query_as::<_, QuestionHeader>(&self.tables.sql(&question_header_query(self.moderation, false)))
+            .bind(question_id)
+            .bind(self.clock.now())
+            .fetch_one(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(missing("question", question_id))
+    }
+
+    async fn get_question_detail_consistent(&self, question_id: EntityId) -> Result<QuestionDetail, DbError> {
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        // The transaction of a scope has already begun, so its isolation level can no longer be raised
+        let snapshot = matches!(self.source, Source::Pool { .. });
+        self.read_question_detail(question_id, snapshot, None, None, std::future::ready(())).await
+    }
+
+    async fn get_question_detail_for_author(&self, question_id: EntityId, author_token: &str) -> Result<QuestionDetail, DbError> {
+        validate_author_token(author_token)?;
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        let snapshot = matches!(self.source, Source::Pool { .. });
+        self.read_question_detail(question_id, snapshot, Some(author_token), None, std::future::ready(())).await
+    }
+
+    async fn get_question_detail_with(&self, question_id: EntityId, options: DetailOptions) -> Result<QuestionDetail, DbError> {
+        options.validate()?;
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        let snapshot = matches!(self.source, Source::Pool { .. });
+        self.read_question_detail(question_id, snapshot, None, Some(options), std::future::ready(())).await
+    }
+
+    async fn get_questions_by_token(&self, author_token: &str) -> Result<Vec<Question>, DbError> {
+        validate_author_token(author_token)?;
+        sqlx::query_as::<_, Question>(&self.tables.sql(
+            "SELECT *, true AS is_mine FROM questions WHERE author_token = $1 ORDER BY created_at DESC, id DESC"
+        ))
+            .bind(author_token)
+            .fetch_all(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(DbError::from)
+    }
+
+    async fn search_questions(&self, query: &str, ranking: SearchRankingConfig, limit: u32) -> Result<Vec<RankedQuestion>, DbError> {
+        if query.trim().is_empty() {
+            return Err(DbError::validation(String::from("search query must not be empty")));
+        }
+        ranking.validate()?;
+        if limit == 0 {
+            return Err(DbError::validation(String::from("limit must be at least 1")));
+        }
+        // The components are computed once per match, then weighted, so the score can be explained from them
+        sqlx::query(&self.tables.sql(&format!(
+            "WITH search AS ( \
+                SELECT plainto_tsquery('english', $1) AS query \
+            ), matches AS ( \
+                SELECT questions.*, \
+                    ts_rank(to_tsvector('english', questions.title || ' ' || questions.question), search.query)::float8 AS text_rank, \
+                    ln(1 + GREATEST(questions.likes, 0)::float8) AS likes_score, \
+                    ln(1 + (SELECT COUNT(*) FROM answers \
+                        WHERE answers.question_id = questions.id AND answers.published AND {})::float8) AS answers_score, \
+                    ln(1 + GREATEST(questions.views, 0)::float8) AS views_score \
+                FROM questions, search \
+                WHERE to_tsvector('english', questions.title || ' ' || questions.question) @@ search.query \
+            ) \
+            SELECT *, text_rank * $2 + likes_score * $3 + answers_score * $4 + views_score * $5 AS score FROM matches \
+            ORDER BY score DESC, created_at, id LIMIT $6",
+            answer_visibility(self.moderation, "$7")
+        )))
+            .bind(query)
+            .bind(ranking.text_weight)
+            .bind(ranking.likes_weight)
+            .bind(ranking.answers_weight)
+            .bind(ranking.views_weight)
+            .bind(i64::from(limit))
+            .bind(self.clock.now())
+            .try_map(|row: PgRow| {
+                let components = RankComponents {
+                    text_rank: row.try_get("text_rank")?,
+                    likes: row.try_get("likes_score")?,
+                    answers: row.try_get("answers_score")?,
+                    views: row.try_get("views_score")?,
+                };
+                Ok(RankedQuestion {
+                    question: sqlx::FromRow::from_row(&row)?,
+                    score: row.try_get("score")?,
+                    components: ranking.debug_explain.then_some(components),
+                })
+            })
+            .fetch_all(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(DbError::from)
+    }
+
+    async fn get_activity_feed(&self, limit: u32, before: Option<ActivityCursor>) -> Result<Vec<ActivityItem>, DbError> {
+        PageRequest { offset: 0, limit }.validate()?;
+        let mut conn = self.source.acquire().await.map_err(DbError::from)?;
+        let mut tx = conn.begin().await.map_err(DbError::from)?;
+        // Each side of the union is limited on its own as well, so neither table is read beyond the page. Among the
+        // items posted at the cursor's time, answers come before questions, so every question follows an answer
+        let feed: Vec<(bool, Uuid)> = sqlx::query_as(&self.tables.sql(&format!(
+            "SELECT is_question, id FROM ( \
+                (SELECT true AS is_question, id, created_at FROM questions \
+                WHERE $1::timestamptz IS NULL OR created_at < $1 OR (created_at = $1 AND (NOT $2 OR id < $3)) \
+                ORDER BY created_at DESC, id DESC LIMIT $4) \
+                UNION ALL \
+                (SELECT false, answers.id, answers.created_at FROM answers JOIN questions ON questions.id = answers.question_id \
+                WHERE answers.published AND answers.deleted_at IS NULL AND {} AND ($1::timestamptz IS NULL OR answers.created_at < $1 \
+                    OR (answers.created_at = $1 AND NOT $2 AND answers.id < $3)) \
+                ORDER BY answers.created_at DESC, answers.id DESC LIMIT $4) \
+            ) feed ORDER BY created_at DESC, is_question, id DESC LIMIT $4",
+            answer_visibility(self.moderation, "$5")
+        )))
+            .bind(before.map(|cursor| cursor.created_at))
+            .bind(before.map(|cursor| cursor.is_question))
+            .bind(before.map(|cursor| cursor.id))
+            .bind(i64::from(limit))
+            .bind(self.clock.now())
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(DbError::from)?;
+        let ids = |kind: bool| feed.iter().filter(|(is_question, _)| *is_question == kind).map(|(_, id)| *id).collect::<Vec<Uuid>>();
+        let (question_ids, answer_ids) = (ids(true), ids(false));
+        let mut questions: HashMap<Uuid, Question> = sqlx::query_as::<_, Question>(&self.tables.sql("SELECT * FROM questions WHERE id = ANY($1)"))
+            .bind(&question_ids)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(DbError::from)?
+            .into_iter()
+            .map(|question| (question.id(), question))
+            .collect();
+        let mut answers: HashMap<Uuid, AnswerWithQuestion> = sqlx::query_as::<_, AnswerWithQuestion>(&self.tables.sql(
+            "SELECT answers.*, questions.title AS question_title FROM answers \
+            JOIN questions ON questions.id = answers.question_id WHERE answers.id = ANY($1)"
+        ))
+            .bind(&answer_ids)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(DbError::from)?
+            .into_iter()
+            .map(|answer| (answer.answer().id(), answer))
+            .collect();
+        tx.commit().await.map_err(DbError::commit)?;
+        // The rows were read in the transaction of the feed, so every item of the feed is found
+        Ok(feed
+            .into_iter()
+            .filter_map(|(is_question, id)| match is_question {
+                true => questions.remove(&id).map(ActivityItem::from),
+                false => answers.remove(&id).and_then(|answer| ActivityItem::try_from(answer).ok()),
+            })
+            .collect())
+    }
+
+    async fn update_question(&self, question_id: EntityId, update: UpdateQuestion) -> Result<Question, DbError> {
+        // Validate before touching the database
+        update.validate(&self.limits)?;
+        let flag = self.check_policy(update.title.as_deref().unwrap_or_default(), update.question.as_deref().unwrap_or_default())?;
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        let mut conn = self.source.acquire().await.map_err(DbError::from)?;
+        let mut tx = conn.begin().await.map_err(DbError::from)?;
+        // Lock the row so the question cannot be locked between the check and the edit
+        if lock_state(&mut tx, &self.tables, question_id).await?.is_some() {
+            return Err(ConflictReason::Locked(question_id).into());
+        }
+        let stats = update.question.as_deref().map(ContentStats::of);
+        let now = self.clock.now();
+        let question = sqlx::query_as::<_, Question>(&self.tables.sql(
+            "UPDATE questions SET title = COALESCE($1, title), question = COALESCE($2, question), updated_at = $3, \
+            title_normalized = COALESCE($5, title_normalized), char_count = COALESCE($6, char_count), \
+            word_count = COALESCE($7, word_count) WHERE id = $4 RETURNING *"
+        ))
+            .bind(update.title.as_deref())
+            .bind(update.question.as_deref())
+            .bind(now)
+            .bind(question_id)
+            .bind(update.title.as_deref().map(normalize_title))
+            .bind(stats.map(|stats| stats.char_count))
+            .bind(stats.map(|stats| stats.word_count))
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(edit_error)?;
+        if let Some(reason) = flag {
+            insert_flag(&mut tx, &self.tables, LikableEntity::Question, question.id(), reason, now).await?;
+        }
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(question)
+    }
+
+    async fn lock_question(&self, question_id: EntityId) -> Result<Question, DbError> {
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        sqlx::query_as::<_, Question>(&self.tables.sql("UPDATE questions SET locked_at = COALESCE(locked_at, $1) WHERE id = $2 RETURNING *"))
+            .bind(self.clock.now())
+            .bind(question_id)
+            .fetch_one(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(DbError::update)
+    }
+
+    async fn unlock_question(&self, question_id: EntityId) -> Result<Question, DbError> {
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        sqlx::query_as::<_, Question>(&self.tables.sql("UPDATE questions SET locked_at = NULL WHERE id = $1 RETURNING *"))
+            .bind(question_id)
+            .fetch_one(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(DbError::update)
+    }
+
+    async fn pin_question(&self, question_id: EntityId) -> Result<Question, DbError> {
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        let mut conn = self.source.acquire().await.map_err(DbError::from)?;
+        let mut tx = conn.begin().await.map_err(DbError::from)?;
+        // Serialize pinning, so concurrent pins cannot each see room for one more question
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext('questions.pinned_at'))")
+            .execute(&mut *tx)
+            .await
+            .map_err(DbError::from)?;
+        let (pinned, already_pinned): (i64, bool) = sqlx::query_as(&self.tables.sql(
+            "SELECT COUNT(*), COALESCE(bool_or(id = $1), false) FROM questions WHERE pinned_at IS NOT NULL"
+        ))
+            .bind(question_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(DbError::from)?;
+        if !already_pinned && pinned >= i64::from(self.max_pinned) {
+            return Err(ValidationReason::LimitExceeded { limit: self.max_pinned }.into());
+        }
+        let question = sqlx::query_as::<_, Question>(&self.tables.sql(
+            "UPDATE questions SET pinned_at = COALESCE(pinned_at, $1) WHERE id = $2 RETURNING *"
+        ))
+            .bind(self.clock.now())
+            .bind(question_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(DbError::update)?;
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(question)
+    }
+
+    async fn unpin_question(&self, question_id: EntityId) -> Result<Question, DbError> {
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        sqlx::query_as::<_, Question>(&self.tables.sql("UPDATE questions SET pinned_at = NULL WHERE id = $1 RETURNING *"))
+            .bind(question_id)
+            .fetch_one(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(DbError::update)
+    }
+
+    async fn pin_answer(&self, question_id: EntityId, answer_id: EntityId) -> Result<Question, DbError> {
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        let answer_id = self.source.resolve_id(&self.tables, "answers", answer_id).await?;
+        let mut conn = self.source.acquire().await.map_err(DbError::from)?;
+        let mut tx = conn.begin().await.map_err(DbError::from)?;
+        check_answer_of(&mut tx, &self.tables, question_id, answer_id).await?;
+        let question = sqlx::query_as::<_, Question>(&self.tables.sql("UPDATE questions SET pinned_answer_id = $1 WHERE id = $2 RETURNING *"))
+            .bind(answer_id)
+            .bind(question_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(DbError::update)?;
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(question)
+    }
+
+    async fn unpin_answer(&self, question_id: EntityId) -> Result<Question, DbError> {
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        sqlx::query_as::<_, Question>(&self.tables.sql("UPDATE questions SET pinned_answer_id = NULL WHERE id = $1 RETURNING *"))
+            .bind(question_id)
+            .fetch_one(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(DbError::update)
+    }
+
+    async fn accept_answer(&self, question_id: EntityId, answer_id: EntityId) -> Result<Question, DbError> {
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        let answer_id = self.source.resolve_id(&self.tables, "answers", answer_id).await?;
+        let mut conn = self.source.acquire().await.map_err(DbError::from)?;
+        let mut tx = conn.begin().await.map_err(DbError::from)?;
+        check_answer_of(&mut tx, &self.tables, question_id, answer_id).await?;
+        let previous = accepted_answer(&mut tx, &self.tables, question_id).await?;
+        clear_archived_acceptance(&mut tx, &self.tables, question_id, self.reputation.answer_accepted).await?;
+        let question = sqlx::query_as::<_, Question>(&self.tables.sql("UPDATE questions SET accepted_answer_id = $1 WHERE id = $2 RETURNING *"))
+            .bind(answer_id)
+            .bind(question_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(DbError::update)?;
+        // Accepting another answer moves the reward from the previously accepted answer
+        if previous != Some(answer_id) {
+            if let Some(previous) = previous {
+                adjust_reputation(&mut tx, &self.tables, "answers", previous, -self.reputation.answer_accepted).await?;
+            }
+            adjust_reputation(&mut tx, &self.tables, "answers", answer_id, self.reputation.answer_accepted).await?;
+        }
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(question)
+    }
+
+    async fn unaccept_answer(&self, question_id: EntityId) -> Result<Question, DbError> {
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        let mut conn = self.source.acquire().await.map_err(DbError::from)?;
+        let mut tx = conn.begin().await.map_err(DbError::from)?;
+        let previous = accepted_answer(&mut tx, &self.tables, question_id).await?;
+        clear_archived_acceptance(&mut tx, &self.tables, question_id, self.reputation.answer_accepted).await?;
+        let question = sqlx::query_as::<_, Question>(&self.tables.sql("UPDATE questions SET accepted_answer_id = NULL WHERE id = $1 RETURNING *"))
+            .bind(question_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(DbError::update)?;
+        if let Some(previous) = previous {
+            adjust_reputation(&mut tx, &self.tables, "answers", previous, -self.reputation.answer_accepted).await?;
+        }
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(question)
+    }
+
+    async fn set_bounty(&self, question_id: EntityId, amount: i32, duration: Duration) -> Result<Question, DbError> {
+        validate_bounty(amount, duration)?;
+        let expires_at = self.clock.now()
+            .checked_add_signed(duration)
+            .ok_or_else(|| DbError::validation(String::from("bounty duration is too long")))?;
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        // Locking only freezes edits, a locked question is still answered and so can still carry a bounty
+        sqlx::query_as::<_, Question>(&self.tables.sql(
+            "UPDATE questions SET bounty_amount = $1, bounty_expires_at = $2 WHERE id = $3 RETURNING *"
+        ))
+            .bind(amount)
+            .bind(expires_at)
+            .bind(question_id)
+            .fetch_one(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(DbError::update)
+    }
+
+    async fn clear_bounty(&self, question_id: EntityId) -> Result<Question, DbError> {
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        sqlx::query_as::<_, Question>(&self.tables.sql(
+            "UPDATE questions SET bounty_amount = NULL, bounty_expires_at = NULL WHERE id = $1 RETURNING *"
+        ))
+            .bind(question_id)
+            .fetch_one(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(DbError::update)
+    }
+
+    async fn upsert_translation(&self, question_id: EntityId, lang: &str, content: NewTranslation) -> Result<QuestionTranslation, DbError> {
+        let lang = normalize_language(lang)?;
+        content.validate(&self.limits)?;
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        let mut conn = self.source.acquire().await.map_err(DbError::from)?;
+        let mut tx = conn.begin().await.map_err(DbError::from)?;
+        // Translations are edits of the content, so they are rejected while the question is locked
+        if lock_state(&mut tx, &self.tables, question_id).await?.is_some() {
+            return Err(ConflictReason::Locked(question_id).into());
+        }
+        let translation = sqlx::query_as::<_, QuestionTranslation>(&self.tables.sql(
+            "INSERT INTO question_translations (question_id, lang, title, question, updated_at) VALUES ($1, $2, $3, $4, $5) \
+            ON CONFLICT (question_id, lang) DO UPDATE SET title = EXCLUDED.title, question = EXCLUDED.question, updated_at = EXCLUDED.updated_at \
+            RETURNING *"
+        ))
+            .bind(question_id)
+            .bind(lang)
+            .bind(content.title)
+            .bind(content.question)
+            .bind(self.clock.now())
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(creation_error)?;
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(translation)
+    }
+
+    async fn get_translation(&self, question_id: EntityId, lang: &str) -> Result<Question, DbError> {
+        let lang = normalize_language(lang)?;
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        sqlx::query(&self.tables.sql(
+            "SELECT questions.*, translation.title AS translation_title, translation.question AS translation_question, \
+                translation.updated_at AS translation_updated_at \
+            FROM questions LEFT JOIN question_translations translation \
+                ON translation.question_id = questions.id AND translation.lang = $2 \
+            WHERE questions.id = $1"
+        ))
+            .bind(question_id)
+            .bind(lang)
+            .try_map(|row: PgRow| {
+                let question: Question = sqlx::FromRow::from_row(&row)?;
+                let Some(title) = row.try_get("translation_title")? else {
+                    return Ok(question);
+                };
+                Ok(question.with_translation(QuestionTranslation::new(
+                    question_id,
+                    lang.to_string(),
+                    title,
+                    row.try_get("translation_question")?,
+                    row.try_get("translation_updated_at")?,
+                )))
+            })
+            .fetch_one(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(missing("question", question_id))
+    }
+
+    async fn link_questions(&self, from_id: EntityId, to_id: EntityId, kind: LinkKind) -> Result<(), DbError> {
+        let from_id = self.source.resolve_id(&self.tables, "questions", from_id).await?;
+        let to_id = self.source.resolve_id(&self.tables, "questions", to_id).await?;
+        if from_id == to_id {
+            return Err(DbError::validation(format!("question {from_id} cannot be linked to itself")));
+        }
+        sqlx::query(&self.tables.sql("INSERT INTO question_links (from_id, to_id, kind, created_at) VALUES ($1, $2, $3, $4)"))
+            .bind(from_id)
+            .bind(to_id)
+            .bind(kind)
+            .bind(self.clock.now())
+            .execute(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map(|_| ())
+            .map_err(link_error)
+    }
+
+    async fn unlink_questions(&self, from_id: EntityId, to_id: EntityId, kind: LinkKind) -> Result<bool, DbError> {
+        let from_id = self.source.resolve_id(&self.tables, "questions", from_id).await?;
+        let to_id = self.source.resolve_id(&self.tables, "questions", to_id).await?;
+        sqlx::query(&self.tables.sql(
+            "DELETE FROM question_links WHERE kind = $3 \
+            AND ((from_id = $1 AND to_id = $2) OR ($4 AND from_id = $2 AND to_id = $1))"
+        ))
+            .bind(from_id)
+            .bind(to_id)
+            .bind(kind)
+            .bind(kind.is_symmetric())
+            .execute(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map(|res| res.rows_affected() > 0)
+            .map_err(DbError::deletion)
+    }
+
+    async fn get_linked_questions(&self, question_id: EntityId) -> Result<Vec<(LinkKind, Question)>, DbError> {
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        let mut conn = self.source.acquire().await.map_err(DbError::from)?;
+        sqlx::query(&self.tables.sql("SELECT id FROM questions WHERE id = $1"))
+            .bind(question_id)
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(missing("question", question_id))?;
+        sqlx::query(&self.tables.sql(
+            "SELECT question_links.kind AS link_kind, questions.* FROM question_links \
+            JOIN questions ON questions.id = CASE WHEN question_links.from_id = $1 THEN question_links.to_id ELSE question_links.from_id END \
+            WHERE question_links.from_id = $1 OR (question_links.to_id = $1 AND question_links.kind = 'related') \
+            ORDER BY question_links.created_at, questions.id"
+        ))
+            .bind(question_id)
+            .try_map(|row: PgRow| Ok((row.try_get::<LinkKind, _>("link_kind")?, sqlx::FromRow::from_row(&row)?)))
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(DbError::from)
+    }
+
+    async fn delete_question(&self, question_id: EntityId, force: bool) -> Result<Uuid, DbError> {
+        self.delete_question_as(question_id, self.delete_policy, force).await
+    }
+
+    async fn delete_question_with_policy(&self, question_id: EntityId, policy: DeletePolicy) -> Result<Uuid, DbError> {
+        self.delete_question_as(question_id, policy, false).await
+    }
+
+    async fn increment_question_likes(&self, question_id: EntityId) -> Result<i64, DbError> {
+        // Attempt to parse entity id
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        // Ensure that both transactions occur by using a Transaction
+        let mut conn = self.source.acquire().await.map_err(DbError::from)?;
+        let mut tx = conn.begin().await.map_err(DbError::from)?;
+        // Record the like and reward the author in the same statement as the increment, so neither can be skipped
+        let likes = sqlx::query(&self.tables.sql(
+            "WITH incremented AS ( \
+                UPDATE questions SET likes = likes + 1, last_activity_at = $2 WHERE id = $1 RETURNING id, likes, author_id \
+            ), recorded AS ( \
+                INSERT INTO like_events (entity_type, entity_id, delta, occurred_at) \
+                SELECT 'question', id, 1, $2 FROM incremented \
+            ), rewarded AS ( \
+                UPDATE users SET reputation = reputation + $3 FROM incremented WHERE users.id = incremented.author_id \
+            ) \
+            SELECT likes FROM incremented"
+        ))
+            .bind(question_id)
+            .bind(self.clock.now())
+            .bind(self.reputation.question_liked)
+            .try_map(|row: PgRow| row.try_get::<i64, &str>("likes"))
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(edit_error)?
+            .ok_or_else(|| DbError::not_found("question", question_id))?;
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(likes)
+    }
+
+    async fn record_view(&self, question_id: EntityId, client_token: &str) -> Result<ViewOutcome, DbError> {
+        // Attempt to parse entity id
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        let now = self.clock.now();
+        // The view is only stored again once the client's last counted view falls outside the window, and the
+        // counter is only incremented when it was, all in one statement so concurrent views cannot both count
+        let row = sqlx::query(&self.tables.sql(
+            "WITH question AS ( \
+                SELECT id FROM questions WHERE id = $1 \
+            ), viewed AS ( \
+                INSERT INTO question_views (question_id, client_token, viewed_at) \
+                SELECT id, $2, $3 FROM question \
+                ON CONFLICT (question_id, client_token) DO UPDATE SET viewed_at = EXCLUDED.viewed_at \
+                WHERE question_views.viewed_at <= $4 \
+                RETURNING question_id \
+            ), counted AS ( \
+                UPDATE questions SET views = views + 1 WHERE id IN (SELECT question_id FROM viewed) RETURNING id \
+            ) \
+            SELECT EXISTS (SELECT 1 FROM question) AS found, EXISTS (SELECT 1 FROM counted) AS counted"
+        ))
+            .bind(question_id)
+            .bind(client_token)
+            .bind(now)
+            .bind(now - self.view_window)
+            .fetch_one(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(edit_error)?;
+        let found: bool = row.try_get("found").map_err(DbError::from)?;
+        let counted: bool = row.try_get("counted").map_err(DbError::from)?;
+        match (found, counted) {
+            (false, _) => Err(DbError::not_found("question", question_id)),
+            (true, true) => Ok(ViewOutcome::Counted),
+            (true, false) => Ok(ViewOutcome::Repeated),
+        }
+    }
+
+    async fn create_question_idempotent(&self, new_question: NewQuestion, key: &str) -> Result<CreateOutcome, DbError> {
+        // Validate before touching the database
+        new_question.validate(&self.limits)?;
+        let flag = self.check_policy(&new_question.title, &new_question.question)?;
+        // The request hash is computed by the database so it remains stable across builds
+        const REQUEST_HASH: &str = "md5(json_build_array($2::text, $3::text)::text)";
+        let mut conn = self.source.acquire().await.map_err(DbError::from)?;
+        let stats = ContentStats::of(&new_question.question);
+        let content_type = new_question.content_type.unwrap_or_default();
+        let max = self.quota.map(|quota| quota.max_questions_per_hour);
+        loop {
+            let mut tx = conn.begin().await.map_err(DbError::from)?;
+            let now = self.clock.now();
+            // A retry of a request that already created its question is still answered once the quota is reached
+            let exceeded = match check_quota(&mut tx, &self.tables, "questions", new_question.author_token.as_deref(), max, now).await {
+                Ok(()) => None,
+                Err(e @ DbError::Unavailable { reason: UnavailableReason::QuotaExceeded { .. }, .. }) => Some(e),
+                Err(e) => return Err(e),
+            };
+            if exceeded.is_none() {
+                let id: Uuid = sqlx::query_scalar(&self.tables.sql(
+                    "INSERT INTO questions (title, question, created_at, external_id, title_normalized, char_count, word_count, content_type, author_token) \
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) RETURNING id"
+                ))
+                    .bind(&new_question.title)
+                    .bind(&new_question.question)
+                    .bind(now)
+                    .bind(&new_question.external_id)
+                    .bind(normalize_title(&new_question.title))
+                    .bind(stats.char_count)
+                    .bind(stats.word_count)
+                    .bind(&content_type)
+                    .bind(&new_question.author_token)
+                    .fetch_one(&mut *tx)
+                    .await
+                    .map_err(creation_error)?;
+                // Claim the key, this waits on any concurrent request holding the same key
+                let claimed = sqlx::query(&self.tables.sql(&format!(
+                    "INSERT INTO idempotency_keys (key, entity_id, request_hash, created_at) VALUES ($1, $4, {REQUEST_HASH}, $5) ON CONFLICT (key) DO NOTHING"
+                )))
+                    .bind(key)
+                    .bind(&new_question.title)
+                    .bind(&new_question.question)
+                    .bind(id)
+                    .bind(now)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(creation_error)?
+                    .rows_affected() == 1;
+                if claimed {
+                    if let Some(reason) = flag {
+                        insert_flag(&mut tx, &self.tables, LikableEntity::Question, id, reason, now).await?;
+                    }
+                    tx.commit().await.map_err(DbError::commit)?;
+                    return Ok(CreateOutcome::Created(id));
+                }
+            }
+            // The key has been used before or the quota was reached, so discard the new question and report the original
+            tx.rollback().await.map_err(DbError::from)?;
+            let existing: Option<(Uuid, bool)> = sqlx::query_as(&self.tables.sql(&format!(
+                "SELECT entity_id, request_hash = {REQUEST_HASH} FROM idempotency_keys WHERE key = $1"
+            )))
+                .bind(key)
+                .bind(&new_question.title)
+                .bind(&new_question.question)
+                .fetch_optional(&mut *conn)
+                .await
+                .map_err(DbError::from)?;
+            match existing {
+                Some((existing_id, true)) => return Ok(CreateOutcome::AlreadyCreated(existing_id)),
+                Some((_, false)) => {
+                    return Err(DbError::conflict(format!("idempotency key {key} was already used for a different question")));
+                }
+                None => match exceeded {
+                    Some(e) => return Err(e),
+                    // The key was purged since the conflict, so it is free to claim again
+                    None => continue,
+                },
+            }
+        }
+    }
+
+    async fn upsert_question_by_external_id(&self, new_question: NewQuestion) -> Result<UpsertOutcome, DbError> {
+        if new_question.external_id.is_none() {
+            // Created without observing it again, the upsert itself is observed
+            let created = <Self as question_calls::Calls>::create_question(self, new_question).await;
+            return created.map(|question| UpsertOutcome::Created(question.id()));
+        }
+        // Validate before touching the database
+        new_question.validate(&self.limits)?;
+        let flag = self.check_policy(&new_question.title, &new_question.question)?;
+        let now = self.clock.now();
+        let mut conn = self.source.acquire().await.map_err(DbError::from)?;
+        let mut tx = conn.begin().await.map_err(DbError::from)?;
+        // Only creations count towards the quota, updates of questions synced before are not limited
+        let exists: bool = sqlx::query_scalar(&self.tables.sql("SELECT EXISTS (SELECT 1 FROM questions WHERE external_id = $1)"))
+            .bind(&new_question.external_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(DbError::from)?;
+        if !exists {
+            let max = self.quota.map(|quota| quota.max_questions_per_hour);
+            check_quota(&mut tx, &self.tables, "questions", new_question.author_token.as_deref(), max, now).await?;
+        }
+        // Only unlocked questions whose content differs are updated, xmax is zero for newly inserted rows
+        let stats = ContentStats::of(&new_question.question);
+        let upserted: Option<(Uuid, bool)> = sqlx::query_as(&self.tables.sql(
+            "INSERT INTO questions (title, question, created_at, external_id, title_normalized, char_count, word_count, content_type, author_token) \
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) \
+            ON CONFLICT (external_id) DO UPDATE SET title = EXCLUDED.title, question = EXCLUDED.question, updated_at = $3, \
+                title_normalized = EXCLUDED.title_normalized, char_count = EXCLUDED.char_count, word_count = EXCLUDED.word_count, \
+                content_type = EXCLUDED.content_type \
+            WHERE questions.locked_at IS NULL \
+                AND (questions.title, questions.question, questions.content_type) \
+                    IS DISTINCT FROM (EXCLUDED.title, EXCLUDED.question, EXCLUDED.content_type) \
+            RETURNING id, (xmax = 0) AS inserted"
+        ))
+            .bind(&new_question.title)
+            .bind(&new_question.question)
+            .bind(now)
+            .bind(&new_question.external_id)
+            .bind(normalize_title(&new_question.title))
+            .bind(stats.char_count)
+            .bind(stats.word_count)
+            .bind(new_question.content_type.unwrap_or_default())
+            .bind(&new_question.author_token)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(creation_error)?;
+        let outcome = match upserted {
+            Some((id, true)) => UpsertOutcome::Created(id),
+            Some((id, false)) => UpsertOutcome::Updated(id),
+            None => {
+                // The conflicting row was left untouched, either because it is locked or because nothing changed
+                let (id, changed): (Uuid, bool) = sqlx::query_as(&self.tables.sql(
+                    "SELECT id, (title, question) IS DISTINCT FROM ($2, $3) FROM questions WHERE external_id = $1"
+                ))
+                    .bind(&new_question.external_id)
+                    .bind(&new_question.title)
+                    .bind(&new_question.question)
+                    .fetch_one(&mut *tx)
+                    .await
+                    .map_err(DbError::from)?;
+                if changed {
+                    return Err(ConflictReason::Locked(id).into());
+                }
+                UpsertOutcome::Unchanged(id)
+            }
+        };
+        // Unchanged questions were already checked when their content was stored
+        if let (Some(reason), UpsertOutcome::Created(id) | UpsertOutcome::Updated(id)) = (flag, outcome) {
+            insert_flag(&mut tx, &self.tables, LikableEntity::Question, id, reason, now).await?;
+        }
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(outcome)
+    }
+
+    async fn purge_idempotency_keys(&self, ttl: Duration) -> Result<u64, DbError> {
+        let cutoff = self.clock.now() - ttl;
+        sqlx::query(&self.tables.sql("DELETE FROM idempotency_keys WHERE created_at < $1"))
+            .bind(cutoff)
+            .execute(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map(|res| res.rows_affected())
+            .map_err(DbError::deletion)
+    }
+
+    async fn expire_bounties(&self) -> Result<Vec<Uuid>, DbError> {
+        sqlx::query_scalar(&self.tables.sql(
+            "UPDATE questions SET bounty_amount = NULL, bounty_expires_at = NULL WHERE bounty_expires_at <= $1 RETURNING id"
+        ))
+            .bind(self.clock.now())
+            .fetch_all(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(DbError::update)
+    }
+
+    async fn delete_questions_batched(
+        &self,
+        ids: Vec<EntityId>,
+        batch_size: usize,
+        force: bool,
+        mut progress: Option<&mut dyn FnMut(BatchProgress)>
+    ) -> Result<u64, DbError> {
+        // Resolve every entity id up front, so an invalid id aborts before anything is deleted
+        let mut uuids = Vec::with_capacity(ids.len());
+        for id in ids {
+            uuids.push(self.source.resolve_id(&self.tables, "questions", id).await?);
+        }
+        let ids = uuids;
+        let batch_size = batch_size.max(1);
+        let total_batches = ids.len().div_ceil(batch_size);
+        let mut deleted = 0;
+        for (batch, chunk) in ids.chunks(batch_size).enumerate() {
+            let affected_in_batch = self.delete_question_batch(chunk, self.delete_policy, force)
+                .await
+                .map_err(|e| e.stopped_after(deleted))?;
+            deleted += affected_in_batch;
+            if let Some(callback) = progress.as_deref_mut() {
+                callback(BatchProgress { batch, total_batches, affected_in_batch, affected_total: deleted });
+            }
+            // Give other tasks a chance to run between batches
+            tokio::task::yield_now().await;
+        }
+        Ok(deleted)
+    }
+}
+
+/// An `AnswerDao` backed by a Postgres connection pool, or by the transaction of a `ScopedDao`.
+pub struct AnswerDaoImpl {
+    source: Source,
+    tables: Tables,
+    limits: ContentLimits,
+    clock: Arc<dyn Clock>,
+    moderation: ModerationMode,
+    policy: Option<Arc<dyn ContentPolicy>>,
+    quota: Option<CreationQuota>,
+    reputation: ReputationConfig,
+    observer: Option<Arc<dyn QueryObserver>>,
+}
+
+impl AnswerDaoImpl {
+    /// Creates the data access object, using the system clock, the default `ContentLimits`, with moderation off and
+    /// without a `ContentPolicy`.
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            source: Source::Pool { pool, waits: None },
+            tables: Tables::default(),
+            limits: ContentLimits::default(),
+            clock: Arc::new(SystemClock),
+            moderation: ModerationMode::default(),
+            policy: None,
+            quota: None,
+            reputation: ReputationConfig::default(),
+            observer: None,
+        }
+    }
+
+    /// Sets the `Clock` used to timestamp new answers and compute time based cutoffs.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Sets the `ContentLimits` new answers are validated against.
+    pub fn with_limits(mut self, limits: ContentLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Places the tables the data access object reads and writes in `schema`, which should have been migrated with
+    /// `migrations::run_in_schema`.
+    pub fn with_schema(mut self, schema: SchemaName) -> Self {
+        self.tables = Tables::in_schema(schema);
+        self
+    }
+
+    /// Records how long each call waited to acquire a connection from the pool in `waits`, such as those reported by
+    /// the `PoolMonitor` of the pool, see `PoolMonitor::acquire_waits`.
+    pub fn with_acquire_waits(mut self, waits: Arc<AcquireWaits>) -> Self {
+        if let Source::Pool { waits: current, .. } = &mut self.source {
+            *current = Some(waits);
+        }
+        self
+    }
+
+    /// Fails every connection the data access object acquires with `error`, so tests of failure paths get the same
+    /// error on every sqlx version, without closing the pool.
+    #[cfg(test)]
+    pub(crate) fn with_failing_source(mut self, error: fn() -> sqlx::Error) -> Self {
+        self.source = Source::Failing(error);
+        self
+    }
+
+    /// Sets the `QueryObserver` notified of every call, see `observer`.
+    pub fn with_observer(mut self, observer: Arc<dyn QueryObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Sets the `ModerationMode` deciding when published answers become visible in listings.
+    pub fn with_moderation(mut self, moderation: ModerationMode) -> Self {
+        self.moderation = moderation;
+        self
+    }
+
+    /// Sets the `ContentPolicy` new answers are checked against once validated. Rejected answers are reported as
+    /// `ValidationReason::PolicyViolation`, while flagged answers are stored along with a moderation flag.
+    pub fn with_policy(mut self, policy: Arc<dyn ContentPolicy>) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Sets the `CreationQuota` limiting the answers, drafts included, created with each author token, of which only
+    /// `max_answers_per_hour` applies. Quotas are not enforced by default.
+    pub fn with_quota(mut self, quota: CreationQuota) -> Self {
+        self.quota = Some(quota);
+        self
+    }
+
+    /// Sets the `ReputationConfig` deciding the reputation authors gain as their answers are liked.
+    pub fn with_reputation(mut self, reputation: ReputationConfig) -> Self {
+        self.reputation = reputation;
+        self
+    }
+
+    /// The approval time of answers published now, which are approved right away only when moderation is off.
+    fn approval_on_publish(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        (self.moderation == ModerationMode::Off).then_some(now)
+    }
+
+    /// Lists visible answers in the default `AnswerSort` order, either those of a single question or all of them.
+    /// Transport errors are classified by `From<sqlx::Error>` and rows that fail to decode reported as
+    /// `InternalReason::Decode`.
+    async fn list_answers(&self, question_id: Option<Uuid>) -> Result<Vec<Answer>, DbError> {
+        sqlx::query_as::<_, Answer>(&self.tables.sql(&answer_listing_query(self.moderation, false)))
+            .bind(question_id)
+            .bind(self.clock.now())
+            .fetch_all(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(DbError::from)
+    }
+
+    /// Validates and inserts a new answer, either published or as a draft.
+    async fn insert_answer(&self, new_answer: NewAnswer, published: bool) -> Result<Answer, DbError> {
+        // First validate the content and parse question_id
+        new_answer.validate(&self.limits)?;
+        let flag = check_policy(self.policy.as_deref(), &[(ContentKind::Answer, &new_answer.answer)])?;
+        let question_id = self.source.resolve_id(&self.tables, "questions", EntityId::new(new_answer.question_id)).await?;
+        let author_id: Option<Uuid> = new_answer.author_id
+            .map(|id| EntityId::new(id).try_into())
+            .transpose()
+            .map_err(DbError::invalid_id)?;
+        let parent_answer_id = match new_answer.parent_answer_id {
+            Some(id) => Some(self.source.resolve_id(&self.tables, "answers", EntityId::new(id)).await?),
+            None => None,
+        };
+        // Get a transaction
+        let mut conn = self.source.acquire().await.map_err(DbError::from)?;
+        let mut tx = conn.begin().await.map_err(DbError::from)?;
+        // Ensure that the associated question actually exists, and keep it from being deleted until the answer is
+        // committed. A concurrent deletion holding the row lock is waited for, after which the question is not found,
+        // while a deletion starting after this waits for the answer and then removes it along with the question
+        sqlx::query(&self.tables.sql("SELECT id FROM questions WHERE id = $1 FOR KEY SHARE"))
+            .bind(question_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(missing("question", question_id))?;
+        // If we make it to this line, we know the associated question exists in the database
+        if let Some(parent_answer_id) = parent_answer_id {
+            // Keep the parent from being deleted until the reply is committed, as with the question
+            let (parent_question_id, grandparent_id): (Option<Uuid>, Option<Uuid>) = sqlx::query_as(&self.tables.sql(
+                "SELECT question_id, parent_answer_id FROM answers WHERE id = $1 FOR KEY SHARE"
+            ))
+                .bind(parent_answer_id)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(missing("answer", parent_answer_id))?;
+            if parent_question_id != Some(question_id) {
+                return Err(DbError::validation(format!("answer {parent_answer_id} does not answer question {question_id}")));
+            }
+            if grandparent_id.is_some() {
+                return Err(ValidationReason::NestedReply(parent_answer_id).into());
+            }
+        }
+        let now = self.clock.now();
+        let max = self.quota.map(|quota| quota.max_answers_per_hour);
+        check_quota(&mut tx, &self.tables, "answers", new_answer.author_token.as_deref(), max, now).await?;
+        let stats = ContentStats::of(&new_answer.answer);
+        // Published answers are activity on their question, drafts only once they are published
+        match sqlx::query_as::<_, Answer>(&self.tables.sql(
+            "WITH inserted AS ( \
+                INSERT INTO answers \
+                    (question_id, answer, author_id, created_at, published, char_count, word_count, approved_at, content_type, \
+                    author_token, parent_answer_id) \
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) RETURNING * \
+            ), touched AS ( \
+                UPDATE questions SET last_activity_at = $4 WHERE id = $1 AND $5 \
+            ) \
+            SELECT * FROM inserted"
+        ))
+            .bind(question_id)
+            .bind(new_answer.answer)
+            .bind(author_id)
+            .bind(now)
+            .bind(published)
+            .bind(stats.char_count)
+            .bind(stats.word_count)
+            .bind(self.approval_on_publish(now).filter(|_| published))
+            .bind(new_answer.content_type.unwrap_or_default())
+            .bind(new_answer.author_token)
+            .bind(parent_answer_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(creation_error)
+        {
+            Ok(answer) => {
+                if let Some(reason) = flag {
+                    insert_flag(&mut tx, &self.tables, LikableEntity::Answer, answer.id(), reason, now).await?;
+                }
+                // commit the transaction
+                tx.commit().await.map_err(DbError::from)?;
+                Ok(answer)
+            }
+            Err(e) => Err(e)
+        }
+    }
+}
+
+observed_dao! {
+    AnswerDao for AnswerDaoImpl via answer_calls as EntityKind::Answer;
+    create_answer(new_answer: NewAnswer) -> Answer;
+    create_answer_draft(new_answer: NewAnswer) -> Answer;
+    publish_answer(answer_id: EntityId) -> Answer, id: answer_id;
+    get_drafts(question_id: EntityId, author_id: EntityId) -> Vec<Answer>, id: question_id;
+    get_answer(answer_id: EntityId) -> Answer, id: answer_id;
+    get_answers(question_id: EntityId) -> Vec<Answer>, id: question_id;
+    get_answer_threads(question_id: EntityId) -> Vec<AnswerThread>, id: question_id;
+    get_answers_sorted(question_id: EntityId, sort: AnswerSort, limit: u32) -> Vec<Answer>, id: question_id;
+    get_answers_paged(question_id: EntityId, limit: u32, page_token: Option<&str>) -> AnswerPage, id: question_id;
+    get_answers_with_authors(question_id: EntityId) -> Vec<AnswerWithAuthor>, id: question_id;
+    get_answers_by_token(author_token: &str) -> Vec<Answer>;
+    search_answers(question_id: EntityId, term: &str) -> Vec<Answer>, id: question_id;
+    search_all_answers(term: &str, limit: u32) -> Vec<AnswerWithQuestion>;
+    get_all_answers() -> Vec<Answer>;
+    get_all_answers_with_question(page: PageRequest) -> Page<AnswerWithQuestion>;
+    delete_answer(answer_id: EntityId) -> Uuid, id: answer_id;
+    delete_answer_with_tombstone(answer_id: EntityId, reason: Option<String>) -> Answer, id: answer_id;
+    increment_answer_likes(answer_id: EntityId) -> i64, id: answer_id;
+    vote_answer(answer_id: EntityId, vote: AnswerVote, user_token: &str) -> VoteOutcome, id: answer_id;
+    approve_answer(answer_id: EntityId) -> Answer, id: answer_id;
+    reject_answer(answer_id: EntityId) -> Uuid, id: answer_id;
+    archive_stale_answers(inactive_for: Duration, batch: usize) -> ArchiveReport;
+}
+
+impl answer_calls::Calls for AnswerDaoImpl {
+    async fn create_answer(&self, new_answer: NewAnswer) -> Result<Answer, DbError> {
+        self.insert_answer(new_answer, true).await
+    }
+
+    async fn create_answer_draft(&self, new_answer: NewAnswer) -> Result<Answer, DbError> {
+        self.insert_answer(new_answer, false).await
+    }
+
+    async fn publish_answer(&self, answer_id: EntityId) -> Result<Answer, DbError> {
+        // Parse entity id
+        let answer_id = self.source.resolve_id(&self.tables, "answers", answer_id).await?;
+        // Only drafts are updated, so publishing twice leaves the answer untouched
+        let now = self.clock.now();
+        let published = sqlx::query_as::<_, Answer>(&self.tables.sql(
+            "WITH published AS ( \
+                UPDATE answers SET published = true, created_at = $2, approved_at = COALESCE(approved_at, $3) \
+                WHERE id = $1 AND NOT published RETURNING * \
+            ), touched AS ( \
+                UPDATE questions SET last_activity_at = $2 WHERE id IN (SELECT question_id FROM published) \
+            ) \
+            SELECT * FROM published"
+        ))
+            .bind(answer_id)
+            .bind(now)
+            .bind(self.approval_on_publish(now))
+            .fetch_optional(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(DbError::update)?;
+        match published {
+            Some(answer) => Ok(answer),
+            None => sqlx::query_as::<_, Answer>(&self.tables.sql("SELECT * FROM answers WHERE id = $1"))
+                .bind(answer_id)
+                .fetch_one(&mut *self.source.acquire().await.map_err(DbError::from)?)
+                .await
+                .map_err(missing("answer", answer_id)),
+        }
+    }
+
+    async fn get_drafts(&self, question_id: EntityId, author_id: EntityId) -> Result<Vec<Answer>, DbError> {
+        // Parse entity ids first
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        let author_id = Uuid::try_from(&author_id).map_err(DbError::invalid_id)?;
+        sqlx::query_as::<_, Answer>(&self.tables.sql(
+            "SELECT * FROM answers WHERE question_id = $1 AND author_id = $2 AND NOT published ORDER BY created_at, id"
+        ))
+            .bind(question_id)
+            .bind(author_id)
+            .fetch_all(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(DbError::from)
+    }
+
+    async fn get_answer(&self, answer_id: EntityId) -> Result<Answer, DbError> {
+        // Parse answer id
+        let answer_id = self.source.resolve_id(&self.tables, "answers", answer_id).await?;
+        // attempt to read answer from database
+        sqlx::query_as::<_, Answer>(&self.tables.sql("SELECT * FROM answers WHERE id = $1"))
+            .bind(answer_id)
+            .fetch_one(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(DbError::from)
+    }
+
+    async fn get_answers(&self, question_id: EntityId) -> Result<Vec<Answer>, DbError> {
+        // Parse entity id first
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        // Attempt to read all associated answers from database
+        self.list_answers(Some(question_id)).await
+    }
+
+    async fn get_answer_threads(&self, question_id: EntityId) -> Result<Vec<AnswerThread>, DbError> {
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        self.list_answers(Some(question_id)).await.map(AnswerThread::assemble)
+    }
+
+    async fn get_answers_sorted(&self, question_id: EntityId, sort: AnswerSort, limit: u32) -> Result<Vec<Answer>, DbError> {
+        // Parse entity id and validate the limit first
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        if limit == 0 {
+            return Err(DbError::validation(String::from("limit must be at least 1")));
+        }
+        let mut conn = self.source.acquire().await.map_err(DbError::from)?;
+        let mut tx = conn.begin().await.map_err(DbError::from)?;
+        // Ensure the question exists, so a missing question is distinguishable from one without answers
+        sqlx::query(&self.tables.sql("SELECT id FROM questions WHERE id = $1"))
+            .bind(question_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(missing("question", question_id))?;
+        let answers = sqlx::query_as::<_, Answer>(&self.tables.sql(&format!(
+            "SELECT * FROM answers WHERE question_id = $1 AND published AND {} ORDER BY {}, {} LIMIT $2",
+            answer_visibility(self.moderation, "$3"),
+            pinned_answer_first("$1"),
+            answer_order(sort)
+        )))
+            .bind(question_id)
+            .bind(i64::from(limit))
+            .bind(self.clock.now())
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(DbError::from)?;
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(answers)
+    }
+
+    async fn get_answers_paged(&self, question_id: EntityId, limit: u32, page_token: Option<&str>) -> Result<AnswerPage, DbError> {
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        if limit == 0 || limit > MAX_PAGE_SIZE {
+            return Err(DbError::validation(format!("limit must be between 1 and {MAX_PAGE_SIZE}, got {limit}")));
+        }
+        let token = page_token.map(AnswerPageToken::decode).transpose()?;
+        let snapshot = token.as_ref().map_or_else(|| self.clock.now(), |token| token.snapshot);
+        let mut conn = self.source.acquire().await.map_err(DbError::from)?;
+        let mut tx = conn.begin().await.map_err(DbError::from)?;
+        sqlx::query(&self.tables.sql("SELECT id FROM questions WHERE id = $1"))
+            .bind(question_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(missing("question", question_id))?;
+        // The likes as of the snapshot are the current likes less the changes recorded since, and the answers are
+        // visible as of the snapshot, so every page orders the same answers the same way. One more answer than
+        // requested is read to tell whether more follow the page
+        let mut rows = sqlx::query(&self.tables.sql(&format!(
+            "WITH snapshot AS ( \
+                SELECT answers.*, answers.likes - COALESCE(( \
+                    SELECT SUM(delta) FROM like_events \
+                    WHERE entity_type = 'answer' AND entity_id = answers.id AND occurred_at > $2 \
+                ), 0)::bigint AS snapshot_likes \
+                FROM answers WHERE question_id = $1 AND published AND created_at <= $2 AND {} \
+            ) \
+            SELECT * FROM snapshot \
+            WHERE $3::bigint IS NULL OR snapshot_likes < $3 OR (snapshot_likes = $3 AND (created_at, id) > ($4, $5)) \
+            ORDER BY snapshot_likes DESC, created_at, id LIMIT $6",
+            answer_visibility(self.moderation, "$2")
+        )))
+            .bind(question_id)
+            .bind(snapshot)
+            .bind(token.as_ref().map(|token| token.likes))
+            .bind(token.as_ref().map(|token| token.created_at))
+            .bind(token.as_ref().map(|token| token.id))
+            .bind(i64::from(limit) + 1)
+            .try_map(|row: PgRow| Ok((<Answer as sqlx::FromRow<PgRow>>::from_row(&row)?, row.try_get::<i64, _>("snapshot_likes")?)))
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(DbError::from)?;
+        tx.commit().await.map_err(DbError::commit)?;
+        let has_more = rows.len() > limit as usize;
+        rows.truncate(limit as usize);
+        let page_token = rows.last().filter(|_| has_more).map(|(answer, likes)| {
+            AnswerPageToken { snapshot, likes: *likes, created_at: answer.created_at(), id: answer.id() }.encode()
+        });
+        Ok(AnswerPage { answers: rows.into_iter().map(|(answer, _)| answer).collect(), page_token })
+    }
+
+    async fn get_answers_with_authors(&self, question_id: EntityId) -> Result<Vec<AnswerWithAuthor>, DbError> {
+        // Parse entity id first
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        // Join the authors in the same query, avoiding a lookup per answer
+        sqlx::query_as::<_, AnswerWithAuthor>(&self.tables.sql(&format!(
+            "SELECT answers.*, users.username AS author_username FROM answers \
+            LEFT JOIN users ON users.id = answers.author_id \
+            WHERE answers.question_id = $1 AND answers.published AND {} \
+            ORDER BY {}, answers.created_at, answers.id",
+            answer_visibility(self.moderation, "$2"),
+            pinned_answer_first("$1")
+        )))
+            .bind(question_id)
+            .bind(self.clock.now())
+            .fetch_all(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(DbError::from)
+    }
+
+    async fn get_answers_by_token(&self, author_token: &str) -> Result<Vec<Answer>, DbError> {
+        validate_author_token(author_token)?;
+        sqlx::query_as::<_, Answer>(&self.tables.sql(
+            "SELECT *, true AS is_mine FROM answers WHERE author_token = $1 ORDER BY created_at DESC, id DESC"
+        ))
+            .bind(author_token)
+            .fetch_all(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(DbError::from)
+    }
+
+    async fn search_answers(&self, question_id: EntityId, term: &str) -> Result<Vec<Answer>, DbError> {
+        // Parse entity id and validate the term first
+        let question_id = self.source.resolve_id(&self.tables, "questions", question_id).await?;
+        let pattern = search_pattern(term)?;
+        let mut conn = self.source.acquire().await.map_err(DbError::from)?;
+        let mut tx = conn.begin().await.map_err(DbError::from)?;
+        // Ensure the question exists, so a missing question is distinguishable from one without matches
+        sqlx::query(&self.tables.sql("SELECT id FROM questions WHERE id = $1"))
+            .bind(question_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(missing("question", question_id))?;
+        let answers = sqlx::query_as::<_, Answer>(&self.tables.sql(&format!(
+            "SELECT * FROM answers WHERE question_id = $1 AND published AND answer ILIKE $2 ESCAPE '\\' AND {} \
+            ORDER BY {}",
+            answer_visibility(self.moderation, "$3"),
+            answer_order(AnswerSort::MostLiked)
+        )))
+            .bind(question_id)
+            .bind(pattern)
+            .bind(self.clock.now())
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(DbError::from)?;
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(answers)
+    }
+
+    async fn search_all_answers(&self, term: &str, limit: u32) -> Result<Vec<AnswerWithQuestion>, DbError> {
+        let pattern = search_pattern(term)?;
+        if limit == 0 {
+            return Err(DbError::validation(String::from("limit must be at least 1")));
+        }
+        // Join the question titles in the same query, avoiding a lookup per answer
+        sqlx::query_as::<_, AnswerWithQuestion>(&self.tables.sql(&format!(
+            "SELECT answers.*, questions.title AS question_title FROM answers \
+            JOIN questions ON questions.id = answers.question_id \
+            WHERE answers.published AND answers.answer ILIKE $1 ESCAPE '\\' AND {} \
+            ORDER BY answers.likes DESC, answers.created_at, answers.id LIMIT $2",
+            answer_visibility(self.moderation, "$3")
+        )))
+            .bind(pattern)
+            .bind(i64::from(limit))
+            .bind(self.clock.now())
+            .fetch_all(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(DbError::from)
+    }
+
+    async fn delete_answer(&self, answer_id: EntityId) -> Result<Uuid, DbError> {
+        // Parse entity id
+        let answer_id = self.source.resolve_id(&self.tables, "answers", answer_id).await?;
+        // Attempt to execute query
+        match sqlx::query(&self.tables.sql("DELETE FROM answers WHERE id = $1"))
+            .bind(answer_id)
+            .execute(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(DbError::from)
+        {
+            Ok(_) => Ok(answer_id),
+            Err(e) => Err(e)
+        }
+    }
+
+    async fn delete_answer_with_tombstone(&self, answer_id: EntityId, reason: Option<String>) -> Result<Answer, DbError> {
+        let reason = reason.map(|reason| reason.trim().to_string()).filter(|reason| !reason.is_empty());
+        if let Some(reason) = &reason {
+            check_length("reason", reason, MAX_DELETED_REASON_LENGTH)?;
+        }
+        let answer_id = self.source.resolve_id(&self.tables, "answers", answer_id).await?;
+        let mut conn = self.source.acquire().await.map_err(DbError::from)?;
+        let mut tx = conn.begin().await.map_err(DbError::from)?;
+        // Lock the answer, so it cannot be liked between being read and being tombstoned
+        let deleted_at: Option<DateTime<Utc>> = sqlx::query_scalar(&self.tables.sql("SELECT deleted_at FROM answers WHERE id = $1 FOR UPDATE"))
+            .bind(answer_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(missing("answer", answer_id))?;
+        if deleted_at.is_some() {
+            return Err(DbError::conflict(String::from("the answer is already deleted")));
+        }
+        // The cleared likes are recorded like any other change of the counter, so snapshots of it stay consistent
+        let stats = ContentStats::of(DELETED_CONTENT);
+        let answer = sqlx::query_as::<_, Answer>(&self.tables.sql(
+            "WITH previous AS ( \
+                SELECT id, likes FROM answers WHERE id = $1 \
+            ), recorded AS ( \
+                INSERT INTO like_events (entity_type, entity_id, delta, occurred_at) \
+                SELECT 'answer', id, -likes, $5 FROM previous WHERE likes <> 0 \
+            ) \
+            UPDATE answers SET answer = $2, char_count = $3, word_count = $4, likes = 0, deleted_at = $5, deleted_reason = $6 \
+            WHERE id = $1 RETURNING *"
+        ))
+            .bind(answer_id)
+            .bind(DELETED_CONTENT)
+            .bind(stats.char_count)
+            .bind(stats.word_count)
+            .bind(self.clock.now())
+            .bind(reason)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(DbError::update)?;
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(answer)
+    }
+
+    async fn get_all_answers(&self) -> Result<Vec<Answer>, DbError> {
+        self.list_answers(None).await
+    }
+
+    async fn get_all_answers_with_question(&self, page: PageRequest) -> Result<Page<AnswerWithQuestion>, DbError> {
+        page.validate()?;
+        // A left join keeps the answers whose question is gone, rather than dropping them from the listing. One more
+        // answer than requested is read to tell whether more follow the page
+        let mut items = sqlx::query_as::<_, AnswerWithQuestion>(&self.tables.sql(&format!(
+            "SELECT answers.*, questions.title AS question_title FROM answers \
+            LEFT JOIN questions ON questions.id = answers.question_id \
+            WHERE answers.published AND {} \
+            ORDER BY answers.likes DESC, answers.created_at, answers.id LIMIT $1 OFFSET $2",
+            answer_visibility(self.moderation, "$3")
+        )))
+            .bind(i64::from(page.limit) + 1)
+            .bind(i64::from(page.offset))
+            .bind(self.clock.now())
+            .fetch_all(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(DbError::from)?;
+        let has_more = items.len() > page.limit as usize;
+        items.truncate(page.limit as usize);
+        Ok(Page { items, has_more })
+    }
+
+    async fn increment_answer_likes(&self, answer_id: EntityId) -> Result<i64, DbError> {
+        // Parse entity id
+        let answer_id = self.source.resolve_id(&self.tables, "answers", answer_id).await?;
+        // Attempt to execute query, use a transaction
+        let mut conn = self.source.acquire().await.map_err(DbError::from)?;
+        let mut tx = conn.begin().await.map_err(DbError::from)?;
+        // Record the like and reward the author in the same statement as the increment, so neither can be skipped
+        let likes = sqlx::query(&self.tables.sql(
+            "WITH incremented AS ( \
+                UPDATE answers SET likes = likes + 1 WHERE id = $1 AND published AND deleted_at IS NULL \
+                RETURNING id, question_id, likes, author_id \
+            ), recorded AS ( \
+                INSERT INTO like_events (entity_type, entity_id, delta, occurred_at) \
+                SELECT 'answer', id, 1, $2 FROM incremented \
+            ), touched AS ( \
+                UPDATE questions SET last_activity_at = $2 WHERE id IN (SELECT question_id FROM incremented) \
+            ), rewarded AS ( \
+                UPDATE users SET reputation = reputation + $3 FROM incremented WHERE users.id = incremented.author_id \
+            ) \
+            SELECT likes FROM incremented"
+        ))
+            .bind(answer_id)
+            .bind(self.clock.now())
+            .bind(self.reputation.answer_liked)
+            .try_map(|row: PgRow| row.try_get::<i64, &str>("likes"))
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(edit_error)?;
+        let Some(likes) = likes else {
+            // Nothing was updated, either because the answer is missing, a draft or tombstoned
+            let (published, deleted): (bool, bool) = sqlx::query_as(&self.tables.sql(
+                "SELECT published, deleted_at IS NOT NULL FROM answers WHERE id = $1"
+            ))
+                .bind(answer_id)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(missing("answer", answer_id))?;
+            if !published {
+                return Err(DbError::conflict(String::from("drafts cannot be liked until they are published")));
+            }
+            if deleted {
+                return Err(DbError::conflict(String::from("deleted answers cannot be liked")));
+            }
+            // The answer was published after the increment was attempted
+            return Err(DbError::conflict(String::from("the answer changed while it was being liked")));
+        };
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(likes)
+    }
+
+    async fn vote_answer(&self, answer_id: EntityId, vote: AnswerVote, user_token: &str) -> Result<VoteOutcome, DbError> {
+        // Parse entity id
+        let answer_id = self.source.resolve_id(&self.tables, "answers", answer_id).await?;
+        let mut conn = self.source.acquire().await.map_err(DbError::from)?;
+        let mut tx = conn.begin().await.map_err(DbError::from)?;
+        // Lock the answer, so concurrent votes by the same user cannot both be counted
+        let (published, deleted): (bool, bool) = sqlx::query_as(&self.tables.sql(
+            "SELECT published, deleted_at IS NOT NULL FROM answers WHERE id = $1 FOR UPDATE"
+        ))
+            .bind(answer_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(missing("answer", answer_id))?;
+        if !published {
+            return Err(DbError::conflict(String::from("drafts cannot be voted on until they are published")));
+        }
+        if deleted {
+            return Err(DbError::conflict(String::from("deleted answers cannot be voted on")));
+        }
+        let previous: Option<String> = sqlx::query_scalar(&self.tables.sql("SELECT vote FROM answer_votes WHERE answer_id = $1 AND user_token = $2"))
+            .bind(answer_id)
+            .bind(user_token)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(DbError::from)?;
+        let outcome = match previous.as_deref() {
+            None => VoteOutcome::Recorded,
+            Some(previous) if previous == vote.as_str() => return Ok(VoteOutcome::Unchanged),
+            Some(_) => VoteOutcome::Switched,
+        };
+        sqlx::query(&self.tables.sql(
+            "INSERT INTO answer_votes (answer_id, user_token, vote, voted_at) VALUES ($1, $2, $3, $4) \
+            ON CONFLICT (answer_id, user_token) DO UPDATE SET vote = EXCLUDED.vote, voted_at = EXCLUDED.voted_at"
+        ))
+            .bind(answer_id)
+            .bind(user_token)
+            .bind(vote.as_str())
+            .bind(self.clock.now())
+            .execute(&mut *tx)
+            .await
+            .map_err(edit_error)?;
+        // A switched vote is taken from the opposite counter in the same statement
+        let switched = i64::from(outcome == VoteOutcome::Switched);
+        let (helpful, unhelpful) = match vote {
+            AnswerVote::Helpful => (1, -switched),
+            AnswerVote::Unhelpful => (-switched, 1),
+        };
+        sqlx::query(&self.tables.sql("UPDATE answers SET helpful_count = helpful_count + $2, unhelpful_count = unhelpful_count + $3 WHERE id = $1"))
+            .bind(answer_id)
+            .bind(helpful)
+            .bind(unhelpful)
+            .execute(&mut *tx)
+            .await
+            .map_err(edit_error)?;
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(outcome)
+    }
+
+    async fn approve_answer(&self, answer_id: EntityId) -> Result<Answer, DbError> {
+        // Parse entity id
+        let answer_id = self.source.resolve_id(&self.tables, "answers", answer_id).await?;
+        let mut conn = self.source.acquire().await.map_err(DbError::from)?;
+        let mut tx = conn.begin().await.map_err(DbError::from)?;
+        let published: bool = sqlx::query_scalar(&self.tables.sql("SELECT published FROM answers WHERE id = $1 FOR UPDATE"))
+            .bind(answer_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(missing("answer", answer_id))?;
+        if !published {
+            return Err(DbError::conflict(String::from("drafts cannot be approved until they are published")));
+        }
+        let answer = sqlx::query_as::<_, Answer>(&self.tables.sql(
+            "UPDATE answers SET approved_at = COALESCE(approved_at, $2) WHERE id = $1 RETURNING *"
+        ))
+            .bind(answer_id)
+            .bind(self.clock.now())
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(DbError::update)?;
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(answer)
+    }
+
+    async fn reject_answer(&self, answer_id: EntityId) -> Result<Uuid, DbError> {
+        // Parse entity id
+        let answer_id = self.source.resolve_id(&self.tables, "answers", answer_id).await?;
+        // Record the rejection in the same statement as the deletion, so the audit row cannot be skipped
+        let rejected: Option<Uuid> = sqlx::query_scalar(&self.tables.sql(
+            "WITH rejected AS ( \
+                DELETE FROM answers WHERE id = $1 RETURNING id, question_id, author_id, answer, created_at \
+            ) \
+            INSERT INTO answer_rejections (answer_id, question_id, author_id, answer, created_at, rejected_at) \
+            SELECT id, question_id, author_id, answer, created_at, $2 FROM rejected \
+            RETURNING answer_id"
+        ))
+            .bind(answer_id)
+            .bind(self.clock.now())
+            .fetch_optional(&mut *self.source.acquire().await.map_err(DbError::from)?)
+            .await
+            .map_err(DbError::deletion)?;
+        rejected.ok_or_else(|| DbError::not_found("answer", answer_id))
+    }
+
+    async fn archive_stale_answers(&self, inactive_for: Duration, batch: usize) -> Result<ArchiveReport, DbError> {
+        if inactive_for < Duration::zero() {
+            return Err(DbError::validation(String::from("inactive_for must not be negative")));
+        }
+        if batch == 0 {
+            return Err(DbError::validation(String::from("batch must be at least one")));
+        }
+        let now = self.clock.now();
+        let cutoff = now - inactive_for;
+        let mut report = ArchiveReport::default();
+        loop {
+            let mut conn = self.source.acquire().await.map_err(DbError::from)?;
+            let mut tx = conn.begin().await.map_err(DbError::from)?;
+            // Locking the questions keeps answers from being added until their answers are archived, questions
+            // locked by a concurrent pass are left to it
+            let question_ids: Vec<Uuid> = sqlx::query_scalar(&self.tables.sql(
+                "SELECT id FROM questions \
+                WHERE COALESCE(last_activity_at, created_at) < $1 \
+                AND EXISTS (SELECT 1 FROM answers WHERE answers.question_id = questions.id) \
+                ORDER BY created_at, id LIMIT $2 FOR UPDATE SKIP LOCKED"
+            ))
+                .bind(cutoff)
+                .bind(batch as i64)
+                .fetch_all(&mut *tx)
+                .await
+                .map_err(DbError::from)?;
+            if question_ids.is_empty() {
+                break;
+            }
+            let moved: Vec<Uuid> = sqlx::query_scalar(&self.tables.sql(
+                "INSERT INTO answers_archive \
+                    (answer_id, serial, question_id, parent_answer_id, author_id, author_token, answer, content_type, likes, \
+                    helpful_count, unhelpful_count, published, approved_at, created_at, deleted_at, deleted_reason, archived_at) \
+                SELECT id, serial, question_id, parent_answer_id, author_id, author_token, answer, content_type, likes, \
+                    helpful_count, unhelpful_count, published, approved_at, created_at, deleted_at, deleted_reason, $2 \
+                FROM answers WHERE question_id = ANY($1) RETURNING question_id"
+            ))
+                .bind(&question_ids)
+                .bind(now)
+                .fetch_all(&mut *tx)
+                .await
+                .map_err(creation_error)?;
+            // Deleting the answers would cascade to their votes and flags and clear the pinned answer, so they are
+            // archived along with them
+            sqlx::query(&self.tables.sql(
+                "INSERT INTO answer_votes_archive (answer_id, user_token, vote, voted_at) \
+                SELECT answer_votes.answer_id, answer_votes.user_token, answer_votes.vote, answer_votes.voted_at \
+                FROM answer_votes JOIN answers ON answers.id = answer_votes.answer_id WHERE answers.question_id = ANY($1)"
+            ))
+                .bind(&question_ids)
+                .execute(&mut *tx)
+                .await
+                .map_err(creation_error)?;
+            sqlx::query(&self.tables.sql(
+                "INSERT INTO moderation_flags_archive (id, answer_id, reason, created_at) \
+                SELECT moderation_flags.id, moderation_flags.answer_id, moderation_flags.reason, moderation_flags.created_at \
+                FROM moderation_flags JOIN answers ON answers.id = moderation_flags.answer_id \
+                WHERE answers.question_id = ANY($1)"
+            ))
+                .bind(&question_ids)
+                .execute(&mut *tx)
+                .await
+                .map_err(creation_error)?;
+            sqlx::query(&self.tables.sql(
+                "UPDATE questions SET archived_accepted_answer_id = accepted_answer_id \
+                WHERE id = ANY($1) AND accepted_answer_id IS NOT NULL"
+            ))
+                .bind(&question_ids)
+                .execute(&mut *tx)
+                .await
+                .map_err(DbError::update)?;
+            sqlx::query(&self.tables.sql("DELETE FROM answers WHERE question_id = ANY($1)"))
+                .bind(&question_ids)
+                .execute(&mut *tx)
+                .await
+                .map_err(DbError::deletion)?;
+            let mut counts: HashMap<Uuid, i64> = HashMap::new();
+            for question_id in moved {
+                *counts.entry(question_id).or_default() += 1;
+            }
+            let counts: Vec<i64> = question_ids.iter().map(|id| counts.get(id).copied().unwrap_or(0)).collect();
+            sqlx::query(&self.tables.sql(
+                "UPDATE questions SET archived_answer_count = archived_answer_count + archived.count \
+                FROM unnest($1::uuid[], $2::bigint[]) AS archived (id, count) WHERE questions.id = archived.id"
+            ))
+                .bind(&question_ids)
+                .bind(&counts)
+                .execute(&mut *tx)
+                .await
+                .map_err(DbError::update)?;
+            tx.commit().await.map_err(DbError::commit)?;
+            report.questions.extend(question_ids.iter().copied().zip(counts.iter().map(|count| *count as u64)));
+            report.batches += 1;
+            if question_ids.len() < batch {
+                break;
+            }
+        }
+        Ok(report)
+    }
+}
+
+
+/// A `SubscriptionDao` backed by a Postgres connection pool.
+pub struct SubscriptionDaoImpl {
+    pool: PgPool,
+    tables: Tables,
+    clock: Arc<dyn Clock>,
+    moderation: ModerationMode,
+}
+
+impl SubscriptionDaoImpl {
+    /// Creates the data access object, using the system clock and reporting answers as soon as they are published.
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool, tables: Tables::default(), clock: Arc::new(SystemClock), moderation: ModerationMode::default() }
+    }
+
+    /// Places the tables the data access object reads and writes in `schema`, which should have been migrated with
+    /// `migrations::run_in_schema`.
+    pub fn with_schema(mut self, schema: SchemaName) -> Self {
+        self.tables = Tables::in_schema(schema);
+        self
+    }
+
+    /// Sets the `Clock` used to timestamp subscriptions and fetches.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Sets the `ModerationMode` deciding which answers are reported, which should match the mode of the
+    /// `AnswerDaoImpl` creating them. Answers held back are reported once visible.
+    pub fn with_moderation(mut self, moderation: ModerationMode) -> Self {
+        self.moderation = moderation;
+        self
+    }
+}
+
+impl SubscriptionDao for SubscriptionDaoImpl {
+    async fn subscribe(&self, user_token: &str, question_id: EntityId) -> Result<(), DbError> {
+        // Parse entity id
+        let question_id = resolve_id(&self.pool, &self.tables, "questions", question_id).await?;
+        let mut tx = self.pool.begin().await.map_err(DbError::from)?;
+        // Ensure that the question actually exists
+        sqlx::query(&self.tables.sql("SELECT id FROM questions WHERE id = $1"))
+            .bind(question_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(missing("question", question_id))?;
+        // A new subscription has seen the answers already visible, answers still held back are reported once visible
+        sqlx::query(&self.tables.sql(&format!(
+            "WITH subscribed AS ( \
+                INSERT INTO subscriptions (user_token, question_id, last_seen_at) VALUES ($1, $2, $3) \
+                ON CONFLICT DO NOTHING RETURNING user_token, question_id \
+            ) \
+            INSERT INTO subscription_seen_answers (user_token, question_id, answer_id) \
+            SELECT subscribed.user_token, subscribed.question_id, answers.id \
+            FROM subscribed JOIN answers ON answers.question_id = subscribed.question_id \
+            WHERE answers.published AND {}",
+            answer_visibility(self.moderation, "$3")
+        )))
+            .bind(user_token)
+            .bind(question_id)
+            .bind(self.clock.now())
+            .execute(&mut *tx)
+            .await
+            .map_err(DbError::creation)?;
+        tx.commit().await.map_err(DbError::commit)
+    }
+
+    async fn unsubscribe(&self, user_token: &str, question_id: EntityId) -> Result<(), DbError> {
+        // Parse entity id
+        let question_id = resolve_id(&self.pool, &self.tables, "questions", question_id).await?;
+        sqlx::query(&self.tables.sql("DELETE FROM subscriptions WHERE user_token = $1 AND question_id = $2"))
+            .bind(user_token)
+            .bind(question_id)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(DbError::deletion)
+    }
+
+    async fn get_updates(&self, user_token: &str) -> Result<Vec<QuestionUpdate>, DbError> {
+        let mut tx = self.pool.begin().await.map_err(DbError::from)?;
+        // Lock the subscriptions so concurrent fetches cannot report the same answers twice
+        sqlx::query(&self.tables.sql("SELECT question_id FROM subscriptions WHERE user_token = $1 FOR UPDATE"))
+            .bind(user_token)
+            .execute(&mut *tx)
+            .await
+            .map_err(DbError::from)?;
+        // Report the visible answers not seen yet, whenever they were created, and mark them as seen
+        let updates = sqlx::query_as::<_, QuestionUpdate>(&self.tables.sql(&format!(
+            "WITH unseen AS ( \
+                SELECT subscriptions.question_id, answers.id AS answer_id, answers.created_at \
+                FROM subscriptions \
+                JOIN answers ON answers.question_id = subscriptions.question_id AND answers.published AND {} \
+                WHERE subscriptions.user_token = $1 AND NOT EXISTS ( \
+                    SELECT 1 FROM subscription_seen_answers AS seen WHERE seen.user_token = $1 AND seen.answer_id = answers.id \
+                ) \
+            ), seen AS ( \
+                INSERT INTO subscription_seen_answers (user_token, question_id, answer_id) \
+                SELECT $1, question_id, answer_id FROM unseen \
+            ), fetched AS ( \
+                UPDATE subscriptions SET last_seen_at = $2 WHERE user_token = $1 \
+            ) \
+            SELECT question_id, COUNT(*) AS new_answers, MAX(created_at) AS latest_answer_at \
+            FROM unseen GROUP BY question_id ORDER BY latest_answer_at DESC",
+            answer_visibility(self.moderation, "$2")
+        )))
+            .bind(user_token)
+            .bind(self.clock.now())
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(DbError::update)?;
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(updates)
+    }
+}
+
+/// A `StatsDao` backed by a Postgres connection pool.
+pub struct StatsDaoImpl {
+    pool: PgPool,
+    tables: Tables,
+    clock: Arc<dyn Clock>,
+}
+
+impl StatsDaoImpl {
+    /// Creates the data access object, using the system clock.
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool, tables: Tables::default(), clock: Arc::new(SystemClock) }
+    }
+
+    /// Places the tables the data access object reads and writes in `schema`, which should have been migrated with
+    /// `migrations::run_in_schema`.
+    pub fn with_schema(mut self, schema: SchemaName) -> Self {
+        self.tables = Tables::in_schema(schema);
+        self
+    }
+
+    /// Sets the `Clock` used to determine the current day.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+}
+
+impl StatsDao for StatsDaoImpl {
+    async fn get_daily_activity(&self, days: u32) -> Result<Vec<DailyActivity>, DbError> {
+        if days == 0 {
+            return Err(DbError::validation(String::from("days must be at least 1")));
+        }
+        let today = self.clock.now().date_naive();
+        let first_day = today - Duration::days(i64::from(days) - 1);
+        // Generate every day in the range so days without activity are reported with zero counts
+        sqlx::query_as::<_, DailyActivity>(&self.tables.sql(
+            "WITH days AS ( \
+                SELECT generate_series($1::date, $2::date, interval '1 day')::date AS date \
+            ), question_counts AS ( \
+                SELECT (created_at AT TIME ZONE 'UTC')::date AS date, COUNT(*) AS count FROM questions \
+                WHERE created_at >= $1::date AT TIME ZONE 'UTC' \
+                GROUP BY 1 \
+            ), answer_counts AS ( \
+                SELECT (created_at AT TIME ZONE 'UTC')::date AS date, COUNT(*) AS count FROM answers \
+                WHERE published AND created_at >= $1::date AT TIME ZONE 'UTC' \
+                GROUP BY 1 \
+            ) \
+            SELECT days.date, COALESCE(question_counts.count, 0) AS questions, COALESCE(answer_counts.count, 0) AS answers \
+            FROM days \
+            LEFT JOIN question_counts ON question_counts.date = days.date \
+            LEFT JOIN answer_counts ON answer_counts.date = days.date \
+            ORDER BY days.date"
+        ))
+            .bind(first_day)
+            .bind(today)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::from)
+    }
+
+    async fn get_totals(&self) -> Result<Totals, DbError> {
+        sqlx::query_as::<_, Totals>(&self.tables.sql(
+            "SELECT (SELECT COUNT(*) FROM questions) AS questions, (SELECT COUNT(*) FROM answers WHERE published) AS answers, \
+            (SELECT COUNT(*) FROM users) AS users, (SELECT COUNT(*) FROM subscriptions) AS subscriptions"
+        ))
+            .fetch_one(&self.pool)
+            .await
+            .map_err(DbError::from)
+    }
+
+    async fn get_time_to_first_answer(&self, since: DateTime<Utc>) -> Result<LatencyStats, DbError> {
+        // Waits are computed in seconds, unanswered questions have no wait and are left out of the aggregates
+        sqlx::query(&self.tables.sql(
+            "WITH waits AS ( \
+                SELECT EXTRACT(EPOCH FROM MIN(answers.created_at) - questions.created_at)::float8 AS wait \
+                FROM questions \
+                LEFT JOIN answers ON answers.question_id = questions.id AND answers.published \
+                WHERE questions.created_at >= $1 \
+                GROUP BY questions.id \
+            ) \
+            SELECT COUNT(wait) AS answered, COUNT(*) - COUNT(wait) AS unanswered, MIN(wait) AS min, \
+                percentile_cont(0.5) WITHIN GROUP (ORDER BY wait) AS median, \
+                percentile_cont(0.9) WITHIN GROUP (ORDER BY wait) AS p90 \
+            FROM waits"
+        ))
+            .bind(since)
+            .try_map(|row: PgRow| {
+                let duration = |column: &str| -> Result<Option<Duration>, sqlx::Error> {
+                    let seconds: Option<f64> = row.try_get(column)?;
+                    Ok(seconds.map(|s| Duration::microseconds((s * 1_000_000.0).round() as i64)))
+                };
+                Ok(LatencyStats {
+                    answered: row.try_get("answered")?,
+                    unanswered: row.try_get("unanswered")?,
+                    min: duration("min")?,
+                    median: duration("median")?,
+                    p90: duration("p90")?,
+                })
+            })
+            .fetch_one(&self.pool)
+            .await
+            .map_err(DbError::from)
+    }
+}
+
+/// An `AdminDao` backed by a Postgres connection pool.
+pub struct AdminDaoImpl {
+    pool: PgPool,
+    tables: Tables,
+}
+
+impl AdminDaoImpl {
+    /// Creates the data access object.
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool, tables: Tables::default() }
+    }
+
+    /// Places the tables the data access object reads and writes in `schema`, which should have been migrated with
+    /// `migrations::run_in_schema`.
+    pub fn with_schema(mut self, schema: SchemaName) -> Self {
+        self.tables = Tables::in_schema(schema);
+        self
+    }
+
+    /// Recomputes content statistics like `AdminDao::recompute_content_stats`, unless another instance is already
+    /// recomputing them, see `lock::with_advisory_lock`.
+    pub async fn recompute_content_stats_guarded(&self) -> Result<LockOutcome<u64>, DbError> {
+        lock::with_advisory_lock(&self.pool, MaintenanceLock::RecomputeContentStats, || self.recompute_content_stats()).await
+    }
+
+    /// Rebuilds the indexes like `AdminDao::reindex`, unless another instance is already rebuilding them, see
+    /// `lock::with_advisory_lock`.
+    pub async fn reindex_guarded(&self) -> Result<LockOutcome<Vec<&'static str>>, DbError> {
+        lock::with_advisory_lock(&self.pool, MaintenanceLock::Reindex, || self.reindex()).await
+    }
+
+    /// Recomputes the content statistics of the rows of `table`, whose content is in `column`, `batch_size` rows
+    /// at a time in id order, returning the number of rows whose statistics changed.
+    async fn recompute_table_stats(&self, table: &str, column: &str, batch_size: i64) -> Result<u64, DbError> {
+        let mut changed = 0;
+        let mut after: Option<Uuid> = None;
+        loop {
+            let rows: Vec<(Uuid, String)> = sqlx::query_as(&self.tables.sql(&format!(
+                "SELECT id, {column} FROM {table} WHERE $1::uuid IS NULL OR id > $1 ORDER BY id LIMIT $2"
+            )))
+                .bind(after)
+                .bind(batch_size)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(DbError::from)?;
+            let Some(&(last, _)) = rows.last() else {
+                break;
+            };
+            after = Some(last);
+            let batch_len = rows.len();
+            let (mut ids, mut contents) = (Vec::with_capacity(batch_len), Vec::with_capacity(batch_len));
+            let (mut char_counts, mut word_counts) = (Vec::with_capacity(batch_len), Vec::with_capacity(batch_len));
+            for (id, content) in rows {
+                let stats = ContentStats::of(&content);
+                ids.push(id);
+                contents.push(content);
+                char_counts.push(stats.char_count);
+                word_counts.push(stats.word_count);
+            }
+            // Rows whose content was edited since it was read already have up to date statistics
+            changed += sqlx::query(&self.tables.sql(&format!(
+                "UPDATE {table} SET char_count = input.char_count, word_count = input.word_count \
+                FROM UNNEST($1::uuid[], $2::text[], $3::integer[], $4::integer[]) AS input (id, content, char_count, word_count) \
+                WHERE {table}.id = input.id AND {table}.{column} = input.content \
+                    AND ({table}.char_count, {table}.word_count) IS DISTINCT FROM (input.char_count, input.word_count)"
+            )))
+                .bind(ids)
+                .bind(contents)
+                .bind(char_counts)
+                .bind(word_counts)
+                .execute(&self.pool)
+                .await
+                .map_err(DbError::update)?
+                .rows_affected();
+            if (batch_len as i64) < batch_size {
+                break;
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Attributes a single row of `table`, which must be `questions` or `answers`, to `new_author`, recording the
+    /// transfer in the ownership audit log.
+    async fn transfer_author(&self, table: &str, id: EntityId, new_author: EntityId) -> Result<(), DbError> {
+        let id = resolve_id(&self.pool, &self.tables, table, id).await?;
+        let new_author: Uuid = new_author.try_into().map_err(DbError::invalid_id)?;
+        let mut tx = self.pool.begin().await.map_err(DbError::from)?;
+        ensure_user(&mut tx, &self.tables, new_author).await?;
+        let transferred = sqlx::query(&self.tables.sql(&format!(
+            "WITH previous AS ( \
+                SELECT id, author_id FROM {table} WHERE id = $1 FOR UPDATE \
+            ), updated AS ( \
+                UPDATE {table} SET author_id = $2 FROM previous WHERE {table}.id = previous.id \
+                RETURNING {table}.id, previous.author_id \
+            ), recorded AS ( \
+                INSERT INTO ownership_transfers (entity_type, entity_id, previous_author_id, new_author_id) \
+                SELECT $3, id, author_id, $2 FROM updated WHERE author_id IS DISTINCT FROM $2 \
+            ) \
+            SELECT id FROM updated"
+        )))
+            .bind(id)
+            .bind(new_author)
+            .bind(like_entity_type(table))
+            .execute(&mut *tx)
+            .await
+            .map_err(DbError::update)?
+            .rows_affected();
+        if transferred == 0 {
+            return Err(DbError::not_found(entity_of(table), id));
+        }
+        tx.commit().await.map_err(DbError::commit)
+    }
+
+    /// Attributes every row of `table`, which must be `questions` or `answers`, authored by `old_author` to
+    /// `new_author`, recording each transfer in the ownership audit log. Returns the number of rows transferred.
+    async fn transfer_all(
+        tx: &mut Transaction<'_, Postgres>,
+        tables: &Tables,
+        table: &str,
+        old_author: Uuid,
+        new_author: Uuid,
+    ) -> Result<u64, DbError> {
+        let transferred: i64 = sqlx::query_scalar(&tables.sql(&format!(
+            "WITH updated AS ( \
+                UPDATE {table} SET author_id = $2 WHERE author_id = $1 RETURNING id \
+            ), recorded AS ( \
+                INSERT INTO ownership_transfers (entity_type, entity_id, previous_author_id, new_author_id) \
+                SELECT $3, id, $1, $2 FROM updated \
+            ) \
+            SELECT COUNT(*) FROM updated"
+        )))
+            .bind(old_author)
+            .bind(new_author)
+            .bind(like_entity_type(table))
+            .fetch_one(&mut **tx)
+            .await
+            .map_err(DbError::update)?;
+        Ok(transferred as u64)
+    }
+
+    /// Sets the likes of a single row of `table`, which must be `questions` or `answers`, recording the
+    /// change in the like audit log.
+    async fn set_likes(&self, table: &str, id: EntityId, likes: i64) -> Result<(), DbError> {
+        validate_likes(likes)?;
+        let id = resolve_id(&self.pool, &self.tables, table, id).await?;
+        let updated = sqlx::query(&self.tables.sql(&format!(
+            "WITH updated AS ( \
+                UPDATE {table} SET likes = $1 \
+                FROM (SELECT id, likes FROM {table} WHERE id = $2 FOR UPDATE) AS previous \
+                WHERE {table}.id = previous.id \
+                RETURNING {table}.id, $1 - previous.likes AS delta \
+            ), recorded AS ( \
+                INSERT INTO like_events (entity_type, entity_id, delta) \
+                SELECT $3, id, delta FROM updated WHERE delta <> 0 \
+            ) \
+            SELECT id FROM updated"
+        )))
+            .bind(likes)
+            .bind(id)
+            .bind(like_entity_type(table))
+            .execute(&self.pool)
+            .await
+            .map_err(DbError::update)?
+            .rows_affected();
+        if updated == 0 {
+            return Err(DbError::not_found(entity_of(table), id));
+        }
+        Ok(())
+    }
+
+    /// Checks exported content like `AdminDao::import_content` does, looking up which records already exist inside
+    /// a read only transaction that is always rolled back.
+    async fn dry_run_import(&self, content: &[QuestionDetailResponse]) -> Result<Vec<ImportRecord>, DbError> {
+        let mut tx = self.pool.begin().await.map_err(DbError::from)?;
+        // Any write would fail in a read only transaction, rather than leak should the rollback be skipped
+        sqlx::query("SET TRANSACTION READ ONLY").execute(&mut *tx).await.map_err(DbError::from)?;
+        let (mut questions, mut answers) = (HashSet::new(), HashSet::new());
+        let mut records = Vec::new();
+        for detail in content {
+            let question = ImportedQuestion::check(&detail.question);
+            let verdict = match &question {
+                Ok(question) => import_verdict(&mut tx, &self.tables, "questions", question.id, &mut questions).await?,
+                Err(e) => ImportVerdict::Invalid(e.to_string()),
+            };
+            records.push(ImportRecord { entity: LikableEntity::Question, id: detail.question.id.clone(), verdict });
+            for answer in &detail.answers {
+                let verdict = match (check_imported_answer(answer), &question) {
+                    (Err(e), _) => ImportVerdict::Invalid(e.to_string()),
+                    (Ok(_), Err(_)) => ImportVerdict::Invalid(String::from("the question of the answer is invalid")),
+                    (Ok((answer_id, _)), Ok(_)) => import_verdict(&mut tx, &self.tables, "answers", answer_id, &mut answers).await?,
+                };
+                records.push(ImportRecord { entity: LikableEntity::Answer, id: answer.id.clone(), verdict });
+            }
+        }
+        tx.rollback().await.map_err(DbError::from)?;
+        Ok(records)
+    }
+
+    /// Sets the likes of many rows of `table`, which must be `questions` or `answers`, in one statement,
+    /// recording each change in the like audit log.
+    async fn bulk_set_likes(&self, table: &str, pairs: Vec<(EntityId, i64)>) -> Result<BulkUpdate, DbError> {
+        // Validate the whole batch before touching the database
+        let mut ids = Vec::with_capacity(pairs.len());
+        let mut likes = Vec::with_capacity(pairs.len());
+        for (id, count) in pairs {
+            validate_likes(count)?;
+            let id = resolve_id(&self.pool, &self.tables, table, id).await?;
+            if ids.contains(&id) {
+                return Err(DbError::validation(format!("duplicate id {id}")));
+            }
+            ids.push(id);
+            likes.push(count);
+        }
+        let requested = ids.len() as u64;
+        let missing: Vec<Uuid> = sqlx::query_scalar(&self.tables.sql(&format!(
+            "WITH input AS ( \
+                SELECT * FROM UNNEST($1::uuid[], $2::bigint[]) WITH ORDINALITY AS input (id, likes, position) \
+            ), previous AS ( \
+                SELECT id, likes FROM {table} WHERE id IN (SELECT id FROM input) FOR UPDATE \
+            ), updated AS ( \
+                UPDATE {table} SET likes = input.likes FROM input, previous \
+                WHERE {table}.id = input.id AND previous.id = input.id \
+                RETURNING {table}.id, input.likes - previous.likes AS delta \
+            ), recorded AS ( \
+                INSERT INTO like_events (entity_type, entity_id, delta) \
+                SELECT $3, id, delta FROM updated WHERE delta <> 0 \
+            ) \
+            SELECT input.id FROM input WHERE NOT EXISTS (SELECT 1 FROM updated WHERE updated.id = input.id) \
+            ORDER BY input.position"
+        )))
+            .bind(ids)
+            .bind(likes)
+            .bind(like_entity_type(table))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::update)?;
+        Ok(BulkUpdate { updated: requested - missing.len() as u64, missing })
+    }
+}
+
+/// Ensures the user exists, locking it so it cannot be deleted before the transaction ends.
+async fn ensure_user(tx: &mut Transaction<'_, Postgres>, tables: &Tables, user_id: Uuid) -> Result<(), DbError> {
+    sqlx::query(&tables.sql("SELECT id FROM users WHERE id = $1 FOR SHARE"))
+        .bind(user_id)
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(missing("user", user_id))?;
+    Ok(())
+}
+
+/// The `entity_type` recorded in the like and ownership audit logs for rows of `table`.
+fn like_entity_type(table: &str) -> &'static str {
+    match table {
+        "questions" => "question",
+        _ => "answer",
+    }
+}
+
+/// Parses an RFC 3339 timestamp of an export.
+fn parse_timestamp(timestamp: &str) -> Result<DateTime<Utc>, DbError> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+        .map_err(|e| DbError::validation(format!("invalid timestamp {timestamp}: {e}")))
+}
 
-    /// # Required Method
-    /// Gets a `Vec` of all answers in the database
-    ///
-    /// # Returns
-    /// A `Result<Vec<Answer>>, DbError>`, in the success case `Ok(Vec<Question>)`, otherwise `Err(DbError)`.
-    async fn get_all_answers(&self) -> Result<Vec<Answer>, DbError>;
+/// The fields of an exported question that are parsed before it is inserted.
+struct ImportedQuestion {
+    id: Uuid,
+    created_at: DateTime<Utc>,
+    updated_at: Option<DateTime<Utc>>,
+    pinned_at: Option<DateTime<Utc>>,
+    pinned_answer_id: Option<Uuid>,
+    accepted_answer_id: Option<Uuid>,
+}
 
-    /// # Required Method
-    /// Deletes an answer from the database.
-    ///
-    /// # Parameters
-    /// `answer_id`: The `EntityId` of the `Answer` to be deleted.
-    ///
-    /// # Returns
-    /// A `Result<Uuid, DbError>`, if the answer is successfully deleted then a `Ok(Uuid)` will be returned,
-    /// otherwise an `Err(DbError)` is returned.
-    async fn delete_answer(&self, answer_id: EntityId) -> Result<Uuid, DbError>;
+impl ImportedQuestion {
+    /// Parses the ids and timestamps of an exported question and checks its content fits the columns.
+    fn check(question: &QuestionResponse) -> Result<Self, DbError> {
+        let limits = ContentLimits::default();
+        check_length("title", &question.title, limits.max_title)?;
+        check_length("question", &question.question, limits.max_question)?;
+        question.content_type.validate()?;
+        Ok(Self {
+            id: EntityId::new(question.id.clone()).try_into().map_err(DbError::invalid_id)?,
+            created_at: parse_timestamp(&question.created_at)?,
+            updated_at: question.updated_at.as_deref().map(parse_timestamp).transpose()?,
+            pinned_at: question.pinned_at.as_deref().map(parse_timestamp).transpose()?,
+            pinned_answer_id: question
+                .pinned_answer_id
+                .clone()
+                .map(|id| EntityId::new(id).try_into())
+                .transpose()
+                .map_err(DbError::invalid_id)?,
+            accepted_answer_id: question
+                .accepted_answer_id
+                .clone()
+                .map(|id| EntityId::new(id).try_into())
+                .transpose()
+                .map_err(DbError::invalid_id)?,
+        })
+    }
+}
 
-    /// # Required Method
-    /// Increments the number of likes associated with a particular answer.
-    ///
-    /// # Parameters
-    /// `answer_id`: The `EntityId` of the `Answer` being queried
-    ///
-    /// # Returns
-    /// A `Result<(), DbError>`, `Ok(())` in the successful case and `Err(DbError)` in the
-    /// unsuccessful case.
-    async fn increment_answer_likes(&self, answer_id: EntityId) -> Result<(), DbError>;
+/// Parses the id and creation timestamp of an exported answer and checks its content fits the column.
+fn check_imported_answer(answer: &AnswerResponse) -> Result<(Uuid, DateTime<Utc>), DbError> {
+    if answer.truncated {
+        return Err(DbError::validation(format!("answer {} is truncated and cannot be imported", answer.id)));
+    }
+    check_length("answer", &answer.answer, ContentLimits::default().max_answer)?;
+    answer.content_type.validate()?;
+    let answer_id = EntityId::new(answer.id.clone()).try_into().map_err(DbError::invalid_id)?;
+    Ok((answer_id, parse_timestamp(&answer.created_at)?))
 }
 
-pub struct QuestionDaoImpl {
-    pool: PgPool,
+/// The verdict on an imported record given the number of rows its insert affected.
+fn inserted_verdict(rows_affected: u64) -> ImportVerdict {
+    if rows_affected == 0 { ImportVerdict::Skip } else { ImportVerdict::Insert }
 }
 
-impl QuestionDaoImpl {
-    fn new(pool: PgPool) -> Self {
-        Self { pool }
+/// Whether a record with id `id` would be inserted into `table`, which must be `questions` or `answers`, or skipped
+/// because it already exists or is in `seen`, the ids of the records checked before it.
+async fn import_verdict(
+    tx: &mut Transaction<'_, Postgres>,
+    tables: &Tables,
+    table: &str,
+    id: Uuid,
+    seen: &mut HashSet<Uuid>,
+) -> Result<ImportVerdict, DbError> {
+    if !seen.insert(id) {
+        return Ok(ImportVerdict::Skip);
     }
+    let exists: bool = sqlx::query_scalar(&tables.sql(&format!("SELECT EXISTS (SELECT 1 FROM {table} WHERE id = $1)")))
+        .bind(id)
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(DbError::from)?;
+    Ok(if exists { ImportVerdict::Skip } else { ImportVerdict::Insert })
 }
 
-impl QuestionDao for QuestionDaoImpl {
-    async fn create_question(&self, new_question: NewQuestion) -> Result<Uuid, DbError> {
-        sqlx::query("INSERT INTO questions (title, question) VALUES ($1, $2) returning id")
-            .bind(new_question.title)
-            .bind(new_question.question)
-            .map(|row: PgRow| -> Uuid { row.get("id") })
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| DbError::Creation(e))
+/// Rejects like counts that could not have been reached by liking.
+fn validate_likes(likes: i64) -> Result<(), DbError> {
+    if likes < 0 {
+        return Err(DbError::validation(format!("likes must not be negative, got {likes}")));
     }
+    Ok(())
+}
 
-    async fn get_question(&self, question_id: EntityId) -> Result<Question, DbError> {
-        // Attempt to parse entity id
-        let question_id: Uuid = question_id.try_into().map_err(|e| DbError::InvalidUuid(e))?;
-        sqlx::query_as::<_, Question>("SELECT * FROM questions WHERE id = $1")
-            .bind(question_id)
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| DbError::NotFound(e))
+impl AdminDao for AdminDaoImpl {
+    async fn set_question_likes(&self, question_id: EntityId, likes: i64) -> Result<(), DbError> {
+        self.set_likes("questions", question_id, likes).await
     }
 
-    async fn get_questions(&self) -> Result<Vec<Question>, DbError> {
-        sqlx::query("SELECT * FROM questions")
-            .map(|row| Question::from_row(&row).map_err(|e| DbError::FromRow(e)))
+    async fn set_answer_likes(&self, answer_id: EntityId, likes: i64) -> Result<(), DbError> {
+        self.set_likes("answers", answer_id, likes).await
+    }
+
+    async fn bulk_set_question_likes(&self, pairs: Vec<(EntityId, i64)>) -> Result<BulkUpdate, DbError> {
+        self.bulk_set_likes("questions", pairs).await
+    }
+
+    async fn bulk_set_answer_likes(&self, pairs: Vec<(EntityId, i64)>) -> Result<BulkUpdate, DbError> {
+        self.bulk_set_likes("answers", pairs).await
+    }
+
+    async fn bulk_set_question_views(&self, pairs: Vec<(EntityId, i32)>) -> Result<BulkUpdate, DbError> {
+        // Validate the whole batch before touching the database
+        let mut ids = Vec::with_capacity(pairs.len());
+        let mut views = Vec::with_capacity(pairs.len());
+        for (id, count) in pairs {
+            if count < 0 {
+                return Err(DbError::validation(format!("views must not be negative, got {count}")));
+            }
+            let id = resolve_id(&self.pool, &self.tables, "questions", id).await?;
+            if ids.contains(&id) {
+                return Err(DbError::validation(format!("duplicate id {id}")));
+            }
+            ids.push(id);
+            views.push(count);
+        }
+        let requested = ids.len() as u64;
+        let missing: Vec<Uuid> = sqlx::query_scalar(&self.tables.sql(
+            "WITH input AS ( \
+                SELECT * FROM UNNEST($1::uuid[], $2::integer[]) WITH ORDINALITY AS input (id, views, position) \
+            ), updated AS ( \
+                UPDATE questions SET views = input.views FROM input WHERE questions.id = input.id RETURNING questions.id \
+            ) \
+            SELECT input.id FROM input WHERE NOT EXISTS (SELECT 1 FROM updated WHERE updated.id = input.id) \
+            ORDER BY input.position"
+        ))
+            .bind(ids)
+            .bind(views)
             .fetch_all(&self.pool)
             .await
-            .map_err(|e| DbError::Access(e))?
-            .into_iter()
-            .collect::<Result<Vec<Question>, DbError>>()
+            .map_err(DbError::update)?;
+        Ok(BulkUpdate { updated: requested - missing.len() as u64, missing })
     }
 
-    async fn delete_question(&self, question_id: EntityId) -> Result<Uuid, DbError> {
-        // Attempt to parse entity id
-        let question_id: Uuid = question_id.try_into().map_err(|e| DbError::InvalidUuid(e))?;
-        let mut tx = self.pool.begin().await.map_err(|e| DbError::Access(e))?;
-        // Ensure that a record with the given id exists
-        sqlx::query("SELECT * FROM questions WHERE id = $1")
-            .bind(question_id)
-            .fetch_one(&mut *tx)
+    async fn import_content(&self, content: Vec<QuestionDetailResponse>, dry_run: bool) -> Result<Vec<ImportRecord>, DbError> {
+        if dry_run {
+            return self.dry_run_import(&content).await;
+        }
+        // Check every record first, so that an invalid record stops the import before anything is inserted
+        let checked = content
+            .iter()
+            .map(|detail| {
+                let question = ImportedQuestion::check(&detail.question)?;
+                Ok((question, detail.answers.iter().map(check_imported_answer).collect::<Result<Vec<_>, _>>()?))
+            })
+            .collect::<Result<Vec<_>, DbError>>()?;
+        let mut tx = self.pool.begin().await.map_err(DbError::from)?;
+        let mut records = Vec::new();
+        for (detail, (imported, answers)) in content.iter().zip(checked) {
+            let question = &detail.question;
+            let stats = ContentStats::of(&question.question);
+            let inserted = sqlx::query(&self.tables.sql(
+                "INSERT INTO questions \
+                    (id, title, question, created_at, updated_at, pinned_at, title_normalized, char_count, word_count, content_type) \
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) ON CONFLICT (id) DO NOTHING"
+            ))
+                .bind(imported.id)
+                .bind(&question.title)
+                .bind(&question.question)
+                .bind(imported.created_at)
+                .bind(imported.updated_at)
+                .bind(imported.pinned_at)
+                .bind(normalize_title(&question.title))
+                .bind(stats.char_count)
+                .bind(stats.word_count)
+                .bind(&question.content_type)
+                .execute(&mut *tx)
+                .await
+                .map_err(creation_error)?
+                .rows_affected();
+            records.push(ImportRecord { entity: LikableEntity::Question, id: question.id.clone(), verdict: inserted_verdict(inserted) });
+            let mut replies = Vec::new();
+            for (answer, (answer_id, created_at)) in detail.answers.iter().zip(answers) {
+                let stats = ContentStats::of(&answer.answer);
+                let inserted = sqlx::query(&self.tables.sql(
+                    "INSERT INTO answers \
+                        (id, question_id, answer, created_at, published, approved_at, char_count, word_count, content_type) \
+                    VALUES ($1, $2, $3, $4, true, $4, $5, $6, $7) ON CONFLICT (id) DO NOTHING"
+                ))
+                    .bind(answer_id)
+                    .bind(imported.id)
+                    .bind(&answer.answer)
+                    .bind(created_at)
+                    .bind(stats.char_count)
+                    .bind(stats.word_count)
+                    .bind(&answer.content_type)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(creation_error)?
+                    .rows_affected();
+                records.push(ImportRecord { entity: LikableEntity::Answer, id: answer.id.clone(), verdict: inserted_verdict(inserted) });
+                if let (1, Some(parent_answer_id)) = (inserted, answer.parent_answer_id.as_deref()) {
+                    replies.push((answer_id, parent_answer_id));
+                }
+            }
+            // Replies are restored once every answer exists, as such only when their parent is an answer to the same
+            // question that is not a reply itself, otherwise they are kept as answers to the question
+            for (answer_id, parent_answer_id) in replies {
+                let Ok(parent_answer_id) = Uuid::parse_str(parent_answer_id) else { continue };
+                sqlx::query(&self.tables.sql(
+                    "UPDATE answers SET parent_answer_id = $1 WHERE id = $2 \
+                    AND EXISTS (SELECT 1 FROM answers WHERE id = $1 AND question_id = $3 AND parent_answer_id IS NULL)"
+                ))
+                    .bind(parent_answer_id)
+                    .bind(answer_id)
+                    .bind(imported.id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(DbError::update)?;
+            }
+            // The pinned and accepted answers can only be restored once the answers exist, and only for a question
+            // imported now. Reputation is not exported, so restoring an acceptance earns none
+            if inserted == 1 && (imported.pinned_answer_id.is_some() || imported.accepted_answer_id.is_some()) {
+                sqlx::query(&self.tables.sql(
+                    "UPDATE questions SET \
+                        pinned_answer_id = (SELECT id FROM answers WHERE id = $1 AND question_id = $3), \
+                        accepted_answer_id = (SELECT id FROM answers WHERE id = $2 AND question_id = $3) \
+                    WHERE id = $3"
+                ))
+                    .bind(imported.pinned_answer_id)
+                    .bind(imported.accepted_answer_id)
+                    .bind(imported.id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(DbError::update)?;
+            }
+        }
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(records)
+    }
+
+    async fn anonymize_author(&self, author_id: EntityId, scrub_content: bool) -> Result<AnonymizeReport, DbError> {
+        let author_id: Uuid = author_id.try_into().map_err(DbError::invalid_id)?;
+        let mut tx = self.pool.begin().await.map_err(DbError::from)?;
+        let scrubbed = ContentStats::of(DELETED_CONTENT);
+        let answers = sqlx::query(&self.tables.sql(
+            "UPDATE answers SET author_id = NULL, answer = CASE WHEN $2 THEN $3 ELSE answer END, \
+            char_count = CASE WHEN $2 THEN $4 ELSE char_count END, word_count = CASE WHEN $2 THEN $5 ELSE word_count END \
+            WHERE author_id = $1"
+        ))
+            .bind(author_id)
+            .bind(scrub_content)
+            .bind(DELETED_CONTENT)
+            .bind(scrubbed.char_count)
+            .bind(scrubbed.word_count)
+            .execute(&mut *tx)
             .await
-            .map_err(|e| DbError::NotFound(e))?;
-        // Now attempt to delete the record, and commit the changes if successful
-        match sqlx::query("DELETE FROM questions WHERE id = $1 RETURNING id")
-            .bind(question_id)
-            .map(|row: PgRow| row.get("id"))
-            .fetch_one(&mut *tx)
+            .map_err(DbError::update)?
+            .rows_affected();
+        // Delete the user last, once nothing refers to it
+        let users = sqlx::query(&self.tables.sql("DELETE FROM users WHERE id = $1"))
+            .bind(author_id)
+            .execute(&mut *tx)
             .await
-            .map_err(|e| DbError::Deletion(e))
-        {
-            Ok(id) => {
-                // Commit the transaction
-                tx.commit().await.map_err(|e| DbError::Access(e))?;
-                Ok(id)
-            },
-            Err(e) => Err(e)
+            .map_err(DbError::deletion)?
+            .rows_affected();
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(AnonymizeReport { answers, users })
+    }
+
+    async fn transfer_question_author(&self, question_id: EntityId, new_author: EntityId) -> Result<(), DbError> {
+        self.transfer_author("questions", question_id, new_author).await
+    }
+
+    async fn transfer_answer_author(&self, answer_id: EntityId, new_author: EntityId) -> Result<(), DbError> {
+        self.transfer_author("answers", answer_id, new_author).await
+    }
+
+    async fn transfer_all_content(&self, old_author: EntityId, new_author: EntityId) -> Result<TransferReport, DbError> {
+        let old_author: Uuid = old_author.try_into().map_err(DbError::invalid_id)?;
+        let new_author: Uuid = new_author.try_into().map_err(DbError::invalid_id)?;
+        if old_author == new_author {
+            return Err(DbError::validation(String::from("content cannot be transferred to its own author")));
         }
+        let mut tx = self.pool.begin().await.map_err(DbError::from)?;
+        ensure_user(&mut tx, &self.tables, new_author).await?;
+        let questions = Self::transfer_all(&mut tx, &self.tables, "questions", old_author, new_author).await?;
+        let answers = Self::transfer_all(&mut tx, &self.tables, "answers", old_author, new_author).await?;
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(TransferReport { questions, answers })
     }
 
-    async fn increment_question_likes(&self, question_id: EntityId) -> Result<(), DbError> {
-        // Attempt to parse entity id
-        let question_id: Uuid = question_id.try_into().map_err(|e| DbError::InvalidUuid(e))?;
-        // Ensure that both transactions occur by using a Transaction
-        let mut tx = self.pool.begin().await.map_err(|e| DbError::Access(e))?;
-        let likes = sqlx::query("SELECT likes FROM questions WHERE id = $1")
-            .bind(question_id)
-            .map(|row: PgRow| row.get::<i32, &str>("likes"))
+    async fn merge_questions(&self, duplicate_id: EntityId, target_id: EntityId) -> Result<MergeReport, DbError> {
+        let duplicate_id = resolve_id(&self.pool, &self.tables, "questions", duplicate_id).await?;
+        let target_id = resolve_id(&self.pool, &self.tables, "questions", target_id).await?;
+        if duplicate_id == target_id {
+            return Err(DbError::validation(String::from("a question cannot be merged into itself")));
+        }
+        let mut tx = self.pool.begin().await.map_err(DbError::from)?;
+        // Lock both questions, in a consistent order so concurrent merges cannot deadlock
+        let locked: Vec<(Uuid, i64)> = sqlx::query_as(&self.tables.sql("SELECT id, likes FROM questions WHERE id = ANY($1) ORDER BY id FOR UPDATE"))
+            .bind([duplicate_id, target_id])
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(DbError::from)?;
+        let Some(&(_, target_likes)) = locked.iter().find(|(id, _)| *id == target_id).filter(|_| locked.len() == 2) else {
+            return Err(DbError::not_found("question", if locked.iter().any(|(id, _)| *id == target_id) { duplicate_id } else { target_id }));
+        };
+        let answers_moved = sqlx::query(&self.tables.sql("UPDATE answers SET question_id = $2 WHERE question_id = $1"))
+            .bind(duplicate_id)
+            .bind(target_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(DbError::update)?
+            .rows_affected();
+        // Subscribers of both questions keep their existing subscription to the target
+        let subscriptions_moved = sqlx::query(&self.tables.sql(
+            "INSERT INTO subscriptions (user_token, question_id, last_seen_at) \
+            SELECT user_token, $2, last_seen_at FROM subscriptions WHERE question_id = $1 \
+            ON CONFLICT (user_token, question_id) DO NOTHING"
+        ))
+            .bind(duplicate_id)
+            .bind(target_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(DbError::update)?
+            .rows_affected();
+        // The answers seen through the duplicate stay seen through the target, which every subscriber now follows
+        sqlx::query(&self.tables.sql("UPDATE subscription_seen_answers SET question_id = $2 WHERE question_id = $1"))
+            .bind(duplicate_id)
+            .bind(target_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(DbError::update)?;
+        // Deleting the duplicate also removes its remaining subscriptions
+        let duplicate_likes: i64 = sqlx::query_scalar(&self.tables.sql("DELETE FROM questions WHERE id = $1 RETURNING likes"))
+            .bind(duplicate_id)
             .fetch_one(&mut *tx)
             .await
-            .map_err(|e| DbError::NotFound(e))?;
-        match sqlx::query("UPDATE questions SET likes = $1 WHERE id = $2")
-            .bind(likes + 1)
-            .bind(question_id)
+            .map_err(DbError::deletion)?;
+        // The target saturates rather than overflowing, so only the likes that fit are added
+        let likes_added = duplicate_likes.min(i64::MAX.saturating_sub(target_likes));
+        // The duplicate's likes are recorded as a single change to the target
+        sqlx::query(&self.tables.sql(
+            "WITH updated AS ( \
+                UPDATE questions SET likes = likes + $2 WHERE id = $1 RETURNING id \
+            ) \
+            INSERT INTO like_events (entity_type, entity_id, delta) \
+            SELECT 'question', id, $2 FROM updated WHERE $2 <> 0"
+        ))
+            .bind(target_id)
+            .bind(likes_added)
             .execute(&mut *tx)
             .await
-            .map_err(|e| DbError::Update(e))
-        {
-            Ok(_) => tx.commit().await.map_err(|e| DbError::Commit(e)),
-            Err(e) => Err(e)
+            .map_err(DbError::update)?;
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(MergeReport { duplicate_id, target_id, answers_moved, subscriptions_moved, likes_added })
+    }
+
+    async fn reindex(&self) -> Result<Vec<&'static str>, DbError> {
+        for table in TABLES {
+            sqlx::query(&self.tables.sql(&format!("REINDEX TABLE {table}")))
+                .execute(&self.pool)
+                .await
+                .map_err(DbError::from)?;
+        }
+        Ok(TABLES.to_vec())
+    }
+
+    async fn recompute_content_stats(&self) -> Result<u64, DbError> {
+        let questions = self.recompute_table_stats("questions", "question", CONTENT_STATS_BATCH_SIZE).await?;
+        let answers = self.recompute_table_stats("answers", "answer", CONTENT_STATS_BATCH_SIZE).await?;
+        Ok(questions + answers)
+    }
+
+    async fn get_like_events(&self, entity_id: EntityId, since: DateTime<Utc>) -> Result<Vec<LikeEvent>, DbError> {
+        let entity_id = Uuid::try_from(&entity_id).map_err(DbError::invalid_id)?;
+        sqlx::query_as::<_, LikeEvent>(&self.tables.sql(
+            "SELECT * FROM like_events WHERE entity_id = $1 AND occurred_at >= $2 ORDER BY occurred_at, id"
+        ))
+            .bind(entity_id)
+            .bind(since)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::from)
+    }
+
+    async fn purge_deleted_answers(&self, before: DateTime<Utc>) -> Result<PurgeReport, DbError> {
+        let mut tx = self.pool.begin().await.map_err(DbError::from)?;
+        // Each pass deletes the tombstones without replies, so a tombstone whose replies were purged goes in the next
+        let mut answers = 0;
+        loop {
+            let deleted = sqlx::query(&self.tables.sql(
+                "DELETE FROM answers WHERE deleted_at < $1 \
+                AND NOT EXISTS (SELECT 1 FROM answers replies WHERE replies.parent_answer_id = answers.id)"
+            ))
+                .bind(before)
+                .execute(&mut *tx)
+                .await
+                .map_err(DbError::deletion)?
+                .rows_affected();
+            if deleted == 0 {
+                break;
+            }
+            answers += deleted;
         }
+        let kept_with_replies: i64 = sqlx::query_scalar(&self.tables.sql("SELECT COUNT(*) FROM answers WHERE deleted_at < $1"))
+            .bind(before)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(DbError::from)?;
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(PurgeReport { before, answers, kept_with_replies: kept_with_replies as u64 })
+    }
+
+    async fn recount_likes(&self) -> Result<RecountReport, DbError> {
+        let mut tx = self.pool.begin().await.map_err(DbError::from)?;
+        let mut corrected = [0; 2];
+        for (table, corrected) in ["questions", "answers"].into_iter().zip(&mut corrected) {
+            // Likes wait until the transaction ends, so none recorded after the sum is read is overwritten
+            sqlx::query(&self.tables.sql(&format!("LOCK TABLE {table} IN SHARE ROW EXCLUSIVE MODE")))
+                .execute(&mut *tx)
+                .await
+                .map_err(DbError::from)?;
+            *corrected = sqlx::query(&self.tables.sql(&format!(
+                "UPDATE {table} SET likes = recorded.likes \
+                FROM ( \
+                    SELECT entity_id, SUM(delta)::bigint AS likes FROM like_events WHERE entity_type = $1 \
+                    GROUP BY entity_id \
+                ) recorded \
+                WHERE {table}.id = recorded.entity_id AND {table}.likes <> recorded.likes"
+            )))
+                .bind(like_entity_type(table))
+                .execute(&mut *tx)
+                .await
+                .map_err(DbError::update)?
+                .rows_affected();
+        }
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(RecountReport { questions: corrected[0], answers: corrected[1] })
     }
 }
 
-pub struct AnswerDaoImpl {
+/// A `CategoryDao` backed by a Postgres connection pool.
+pub struct CategoryDaoImpl {
     pool: PgPool,
+    tables: Tables,
+    limits: ContentLimits,
 }
 
-impl AnswerDaoImpl {
-    fn new(pool: PgPool) -> Self {
-        Self { pool }
+impl CategoryDaoImpl {
+    /// Creates the data access object, using the default `ContentLimits`.
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool, tables: Tables::default(), limits: ContentLimits::default() }
+    }
+
+    /// Places the tables the data access object reads and writes in `schema`, which should have been migrated with
+    /// `migrations::run_in_schema`.
+    pub fn with_schema(mut self, schema: SchemaName) -> Self {
+        self.tables = Tables::in_schema(schema);
+        self
+    }
+
+    /// Sets the `ContentLimits` the names of new categories are validated against.
+    pub fn with_limits(mut self, limits: ContentLimits) -> Self {
+        self.limits = limits;
+        self
     }
 }
 
-impl AnswerDao for AnswerDaoImpl {
-    async fn create_answer(&self, new_answer: NewAnswer) -> Result<Uuid, DbError> {
-        // First parse question_id
-        let question_id: Uuid = Uuid::parse_str(new_answer.question_id.as_str()).map_err(|_| DbError::InvalidUuid("invalid uuid"))?;
-        // Get a transaction
-        let mut tx = self.pool.begin().await.map_err(|e| DbError::Access(e))?;
-        // Ensure that the associated question actually exists
-        sqlx::query("SELECT * FROM questions WHERE id = $1")
-            .bind(question_id)
-            .map(|_row| ())
+/// Ensures a category exists, reporting a missing category as `DbError::NotFound`.
+async fn ensure_category(tx: &mut Transaction<'_, Postgres>, tables: &Tables, category_id: Uuid) -> Result<(), DbError> {
+    sqlx::query(&tables.sql("SELECT id FROM categories WHERE id = $1"))
+        .bind(category_id)
+        .fetch_one(&mut **tx)
+        .await
+        .map(|_| ())
+        .map_err(missing("category", category_id))
+}
+
+/// Maps errors from linking questions, classifying an existing identical link as `DbError::Conflict` and a
+/// missing question as `DbError::NotFound`.
+fn link_error(e: sqlx::Error) -> DbError {
+    // SQLSTATE codes for unique_violation and foreign_key_violation
+    match e.as_database_error().and_then(|db_err| db_err.code()).as_deref() {
+        Some("23505") => DbError::conflict(String::from("the questions are already linked this way")),
+        Some("23503") => DbError::NotFound { entity: "question", id: None, source: Some(Box::new(e)) },
+        _ => creation_error(e),
+    }
+}
+
+/// Maps errors from writing a category, classifying a duplicate sibling name as `DbError::Conflict`.
+/// Any other error is mapped with `otherwise`.
+fn category_error(e: sqlx::Error, otherwise: fn(sqlx::Error) -> DbError) -> DbError {
+    // SQLSTATE code for unique_violation
+    match e.as_database_error() {
+        Some(db_err) if db_err.code().is_some_and(|code| code == "23505") => {
+            DbError::conflict(String::from("a sibling category already has this name"))
+        }
+        _ => otherwise(e),
+    }
+}
+
+impl CategoryDao for CategoryDaoImpl {
+    async fn create_category(&self, new_category: NewCategory) -> Result<Category, DbError> {
+        // Validate the name and parse the parent id first
+        new_category.validate(&self.limits)?;
+        let parent_id: Option<Uuid> = new_category.parent_id
+            .map(|id| EntityId::new(id).try_into())
+            .transpose()
+            .map_err(DbError::invalid_id)?;
+        let mut tx = self.pool.begin().await.map_err(DbError::from)?;
+        if let Some(parent_id) = parent_id {
+            ensure_category(&mut tx, &self.tables, parent_id).await?;
+        }
+        let category = sqlx::query_as::<_, Category>(&self.tables.sql("INSERT INTO categories (name, parent_id) VALUES ($1, $2) RETURNING *"))
+            .bind(new_category.name)
+            .bind(parent_id)
             .fetch_one(&mut *tx)
             .await
-            .map_err(|e| DbError::NotFound(e))?;
-        // If we make it to this line, we know the associated question exists in the database
-        match sqlx::query("INSERT INTO answers (question_id, answer) VALUES ($1, $2) returning id")
-            .bind(question_id)
-            .bind(new_answer.answer)
-            .map(|row| row.get("id"))
-            .fetch_one(&mut *tx)
+            .map_err(|e| category_error(e, creation_error))?;
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(category)
+    }
+
+    async fn set_category_parent(&self, category_id: EntityId, parent_id: Option<EntityId>) -> Result<Category, DbError> {
+        // Parse entity ids first
+        let category_id = Uuid::try_from(&category_id).map_err(DbError::invalid_id)?;
+        let parent_id: Option<Uuid> = parent_id
+            .map(|id| Uuid::try_from(&id))
+            .transpose()
+            .map_err(DbError::invalid_id)?;
+        let mut tx = self.pool.begin().await.map_err(DbError::from)?;
+        // Block concurrent moves, which could otherwise combine into a cycle neither would create alone
+        sqlx::query(&self.tables.sql("LOCK TABLE categories IN SHARE ROW EXCLUSIVE MODE"))
+            .execute(&mut *tx)
             .await
-            .map_err(|e| DbError::Creation(e))
-        {
-            Ok(id) => {
-                // commit the transaction
-                tx.commit().await.map_err(|e| DbError::Access(e))?;
-                Ok(id)
+            .map_err(DbError::from)?;
+        ensure_category(&mut tx, &self.tables, category_id).await?;
+        if let Some(parent_id) = parent_id {
+            ensure_category(&mut tx, &self.tables, parent_id).await?;
+            // Walk up from the new parent, the move creates a cycle if the category is one of its ancestors
+            let creates_cycle: bool = sqlx::query_scalar(&self.tables.sql(
+                "WITH RECURSIVE ancestors AS ( \
+                    SELECT id, parent_id FROM categories WHERE id = $1 \
+                    UNION ALL \
+                    SELECT categories.id, categories.parent_id FROM categories \
+                    JOIN ancestors ON categories.id = ancestors.parent_id \
+                ) \
+                SELECT EXISTS (SELECT 1 FROM ancestors WHERE id = $2)"
+            ))
+                .bind(parent_id)
+                .bind(category_id)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(DbError::from)?;
+            if creates_cycle {
+                return Err(DbError::conflict(String::from("a category cannot be moved under itself or its descendants")));
             }
-            Err(e) => Err(e)
         }
+        let category = sqlx::query_as::<_, Category>(&self.tables.sql("UPDATE categories SET parent_id = $2 WHERE id = $1 RETURNING *"))
+            .bind(category_id)
+            .bind(parent_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| category_error(e, edit_error))?;
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(category)
     }
 
-    async fn get_answer(&self, answer_id: EntityId) -> Result<Answer, DbError> {
-        // Parse answer id
-        let answer_id: Uuid = answer_id.try_into().map_err(|e| DbError::InvalidUuid(e))?;
-        // attempt to read answer from database
-        sqlx::query_as::<_, Answer>("SELECT * FROM answers WHERE id = $1")
-            .bind(answer_id)
-            .fetch_one(&self.pool)
+    async fn get_category_tree(&self) -> Result<Vec<CategoryNode>, DbError> {
+        let categories = sqlx::query_as::<_, Category>(&self.tables.sql("SELECT * FROM categories ORDER BY name, id"))
+            .fetch_all(&self.pool)
             .await
-            .map_err(|e| DbError::Access(e))
+            .map_err(DbError::from)?;
+        Ok(CategoryNode::build_tree(categories))
     }
 
-    async fn get_answers(&self, question_id: EntityId) -> Result<Vec<Answer>, DbError> {
+    async fn assign_category(&self, question_id: EntityId, category_id: Option<EntityId>) -> Result<Question, DbError> {
+        // Parse entity ids first
+        let question_id = resolve_id(&self.pool, &self.tables, "questions", question_id).await?;
+        let category_id: Option<Uuid> = category_id
+            .map(|id| Uuid::try_from(&id))
+            .transpose()
+            .map_err(DbError::invalid_id)?;
+        let mut tx = self.pool.begin().await.map_err(DbError::from)?;
+        if let Some(category_id) = category_id {
+            ensure_category(&mut tx, &self.tables, category_id).await?;
+        }
+        let question = sqlx::query_as::<_, Question>(&self.tables.sql("UPDATE questions SET category_id = $2 WHERE id = $1 RETURNING *"))
+            .bind(question_id)
+            .bind(category_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(DbError::update)?;
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(question)
+    }
+
+    async fn get_questions_in_category(&self, category_id: EntityId, include_descendants: bool) -> Result<Vec<Question>, DbError> {
         // Parse entity id first
-        let question_id: Uuid = question_id.try_into().map_err(|e| DbError::InvalidUuid(e))?;
-        // Attempt to read all associated answers from database
-        sqlx::query("SELECT * FROM answers WHERE question_id = $1")
+        let category_id = Uuid::try_from(&category_id).map_err(DbError::invalid_id)?;
+        let mut tx = self.pool.begin().await.map_err(DbError::from)?;
+        // Ensure the category exists, so a missing category is distinguishable from an empty one
+        ensure_category(&mut tx, &self.tables, category_id).await?;
+        let questions = sqlx::query_as::<_, Question>(&self.tables.sql(&format!(
+            "WITH RECURSIVE descendants AS ( \
+                SELECT id FROM categories WHERE id = $1 \
+                UNION ALL \
+                SELECT categories.id FROM categories \
+                JOIN descendants ON categories.parent_id = descendants.id \
+                WHERE $2 \
+            ) \
+            SELECT questions.* FROM questions \
+            WHERE category_id IN (SELECT id FROM descendants) \
+            ORDER BY {PINNED_FIRST}, created_at, id"
+        )))
+            .bind(category_id)
+            .bind(include_descendants)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(DbError::from)?;
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(questions)
+    }
+}
+
+/// A `TagDao` backed by a Postgres connection pool.
+pub struct TagDaoImpl {
+    pool: PgPool,
+    tables: Tables,
+    clock: Arc<dyn Clock>,
+}
+
+impl TagDaoImpl {
+    /// Creates the data access object, using the system clock.
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool, tables: Tables::default(), clock: Arc::new(SystemClock) }
+    }
+
+    /// Places the tables the data access object reads and writes in `schema`, which should have been migrated with
+    /// `migrations::run_in_schema`.
+    pub fn with_schema(mut self, schema: SchemaName) -> Self {
+        self.tables = Tables::in_schema(schema);
+        self
+    }
+
+    /// Sets the `Clock` recording when questions are labelled with tags.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+}
+
+/// Trims the name of a tag, rejecting names that are blank or longer than `MAX_TAG_LENGTH` characters.
+fn validate_tag_name(name: &str) -> Result<&str, DbError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(DbError::validation(String::from("tag must not be blank")));
+    }
+    if name.chars().count() > MAX_TAG_LENGTH {
+        return Err(DbError::validation(format!("tag must be at most {MAX_TAG_LENGTH} characters")));
+    }
+    Ok(name)
+}
+
+/// The `ILIKE` pattern matching text containing `term`, rejecting a blank term.
+fn search_pattern(term: &str) -> Result<String, DbError> {
+    let term = term.trim();
+    if term.is_empty() {
+        return Err(DbError::validation(String::from("search term must not be empty")));
+    }
+    Ok(format!("%{}%", escape_like(term)))
+}
+
+/// Escapes the wildcards of `LIKE`, and its escape character, so that `text` only matches itself.
+fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+impl TagDao for TagDaoImpl {
+    async fn tag_question(&self, question_id: EntityId, name: &str) -> Result<Tag, DbError> {
+        // Validate the name and parse the question id first
+        let name = validate_tag_name(name)?;
+        let question_id = resolve_id(&self.pool, &self.tables, "questions", question_id).await?;
+        let mut tx = self.pool.begin().await.map_err(DbError::from)?;
+        // Lock the question, so it cannot be deleted before it is labelled
+        sqlx::query(&self.tables.sql("SELECT id FROM questions WHERE id = $1 FOR SHARE"))
             .bind(question_id)
-            .fetch_all(&self.pool)
+            .fetch_one(&mut *tx)
             .await
-            .map_err(|e| DbError::Access(e))?
-            .into_iter()
-            .map(|row| Answer::from_row(&row).map_err(|e| DbError::FromRow(e)))
-            .collect::<Result<Vec<Answer>, DbError>>()
+            .map_err(missing("question", question_id))?;
+        // Updating the name to itself returns the existing tag, keeping the spelling of its first use
+        let tag = sqlx::query_as::<_, Tag>(&self.tables.sql(
+            "INSERT INTO tags (name, created_at) VALUES ($1, $2) \
+            ON CONFLICT ((lower(name))) DO UPDATE SET name = tags.name RETURNING *"
+        ))
+            .bind(name)
+            .bind(self.clock.now())
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(creation_error)?;
+        sqlx::query(&self.tables.sql("INSERT INTO question_tags (question_id, tag_id, tagged_at) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING"))
+            .bind(question_id)
+            .bind(tag.id())
+            .bind(self.clock.now())
+            .execute(&mut *tx)
+            .await
+            .map_err(creation_error)?;
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(tag)
     }
 
-    async fn delete_answer(&self, answer_id: EntityId) -> Result<Uuid, DbError> {
-        // Parse entity id
-        let answer_id: Uuid = answer_id.try_into().map_err(|e| DbError::InvalidUuid(e))?;
-        // Attempt to execute query
-        match sqlx::query("DELETE * FROM answers WHERE id = $1")
-            .bind(answer_id)
+    async fn untag_question(&self, question_id: EntityId, name: &str) -> Result<bool, DbError> {
+        let question_id = resolve_id(&self.pool, &self.tables, "questions", question_id).await?;
+        sqlx::query(&self.tables.sql(
+            "DELETE FROM question_tags USING tags \
+            WHERE question_tags.tag_id = tags.id AND question_tags.question_id = $1 AND lower(tags.name) = lower($2)"
+        ))
+            .bind(question_id)
+            .bind(name.trim())
             .execute(&self.pool)
             .await
-            .map_err(|e| DbError::Access(e))
-        {
-            Ok(_) => Ok(answer_id),
-            Err(e) => Err(e)
+            .map(|res| res.rows_affected() > 0)
+            .map_err(DbError::deletion)
+    }
+
+    async fn suggest_tags(&self, prefix: &str, limit: u32) -> Result<Vec<TagSuggestion>, DbError> {
+        let prefix = prefix.trim();
+        if prefix.is_empty() {
+            return Err(DbError::validation(String::from("prefix must not be blank")));
         }
+        if limit == 0 {
+            return Err(DbError::validation(String::from("limit must be at least 1")));
+        }
+        sqlx::query_as::<_, TagSuggestion>(&self.tables.sql(
+            "SELECT tags.name, COUNT(question_tags.question_id) AS usage_count FROM tags \
+            LEFT JOIN question_tags ON question_tags.tag_id = tags.id \
+            WHERE lower(tags.name) LIKE lower($1) || '%' ESCAPE '\\' \
+            GROUP BY tags.id ORDER BY usage_count DESC, lower(tags.name) LIMIT $2"
+        ))
+            .bind(escape_like(prefix))
+            .bind(i64::from(limit))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::from)
     }
 
-    async fn get_all_answers(&self) -> Result<Vec<Answer>, DbError> {
-        // Execute query
-        sqlx::query_as::<_, Answer>("SELECT * FROM answers")
+    async fn get_tag_stats(&self) -> Result<Vec<TagStats>, DbError> {
+        sqlx::query_as::<_, TagStats>(&self.tables.sql(
+            "SELECT tags.name, COUNT(question_tags.question_id) AS usage_count, MAX(question_tags.tagged_at) AS last_used_at \
+            FROM tags LEFT JOIN question_tags ON question_tags.tag_id = tags.id \
+            GROUP BY tags.id ORDER BY usage_count DESC, lower(tags.name)"
+        ))
             .fetch_all(&self.pool)
             .await
-            .map_err(|e| DbError::Access(e))
+            .map_err(DbError::from)
     }
 
-    async fn increment_answer_likes(&self, answer_id: EntityId) -> Result<(), DbError> {
-        // Parse entity id
-        let answer_id: Uuid = answer_id.try_into().map_err(|e| DbError::InvalidUuid(e))?;
-        // Attempt to execute query, use a transaction
-        let mut tx = self.pool.begin().await.map_err(|e| DbError::Access(e))?;
-        let likes = sqlx::query("SELECT likes FROM answers WHERE id = $1")
-            .bind(answer_id)
-            .map(|row| row.get::<i32, &str>("id"))
+    async fn get_acceptance_rate_by_tag(&self, min_questions: i64) -> Result<Vec<TagAcceptance>, DbError> {
+        // Tags labelling no question have no rate, so the inner join leaves them out
+        sqlx::query_as::<_, TagAcceptance>(&self.tables.sql(
+            "SELECT name, question_count, accepted_count, accepted_count::float8 / question_count AS acceptance_rate \
+            FROM ( \
+                SELECT tags.name, COUNT(*) AS question_count, \
+                    COUNT(*) FILTER ( \
+                        WHERE questions.accepted_answer_id IS NOT NULL OR questions.archived_accepted_answer_id IS NOT NULL \
+                    ) AS accepted_count \
+                FROM tags \
+                JOIN question_tags ON question_tags.tag_id = tags.id \
+                JOIN questions ON questions.id = question_tags.question_id \
+                GROUP BY tags.id HAVING COUNT(*) >= $1 \
+            ) AS counts \
+            ORDER BY acceptance_rate DESC, question_count DESC, lower(name)"
+        ))
+            .bind(min_questions)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::from)
+    }
+
+    async fn rename_tag(&self, from: &str, to: &str) -> Result<RetagReport, DbError> {
+        let (from, to) = (validate_tag_name(from)?, validate_tag_name(to)?);
+        let mut tx = self.pool.begin().await.map_err(DbError::from)?;
+        // Lock both tags, so neither can be labelled or renamed until the questions have been moved
+        let tags: Vec<(Uuid, bool)> = sqlx::query_as(&self.tables.sql(
+            "SELECT id, lower(name) = lower($1) FROM tags WHERE lower(name) IN (lower($1), lower($2)) ORDER BY id FOR UPDATE"
+        ))
+            .bind(from)
+            .bind(to)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(DbError::from)?;
+        let from_id = tags.iter()
+            .find_map(|&(id, is_from)| is_from.then_some(id))
+            .ok_or_else(|| DbError::not_found("tag", from))?;
+        let questions_affected: i64 = sqlx::query_scalar(&self.tables.sql("SELECT COUNT(*) FROM question_tags WHERE tag_id = $1"))
+            .bind(from_id)
             .fetch_one(&mut *tx)
             .await
-            .map_err(|e| DbError::NotFound(e))?;
-        // Attempt to update database
-        match sqlx::query("UPDATE answers SET likes = $1 WHERE id = $2")
-            .bind(likes + 1)
-            .bind(answer_id)
-            .execute(&mut *tx)
+            .map_err(DbError::from)?;
+        let report = match tags.iter().find(|&&(_, is_from)| !is_from) {
+            // The names only differ in case, if at all
+            None if from.to_lowercase() == to.to_lowercase() => RetagReport::default(),
+            None => {
+                sqlx::query(&self.tables.sql("UPDATE tags SET name = $1 WHERE id = $2"))
+                    .bind(to)
+                    .bind(from_id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(edit_error)?;
+                RetagReport { questions_affected: questions_affected as u64, already_tagged: 0 }
+            }
+            Some(&(to_id, _)) => {
+                // Questions already labelled with the new name keep their label, the old labels are removed with the tag
+                let moved = sqlx::query(&self.tables.sql(
+                    "INSERT INTO question_tags (question_id, tag_id, tagged_at) \
+                    SELECT question_id, $2, tagged_at FROM question_tags WHERE tag_id = $1 ON CONFLICT DO NOTHING"
+                ))
+                    .bind(from_id)
+                    .bind(to_id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(DbError::update)?
+                    .rows_affected();
+                sqlx::query(&self.tables.sql("DELETE FROM tags WHERE id = $1"))
+                    .bind(from_id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(DbError::deletion)?;
+                RetagReport { questions_affected: questions_affected as u64, already_tagged: questions_affected as u64 - moved }
+            }
+        };
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(report)
+    }
+}
+
+/// A `LikeDao` backed by a Postgres connection pool.
+pub struct LikeDaoImpl {
+    pool: PgPool,
+    tables: Tables,
+    clock: Arc<dyn Clock>,
+    reputation: ReputationConfig,
+}
+
+impl LikeDaoImpl {
+    /// Creates the data access object, using the system clock and the default `ReputationConfig`.
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool, tables: Tables::default(), clock: Arc::new(SystemClock), reputation: ReputationConfig::default() }
+    }
+
+    /// Places the tables the data access object reads and writes in `schema`, which should have been migrated with
+    /// `migrations::run_in_schema`.
+    pub fn with_schema(mut self, schema: SchemaName) -> Self {
+        self.tables = Tables::in_schema(schema);
+        self
+    }
+
+    /// Sets the `Clock` recording when like counters change.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Sets the `ReputationConfig` deciding the reputation authors gain and lose as their content is liked and
+    /// unliked.
+    pub fn with_reputation(mut self, reputation: ReputationConfig) -> Self {
+        self.reputation = reputation;
+        self
+    }
+
+    /// Applies a single operation of a batch within its transaction.
+    async fn apply_like_op(&self, tx: &mut Transaction<'_, Postgres>, user_token: &str, op: LikeOp) -> Result<LikeOutcome, DbError> {
+        let (table, entity_type, lock, count, weight) = match op.entity {
+            LikableEntity::Question => (
+                "questions",
+                "question",
+                "SELECT NULL::text FROM questions WHERE id = $1 FOR UPDATE",
+                "WITH counted AS ( \
+                    UPDATE questions SET likes = likes + $2, last_activity_at = $3 WHERE id = $1 RETURNING id \
+                ) \
+                INSERT INTO like_events (entity_type, entity_id, delta, occurred_at, source_token) \
+                SELECT 'question', id, $2, $3, $4 FROM counted",
+                self.reputation.question_liked,
+            ),
+            LikableEntity::Answer => (
+                "answers",
+                "answer",
+                "SELECT CASE \
+                    WHEN NOT published THEN 'drafts cannot be liked until they are published' \
+                    WHEN deleted_at IS NOT NULL THEN 'deleted answers cannot be liked' \
+                END FROM answers WHERE id = $1 FOR UPDATE",
+                "WITH counted AS ( \
+                    UPDATE answers SET likes = likes + $2 WHERE id = $1 RETURNING id, question_id \
+                ), touched AS ( \
+                    UPDATE questions SET last_activity_at = $3 WHERE id IN (SELECT question_id FROM counted) \
+                ) \
+                INSERT INTO like_events (entity_type, entity_id, delta, occurred_at, source_token) \
+                SELECT 'answer', id, $2, $3, $4 FROM counted",
+                self.reputation.answer_liked,
+            ),
+        };
+        let id = match resolve_id(&mut **tx, &self.tables, table, op.id).await {
+            Ok(id) => id,
+            Err(DbError::NotFound { .. }) => return Ok(LikeOutcome::EntityMissing),
+            Err(DbError::InvalidId { reason, .. }) => return Ok(LikeOutcome::Invalid(String::from(reason))),
+            Err(e) => return Err(e),
+        };
+        // Lock the entity, so it cannot be deleted while it is counted and concurrent batches of the user apply in turn
+        let unlikable: Option<Option<String>> = sqlx::query_scalar(&self.tables.sql(lock))
+            .bind(id)
+            .fetch_optional(&mut **tx)
             .await
-            .map_err(|e| DbError::Update(e))
-        {
-            Ok(_) => tx.commit().await.map_err(|e| DbError::Commit(e)),
-            Err(e) => Err(e)
+            .map_err(DbError::from)?;
+        match unlikable {
+            None => return Ok(LikeOutcome::EntityMissing),
+            Some(Some(reason)) => return Ok(LikeOutcome::Invalid(reason)),
+            Some(None) => {}
+        }
+        let previous: Option<(bool, DateTime<Utc>)> = sqlx::query_as(&self.tables.sql(
+            "SELECT liked, client_ts FROM likes WHERE entity_type = $1 AND entity_id = $2 AND user_token = $3"
+        ))
+            .bind(entity_type)
+            .bind(id)
+            .bind(user_token)
+            .fetch_optional(&mut **tx)
+            .await
+            .map_err(DbError::from)?;
+        let liked = op.op == LikeAction::Like;
+        let was_liked = match previous {
+            // The operation is a replay, or was overtaken by a later one
+            Some((_, client_ts)) if client_ts >= op.client_ts => return Ok(LikeOutcome::AlreadyApplied),
+            Some((was_liked, _)) => was_liked,
+            None => false,
+        };
+        // The latest operation is stored even when it changes nothing, so older operations synced later are ignored
+        sqlx::query(&self.tables.sql(
+            "INSERT INTO likes (entity_type, entity_id, user_token, liked, client_ts) VALUES ($1, $2, $3, $4, $5) \
+            ON CONFLICT (entity_type, entity_id, user_token) DO UPDATE SET liked = EXCLUDED.liked, client_ts = EXCLUDED.client_ts"
+        ))
+            .bind(entity_type)
+            .bind(id)
+            .bind(user_token)
+            .bind(liked)
+            .bind(op.client_ts)
+            .execute(&mut **tx)
+            .await
+            .map_err(edit_error)?;
+        if liked == was_liked {
+            return Ok(LikeOutcome::AlreadyApplied);
+        }
+        sqlx::query(&self.tables.sql(count))
+            .bind(id)
+            .bind(if liked { 1i64 } else { -1 })
+            .bind(self.clock.now())
+            .bind(user_token)
+            .execute(&mut **tx)
+            .await
+            .map_err(edit_error)?;
+        adjust_reputation(tx, &self.tables, table, id, if liked { weight } else { -weight }).await?;
+        Ok(LikeOutcome::Applied)
+    }
+}
+
+impl LikeDao for LikeDaoImpl {
+    async fn apply_like_batch(&self, user_token: &str, ops: Vec<LikeOp>) -> Result<LikeBatchReport, DbError> {
+        let mut tx = self.pool.begin().await.map_err(DbError::from)?;
+        let mut report = LikeBatchReport { outcomes: Vec::with_capacity(ops.len()) };
+        for op in ops {
+            report.outcomes.push(self.apply_like_op(&mut tx, user_token, op).await?);
         }
+        tx.commit().await.map_err(DbError::commit)?;
+        Ok(report)
+    }
+}
+
+/// An `AuthorDao` backed by a Postgres connection pool.
+pub struct AuthorDaoImpl {
+    pool: PgPool,
+    tables: Tables,
+}
+
+impl AuthorDaoImpl {
+    /// Creates the data access object.
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool, tables: Tables::default() }
+    }
 
+    /// Places the tables the data access object reads and writes in `schema`, which should have been migrated with
+    /// `migrations::run_in_schema`.
+    pub fn with_schema(mut self, schema: SchemaName) -> Self {
+        self.tables = Tables::in_schema(schema);
+        self
     }
 }
 
+impl AuthorDao for AuthorDaoImpl {
+    async fn get_top_users_by_reputation(&self, limit: u32) -> Result<Vec<UserReputation>, DbError> {
+        if limit == 0 {
+            return Err(DbError::validation(String::from("limit must be at least 1")));
+        }
+        sqlx::query_as::<_, UserReputation>(&self.tables.sql(
+            "SELECT id, username, reputation FROM users ORDER BY reputation DESC, username LIMIT $1"
+        ))
+            .bind(i64::from(limit))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::from)
+    }
+}
@@ -0,0 +1,224 @@
+//! Contains `QueryObserver`, which is notified of every call made to the question and answer data access objects,
+//! so that downstream pipelines can collect structured events about them without depending on a logging framework.
+//!
+//! Each call is reported once it completes, whether it succeeded or failed, as a `DaoEvent` that serializes to JSON.
+//! The observer runs on the task making the call, after the call, so observers should hand events off rather than
+//! block. A panicking observer is isolated from the call, whose result is returned unchanged.
+//!
+//! # Example
+//! ```no_run
+//! # fn example(pool: sqlx::PgPool) {
+//! use std::sync::Arc;
+//! use question_answer::persistence::QuestionDaoImpl;
+//! use question_answer::persistence::observer::{DaoEvent, QueryObserver};
+//!
+//! struct StdoutObserver;
+//!
+//! impl QueryObserver for StdoutObserver {
+//!     fn on_complete(&self, event: DaoEvent) {
+//!         println!("{}", serde_json::to_string(&event).unwrap());
+//!     }
+//! }
+//!
+//! let question_dao = QuestionDaoImpl::new(pool).with_observer(Arc::new(StdoutObserver));
+//! # }
+//! ```
+
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+#[cfg(any(test, feature = "testing"))]
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use serde::{Serialize, Serializer};
+use sqlx::types::Uuid;
+use crate::models::*;
+
+/// Receives a `DaoEvent` for every call made to a data access object it is set on.
+pub trait QueryObserver: Send + Sync {
+    /// Called once a call completes, successfully or not. Panics are caught and ignored.
+    fn on_complete(&self, event: DaoEvent);
+}
+
+/// The kind of entity a data access object manages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityKind {
+    /// Calls made to a `QuestionDao`
+    Question,
+    /// Calls made to an `AnswerDao`
+    Answer,
+}
+
+/// A completed call to a data access object.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DaoEvent {
+    /// The name of the method called, such as `get_question`
+    pub method: &'static str,
+    /// The kind of entity the data access object manages
+    pub entity: EntityKind,
+    /// The id the call was made with, as it was given, for methods taking the id of one entity
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// How long the call took, serialized in milliseconds
+    #[serde(rename = "duration_ms", serialize_with = "as_millis")]
+    pub duration: Duration,
+    /// The number of rows the call returned or changed, `None` if it failed or returns nothing countable
+    pub rows: Option<u64>,
+    /// The classification of the error the call failed with, `None` if it succeeded
+    pub error: Option<DbErrorKind>,
+}
+
+/// Serializes a duration as fractional milliseconds.
+fn as_millis<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64() * 1000.0)
+}
+
+/// A `QueryObserver` keeping every event in memory, for tests, available with the `testing` feature.
+#[cfg(any(test, feature = "testing"))]
+#[derive(Debug, Default)]
+pub struct VecObserver {
+    events: Mutex<Vec<DaoEvent>>,
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl VecObserver {
+    /// The events observed so far, in the order the calls completed.
+    pub fn events(&self) -> Vec<DaoEvent> {
+        self.events.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl QueryObserver for VecObserver {
+    fn on_complete(&self, event: DaoEvent) {
+        self.events.lock().unwrap_or_else(|e| e.into_inner()).push(event);
+    }
+}
+
+/// Awaits `call`, reporting it to `observer` if there is one. Without an observer the call is awaited as is.
+pub(crate) async fn observe<T: RowCount>(
+    observer: Option<&dyn QueryObserver>,
+    method: &'static str,
+    entity: EntityKind,
+    id: Option<String>,
+    call: impl Future<Output = Result<T, DbError>>,
+) -> Result<T, DbError> {
+    let Some(observer) = observer else {
+        return call.await;
+    };
+    let start = Instant::now();
+    let res = call.await;
+    let event = DaoEvent {
+        method,
+        entity,
+        id,
+        duration: start.elapsed(),
+        rows: res.as_ref().ok().and_then(RowCount::row_count),
+        error: res.as_ref().err().map(DbError::kind),
+    };
+    // The observer only sees the event, so a panic cannot leave anything the call depends on inconsistent
+    let _ = panic::catch_unwind(AssertUnwindSafe(|| observer.on_complete(event)));
+    res
+}
+
+/// The number of rows a successful call returned or changed, as reported by `DaoEvent::rows`.
+pub(crate) trait RowCount {
+    fn row_count(&self) -> Option<u64>;
+}
+
+/// Implements `RowCount` for results made from a single row.
+macro_rules! single_row {
+    ($($ty:ty),*) => {
+        $(
+            impl RowCount for $ty {
+                fn row_count(&self) -> Option<u64> {
+                    Some(1)
+                }
+            }
+        )*
+    };
+}
+
+single_row!(
+    Question, Answer, QuestionHeader, QuestionTranslation, Uuid, i64, ViewOutcome, CreateOutcome, UpsertOutcome, VoteOutcome
+);
+
+impl RowCount for () {
+    fn row_count(&self) -> Option<u64> {
+        None
+    }
+}
+
+impl RowCount for bool {
+    fn row_count(&self) -> Option<u64> {
+        Some(u64::from(*self))
+    }
+}
+
+impl RowCount for u64 {
+    fn row_count(&self) -> Option<u64> {
+        Some(*self)
+    }
+}
+
+impl<T> RowCount for Vec<T> {
+    fn row_count(&self) -> Option<u64> {
+        Some(self.len() as u64)
+    }
+}
+
+impl<T> RowCount for Page<T> {
+    fn row_count(&self) -> Option<u64> {
+        Some(self.items.len() as u64)
+    }
+}
+
+impl RowCount for AnswerPage {
+    fn row_count(&self) -> Option<u64> {
+        Some(self.answers.len() as u64)
+    }
+}
+
+impl RowCount for QuestionDetail {
+    fn row_count(&self) -> Option<u64> {
+        Some(1 + self.answers().len() as u64)
+    }
+}
+
+impl RowCount for ArchiveReport {
+    fn row_count(&self) -> Option<u64> {
+        Some(self.answers())
+    }
+}
+
+/// Implements a data access trait for its implementation by observing the calls of a private trait, `Calls` in the
+/// module `$calls`, which the implementation implements in place of the data access trait. Each method may name the
+/// argument holding the `EntityId` it is called with, which is reported as `DaoEvent::id`.
+macro_rules! observed_dao {
+    (
+        $dao:ident for $imp:ident via $calls:ident as $entity:expr;
+        $($name:ident($($arg:ident: $ty:ty),*) -> $ret:ty $(, id: $id:ident)?;)*
+    ) => {
+        mod $calls {
+            use super::*;
+
+            /// The methods of the data access trait, before they are observed.
+            pub(crate) trait Calls {
+                $(async fn $name(&self, $($arg: $ty),*) -> Result<$ret, DbError>;)*
+            }
+        }
+
+        impl $dao for $imp {
+            $(
+                async fn $name(&self, $($arg: $ty),*) -> Result<$ret, DbError> {
+                    // The id is only formatted for an observer
+                    let id: Option<String> = self.observer.as_ref().and_then(|_| None $(.or(Some($id.as_str().to_owned())))?);
+                    let call = <Self as $calls::Calls>::$name(self, $($arg),*);
+                    observer::observe(self.observer.as_deref(), stringify!($name), $entity, id, call).await
+                }
+            )*
+        }
+    };
+}
+
+pub(crate) use observed_dao;
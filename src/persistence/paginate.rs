@@ -0,0 +1,147 @@
+//! Contains `all_pages` and `cursor_pages`, which walk every page of a paged listing as one stream of items, so that
+//! callers do not have to advance the pages themselves.
+//!
+//! A walk stops at the first page with fewer items than requested, or that reports nothing after it, so a listing
+//! growing while it is walked cannot keep it going forever. As a last resort a walk gives up after `MAX_PAGES` pages,
+//! yielding `DbError::Validation`. A failed fetch is yielded as the last item of the stream.
+//!
+//! # Example
+//! ```no_run
+//! # async fn example(question_dao: question_answer::persistence::QuestionDaoImpl) -> Result<(), question_answer::models::DbError> {
+//! use std::pin::pin;
+//! use futures_util::StreamExt;
+//! use question_answer::models::QuestionFields;
+//! use question_answer::persistence::QuestionDao;
+//! use question_answer::persistence::paginate::all_pages;
+//!
+//! let mut questions = pin!(all_pages(|page| question_dao.get_questions_projected(QuestionFields::TITLE, page)));
+//! while let Some(question) = questions.next().await {
+//!     println!("{:?}", question?.title());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::future::Future;
+use futures_core::Stream;
+use futures_util::stream;
+use crate::models::{DbError, Page, PageRequest, MAX_PAGE_SIZE};
+
+/// The maximum number of pages a walk fetches before giving up.
+pub const MAX_PAGES: u32 = 10_000;
+
+/// Walks every page of an offset paged listing, `MAX_PAGE_SIZE` items at a time, see `all_pages_of`.
+pub fn all_pages<'a, T, F, Fut>(fetch: F) -> impl Stream<Item = Result<T, DbError>> + 'a
+where
+    T: 'a,
+    F: FnMut(PageRequest) -> Fut + 'a,
+    Fut: Future<Output = Result<Page<T>, DbError>> + 'a,
+{
+    all_pages_of(MAX_PAGE_SIZE, fetch)
+}
+
+/// Walks every page of an offset paged listing, yielding its items in order.
+///
+/// # Parameters
+/// `limit`: The number of items requested per page
+/// `fetch`: Fetches the page of the listing it is given
+///
+/// # Returns
+/// A stream of the items of every page, ending after the last page or the first error.
+pub fn all_pages_of<'a, T, F, Fut>(limit: u32, mut fetch: F) -> impl Stream<Item = Result<T, DbError>> + 'a
+where
+    T: 'a,
+    F: FnMut(PageRequest) -> Fut + 'a,
+    Fut: Future<Output = Result<Page<T>, DbError>> + 'a,
+{
+    walk(
+        limit,
+        move |offset: Option<u32>| {
+            let page = fetch(PageRequest { offset: offset.unwrap_or(0), limit });
+            async move { page.await.map(|page| (page.items, page.has_more)) }
+        },
+        |offset, items| Some(offset.unwrap_or(0) + items.len() as u32),
+    )
+}
+
+/// Walks every page of a cursor paged listing, such as `QuestionDao::get_activity_feed`, yielding its items in order.
+///
+/// # Parameters
+/// `limit`: The number of items requested per page
+/// `fetch`: Fetches at most `limit` items after the cursor it is given, from the start of the listing for `None`
+/// `cursor`: The cursor after an item, from which the next page is fetched. It must tell apart items sharing a sort
+/// key, such as `ActivityItem::cursor`, or the items after the end of a page that share its last key are skipped
+///
+/// # Returns
+/// A stream of the items of every page, ending after the last page or the first error.
+pub fn cursor_pages<'a, T, C, F, Fut>(limit: u32, mut fetch: F, cursor: impl Fn(&T) -> C + 'a) -> impl Stream<Item = Result<T, DbError>> + 'a
+where
+    T: 'a,
+    C: Clone + 'a,
+    F: FnMut(Option<C>, u32) -> Fut + 'a,
+    Fut: Future<Output = Result<Vec<T>, DbError>> + 'a,
+{
+    walk(
+        limit,
+        move |after: Option<C>| {
+            let page = fetch(after, limit);
+            // Without a count of what follows, only a short page tells that the listing ended
+            async move { page.await.map(|items| (items, true)) }
+        },
+        move |_, items| items.last().map(&cursor),
+    )
+}
+
+/// The progress of a walk through the pages of a listing.
+struct Walk<T, K, F, N> {
+    fetch: F,
+    next_key: N,
+    limit: u32,
+    /// The key of the next page, `None` for the first
+    key: Option<K>,
+    /// The items of the current page not yet yielded
+    items: std::vec::IntoIter<T>,
+    pages: u32,
+    done: bool,
+}
+
+/// Walks the pages of a listing, where `fetch` fetches the page at a key along with whether more may follow it and
+/// `next_key` computes the key of the page following the items of a page.
+fn walk<'a, T, K, F, Fut, N>(limit: u32, fetch: F, next_key: N) -> impl Stream<Item = Result<T, DbError>> + 'a
+where
+    T: 'a,
+    K: Clone + 'a,
+    F: FnMut(Option<K>) -> Fut + 'a,
+    Fut: Future<Output = Result<(Vec<T>, bool), DbError>> + 'a,
+    N: FnMut(Option<K>, &[T]) -> Option<K> + 'a,
+{
+    let walk = Walk { fetch, next_key, limit, key: None, items: Vec::new().into_iter(), pages: 0, done: false };
+    stream::unfold(walk, |mut walk| async move {
+        loop {
+            if let Some(item) = walk.items.next() {
+                return Some((Ok(item), walk));
+            }
+            if walk.done {
+                return None;
+            }
+            if walk.pages == MAX_PAGES {
+                walk.done = true;
+                let e = DbError::validation(format!("stopped walking the listing after {MAX_PAGES} pages"));
+                return Some((Err(e), walk));
+            }
+            walk.pages += 1;
+            let key = walk.key.take();
+            let (items, more) = match (walk.fetch)(key.clone()).await {
+                Ok(page) => page,
+                Err(e) => {
+                    walk.done = true;
+                    return Some((Err(e), walk));
+                }
+            };
+            // An empty page ends the walk even if the listing claims more follow, as the key could not advance
+            walk.done = !more || items.is_empty() || items.len() < walk.limit as usize;
+            walk.key = (walk.next_key)(key, &items);
+            walk.items = items.into_iter();
+        }
+    })
+}
@@ -0,0 +1,183 @@
+//! Contains helpers for constructing the connection pool shared by the data access objects.
+
+use std::time::Duration;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::PgPool;
+use crate::models::{DbError, InternalReason};
+use super::migrations::MIGRATOR;
+
+/// Options controlling how the pool is sized and when its connections are established.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolConfig {
+    /// The number of connections the pool keeps open, and eagerly opens when warming up
+    pub min_connections: u32,
+    /// The maximum number of connections the pool opens
+    pub max_connections: u32,
+    /// How long acquiring a connection may take before failing
+    pub acquire_timeout: Duration,
+    /// Opens `min_connections` connections before returning, so the first queries skip the handshake
+    pub warm_up: bool,
+    /// Constructs the pool without connecting, deferring any connection failure to the first query
+    pub lazy: bool,
+    /// Applies any missing migrations when the pool is constructed, instead of failing
+    pub auto_migrate: bool,
+    /// Skips checking the schema for missing migrations, for read-only replicas whose schema is managed elsewhere
+    pub skip_schema_check: bool,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            min_connections: 1,
+            max_connections: 10,
+            acquire_timeout: Duration::from_secs(30),
+            warm_up: false,
+            lazy: false,
+            auto_migrate: false,
+            skip_schema_check: false,
+        }
+    }
+}
+
+/// A snapshot of the connections held by a pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolMetrics {
+    /// The number of open connections, idle or in use
+    pub size: u32,
+    /// The number of open connections not currently in use
+    pub idle: usize,
+    /// The maximum number of connections the pool opens
+    pub max_connections: u32,
+}
+
+impl PoolMetrics {
+    /// The number of connections currently in use.
+    pub fn in_use(&self) -> u32 {
+        self.size.saturating_sub(self.idle as u32)
+    }
+
+    /// Renders the metrics in the Prometheus text exposition format, as gauges labelled with `pool`, so that
+    /// the metrics of several pools can be concatenated into one scrape.
+    ///
+    /// # Parameters
+    /// `pool`: The name distinguishing the pool, such as `"primary"`, escaped as a label value
+    ///
+    /// # Returns
+    /// The exposition text, ending with a newline.
+    pub fn prometheus_export(&self, pool: &str) -> String {
+        let pool = escape_label_value(pool);
+        format!(
+            "# HELP qa_pool_connections The open connections of the pool, by state.\n\
+            # TYPE qa_pool_connections gauge\n\
+            qa_pool_connections{{pool=\"{pool}\",state=\"idle\"}} {}\n\
+            qa_pool_connections{{pool=\"{pool}\",state=\"in_use\"}} {}\n\
+            # HELP qa_pool_max_connections The maximum number of connections the pool opens.\n\
+            # TYPE qa_pool_max_connections gauge\n\
+            qa_pool_max_connections{{pool=\"{pool}\"}} {}\n",
+            self.idle,
+            self.in_use(),
+            self.max_connections,
+        )
+    }
+}
+
+/// Escapes a Prometheus label value, whose backslashes, double quotes and line feeds must be escaped.
+pub(super) fn escape_label_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Creates a pool for the database at `url`, see `connect_with`.
+pub async fn connect(url: &str, config: PoolConfig) -> Result<PgPool, DbError> {
+    let options: PgConnectOptions = url.parse().map_err(DbError::connection)?;
+    connect_with(options, config).await
+}
+
+/// Creates a pool for the database described by `options`.
+///
+/// # Parameters
+/// `options`: The connection options of the database
+/// `config`: The `PoolConfig` sizing the pool and choosing whether it connects lazily or warms up
+///
+/// # Returns
+/// A `Result<PgPool, DbError>`, `Ok(PgPool)` once the pool is constructed. A lazy pool is returned without
+/// connecting or checking the schema, otherwise a failure to connect or warm up is returned as
+/// `Err(DbError)` with the underlying cause as its `source`. Unless `auto_migrate` applies them, missing
+/// migrations are reported as `InternalReason::SchemaMismatch`. Setting both `lazy` and `warm_up`, or `lazy` or
+/// `skip_schema_check` along with `auto_migrate`, is rejected with `Err(DbError::Validation)`.
+pub async fn connect_with(options: PgConnectOptions, config: PoolConfig) -> Result<PgPool, DbError> {
+    if config.lazy && config.warm_up {
+        return Err(DbError::validation(String::from("a pool cannot be both lazy and warmed up")));
+    }
+    if config.auto_migrate && (config.lazy || config.skip_schema_check) {
+        return Err(DbError::validation(String::from("migrations can only be applied by a pool that checks its schema on connecting")));
+    }
+    let pool_options = PgPoolOptions::new()
+        .min_connections(config.min_connections)
+        .max_connections(config.max_connections)
+        .acquire_timeout(config.acquire_timeout);
+    if config.lazy {
+        return Ok(pool_options.connect_lazy_with(options));
+    }
+    let pool = pool_options.connect_with(options).await.map_err(DbError::connection)?;
+    if !config.skip_schema_check {
+        check_schema(&pool, config.auto_migrate).await?;
+    }
+    if config.warm_up {
+        warm_up(&pool, config.min_connections).await?;
+    }
+    Ok(pool)
+}
+
+/// Checks that every migration the crate was built with has been applied, applying the missing ones if
+/// `auto_migrate` is set and reporting them as `InternalReason::SchemaMismatch` otherwise.
+async fn check_schema(pool: &PgPool, auto_migrate: bool) -> Result<(), DbError> {
+    // The table recording applied migrations only exists once migrations have been run
+    let recorded: bool = sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+        .fetch_one(pool)
+        .await
+        .map_err(DbError::from)?;
+    let applied: Vec<i64> = if recorded {
+        sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success")
+            .fetch_all(pool)
+            .await
+            .map_err(DbError::from)?
+    } else {
+        Vec::new()
+    };
+    let migrations: Vec<_> = MIGRATOR.iter().filter(|migration| migration.migration_type.is_up_migration()).collect();
+    let missing: Vec<String> = migrations.iter()
+        .filter(|migration| !applied.contains(&migration.version))
+        .map(|migration| format!("{}_{}", migration.version, migration.description.replace(' ', "_")))
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    if auto_migrate {
+        return MIGRATOR.run(pool).await.map_err(|e| DbError::from(sqlx::Error::from(e)));
+    }
+    Err(InternalReason::SchemaMismatch {
+        expected: migrations.iter().map(|migration| migration.version).max().unwrap_or(0),
+        found: applied.into_iter().max().unwrap_or(0),
+        missing,
+    }
+    .into())
+}
+
+/// Opens up to `connections` connections at once and returns them to the pool as idle connections.
+pub async fn warm_up(pool: &PgPool, connections: u32) -> Result<(), DbError> {
+    let mut acquired = Vec::with_capacity(connections as usize);
+    // Hold every connection until all are acquired, otherwise the same connection is reused
+    for _ in 0..connections {
+        acquired.push(pool.acquire().await.map_err(DbError::connection)?);
+    }
+    Ok(())
+}
+
+/// Reports the connections currently held by `pool`.
+pub fn pool_metrics(pool: &PgPool) -> PoolMetrics {
+    PoolMetrics {
+        size: pool.size(),
+        idle: pool.num_idle(),
+        max_connections: pool.options().get_max_connections(),
+    }
+}
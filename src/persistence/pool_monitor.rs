@@ -0,0 +1,201 @@
+//! Contains `spawn`, which samples the connections of a pool on a background task, so that an exhausted pool is
+//! noticed before acquiring a connection times out.
+//!
+//! The pool does not report the callers waiting for a connection, so the waits are recorded by the data access objects
+//! themselves: those given the monitor's `AcquireWaits` time every connection they acquire, and each sample reports
+//! the longest wait since the previous one, along with how long the pool has been saturated across consecutive
+//! samples. The monitor never acquires a connection, so it adds no load to a saturated pool.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use sqlx::PgPool;
+use tokio::task::JoinHandle;
+use tokio::time::{self, Instant, MissedTickBehavior};
+use crate::models::DbError;
+use super::pool::{escape_label_value, pool_metrics, PoolMetrics};
+
+/// A sample of a pool taken by the monitor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolSnapshot {
+    /// The connections held by the pool when it was sampled
+    pub metrics: PoolMetrics,
+    /// How long every connection has been in use, from the first of the consecutive samples finding the pool
+    /// saturated, `None` if it has an idle connection or can open another
+    pub saturated_for: Option<Duration>,
+    /// The longest a data access object recording into the monitor's `AcquireWaits` waited to acquire a connection
+    /// since the previous sample, `None` if none acquired one
+    pub acquire_wait: Option<Duration>,
+}
+
+impl PoolSnapshot {
+    /// Renders the snapshot in the Prometheus text exposition format, the gauges of `PoolMetrics::prometheus_export`
+    /// followed by the saturation and the longest acquire wait, which are zero while the pool is not saturated and
+    /// nothing acquired a connection respectively.
+    ///
+    /// # Parameters
+    /// `pool`: The name distinguishing the pool, such as `"primary"`, escaped as a label value
+    ///
+    /// # Returns
+    /// The exposition text, ending with a newline.
+    pub fn prometheus_export(&self, pool: &str) -> String {
+        let mut text = self.metrics.prometheus_export(pool);
+        text.push_str(&format!(
+            "# HELP qa_pool_saturated_seconds How long every connection of the pool has been in use.\n\
+            # TYPE qa_pool_saturated_seconds gauge\n\
+            qa_pool_saturated_seconds{{pool=\"{pool}\"}} {}\n\
+            # HELP qa_pool_acquire_wait_seconds The longest wait to acquire a connection since the previous sample.\n\
+            # TYPE qa_pool_acquire_wait_seconds gauge\n\
+            qa_pool_acquire_wait_seconds{{pool=\"{pool}\"}} {}\n",
+            self.saturated_for.unwrap_or_default().as_secs_f64(),
+            self.acquire_wait.unwrap_or_default().as_secs_f64(),
+            pool = escape_label_value(pool),
+        ));
+        text
+    }
+}
+
+/// The waits to acquire a connection recorded by the data access objects sharing it, see
+/// `QuestionDaoImpl::with_acquire_waits`.
+#[derive(Debug, Default)]
+pub struct AcquireWaits {
+    longest: Mutex<Option<Duration>>,
+}
+
+impl AcquireWaits {
+    /// Records how long acquiring a connection waited.
+    pub fn record(&self, wait: Duration) {
+        let mut longest = self.longest.lock().unwrap_or_else(|e| e.into_inner());
+        *longest = Some(longest.map_or(wait, |longest| longest.max(wait)));
+    }
+
+    /// The longest wait recorded since the previous call, `None` if none was.
+    fn take(&self) -> Option<Duration> {
+        self.longest.lock().unwrap_or_else(|e| e.into_inner()).take()
+    }
+}
+
+/// The handle of a running monitor, which stops it when shut down or dropped.
+#[derive(Debug)]
+pub struct PoolMonitor {
+    task: Option<JoinHandle<()>>,
+    waits: Arc<AcquireWaits>,
+}
+
+impl PoolMonitor {
+    /// The `AcquireWaits` whose longest wait each sample reports, to be given to the data access objects using the
+    /// pool, see `QuestionDaoImpl::with_acquire_waits`.
+    pub fn acquire_waits(&self) -> Arc<AcquireWaits> {
+        self.waits.clone()
+    }
+
+    /// Stops the monitor, returning once it is stopped, after which the observer is not called again.
+    pub async fn shutdown(mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+            // The task is cancelled, unless the observer panicked and stopped it already
+            let _ = task.await;
+        }
+    }
+}
+
+impl Drop for PoolMonitor {
+    fn drop(&mut self) {
+        if let Some(task) = &self.task {
+            task.abort();
+        }
+    }
+}
+
+/// Samples `pool` every `interval` on a background task, the first time right away, calling `observer` with each
+/// `PoolSnapshot`, which reports the waits recorded in `PoolMonitor::acquire_waits`. Samples missed while the runtime
+/// is busy are skipped rather than taken in a burst. Must be called within a Tokio runtime.
+///
+/// # Parameters
+/// `pool`: The pool to sample, which the monitor only reads
+/// `interval`: The time between samples, which must not be zero
+/// `observer`: Consumes the snapshots, such as `tracing_observer` or a Prometheus exporter
+///
+/// # Returns
+/// A `Result<PoolMonitor, DbError>`, `Ok(PoolMonitor)` stopping the task when shut down or dropped, and
+/// `Err(DbError::Validation)` without spawning it if `interval` is zero.
+pub fn spawn(pool: PgPool, interval: Duration, observer: impl Fn(PoolSnapshot) + Send + 'static) -> Result<PoolMonitor, DbError> {
+    if interval.is_zero() {
+        return Err(DbError::validation(String::from("the interval of a pool monitor must not be zero")));
+    }
+    let waits = Arc::new(AcquireWaits::default());
+    let recorded = waits.clone();
+    let task = tokio::spawn(async move {
+        let mut ticks = time::interval(interval);
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        let mut saturated_since: Option<Instant> = None;
+        loop {
+            let now = ticks.tick().await;
+            let metrics = pool_metrics(&pool);
+            saturated_since = if metrics.idle == 0 && metrics.size >= metrics.max_connections {
+                Some(saturated_since.unwrap_or(now))
+            } else {
+                None
+            };
+            let acquire_wait = recorded.take();
+            observer(PoolSnapshot { metrics, saturated_for: saturated_since.map(|since| now - since), acquire_wait });
+        }
+    });
+    Ok(PoolMonitor { task: Some(task), waits })
+}
+
+/// An observer emitting each snapshot as a `tracing` event with the numbers as fields, at the warning level while
+/// the pool is saturated and at the debug level otherwise.
+///
+/// # Parameters
+/// `pool`: The name distinguishing the pool, recorded as the `pool` field
+pub fn tracing_observer(pool: impl Into<Arc<str>>) -> impl Fn(PoolSnapshot) + Send + 'static {
+    let pool = pool.into();
+    move |snapshot| {
+        let PoolSnapshot { metrics, saturated_for, acquire_wait } = snapshot;
+        match saturated_for {
+            Some(saturated_for) => tracing::warn!(
+                pool = &*pool,
+                in_use = metrics.in_use(),
+                idle = metrics.idle,
+                max_connections = metrics.max_connections,
+                saturated_seconds = saturated_for.as_secs_f64(),
+                acquire_wait_seconds = acquire_wait.unwrap_or_default().as_secs_f64(),
+                "pool saturated",
+            ),
+            None => tracing::debug!(
+                pool = &*pool,
+                in_use = metrics.in_use(),
+                idle = metrics.idle,
+                max_connections = metrics.max_connections,
+                "pool sampled",
+            ),
+        }
+    }
+}
+
+/// An observer logging each snapshot, at the warning level while the pool is saturated and at the debug level
+/// otherwise.
+///
+/// # Parameters
+/// `pool`: The name distinguishing the pool in the log records
+pub fn log_observer(pool: impl Into<Arc<str>>) -> impl Fn(PoolSnapshot) + Send + 'static {
+    let pool = pool.into();
+    move |snapshot| {
+        let PoolSnapshot { metrics, saturated_for, acquire_wait } = snapshot;
+        match saturated_for {
+            Some(saturated_for) => log::warn!(
+                "pool {pool} saturated for {:.1}s: {} of {} connections in use, acquiring one waited {:.3}s",
+                saturated_for.as_secs_f64(),
+                metrics.in_use(),
+                metrics.max_connections,
+                acquire_wait.unwrap_or_default().as_secs_f64(),
+            ),
+            None => log::debug!(
+                "pool {pool}: {} of {} connections in use, {} idle",
+                metrics.in_use(),
+                metrics.max_connections,
+                metrics.idle,
+            ),
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! Contains `SchemaName`, which places the tables of the data access objects in a dedicated schema, for
+//! applications that share their database, and their pool, with the crate.
+//!
+//! The statements of a data access object given a schema refer to the crate's tables by their qualified name, so
+//! connections are used as they come from the pool and a table missing from the schema is an error rather than
+//! resolved in another schema. The statements are written as templates naming the tables unqualified, which are
+//! qualified by `Tables::sql` and executed as is without a schema.
+//!
+//! # Example
+//! ```no_run
+//! # async fn example(pool: sqlx::PgPool) -> Result<(), Box<dyn std::error::Error>> {
+//! use question_answer::persistence::{migrations, QuestionDaoImpl};
+//! use question_answer::persistence::schema::SchemaName;
+//!
+//! let schema = SchemaName::new("qa")?;
+//! migrations::run_in_schema(&pool, &schema).await?;
+//! let question_dao = QuestionDaoImpl::new(pool).with_schema(schema);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::borrow::Cow;
+use std::fmt::Display;
+use std::sync::Arc;
+use crate::models::DbError;
+
+/// The maximum length of a Postgres identifier, in bytes.
+const MAX_IDENTIFIER_LENGTH: usize = 63;
+
+/// The tables of the crate.
+pub(crate) const TABLES: [&str; 22] = [
+    "questions", "answers", "users", "idempotency_keys", "subscriptions", "categories", "like_events", "answer_votes",
+    "question_views", "answer_rejections", "ownership_transfers", "tags", "question_tags", "moderation_flags",
+    "question_links", "question_revisions", "question_translations", "answers_archive", "likes",
+    "answer_votes_archive", "moderation_flags_archive", "subscription_seen_answers",
+];
+
+/// The keywords a table is named after in the statements of the data access objects.
+const TABLE_KEYWORDS: [&str; 6] = ["FROM", "JOIN", "INTO", "UPDATE", "TABLE", "USING"];
+
+/// The name of a schema, validated as a lowercase unquoted identifier matching `[a-z_][a-z0-9_]*`, so that it can
+/// be written into statements without quoting or escaping.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SchemaName(Arc<str>);
+
+impl SchemaName {
+    /// Validates the name of a schema.
+    ///
+    /// # Returns
+    /// A `Result<SchemaName, DbError>`, `Ok(SchemaName)` if the name is a lowercase identifier of at most 63 bytes,
+    /// otherwise `Err(DbError::Validation)`.
+    pub fn new(name: &str) -> Result<Self, DbError> {
+        let mut chars = name.chars();
+        let valid = chars.next().is_some_and(|first| first.is_ascii_lowercase() || first == '_')
+            && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+            && name.len() <= MAX_IDENTIFIER_LENGTH;
+        if !valid {
+            return Err(DbError::validation(format!("invalid schema name `{name}`, expected a match of [a-z_][a-z0-9_]*")));
+        }
+        Ok(Self(Arc::from(name)))
+    }
+
+    /// The name of the schema.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for SchemaName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Writes the statements of a data access object for the schema of its tables, if it was given one.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Tables(Option<SchemaName>);
+
+impl Tables {
+    /// The tables of `schema`.
+    pub(crate) fn in_schema(schema: SchemaName) -> Self {
+        Self(Some(schema))
+    }
+
+    /// Qualifies the crate's tables named by a statement template with the schema, leaving the template as is
+    /// without one.
+    ///
+    /// A table is qualified where it follows `FROM`, `JOIN`, `INTO`, `UPDATE`, `TABLE` or `USING`, so columns named
+    /// like a table, references such as `answers.id` and string literals are left alone. Tables listed after a comma
+    /// are not qualified, so templates join them instead.
+    pub(crate) fn sql<'a>(&self, template: &'a str) -> Cow<'a, str> {
+        let Some(schema) = &self.0 else { return Cow::Borrowed(template) };
+        let mut sql = String::with_capacity(template.len() + 64);
+        // The word the current one follows, empty if anything else comes between them
+        let mut previous = "";
+        let mut rest = template;
+        while let Some(c) = rest.chars().next() {
+            let end = match c {
+                // A doubled quote within a literal or an identifier is read as two quoted runs
+                '\'' | '"' => rest[1..].find(c).map_or(rest.len(), |end| end + 2),
+                c if c.is_ascii_alphabetic() || c == '_' => {
+                    let end = rest.find(|c: char| !c.is_ascii_alphanumeric() && c != '_').unwrap_or(rest.len());
+                    let word = &rest[..end];
+                    if TABLE_KEYWORDS.iter().any(|keyword| keyword.eq_ignore_ascii_case(previous))
+                        && TABLES.contains(&word)
+                        && !rest[end..].starts_with('.')
+                    {
+                        sql.push_str(schema.as_str());
+                        sql.push('.');
+                    }
+                    sql.push_str(word);
+                    previous = word;
+                    rest = &rest[end..];
+                    continue;
+                }
+                c => c.len_utf8(),
+            };
+            if !c.is_whitespace() {
+                previous = "";
+            }
+            sql.push_str(&rest[..end]);
+            rest = &rest[end..];
+        }
+        Cow::Owned(sql)
+    }
+}
@@ -0,0 +1,471 @@
+//! Contains the scoped data access object, which runs the calls of a whole unit of work, such as a
+//! request, in a single transaction.
+
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use std::time::Instant;
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+use sqlx::pool::PoolConnection;
+use sqlx::postgres::PgConnection;
+use sqlx::types::Uuid;
+use sqlx::{PgPool, Postgres, Transaction};
+use tokio::sync::{Mutex, MutexGuard};
+use crate::clock::Clock;
+use crate::models::*;
+use crate::models::diff::DiffHunk;
+use crate::models::period::Period;
+use crate::models::policy::ContentPolicy;
+use super::observer::QueryObserver;
+use super::pool_monitor::AcquireWaits;
+use super::schema::{SchemaName, Tables};
+use super::{AnswerDao, AnswerDaoImpl, QuestionDao, QuestionDaoImpl};
+
+/// Where a data access object gets the connection each of its calls executes on.
+#[derive(Clone)]
+pub(crate) enum Source {
+    /// A connection is acquired from the pool for every call, recording how long acquiring it waited in `waits` if set
+    Pool { pool: PgPool, waits: Option<Arc<AcquireWaits>> },
+    /// Every call executes on the transaction of a `ScopedDao`, one call at a time
+    Scope(Arc<Mutex<Transaction<'static, Postgres>>>),
+    /// Every connection fails to be acquired with the given error, for tests of the failure paths of the calls
+    #[cfg(test)]
+    Failing(fn() -> sqlx::Error),
+}
+
+impl Source {
+    /// Acquires the connection the next statements execute on. Transactions begun on the connection of a
+    /// scope are savepoints, so a call failing midway only rolls back its own changes.
+    pub(crate) async fn acquire(&self) -> Result<SourceConnection<'_>, sqlx::Error> {
+        match self {
+            Source::Pool { pool, waits } => {
+                let started = Instant::now();
+                let conn = pool.acquire().await?;
+                if let Some(waits) = waits {
+                    waits.record(started.elapsed());
+                }
+                Ok(SourceConnection::Pooled(Box::new(conn)))
+            }
+            Source::Scope(tx) => Ok(SourceConnection::Scoped(tx.lock().await)),
+            #[cfg(test)]
+            Source::Failing(error) => Err(error()),
+        }
+    }
+
+    /// Resolves an `EntityId` like `resolve_id`, only acquiring a connection to look up serial ids.
+    pub(crate) async fn resolve_id(&self, tables: &Tables, table: &str, id: EntityId) -> Result<Uuid, DbError> {
+        match self {
+            Source::Pool { pool, .. } => super::resolve_id(pool, tables, table, id).await,
+            Source::Scope(tx) => super::resolve_id(&mut **tx.lock().await, tables, table, id).await,
+            // Uuids are still parsed without a connection, as they are for a pool
+            #[cfg(test)]
+            Source::Failing(error) => match id.kind().map_err(DbError::invalid_id)? {
+                EntityIdKind::Uuid(id) => Ok(id),
+                EntityIdKind::Serial(_) => Err(DbError::from(error())),
+            },
+        }
+    }
+}
+
+/// A connection acquired from a `Source`.
+pub(crate) enum SourceConnection<'a> {
+    /// A connection from the pool, returned to it when dropped
+    Pooled(Box<PoolConnection<Postgres>>),
+    /// The transaction of a scope, released for the next call when dropped
+    Scoped(MutexGuard<'a, Transaction<'static, Postgres>>),
+}
+
+impl Deref for SourceConnection<'_> {
+    type Target = PgConnection;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            SourceConnection::Pooled(conn) => conn,
+            SourceConnection::Scoped(tx) => tx,
+        }
+    }
+}
+
+impl DerefMut for SourceConnection<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            SourceConnection::Pooled(conn) => conn,
+            SourceConnection::Scoped(tx) => tx,
+        }
+    }
+}
+
+/// A `QuestionDao` and `AnswerDao` whose calls all execute on one transaction, so that they are committed
+/// or rolled back together. Dropping the scope without calling `commit` rolls the transaction back.
+///
+/// A call that fails rolls back its own changes only, leaving the changes of earlier calls in place. However
+/// a statement failing outside of a call's own savepoint aborts the transaction, after which the scope can only
+/// be rolled back.
+pub struct ScopedDao {
+    transaction: Arc<Mutex<Transaction<'static, Postgres>>>,
+    questions: QuestionDaoImpl,
+    answers: AnswerDaoImpl,
+}
+
+impl ScopedDao {
+    /// Begins a transaction on a connection from `pool`, which is held until the scope is committed, rolled
+    /// back or dropped. The scope uses the system clock, the default `ContentLimits`, moderation off and no
+    /// `ContentPolicy`.
+    ///
+    /// # Parameters
+    /// `pool`: The pool the connection is acquired from
+    ///
+    /// # Returns
+    /// A `Result<ScopedDao, DbError>`, `Ok(ScopedDao)` in the successful case, otherwise `Err(DbError)`
+    /// if the transaction cannot be begun.
+    pub async fn begin(pool: &PgPool) -> Result<Self, DbError> {
+        let transaction = Arc::new(Mutex::new(pool.begin().await.map_err(DbError::from)?));
+        let mut questions = QuestionDaoImpl::new(pool.clone());
+        questions.source = Source::Scope(transaction.clone());
+        let mut answers = AnswerDaoImpl::new(pool.clone());
+        answers.source = Source::Scope(transaction.clone());
+        Ok(Self { transaction, questions, answers })
+    }
+
+    /// Places the tables the scope reads and writes in `schema`, see `QuestionDaoImpl::with_schema`.
+    pub fn with_schema(mut self, schema: SchemaName) -> Self {
+        self.questions = self.questions.with_schema(schema.clone());
+        self.answers = self.answers.with_schema(schema);
+        self
+    }
+
+    /// Sets the `Clock` used to timestamp new questions and answers and compute time based cutoffs.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.questions = self.questions.with_clock(clock.clone());
+        self.answers = self.answers.with_clock(clock);
+        self
+    }
+
+    /// Sets the `ContentLimits` new questions and answers are validated against.
+    pub fn with_limits(mut self, limits: ContentLimits) -> Self {
+        self.questions = self.questions.with_limits(limits);
+        self.answers = self.answers.with_limits(limits);
+        self
+    }
+
+    /// Sets the `ModerationMode` deciding which answers are listed and counted.
+    pub fn with_moderation(mut self, moderation: ModerationMode) -> Self {
+        self.questions = self.questions.with_moderation(moderation);
+        self.answers = self.answers.with_moderation(moderation);
+        self
+    }
+
+    /// Sets the `QueryObserver` notified of every call made within the scope.
+    pub fn with_observer(mut self, observer: Arc<dyn QueryObserver>) -> Self {
+        self.questions = self.questions.with_observer(observer.clone());
+        self.answers = self.answers.with_observer(observer);
+        self
+    }
+
+    /// Sets the `ContentPolicy` new and edited questions and answers are checked against.
+    pub fn with_policy(mut self, policy: Arc<dyn ContentPolicy>) -> Self {
+        self.questions = self.questions.with_policy(policy.clone());
+        self.answers = self.answers.with_policy(policy);
+        self
+    }
+
+    /// Sets the `CreationQuota` limiting the questions and answers created with each author token.
+    pub fn with_quota(mut self, quota: CreationQuota) -> Self {
+        self.questions = self.questions.with_quota(quota);
+        self.answers = self.answers.with_quota(quota);
+        self
+    }
+
+    /// Sets the `ReputationConfig` deciding the reputation authors gain as their content is liked and accepted.
+    pub fn with_reputation(mut self, reputation: ReputationConfig) -> Self {
+        self.questions = self.questions.with_reputation(reputation);
+        self.answers = self.answers.with_reputation(reputation);
+        self
+    }
+
+    /// Commits every change made through the scope.
+    pub async fn commit(self) -> Result<(), DbError> {
+        self.into_transaction().commit().await.map_err(DbError::commit)
+    }
+
+    /// Rolls back every change made through the scope.
+    pub async fn rollback(self) -> Result<(), DbError> {
+        self.into_transaction().rollback().await.map_err(DbError::from)
+    }
+
+    /// Takes the transaction out of the scope, once the data access objects sharing it are dropped.
+    fn into_transaction(self) -> Transaction<'static, Postgres> {
+        let Self { transaction, questions, answers } = self;
+        drop((questions, answers));
+        match Arc::try_unwrap(transaction) {
+            Ok(transaction) => transaction.into_inner(),
+            Err(_) => unreachable!("the transaction is only shared with the data access objects of the scope"),
+        }
+    }
+}
+
+impl QuestionDao for ScopedDao {
+    async fn create_question(&self, new_question: NewQuestion) -> Result<Question, DbError> {
+        self.questions.create_question(new_question).await
+    }
+
+    async fn get_question(&self, question_id: EntityId) -> Result<Question, DbError> {
+        self.questions.get_question(question_id).await
+    }
+
+    async fn get_question_as_of(&self, question_id: EntityId, at: DateTime<Utc>) -> Result<Question, DbError> {
+        self.questions.get_question_as_of(question_id, at).await
+    }
+
+    async fn get_revision_diff(&self, question_id: EntityId, from_rev: u32, to_rev: u32) -> Result<Vec<DiffHunk>, DbError> {
+        self.questions.get_revision_diff(question_id, from_rev, to_rev).await
+    }
+
+    async fn get_questions(&self) -> Result<Vec<Question>, DbError> {
+        self.questions.get_questions().await
+    }
+
+    async fn get_questions_projected(&self, fields: QuestionFields, page: PageRequest) -> Result<Page<QuestionPartial>, DbError> {
+        self.questions.get_questions_projected(fields, page).await
+    }
+
+    async fn get_questions_shorter_than(&self, max_words: i32) -> Result<Vec<Question>, DbError> {
+        self.questions.get_questions_shorter_than(max_words).await
+    }
+
+    async fn get_questions_in_period(&self, period: Period, tz: Tz) -> Result<Vec<Question>, DbError> {
+        self.questions.get_questions_in_period(period, tz).await
+    }
+
+    async fn get_questions_by_like_range(&self, min: i32, max: Option<i32>, created_after: Option<DateTime<Utc>>) -> Result<Vec<Question>, DbError> {
+        self.questions.get_questions_by_like_range(min, max, created_after).await
+    }
+
+    async fn get_questions_exceeding_like_rate(&self, likes_per_hour: f64, window: Duration) -> Result<Vec<QuestionLikeRate>, DbError> {
+        self.questions.get_questions_exceeding_like_rate(likes_per_hour, window).await
+    }
+
+    async fn get_question_header(&self, question_id: EntityId) -> Result<QuestionHeader, DbError> {
+        self.questions.get_question_header(question_id).await
+    }
+
+    async fn get_question_detail_consistent(&self, question_id: EntityId) -> Result<QuestionDetail, DbError> {
+        self.questions.get_question_detail_consistent(question_id).await
+    }
+
+    async fn get_question_detail_for_author(&self, question_id: EntityId, author_token: &str) -> Result<QuestionDetail, DbError> {
+        self.questions.get_question_detail_for_author(question_id, author_token).await
+    }
+
+    async fn get_question_detail_with(&self, question_id: EntityId, options: DetailOptions) -> Result<QuestionDetail, DbError> {
+        self.questions.get_question_detail_with(question_id, options).await
+    }
+
+    async fn get_questions_by_token(&self, author_token: &str) -> Result<Vec<Question>, DbError> {
+        self.questions.get_questions_by_token(author_token).await
+    }
+
+    async fn search_questions(&self, query: &str, ranking: SearchRankingConfig, limit: u32) -> Result<Vec<RankedQuestion>, DbError> {
+        self.questions.search_questions(query, ranking, limit).await
+    }
+
+    async fn get_activity_feed(&self, limit: u32, before: Option<ActivityCursor>) -> Result<Vec<ActivityItem>, DbError> {
+        self.questions.get_activity_feed(limit, before).await
+    }
+
+    async fn update_question(&self, question_id: EntityId, update: UpdateQuestion) -> Result<Question, DbError> {
+        self.questions.update_question(question_id, update).await
+    }
+
+    async fn lock_question(&self, question_id: EntityId) -> Result<Question, DbError> {
+        self.questions.lock_question(question_id).await
+    }
+
+    async fn unlock_question(&self, question_id: EntityId) -> Result<Question, DbError> {
+        self.questions.unlock_question(question_id).await
+    }
+
+    async fn pin_question(&self, question_id: EntityId) -> Result<Question, DbError> {
+        self.questions.pin_question(question_id).await
+    }
+
+    async fn unpin_question(&self, question_id: EntityId) -> Result<Question, DbError> {
+        self.questions.unpin_question(question_id).await
+    }
+
+    async fn pin_answer(&self, question_id: EntityId, answer_id: EntityId) -> Result<Question, DbError> {
+        self.questions.pin_answer(question_id, answer_id).await
+    }
+
+    async fn unpin_answer(&self, question_id: EntityId) -> Result<Question, DbError> {
+        self.questions.unpin_answer(question_id).await
+    }
+
+    async fn accept_answer(&self, question_id: EntityId, answer_id: EntityId) -> Result<Question, DbError> {
+        self.questions.accept_answer(question_id, answer_id).await
+    }
+
+    async fn unaccept_answer(&self, question_id: EntityId) -> Result<Question, DbError> {
+        self.questions.unaccept_answer(question_id).await
+    }
+
+    async fn set_bounty(&self, question_id: EntityId, amount: i32, duration: Duration) -> Result<Question, DbError> {
+        self.questions.set_bounty(question_id, amount, duration).await
+    }
+
+    async fn clear_bounty(&self, question_id: EntityId) -> Result<Question, DbError> {
+        self.questions.clear_bounty(question_id).await
+    }
+
+    async fn upsert_translation(&self, question_id: EntityId, lang: &str, content: NewTranslation) -> Result<QuestionTranslation, DbError> {
+        self.questions.upsert_translation(question_id, lang, content).await
+    }
+
+    async fn get_translation(&self, question_id: EntityId, lang: &str) -> Result<Question, DbError> {
+        self.questions.get_translation(question_id, lang).await
+    }
+
+    async fn link_questions(&self, from_id: EntityId, to_id: EntityId, kind: LinkKind) -> Result<(), DbError> {
+        self.questions.link_questions(from_id, to_id, kind).await
+    }
+
+    async fn unlink_questions(&self, from_id: EntityId, to_id: EntityId, kind: LinkKind) -> Result<bool, DbError> {
+        self.questions.unlink_questions(from_id, to_id, kind).await
+    }
+
+    async fn get_linked_questions(&self, question_id: EntityId) -> Result<Vec<(LinkKind, Question)>, DbError> {
+        self.questions.get_linked_questions(question_id).await
+    }
+
+    async fn delete_question(&self, question_id: EntityId, force: bool) -> Result<Uuid, DbError> {
+        self.questions.delete_question(question_id, force).await
+    }
+
+    async fn delete_question_with_policy(&self, question_id: EntityId, policy: DeletePolicy) -> Result<Uuid, DbError> {
+        self.questions.delete_question_with_policy(question_id, policy).await
+    }
+
+    async fn increment_question_likes(&self, question_id: EntityId) -> Result<i64, DbError> {
+        self.questions.increment_question_likes(question_id).await
+    }
+
+    async fn record_view(&self, question_id: EntityId, client_token: &str) -> Result<ViewOutcome, DbError> {
+        self.questions.record_view(question_id, client_token).await
+    }
+
+    async fn create_question_idempotent(&self, new_question: NewQuestion, key: &str) -> Result<CreateOutcome, DbError> {
+        self.questions.create_question_idempotent(new_question, key).await
+    }
+
+    async fn upsert_question_by_external_id(&self, new_question: NewQuestion) -> Result<UpsertOutcome, DbError> {
+        self.questions.upsert_question_by_external_id(new_question).await
+    }
+
+    async fn purge_idempotency_keys(&self, ttl: Duration) -> Result<u64, DbError> {
+        self.questions.purge_idempotency_keys(ttl).await
+    }
+
+    async fn expire_bounties(&self) -> Result<Vec<Uuid>, DbError> {
+        self.questions.expire_bounties().await
+    }
+
+    async fn delete_questions_batched(
+        &self,
+        ids: Vec<EntityId>,
+        batch_size: usize,
+        force: bool,
+        progress: Option<&mut dyn FnMut(BatchProgress)>
+    ) -> Result<u64, DbError> {
+        self.questions.delete_questions_batched(ids, batch_size, force, progress).await
+    }
+}
+
+impl AnswerDao for ScopedDao {
+    async fn create_answer(&self, new_answer: NewAnswer) -> Result<Answer, DbError> {
+        self.answers.create_answer(new_answer).await
+    }
+
+    async fn create_answer_draft(&self, new_answer: NewAnswer) -> Result<Answer, DbError> {
+        self.answers.create_answer_draft(new_answer).await
+    }
+
+    async fn publish_answer(&self, answer_id: EntityId) -> Result<Answer, DbError> {
+        self.answers.publish_answer(answer_id).await
+    }
+
+    async fn get_drafts(&self, question_id: EntityId, author_id: EntityId) -> Result<Vec<Answer>, DbError> {
+        self.answers.get_drafts(question_id, author_id).await
+    }
+
+    async fn get_answer(&self, answer_id: EntityId) -> Result<Answer, DbError> {
+        self.answers.get_answer(answer_id).await
+    }
+
+    async fn get_answers(&self, question_id: EntityId) -> Result<Vec<Answer>, DbError> {
+        self.answers.get_answers(question_id).await
+    }
+
+    async fn get_answer_threads(&self, question_id: EntityId) -> Result<Vec<AnswerThread>, DbError> {
+        self.answers.get_answer_threads(question_id).await
+    }
+
+    async fn get_answers_sorted(&self, question_id: EntityId, sort: AnswerSort, limit: u32) -> Result<Vec<Answer>, DbError> {
+        self.answers.get_answers_sorted(question_id, sort, limit).await
+    }
+
+    async fn get_answers_paged(&self, question_id: EntityId, limit: u32, page_token: Option<&str>) -> Result<AnswerPage, DbError> {
+        self.answers.get_answers_paged(question_id, limit, page_token).await
+    }
+
+    async fn get_answers_with_authors(&self, question_id: EntityId) -> Result<Vec<AnswerWithAuthor>, DbError> {
+        self.answers.get_answers_with_authors(question_id).await
+    }
+
+    async fn get_answers_by_token(&self, author_token: &str) -> Result<Vec<Answer>, DbError> {
+        self.answers.get_answers_by_token(author_token).await
+    }
+
+    async fn search_answers(&self, question_id: EntityId, term: &str) -> Result<Vec<Answer>, DbError> {
+        self.answers.search_answers(question_id, term).await
+    }
+
+    async fn search_all_answers(&self, term: &str, limit: u32) -> Result<Vec<AnswerWithQuestion>, DbError> {
+        self.answers.search_all_answers(term, limit).await
+    }
+
+    async fn get_all_answers(&self) -> Result<Vec<Answer>, DbError> {
+        self.answers.get_all_answers().await
+    }
+
+    async fn get_all_answers_with_question(&self, page: PageRequest) -> Result<Page<AnswerWithQuestion>, DbError> {
+        self.answers.get_all_answers_with_question(page).await
+    }
+
+    async fn delete_answer(&self, answer_id: EntityId) -> Result<Uuid, DbError> {
+        self.answers.delete_answer(answer_id).await
+    }
+
+    async fn delete_answer_with_tombstone(&self, answer_id: EntityId, reason: Option<String>) -> Result<Answer, DbError> {
+        self.answers.delete_answer_with_tombstone(answer_id, reason).await
+    }
+
+    async fn increment_answer_likes(&self, answer_id: EntityId) -> Result<i64, DbError> {
+        self.answers.increment_answer_likes(answer_id).await
+    }
+
+    async fn vote_answer(&self, answer_id: EntityId, vote: AnswerVote, user_token: &str) -> Result<VoteOutcome, DbError> {
+        self.answers.vote_answer(answer_id, vote, user_token).await
+    }
+
+    async fn approve_answer(&self, answer_id: EntityId) -> Result<Answer, DbError> {
+        self.answers.approve_answer(answer_id).await
+    }
+
+    async fn reject_answer(&self, answer_id: EntityId) -> Result<Uuid, DbError> {
+        self.answers.reject_answer(answer_id).await
+    }
+
+    async fn archive_stale_answers(&self, inactive_for: Duration, batch: usize) -> Result<ArchiveReport, DbError> {
+        self.answers.archive_stale_answers(inactive_for, batch).await
+    }
+}
@@ -1,35 +1,99 @@
-use sqlx::test;
-use sqlx::types::Uuid;
-use crate::models::prelude::*;
-use super::prelude::*;
-
 mod question_tests {
     // use super::prelude::*;
+    use std::sync::Arc;
+    use chrono::{Duration, TimeZone, Utc};
     use sqlx::types::Uuid;
-    use crate::models::{DbError, EntityId, NewQuestion};
+    use crate::clock::{FixedClock, SteppingClock};
+    use crate::fixtures;
+    use crate::models::{
+        ActivityItem, Answer, AnswerBodyMode, AnswerSort, BatchProgress, ConflictReason, ContentLimits, CreateOutcome, CreationQuota, DbError, DbErrorKind, DeletePolicy, DetailOptions, EntityId,
+        InternalReason, LinkKind, ModerationMode, NewAnswer, NewTranslation, PageRequest, Question, QuestionDetail, QuestionFields, QuestionLikeRate, QuestionPartial, SearchRankingConfig, UnavailableReason, UpdateQuestion, UpsertOutcome,
+        ValidationReason, ViewOutcome, ANSWER_PREVIEW_CHARS, MAX_BOUNTY, MAX_LIKE_RATE_WINDOW_DAYS, MAX_PAGE_SIZE,
+    };
+    use crate::models::diff::DiffLine;
+    use crate::models::dto::{AnswerResponse, QuestionDetailResponse, QuestionResponse};
     use crate::persistence::prelude::PgPool;
+    use crate::persistence::{AnswerDao, AnswerDaoImpl};
     use crate::persistence::QuestionDaoImpl;
     use crate::persistence::QuestionDao;
+    use crate::persistence::{AdminDao, AdminDaoImpl};
+    use crate::persistence::scoped::ScopedDao;
+    use crate::models::period::Period;
+    use crate::query_counter::QueryCounter;
+    use chrono_tz::{America::New_York, UTC};
 
     #[sqlx::test]
     async fn create_question_should_work(pool: PgPool) -> Result<(), DbError> {
         let question_dao = QuestionDaoImpl::new(pool);
-        let new_question = NewQuestion { title: String::from("Test Question"), question: String::from("Hello this question is a test") };
+        let new_question = fixtures::question().build();
         let question_res = question_dao.create_question(new_question).await;
         println!("{:?}", question_res);
         assert!(question_res.is_ok());
         Ok(())
     }
 
+    #[sqlx::test]
+    async fn create_question_should_return_persisted_question_with_defaults(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool);
+        let before = Utc::now();
+        let question = question_dao.create_question(fixtures::question().title("Defaults").question("What are the defaults?").build())
+            .await
+            .expect("question should be created successfully");
+        println!("{:?}", question);
+        assert_eq!(question.title(), "Defaults");
+        assert_eq!(question.question(), "What are the defaults?");
+        assert_eq!(question.likes(), 0);
+        assert!(question.created_at() >= before - Duration::seconds(1) && question.created_at() <= Utc::now());
+        // The returned question should be exactly what is persisted
+        let persisted = question_dao.get_question(EntityId::new(question.id().to_string())).await.expect("question should be found");
+        assert_eq!(persisted.likes(), question.likes());
+        assert_eq!(persisted.created_at(), question.created_at());
+    }
+
+    #[sqlx::test]
+    async fn create_question_should_succeed_at_content_limits(pool: PgPool) {
+        let limits = ContentLimits::default();
+        let question_dao = QuestionDaoImpl::new(pool);
+        let new_question = fixtures::question().title("t".repeat(limits.max_title)).question("q".repeat(limits.max_question)).build();
+        let question_res = question_dao.create_question(new_question).await;
+        println!("{:?}", question_res);
+        assert!(question_res.is_ok());
+    }
+
+    #[sqlx::test]
+    async fn create_question_should_fail_with_validation_error_before_querying(pool: PgPool) {
+        let limits = ContentLimits::default();
+        // Failing every connection ensures any query would fail with a different error
+        let question_dao = QuestionDaoImpl::new(pool).with_failing_source(|| sqlx::Error::PoolClosed);
+        let new_question = fixtures::question().title("t".repeat(limits.max_title + 1)).build();
+        let question_res = question_dao.create_question(new_question).await;
+        println!("{:?}", question_res);
+        let Err(e @ DbError::Validation { .. }) = question_res else { panic!("Error should be `Validation` variant") };
+        // The message should not name the table or constraint
+        let message = e.to_string();
+        assert!(!message.contains("questions") && !message.contains("constraint"), "{message}");
+    }
+
+    #[sqlx::test]
+    async fn create_question_should_map_check_violation_to_validation_error(pool: PgPool) {
+        // Simulate the limits being raised without the database schema being updated
+        let limits = ContentLimits { max_title: 1000, ..ContentLimits::default() };
+        let question_dao = QuestionDaoImpl::new(pool).with_limits(limits);
+        let new_question = fixtures::question().title("t".repeat(301)).build();
+        let question_res = question_dao.create_question(new_question).await;
+        println!("{:?}", question_res);
+        let Err(DbError::Validation { .. }) = question_res else { panic!("Error should be `Validation` variant") };
+    }
+
     #[sqlx::test]
     async fn create_question_should_fail_with_creation_error(pool: PgPool) {
-        let question_dao = QuestionDaoImpl::new(pool.clone());
-        pool.close().await;
-        let new_question = NewQuestion { title: String::from("Test Question"), question: String::from("Hello this question is a test") };
+        let question_dao = QuestionDaoImpl::new(pool).with_failing_source(|| sqlx::Error::PoolClosed);
+        let new_question = fixtures::question().build();
         let question_res = question_dao.create_question(new_question).await;
         println!("{:?}", question_res);
         assert!(question_res.is_err());
-        let Err(DbError::Creation(e)) = question_res else {panic!("result should be a creation error")};
+        let Err(e @ DbError::Unavailable { reason: UnavailableReason::Unreachable, .. }) = question_res else {panic!("result should be a creation error")};
+        assert_eq!(e.kind(), DbErrorKind::Unavailable);
     }
 
     #[sqlx::test]
@@ -47,8 +111,8 @@ mod question_tests {
     async fn get_question_should_succeed(pool: PgPool) -> Result<(), DbError> {
         // First create a sample mock question
         let question_dao = QuestionDaoImpl::new(pool);
-        let new_question = NewQuestion { title: String::from("Test Question"), question: String::from("Hello this question is a test") };
-        let new_question_id = question_dao.create_question(new_question).await?;
+        let new_question = fixtures::question().build();
+        let new_question_id = question_dao.create_question(new_question).await?.id();
         println!("new question uuid: {new_question_id}");
         // Create new entity id
         let question_id = EntityId::new(new_question_id.to_string());
@@ -58,6 +122,25 @@ mod question_tests {
         Ok(())
     }
 
+    #[sqlx::test]
+    async fn get_question_should_find_by_serial(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool);
+        fixtures::seed_questions(&question_dao, 2).await;
+        let question = question_dao.create_question(fixtures::question().build()).await.expect("question should be created successfully");
+        let serial = question.serial().expect("serial should be assigned");
+        for question_id in [EntityId::serial(serial), EntityId::new(serial.to_string())] {
+            let get_res = question_dao.get_question(question_id).await;
+            println!("{:?}", get_res);
+            assert_eq!(get_res.unwrap().id(), question.id());
+        }
+        // The uuid path is unaffected
+        let get_res = question_dao.get_question(EntityId::uuid(question.id())).await;
+        assert_eq!(get_res.unwrap().serial(), Some(serial));
+        let get_res = question_dao.get_question(EntityId::serial(serial + 100)).await;
+        println!("{:?}", get_res);
+        let Err(DbError::NotFound { .. }) = get_res else { panic!("Error should be `NotFound` variant") };
+    }
+
     #[sqlx::test]
     async fn get_question_should_fail_with_invalid_uuid(pool: PgPool) {
         let question_dao = QuestionDaoImpl::new(pool);
@@ -65,16 +148,35 @@ mod question_tests {
         let get_res = question_dao.get_question(question_id).await;
         println!("{:?}", get_res);
         assert!(get_res.is_err());
-        let Err(DbError::InvalidUuid(s)) = get_res else {panic!("Error should be a `InvalidUuid`")};
+        let Err(DbError::InvalidId { .. }) = get_res else {panic!("Error should be a `InvalidId`")};
     }
 
     #[sqlx::test]
     async fn get_questions_should_fail(pool: PgPool) {
-        let question_dao = QuestionDaoImpl::new(pool.clone());
-        pool.close().await;
+        let question_dao = QuestionDaoImpl::new(pool).with_failing_source(|| sqlx::Error::PoolTimedOut);
         let get_res = question_dao.get_questions().await;
         println!("{:?}", get_res);
         assert!(get_res.is_err());
+        let Err(e @ DbError::Unavailable { reason: UnavailableReason::Transient, .. }) = get_res else { panic!("Error should be `Unavailable` variant") };
+        assert!(e.is_transient());
+    }
+
+    #[sqlx::test]
+    async fn get_question_and_get_questions_should_fail_with_from_row_on_schema_drift(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let new_question = fixtures::question().build();
+        let question_id = question_dao.create_question(new_question).await.expect("question should be created successfully").id();
+        // Change the type of a column so it no longer decodes into the model
+        sqlx::query("ALTER TABLE questions ALTER COLUMN likes TYPE TEXT")
+            .execute(&pool)
+            .await
+            .expect("column type should be altered successfully");
+        let get_res = question_dao.get_question(EntityId::new(question_id.to_string())).await;
+        println!("{:?}", get_res);
+        let Err(DbError::Internal { reason: InternalReason::Decode, .. }) = get_res else { panic!("Error should be `Decode` reason") };
+        let get_res = question_dao.get_questions().await;
+        println!("{:?}", get_res);
+        let Err(DbError::Internal { reason: InternalReason::Decode, .. }) = get_res else { panic!("Error should be `Decode` reason") };
     }
 
     #[sqlx::test]
@@ -89,13 +191,13 @@ mod question_tests {
     #[sqlx::test]
     async fn get_questions_should_succeed_in_non_empty_state(pool: PgPool) {
         let question_dao = QuestionDaoImpl::new(pool);
-        let new_question1 = NewQuestion { title: String::from("Test Question1"), question: String::from("Hello this question is a test") };
-        let new_question2 = NewQuestion { title: String::from("Test Question2"), question: String::from("Hello this question is a test") };
-        let new_question3 = NewQuestion { title: String::from("Test Question3"), question: String::from("Hello this question is a test") };
+        let new_question1 = fixtures::question().build();
+        let new_question2 = fixtures::question().build();
+        let new_question3 = fixtures::question().build();
         // Insert into database
-        let new_question1_id = question_dao.create_question(new_question1).await.expect("question should be created successfully");
-        let new_question2_id = question_dao.create_question(new_question2).await.expect("question should be created successfully");
-        let new_question3_id = question_dao.create_question(new_question3).await.expect("question should be created successfully");
+        let new_question1_id = question_dao.create_question(new_question1).await.expect("question should be created successfully").id();
+        let new_question2_id = question_dao.create_question(new_question2).await.expect("question should be created successfully").id();
+        let new_question3_id = question_dao.create_question(new_question3).await.expect("question should be created successfully").id();
         // Attempt to get records from the database
         let get_res = question_dao.get_questions().await;
         println!("{:?}", get_res);
@@ -112,23 +214,23 @@ mod question_tests {
         let question_dao = QuestionDaoImpl::new(pool);
         let id = Uuid::new_v4();
         let question_id = EntityId::new(id.to_string());
-        let del_res = question_dao.delete_question(question_id).await;
+        let del_res = question_dao.delete_question(question_id, false).await;
         println!("{:?}", del_res);
         assert!(del_res.is_err());
-        let Err(DbError::NotFound(e)) = del_res else {panic!("error should be `Deletion`")};
+        let Err(DbError::NotFound { .. }) = del_res else {panic!("error should be `NotFound`")};
     }
 
     #[sqlx::test]
     async fn delete_question_should_succeed(pool: PgPool) {
         let question_dao = QuestionDaoImpl::new(pool);
         // insert a question into the database
-        let new_question = NewQuestion { title: String::from("Test Question1"), question: String::from("Hello this question is a test") };
+        let new_question = fixtures::question().build();
         let new_question_id = question_dao.create_question(new_question)
             .await;
         println!("{:?}", new_question_id);
         assert!(new_question_id.is_ok());
-        let new_question_id = EntityId::new(new_question_id.unwrap().to_string());
-        let deleted_question_id = question_dao.delete_question(new_question_id).await;
+        let new_question_id = EntityId::new(new_question_id.unwrap().id().to_string());
+        let deleted_question_id = question_dao.delete_question(new_question_id, false).await;
         println!("{:?}", deleted_question_id);
         assert!(deleted_question_id.is_ok());
     }
@@ -141,85 +243,5638 @@ mod question_tests {
         let inc_res = question_dao.increment_question_likes(question_id).await;
         println!("{:?}", inc_res);
         assert!(inc_res.is_err());
-        let Err(DbError::NotFound(e)) = inc_res else { panic!("Error should be `NotFound` variant") };
+        let Err(DbError::NotFound { .. }) = inc_res else { panic!("Error should be `NotFound` variant") };
     }
 
     #[sqlx::test]
     async fn increment_question_likes_should_succeed(pool: PgPool) {
         let question_dao = QuestionDaoImpl::new(pool);
-        let new_question = NewQuestion { title: String::from("Test Question1"), question: String::from("Hello this question is a test") };
+        let new_question = fixtures::question().build();
         let question_id = question_dao.create_question(new_question).await;
         println!("{:?}", question_id);
         assert!(question_id.is_ok());
-        let question_id = EntityId::new(question_id.unwrap().to_string());
+        let question_id = EntityId::new(question_id.unwrap().id().to_string());
         let inc_res = question_dao.increment_question_likes(question_id).await;
         println!("{:?}", inc_res);
-        assert!(inc_res.is_ok());
+        assert_eq!(inc_res.unwrap(), 1);
     }
-}
 
-mod answer_tests {
-    use sqlx::types::Uuid;
-    use crate::models::{DbError, EntityId, NewAnswer, NewQuestion};
-    use crate::persistence::prelude::PgPool;
-    use crate::persistence::AnswerDaoImpl;
-    use crate::persistence::AnswerDao;
-    use crate::persistence::QuestionDaoImpl;
-    use crate::persistence::QuestionDao;
+    #[sqlx::test]
+    async fn create_question_idempotent_should_create_once(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let fixture = fixtures::question();
+        let first_res = question_dao.create_question_idempotent(fixture.clone().build(), "test-key").await;
+        println!("{:?}", first_res);
+        let Ok(CreateOutcome::Created(id)) = first_res else { panic!("Outcome should be `Created` variant") };
+
+        // An exact retry should return the original question without inserting
+        let retry_res = question_dao.create_question_idempotent(fixture.build(), "test-key").await;
+        println!("{:?}", retry_res);
+        assert_eq!(retry_res.unwrap(), CreateOutcome::AlreadyCreated(id));
+        assert_eq!(count_rows(&pool, "questions").await, 1);
+    }
 
     #[sqlx::test]
-    async fn create_answer_should_fail_with_invalid_id_err(pool: PgPool) {
-        let new_answer = NewAnswer { question_id: String::from("invalid question id"), answer: String::from("Test answer") };
-        let answer_dao = AnswerDaoImpl::new(pool);
-        let res = answer_dao.create_answer(new_answer).await;
+    async fn purge_idempotency_keys_should_keep_keys_at_the_cutoff(pool: PgPool) {
+        let clock = Arc::new(FixedClock::new(Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap()));
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_clock(clock.clone());
+        question_dao.create_question_idempotent(fixtures::question().build(), "test-key").await.expect("question should be created successfully");
+        // A key exactly as old as the time to live has not yet expired
+        clock.advance(Duration::days(1));
+        let purge_res = question_dao.purge_idempotency_keys(Duration::days(1)).await;
+        println!("{:?}", purge_res);
+        assert_eq!(purge_res.unwrap(), 0);
+        clock.advance(Duration::seconds(1));
+        let purge_res = question_dao.purge_idempotency_keys(Duration::days(1)).await;
+        println!("{:?}", purge_res);
+        assert_eq!(purge_res.unwrap(), 1);
+    }
+
+    #[sqlx::test]
+    async fn create_question_idempotent_should_fail_with_conflict(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let fixture = fixtures::question();
+        question_dao.create_question_idempotent(fixture.clone().build(), "test-key").await.expect("question should be created successfully");
+        let new_question = fixture.question("Different content").build();
+        let retry_res = question_dao.create_question_idempotent(new_question, "test-key").await;
+        println!("{:?}", retry_res);
+        let Err(DbError::Conflict { .. }) = retry_res else { panic!("Error should be `Conflict` variant") };
+        assert_eq!(count_rows(&pool, "questions").await, 1);
+    }
+
+    #[sqlx::test]
+    async fn create_question_should_use_clock_for_created_at(pool: PgPool) {
+        let now = Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap();
+        let question_dao = QuestionDaoImpl::new(pool).with_clock(Arc::new(FixedClock::new(now)));
+        let question_id = question_dao.create_question(fixtures::question().build()).await.expect("question should be created successfully").id();
+        let question = question_dao.get_question(EntityId::new(question_id.to_string())).await.expect("question should be found");
+        assert_eq!(question.created_at(), now);
+    }
+
+    #[sqlx::test]
+    async fn purge_idempotency_keys_should_remove_expired_keys(pool: PgPool) {
+        let clock = Arc::new(FixedClock::new(Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap()));
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_clock(clock.clone());
+        question_dao.create_question_idempotent(fixtures::question().build(), "old-key").await.expect("question should be created successfully");
+        clock.advance(Duration::days(2));
+        question_dao.create_question_idempotent(fixtures::question().build(), "new-key").await.expect("question should be created successfully");
+        let purge_res = question_dao.purge_idempotency_keys(Duration::days(1)).await;
+        println!("{:?}", purge_res);
+        assert_eq!(purge_res.unwrap(), 1);
+        let keys: Vec<String> = sqlx::query_scalar("SELECT key FROM idempotency_keys")
+            .fetch_all(&pool)
+            .await
+            .expect("keys should be selected successfully");
+        assert_eq!(keys, vec![String::from("new-key")]);
+        // Once purged, the key can be used to create a new question
+        let new_question = fixtures::question().build();
+        let res = question_dao.create_question_idempotent(new_question, "old-key").await;
+        let Ok(CreateOutcome::Created(_)) = res else { panic!("Outcome should be `Created` variant") };
+    }
+
+    #[sqlx::test]
+    async fn increment_question_likes_should_fail_with_from_row_on_mistyped_column(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let new_question = fixtures::question().build();
+        let question_id = question_dao.create_question(new_question).await.expect("question should be created successfully").id();
+        // Change the column type so it no longer decodes as an i64
+        sqlx::query("ALTER TABLE questions ALTER COLUMN likes TYPE NUMERIC")
+            .execute(&pool)
+            .await
+            .expect("column type should be altered successfully");
+        let inc_res = question_dao.increment_question_likes(EntityId::new(question_id.to_string())).await;
+        println!("{:?}", inc_res);
+        let Err(e @ DbError::Internal { reason: InternalReason::Decode, .. }) = inc_res else { panic!("Error should be `Decode` reason") };
+        assert!(e.debug_details().is_some_and(|details| details.contains("likes")));
+    }
+
+    #[sqlx::test]
+    async fn increment_question_likes_should_increment_past_i32_max(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let question_id = question_dao.create_question(fixtures::question().build()).await.expect("question should be created successfully").id();
+        let admin_dao = AdminDaoImpl::new(pool.clone());
+        admin_dao.set_question_likes(EntityId::uuid(question_id), i32::MAX as i64).await.expect("likes should be set successfully");
+        for _ in 0..2 {
+            question_dao.increment_question_likes(EntityId::uuid(question_id)).await.expect("question should be liked successfully");
+        }
+        let question = question_dao.get_question(EntityId::uuid(question_id)).await.expect("question should be found");
+        assert_eq!(question.likes(), i32::MAX as i64 + 2);
+    }
+
+    #[sqlx::test]
+    async fn increment_question_likes_should_fail_with_validation_on_overflow(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let question_id = question_dao.create_question(fixtures::question().build()).await.expect("question should be created successfully").id();
+        AdminDaoImpl::new(pool.clone()).set_question_likes(EntityId::uuid(question_id), i64::MAX).await.expect("likes should be set successfully");
+        let inc_res = question_dao.increment_question_likes(EntityId::uuid(question_id)).await;
+        println!("{:?}", inc_res);
+        let Err(DbError::Validation { .. }) = inc_res else { panic!("Error should be `Validation` variant") };
+        let question = question_dao.get_question(EntityId::uuid(question_id)).await.expect("question should be found");
+        assert_eq!(question.likes(), i64::MAX);
+    }
+
+    #[sqlx::test]
+    async fn create_and_update_question_should_maintain_content_stats(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool);
+        let question = question_dao.create_question(fixtures::question().question("How do I sort a vec?").build())
+            .await
+            .expect("question should be created successfully");
+        assert_eq!((question.char_count(), question.word_count()), (20, 6));
+        let update = UpdateQuestion { title: Some(String::from("Only the title")), question: None };
+        let question = question_dao.update_question(EntityId::uuid(question.id()), update).await.expect("question should be updated successfully");
+        assert_eq!((question.char_count(), question.word_count()), (20, 6));
+        let update = UpdateQuestion { title: None, question: Some(String::from("Sorting?")) };
+        let question = question_dao.update_question(EntityId::uuid(question.id()), update).await.expect("question should be updated successfully");
+        assert_eq!((question.char_count(), question.word_count()), (8, 1));
+    }
+
+    #[sqlx::test]
+    async fn get_questions_shorter_than_should_return_short_questions_shortest_first(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool);
+        let mut ids = Vec::new();
+        for content in ["one two three", "one", "one two three four five six", "uno"] {
+            let question = question_dao.create_question(fixtures::question().question(content).build())
+                .await
+                .expect("question should be created successfully");
+            ids.push(question.id());
+        }
+        let res = question_dao.get_questions_shorter_than(4).await;
         println!("{:?}", res);
-        assert!(res.is_err());
-        let Err(DbError::InvalidUuid(s)) = res else { panic!("Error should be `InvalidUuid` variant") };
+        let short: Vec<Uuid> = res.unwrap().iter().map(|question| question.id()).collect();
+        assert_eq!(short, [ids[1], ids[3], ids[0]]);
+        assert!(question_dao.get_questions_shorter_than(1).await.unwrap().is_empty());
     }
 
     #[sqlx::test]
-    async fn create_answer_should_fail_with_access_err(pool: PgPool) {
+    async fn get_questions_in_period_should_use_local_boundaries(pool: PgPool) {
+        let clock = Arc::new(FixedClock::new(Utc.with_ymd_and_hms(2024, 3, 10, 4, 30, 0).unwrap()));
+        let question_dao = QuestionDaoImpl::new(pool).with_clock(clock.clone());
+        // 2024-03-08T23:30 in New York, the Friday before the clocks spring forward
+        clock.set(Utc.with_ymd_and_hms(2024, 3, 9, 4, 30, 0).unwrap());
+        let friday = question_dao.create_question(fixtures::question().build()).await.expect("question should be created successfully").id();
+        // 2024-03-09T23:30 in New York, but already 2024-03-10 in UTC
+        clock.set(Utc.with_ymd_and_hms(2024, 3, 10, 4, 30, 0).unwrap());
+        let saturday = question_dao.create_question(fixtures::question().build()).await.expect("question should be created successfully").id();
+        // 2024-03-02T12:00 in New York, the previous week
+        clock.set(Utc.with_ymd_and_hms(2024, 3, 2, 17, 0, 0).unwrap());
+        let last_week = question_dao.create_question(fixtures::question().build()).await.expect("question should be created successfully").id();
+
+        let ids = |questions: Vec<crate::models::Question>| questions.iter().map(|question| question.id()).collect::<Vec<Uuid>>();
+        clock.set(Utc.with_ymd_and_hms(2024, 3, 10, 4, 45, 0).unwrap());
+        let res = question_dao.get_questions_in_period(Period::Today, New_York).await;
+        println!("{:?}", res);
+        assert_eq!(ids(res.unwrap()), [saturday]);
+        assert_eq!(ids(question_dao.get_questions_in_period(Period::Today, UTC).await.unwrap()), [saturday]);
+        assert_eq!(ids(question_dao.get_questions_in_period(Period::ThisWeek, New_York).await.unwrap()), [friday, saturday]);
+        assert_eq!(ids(question_dao.get_questions_in_period(Period::ThisMonth, New_York).await.unwrap()), [last_week, friday, saturday]);
+        // Sunday 2024-03-10 in New York lasts until 04:00 UTC, an hour earlier than the day before
+        clock.set(Utc.with_ymd_and_hms(2024, 3, 11, 3, 59, 0).unwrap());
+        assert!(question_dao.get_questions_in_period(Period::Today, New_York).await.unwrap().is_empty());
+    }
+
+    async fn set_likes(pool: &PgPool, question_id: Uuid, likes: i64) {
+        sqlx::query("UPDATE questions SET likes = $1 WHERE id = $2").bind(likes).bind(question_id).execute(pool).await.unwrap();
+    }
+
+    #[sqlx::test]
+    async fn get_questions_by_like_range_should_include_both_bounds(pool: PgPool) {
+        let clock = Arc::new(FixedClock::new(Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap()));
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_clock(clock.clone());
+        let mut ids = Vec::new();
+        for likes in [0, 3, 5, 8] {
+            let question_id = question_dao.create_question(fixtures::question().build()).await.expect("question should be created successfully").id();
+            set_likes(&pool, question_id, likes).await;
+            ids.push(question_id);
+            clock.advance(Duration::hours(1));
+        }
+
+        let ids_of = |questions: Vec<Question>| questions.iter().map(|question| question.id()).collect::<Vec<Uuid>>();
+        let res = question_dao.get_questions_by_like_range(3, Some(5), None).await;
+        println!("{:?}", res);
+        assert_eq!(ids_of(res.unwrap()), [ids[2], ids[1]]);
+        assert_eq!(ids_of(question_dao.get_questions_by_like_range(3, Some(3), None).await.unwrap()), [ids[1]]);
+        assert_eq!(ids_of(question_dao.get_questions_by_like_range(5, None, None).await.unwrap()), [ids[3], ids[2]]);
+        assert_eq!(ids_of(question_dao.get_questions_by_like_range(0, None, None).await.unwrap()), [ids[3], ids[2], ids[1], ids[0]]);
+        // Questions created at the given time are not created after it
+        let created_after = Utc.with_ymd_and_hms(2024, 6, 1, 14, 0, 0).unwrap();
+        assert_eq!(ids_of(question_dao.get_questions_by_like_range(0, None, Some(created_after)).await.unwrap()), [ids[3]]);
+
+        let res = question_dao.get_questions_by_like_range(-1, None, None).await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        let res = question_dao.get_questions_by_like_range(5, Some(4), None).await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+    }
+
+    #[sqlx::test]
+    async fn get_questions_exceeding_like_rate_should_measure_the_window(pool: PgPool) {
+        let now = Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap();
+        let clock = Arc::new(FixedClock::new(now - Duration::hours(10)));
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_clock(clock.clone());
+        let fast = question_dao.create_question(fixtures::question().build()).await.expect("question should be created successfully").id();
+        let slow = question_dao.create_question(fixtures::question().build()).await.expect("question should be created successfully").id();
+        clock.set(now - Duration::hours(4));
+        let legacy = question_dao.create_question(fixtures::question().build()).await.expect("question should be created successfully").id();
+        clock.set(now - Duration::hours(1));
+        let unliked = question_dao.create_question(fixtures::question().build()).await.expect("question should be created successfully").id();
+        // Likes before the window are left out, including those exactly at its start
+        clock.set(now - Duration::hours(5));
+        for _ in 0..2 {
+            question_dao.increment_question_likes(EntityId::uuid(fast)).await.expect("question should be liked");
+        }
+        clock.set(now - Duration::hours(2));
+        question_dao.increment_question_likes(EntityId::uuid(slow)).await.expect("question should be liked");
+        clock.set(now - Duration::hours(1));
+        for _ in 0..6 {
+            question_dao.increment_question_likes(EntityId::uuid(fast)).await.expect("question should be liked");
+        }
+        clock.set(now - Duration::minutes(30));
+        question_dao.increment_question_likes(EntityId::uuid(slow)).await.expect("question should be liked");
+        // Liked before like events were recorded
+        set_likes(&pool, legacy, 10).await;
+        clock.set(now);
+
+        let rates = |questions: Vec<QuestionLikeRate>| questions.iter()
+            .map(|rate| (rate.question.id(), rate.likes_per_hour, rate.estimated))
+            .collect::<Vec<_>>();
+        // 6 likes over 2 hours, and 10 likes over the 4 hours since the legacy question was created
+        let res = question_dao.get_questions_exceeding_like_rate(0.5, Duration::hours(2)).await;
+        println!("{:?}", res);
+        assert_eq!(rates(res.unwrap()), [(fast, 3.0, false), (legacy, 2.5, true)]);
+        let res = question_dao.get_questions_exceeding_like_rate(0.0, Duration::hours(2)).await;
+        assert_eq!(rates(res.unwrap()), [(fast, 3.0, false), (legacy, 2.5, true), (slow, 0.5, false)]);
+        // Over a wider window the earlier likes count, but are spread over more time
+        let res = question_dao.get_questions_exceeding_like_rate(0.0, Duration::hours(8)).await;
+        assert_eq!(rates(res.unwrap()), [(legacy, 2.5, true), (fast, 1.0, false), (slow, 0.25, false)]);
+        assert!(question_dao.get_questions_exceeding_like_rate(3.0, Duration::hours(2)).await.unwrap().iter().all(|rate| rate.question.id() != unliked));
+
+        for (likes_per_hour, window) in [
+            (-1.0, Duration::hours(1)),
+            (f64::NAN, Duration::hours(1)),
+            (1.0, Duration::zero()),
+            (1.0, Duration::days(MAX_LIKE_RATE_WINDOW_DAYS) + Duration::seconds(1)),
+        ] {
+            let res = question_dao.get_questions_exceeding_like_rate(likes_per_hour, window).await;
+            println!("{:?}", res);
+            let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        }
+    }
+
+    #[sqlx::test]
+    async fn record_view_should_count_once_per_window(pool: PgPool) {
+        let clock = Arc::new(FixedClock::new(Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap()));
+        let question_dao = QuestionDaoImpl::new(pool).with_clock(clock.clone()).with_view_window(Duration::minutes(30));
+        let question_id = question_dao.create_question(fixtures::question().build()).await.expect("question should be created successfully").id();
+        let view_res = question_dao.record_view(EntityId::uuid(question_id), "client-a").await;
+        println!("{:?}", view_res);
+        assert_eq!(view_res.unwrap(), ViewOutcome::Counted);
+        // Refreshing within the window should not count, even as the window nears its end
+        clock.advance(Duration::minutes(29));
+        let view_res = question_dao.record_view(EntityId::uuid(question_id), "client-a").await;
+        println!("{:?}", view_res);
+        assert_eq!(view_res.unwrap(), ViewOutcome::Repeated);
+        // Other clients count independently
+        let view_res = question_dao.record_view(EntityId::uuid(question_id), "client-b").await;
+        assert_eq!(view_res.unwrap(), ViewOutcome::Counted);
+        // The window runs from the last counted view rather than the last refresh
+        clock.advance(Duration::minutes(1));
+        let view_res = question_dao.record_view(EntityId::uuid(question_id), "client-a").await;
+        println!("{:?}", view_res);
+        assert_eq!(view_res.unwrap(), ViewOutcome::Counted);
+        let question = question_dao.get_question(EntityId::uuid(question_id)).await.expect("question should be found");
+        assert_eq!(question.views(), 3);
+    }
+
+    #[sqlx::test]
+    async fn record_view_should_fail_with_not_found(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool);
+        let view_res = question_dao.record_view(EntityId::uuid(Uuid::new_v4()), "client-a").await;
+        println!("{:?}", view_res);
+        let Err(DbError::NotFound { .. }) = view_res else { panic!("Error should be `NotFound` variant") };
+    }
+
+    #[sqlx::test]
+    async fn get_question_header_should_count_published_answers(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
         let answer_dao = AnswerDaoImpl::new(pool.clone());
-        pool.close().await;
-        let new_answer = NewAnswer { question_id: Uuid::new_v4().to_string(), answer: String::from("Test answer") };
-        let res = answer_dao.create_answer(new_answer).await;
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 2).await;
+        let new_answer = NewAnswer { question_id: question_id.to_string(), answer: String::from("Draft answer"), author_id: None, content_type: None, author_token: None, parent_answer_id: None };
+        answer_dao.create_answer_draft(new_answer).await.expect("draft should be created successfully");
+        question_dao.increment_question_likes(EntityId::uuid(question_id)).await.expect("question should be liked successfully");
+        question_dao.pin_answer(EntityId::uuid(question_id), EntityId::uuid(answer_ids[0])).await.expect("answer should be pinned successfully");
+        question_dao.accept_answer(EntityId::uuid(question_id), EntityId::uuid(answer_ids[1])).await.expect("answer should be accepted successfully");
+        let res = question_dao.get_question_header(EntityId::uuid(question_id)).await;
         println!("{:?}", res);
-        assert!(res.is_err());
-        let Err(DbError::Access(e)) = res else { panic!("Error should be `Creation` variant") };
+        let header = res.unwrap();
+        assert_eq!(header.question().id(), question_id);
+        assert_eq!(header.question().likes(), 1);
+        // Drafts are not counted
+        assert_eq!(header.answer_count(), 2);
+        assert!(header.has_accepted_answer());
+        assert_eq!(header.accepted_answer_id(), Some(answer_ids[1]));
     }
 
     #[sqlx::test]
-    async fn create_answer_should_fail_with_not_found_err(pool: PgPool) {
+    async fn get_question_header_should_succeed_for_bare_question(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let question = question_dao.create_question(fixtures::question().build()).await.expect("question should be created successfully");
+        let res = question_dao.get_question_header(EntityId::serial(question.serial().expect("serial should be assigned"))).await;
+        println!("{:?}", res);
+        let header = res.unwrap();
+        assert_eq!(header.question().title(), question.title());
+        assert_eq!(header.answer_count(), 0);
+        assert_eq!((header.has_accepted_answer(), header.accepted_answer_id()), (false, None));
+    }
+
+    #[sqlx::test]
+    async fn get_question_header_should_fail_with_not_found(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool);
+        let res = question_dao.get_question_header(EntityId::uuid(Uuid::new_v4())).await;
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+    }
+
+    /// Reads the detail of a question, creating an answer on another connection between the header and the answers.
+    async fn read_detail_with_concurrent_answer(pool: &PgPool, snapshot: bool) -> QuestionDetail {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        let (question_id, _) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 1).await;
+        let concurrent_answer = async {
+            let new_answer = NewAnswer { question_id: question_id.to_string(), answer: String::from("Concurrent answer"), author_id: None, content_type: None, author_token: None, parent_answer_id: None };
+            answer_dao.create_answer(new_answer).await.expect("answer should be created successfully");
+        };
+        let res = question_dao.read_question_detail(question_id, snapshot, None, None, concurrent_answer).await;
+        println!("{:?}", res);
+        res.unwrap()
+    }
+
+    #[sqlx::test]
+    async fn question_detail_should_stay_consistent_within_a_snapshot(pool: PgPool) {
+        let detail = read_detail_with_concurrent_answer(&pool, true).await;
+        assert!(detail.is_snapshot());
+        assert_eq!(detail.header().answer_count(), 1);
+        assert_eq!(detail.answers().len(), 1);
+    }
+
+    #[sqlx::test]
+    async fn question_detail_can_drift_with_separate_reads(pool: PgPool) {
+        let detail = read_detail_with_concurrent_answer(&pool, false).await;
+        assert!(!detail.is_snapshot());
+        // The header was read before the answer was created, and the answers after
+        assert_eq!(detail.header().answer_count(), 1);
+        assert_eq!(detail.answers().len(), 2);
+    }
+
+    #[sqlx::test]
+    async fn get_question_detail_consistent_should_fall_back_in_a_scope(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 2).await;
+        let res = question_dao.get_question_detail_consistent(EntityId::uuid(question_id)).await;
+        println!("{:?}", res);
+        let detail = res.unwrap();
+        assert!(detail.is_snapshot());
+        assert_eq!(detail.header().question().id(), question_id);
+        assert_eq!(detail.header().answer_count(), 2);
+        assert_eq!(detail.answers().iter().map(Answer::id).collect::<Vec<_>>(), answer_ids);
+
+        let scope = ScopedDao::begin(&pool).await.expect("scope should begin");
+        let detail = scope.get_question_detail_consistent(EntityId::uuid(question_id)).await.expect("detail should be read");
+        assert!(!detail.is_snapshot());
+        assert_eq!(detail.answers().len(), 2);
+        scope.rollback().await.expect("scope should roll back");
+
+        let res = question_dao.get_question_detail_consistent(EntityId::uuid(Uuid::new_v4())).await;
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+    }
+
+    #[sqlx::test]
+    async fn get_question_detail_with_should_embed_the_top_answers(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 30).await;
+        // Answers created later are liked more, so the most liked are the last ones
+        for (likes, answer_id) in answer_ids.iter().enumerate() {
+            sqlx::query("UPDATE answers SET likes = $2 WHERE id = $1").bind(answer_id).bind(likes as i32).execute(&pool).await.unwrap();
+        }
+        let options = DetailOptions {
+            max_embedded_answers: 5,
+            sort: AnswerSort::MostLiked,
+            include_total: true,
+            include_translations: false,
+            include_archived: false,
+            answer_bodies: AnswerBodyMode::Full,
+        };
+        let (res, statements) = QueryCounter::record(question_dao.get_question_detail_with(EntityId::uuid(question_id), options)).await;
+        println!("{:?}", res);
+        let detail = res.unwrap();
+        let top_ids = answer_ids.iter().rev().take(5).copied().collect::<Vec<_>>();
+        assert_eq!(detail.answers().iter().map(Answer::id).collect::<Vec<_>>(), top_ids);
+        assert_eq!(detail.total_answers(), Some(30));
+        // The cap is applied by the query rather than after reading every answer
+        let limited = |statement: &String| statement.replace("\\n", " ").split_whitespace().collect::<Vec<_>>().windows(2).any(|words| words == ["LIMIT", "5"]);
+        assert!(statements.iter().any(limited), "{statements:?}");
+
+        let options = DetailOptions { include_total: false, ..options };
+        let detail = question_dao.get_question_detail_with(EntityId::uuid(question_id), options).await.expect("detail should be read");
+        assert_eq!(detail.answers().len(), 5);
+        assert_eq!(detail.total_answers(), None);
+        let res = question_dao.get_question_detail_with(EntityId::uuid(question_id), DetailOptions { max_embedded_answers: MAX_PAGE_SIZE + 1, ..options }).await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+    }
+
+    #[sqlx::test]
+    async fn get_question_detail_with_should_embed_every_answer_below_the_cap(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
         let answer_dao = AnswerDaoImpl::new(pool);
-        let new_answer = NewAnswer { question_id: Uuid::new_v4().to_string(), answer: String::from("Test answer") };
-        let res = answer_dao.create_answer(new_answer).await;
+        let options = DetailOptions { max_embedded_answers: 5, sort: AnswerSort::Newest, ..DetailOptions::default() };
+        let (question_id, _) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 0).await;
+        let detail = question_dao.get_question_detail_with(EntityId::uuid(question_id), options).await.expect("detail should be read");
+        assert!(detail.answers().is_empty());
+        assert_eq!(detail.total_answers(), Some(0));
+
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 3).await;
+        let res = question_dao.get_question_detail_with(EntityId::uuid(question_id), options).await;
         println!("{:?}", res);
-        assert!(res.is_err());
-        let Err(DbError::NotFound(e)) = res else { panic!("Error should be `NotFound` variant.")};
+        let detail = res.unwrap();
+        let mut embedded = detail.answers().iter().map(Answer::id).collect::<Vec<_>>();
+        embedded.sort();
+        let mut expected = answer_ids.clone();
+        expected.sort();
+        assert_eq!(embedded, expected);
+        assert_eq!(detail.total_answers(), Some(3));
     }
 
     #[sqlx::test]
-    async fn create_answer_should_succeed(pool: PgPool){
-        // Create Dao's for question and answer tables
+    async fn get_question_detail_with_should_shape_answer_bodies(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_clock(Arc::new(SteppingClock::new(Utc::now(), Duration::seconds(1))));
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let (question_id, _) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 0).await;
+        // Two bytes per character, so a cut by bytes would split one
+        let long = "é".repeat(150);
+        for content in [long.as_str(), "Short answer"] {
+            answer_dao.create_answer(fixtures::answer(question_id).answer(content).build()).await.expect("answer should be created successfully");
+        }
+        let options = DetailOptions { sort: AnswerSort::Oldest, ..DetailOptions::default() };
+
+        let detail = question_dao.get_question_detail_with(EntityId::uuid(question_id), options).await.expect("detail should be read");
+        let response = QuestionDetailResponse::from_detail(detail, options.answer_bodies);
+        assert_eq!(response.answers[0].answer, long);
+        assert!(response.answers.iter().all(|answer| !answer.truncated));
+
+        let options = DetailOptions { answer_bodies: AnswerBodyMode::Preview, ..options };
+        let detail = question_dao.get_question_detail_with(EntityId::uuid(question_id), options).await.expect("detail should be read");
+        let response = QuestionDetailResponse::from_detail(detail, options.answer_bodies);
+        assert_eq!(response.answers[0].answer, "é".repeat(ANSWER_PREVIEW_CHARS));
+        assert!(response.answers[0].truncated);
+        assert_eq!((response.answers[1].answer.as_str(), response.answers[1].truncated), ("Short answer", false));
+
+        let options = DetailOptions { answer_bodies: AnswerBodyMode::None, ..options };
+        let (res, statements) = QueryCounter::record(question_dao.get_question_detail_with(EntityId::uuid(question_id), options)).await;
+        println!("{:?}", res);
+        let detail = res.unwrap();
+        assert!(detail.answers().iter().all(|answer| answer.answer().is_empty() && answer.likes() == 0));
+        // The content column of the answers is left out of the statement reading them
+        let statement = statements.iter()
+            .map(|statement| statement.replace("\\n", " "))
+            .find(|statement| statement.contains("LIMIT"))
+            .unwrap_or_else(|| panic!("the embedded answers should be read, got {statements:?}"));
+        let words: Vec<&str> = statement.split(|c: char| !c.is_alphanumeric() && c != '_').collect();
+        assert!(words.contains(&"likes") && !words.contains(&"answer"), "{statement}");
+        let json = serde_json::to_value(QuestionDetailResponse::from_detail(detail, options.answer_bodies)).unwrap();
+        let answers = json["answers"].as_array().unwrap();
+        assert_eq!(answers.len(), 2);
+        assert!(answers.iter().all(|answer| answer.get("answer").is_none() && answer["truncated"] == true), "{json}");
+    }
+
+    #[sqlx::test]
+    async fn upsert_translation_should_replace_the_translation_into_a_language(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let question_id = fixtures::seed_questions(&question_dao, 1).await[0];
+        let content = |title: &str| NewTranslation { title: String::from(title), question: String::from("Wie benutze ich das?") };
+        let res = question_dao.upsert_translation(EntityId::uuid(question_id), "DE", content("Erste Frage")).await;
+        println!("{:?}", res);
+        let translation = res.unwrap();
+        assert_eq!((translation.question_id(), translation.lang()), (question_id, "de"));
+        question_dao.upsert_translation(EntityId::uuid(question_id), "de", content("Frage")).await.expect("translation should be replaced");
+
+        let res = question_dao.get_translation(EntityId::uuid(question_id), "de").await;
+        println!("{:?}", res);
+        let question = res.unwrap();
+        assert_eq!((question.title(), question.question()), ("Frage", "Wie benutze ich das?"));
+        assert_eq!(question.lang(), Some("de"));
+        assert!(question.is_translation());
+        let response = QuestionResponse::from(question);
+        assert_eq!((response.lang.as_deref(), response.is_translation), (Some("de"), true));
+
+        let options = DetailOptions { include_translations: true, ..DetailOptions::default() };
+        let detail = question_dao.get_question_detail_with(EntityId::uuid(question_id), options).await.expect("detail should be read");
+        assert_eq!(detail.translations().iter().map(|translation| translation.title()).collect::<Vec<_>>(), ["Frage"]);
+        let detail = question_dao.get_question_detail_with(EntityId::uuid(question_id), DetailOptions::default()).await.expect("detail should be read");
+        assert!(detail.translations().is_empty());
+
+        question_dao.lock_question(EntityId::uuid(question_id)).await.expect("question should be locked");
+        let res = question_dao.upsert_translation(EntityId::uuid(question_id), "de", content("Gesperrt")).await;
+        println!("{:?}", res);
+        let Err(DbError::Conflict { reason: ConflictReason::Locked(_), .. }) = res else { panic!("Error should be `Locked` reason") };
+    }
+
+    #[sqlx::test]
+    async fn get_translation_should_fall_back_to_the_original(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let question = question_dao.create_question(fixtures::question().build()).await.expect("question should be created successfully");
+        let content = NewTranslation { title: String::from("Question"), question: String::from("Contenu") };
+        question_dao.upsert_translation(EntityId::uuid(question.id()), "fr", content).await.expect("translation should be created");
+        let res = question_dao.get_translation(EntityId::uuid(question.id()), "es").await;
+        println!("{:?}", res);
+        let original = res.unwrap();
+        assert_eq!((original.title(), original.question()), (question.title(), question.question()));
+        assert!(!original.is_translation());
+        let body = serde_json::to_value(QuestionResponse::from(original)).unwrap();
+        assert!(body.get("lang").is_none() && body.get("is_translation").is_none());
+
+        let res = question_dao.get_translation(EntityId::uuid(Uuid::new_v4()), "fr").await;
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+    }
+
+    #[sqlx::test]
+    async fn upsert_translation_should_fail_with_validation_err_for_unsupported_language(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let question_id = fixtures::seed_questions(&question_dao, 1).await[0];
+        for lang in ["", "xx", "de_DE", "english"] {
+            let content = NewTranslation { title: String::from("Title"), question: String::from("Question") };
+            let res = question_dao.upsert_translation(EntityId::uuid(question_id), lang, content).await;
+            println!("{:?}", res);
+            let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+            let res = question_dao.get_translation(EntityId::uuid(question_id), lang).await;
+            let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        }
+        let content = NewTranslation { title: String::from("Title"), question: String::from("Question") };
+        let res = question_dao.upsert_translation(EntityId::uuid(Uuid::new_v4()), "en", content).await;
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+    }
+
+    #[sqlx::test]
+    async fn delete_question_should_delete_its_translations(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let question_id = fixtures::seed_questions(&question_dao, 1).await[0];
+        for lang in ["en", "pt-BR"] {
+            let content = NewTranslation { title: String::from("Title"), question: String::from("Question") };
+            question_dao.upsert_translation(EntityId::uuid(question_id), lang, content).await.expect("translation should be created");
+        }
+        let res = question_dao.delete_question(EntityId::uuid(question_id), true).await;
+        println!("{:?}", res);
+        assert!(res.is_ok());
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM question_translations WHERE question_id = $1")
+            .bind(question_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    /// The kind and id of each item of an activity feed, in order.
+    fn feed_ids(items: &[ActivityItem]) -> Vec<(&'static str, Uuid)> {
+        items
+            .iter()
+            .map(|item| match item {
+                ActivityItem::QuestionPosted(question) => ("question", question.id()),
+                ActivityItem::AnswerPosted { answer, .. } => ("answer", answer.id()),
+            })
+            .collect()
+    }
+
+    #[sqlx::test]
+    async fn get_activity_feed_should_interleave_questions_and_answers(pool: PgPool) {
+        let clock = Arc::new(SteppingClock::new(Utc::now(), Duration::seconds(1)));
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_clock(clock.clone());
+        let answer_dao = AnswerDaoImpl::new(pool).with_clock(clock);
+        let first = question_dao.create_question(fixtures::question().title("First").build()).await.unwrap().id();
+        let a1 = answer_dao.create_answer(fixtures::answer(first).build()).await.unwrap().id();
+        let second = question_dao.create_question(fixtures::question().title("Second").build()).await.unwrap().id();
+        let a2 = answer_dao.create_answer(fixtures::answer(first).build()).await.unwrap().id();
+        let a3 = answer_dao.create_answer(fixtures::answer(second).build()).await.unwrap().id();
+
+        let res = question_dao.get_activity_feed(10, None).await;
+        println!("{:?}", res);
+        let items = res.unwrap();
+        assert_eq!(feed_ids(&items), [("answer", a3), ("answer", a2), ("question", second), ("answer", a1), ("question", first)]);
+        let ActivityItem::AnswerPosted { question_title, .. } = &items[0] else { panic!("Item should be `AnswerPosted` variant") };
+        assert_eq!(question_title, "Second");
+        let json = serde_json::to_value(&items[0]).unwrap();
+        assert_eq!(json["type"], "answer_posted");
+        assert_eq!(json["item"]["question_title"], "Second");
+        assert_eq!(json["item"]["answer"]["id"], a3.to_string());
+        assert_eq!(serde_json::to_value(&items[2]).unwrap()["type"], "question_posted");
+
+        for limit in [0, MAX_PAGE_SIZE + 1] {
+            let res = question_dao.get_activity_feed(limit, None).await;
+            let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        }
+    }
+
+    #[sqlx::test]
+    async fn get_activity_feed_should_continue_across_pages(pool: PgPool) {
+        let clock = Arc::new(SteppingClock::new(Utc::now(), Duration::seconds(1)));
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_clock(clock.clone());
+        let answer_dao = AnswerDaoImpl::new(pool).with_clock(clock);
+        for _ in 0..3 {
+            fixtures::seed_question_with_answers(&question_dao, &answer_dao, 2).await;
+        }
+        let everything = question_dao.get_activity_feed(MAX_PAGE_SIZE, None).await.expect("feed should be read");
+        assert_eq!(everything.len(), 9);
+
+        let mut paged = Vec::new();
+        let mut before = None;
+        loop {
+            let res = question_dao.get_activity_feed(2, before).await;
+            println!("{:?}", res);
+            let page = res.unwrap();
+            let Some(last) = page.last() else { break };
+            before = Some(last.cursor());
+            paged.extend(feed_ids(&page));
+        }
+        assert_eq!(paged, feed_ids(&everything));
+    }
+
+    #[sqlx::test]
+    async fn get_activity_feed_should_continue_among_items_posted_at_once(pool: PgPool) {
+        // Every item is posted at the same time, so only their kind and id tell where a page ends
+        let clock = Arc::new(FixedClock::new(Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap()));
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_clock(clock.clone());
+        let answer_dao = AnswerDaoImpl::new(pool).with_clock(clock);
+        for _ in 0..3 {
+            fixtures::seed_question_with_answers(&question_dao, &answer_dao, 2).await;
+        }
+        let everything = question_dao.get_activity_feed(MAX_PAGE_SIZE, None).await.expect("feed should be read");
+        assert_eq!(everything.len(), 9);
+
+        let mut paged = Vec::new();
+        let mut before = None;
+        loop {
+            let res = question_dao.get_activity_feed(2, before).await;
+            println!("{:?}", res);
+            let page = res.unwrap();
+            let Some(last) = page.last() else { break };
+            before = Some(last.cursor());
+            paged.extend(feed_ids(&page));
+        }
+        assert_eq!(paged, feed_ids(&everything));
+    }
+
+    #[sqlx::test]
+    async fn get_activity_feed_should_exclude_hidden_answers(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_moderation(ModerationMode::Manual);
+        let answer_dao = AnswerDaoImpl::new(pool.clone()).with_moderation(ModerationMode::Manual);
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 1).await;
+        answer_dao.create_answer_draft(fixtures::answer(question_id).build()).await.expect("draft should be created successfully");
+        // Under manual moderation only the approved answer is listed, and drafts never are
+        let res = question_dao.get_activity_feed(10, None).await;
+        println!("{:?}", res);
+        assert_eq!(feed_ids(&res.unwrap()), [("question", question_id)]);
+        answer_dao.approve_answer(EntityId::uuid(answer_ids[0])).await.expect("answer should be approved");
+        let items = question_dao.get_activity_feed(10, None).await.expect("feed should be read");
+        assert_eq!(feed_ids(&items).len(), 2);
+        assert!(feed_ids(&items).contains(&("answer", answer_ids[0])));
+    }
+
+    #[sqlx::test]
+    async fn get_activity_feed_should_exclude_tombstoned_answers(pool: PgPool) {
         let question_dao = QuestionDaoImpl::new(pool.clone());
         let answer_dao = AnswerDaoImpl::new(pool);
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 2).await;
+        answer_dao.delete_answer_with_tombstone(EntityId::uuid(answer_ids[0]), None).await.expect("answer should be tombstoned");
+        let res = question_dao.get_activity_feed(10, None).await;
+        println!("{:?}", res);
+        let ids = feed_ids(&res.unwrap());
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&("question", question_id)) && ids.contains(&("answer", answer_ids[1])));
+    }
 
-        // Insert a new test question into the question table
-        let new_question = NewQuestion { title: String::from("Test Question"), question: String::from("Hello this question is a test") };
-        let new_question_res = question_dao.create_question(new_question).await;
-        println!("{:?}", new_question_res);
-        assert!(new_question_res.is_ok());
+    /// Seeds questions whose text relevance and engagement disagree: a duplicate repeating the search words without
+    /// engagement, a popular question and a question with many answers, returning their ids in that order.
+    async fn seed_rankable_questions(pool: &PgPool) -> [Uuid; 3] {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        let mut ids = Vec::new();
+        for (title, question) in [
+            ("Sort vec sort vec", "sort vec sort vec sort vec"),
+            ("How do I sort a Vec in Rust?", "Sorting a vector of numbers"),
+            ("Sort a Vec", "Which method should I use?"),
+        ] {
+            ids.push(question_dao.create_question(fixtures::question().title(title).question(question).build()).await.unwrap().id());
+        }
+        question_dao.create_question(fixtures::question().title("Unrelated").question("Popular but unrelated").build()).await.unwrap();
+        AdminDaoImpl::new(pool.clone()).set_question_likes(EntityId::uuid(ids[1]), 50).await.expect("likes should be set successfully");
+        for _ in 0..5 {
+            answer_dao.create_answer(fixtures::answer(ids[2]).build()).await.expect("answer should be created successfully");
+        }
+        [ids[0], ids[1], ids[2]]
+    }
 
-        // Get id for new answer
-        let question_id = new_question_res.unwrap().to_string();
+    #[sqlx::test]
+    async fn search_questions_should_blend_text_rank_with_engagement(pool: PgPool) {
+        let [duplicate, popular, answered] = seed_rankable_questions(&pool).await;
+        let question_dao = QuestionDaoImpl::new(pool);
+        let ranked = |ranking: SearchRankingConfig| {
+            let question_dao = &question_dao;
+            async move {
+                let res = question_dao.search_questions("sorting vec", ranking, 10).await;
+                println!("{:?}", res);
+                res.unwrap().iter().map(|ranked| ranked.question.id()).collect::<Vec<_>>()
+            }
+        };
+        let weights = |text_weight, likes_weight, answers_weight| {
+            SearchRankingConfig { text_weight, likes_weight, answers_weight, ..SearchRankingConfig::default() }
+        };
+        // Text relevance alone favors the duplicate repeating the words, and unrelated questions never match
+        assert_eq!(ranked(weights(1.0, 0.0, 0.0)).await, [duplicate, popular, answered]);
+        assert_eq!(ranked(weights(0.0, 1.0, 0.0)).await, [popular, duplicate, answered]);
+        assert_eq!(ranked(weights(0.0, 0.0, 1.0)).await, [answered, duplicate, popular]);
+        // With the default weights engagement lifts the popular question over the duplicate
+        assert_eq!(ranked(SearchRankingConfig::default()).await, [popular, duplicate, answered]);
 
-        // create new answer
-        let new_answer = NewAnswer { question_id, answer: String::from("Test answer") };
+        let res = question_dao.search_questions("sorting vec", SearchRankingConfig::default(), 1).await;
+        assert_eq!(res.unwrap().len(), 1);
+        assert!(question_dao.search_questions("unknown words", SearchRankingConfig::default(), 10).await.unwrap().is_empty());
+    }
 
-        // Attempt to make the query
-        let new_answer_res = answer_dao.create_answer(new_answer).await;
-        println!("{:?}", new_answer_res);
-        assert!(new_answer_res.is_ok());
+    #[sqlx::test]
+    async fn search_questions_should_explain_scores_on_request(pool: PgPool) {
+        let [_, popular, _] = seed_rankable_questions(&pool).await;
+        let question_dao = QuestionDaoImpl::new(pool);
+        let results = question_dao.search_questions("sorting vec", SearchRankingConfig::default(), 10).await.unwrap();
+        assert!(results.iter().all(|ranked| ranked.components.is_none()));
+
+        let ranking = SearchRankingConfig { views_weight: 0.5, debug_explain: true, ..SearchRankingConfig::default() };
+        let res = question_dao.search_questions("sorting vec", ranking, 10).await;
+        println!("{:?}", res);
+        for ranked in res.unwrap() {
+            let components = ranked.components.expect("components should be explained");
+            let expected = components.text_rank * ranking.text_weight + components.likes * ranking.likes_weight
+                + components.answers * ranking.answers_weight + components.views * ranking.views_weight;
+            assert!((ranked.score - expected).abs() < 1e-9, "{} != {expected}", ranked.score);
+            assert!(components.text_rank > 0.0);
+            if ranked.question.id() == popular {
+                assert!((components.likes - 51f64.ln()).abs() < 1e-9);
+                assert_eq!(components.answers, 0.0);
+            }
+            let json = serde_json::to_value(&ranked).unwrap();
+            assert!(json["components"]["text_rank"].is_number());
+        }
+    }
+
+    #[sqlx::test]
+    async fn search_questions_should_reject_invalid_searches(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool);
+        for (query, ranking, limit) in [
+            ("  ", SearchRankingConfig::default(), 10),
+            ("vec", SearchRankingConfig { likes_weight: -1.0, ..SearchRankingConfig::default() }, 10),
+            ("vec", SearchRankingConfig { text_weight: f64::NAN, ..SearchRankingConfig::default() }, 10),
+            ("vec", SearchRankingConfig::default(), 0),
+        ] {
+            let res = question_dao.search_questions(query, ranking, limit).await;
+            println!("{:?}", res);
+            let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        }
+    }
+
+    #[sqlx::test]
+    async fn get_questions_by_token_should_only_list_the_authors_questions(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_clock(Arc::new(SteppingClock::new(Utc::now(), Duration::seconds(1))));
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let token = "my-token_0123456789";
+        let older = question_dao.create_question(fixtures::question().author_token(token).build()).await.unwrap();
+        question_dao.create_question(fixtures::question().author_token("another-token-0123456789").build()).await.unwrap();
+        question_dao.create_question(fixtures::question().build()).await.unwrap();
+        let newer = question_dao.create_question(fixtures::question().author_token(token).build()).await.unwrap();
+        let draft = answer_dao.create_answer_draft(fixtures::answer(older.id()).author_token(token).build()).await.unwrap();
+        answer_dao.create_answer(fixtures::answer(older.id()).build()).await.unwrap();
+
+        let res = question_dao.get_questions_by_token(token).await;
+        println!("{:?}", res);
+        let questions = res.unwrap();
+        assert_eq!(questions.iter().map(Question::id).collect::<Vec<_>>(), [newer.id(), older.id()]);
+        assert!(questions.iter().all(|question| question.is_mine() == Some(true)));
+        let res = answer_dao.get_answers_by_token(token).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap().iter().map(Answer::id).collect::<Vec<_>>(), [draft.id()]);
+        assert!(question_dao.get_questions_by_token("unused-token-0123456789").await.unwrap().is_empty());
+
+        let res = question_dao.get_questions_by_token("short").await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        let Err(DbError::Validation { .. }) = answer_dao.get_answers_by_token("not/url-safe/0123456789").await else {
+            panic!("Error should be `Validation` variant")
+        };
     }
 
+    #[sqlx::test]
+    async fn author_tokens_should_only_surface_as_is_mine(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let token = "my-token_0123456789";
+        let question = question_dao.create_question(fixtures::question().author_token(token).build()).await.unwrap();
+        let mine = answer_dao.create_answer(fixtures::answer(question.id()).author_token(token).build()).await.unwrap();
+        answer_dao.create_answer(fixtures::answer(question.id()).build()).await.unwrap();
+        // Ownership is unknown until the caller gives their token
+        assert_eq!(question.is_mine(), None);
+        let detail = question_dao.get_question_detail_consistent(EntityId::uuid(question.id())).await.unwrap();
+        assert_eq!(detail.header().question().is_mine(), None);
+        assert!(detail.answers().iter().all(|answer| answer.is_mine().is_none()));
+
+        let res = question_dao.get_question_detail_for_author(EntityId::uuid(question.id()), token).await;
+        println!("{:?}", res);
+        let detail = res.unwrap();
+        assert_eq!(detail.header().question().is_mine(), Some(true));
+        let ownership: Vec<_> = detail.answers().iter().map(|answer| (answer.id() == mine.id(), answer.is_mine())).collect();
+        assert!(ownership.iter().all(|(is_mine, reported)| *reported == Some(*is_mine)));
+        let other = question_dao.get_question_detail_for_author(EntityId::uuid(question.id()), "another-token-0123456789").await.unwrap();
+        assert_eq!(other.header().question().is_mine(), Some(false));
+
+        // The token itself is never serialized, only whether the caller is the author
+        let (header, answers) = detail.into_parts();
+        let answers: Vec<AnswerResponse> = answers.into_iter().map(AnswerResponse::from).collect();
+        let json = serde_json::to_string(&(header.question(), answers)).unwrap();
+        assert!(!json.contains(token) && !json.contains("author_token"), "{json}");
+        assert!(json.contains(r#""is_mine":true"#) && json.contains(r#""is_mine":false"#), "{json}");
+        let listed = question_dao.get_questions_by_token(token).await.unwrap().remove(0);
+        let json = serde_json::to_string(&QuestionResponse::from(listed)).unwrap();
+        assert!(!json.contains(token) && json.contains(r#""is_mine":true"#), "{json}");
+        let json = serde_json::to_string(&question).unwrap();
+        assert!(!json.contains(token) && !json.contains("is_mine"), "{json}");
+    }
+
+    #[sqlx::test]
+    async fn set_bounty_should_attach_and_clear_bounty(pool: PgPool) {
+        let now = Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap();
+        let question_dao = QuestionDaoImpl::new(pool).with_clock(Arc::new(FixedClock::new(now)));
+        let question = question_dao.create_question(fixtures::question().build()).await.unwrap();
+        assert_eq!(question.bounty_amount(), None);
+
+        let res = question_dao.set_bounty(EntityId::uuid(question.id()), 100, Duration::days(7)).await;
+        println!("{:?}", res);
+        let bountied = res.unwrap();
+        assert_eq!(bountied.bounty_amount(), Some(100));
+        assert_eq!(bountied.bounty_expires_at(), Some(now + Duration::days(7)));
+        assert_eq!(bountied.bounty_remaining(now), Some(Duration::days(7)));
+        // Setting another bounty replaces it
+        let bountied = question_dao.set_bounty(EntityId::uuid(question.id()), MAX_BOUNTY, Duration::hours(1)).await.unwrap();
+        assert_eq!((bountied.bounty_amount(), bountied.bounty_expires_at()), (Some(MAX_BOUNTY), Some(now + Duration::hours(1))));
+
+        let cleared = question_dao.clear_bounty(EntityId::uuid(question.id())).await.unwrap();
+        assert_eq!((cleared.bounty_amount(), cleared.bounty_expires_at()), (None, None));
+        let fetched = question_dao.get_question(EntityId::uuid(question.id())).await.unwrap();
+        assert_eq!(fetched.bounty_amount(), None);
+    }
+
+    #[sqlx::test]
+    async fn set_bounty_should_reject_invalid_bounties(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool);
+        let question = question_dao.create_question(fixtures::question().build()).await.unwrap();
+        for (amount, duration) in [(0, Duration::days(1)), (MAX_BOUNTY + 1, Duration::days(1)), (-5, Duration::days(1)), (50, Duration::zero())] {
+            let res = question_dao.set_bounty(EntityId::uuid(question.id()), amount, duration).await;
+            println!("{:?}", res);
+            let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        }
+        // Locked questions are still answered, so they still accept bounties
+        question_dao.lock_question(EntityId::uuid(question.id())).await.unwrap();
+        let res = question_dao.set_bounty(EntityId::uuid(question.id()), 50, Duration::days(1)).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap().bounty_amount(), Some(50));
+        let res = question_dao.set_bounty(EntityId::uuid(Uuid::new_v4()), 50, Duration::days(1)).await;
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+    }
+
+    #[sqlx::test]
+    async fn expire_bounties_should_only_clear_expired_bounties(pool: PgPool) {
+        let clock = Arc::new(FixedClock::new(Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap()));
+        let question_dao = QuestionDaoImpl::new(pool).with_clock(clock.clone());
+        let ids = fixtures::seed_questions(&question_dao, 3).await;
+        question_dao.set_bounty(EntityId::uuid(ids[0]), 10, Duration::hours(1)).await.unwrap();
+        question_dao.set_bounty(EntityId::uuid(ids[1]), 20, Duration::hours(3)).await.unwrap();
+        assert!(question_dao.expire_bounties().await.unwrap().is_empty());
+
+        clock.advance(Duration::hours(1));
+        let res = question_dao.expire_bounties().await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), [ids[0]]);
+        let mut bounties = Vec::new();
+        for id in &ids {
+            bounties.push(question_dao.get_question(EntityId::uuid(*id)).await.unwrap().bounty_amount());
+        }
+        assert_eq!(bounties, [None, Some(20), None]);
+        // Sweeping again finds nothing left to expire
+        assert!(question_dao.expire_bounties().await.unwrap().is_empty());
+    }
+
+    #[sqlx::test]
+    async fn get_questions_projected_should_only_read_the_selected_columns(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_clock(Arc::new(SteppingClock::new(Utc::now(), Duration::seconds(1))));
+        let ids = fixtures::seed_questions(&question_dao, 3).await;
+        let page = PageRequest { offset: 0, limit: 2 };
+        let (res, statements) = QueryCounter::record(question_dao.get_questions_projected(QuestionFields::TITLE | QuestionFields::LIKES, page)).await;
+        println!("{:?}", res);
+        let page = res.unwrap();
+        assert_eq!(page.items.iter().map(QuestionPartial::id).collect::<Vec<_>>(), ids[..2]);
+        assert!(page.has_more);
+        assert!(page.items.iter().all(|question| question.title().is_some() && question.likes() == Some(0)));
+        assert!(page.items.iter().all(|question| question.question().is_none() && question.created_at().is_none()));
+        // The content column is left out of the statement, while the table shares its name. The logged statement is
+        // formatted over several lines, with escaped line breaks
+        let [statement] = statements.as_slice() else { panic!("one statement should be executed, got {statements:?}") };
+        let statement = statement.replace("\\n", " ");
+        let words: Vec<&str> = statement.split(|c: char| !c.is_alphanumeric() && c != '_').collect();
+        assert!(words.contains(&"title") && words.contains(&"likes") && words.contains(&"questions"), "{statement}");
+        assert!(!words.contains(&"question"), "{statement}");
+
+        let res = question_dao.get_questions_projected(QuestionFields::NONE, PageRequest { offset: 2, limit: 2 }).await;
+        let page = res.unwrap();
+        assert_eq!(page.items.iter().map(QuestionPartial::id).collect::<Vec<_>>(), ids[2..]);
+        assert!(!page.has_more);
+        assert!(page.items[0].title().is_none());
+    }
+
+    #[sqlx::test]
+    async fn get_questions_projected_with_all_fields_should_match_get_questions(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool);
+        fixtures::seed_questions(&question_dao, 3).await;
+        let page = question_dao.get_questions_projected(QuestionFields::ALL, PageRequest { offset: 0, limit: 10 }).await.unwrap();
+        let questions: Vec<QuestionPartial> = question_dao.get_questions().await.unwrap().into_iter().map(QuestionPartial::from).collect();
+        assert_eq!(page.items, questions);
+        assert!(!page.has_more);
+
+        for limit in [0, MAX_PAGE_SIZE + 1] {
+            let res = question_dao.get_questions_projected(QuestionFields::ALL, PageRequest { offset: 0, limit }).await;
+            println!("{:?}", res);
+            let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        }
+    }
+
+    #[sqlx::test]
+    async fn create_question_should_enforce_the_creation_quota_per_token(pool: PgPool) {
+        let start = Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap();
+        let clock = Arc::new(FixedClock::new(start));
+        let quota = CreationQuota { max_questions_per_hour: 2, max_answers_per_hour: 0 };
+        let question_dao = QuestionDaoImpl::new(pool).with_clock(clock.clone()).with_quota(quota);
+        let token = "my-token_0123456789";
+        question_dao.create_question(fixtures::question().author_token(token).build()).await.unwrap();
+        clock.advance(Duration::minutes(10));
+        question_dao.create_question(fixtures::question().author_token(token).build()).await.unwrap();
+
+        // The quota trips once reached, until the oldest question counted is an hour old
+        let res = question_dao.create_question(fixtures::question().author_token(token).build()).await;
+        println!("{:?}", res);
+        let Err(DbError::Unavailable { reason: UnavailableReason::QuotaExceeded { retry_after }, .. }) = res else { panic!("Error should be `QuotaExceeded` reason") };
+        assert_eq!(retry_after, Duration::minutes(50));
+        // Other tokens and tokenless creations are not limited by the token's quota
+        question_dao.create_question(fixtures::question().author_token("another-token-0123456789").build()).await.unwrap();
+        for _ in 0..3 {
+            question_dao.create_question(fixtures::question().build()).await.unwrap();
+        }
+
+        clock.advance(retry_after);
+        question_dao.create_question(fixtures::question().author_token(token).build()).await.unwrap();
+        let res = question_dao.create_question(fixtures::question().author_token(token).build()).await;
+        let Err(DbError::Unavailable { reason: UnavailableReason::QuotaExceeded { retry_after }, .. }) = res else { panic!("Error should be `QuotaExceeded` reason") };
+        assert_eq!(retry_after, Duration::minutes(10));
+    }
+
+    #[sqlx::test]
+    async fn create_question_idempotent_should_enforce_the_creation_quota(pool: PgPool) {
+        let clock = Arc::new(FixedClock::new(Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap()));
+        let quota = CreationQuota { max_questions_per_hour: 1, max_answers_per_hour: 0 };
+        let question_dao = QuestionDaoImpl::new(pool).with_clock(clock.clone()).with_quota(quota);
+        let token = "my-token_0123456789";
+        let new_question = || fixtures::question().title("Quota title").question("Quota question").author_token(token).build();
+        let res = question_dao.create_question_idempotent(new_question(), "key-1").await;
+        println!("{:?}", res);
+        let CreateOutcome::Created(id) = res.unwrap() else { panic!("question should be created") };
+        clock.advance(Duration::minutes(15));
+
+        let res = question_dao.create_question_idempotent(fixtures::question().author_token(token).build(), "key-2").await;
+        println!("{:?}", res);
+        let Err(DbError::Unavailable { reason: UnavailableReason::QuotaExceeded { retry_after }, .. }) = res else { panic!("Error should be `QuotaExceeded` reason") };
+        assert_eq!(retry_after, Duration::minutes(45));
+        // Retrying the first request creates nothing, so it is still answered
+        let res = question_dao.create_question_idempotent(new_question(), "key-1").await;
+        assert_eq!(res.unwrap(), CreateOutcome::AlreadyCreated(id));
+        assert_eq!(question_dao.get_questions_by_token(token).await.unwrap().len(), 1);
+    }
+
+    #[sqlx::test]
+    async fn upsert_question_by_external_id_should_enforce_the_creation_quota(pool: PgPool) {
+        let clock = Arc::new(FixedClock::new(Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap()));
+        let quota = CreationQuota { max_questions_per_hour: 1, max_answers_per_hour: 0 };
+        let question_dao = QuestionDaoImpl::new(pool).with_clock(clock.clone()).with_quota(quota);
+        let token = "my-token_0123456789";
+        let res = question_dao.upsert_question_by_external_id(fixtures::question().external_id("ext-1").author_token(token).build()).await;
+        println!("{:?}", res);
+        let UpsertOutcome::Created(id) = res.unwrap() else { panic!("question should be created") };
+
+        let res = question_dao.upsert_question_by_external_id(fixtures::question().external_id("ext-2").author_token(token).build()).await;
+        println!("{:?}", res);
+        let Err(DbError::Unavailable { reason: UnavailableReason::QuotaExceeded { retry_after }, .. }) = res else { panic!("Error should be `QuotaExceeded` reason") };
+        assert_eq!(retry_after, Duration::hours(1));
+        // Updating the question synced before creates nothing, so it is not limited
+        let updated = fixtures::question().title("An updated title").external_id("ext-1").author_token(token).build();
+        let res = question_dao.upsert_question_by_external_id(updated).await;
+        assert_eq!(res.unwrap(), UpsertOutcome::Updated(id));
+    }
+
+    /// Reads the normalized title stored for a question.
+    async fn title_normalized(pool: &PgPool, question_id: Uuid) -> Option<String> {
+        sqlx::query_scalar("SELECT title_normalized FROM questions WHERE id = $1")
+            .bind(question_id)
+            .fetch_one(pool)
+            .await
+            .expect("normalized title should be read")
+    }
+
+    #[sqlx::test]
+    async fn questions_should_store_normalized_titles_on_insert_and_update(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let created = question_dao.create_question(fixtures::question().title("Café Question").build()).await.expect("question should be created successfully");
+        assert_eq!(title_normalized(&pool, created.id()).await.as_deref(), Some("cafe question"));
+        let outcome = question_dao.create_question_idempotent(fixtures::question().title("ＣＡＦＥ question").build(), "key").await.expect("question should be created successfully");
+        assert_eq!(title_normalized(&pool, outcome.id()).await.as_deref(), Some("cafe question"));
+        let upserted = question_dao.upsert_question_by_external_id(fixtures::question().title("cafe\u{301} QUESTION").external_id("external").build())
+            .await
+            .expect("question should be upserted successfully");
+        assert_eq!(title_normalized(&pool, upserted.id()).await.as_deref(), Some("cafe question"));
+
+        // Only updates of the title change the normalized title
+        let update = UpdateQuestion { title: Some(String::from("Ｎａïｖｅ Question")), question: None };
+        question_dao.update_question(EntityId::uuid(created.id()), update).await.expect("question should be updated successfully");
+        assert_eq!(title_normalized(&pool, created.id()).await.as_deref(), Some("naive question"));
+        let update = UpdateQuestion { title: None, question: Some(String::from("Edited")) };
+        question_dao.update_question(EntityId::uuid(created.id()), update).await.expect("question should be updated successfully");
+        assert_eq!(title_normalized(&pool, created.id()).await.as_deref(), Some("naive question"));
+        let upserted = question_dao.upsert_question_by_external_id(fixtures::question().title("Crème").external_id("external").build())
+            .await
+            .expect("question should be upserted successfully");
+        assert_eq!(title_normalized(&pool, upserted.id()).await.as_deref(), Some("creme"));
+    }
+
+    /// Inserts `n` sample questions directly into the database, returning their ids in insertion order.
+    async fn seed_questions(pool: &PgPool, n: i32) -> Vec<Uuid> {
+        sqlx::query_scalar("INSERT INTO questions (title, question) SELECT 'Test Question' || n, 'Hello this question is a test' FROM generate_series(1, $1) AS n RETURNING id")
+            .bind(n)
+            .fetch_all(pool)
+            .await
+            .expect("questions should be seeded successfully")
+    }
+
+    async fn count_rows(pool: &PgPool, table: &str) -> i64 {
+        sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table}"))
+            .fetch_one(pool)
+            .await
+            .expect("rows should be counted successfully")
+    }
+
+    #[sqlx::test]
+    async fn delete_questions_batched_should_delete_in_batches(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let ids = seed_questions(&pool, 250).await;
+        // Give one of the questions an answer, which should be deleted along with it
+        sqlx::query("INSERT INTO answers (question_id, answer) VALUES ($1, 'Test answer')")
+            .bind(ids[0])
+            .execute(&pool)
+            .await
+            .expect("answer should be created successfully");
+        let ids = ids.into_iter().map(|id| EntityId::new(id.to_string())).collect();
+        let mut batches = vec![];
+        let mut record = |progress: BatchProgress| batches.push(progress);
+        let del_res = question_dao.delete_questions_batched(ids, 100, false, Some(&mut record)).await;
+        println!("{:?}", del_res);
+        assert_eq!(del_res.unwrap(), 250);
+        assert_eq!(batches.len(), 3);
+        assert!(batches.iter().all(|p| p.total_batches == 3));
+        assert_eq!(batches.iter().map(|p| p.affected_in_batch).collect::<Vec<u64>>(), vec![100, 100, 50]);
+        assert_eq!(batches.last().unwrap().affected_total, 250);
+        assert_eq!(count_rows(&pool, "questions").await, 0);
+        assert_eq!(count_rows(&pool, "answers").await, 0);
+    }
+
+    #[sqlx::test]
+    async fn delete_questions_batched_should_report_partial_failure(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let ids = seed_questions(&pool, 250).await;
+        // Make deletion of a question in the second batch fail
+        sqlx::query(&format!(
+            "CREATE FUNCTION reject_delete() RETURNS trigger AS $$ BEGIN IF OLD.id = '{}' THEN RAISE EXCEPTION 'rejected'; END IF; RETURN OLD; END $$ LANGUAGE plpgsql",
+            ids[150]
+        ))
+            .execute(&pool)
+            .await
+            .expect("function should be created successfully");
+        sqlx::query("CREATE TRIGGER reject_delete BEFORE DELETE ON questions FOR EACH ROW EXECUTE FUNCTION reject_delete()")
+            .execute(&pool)
+            .await
+            .expect("trigger should be created successfully");
+        let ids = ids.into_iter().map(|id| EntityId::new(id.to_string())).collect();
+        let del_res = question_dao.delete_questions_batched(ids, 100, false, None).await;
+        println!("{:?}", del_res);
+        let Err(error) = del_res else { panic!("Deleting should fail") };
+        assert_eq!(error.partial_batch().map(|batch| batch.completed), Some(100));
+        let DbError::Internal { reason: InternalReason::Delete, .. } = error else { panic!("Error should be `Delete` reason") };
+        // Only the first batch should have been committed
+        assert_eq!(count_rows(&pool, "questions").await, 150);
+    }
+
+    #[sqlx::test]
+    async fn delete_questions_batched_should_fail_with_invalid_uuid_before_deleting(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let mut ids: Vec<EntityId> = seed_questions(&pool, 5).await
+            .into_iter()
+            .map(|id| EntityId::new(id.to_string()))
+            .collect();
+        ids.push(EntityId::new(String::from("invalid Uuid")));
+        let del_res = question_dao.delete_questions_batched(ids, 2, false, None).await;
+        println!("{:?}", del_res);
+        let Err(DbError::InvalidId { .. }) = del_res else { panic!("Error should be `InvalidId` variant") };
+        assert_eq!(count_rows(&pool, "questions").await, 5);
+    }
+
+    #[sqlx::test]
+    async fn delete_questions_batched_should_follow_the_delete_policy(pool: PgPool) {
+        let ids = seed_questions(&pool, 4).await;
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        let answer = answer_dao.create_answer(fixtures::answer(ids[1]).build()).await.unwrap();
+        let batch = |ids: &[Uuid]| ids.iter().map(|id| EntityId::uuid(*id)).collect::<Vec<_>>();
+
+        // A batch holding a question with answers is rolled back as a whole
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_delete_policy(DeletePolicy::Restrict);
+        let res = question_dao.delete_questions_batched(batch(&ids), 2, false, None).await;
+        println!("{:?}", res);
+        let Err(error) = res else { panic!("Deleting should fail") };
+        assert_eq!(error.partial_batch().map(|batch| batch.completed), Some(0));
+        let DbError::Conflict { reason: ConflictReason::HasDependents { count }, .. } = error else {
+            panic!("Error should be `HasDependents` reason")
+        };
+        assert_eq!(count, 1);
+        assert_eq!(count_rows(&pool, "questions").await, 4);
+
+        // Orphaned answers outlive their question
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_delete_policy(DeletePolicy::Orphan);
+        assert_eq!(question_dao.delete_questions_batched(batch(&ids[..2]), 2, false, None).await.unwrap(), 2);
+        let orphan = answer_dao.get_answer(EntityId::uuid(answer.id())).await.unwrap();
+        assert_eq!(orphan.question_id(), None);
+
+        // Locked questions are only deleted when forced
+        question_dao.lock_question(EntityId::uuid(ids[3])).await.unwrap();
+        let res = question_dao.delete_questions_batched(batch(&ids[2..]), 2, false, None).await;
+        println!("{:?}", res);
+        let Err(error) = res else { panic!("Deleting should fail") };
+        assert_eq!(error.partial_batch().map(|batch| batch.completed), Some(0));
+        let DbError::Conflict { reason: ConflictReason::Locked(id), .. } = error else { panic!("Error should be `Locked` reason") };
+        assert_eq!(id, ids[3]);
+        assert_eq!(question_dao.delete_questions_batched(batch(&ids[2..]), 2, true, None).await.unwrap(), 2);
+        assert_eq!(count_rows(&pool, "questions").await, 0);
+    }
+
+    #[sqlx::test]
+    async fn update_question_should_edit_only_given_fields(pool: PgPool) {
+        let now = Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap();
+        let question_dao = QuestionDaoImpl::new(pool).with_clock(Arc::new(FixedClock::new(now)));
+        let question = question_dao.create_question(fixtures::question().title("Before").question("Unchanged").build())
+            .await
+            .expect("question should be created successfully");
+        let update = UpdateQuestion { title: Some(String::from("After")), question: None };
+        let res = question_dao.update_question(EntityId::new(question.id().to_string()), update).await;
+        println!("{:?}", res);
+        let updated = res.unwrap();
+        assert_eq!(updated.title(), "After");
+        assert_eq!(updated.question(), "Unchanged");
+        assert_eq!(updated.updated_at(), Some(now));
+        let res = question_dao.update_question(EntityId::new(question.id().to_string()), UpdateQuestion::default()).await;
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        let res = question_dao.update_question(EntityId::new(Uuid::new_v4().to_string()), UpdateQuestion { title: Some(String::from("Missing")), question: None }).await;
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+    }
+
+    #[sqlx::test]
+    async fn locked_question_should_reject_edits_until_unlocked(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let question_id = question_dao.create_question(fixtures::question().build()).await.expect("question should be created successfully").id();
+        let locked = question_dao.lock_question(EntityId::new(question_id.to_string())).await.expect("question should be locked successfully");
+        assert!(locked.is_locked());
+        let edit = || UpdateQuestion { title: Some(String::from("Edited")), question: None };
+        let res = question_dao.update_question(EntityId::new(question_id.to_string()), edit()).await;
+        println!("{:?}", res);
+        let Err(DbError::Conflict { reason: ConflictReason::Locked(id), .. }) = res else { panic!("Error should be `Locked` reason") };
+        assert_eq!(id, question_id);
+        // Locking does not prevent answering
+        let res = answer_dao.create_answer(fixtures::answer(question_id).build()).await;
+        println!("{:?}", res);
+        assert!(res.is_ok());
+        let unlocked = question_dao.unlock_question(EntityId::new(question_id.to_string())).await.expect("question should be unlocked successfully");
+        assert!(!unlocked.is_locked());
+        let res = question_dao.update_question(EntityId::new(question_id.to_string()), edit()).await;
+        assert_eq!(res.unwrap().title(), "Edited");
+    }
+
+    #[sqlx::test]
+    async fn lock_question_should_keep_original_lock_time(pool: PgPool) {
+        let clock = Arc::new(FixedClock::new(Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap()));
+        let question_dao = QuestionDaoImpl::new(pool).with_clock(clock.clone());
+        let question_id = fixtures::seed_questions(&question_dao, 1).await[0];
+        let first = question_dao.lock_question(EntityId::new(question_id.to_string())).await.unwrap();
+        clock.advance(Duration::hours(1));
+        let second = question_dao.lock_question(EntityId::new(question_id.to_string())).await.unwrap();
+        assert_eq!(second.locked_at(), first.locked_at());
+        let res = question_dao.lock_question(EntityId::new(Uuid::new_v4().to_string())).await;
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+    }
+
+    #[sqlx::test]
+    async fn delete_question_should_require_force_when_locked(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool);
+        let question_id = fixtures::seed_questions(&question_dao, 1).await[0];
+        question_dao.lock_question(EntityId::new(question_id.to_string())).await.expect("question should be locked successfully");
+        let res = question_dao.delete_question(EntityId::new(question_id.to_string()), false).await;
+        println!("{:?}", res);
+        let Err(DbError::Conflict { reason: ConflictReason::Locked(_), .. }) = res else { panic!("Error should be `Locked` reason") };
+        let res = question_dao.delete_question(EntityId::new(question_id.to_string()), true).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), question_id);
+        let res = question_dao.get_question(EntityId::new(question_id.to_string())).await;
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+    }
+
+    #[sqlx::test]
+    async fn upsert_question_by_external_id_should_create_update_and_skip_unchanged(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool);
+        let synced = fixtures::question().title("Synced").external_id("forum-1");
+
+        let res = question_dao.upsert_question_by_external_id(synced.clone().build()).await;
+        println!("{:?}", res);
+        let Ok(UpsertOutcome::Created(id)) = res else { panic!("Outcome should be `Created` variant") };
+        let question = question_dao.get_question(EntityId::uuid(id)).await.unwrap();
+        assert_eq!(question.external_id(), Some("forum-1"));
+
+        // Re-running with the same content changes nothing
+        let res = question_dao.upsert_question_by_external_id(synced.clone().build()).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), UpsertOutcome::Unchanged(id));
+        assert_eq!(question_dao.get_question(EntityId::uuid(id)).await.unwrap().updated_at(), None);
+
+        let res = question_dao.upsert_question_by_external_id(synced.clone().title("Synced and edited").build()).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), UpsertOutcome::Updated(id));
+        let question = question_dao.get_question(EntityId::uuid(id)).await.unwrap();
+        assert_eq!(question.title(), "Synced and edited");
+        assert!(question.updated_at().is_some());
+        assert_eq!(question_dao.get_questions().await.unwrap().len(), 1);
+
+        // A locked question is not overwritten
+        question_dao.lock_question(EntityId::uuid(id)).await.unwrap();
+        let res = question_dao.upsert_question_by_external_id(synced.title("Edited while locked").build()).await;
+        println!("{:?}", res);
+        let Err(DbError::Conflict { reason: ConflictReason::Locked(_), .. }) = res else { panic!("Error should be `Locked` reason") };
+    }
+
+    #[sqlx::test]
+    async fn upsert_question_by_external_id_should_create_without_external_id(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool);
+        let new_question = fixtures::question().title("Local");
+        let first = question_dao.upsert_question_by_external_id(new_question.clone().build()).await;
+        let second = question_dao.upsert_question_by_external_id(new_question.build()).await;
+        println!("{:?} {:?}", first, second);
+        let (Ok(UpsertOutcome::Created(first)), Ok(UpsertOutcome::Created(second))) = (first, second) else {
+            panic!("Outcomes should be `Created` variant")
+        };
+        assert_ne!(first, second);
+        let question = question_dao.get_question(EntityId::uuid(first)).await.unwrap();
+        assert_eq!(question.external_id(), None);
+    }
+
+    #[sqlx::test]
+    async fn delete_question_with_policy_should_handle_questions_without_answers(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool);
+        let question_ids = fixtures::seed_questions(&question_dao, 3).await;
+        for (question_id, policy) in question_ids.into_iter().zip([DeletePolicy::Restrict, DeletePolicy::Cascade, DeletePolicy::Orphan]) {
+            let res = question_dao.delete_question_with_policy(EntityId::uuid(question_id), policy).await;
+            println!("{:?}", res);
+            assert_eq!(res.unwrap(), question_id);
+        }
+        assert!(question_dao.get_questions().await.unwrap().is_empty());
+    }
+
+    #[sqlx::test]
+    async fn delete_question_with_restrict_policy_should_report_dependents(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let (question_id, _) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 3).await;
+        // Drafts are answers too
+        answer_dao.create_answer_draft(fixtures::answer(question_id).build()).await.unwrap();
+        let res = question_dao.delete_question_with_policy(EntityId::uuid(question_id), DeletePolicy::Restrict).await;
+        println!("{:?}", res);
+        let Err(DbError::Conflict { reason: ConflictReason::HasDependents { count }, .. }) = res else {
+            panic!("Error should be `HasDependents` reason")
+        };
+        assert_eq!(count, 4);
+        assert!(question_dao.get_question(EntityId::uuid(question_id)).await.is_ok());
+    }
+
+    #[sqlx::test]
+    async fn delete_question_with_cascade_and_orphan_policies_should_handle_answers(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        let (cascaded, cascaded_answers) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 2).await;
+        let (orphaned, orphaned_answers) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 2).await;
+
+        let res = question_dao.delete_question_with_policy(EntityId::uuid(cascaded), DeletePolicy::Cascade).await;
+        println!("{:?}", res);
+        assert!(res.is_ok());
+        assert!(answer_dao.get_answer(EntityId::uuid(cascaded_answers[0])).await.is_err());
+
+        // The configured policy is used by `delete_question`
+        let question_dao = QuestionDaoImpl::new(pool).with_delete_policy(DeletePolicy::Orphan);
+        let res = question_dao.delete_question(EntityId::uuid(orphaned), false).await;
+        println!("{:?}", res);
+        assert!(res.is_ok());
+        for answer_id in orphaned_answers {
+            let answer = answer_dao.get_answer(EntityId::uuid(answer_id)).await.expect("orphaned answer should be kept");
+            assert_eq!(answer.question_id(), None);
+        }
+        assert_eq!(answer_dao.get_all_answers().await.unwrap().len(), 2);
+    }
+
+    #[sqlx::test]
+    async fn pinned_questions_should_be_listed_first(pool: PgPool) {
+        let clock = Arc::new(SteppingClock::new(Utc.with_ymd_and_hms(2024, 7, 1, 12, 0, 0).unwrap(), Duration::minutes(1)));
+        let question_dao = QuestionDaoImpl::new(pool).with_clock(clock);
+        let question_ids = fixtures::seed_questions(&question_dao, 4).await;
+        let pinned = question_dao.pin_question(EntityId::uuid(question_ids[2])).await.expect("question should be pinned");
+        assert!(pinned.is_pinned());
+        question_dao.pin_question(EntityId::uuid(question_ids[1])).await.expect("question should be pinned");
+
+        let ids: Vec<Uuid> = question_dao.get_questions().await.unwrap().iter().map(|q| q.id()).collect();
+        assert_eq!(ids, vec![question_ids[1], question_ids[2], question_ids[0], question_ids[3]]);
+
+        let res = question_dao.unpin_question(EntityId::uuid(question_ids[1])).await;
+        println!("{:?}", res);
+        assert!(!res.unwrap().is_pinned());
+        let ids: Vec<Uuid> = question_dao.get_questions().await.unwrap().iter().map(|q| q.id()).collect();
+        assert_eq!(ids, vec![question_ids[2], question_ids[0], question_ids[1], question_ids[3]]);
+    }
+
+    #[sqlx::test]
+    async fn pin_question_should_enforce_max_pinned(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool).with_max_pinned(2);
+        let question_ids = fixtures::seed_questions(&question_dao, 3).await;
+        for question_id in &question_ids[..2] {
+            question_dao.pin_question(EntityId::uuid(*question_id)).await.expect("question should be pinned");
+        }
+        let res = question_dao.pin_question(EntityId::uuid(question_ids[2])).await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { reason: ValidationReason::LimitExceeded { limit: 2 }, .. }) = res else {
+            panic!("Error should be `LimitExceeded` reason")
+        };
+        // Re-pinning a pinned question does not count against the limit, and keeps its original pin time
+        let pinned_at = question_dao.get_question(EntityId::uuid(question_ids[0])).await.unwrap().pinned_at();
+        let res = question_dao.pin_question(EntityId::uuid(question_ids[0])).await;
+        assert_eq!(res.unwrap().pinned_at(), pinned_at);
+        // Unpinning makes room for another question
+        question_dao.unpin_question(EntityId::uuid(question_ids[0])).await.expect("question should be unpinned");
+        assert!(question_dao.pin_question(EntityId::uuid(question_ids[2])).await.is_ok());
+    }
+
+    #[sqlx::test]
+    async fn pin_answer_should_list_pinned_answer_first_in_every_order(pool: PgPool) {
+        use crate::models::{AnswerSort, DEFAULT_ANSWER_LIMIT};
+        let clock = Arc::new(SteppingClock::new(Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap(), Duration::minutes(1)));
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool).with_clock(clock);
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 3).await;
+        for answer_id in [answer_ids[2], answer_ids[2], answer_ids[0]] {
+            answer_dao.increment_answer_likes(EntityId::uuid(answer_id)).await.expect("answer should be liked successfully");
+        }
+        let sorted = |sort| {
+            let answer_dao = &answer_dao;
+            async move {
+                let answers = answer_dao.get_answers_sorted(EntityId::uuid(question_id), sort, DEFAULT_ANSWER_LIMIT).await.unwrap();
+                answers.iter().map(|answer| answer.id()).collect::<Vec<Uuid>>()
+            }
+        };
+        assert_eq!(sorted(AnswerSort::MostLiked).await, [answer_ids[2], answer_ids[0], answer_ids[1]]);
+
+        let res = question_dao.pin_answer(EntityId::uuid(question_id), EntityId::uuid(answer_ids[1])).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap().pinned_answer_id(), Some(answer_ids[1]));
+        assert_eq!(sorted(AnswerSort::MostLiked).await, [answer_ids[1], answer_ids[2], answer_ids[0]]);
+        assert_eq!(sorted(AnswerSort::Newest).await, [answer_ids[1], answer_ids[2], answer_ids[0]]);
+        assert_eq!(sorted(AnswerSort::Oldest).await, [answer_ids[1], answer_ids[0], answer_ids[2]]);
+        let listed: Vec<Uuid> = answer_dao.get_answers(EntityId::uuid(question_id)).await.unwrap().iter().map(|answer| answer.id()).collect();
+        assert_eq!(listed, [answer_ids[1], answer_ids[2], answer_ids[0]]);
+
+        // Unpinning restores the normal order
+        let res = question_dao.unpin_answer(EntityId::uuid(question_id)).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap().pinned_answer_id(), None);
+        assert_eq!(sorted(AnswerSort::MostLiked).await, [answer_ids[2], answer_ids[0], answer_ids[1]]);
+    }
+
+    #[sqlx::test]
+    async fn pin_answer_should_fail_for_answer_to_another_question(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let (question_id, _) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 1).await;
+        let (_, other_answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 1).await;
+        let res = question_dao.pin_answer(EntityId::uuid(question_id), EntityId::uuid(other_answer_ids[0])).await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        let res = question_dao.pin_answer(EntityId::uuid(question_id), EntityId::uuid(Uuid::new_v4())).await;
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+        assert_eq!(question_dao.get_question(EntityId::uuid(question_id)).await.unwrap().pinned_answer_id(), None);
+    }
+
+    #[sqlx::test]
+    async fn deleting_pinned_answer_should_unpin_it(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 2).await;
+        question_dao.pin_answer(EntityId::uuid(question_id), EntityId::uuid(answer_ids[0])).await.expect("answer should be pinned successfully");
+        let res = answer_dao.delete_answer(EntityId::uuid(answer_ids[0])).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), answer_ids[0]);
+        assert_eq!(question_dao.get_question(EntityId::uuid(question_id)).await.unwrap().pinned_answer_id(), None);
+        let listed: Vec<Uuid> = answer_dao.get_answers(EntityId::uuid(question_id)).await.unwrap().iter().map(|answer| answer.id()).collect();
+        assert_eq!(listed, [answer_ids[1]]);
+    }
+
+    #[sqlx::test]
+    async fn get_question_as_of_should_read_the_content_at_the_time(pool: PgPool) {
+        let created_at = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+        let clock = Arc::new(FixedClock::new(created_at));
+        let question_dao = QuestionDaoImpl::new(pool).with_clock(clock.clone());
+        let question = question_dao.create_question(fixtures::question().title("Original").question("First draft").build()).await.unwrap();
+        let id = EntityId::uuid(question.id());
+        for (hours, title) in [(1, "First edit"), (2, "Second edit")] {
+            clock.set(created_at + Duration::hours(hours));
+            let update = UpdateQuestion { title: Some(String::from(title)), question: Some(format!("{title} content")) };
+            question_dao.update_question(id.clone(), update).await.expect("question should be updated successfully");
+        }
+        question_dao.increment_question_likes(id.clone()).await.unwrap();
+
+        let res = question_dao.get_question_as_of(id.clone(), created_at + Duration::minutes(90)).await;
+        println!("{:?}", res);
+        let between = res.unwrap();
+        assert_eq!((between.title(), between.question()), ("First edit", "First edit content"));
+        assert_eq!(between.updated_at(), Some(created_at + Duration::hours(1)));
+        // Only the content is historical
+        assert_eq!(between.likes(), 1);
+
+        let before_edits = question_dao.get_question_as_of(id.clone(), created_at + Duration::minutes(30)).await.unwrap();
+        assert_eq!((before_edits.title(), before_edits.question(), before_edits.updated_at()), ("Original", "First draft", None));
+        assert_eq!(question_dao.get_question_as_of(id.clone(), created_at).await.unwrap().title(), "Original");
+        // An edit made exactly at the time is included
+        assert_eq!(question_dao.get_question_as_of(id.clone(), created_at + Duration::hours(2)).await.unwrap().title(), "Second edit");
+        assert_eq!(question_dao.get_question_as_of(id.clone(), Utc::now()).await.unwrap().title(), "Second edit");
+
+        let res = question_dao.get_question_as_of(id, created_at - Duration::seconds(1)).await;
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+        let res = question_dao.get_question_as_of(EntityId::uuid(Uuid::new_v4()), Utc::now()).await;
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+    }
+
+    #[sqlx::test]
+    async fn get_revision_diff_should_compare_any_two_revisions(pool: PgPool) {
+        let created_at = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+        let clock = Arc::new(FixedClock::new(created_at));
+        let question_dao = QuestionDaoImpl::new(pool).with_clock(clock.clone());
+        let question = fixtures::question().title("Sorting").question("How do I\nsort a Vec?").build();
+        let id = EntityId::uuid(question_dao.create_question(question).await.unwrap().id());
+        for (hours, content) in [(1, "How do I\nsort a Vec\nin place?"), (2, "How do I\nsort a slice\nin place?")] {
+            clock.set(created_at + Duration::hours(hours));
+            let update = UpdateQuestion { title: None, question: Some(String::from(content)) };
+            question_dao.update_question(id.clone(), update).await.expect("question should be updated successfully");
+        }
+
+        let res = question_dao.get_revision_diff(id.clone(), 1, 3).await;
+        println!("{:?}", res);
+        let hunks = res.unwrap();
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(hunks[0].lines, vec![
+            DiffLine::Context(String::from("How do I")),
+            DiffLine::Removed(String::from("sort a Vec?")),
+            DiffLine::Added(String::from("sort a slice")),
+            DiffLine::Added(String::from("in place?")),
+        ]);
+        // Revisions compare in either direction, and a revision with itself is unchanged
+        let reverse = question_dao.get_revision_diff(id.clone(), 3, 2).await.unwrap();
+        assert_eq!(reverse[0].lines[1], DiffLine::Removed(String::from("sort a slice")));
+        assert!(question_dao.get_revision_diff(id.clone(), 2, 2).await.unwrap().is_empty());
+        // A title edit adds a revision without changing the content
+        let update = UpdateQuestion { title: Some(String::from("Sorting slices")), question: None };
+        question_dao.update_question(id.clone(), update).await.unwrap();
+        assert!(question_dao.get_revision_diff(id, 3, 4).await.unwrap().is_empty());
+    }
+
+    #[sqlx::test]
+    async fn get_revision_diff_should_reject_missing_revisions(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool);
+        let id = EntityId::uuid(question_dao.create_question(fixtures::question().build()).await.unwrap().id());
+        assert!(question_dao.get_revision_diff(id.clone(), 1, 1).await.unwrap().is_empty());
+        for (from_rev, to_rev) in [(0, 1), (1, 2)] {
+            let res = question_dao.get_revision_diff(id.clone(), from_rev, to_rev).await;
+            println!("{:?}", res);
+            let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        }
+        let res = question_dao.get_revision_diff(EntityId::uuid(Uuid::new_v4()), 1, 1).await;
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+    }
+
+    #[sqlx::test]
+    async fn get_linked_questions_should_resolve_related_links_both_ways(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool).with_clock(Arc::new(SteppingClock::new(Utc::now(), Duration::seconds(1))));
+        let ids = fixtures::seed_questions(&question_dao, 4).await;
+        let [a, b, c, d] = [ids[0], ids[1], ids[2], ids[3]].map(EntityId::uuid);
+        question_dao.link_questions(a.clone(), b.clone(), LinkKind::Related).await.expect("questions should be linked successfully");
+        question_dao.link_questions(c.clone(), a.clone(), LinkKind::DuplicateOf).await.unwrap();
+        question_dao.link_questions(a.clone(), d.clone(), LinkKind::FollowUp).await.unwrap();
+        let linked = |res: Vec<(LinkKind, Question)>| res.into_iter().map(|(kind, question)| (kind, question.id())).collect::<Vec<_>>();
+        let res = question_dao.get_linked_questions(a.clone()).await;
+        println!("{:?}", res);
+        assert_eq!(linked(res.unwrap()), [(LinkKind::Related, ids[1]), (LinkKind::FollowUp, ids[3])]);
+        // Related links are listed from both questions, directional links only from the linking question
+        assert_eq!(linked(question_dao.get_linked_questions(b.clone()).await.unwrap()), [(LinkKind::Related, ids[0])]);
+        assert_eq!(linked(question_dao.get_linked_questions(c.clone()).await.unwrap()), [(LinkKind::DuplicateOf, ids[0])]);
+        assert!(question_dao.get_linked_questions(d).await.unwrap().is_empty());
+
+        assert!(question_dao.unlink_questions(b.clone(), a.clone(), LinkKind::Related).await.unwrap());
+        assert!(!question_dao.unlink_questions(a.clone(), c.clone(), LinkKind::DuplicateOf).await.unwrap());
+        assert!(question_dao.get_linked_questions(b).await.unwrap().is_empty());
+        let res = question_dao.get_linked_questions(EntityId::uuid(Uuid::new_v4())).await;
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+    }
+
+    #[sqlx::test]
+    async fn link_questions_should_reject_self_links_and_missing_questions(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool);
+        let id = EntityId::uuid(fixtures::seed_questions(&question_dao, 1).await[0]);
+        let res = question_dao.link_questions(id.clone(), id.clone(), LinkKind::Related).await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        let res = question_dao.link_questions(id.clone(), EntityId::uuid(Uuid::new_v4()), LinkKind::FollowUp).await;
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+        assert!(question_dao.get_linked_questions(id).await.unwrap().is_empty());
+    }
+
+    #[sqlx::test]
+    async fn link_questions_should_reject_identical_links(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool);
+        let ids = fixtures::seed_questions(&question_dao, 2).await;
+        let (a, b) = (EntityId::uuid(ids[0]), EntityId::uuid(ids[1]));
+        question_dao.link_questions(a.clone(), b.clone(), LinkKind::Related).await.unwrap();
+        for (from, to) in [(&a, &b), (&b, &a)] {
+            let res = question_dao.link_questions(from.clone(), to.clone(), LinkKind::Related).await;
+            println!("{:?}", res);
+            let Err(DbError::Conflict { .. }) = res else { panic!("Error should be `Conflict` variant") };
+        }
+        question_dao.link_questions(a.clone(), b.clone(), LinkKind::DuplicateOf).await.unwrap();
+        let Err(DbError::Conflict { .. }) = question_dao.link_questions(a.clone(), b.clone(), LinkKind::DuplicateOf).await else {
+            panic!("Error should be `Conflict` variant")
+        };
+        // The reverse of a directional link is a different link
+        question_dao.link_questions(b.clone(), a.clone(), LinkKind::DuplicateOf).await.expect("reverse link should be created");
+        assert_eq!(question_dao.get_linked_questions(a).await.unwrap().len(), 2);
+    }
+
+    #[sqlx::test]
+    async fn delete_question_should_remove_its_links(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let ids = fixtures::seed_questions(&question_dao, 3).await;
+        question_dao.link_questions(EntityId::uuid(ids[0]), EntityId::uuid(ids[1]), LinkKind::Related).await.unwrap();
+        question_dao.link_questions(EntityId::uuid(ids[2]), EntityId::uuid(ids[1]), LinkKind::FollowUp).await.unwrap();
+        question_dao.delete_question(EntityId::uuid(ids[1]), true).await.expect("question should be deleted successfully");
+        let links: i64 = sqlx::query_scalar("SELECT count(*) FROM question_links").fetch_one(&pool).await.unwrap();
+        assert_eq!(links, 0);
+        assert!(question_dao.get_linked_questions(EntityId::uuid(ids[0])).await.unwrap().is_empty());
+    }
+}
+
+mod answer_tests {
+    use std::sync::Arc;
+    use chrono::{Duration, TimeZone, Utc};
+    use sqlx::types::Uuid;
+    use crate::clock::{Clock, FixedClock, SteppingClock};
+    use crate::fixtures;
+    use crate::models::{
+        AnswerSort, AnswerVote, ArchiveReport, ContentLimits, ContentType, CreationQuota, DbError, DetailOptions,
+        EntityId, InternalReason, LikableEntity, LikeTarget, ModerationMode, NewAnswer, NewQuestion, PageRequest, ReputationConfig,
+        UnavailableReason, ValidationReason, VoteOutcome, DEFAULT_ANSWER_LIMIT, DELETED_CONTENT, MAX_DELETED_REASON_LENGTH, MAX_PAGE_SIZE,
+    };
+    use crate::persistence::prelude::PgPool;
+    use crate::persistence::AnswerDaoImpl;
+    use crate::persistence::AnswerDao;
+    use crate::persistence::QuestionDaoImpl;
+    use crate::persistence::QuestionDao;
+    use crate::persistence::like_entity;
+    use crate::persistence::{AuthorDao, AuthorDaoImpl, TagDao, TagDaoImpl};
+
+    #[sqlx::test]
+    async fn create_answer_should_fail_with_invalid_id_err(pool: PgPool) {
+        let new_answer = NewAnswer { question_id: String::from("invalid question id"), answer: String::from("Test answer"), author_id: None, content_type: None, author_token: None, parent_answer_id: None };
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let res = answer_dao.create_answer(new_answer).await;
+        println!("{:?}", res);
+        assert!(res.is_err());
+        let Err(DbError::InvalidId { .. }) = res else { panic!("Error should be `InvalidId` variant") };
+    }
+
+    #[sqlx::test]
+    async fn create_answer_should_fail_with_validation_err(pool: PgPool) {
+        let limits = ContentLimits::default();
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let new_answer = NewAnswer { question_id: Uuid::new_v4().to_string(), answer: "a".repeat(limits.max_answer + 1), author_id: None, content_type: None, author_token: None, parent_answer_id: None };
+        let res = answer_dao.create_answer(new_answer).await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+    }
+
+    #[sqlx::test]
+    async fn create_answer_should_fail_with_access_err(pool: PgPool) {
+        let answer_dao = AnswerDaoImpl::new(pool).with_failing_source(|| sqlx::Error::PoolClosed);
+        let new_answer = NewAnswer { question_id: Uuid::new_v4().to_string(), answer: String::from("Test answer"), author_id: None, content_type: None, author_token: None, parent_answer_id: None };
+        let res = answer_dao.create_answer(new_answer).await;
+        println!("{:?}", res);
+        assert!(res.is_err());
+        let Err(DbError::Unavailable { reason: UnavailableReason::Unreachable, .. }) = res else { panic!("Error should be `Unavailable` variant") };
+    }
+
+    #[sqlx::test]
+    async fn create_answer_should_fail_with_not_found_err(pool: PgPool) {
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let new_answer = NewAnswer { question_id: Uuid::new_v4().to_string(), answer: String::from("Test answer"), author_id: None, content_type: None, author_token: None, parent_answer_id: None };
+        let res = answer_dao.create_answer(new_answer).await;
+        println!("{:?}", res);
+        assert!(res.is_err());
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant.")};
+    }
+
+    #[sqlx::test]
+    async fn content_type_should_round_trip_and_default_to_text(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        let question = question_dao.create_question(fixtures::question().content_type(ContentType::Markdown).build())
+            .await
+            .expect("question should be created successfully");
+        assert_eq!(question.content_type(), ContentType::Markdown);
+        let text = answer_dao.create_answer(fixtures::answer(question.id()).build()).await.expect("answer should be created successfully");
+        assert_eq!(text.content_type(), ContentType::Text);
+        let markdown = answer_dao.create_answer(fixtures::answer(question.id()).content_type(ContentType::Markdown).build())
+            .await
+            .expect("answer should be created successfully");
+
+        let question = question_dao.get_question(EntityId::uuid(question.id())).await.expect("question should be found");
+        assert_eq!(question.content_type(), ContentType::Markdown);
+        let answer = answer_dao.get_answer(EntityId::uuid(text.id())).await.expect("answer should be found");
+        assert_eq!(answer.content_type(), ContentType::Text);
+        let answer = answer_dao.get_answer(EntityId::uuid(markdown.id())).await.expect("answer should be found");
+        assert_eq!(answer.content_type(), ContentType::Markdown);
+
+        // The schema rejects any other content type
+        let res = sqlx::query("UPDATE answers SET content_type = 'html' WHERE id = $1").bind(text.id()).execute(&pool).await;
+        println!("{:?}", res);
+        assert!(res.is_err());
+    }
+
+    #[sqlx::test]
+    async fn content_type_should_read_values_written_by_newer_versions_as_unknown(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        for content_type in [ContentType::Text, ContentType::Markdown] {
+            let decoded: ContentType = sqlx::query_scalar("SELECT $1").bind(&content_type).fetch_one(&pool).await.unwrap();
+            assert_eq!(decoded, content_type);
+        }
+        let question = question_dao.create_question(fixtures::question().build()).await.expect("question should be created successfully");
+        let answer = answer_dao.create_answer(fixtures::answer(question.id()).build()).await.expect("answer should be created successfully");
+
+        // A newer version relaxes the constraint and stores a content type this version does not know
+        sqlx::query("ALTER TABLE questions DROP CONSTRAINT questions_content_type_check").execute(&pool).await.unwrap();
+        sqlx::query("ALTER TABLE answers DROP CONSTRAINT answers_content_type_check").execute(&pool).await.unwrap();
+        sqlx::query("UPDATE questions SET content_type = 'html' WHERE id = $1").bind(question.id()).execute(&pool).await.unwrap();
+        sqlx::query("UPDATE answers SET content_type = 'html' WHERE id = $1").bind(answer.id()).execute(&pool).await.unwrap();
+
+        let question = question_dao.get_question(EntityId::uuid(question.id())).await.expect("question should be found");
+        assert_eq!(question.content_type(), ContentType::Unknown(String::from("html")));
+        let answer = answer_dao.get_answer(EntityId::uuid(answer.id())).await.expect("answer should be found");
+        assert_eq!(answer.content_type(), ContentType::Unknown(String::from("html")));
+        let questions = question_dao.get_questions().await.expect("questions should be listed");
+        assert_eq!(questions.len(), 1);
+
+        // An unknown content type is never written, even though the schema would now accept it
+        let res = question_dao.create_question(fixtures::question().content_type(ContentType::Unknown(String::from("html"))).build()).await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        let res = answer_dao.create_answer(fixtures::answer(question.id()).content_type(ContentType::Unknown(String::from("html"))).build()).await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+    }
+
+    #[sqlx::test]
+    async fn create_answer_should_succeed(pool: PgPool){
+        // Create Dao's for question and answer tables
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool);
+
+        // Insert a new test question into the question table
+        let new_question = NewQuestion { title: String::from("Test Question"), question: String::from("Hello this question is a test"), external_id: None, content_type: None, author_token: None };
+        let new_question_res = question_dao.create_question(new_question).await;
+        println!("{:?}", new_question_res);
+        assert!(new_question_res.is_ok());
+
+        // Get id for new answer
+        let question_id = new_question_res.unwrap().id().to_string();
+
+        // create new answer
+        let new_answer = NewAnswer { question_id, answer: String::from("Test answer"), author_id: None, content_type: None, author_token: None, parent_answer_id: None };
+
+        // Attempt to make the query
+        let new_answer_res = answer_dao.create_answer(new_answer).await;
+        println!("{:?}", new_answer_res);
+        assert!(new_answer_res.is_ok());
+    }
+
+    #[sqlx::test]
+    async fn create_answer_should_return_persisted_answer_with_defaults(pool: PgPool) {
+        let now = Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap();
+        let clock = Arc::new(FixedClock::new(now));
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool).with_clock(clock);
+        let question_id = question_dao.create_question(fixtures::question().build()).await.expect("question should be created successfully").id();
+        let answer = answer_dao.create_answer(fixtures::answer(question_id).answer("Default answer").build())
+            .await
+            .expect("answer should be created successfully");
+        println!("{:?}", answer);
+        assert_eq!(answer.question_id(), Some(question_id));
+        assert_eq!(answer.answer(), "Default answer");
+        assert_eq!(answer.likes(), 0);
+        assert_eq!(answer.created_at(), now);
+        assert_eq!(answer.author_id(), None);
+        let persisted = answer_dao.get_answer(EntityId::new(answer.id().to_string())).await.expect("answer should be found");
+        assert_eq!(persisted.created_at(), answer.created_at());
+    }
+
+    #[sqlx::test]
+    async fn answers_should_be_accessible_by_serial(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let question = question_dao.create_question(fixtures::question().build()).await.expect("question should be created successfully");
+        let question_serial = question.serial().expect("serial should be assigned");
+        // Legacy clients refer to the question by its serial
+        let new_answer = NewAnswer { question_id: question_serial.to_string(), answer: String::from("Legacy answer"), author_id: None, content_type: None, author_token: None, parent_answer_id: None };
+        let answer = answer_dao.create_answer(new_answer).await.expect("answer should be created successfully");
+        assert_eq!(answer.question_id(), Some(question.id()));
+        let res = answer_dao.get_answer(EntityId::serial(answer.serial().expect("serial should be assigned"))).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap().id(), answer.id());
+        let res = answer_dao.get_answers(EntityId::serial(question_serial)).await;
+        assert_eq!(res.unwrap().len(), 1);
+    }
+
+    async fn create_question_and_answer(pool: &PgPool) -> Uuid {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        let (_, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 1).await;
+        answer_ids[0]
+    }
+
+    #[sqlx::test]
+    async fn increment_answer_likes_should_succeed(pool: PgPool) {
+        let answer_id = create_question_and_answer(&pool).await;
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let inc_res = answer_dao.increment_answer_likes(EntityId::new(answer_id.to_string())).await;
+        println!("{:?}", inc_res);
+        assert_eq!(inc_res.unwrap(), 1);
+        let answer = answer_dao.get_answer(EntityId::new(answer_id.to_string())).await.expect("answer should be found");
+        assert_eq!(answer.likes(), 1);
+    }
+
+    #[sqlx::test]
+    async fn vote_answer_should_record_first_vote(pool: PgPool) {
+        let answer_id = create_question_and_answer(&pool).await;
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let res = answer_dao.vote_answer(EntityId::uuid(answer_id), AnswerVote::Helpful, "token").await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), VoteOutcome::Recorded);
+        let res = answer_dao.vote_answer(EntityId::uuid(answer_id), AnswerVote::Unhelpful, "other").await;
+        assert_eq!(res.unwrap(), VoteOutcome::Recorded);
+        let answer = answer_dao.get_answer(EntityId::uuid(answer_id)).await.expect("answer should be found");
+        assert_eq!((answer.helpful_count(), answer.unhelpful_count(), answer.score()), (1, 1, 0));
+        // Votes are separate from likes
+        assert_eq!(answer.likes(), 0);
+    }
+
+    #[sqlx::test]
+    async fn vote_answer_should_flip_counters_when_switching_vote(pool: PgPool) {
+        let answer_id = create_question_and_answer(&pool).await;
+        let answer_dao = AnswerDaoImpl::new(pool);
+        answer_dao.vote_answer(EntityId::uuid(answer_id), AnswerVote::Helpful, "token").await.expect("vote should be recorded");
+        let res = answer_dao.vote_answer(EntityId::uuid(answer_id), AnswerVote::Unhelpful, "token").await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), VoteOutcome::Switched);
+        let answer = answer_dao.get_answer(EntityId::uuid(answer_id)).await.expect("answer should be found");
+        assert_eq!((answer.helpful_count(), answer.unhelpful_count(), answer.score()), (0, 1, -1));
+        let res = answer_dao.vote_answer(EntityId::uuid(answer_id), AnswerVote::Helpful, "token").await;
+        assert_eq!(res.unwrap(), VoteOutcome::Switched);
+        let answer = answer_dao.get_answer(EntityId::uuid(answer_id)).await.expect("answer should be found");
+        assert_eq!((answer.helpful_count(), answer.unhelpful_count(), answer.score()), (1, 0, 1));
+    }
+
+    #[sqlx::test]
+    async fn vote_answer_should_ignore_repeated_identical_vote(pool: PgPool) {
+        let answer_id = create_question_and_answer(&pool).await;
+        let answer_dao = AnswerDaoImpl::new(pool);
+        answer_dao.vote_answer(EntityId::uuid(answer_id), AnswerVote::Unhelpful, "token").await.expect("vote should be recorded");
+        let res = answer_dao.vote_answer(EntityId::uuid(answer_id), AnswerVote::Unhelpful, "token").await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), VoteOutcome::Unchanged);
+        let answer = answer_dao.get_answer(EntityId::uuid(answer_id)).await.expect("answer should be found");
+        assert_eq!((answer.helpful_count(), answer.unhelpful_count()), (0, 1));
+    }
+
+    #[sqlx::test]
+    async fn vote_answer_should_fail_with_not_found_and_conflict_for_drafts(pool: PgPool) {
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        let res = answer_dao.vote_answer(EntityId::uuid(Uuid::new_v4()), AnswerVote::Helpful, "token").await;
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+        let question_id = QuestionDaoImpl::new(pool.clone()).create_question(fixtures::question().build()).await.expect("question should be created successfully").id();
+        let new_answer = NewAnswer { question_id: question_id.to_string(), answer: String::from("Draft answer"), author_id: None, content_type: None, author_token: None, parent_answer_id: None };
+        let draft = answer_dao.create_answer_draft(new_answer).await.expect("draft should be created successfully");
+        let res = answer_dao.vote_answer(EntityId::uuid(draft.id()), AnswerVote::Helpful, "token").await;
+        println!("{:?}", res);
+        let Err(DbError::Conflict { .. }) = res else { panic!("Error should be `Conflict` variant") };
+    }
+
+    /// Counts the answers to a question that are listed and counted under the moderation mode of the data access objects.
+    async fn visible_answers(question_dao: &QuestionDaoImpl, answer_dao: &AnswerDaoImpl, question_id: Uuid) -> (usize, i64) {
+        let listed = answer_dao.get_answers(EntityId::uuid(question_id)).await.expect("answers should be listed");
+        let sorted = answer_dao.get_answers_sorted(EntityId::uuid(question_id), AnswerSort::Newest, DEFAULT_ANSWER_LIMIT).await.unwrap();
+        let with_authors = answer_dao.get_answers_with_authors(EntityId::uuid(question_id)).await.unwrap();
+        assert_eq!((listed.len(), sorted.len()), (with_authors.len(), with_authors.len()));
+        let header = question_dao.get_question_header(EntityId::uuid(question_id)).await.expect("header should be found");
+        (listed.len(), header.answer_count())
+    }
+
+    #[sqlx::test]
+    async fn moderation_off_should_show_and_approve_answers_immediately(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 2).await;
+        assert_eq!(visible_answers(&question_dao, &answer_dao, question_id).await, (2, 2));
+        let answer = answer_dao.get_answer(EntityId::uuid(answer_ids[0])).await.expect("answer should be found");
+        assert_eq!(answer.approved_at(), Some(answer.created_at()));
+    }
+
+    #[sqlx::test]
+    async fn moderation_manual_should_hide_answers_until_approved(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_moderation(ModerationMode::Manual);
+        let answer_dao = AnswerDaoImpl::new(pool.clone()).with_moderation(ModerationMode::Manual);
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 2).await;
+        assert_eq!(visible_answers(&question_dao, &answer_dao, question_id).await, (0, 0));
+        let res = answer_dao.approve_answer(EntityId::uuid(answer_ids[0])).await;
+        println!("{:?}", res);
+        assert!(res.unwrap().approved_at().is_some());
+        assert_eq!(visible_answers(&question_dao, &answer_dao, question_id).await, (1, 1));
+        // Approving twice keeps the first approval
+        let first = answer_dao.get_answer(EntityId::uuid(answer_ids[0])).await.unwrap().approved_at();
+        assert_eq!(answer_dao.approve_answer(EntityId::uuid(answer_ids[0])).await.unwrap().approved_at(), first);
+        // A rejected answer is deleted, with a copy kept for auditing
+        let res = answer_dao.reject_answer(EntityId::uuid(answer_ids[1])).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), answer_ids[1]);
+        assert!(answer_dao.get_answer(EntityId::uuid(answer_ids[1])).await.is_err());
+        let audited: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM answer_rejections WHERE answer_id = $1 AND question_id = $2")
+            .bind(answer_ids[1])
+            .bind(question_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(audited, 1);
+        assert_eq!(visible_answers(&question_dao, &answer_dao, question_id).await, (1, 1));
+    }
+
+    #[sqlx::test]
+    async fn moderation_delay_should_show_answers_once_the_delay_passes(pool: PgPool) {
+        let clock = Arc::new(FixedClock::new(Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap()));
+        let moderation = ModerationMode::DelayMinutes(10);
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_clock(clock.clone()).with_moderation(moderation);
+        let answer_dao = AnswerDaoImpl::new(pool.clone()).with_clock(clock.clone()).with_moderation(moderation);
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 2).await;
+        assert_eq!(visible_answers(&question_dao, &answer_dao, question_id).await, (0, 0));
+        clock.advance(Duration::minutes(9));
+        assert_eq!(visible_answers(&question_dao, &answer_dao, question_id).await, (0, 0));
+        // An approval shows an answer before its delay passes
+        answer_dao.approve_answer(EntityId::uuid(answer_ids[1])).await.expect("answer should be approved successfully");
+        assert_eq!(visible_answers(&question_dao, &answer_dao, question_id).await, (1, 1));
+        clock.advance(Duration::minutes(1));
+        assert_eq!(visible_answers(&question_dao, &answer_dao, question_id).await, (2, 2));
+        // The answer shown by its delay has not been approved
+        assert_eq!(answer_dao.get_answer(EntityId::uuid(answer_ids[0])).await.unwrap().approved_at(), None);
+    }
+
+    #[sqlx::test]
+    async fn approve_and_reject_answer_should_fail_with_not_found_and_conflict_for_drafts(pool: PgPool) {
+        let answer_dao = AnswerDaoImpl::new(pool.clone()).with_moderation(ModerationMode::Manual);
+        let res = answer_dao.approve_answer(EntityId::uuid(Uuid::new_v4())).await;
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+        let res = answer_dao.reject_answer(EntityId::uuid(Uuid::new_v4())).await;
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+        let question_id = QuestionDaoImpl::new(pool.clone()).create_question(fixtures::question().build()).await.expect("question should be created successfully").id();
+        let new_answer = NewAnswer { question_id: question_id.to_string(), answer: String::from("Draft answer"), author_id: None, content_type: None, author_token: None, parent_answer_id: None };
+        let draft = answer_dao.create_answer_draft(new_answer).await.expect("draft should be created successfully");
+        let res = answer_dao.approve_answer(EntityId::uuid(draft.id())).await;
+        println!("{:?}", res);
+        let Err(DbError::Conflict { .. }) = res else { panic!("Error should be `Conflict` variant") };
+        // Publishing a draft under moderation leaves it unapproved
+        let published = answer_dao.publish_answer(EntityId::uuid(draft.id())).await.expect("draft should be published successfully");
+        assert_eq!(published.approved_at(), None);
+    }
+
+    #[sqlx::test]
+    async fn get_answers_sorted_should_order_by_score(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone()).with_clock(Arc::new(SteppingClock::new(Utc::now(), Duration::seconds(1))));
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 3).await;
+        // The first answer scores -1, the second 2 and the third 0, its votes cancelling out
+        let votes = [
+            (0, AnswerVote::Unhelpful, "a"),
+            (1, AnswerVote::Helpful, "a"),
+            (1, AnswerVote::Helpful, "b"),
+            (2, AnswerVote::Helpful, "a"),
+            (2, AnswerVote::Unhelpful, "b"),
+        ];
+        for (answer, vote, token) in votes {
+            answer_dao.vote_answer(EntityId::uuid(answer_ids[answer]), vote, token).await.expect("vote should be recorded");
+        }
+        let res = answer_dao.get_answers_sorted(EntityId::uuid(question_id), AnswerSort::Score, DEFAULT_ANSWER_LIMIT).await;
+        println!("{:?}", res);
+        let sorted: Vec<Uuid> = res.unwrap().iter().map(|answer| answer.id()).collect();
+        assert_eq!(sorted, vec![answer_ids[1], answer_ids[2], answer_ids[0]]);
+        assert_eq!("score".parse::<AnswerSort>().unwrap(), AnswerSort::Score);
+    }
+
+    #[sqlx::test]
+    async fn increment_answer_likes_should_fail_with_from_row_on_mistyped_column(pool: PgPool) {
+        let answer_id = create_question_and_answer(&pool).await;
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        // Change the column type so it no longer decodes as an i64
+        sqlx::query("ALTER TABLE answers ALTER COLUMN likes TYPE NUMERIC")
+            .execute(&pool)
+            .await
+            .expect("column type should be altered successfully");
+        let inc_res = answer_dao.increment_answer_likes(EntityId::new(answer_id.to_string())).await;
+        println!("{:?}", inc_res);
+        let Err(e @ DbError::Internal { reason: InternalReason::Decode, .. }) = inc_res else { panic!("Error should be `Decode` reason") };
+        assert!(e.debug_details().is_some_and(|details| details.contains("likes")));
+    }
+
+    /// Creates a question with three answers created a minute apart, the second of which has two likes
+    /// and the third one like. Returns the question id and the answer ids in creation order.
+    async fn seed_sortable_answers(pool: &PgPool) -> (Uuid, Vec<Uuid>) {
+        let clock = Arc::new(SteppingClock::new(Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap(), Duration::minutes(1)));
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone()).with_clock(clock);
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 3).await;
+        for answer_id in [answer_ids[1], answer_ids[1], answer_ids[2]] {
+            answer_dao.increment_answer_likes(EntityId::new(answer_id.to_string())).await.expect("answer should be liked successfully");
+        }
+        (question_id, answer_ids)
+    }
+
+    #[sqlx::test]
+    async fn get_answers_sorted_should_order_by_most_liked_by_default(pool: PgPool) {
+        let (question_id, answer_ids) = seed_sortable_answers(&pool).await;
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let res = answer_dao.get_answers_sorted(EntityId::new(question_id.to_string()), AnswerSort::default(), DEFAULT_ANSWER_LIMIT).await;
+        println!("{:?}", res);
+        let ids: Vec<Uuid> = res.unwrap().iter().map(|a| a.id()).collect();
+        assert_eq!(ids, vec![answer_ids[1], answer_ids[2], answer_ids[0]]);
+    }
+
+    #[sqlx::test]
+    async fn get_answers_and_get_all_answers_should_share_default_order(pool: PgPool) {
+        let (question_id, answer_ids) = seed_sortable_answers(&pool).await;
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let answers = answer_dao.get_answers(EntityId::new(question_id.to_string())).await;
+        println!("{:?}", answers);
+        let ids: Vec<Uuid> = answers.unwrap().iter().map(|a| a.id()).collect();
+        assert_eq!(ids, vec![answer_ids[1], answer_ids[2], answer_ids[0]]);
+        let all_answers = answer_dao.get_all_answers().await;
+        let all_ids: Vec<Uuid> = all_answers.unwrap().iter().map(|a| a.id()).collect();
+        assert_eq!(all_ids, ids);
+    }
+
+    #[sqlx::test]
+    async fn get_answers_and_get_all_answers_should_fail_with_from_row_on_decode_failure(pool: PgPool) {
+        let (question_id, _) = fixtures::seed_question_with_answers(&QuestionDaoImpl::new(pool.clone()), &AnswerDaoImpl::new(pool.clone()), 1).await;
+        // Replace the table with a view whose likes column has the wrong type
+        sqlx::query("ALTER TABLE answers RENAME TO answers_base")
+            .execute(&pool)
+            .await
+            .expect("table should be renamed successfully");
+        sqlx::query("CREATE VIEW answers AS SELECT id, question_id, answer, likes::text AS likes, created_at, author_id, published FROM answers_base")
+            .execute(&pool)
+            .await
+            .expect("view should be created successfully");
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let res = answer_dao.get_answers(EntityId::new(question_id.to_string())).await;
+        println!("{:?}", res);
+        let Err(DbError::Internal { reason: InternalReason::Decode, .. }) = res else { panic!("Error should be `Decode` reason") };
+        let res = answer_dao.get_all_answers().await;
+        println!("{:?}", res);
+        let Err(DbError::Internal { reason: InternalReason::Decode, .. }) = res else { panic!("Error should be `Decode` reason") };
+    }
+
+    #[sqlx::test]
+    async fn get_answers_and_get_all_answers_should_fail_with_access_on_transport_error(pool: PgPool) {
+        let answer_dao = AnswerDaoImpl::new(pool).with_failing_source(|| sqlx::Error::PoolClosed);
+        let res = answer_dao.get_answers(EntityId::new(Uuid::new_v4().to_string())).await;
+        println!("{:?}", res);
+        let Err(DbError::Unavailable { .. }) = res else { panic!("Error should be `Unavailable` variant") };
+        let res = answer_dao.get_all_answers().await;
+        println!("{:?}", res);
+        let Err(DbError::Unavailable { .. }) = res else { panic!("Error should be `Unavailable` variant") };
+    }
+
+    #[sqlx::test]
+    async fn get_answers_sorted_should_order_by_creation(pool: PgPool) {
+        let (question_id, answer_ids) = seed_sortable_answers(&pool).await;
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let newest = answer_dao.get_answers_sorted(EntityId::new(question_id.to_string()), "newest".parse().unwrap(), DEFAULT_ANSWER_LIMIT).await;
+        let ids: Vec<Uuid> = newest.unwrap().iter().map(|a| a.id()).collect();
+        assert_eq!(ids, vec![answer_ids[2], answer_ids[1], answer_ids[0]]);
+        let oldest = answer_dao.get_answers_sorted(EntityId::new(question_id.to_string()), "oldest".parse().unwrap(), DEFAULT_ANSWER_LIMIT).await;
+        let ids: Vec<Uuid> = oldest.unwrap().iter().map(|a| a.id()).collect();
+        assert_eq!(ids, answer_ids);
+    }
+
+    #[sqlx::test]
+    async fn get_answers_paged_should_neither_repeat_nor_skip_answers_when_likes_change(pool: PgPool) {
+        let started = Utc.with_ymd_and_hms(2025, 5, 1, 9, 0, 0).unwrap();
+        let clock = Arc::new(FixedClock::new(started));
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_clock(clock.clone());
+        let answer_dao = AnswerDaoImpl::new(pool).with_clock(clock.clone());
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 5).await;
+        // The first answer is the most liked, the last the least
+        for (answer_id, likes) in answer_ids.iter().zip([5, 4, 3, 2, 1]) {
+            for _ in 0..likes {
+                answer_dao.increment_answer_likes(EntityId::uuid(*answer_id)).await.unwrap();
+            }
+        }
+        let question_id = EntityId::uuid(question_id);
+        clock.set(started + Duration::minutes(1));
+        let res = answer_dao.get_answers_paged(question_id.clone(), 2, None).await;
+        println!("{:?}", res);
+        let first = res.unwrap();
+        let mut seen: Vec<Uuid> = first.answers.iter().map(|answer| answer.id()).collect();
+        assert_eq!(seen, answer_ids[..2]);
+
+        // The least liked answer, not yet seen, becomes the most liked between the pages
+        clock.set(started + Duration::minutes(2));
+        for _ in 0..10 {
+            answer_dao.increment_answer_likes(EntityId::uuid(answer_ids[4])).await.unwrap();
+        }
+        let sorted = answer_dao.get_answers_sorted(question_id.clone(), AnswerSort::MostLiked, 1).await.unwrap();
+        assert_eq!(sorted[0].id(), answer_ids[4]);
+        // An answer created after the first page is left out
+        let late = answer_dao.create_answer(fixtures::answer(Uuid::try_from(&question_id).unwrap()).build()).await.unwrap();
+
+        let mut page_token = first.page_token;
+        while let Some(token) = page_token {
+            let res = answer_dao.get_answers_paged(question_id.clone(), 2, Some(&token)).await;
+            println!("{:?}", res);
+            let page = res.unwrap();
+            seen.extend(page.answers.iter().map(|answer| answer.id()));
+            page_token = page.page_token;
+        }
+        assert_eq!(seen, answer_ids);
+        let restarted = answer_dao.get_answers_paged(question_id, 10, None).await.unwrap();
+        assert_eq!(restarted.answers[0].id(), answer_ids[4]);
+        assert!(restarted.answers.iter().any(|answer| answer.id() == late.id()));
+    }
+
+    #[sqlx::test]
+    async fn get_answers_paged_should_reject_invalid_pages(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let (question_id, _) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 3).await;
+        let question_id = EntityId::uuid(question_id);
+        let page = answer_dao.get_answers_paged(question_id.clone(), 3, None).await.unwrap();
+        assert_eq!((page.answers.len(), page.page_token), (3, None));
+        let token = answer_dao.get_answers_paged(question_id.clone(), 1, None).await.unwrap().page_token.expect("more answers should follow");
+        for (limit, token) in [(0, None), (MAX_PAGE_SIZE + 1, None), (1, Some("not a token")), (1, Some(&*format!("{token}.1")))] {
+            let res = answer_dao.get_answers_paged(question_id.clone(), limit, token).await;
+            println!("{:?}", res);
+            let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        }
+        let res = answer_dao.get_answers_paged(EntityId::uuid(Uuid::new_v4()), 1, None).await;
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+    }
+
+    #[sqlx::test]
+    async fn get_answers_sorted_should_respect_limit(pool: PgPool) {
+        let (question_id, answer_ids) = seed_sortable_answers(&pool).await;
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let res = answer_dao.get_answers_sorted(EntityId::new(question_id.to_string()), AnswerSort::Oldest, 2).await;
+        let ids: Vec<Uuid> = res.unwrap().iter().map(|a| a.id()).collect();
+        assert_eq!(ids, answer_ids[..2]);
+        let res = answer_dao.get_answers_sorted(EntityId::new(question_id.to_string()), AnswerSort::Oldest, 0).await;
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+    }
+
+    #[test]
+    fn answer_sort_should_fail_to_parse_listing_valid_options() {
+        let res = "most_recent".parse::<AnswerSort>();
+        let Err(DbError::Validation { reason: ValidationReason::Input(message), .. }) = res else { panic!("Error should be `Validation` variant") };
+        assert!(AnswerSort::VARIANTS.iter().all(|variant| message.contains(variant)));
+    }
+
+    #[sqlx::test]
+    async fn get_answers_sorted_should_distinguish_missing_question_from_no_answers(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let question_id = question_dao.create_question(fixtures::question().build()).await.expect("question should be created successfully").id();
+        let res = answer_dao.get_answers_sorted(EntityId::new(question_id.to_string()), AnswerSort::default(), DEFAULT_ANSWER_LIMIT).await;
+        println!("{:?}", res);
+        assert!(res.unwrap().is_empty());
+        let res = answer_dao.get_answers_sorted(EntityId::new(Uuid::new_v4().to_string()), AnswerSort::default(), DEFAULT_ANSWER_LIMIT).await;
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+    }
+
+    #[sqlx::test]
+    async fn get_answers_with_authors_should_include_anonymous_answers(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        let new_question = NewQuestion { title: String::from("Test Question"), question: String::from("Hello this question is a test"), external_id: None, content_type: None, author_token: None };
+        let question_id = question_dao.create_question(new_question).await.expect("question should be created successfully").id().to_string();
+        let author_id: Uuid = sqlx::query_scalar("INSERT INTO users (username) VALUES ('test_user') RETURNING id")
+            .fetch_one(&pool)
+            .await
+            .expect("user should be created successfully");
+
+        // Create one authored and one anonymous answer
+        let authored = NewAnswer { question_id: question_id.clone(), answer: String::from("Authored answer"), author_id: Some(author_id.to_string()), content_type: None, author_token: None, parent_answer_id: None };
+        let authored_id = answer_dao.create_answer(authored).await.expect("answer should be created successfully").id();
+        let anonymous = NewAnswer { question_id: question_id.clone(), answer: String::from("Anonymous answer"), author_id: None, content_type: None, author_token: None, parent_answer_id: None };
+        let anonymous_id = answer_dao.create_answer(anonymous).await.expect("answer should be created successfully").id();
+
+        let res = answer_dao.get_answers_with_authors(EntityId::new(question_id)).await;
+        println!("{:?}", res);
+        assert!(res.is_ok());
+        let answers = res.unwrap();
+        assert_eq!(answers.len(), 2);
+        let authored = answers.iter().find(|a| a.answer().id() == authored_id).expect("authored answer should be returned");
+        assert_eq!(authored.author_username(), Some("test_user"));
+        assert_eq!(authored.answer().author_id(), Some(author_id));
+        let anonymous = answers.iter().find(|a| a.answer().id() == anonymous_id).expect("anonymous answer should be returned");
+        assert_eq!(anonymous.author_username(), None);
+
+        // The author should be serialized as a sub-object
+        let authored_json = serde_json::to_value(authored).expect("answer should serialize");
+        assert_eq!(authored_json["author"], serde_json::json!({ "username": "test_user" }));
+        assert_eq!(authored_json["answer"], "Authored answer");
+        let anonymous_json = serde_json::to_value(anonymous).expect("answer should serialize");
+        assert!(anonymous_json["author"].is_null());
+    }
+
+    #[sqlx::test]
+    async fn create_answer_should_not_race_with_delete_question(pool: PgPool) {
+        let question_dao = Arc::new(QuestionDaoImpl::new(pool.clone()));
+        let answer_dao = Arc::new(AnswerDaoImpl::new(pool.clone()));
+        for _ in 0..20 {
+            let (question_id, _) = fixtures::seed_question_with_answers(&*question_dao, &*answer_dao, 1).await;
+            let answers: Vec<_> = (0..4)
+                .map(|_| {
+                    let answer_dao = answer_dao.clone();
+                    tokio::spawn(async move { answer_dao.create_answer(fixtures::answer(question_id).build()).await })
+                })
+                .collect();
+            let deletion = {
+                let question_dao = question_dao.clone();
+                tokio::spawn(async move { question_dao.delete_question(EntityId::uuid(question_id), false).await })
+            };
+            for answer in answers {
+                // Each answer either lands before the deletion, and is removed with the question, or is not found
+                match answer.await.unwrap() {
+                    Ok(_) | Err(DbError::NotFound { .. }) => {}
+                    Err(e) => panic!("Error should be `NotFound` variant, got {e:?}"),
+                }
+            }
+            assert_eq!(deletion.await.unwrap().expect("question should be deleted successfully"), question_id);
+            let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM answers WHERE question_id = $1 OR question_id IS NULL")
+                .bind(question_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            assert_eq!(remaining, 0);
+        }
+    }
+
+    #[sqlx::test]
+    async fn create_answer_should_enforce_the_creation_quota_concurrently(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let quota = CreationQuota { max_questions_per_hour: 0, max_answers_per_hour: 3 };
+        let answer_dao = Arc::new(AnswerDaoImpl::new(pool).with_quota(quota));
+        let question = question_dao.create_question(fixtures::question().build()).await.unwrap();
+        let token = "my-token_0123456789";
+        // Drafts count towards the quota
+        answer_dao.create_answer_draft(fixtures::answer(question.id()).author_token(token).build()).await.unwrap();
+        let answers: Vec<_> = (0..5)
+            .map(|_| {
+                let answer_dao = answer_dao.clone();
+                let new_answer = fixtures::answer(question.id()).author_token(token).build();
+                tokio::spawn(async move { answer_dao.create_answer(new_answer).await })
+            })
+            .collect();
+        let mut created = 0;
+        for answer in answers {
+            match answer.await.unwrap() {
+                Ok(_) => created += 1,
+                Err(DbError::Unavailable { reason: UnavailableReason::QuotaExceeded { retry_after }, .. }) => {
+                    assert!(retry_after > Duration::minutes(59) && retry_after <= Duration::hours(1), "{retry_after:?}");
+                }
+                Err(e) => panic!("Error should be `QuotaExceeded` reason, got {e:?}"),
+            }
+        }
+        assert_eq!(created, 2);
+        // Tokenless answers are not limited
+        answer_dao.create_answer(fixtures::answer(question.id()).build()).await.unwrap();
+    }
+
+    #[sqlx::test]
+    async fn search_answers_should_only_match_answers_of_the_question(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let question = question_dao.create_question(fixtures::question().title("Searched question").build()).await.expect("question should be created successfully");
+        let other = question_dao.create_question(fixtures::question().title("Other question").build()).await.expect("question should be created successfully");
+        let matching = answer_dao.create_answer(fixtures::answer(question.id()).answer("Restart the Server first").build()).await.expect("answer should be created successfully");
+        let liked = answer_dao.create_answer(fixtures::answer(question.id()).answer("The server logs say why").build()).await.expect("answer should be created successfully");
+        answer_dao.create_answer(fixtures::answer(question.id()).answer("Reinstall the package").build()).await.expect("answer should be created successfully");
+        answer_dao.create_answer(fixtures::answer(other.id()).answer("The server is down").build()).await.expect("answer should be created successfully");
+        answer_dao.increment_answer_likes(EntityId::uuid(liked.id())).await.unwrap();
+
+        let res = answer_dao.search_answers(EntityId::uuid(question.id()), "SERVER").await;
+        println!("{:?}", res);
+        let ids: Vec<Uuid> = res.unwrap().iter().map(|answer| answer.id()).collect();
+        assert_eq!(ids, vec![liked.id(), matching.id()]);
+    }
+
+    #[sqlx::test]
+    async fn search_answers_should_match_wildcards_literally(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let question = question_dao.create_question(fixtures::question().build()).await.expect("question should be created successfully");
+        let literal = answer_dao.create_answer(fixtures::answer(question.id()).answer("It uses 100% of the cpu").build()).await.expect("answer should be created successfully");
+        answer_dao.create_answer(fixtures::answer(question.id()).answer("It uses 100 threads").build()).await.expect("answer should be created successfully");
+        answer_dao.create_answer(fixtures::answer(question.id()).answer("Call some_function").build()).await.expect("answer should be created successfully");
+
+        let res = answer_dao.search_answers(EntityId::uuid(question.id()), "100%").await;
+        println!("{:?}", res);
+        let ids: Vec<Uuid> = res.unwrap().iter().map(|answer| answer.id()).collect();
+        assert_eq!(ids, vec![literal.id()]);
+        let res = answer_dao.search_answers(EntityId::uuid(question.id()), "s_m").await;
+        println!("{:?}", res);
+        assert!(res.unwrap().is_empty());
+    }
+
+    #[sqlx::test]
+    async fn search_answers_should_distinguish_missing_question_from_no_matches(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let question = question_dao.create_question(fixtures::question().build()).await.expect("question should be created successfully");
+        let res = answer_dao.search_answers(EntityId::uuid(question.id()), "anything").await;
+        println!("{:?}", res);
+        assert!(res.unwrap().is_empty());
+        let res = answer_dao.search_answers(EntityId::uuid(Uuid::new_v4()), "anything").await;
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+        let res = answer_dao.search_answers(EntityId::uuid(question.id()), "  ").await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+    }
+
+    #[sqlx::test]
+    async fn search_all_answers_should_include_question_titles(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let first = question_dao.create_question(fixtures::question().title("First question").build()).await.expect("question should be created successfully");
+        let second = question_dao.create_question(fixtures::question().title("Second question").build()).await.expect("question should be created successfully");
+        answer_dao.create_answer(fixtures::answer(first.id()).answer("Check the firewall").build()).await.expect("answer should be created successfully");
+        let liked = answer_dao.create_answer(fixtures::answer(second.id()).answer("The firewall blocks the port").build()).await.expect("answer should be created successfully");
+        answer_dao.create_answer(fixtures::answer(second.id()).answer("Unrelated").build()).await.expect("answer should be created successfully");
+        answer_dao.increment_answer_likes(EntityId::uuid(liked.id())).await.unwrap();
+
+        let res = answer_dao.search_all_answers("firewall", 10).await;
+        println!("{:?}", res);
+        let results = res.unwrap();
+        let titles: Vec<Option<&str>> = results.iter().map(|result| result.question_title()).collect();
+        assert_eq!(titles, vec![Some("Second question"), Some("First question")]);
+        assert_eq!(results[0].answer().id(), liked.id());
+        let res = answer_dao.search_all_answers("firewall", 1).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap().len(), 1);
+        let res = answer_dao.search_all_answers("firewall", 0).await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+    }
+
+    #[sqlx::test]
+    async fn get_all_answers_with_question_should_join_titles_and_page(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool).with_clock(Arc::new(SteppingClock::new(Utc::now(), Duration::seconds(1))));
+        let first = question_dao.create_question(fixtures::question().title("First question").build()).await.expect("question should be created successfully");
+        let second = question_dao.create_question(fixtures::question().title("Second question").build()).await.expect("question should be created successfully");
+        let mut answer_ids = Vec::new();
+        for question_id in [first.id(), second.id(), first.id()] {
+            let answer = answer_dao.create_answer(fixtures::answer(question_id).build()).await.expect("answer should be created successfully");
+            answer_ids.push(answer.id());
+        }
+
+        let res = answer_dao.get_all_answers_with_question(PageRequest { offset: 0, limit: 2 }).await;
+        println!("{:?}", res);
+        let page = res.unwrap();
+        assert_eq!(page.items.iter().map(|item| item.answer().id()).collect::<Vec<_>>(), answer_ids[..2]);
+        assert_eq!(page.items.iter().map(|item| item.question_id()).collect::<Vec<_>>(), vec![Some(first.id()), Some(second.id())]);
+        assert_eq!(page.items.iter().map(|item| item.question_title()).collect::<Vec<_>>(), vec![Some("First question"), Some("Second question")]);
+        assert!(page.has_more);
+
+        let page = answer_dao.get_all_answers_with_question(PageRequest { offset: 2, limit: 2 }).await.unwrap();
+        assert_eq!(page.items.iter().map(|item| item.answer().id()).collect::<Vec<_>>(), answer_ids[2..]);
+        assert!(!page.has_more);
+
+        for limit in [0, MAX_PAGE_SIZE + 1] {
+            let res = answer_dao.get_all_answers_with_question(PageRequest { offset: 0, limit }).await;
+            println!("{:?}", res);
+            let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        }
+    }
+
+    #[sqlx::test]
+    async fn get_all_answers_with_question_should_keep_answers_of_hidden_questions(pool: PgPool) {
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&QuestionDaoImpl::new(pool.clone()), &AnswerDaoImpl::new(pool.clone()), 1).await;
+        // Hide the question without removing its answers, as deleting it would cascade to them
+        sqlx::query("ALTER TABLE answers DROP CONSTRAINT answers_question_id_fkey")
+            .execute(&pool)
+            .await
+            .expect("constraint should be dropped successfully");
+        sqlx::query("DELETE FROM questions WHERE id = $1")
+            .bind(question_id)
+            .execute(&pool)
+            .await
+            .expect("question should be deleted successfully");
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let res = answer_dao.get_all_answers_with_question(PageRequest { offset: 0, limit: 10 }).await;
+        println!("{:?}", res);
+        let page = res.unwrap();
+        assert_eq!(page.items.iter().map(|item| item.answer().id()).collect::<Vec<_>>(), answer_ids);
+        assert_eq!(page.items[0].question_id(), Some(question_id));
+        assert_eq!(page.items[0].question_title(), None);
+    }
+
+    #[sqlx::test]
+    async fn drafts_should_only_be_listed_once_published(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        let new_question = NewQuestion { title: String::from("Test Question"), question: String::from("Hello this question is a test"), external_id: None, content_type: None, author_token: None };
+        let question_id = question_dao.create_question(new_question).await.expect("question should be created successfully").id().to_string();
+        let author_id: Uuid = sqlx::query_scalar("INSERT INTO users (username) VALUES ('test_user') RETURNING id")
+            .fetch_one(&pool)
+            .await
+            .expect("user should be created successfully");
+
+        let new_answer = NewAnswer { question_id: question_id.clone(), answer: String::from("Draft answer"), author_id: Some(author_id.to_string()), content_type: None, author_token: None, parent_answer_id: None };
+        let draft = answer_dao.create_answer_draft(new_answer).await.expect("draft should be created successfully");
+        assert!(!draft.is_published());
+
+        // The draft is only visible to its author
+        let answers = answer_dao.get_answers(EntityId::new(question_id.clone())).await.expect("answers should be returned");
+        assert!(answers.is_empty());
+        let sorted = answer_dao.get_answers_sorted(EntityId::new(question_id.clone()), AnswerSort::Newest, DEFAULT_ANSWER_LIMIT).await.expect("answers should be returned");
+        assert!(sorted.is_empty());
+        let drafts = answer_dao.get_drafts(EntityId::new(question_id.clone()), EntityId::uuid(author_id)).await.expect("drafts should be returned");
+        assert_eq!(drafts.len(), 1);
+        assert_eq!(drafts[0].id(), draft.id());
+
+        let res = answer_dao.publish_answer(EntityId::uuid(draft.id())).await;
+        println!("{:?}", res);
+        assert!(res.as_ref().is_ok_and(|answer| answer.is_published()));
+        let answers = answer_dao.get_answers(EntityId::new(question_id.clone())).await.expect("answers should be returned");
+        assert_eq!(answers.len(), 1);
+        assert_eq!(answers[0].id(), draft.id());
+        let drafts = answer_dao.get_drafts(EntityId::new(question_id), EntityId::uuid(author_id)).await.expect("drafts should be returned");
+        assert!(drafts.is_empty());
+
+        // Publishing again is a no-op
+        let res = answer_dao.publish_answer(EntityId::uuid(draft.id())).await;
+        println!("{:?}", res);
+        assert!(res.is_ok_and(|answer| answer.is_published()));
+    }
+
+    #[sqlx::test]
+    async fn publish_answer_should_fail_with_not_found_err(pool: PgPool) {
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let res = answer_dao.publish_answer(EntityId::uuid(Uuid::new_v4())).await;
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+    }
+
+    #[sqlx::test]
+    async fn increment_answer_likes_should_reject_drafts(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let new_question = NewQuestion { title: String::from("Test Question"), question: String::from("Hello this question is a test"), external_id: None, content_type: None, author_token: None };
+        let question_id = question_dao.create_question(new_question).await.expect("question should be created successfully").id().to_string();
+        let new_answer = NewAnswer { question_id, answer: String::from("Draft answer"), author_id: None, content_type: None, author_token: None, parent_answer_id: None };
+        let draft = answer_dao.create_answer_draft(new_answer).await.expect("draft should be created successfully");
+
+        let res = answer_dao.increment_answer_likes(EntityId::uuid(draft.id())).await;
+        println!("{:?}", res);
+        let Err(DbError::Conflict { .. }) = res else { panic!("Error should be `Conflict` variant") };
+        let answer = answer_dao.get_answer(EntityId::uuid(draft.id())).await.expect("draft should be returned");
+        assert_eq!(answer.likes(), 0);
+    }
+
+    #[sqlx::test]
+    async fn like_entity_should_dispatch_on_entity_kind(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 1).await;
+        let like = |entity, id: Uuid| LikeTarget { entity, id: id.to_string() };
+
+        let res = like_entity(&question_dao, &answer_dao, like(LikableEntity::Question, question_id)).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), 1);
+        let res = like_entity(&question_dao, &answer_dao, like(LikableEntity::Question, question_id)).await;
+        assert_eq!(res.unwrap(), 2);
+        let res = like_entity(&question_dao, &answer_dao, like(LikableEntity::Answer, answer_ids[0])).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), 1);
+
+        let question = question_dao.get_question(EntityId::uuid(question_id)).await.expect("question should be found");
+        assert_eq!(question.likes(), 2);
+        let answer = answer_dao.get_answer(EntityId::uuid(answer_ids[0])).await.expect("answer should be found");
+        assert_eq!(answer.likes(), 1);
+    }
+
+    #[sqlx::test]
+    async fn like_entity_should_fail_with_not_found_on_missing_id(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let (question_id, _) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 1).await;
+        for entity in [LikableEntity::Question, LikableEntity::Answer] {
+            let res = like_entity(&question_dao, &answer_dao, LikeTarget { entity, id: Uuid::new_v4().to_string() }).await;
+            println!("{:?}", res);
+            let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+        }
+        // The id of a question is not the id of an answer
+        let res = like_entity(&question_dao, &answer_dao, LikeTarget { entity: LikableEntity::Answer, id: question_id.to_string() }).await;
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+        let question = question_dao.get_question(EntityId::uuid(question_id)).await.expect("question should be found");
+        assert_eq!(question.likes(), 0);
+    }
+
+    #[sqlx::test]
+    async fn get_answer_threads_should_nest_replies(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 2).await;
+        let mut reply_ids = Vec::new();
+        for _ in 0..2 {
+            let reply = answer_dao.create_answer(fixtures::answer(question_id).parent_answer_id(answer_ids[0]).build())
+                .await
+                .expect("reply should be created successfully");
+            assert_eq!(reply.parent_answer_id(), Some(answer_ids[0]));
+            reply_ids.push(reply.id());
+        }
+
+        let res = answer_dao.get_answer_threads(EntityId::uuid(question_id)).await;
+        println!("{:?}", res);
+        let threads = res.unwrap();
+        let thread_ids = threads.iter().map(|thread| thread.answer().id()).collect::<Vec<_>>();
+        assert_eq!(thread_ids, answer_ids);
+        let nested_ids = threads[0].replies().iter().map(|reply| reply.id()).collect::<Vec<_>>();
+        assert_eq!(nested_ids, reply_ids);
+        assert!(threads[1].replies().is_empty());
+
+        // The flat listing still contains every answer
+        let answers = answer_dao.get_answers(EntityId::uuid(question_id)).await.expect("answers should be returned");
+        assert_eq!(answers.len(), 4);
+    }
+
+    #[sqlx::test]
+    async fn create_answer_should_fail_with_nested_reply_err(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 1).await;
+        let reply = answer_dao.create_answer(fixtures::answer(question_id).parent_answer_id(answer_ids[0]).build())
+            .await
+            .expect("reply should be created successfully");
+        let res = answer_dao.create_answer(fixtures::answer(question_id).parent_answer_id(reply.id()).build()).await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { reason: ValidationReason::NestedReply(id), .. }) = res else { panic!("Error should be `NestedReply` reason") };
+        assert_eq!(id, reply.id());
+    }
+
+    #[sqlx::test]
+    async fn create_answer_should_fail_to_reply_across_questions(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let (_, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 1).await;
+        let (other_question_id, _) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 0).await;
+        let res = answer_dao.create_answer(fixtures::answer(other_question_id).parent_answer_id(answer_ids[0]).build()).await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        let res = answer_dao.create_answer(fixtures::answer(other_question_id).parent_answer_id(Uuid::new_v4()).build()).await;
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+    }
+
+    #[sqlx::test]
+    async fn delete_answer_should_delete_its_replies(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 2).await;
+        let reply = answer_dao.create_answer(fixtures::answer(question_id).parent_answer_id(answer_ids[0]).build())
+            .await
+            .expect("reply should be created successfully");
+        let header = question_dao.get_question_header(EntityId::uuid(question_id)).await.expect("header should be returned");
+        assert_eq!(header.answer_count(), 3);
+        assert_eq!(header.top_level_answer_count(), 2);
+
+        let res = answer_dao.delete_answer(EntityId::uuid(answer_ids[0])).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), answer_ids[0]);
+        let listed: Vec<Uuid> = answer_dao.get_answers(EntityId::uuid(question_id)).await.unwrap().iter().map(|answer| answer.id()).collect();
+        assert_eq!(listed, [answer_ids[1]]);
+        assert!(!listed.contains(&reply.id()));
+        let header = question_dao.get_question_header(EntityId::uuid(question_id)).await.expect("header should be returned");
+        assert_eq!(header.answer_count(), 1);
+        assert_eq!(header.top_level_answer_count(), 1);
+    }
+
+    #[sqlx::test]
+    async fn delete_answer_with_tombstone_should_keep_the_thread(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 2).await;
+        let reply = answer_dao.create_answer(fixtures::answer(question_id).parent_answer_id(answer_ids[0]).build())
+            .await
+            .expect("reply should be created successfully");
+        answer_dao.increment_answer_likes(EntityId::uuid(answer_ids[0])).await.expect("answer should be liked");
+
+        let res = answer_dao.delete_answer_with_tombstone(EntityId::uuid(answer_ids[0]), Some(String::from("  spam  "))).await;
+        println!("{:?}", res);
+        let tombstone = res.unwrap();
+        assert!(tombstone.is_deleted());
+        assert!(tombstone.deleted_at().is_some());
+        assert_eq!(tombstone.deleted_reason(), Some("spam"));
+        assert_eq!(tombstone.answer(), DELETED_CONTENT);
+        assert_eq!(tombstone.likes(), 0);
+
+        // The tombstone keeps its place, and its replies stay attached to it
+        let threads = answer_dao.get_answer_threads(EntityId::uuid(question_id)).await.expect("threads should be returned");
+        let thread_ids = threads.iter().map(|thread| thread.answer().id()).collect::<Vec<_>>();
+        assert_eq!(thread_ids, answer_ids);
+        assert!(threads[0].answer().is_deleted());
+        let nested_ids = threads[0].replies().iter().map(|reply| reply.id()).collect::<Vec<_>>();
+        assert_eq!(nested_ids, [reply.id()]);
+        let answers = answer_dao.get_answers(EntityId::uuid(question_id)).await.expect("answers should be returned");
+        assert_eq!(answers.len(), 3);
+        assert_eq!(answers.iter().filter(|answer| answer.is_deleted()).count(), 1);
+
+        // The removed like is recorded in the audit log
+        let delta: i64 = sqlx::query_scalar("SELECT COALESCE(SUM(delta), 0)::BIGINT FROM like_events WHERE entity_id = $1")
+            .bind(answer_ids[0])
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(delta, 0);
+
+        let json = serde_json::to_value(crate::models::dto::AnswerResponse::from(tombstone)).unwrap();
+        assert_eq!(json["is_deleted"], true);
+        assert_eq!(json["deleted_reason"], "spam");
+        assert_eq!(json["answer"], DELETED_CONTENT);
+        let json = serde_json::to_value(crate::models::dto::AnswerResponse::from(reply)).unwrap();
+        assert!(json.get("is_deleted").is_none());
+        assert!(json.get("deleted_reason").is_none());
+    }
+
+    #[sqlx::test]
+    async fn delete_answer_with_tombstone_should_reject_interactions(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let (_, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 1).await;
+        let answer_id = EntityId::uuid(answer_ids[0]);
+
+        let res = answer_dao.delete_answer_with_tombstone(answer_id.clone(), Some("x".repeat(MAX_DELETED_REASON_LENGTH + 1))).await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        let res = answer_dao.delete_answer_with_tombstone(EntityId::uuid(Uuid::new_v4()), None).await;
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+
+        let tombstone = answer_dao.delete_answer_with_tombstone(answer_id.clone(), Some(String::from("   "))).await
+            .expect("answer should be tombstoned");
+        assert_eq!(tombstone.deleted_reason(), None);
+        let res = answer_dao.delete_answer_with_tombstone(answer_id.clone(), None).await;
+        println!("{:?}", res);
+        let Err(DbError::Conflict { .. }) = res else { panic!("Error should be `Conflict` variant") };
+        let res = answer_dao.increment_answer_likes(answer_id.clone()).await;
+        println!("{:?}", res);
+        let Err(DbError::Conflict { .. }) = res else { panic!("Error should be `Conflict` variant") };
+        let res = answer_dao.vote_answer(answer_id.clone(), AnswerVote::Helpful, "token").await;
+        println!("{:?}", res);
+        let Err(DbError::Conflict { .. }) = res else { panic!("Error should be `Conflict` variant") };
+        assert_eq!(answer_dao.get_answer(answer_id).await.unwrap().likes(), 0);
+    }
+
+    #[sqlx::test]
+    async fn delete_answer_should_remove_a_tombstone(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 2).await;
+        let reply = answer_dao.create_answer(fixtures::answer(question_id).parent_answer_id(answer_ids[0]).build())
+            .await
+            .expect("reply should be created successfully");
+        answer_dao.delete_answer_with_tombstone(EntityId::uuid(answer_ids[0]), None).await.expect("answer should be tombstoned");
+
+        let res = answer_dao.delete_answer(EntityId::uuid(answer_ids[0])).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), answer_ids[0]);
+        let listed: Vec<Uuid> = answer_dao.get_answers(EntityId::uuid(question_id)).await.unwrap().iter().map(|answer| answer.id()).collect();
+        assert_eq!(listed, [answer_ids[1]]);
+        assert!(!listed.contains(&reply.id()));
+    }
+
+
+    async fn last_activity(pool: &PgPool, question_id: Uuid) -> Option<chrono::DateTime<Utc>> {
+        sqlx::query_scalar("SELECT last_activity_at FROM questions WHERE id = $1")
+            .bind(question_id)
+            .fetch_one(pool)
+            .await
+            .expect("question should exist")
+    }
+
+    #[sqlx::test]
+    async fn answers_and_likes_should_record_question_activity(pool: PgPool) {
+        let clock = Arc::new(FixedClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()));
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_clock(clock.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone()).with_clock(clock.clone());
+        let question_id = question_dao.create_question(fixtures::question().build()).await.unwrap().id();
+        // Asking a question is not activity, it counts from its creation instead
+        assert_eq!(last_activity(&pool, question_id).await, None);
+
+        clock.advance(Duration::days(1));
+        let answer = answer_dao.create_answer(fixtures::answer(question_id).build()).await.unwrap();
+        assert_eq!(last_activity(&pool, question_id).await, Some(clock.now()));
+        clock.advance(Duration::days(1));
+        let draft = answer_dao.create_answer_draft(fixtures::answer(question_id).build()).await.unwrap();
+        assert_eq!(last_activity(&pool, question_id).await, Some(clock.now() - Duration::days(1)));
+        answer_dao.publish_answer(EntityId::uuid(draft.id())).await.unwrap();
+        assert_eq!(last_activity(&pool, question_id).await, Some(clock.now()));
+
+        clock.advance(Duration::days(1));
+        question_dao.increment_question_likes(EntityId::uuid(question_id)).await.unwrap();
+        assert_eq!(last_activity(&pool, question_id).await, Some(clock.now()));
+        clock.advance(Duration::days(1));
+        answer_dao.increment_answer_likes(EntityId::uuid(answer.id())).await.unwrap();
+        assert_eq!(last_activity(&pool, question_id).await, Some(clock.now()));
+    }
+
+    #[sqlx::test]
+    async fn archive_stale_answers_should_move_answers_of_inactive_questions(pool: PgPool) {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let clock = Arc::new(FixedClock::new(start));
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_clock(clock.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone()).with_clock(clock.clone());
+        let (stale_id, stale_answers) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 2).await;
+        let reply = NewAnswer { parent_answer_id: Some(stale_answers[0].to_string()), ..fixtures::answer(stale_id).build() };
+        answer_dao.create_answer(reply).await.unwrap();
+        question_dao.pin_answer(EntityId::uuid(stale_id), EntityId::uuid(stale_answers[1])).await.unwrap();
+        let (liked_id, _) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 1).await;
+        // Stale, but without answers to archive
+        let unanswered_id = question_dao.create_question(fixtures::question().build()).await.unwrap().id();
+
+        clock.advance(Duration::days(60));
+        question_dao.increment_question_likes(EntityId::uuid(liked_id)).await.unwrap();
+        let (recent_id, _) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 1).await;
+        clock.advance(Duration::days(1));
+
+        let res = answer_dao.archive_stale_answers(Duration::days(30), 10).await;
+        println!("{:?}", res);
+        let report = res.unwrap();
+        assert_eq!(report.questions, vec![(stale_id, 3)]);
+        assert_eq!((report.answers(), report.batches), (3, 1));
+        assert!(answer_dao.get_answers(EntityId::uuid(stale_id)).await.unwrap().is_empty());
+        for id in [liked_id, recent_id] {
+            assert_eq!(answer_dao.get_answers(EntityId::uuid(id)).await.unwrap().len(), 1);
+        }
+        let archived: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM answers_archive WHERE question_id = $1")
+            .bind(stale_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(archived, 3);
+
+        let options = DetailOptions { include_archived: true, ..DetailOptions::default() };
+        let detail = question_dao.get_question_detail_with(EntityId::uuid(stale_id), options).await.unwrap();
+        assert_eq!(detail.archived_answers(), Some(3));
+        assert_eq!(detail.header().answer_count(), 0);
+        assert_eq!(question_dao.get_question(EntityId::uuid(stale_id)).await.unwrap().pinned_answer_id(), None);
+        let detail = question_dao.get_question_detail_with(EntityId::uuid(unanswered_id), options).await.unwrap();
+        assert_eq!(detail.archived_answers(), Some(0));
+        let detail = question_dao.get_question_detail_with(EntityId::uuid(stale_id), DetailOptions::default()).await.unwrap();
+        assert_eq!(detail.archived_answers(), None);
+    }
+
+    #[sqlx::test]
+    async fn archive_stale_answers_should_archive_nothing_on_a_second_pass(pool: PgPool) {
+        let clock = Arc::new(FixedClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()));
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_clock(clock.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone()).with_clock(clock.clone());
+        let (first_id, _) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 2).await;
+        // Questions are archived in the order they were asked
+        clock.advance(Duration::seconds(1));
+        let (second_id, _) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 1).await;
+        clock.advance(Duration::days(90));
+
+        let res = answer_dao.archive_stale_answers(Duration::days(30), 0).await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        // One question per batch, the last batch finding none left
+        let report = answer_dao.archive_stale_answers(Duration::days(30), 1).await.unwrap();
+        println!("{}", report);
+        assert_eq!(report.questions, vec![(first_id, 2), (second_id, 1)]);
+        assert_eq!(report.batches, 2);
+
+        let report = answer_dao.archive_stale_answers(Duration::days(30), 1).await.unwrap();
+        assert_eq!(report, ArchiveReport::default());
+        let options = DetailOptions { include_archived: true, ..DetailOptions::default() };
+        let detail = question_dao.get_question_detail_with(EntityId::uuid(first_id), options).await.unwrap();
+        assert_eq!(detail.archived_answers(), Some(2));
+    }
+
+    #[sqlx::test]
+    async fn archive_stale_answers_should_keep_votes_flags_and_acceptance(pool: PgPool) {
+        let clock = Arc::new(FixedClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()));
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_clock(clock.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone()).with_clock(clock.clone());
+        let tag_dao = TagDaoImpl::new(pool.clone());
+        let author_dao = AuthorDaoImpl::new(pool.clone());
+        let user_id: Uuid = sqlx::query_scalar("INSERT INTO users (username) VALUES ('answerer') RETURNING id")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let question_id = question_dao.create_question(fixtures::question().build()).await.unwrap().id();
+        tag_dao.tag_question(EntityId::uuid(question_id), "rust").await.unwrap();
+        let answer_id = answer_dao.create_answer(fixtures::answer(question_id).author_id(user_id).build()).await.unwrap().id();
+        answer_dao.increment_answer_likes(EntityId::uuid(answer_id)).await.unwrap();
+        answer_dao.vote_answer(EntityId::uuid(answer_id), AnswerVote::Helpful, "voter").await.unwrap();
+        sqlx::query("INSERT INTO moderation_flags (answer_id, reason) VALUES ($1, 'spam')")
+            .bind(answer_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        question_dao.accept_answer(EntityId::uuid(question_id), EntityId::uuid(answer_id)).await.unwrap();
+        question_dao.pin_answer(EntityId::uuid(question_id), EntityId::uuid(answer_id)).await.unwrap();
+        let acceptance = tag_dao.get_acceptance_rate_by_tag(1).await.unwrap();
+        assert_eq!(acceptance[0].accepted_count, 1);
+        let reputation = author_dao.get_top_users_by_reputation(10).await.unwrap();
+        clock.advance(Duration::days(60));
+
+        let res = answer_dao.archive_stale_answers(Duration::days(30), 10).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap().questions, vec![(question_id, 1)]);
+        assert_eq!(tag_dao.get_acceptance_rate_by_tag(1).await.unwrap(), acceptance);
+        assert_eq!(author_dao.get_top_users_by_reputation(10).await.unwrap(), reputation);
+        let question = question_dao.get_question(EntityId::uuid(question_id)).await.unwrap();
+        assert_eq!((question.accepted_answer_id(), question.archived_accepted_answer_id()), (None, Some(answer_id)));
+        assert!(question.has_accepted_answer());
+        // The pin only orders the listed answers, so it is not kept
+        assert_eq!(question.pinned_answer_id(), None);
+        let header = question_dao.get_question_header(EntityId::uuid(question_id)).await.unwrap();
+        assert_eq!(header.accepted_answer_id(), Some(answer_id));
+        let archived: (i64, i64) = sqlx::query_as(
+            "SELECT (SELECT COUNT(*) FROM answer_votes_archive WHERE answer_id = $1), \
+            (SELECT COUNT(*) FROM moderation_flags_archive WHERE answer_id = $1)"
+        )
+            .bind(answer_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(archived, (1, 1));
+
+        // Accepting a new answer replaces the archived acceptance, and the reputation it earned
+        let new_answer_id = answer_dao.create_answer(fixtures::answer(question_id).build()).await.unwrap().id();
+        let question = question_dao.accept_answer(EntityId::uuid(question_id), EntityId::uuid(new_answer_id)).await.unwrap();
+        assert_eq!((question.accepted_answer_id(), question.archived_accepted_answer_id()), (Some(new_answer_id), None));
+        let reputation: i32 = sqlx::query_scalar("SELECT reputation FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(reputation, ReputationConfig::default().answer_liked);
+
+    }
+}
+
+mod subscription_tests {
+    use std::sync::Arc;
+    use chrono::{Duration, TimeZone, Utc};
+    use sqlx::types::Uuid;
+    use crate::clock::{Clock, FixedClock};
+    use crate::fixtures;
+    use crate::models::{DbError, EntityId, ModerationMode};
+    use crate::persistence::prelude::PgPool;
+    use crate::persistence::{AnswerDao, AnswerDaoImpl, QuestionDao, QuestionDaoImpl, SubscriptionDao, SubscriptionDaoImpl};
+
+    async fn create_question(pool: &PgPool) -> Uuid {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        question_dao.create_question(fixtures::question().build()).await.expect("question should be created successfully").id()
+    }
+
+    async fn create_answer(pool: &PgPool, question_id: Uuid) -> Uuid {
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        answer_dao.create_answer(fixtures::answer(question_id).build()).await.expect("answer should be created successfully").id()
+    }
+
+    #[sqlx::test]
+    async fn subscribe_should_fail_with_not_found(pool: PgPool) {
+        let subscription_dao = SubscriptionDaoImpl::new(pool);
+        let res = subscription_dao.subscribe("test-user", EntityId::new(Uuid::new_v4().to_string())).await;
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+    }
+
+    #[sqlx::test]
+    async fn get_updates_should_report_new_answers_once(pool: PgPool) {
+        let subscription_dao = SubscriptionDaoImpl::new(pool.clone());
+        let question_id = create_question(&pool).await;
+        let unsubscribed_question_id = create_question(&pool).await;
+        subscription_dao.subscribe("test-user", EntityId::new(question_id.to_string())).await.expect("subscription should succeed");
+        // Subscribing twice should have no effect
+        subscription_dao.subscribe("test-user", EntityId::new(question_id.to_string())).await.expect("subscription should succeed");
+        create_answer(&pool, question_id).await;
+        create_answer(&pool, question_id).await;
+        create_answer(&pool, unsubscribed_question_id).await;
+
+        let updates = subscription_dao.get_updates("test-user").await;
+        println!("{:?}", updates);
+        let updates = updates.unwrap();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].question_id(), question_id);
+        assert_eq!(updates[0].new_answers(), 2);
+
+        // The answers have now been seen
+        let updates = subscription_dao.get_updates("test-user").await;
+        println!("{:?}", updates);
+        assert!(updates.unwrap().is_empty());
+
+        // Only answers created since the last fetch are reported
+        create_answer(&pool, question_id).await;
+        let updates = subscription_dao.get_updates("test-user").await.unwrap();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].new_answers(), 1);
+    }
+
+    #[sqlx::test]
+    async fn get_updates_should_report_answers_once_approved(pool: PgPool) {
+        let clock = Arc::new(FixedClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()));
+        let subscription_dao = SubscriptionDaoImpl::new(pool.clone()).with_clock(clock.clone()).with_moderation(ModerationMode::Manual);
+        let answer_dao = AnswerDaoImpl::new(pool.clone()).with_clock(clock.clone()).with_moderation(ModerationMode::Manual);
+        let question_id = create_question(&pool).await;
+        // Held back when subscribing, so reported once approved
+        let pending_id = answer_dao.create_answer(fixtures::answer(question_id).build()).await.unwrap().id();
+        subscription_dao.subscribe("test-user", EntityId::uuid(question_id)).await.expect("subscription should succeed");
+        clock.advance(Duration::minutes(1));
+        let earlier_id = answer_dao.create_answer(fixtures::answer(question_id).build()).await.unwrap().id();
+        clock.advance(Duration::minutes(1));
+        let later_id = answer_dao.create_answer(fixtures::answer(question_id).build()).await.unwrap().id();
+        assert!(subscription_dao.get_updates("test-user").await.unwrap().is_empty());
+
+        answer_dao.approve_answer(EntityId::uuid(later_id)).await.unwrap();
+        let updates = subscription_dao.get_updates("test-user").await.unwrap();
+        assert_eq!((updates.len(), updates[0].new_answers()), (1, 1));
+        // Approving answers created before the last answer reported still reports them
+        answer_dao.approve_answer(EntityId::uuid(earlier_id)).await.unwrap();
+        answer_dao.approve_answer(EntityId::uuid(pending_id)).await.unwrap();
+        let updates = subscription_dao.get_updates("test-user").await;
+        println!("{:?}", updates);
+        let updates = updates.unwrap();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].new_answers(), 2);
+        assert_eq!(updates[0].latest_answer_at(), clock.now() - Duration::minutes(1));
+        assert!(subscription_dao.get_updates("test-user").await.unwrap().is_empty());
+    }
+
+    #[sqlx::test]
+    async fn get_updates_should_exclude_unsubscribed_questions(pool: PgPool) {
+        let subscription_dao = SubscriptionDaoImpl::new(pool.clone());
+        let question_id = create_question(&pool).await;
+        subscription_dao.subscribe("test-user", EntityId::new(question_id.to_string())).await.expect("subscription should succeed");
+        subscription_dao.unsubscribe("test-user", EntityId::new(question_id.to_string())).await.expect("unsubscription should succeed");
+        create_answer(&pool, question_id).await;
+        let updates = subscription_dao.get_updates("test-user").await;
+        println!("{:?}", updates);
+        assert!(updates.unwrap().is_empty());
+    }
+
+    #[sqlx::test]
+    async fn get_updates_should_exclude_deleted_questions(pool: PgPool) {
+        let subscription_dao = SubscriptionDaoImpl::new(pool.clone());
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let question_id = create_question(&pool).await;
+        subscription_dao.subscribe("test-user", EntityId::new(question_id.to_string())).await.expect("subscription should succeed");
+        create_answer(&pool, question_id).await;
+        question_dao.delete_question(EntityId::new(question_id.to_string()), false).await.expect("question should be deleted successfully");
+        let updates = subscription_dao.get_updates("test-user").await;
+        println!("{:?}", updates);
+        assert!(updates.unwrap().is_empty());
+        let subscriptions: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM subscriptions")
+            .fetch_one(&pool)
+            .await
+            .expect("subscriptions should be counted successfully");
+        assert_eq!(subscriptions, 0);
+    }
+}
+
+mod stats_tests {
+    use std::sync::Arc;
+    use chrono::{Duration, NaiveDate, TimeZone, Utc};
+    use sqlx::types::Uuid;
+    use crate::clock::FixedClock;
+    use crate::fixtures;
+    use crate::models::{DailyActivity, DbError, LatencyStats, NewAnswer, NewQuestion};
+    use crate::persistence::prelude::PgPool;
+    use crate::persistence::{AnswerDao, AnswerDaoImpl, QuestionDao, QuestionDaoImpl, StatsDao, StatsDaoImpl};
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, d).unwrap()
+    }
+
+    #[sqlx::test]
+    async fn get_daily_activity_should_zero_fill_days_without_activity(pool: PgPool) {
+        let clock = Arc::new(FixedClock::new(Utc.with_ymd_and_hms(2024, 1, 13, 23, 59, 0).unwrap()));
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_clock(clock.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone()).with_clock(clock.clone());
+        // One question and one answer on the 13th
+        let (question_id, _) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 1).await;
+        // Two questions and three answers on the 15th
+        clock.set(Utc.with_ymd_and_hms(2024, 1, 15, 0, 0, 0).unwrap());
+        fixtures::seed_questions(&question_dao, 2).await;
+        for _ in 0..3 {
+            answer_dao.create_answer(fixtures::answer(question_id).build()).await.expect("answer should be created successfully");
+        }
+        // Activity before the reported range is excluded
+        clock.set(Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap());
+        fixtures::seed_questions(&question_dao, 1).await;
+
+        clock.set(Utc.with_ymd_and_hms(2024, 1, 15, 18, 0, 0).unwrap());
+        let stats_dao = StatsDaoImpl::new(pool).with_clock(clock);
+        let res = stats_dao.get_daily_activity(5).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), vec![
+            DailyActivity { date: day(11), questions: 0, answers: 0 },
+            DailyActivity { date: day(12), questions: 0, answers: 0 },
+            DailyActivity { date: day(13), questions: 1, answers: 1 },
+            DailyActivity { date: day(14), questions: 0, answers: 0 },
+            DailyActivity { date: day(15), questions: 2, answers: 3 },
+        ]);
+    }
+
+    #[sqlx::test]
+    async fn get_daily_activity_should_report_today_only(pool: PgPool) {
+        let clock = Arc::new(FixedClock::new(Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap()));
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_clock(clock.clone());
+        fixtures::seed_questions(&question_dao, 1).await;
+        clock.advance(Duration::hours(1));
+        let stats_dao = StatsDaoImpl::new(pool).with_clock(clock);
+        let res = stats_dao.get_daily_activity(1).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), vec![DailyActivity { date: day(15), questions: 1, answers: 0 }]);
+        let res = stats_dao.get_daily_activity(0).await;
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+    }
+
+    #[test]
+    fn daily_activity_should_serialize_date_as_iso_string() {
+        let json = serde_json::to_value(DailyActivity { date: day(15), questions: 2, answers: 3 }).unwrap();
+        assert_eq!(json, serde_json::json!({ "date": "2024-01-15", "questions": 2, "answers": 3 }));
+    }
+
+    #[sqlx::test]
+    async fn get_time_to_first_answer_should_compute_percentiles_and_unanswered(pool: PgPool) {
+        let since = Utc.with_ymd_and_hms(2024, 1, 10, 0, 0, 0).unwrap();
+        let clock = Arc::new(FixedClock::new(since - Duration::days(1)));
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_clock(clock.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone()).with_clock(clock.clone());
+        let ask = || NewQuestion { title: String::from("Test Question"), question: String::from("Hello this question is a test"), external_id: None, content_type: None, author_token: None };
+        let answer = |question_id: Uuid| NewAnswer { question_id: question_id.to_string(), answer: String::from("Test answer"), author_id: None, content_type: None, author_token: None, parent_answer_id: None };
+
+        // A question from before the window, answered quickly, is not measured
+        let early = question_dao.create_question(ask()).await.unwrap().id();
+        clock.advance(Duration::seconds(1));
+        answer_dao.create_answer(answer(early)).await.unwrap();
+
+        // Questions in the window are answered after 60, 120 and 300 seconds, only the first answer counts
+        for wait in [60, 120, 300] {
+            clock.set(since + Duration::hours(wait));
+            let question_id = question_dao.create_question(ask()).await.unwrap().id();
+            clock.advance(Duration::seconds(wait));
+            answer_dao.create_answer(answer(question_id)).await.unwrap();
+            clock.advance(Duration::seconds(1000));
+            answer_dao.create_answer(answer(question_id)).await.unwrap();
+        }
+        // One question without answers and one with only a draft are unanswered
+        question_dao.create_question(ask()).await.unwrap();
+        let drafted = question_dao.create_question(ask()).await.unwrap().id();
+        answer_dao.create_answer_draft(answer(drafted)).await.unwrap();
+
+        let stats_dao = StatsDaoImpl::new(pool);
+        let res = stats_dao.get_time_to_first_answer(since).await;
+        println!("{:?}", res);
+        let stats = res.unwrap();
+        assert_eq!(stats, LatencyStats {
+            answered: 3,
+            unanswered: 2,
+            min: Some(Duration::seconds(60)),
+            median: Some(Duration::seconds(120)),
+            // Interpolated between 120 and 300 seconds
+            p90: Some(Duration::seconds(264)),
+        });
+        let json = serde_json::to_value(stats).unwrap();
+        assert_eq!(json["median"], serde_json::json!(120.0));
+    }
+
+    #[sqlx::test]
+    async fn get_time_to_first_answer_should_report_no_durations_without_answers(pool: PgPool) {
+        let stats_dao = StatsDaoImpl::new(pool);
+        let res = stats_dao.get_time_to_first_answer(Utc::now() - Duration::days(7)).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), LatencyStats::default());
+    }
+}
+
+mod migration_tests {
+    use crate::fixtures;
+    use crate::persistence::prelude::PgPool;
+    use crate::persistence::migrations::{self, MigrationError, ResetOptions};
+    use crate::persistence::{AnswerDaoImpl, QuestionDaoImpl};
+
+    async fn count_rows(pool: &PgPool, table: &str) -> i64 {
+        sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table}"))
+            .fetch_one(pool)
+            .await
+            .expect("table should exist")
+    }
+
+    #[sqlx::test]
+    async fn reset_should_recreate_empty_tables(pool: PgPool) {
+        fixtures::seed_question_with_answers(&QuestionDaoImpl::new(pool.clone()), &AnswerDaoImpl::new(pool.clone()), 2).await;
+        sqlx::query("INSERT INTO users (username) VALUES ('reset')")
+            .execute(&pool)
+            .await
+            .expect("user should be created successfully");
+        let res = migrations::reset(&pool, ResetOptions { allow_destructive: true, force: false }).await;
+        println!("{:?}", res);
+        assert!(res.is_ok());
+        for table in ["questions", "answers", "users", "idempotency_keys", "subscriptions", "categories", "like_events"] {
+            assert_eq!(count_rows(&pool, table).await, 0, "{table} should be empty");
+        }
+        // The schema should be fully usable again
+        fixtures::seed_question_with_answers(&QuestionDaoImpl::new(pool.clone()), &AnswerDaoImpl::new(pool.clone()), 1).await;
+    }
+
+    #[sqlx::test]
+    async fn reset_should_refuse_without_allow_destructive(pool: PgPool) {
+        fixtures::seed_questions(&QuestionDaoImpl::new(pool.clone()), 1).await;
+        let res = migrations::reset(&pool, ResetOptions::default()).await;
+        println!("{:?}", res);
+        let Err(MigrationError::DestructiveNotAllowed) = res else { panic!("Error should be `DestructiveNotAllowed` variant") };
+        assert_eq!(count_rows(&pool, "questions").await, 1);
+    }
+
+    #[test]
+    fn is_disposable_should_require_dev_or_test_in_name() {
+        assert!(migrations::is_disposable("qa_dev"));
+        assert!(migrations::is_disposable("_sqlx_test_42"));
+        assert!(migrations::is_disposable("QA_TEST"));
+        assert!(!migrations::is_disposable("qa"));
+        assert!(!migrations::is_disposable("production"));
+    }
+}
+
+mod admin_tests {
+    use sqlx::types::Uuid;
+    use crate::fixtures;
+    use std::sync::Arc;
+    use chrono::{Duration, TimeZone, Utc};
+    use crate::clock::FixedClock;
+    use crate::models::{AnonymizeReport, BulkUpdate, DbError, EntityId, TransferReport, DELETED_CONTENT};
+    use crate::persistence::prelude::PgPool;
+    use crate::persistence::{AdminDao, AdminDaoImpl, AnswerDao, AnswerDaoImpl, QuestionDao, QuestionDaoImpl};
+
+    fn entity_id(id: Uuid) -> EntityId {
+        EntityId::new(id.to_string())
+    }
+
+    #[sqlx::test]
+    async fn recompute_content_stats_should_backfill_existing_rows(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        let (question_id, _) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 3).await;
+        for _ in 0..4 {
+            question_dao.create_question(fixtures::question().build()).await.expect("question should be created successfully");
+        }
+        // Simulate rows created before the counts were maintained
+        sqlx::query("UPDATE questions SET char_count = 0, word_count = 0").execute(&pool).await.unwrap();
+        sqlx::query("UPDATE answers SET char_count = 0, word_count = 0").execute(&pool).await.unwrap();
+        let admin_dao = AdminDaoImpl::new(pool.clone());
+        // Batches smaller than the table should still visit every row
+        let res = admin_dao.recompute_table_stats("questions", "question", 2).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), 5);
+        let res = admin_dao.recompute_content_stats().await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), 3);
+        let question = question_dao.get_question(entity_id(question_id)).await.expect("question should be found");
+        assert!(question.word_count() > 0 && question.char_count() >= question.word_count());
+        let answers = answer_dao.get_answers(entity_id(question_id)).await.expect("answers should be found");
+        assert!(answers.iter().all(|answer| answer.word_count() > 0));
+        // Nothing changes once the counts are up to date
+        assert_eq!(admin_dao.recompute_content_stats().await.unwrap(), 0);
+    }
+
+    #[sqlx::test]
+    async fn set_likes_should_overwrite_counts(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 1).await;
+        let admin_dao = AdminDaoImpl::new(pool);
+        let res = admin_dao.set_question_likes(entity_id(question_id), 42).await;
+        println!("{:?}", res);
+        assert!(res.is_ok());
+        let res = admin_dao.set_answer_likes(entity_id(answer_ids[0]), 7).await;
+        println!("{:?}", res);
+        assert!(res.is_ok());
+        assert_eq!(question_dao.get_question(entity_id(question_id)).await.unwrap().likes(), 42);
+        assert_eq!(answer_dao.get_answer(entity_id(answer_ids[0])).await.unwrap().likes(), 7);
+    }
+
+    #[sqlx::test]
+    async fn set_likes_should_reject_negative_and_missing(pool: PgPool) {
+        let question_ids = fixtures::seed_questions(&QuestionDaoImpl::new(pool.clone()), 1).await;
+        let admin_dao = AdminDaoImpl::new(pool);
+        let res = admin_dao.set_question_likes(entity_id(question_ids[0]), -1).await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        let res = admin_dao.set_question_likes(entity_id(Uuid::new_v4()), 1).await;
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+        let res = admin_dao.set_answer_likes(entity_id(Uuid::new_v4()), 1).await;
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+    }
+
+    #[sqlx::test]
+    async fn bulk_set_likes_should_update_existing_and_report_missing(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let question_ids = fixtures::seed_questions(&question_dao, 2).await;
+        let missing_id = Uuid::new_v4();
+        let admin_dao = AdminDaoImpl::new(pool);
+        let res = admin_dao.bulk_set_question_likes(vec![
+            (entity_id(question_ids[0]), 10),
+            (entity_id(missing_id), 5),
+            (entity_id(question_ids[1]), 0),
+        ]).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), BulkUpdate { updated: 2, missing: vec![missing_id] });
+        assert_eq!(question_dao.get_question(entity_id(question_ids[0])).await.unwrap().likes(), 10);
+        assert_eq!(question_dao.get_question(entity_id(question_ids[1])).await.unwrap().likes(), 0);
+    }
+
+    #[sqlx::test]
+    async fn bulk_set_likes_should_reject_invalid_batches_before_updating(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        let (_, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 2).await;
+        let admin_dao = AdminDaoImpl::new(pool);
+        let res = admin_dao.bulk_set_answer_likes(vec![(entity_id(answer_ids[0]), 3), (entity_id(answer_ids[1]), -3)]).await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        let res = admin_dao.bulk_set_answer_likes(vec![(entity_id(answer_ids[0]), 3), (entity_id(answer_ids[0]), 4)]).await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        assert_eq!(answer_dao.get_answer(entity_id(answer_ids[0])).await.unwrap().likes(), 0);
+        let res = admin_dao.bulk_set_answer_likes(vec![(entity_id(answer_ids[0]), 3), (entity_id(answer_ids[1]), 4)]).await;
+        assert_eq!(res.unwrap(), BulkUpdate { updated: 2, missing: vec![] });
+        assert_eq!(answer_dao.get_answer(entity_id(answer_ids[1])).await.unwrap().likes(), 4);
+    }
+
+    async fn create_author(pool: &PgPool, username: &str) -> Uuid {
+        sqlx::query_scalar("INSERT INTO users (username) VALUES ($1) RETURNING id")
+            .bind(username)
+            .fetch_one(pool)
+            .await
+            .expect("user should be created successfully")
+    }
+
+    /// Seeds a question with two answers by `author_id` and one by another author.
+    async fn seed_authored_answers(pool: &PgPool, author_id: Uuid) -> Uuid {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        let question_id = fixtures::seed_questions(&question_dao, 1).await[0];
+        let other_id = create_author(pool, "other").await;
+        for author in [author_id, author_id, other_id] {
+            answer_dao.create_answer(fixtures::answer(question_id).answer("Authored answer").author_id(author).build())
+                .await
+                .expect("answer should be created successfully");
+        }
+        question_id
+    }
+
+    async fn dangling_author_count(pool: &PgPool) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM answers LEFT JOIN users ON users.id = answers.author_id WHERE answers.author_id IS NOT NULL AND users.id IS NULL")
+            .fetch_one(pool)
+            .await
+            .expect("query should succeed")
+    }
+
+    #[sqlx::test]
+    async fn anonymize_author_should_keep_content_without_scrubbing(pool: PgPool) {
+        let author_id = create_author(&pool, "erased").await;
+        let question_id = seed_authored_answers(&pool, author_id).await;
+        let admin_dao = AdminDaoImpl::new(pool.clone());
+        let res = admin_dao.anonymize_author(entity_id(author_id), false).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), AnonymizeReport { answers: 2, users: 1 });
+        let answers = AnswerDaoImpl::new(pool.clone()).get_answers(entity_id(question_id)).await.unwrap();
+        assert_eq!(answers.len(), 3);
+        assert!(answers.iter().all(|a| a.answer() == "Authored answer"));
+        assert_eq!(answers.iter().filter(|a| a.author_id().is_none()).count(), 2);
+        assert_eq!(dangling_author_count(&pool).await, 0);
+    }
+
+    #[sqlx::test]
+    async fn anonymize_author_should_scrub_content_and_be_idempotent(pool: PgPool) {
+        let author_id = create_author(&pool, "erased").await;
+        let question_id = seed_authored_answers(&pool, author_id).await;
+        let admin_dao = AdminDaoImpl::new(pool.clone());
+        let res = admin_dao.anonymize_author(entity_id(author_id), true).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), AnonymizeReport { answers: 2, users: 1 });
+        let answers = AnswerDaoImpl::new(pool.clone()).get_answers(entity_id(question_id)).await.unwrap();
+        let scrubbed: Vec<_> = answers.iter().filter(|a| a.answer() == DELETED_CONTENT).collect();
+        assert_eq!(scrubbed.len(), 2);
+        assert!(scrubbed.iter().all(|a| a.author_id().is_none()));
+        // The other author's answer is untouched
+        assert!(answers.iter().any(|a| a.answer() == "Authored answer" && a.author_id().is_some()));
+        assert_eq!(dangling_author_count(&pool).await, 0);
+        let res = admin_dao.anonymize_author(entity_id(author_id), true).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), AnonymizeReport::default());
+    }
+
+    /// Reads the ownership audit log as `(entity_type, entity_id, previous_author_id, new_author_id)`, oldest first.
+    async fn ownership_transfers(pool: &PgPool) -> Vec<(String, Uuid, Option<Uuid>, Uuid)> {
+        sqlx::query_as("SELECT entity_type, entity_id, previous_author_id, new_author_id FROM ownership_transfers ORDER BY id")
+            .fetch_all(pool)
+            .await
+            .expect("transfers should be read")
+    }
+
+    #[sqlx::test]
+    async fn transfer_question_and_answer_author_should_record_transfers(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 1).await;
+        let ghost_id = create_author(&pool, "ghost").await;
+        let author_id = create_author(&pool, "author").await;
+        let admin_dao = AdminDaoImpl::new(pool.clone());
+
+        let res = admin_dao.transfer_question_author(entity_id(question_id), entity_id(ghost_id)).await;
+        println!("{:?}", res);
+        assert!(res.is_ok());
+        admin_dao.transfer_question_author(entity_id(question_id), entity_id(author_id)).await.expect("question should be transferred");
+        let res = admin_dao.transfer_answer_author(entity_id(answer_ids[0]), entity_id(author_id)).await;
+        println!("{:?}", res);
+        assert!(res.is_ok());
+        // Transferring to the current author changes nothing and records nothing
+        admin_dao.transfer_answer_author(entity_id(answer_ids[0]), entity_id(author_id)).await.expect("answer should be transferred");
+
+        let question = question_dao.get_question(entity_id(question_id)).await.unwrap();
+        assert_eq!(question.author_id(), Some(author_id));
+        let answer = answer_dao.get_answer(entity_id(answer_ids[0])).await.unwrap();
+        assert_eq!(answer.author_id(), Some(author_id));
+        assert_eq!(ownership_transfers(&pool).await, vec![
+            (String::from("question"), question_id, None, ghost_id),
+            (String::from("question"), question_id, Some(ghost_id), author_id),
+            (String::from("answer"), answer_ids[0], None, author_id),
+        ]);
+    }
+
+    #[sqlx::test]
+    async fn transfer_author_should_fail_with_not_found_on_unknown_target_user(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 1).await;
+        let admin_dao = AdminDaoImpl::new(pool.clone());
+        let res = admin_dao.transfer_question_author(entity_id(question_id), entity_id(Uuid::new_v4())).await;
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+        let res = admin_dao.transfer_answer_author(entity_id(answer_ids[0]), entity_id(Uuid::new_v4())).await;
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+        let author_id = create_author(&pool, "author").await;
+        let res = admin_dao.transfer_answer_author(entity_id(Uuid::new_v4()), entity_id(author_id)).await;
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+        let res = admin_dao.transfer_all_content(entity_id(author_id), entity_id(Uuid::new_v4())).await;
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+        assert!(ownership_transfers(&pool).await.is_empty());
+    }
+
+    #[sqlx::test]
+    async fn transfer_all_content_should_move_questions_and_answers(pool: PgPool) {
+        let old_id = create_author(&pool, "old").await;
+        let new_id = create_author(&pool, "new").await;
+        let question_id = seed_authored_answers(&pool, old_id).await;
+        let admin_dao = AdminDaoImpl::new(pool.clone());
+        admin_dao.transfer_question_author(entity_id(question_id), entity_id(old_id)).await.expect("question should be transferred");
+
+        let res = admin_dao.transfer_all_content(entity_id(old_id), entity_id(new_id)).await;
+        println!("{:?}", res);
+        let report = res.unwrap();
+        assert_eq!(report, TransferReport { questions: 1, answers: 2 });
+        assert_eq!(report.to_string(), "Transferred 1 questions and 2 answers");
+        let answers = AnswerDaoImpl::new(pool.clone()).get_answers(entity_id(question_id)).await.unwrap();
+        assert_eq!(answers.iter().filter(|a| a.author_id() == Some(new_id)).count(), 2);
+        assert!(answers.iter().all(|a| a.author_id() != Some(old_id)));
+        // One transfer for the attribution, then one for each transferred row
+        let transfers = ownership_transfers(&pool).await;
+        assert_eq!(transfers.len(), 4);
+        assert!(transfers[1..].iter().all(|(_, _, previous, new)| *previous == Some(old_id) && *new == new_id));
+        // Nothing is left to transfer, and merging an account into itself is rejected
+        assert_eq!(admin_dao.transfer_all_content(entity_id(old_id), entity_id(new_id)).await.unwrap(), TransferReport::default());
+        let res = admin_dao.transfer_all_content(entity_id(new_id), entity_id(new_id)).await;
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+    }
+
+    #[sqlx::test]
+    async fn like_events_should_record_increments_and_decrements(pool: PgPool) {
+        let started = Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap();
+        let clock = Arc::new(FixedClock::new(started));
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_clock(clock.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone()).with_clock(clock.clone());
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 1).await;
+        let admin_dao = AdminDaoImpl::new(pool);
+
+        question_dao.increment_question_likes(entity_id(question_id)).await.expect("like should be counted");
+        clock.advance(Duration::hours(1));
+        question_dao.increment_question_likes(entity_id(question_id)).await.expect("like should be counted");
+        answer_dao.increment_answer_likes(entity_id(answer_ids[0])).await.expect("like should be counted");
+        // Setting a lower count is recorded as a decrement, and setting the same count is not recorded
+        admin_dao.set_question_likes(entity_id(question_id), 1).await.expect("likes should be set");
+        admin_dao.set_question_likes(entity_id(question_id), 1).await.expect("likes should be set");
+
+        let res = admin_dao.get_like_events(entity_id(question_id), started).await;
+        println!("{:?}", res);
+        let events = res.unwrap();
+        let deltas: Vec<i64> = events.iter().map(|event| event.delta()).collect();
+        assert_eq!(deltas, vec![1, 1, -1]);
+        assert!(events.iter().all(|event| event.entity_type() == "question" && event.entity_id() == question_id));
+        assert_eq!(events[0].occurred_at(), started);
+        let answer_events = admin_dao.get_like_events(entity_id(answer_ids[0]), started).await.unwrap();
+        assert_eq!(answer_events.len(), 1);
+        assert_eq!(answer_events[0].entity_type(), "answer");
+
+        // Only the events within the window are returned
+        let res = admin_dao.get_like_events(entity_id(question_id), started + Duration::minutes(30)).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap()[0].occurred_at(), started + Duration::hours(1));
+    }
+
+    #[sqlx::test]
+    async fn like_events_should_sum_to_the_counters(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let question_ids = fixtures::seed_questions(&question_dao, 3).await;
+        let admin_dao = AdminDaoImpl::new(pool.clone());
+        for _ in 0..3 {
+            question_dao.increment_question_likes(entity_id(question_ids[0])).await.expect("like should be counted");
+        }
+        question_dao.increment_question_likes(entity_id(question_ids[1])).await.expect("like should be counted");
+        admin_dao.bulk_set_question_likes(vec![(entity_id(question_ids[0]), 1), (entity_id(question_ids[2]), 5)]).await.expect("likes should be set");
+        admin_dao.merge_questions(entity_id(question_ids[2]), entity_id(question_ids[1])).await.expect("questions should be merged");
+        // Events cannot be rewritten
+        let res = sqlx::query("DELETE FROM like_events").execute(&pool).await;
+        assert!(res.is_err());
+
+        let since = Utc::now() - Duration::days(1);
+        for question_id in &question_ids[..2] {
+            let events = admin_dao.get_like_events(entity_id(*question_id), since).await.unwrap();
+            let likes = question_dao.get_question(entity_id(*question_id)).await.unwrap().likes();
+            assert_eq!(events.iter().map(|event| event.delta()).sum::<i64>(), likes);
+        }
+    }
+}
+
+mod pool_tests {
+    use std::error::Error as _;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+    use sqlx::types::Uuid;
+    use crate::models::{ConflictReason, DbError, DbErrorKind, InternalReason, ValidationReason};
+    use crate::persistence::prelude::PgPool;
+    use sqlx::migrate::Migration;
+    use crate::persistence::{migrations, QuestionDao, QuestionDaoImpl};
+    use crate::persistence::pool::{self, PoolConfig};
+    use crate::persistence::pool_monitor::{self, PoolSnapshot};
+
+    /// A well formed url of a database that refuses connections.
+    const UNREACHABLE_URL: &str = "postgres://postgres@127.0.0.1:1/qa";
+
+    #[sqlx::test]
+    async fn warm_up_should_open_min_connections(pool: PgPool) {
+        let options = (*pool.connect_options()).clone();
+        let config = PoolConfig { min_connections: 3, max_connections: 5, warm_up: true, ..PoolConfig::default() };
+        let res = pool::connect_with(options, config).await;
+        println!("{:?}", res);
+        let warmed = res.expect("pool should warm up successfully");
+        let metrics = pool::pool_metrics(&warmed);
+        // Released connections are returned to the pool asynchronously, so only the size is deterministic
+        assert!(metrics.size >= 3);
+        assert!(metrics.idle <= metrics.size as usize);
+        assert_eq!(metrics.max_connections, 5);
+    }
+
+    #[sqlx::test]
+    async fn prometheus_export_should_render_gauges_per_pool(pool: PgPool) {
+        let held = pool.acquire().await.unwrap();
+        let metrics = pool::pool_metrics(&pool);
+        let exported = metrics.prometheus_export("primary \"a\"\\b\nc");
+        println!("{exported}");
+        assert!(exported.ends_with('\n'));
+        let mut types = Vec::new();
+        let mut samples = Vec::new();
+        for line in exported.lines() {
+            if let Some(declaration) = line.strip_prefix("# TYPE ") {
+                types.push(declaration.to_string());
+            } else if !line.starts_with('#') {
+                let (series, value) = line.rsplit_once(' ').expect("samples should have a value");
+                samples.push((series.to_string(), value.parse::<f64>().expect("values should be numbers")));
+            }
+        }
+        assert_eq!(types, ["qa_pool_connections gauge", "qa_pool_max_connections gauge"]);
+        let label = r#"pool="primary \"a\"\\b\nc""#;
+        assert_eq!(samples, [
+            (format!("qa_pool_connections{{{label},state=\"idle\"}}"), metrics.idle as f64),
+            (format!("qa_pool_connections{{{label},state=\"in_use\"}}"), metrics.in_use() as f64),
+            (format!("qa_pool_max_connections{{{label}}}"), metrics.max_connections as f64),
+        ]);
+        assert!(metrics.in_use() >= 1);
+        drop(held);
+    }
+
+    #[tokio::test]
+    async fn warm_up_should_fail_fast_with_connection_err() {
+        let config = PoolConfig { acquire_timeout: Duration::from_secs(1), warm_up: true, ..PoolConfig::default() };
+        let started = Instant::now();
+        let res = pool::connect(UNREACHABLE_URL, config).await;
+        println!("{:?}", res);
+        let Err(DbError::Unavailable { .. }) = res else { panic!("Error should be `Unavailable` variant") };
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn lazy_pool_should_defer_failure_to_first_use() {
+        let config = PoolConfig { acquire_timeout: Duration::from_secs(1), lazy: true, ..PoolConfig::default() };
+        let pool = pool::connect(UNREACHABLE_URL, config).await.expect("lazy pool should be constructed without connecting");
+        assert_eq!(pool::pool_metrics(&pool).size, 0);
+        let res = sqlx::query("SELECT 1").execute(&pool).await;
+        assert!(res.is_err());
+    }
+
+    #[tokio::test]
+    async fn unavailable_pool_should_fail_calls_within_the_acquire_timeout() {
+        let config = PoolConfig { acquire_timeout: Duration::from_millis(500), lazy: true, ..PoolConfig::default() };
+        let unreachable = pool::connect(UNREACHABLE_URL, config).await.expect("lazy pool should be constructed without connecting");
+        let closed = pool::connect(UNREACHABLE_URL, config).await.expect("lazy pool should be constructed without connecting");
+        // A pool without connections closes at once
+        closed.close().await;
+        for pool in [unreachable, closed] {
+            let started = Instant::now();
+            let res = QuestionDaoImpl::new(pool).get_questions().await;
+            println!("{:?}", res);
+            let Err(e @ DbError::Unavailable { .. }) = res else { panic!("Error should be `Unavailable` variant") };
+            assert_eq!(e.kind(), DbErrorKind::Unavailable);
+            assert!(started.elapsed() < Duration::from_secs(10));
+        }
+    }
+
+    #[tokio::test]
+    async fn connect_should_reject_lazy_warm_up() {
+        let config = PoolConfig { lazy: true, warm_up: true, ..PoolConfig::default() };
+        let res = pool::connect(UNREACHABLE_URL, config).await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+    }
+
+    #[tokio::test]
+    async fn connect_should_reject_auto_migrate_without_schema_check() {
+        for config in [
+            PoolConfig { lazy: true, auto_migrate: true, ..PoolConfig::default() },
+            PoolConfig { skip_schema_check: true, auto_migrate: true, ..PoolConfig::default() },
+        ] {
+            let res = pool::connect(UNREACHABLE_URL, config).await;
+            println!("{:?}", res);
+            let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        }
+    }
+
+    /// The up migrations the crate was built with, oldest first.
+    fn up_migrations() -> Vec<&'static Migration> {
+        migrations::MIGRATOR.iter().filter(|migration| migration.migration_type.is_up_migration()).collect()
+    }
+
+    /// Reverts the latest migration, returning its name.
+    async fn revert_latest_migration(pool: &PgPool) -> String {
+        let migrations = up_migrations();
+        let [.., previous, latest] = migrations.as_slice() else { panic!("there should be at least two migrations") };
+        migrations::MIGRATOR.undo(pool, previous.version).await.expect("latest migration should be reverted");
+        format!("{}_{}", latest.version, latest.description.replace(' ', "_"))
+    }
+
+    #[sqlx::test]
+    async fn connect_should_succeed_for_up_to_date_schema(pool: PgPool) {
+        let res = pool::connect_with((*pool.connect_options()).clone(), PoolConfig::default()).await;
+        println!("{:?}", res);
+        assert!(res.is_ok());
+    }
+
+    #[sqlx::test]
+    async fn connect_should_fail_with_schema_mismatch_for_missing_migration(pool: PgPool) {
+        let latest = revert_latest_migration(&pool).await;
+        let res = pool::connect_with((*pool.connect_options()).clone(), PoolConfig::default()).await;
+        println!("{:?}", res);
+        let Err(e @ DbError::Internal { reason: InternalReason::SchemaMismatch { .. }, .. }) = res else {
+            panic!("Error should be `SchemaMismatch` reason")
+        };
+        assert!(e.to_string().contains(&latest));
+        let DbError::Internal { reason: InternalReason::SchemaMismatch { expected, found, missing }, .. } = e else { unreachable!() };
+        let migrations = up_migrations();
+        assert_eq!(expected, migrations[migrations.len() - 1].version);
+        assert_eq!(found, migrations[migrations.len() - 2].version);
+        assert_eq!(missing, vec![latest]);
+
+        // Read-only replicas skip the check
+        let config = PoolConfig { skip_schema_check: true, ..PoolConfig::default() };
+        assert!(pool::connect_with((*pool.connect_options()).clone(), config).await.is_ok());
+    }
+
+    #[sqlx::test]
+    async fn connect_should_apply_missing_migrations_with_auto_migrate(pool: PgPool) {
+        revert_latest_migration(&pool).await;
+        let config = PoolConfig { auto_migrate: true, ..PoolConfig::default() };
+        let res = pool::connect_with((*pool.connect_options()).clone(), config).await;
+        println!("{:?}", res);
+        assert!(res.is_ok());
+        let res = pool::connect_with((*pool.connect_options()).clone(), PoolConfig::default()).await;
+        assert!(res.is_ok());
+    }
+
+    #[sqlx::test]
+    async fn is_transient_should_classify_database_errors(pool: PgPool) {
+        let mut conn = pool.acquire().await.unwrap();
+        sqlx::query("SET statement_timeout = 10").execute(&mut *conn).await.unwrap();
+        let res = sqlx::query("SELECT pg_sleep(1)").execute(&mut *conn).await.map_err(DbError::from);
+        println!("{:?}", res);
+        assert!(res.unwrap_err().is_transient());
+        let res = sqlx::query("SELECT 1 / 0").execute(&mut *conn).await.map_err(DbError::from);
+        println!("{:?}", res);
+        assert!(!res.unwrap_err().is_transient());
+    }
+
+    #[sqlx::test]
+    async fn from_should_classify_database_errors_and_keep_them_as_source(pool: PgPool) {
+        /// The SQLSTATE code of the driver error kept as the source of an error.
+        fn code(e: &DbError) -> Option<String> {
+            let source = e.source().and_then(|e| e.downcast_ref::<sqlx::Error>());
+            source.and_then(|e| e.as_database_error()).and_then(|e| e.code()).map(|code| code.into_owned())
+        }
+        let question_id = Uuid::new_v4();
+        let insert_question = || sqlx::query("INSERT INTO questions (id, title, question) VALUES ($1, 'Title', 'Question')").bind(question_id);
+        insert_question().execute(&pool).await.expect("question should be inserted");
+        let res = insert_question().execute(&pool).await.map_err(DbError::from);
+        println!("{:?}", res);
+        let e = res.unwrap_err();
+        let DbError::Conflict { reason: ConflictReason::Constraint, .. } = e else { panic!("Error should be `Conflict` variant") };
+        assert_eq!(code(&e).as_deref(), Some("23505"));
+
+        let res = sqlx::query("INSERT INTO answers (question_id, answer) VALUES ($1, 'Answer')")
+            .bind(Uuid::new_v4())
+            .execute(&pool)
+            .await
+            .map_err(DbError::creation);
+        println!("{:?}", res);
+        let e = res.unwrap_err();
+        let DbError::Conflict { reason: ConflictReason::Constraint, .. } = e else { panic!("Error should be `Conflict` variant") };
+        assert_eq!(code(&e).as_deref(), Some("23503"));
+
+        let res = sqlx::query("SELECT 'not a uuid'::uuid").execute(&pool).await.map_err(DbError::from);
+        let e = res.unwrap_err();
+        let DbError::Validation { reason: ValidationReason::Constraint, .. } = e else { panic!("Error should be `Validation` variant") };
+        assert_eq!(code(&e).as_deref(), Some("22P02"));
+
+        let res = sqlx::query("SELECT 1 WHERE false").fetch_one(&pool).await.map(|_| ()).map_err(DbError::from);
+        let e = res.unwrap_err();
+        let DbError::NotFound { .. } = e else { panic!("Error should be `NotFound` variant") };
+        assert!(matches!(e.source().and_then(|e| e.downcast_ref()), Some(sqlx::Error::RowNotFound)));
+    }
+
+    /// The snapshots of a monitor, along with the time each was taken.
+    type Snapshots = Arc<Mutex<Vec<(tokio::time::Instant, PoolSnapshot)>>>;
+
+    /// Collects the snapshots of a monitor.
+    fn collector() -> (Snapshots, impl Fn(PoolSnapshot) + Send + 'static) {
+        let snapshots = Arc::new(Mutex::new(Vec::new()));
+        let observed = snapshots.clone();
+        (snapshots, move |snapshot| observed.lock().unwrap().push((tokio::time::Instant::now(), snapshot)))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn pool_monitor_should_sample_at_its_interval_until_shut_down() {
+        let config = PoolConfig { lazy: true, ..PoolConfig::default() };
+        let pool = pool::connect(UNREACHABLE_URL, config).await.expect("lazy pool should be constructed without connecting");
+        let (snapshots, observer) = collector();
+        let started = tokio::time::Instant::now();
+        let monitor = pool_monitor::spawn(pool.clone(), Duration::from_secs(10), observer).unwrap();
+        tokio::time::sleep(Duration::from_secs(35)).await;
+        let taken: Vec<Duration> = snapshots.lock().unwrap().iter().map(|(at, _)| *at - started).collect();
+        assert_eq!(taken, [0, 10, 20, 30].map(Duration::from_secs));
+        let (_, snapshot) = snapshots.lock().unwrap()[0];
+        assert_eq!(snapshot, PoolSnapshot { metrics: pool::pool_metrics(&pool), saturated_for: None, acquire_wait: None });
+
+        monitor.shutdown().await;
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        assert_eq!(snapshots.lock().unwrap().len(), 4);
+        // Dropping the handle stops the monitor as well
+        let (snapshots, observer) = collector();
+        drop(pool_monitor::spawn(pool.clone(), Duration::from_secs(10), observer).unwrap());
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        assert!(snapshots.lock().unwrap().len() <= 1);
+        // A zero interval is rejected up front, rather than panicking on the detached task
+        let (snapshots, observer) = collector();
+        let res = pool_monitor::spawn(pool, Duration::ZERO, observer);
+        println!("{:?}", res);
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        assert!(snapshots.lock().unwrap().is_empty());
+    }
+
+    #[sqlx::test]
+    async fn pool_monitor_should_report_held_connections_and_saturation(pool: PgPool) {
+        let options = (*pool.connect_options()).clone();
+        let config = PoolConfig { max_connections: 2, acquire_timeout: Duration::from_secs(5), ..PoolConfig::default() };
+        let monitored = pool::connect_with(options, config).await.expect("pool should connect successfully");
+        let (snapshots, observer) = collector();
+        let monitor = pool_monitor::spawn(monitored.clone(), Duration::from_millis(10), observer).unwrap();
+        // Waits for a snapshot matching `condition`, taken after any already observed
+        let wait_for = |condition: fn(&PoolSnapshot) -> bool| {
+            let snapshots = snapshots.clone();
+            async move {
+                let seen = snapshots.lock().unwrap().len();
+                for _ in 0..200 {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    if let Some((_, snapshot)) = snapshots.lock().unwrap()[seen..].iter().find(|(_, snapshot)| condition(snapshot)) {
+                        return *snapshot;
+                    }
+                }
+                panic!("no snapshot matched within two seconds");
+            }
+        };
+
+        let dao = QuestionDaoImpl::new(monitored.clone()).with_acquire_waits(monitor.acquire_waits());
+
+        let held = vec![monitored.acquire().await.unwrap(), monitored.acquire().await.unwrap()];
+        let saturated = wait_for(|snapshot| snapshot.saturated_for.is_some()).await;
+        assert_eq!((saturated.metrics.in_use(), saturated.metrics.max_connections), (2, 2));
+        // Nothing waited for a connection, the monitor itself never acquires one
+        assert_eq!(saturated.acquire_wait, None);
+        let longer = wait_for(|snapshot| snapshot.saturated_for.is_some()).await;
+        assert!(longer.saturated_for > saturated.saturated_for, "{longer:?} should be saturated for longer than {saturated:?}");
+        assert!(longer.prometheus_export("primary").contains("qa_pool_connections{pool=\"primary\",state=\"in_use\"} 2\n"));
+        assert!(longer.prometheus_export("primary").ends_with("qa_pool_acquire_wait_seconds{pool=\"primary\"} 0\n"));
+
+        // The call waits until a held connection is released
+        let (res, ()) = tokio::join!(dao.get_questions(), async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            drop(held);
+        });
+        println!("{:?}", res);
+        assert!(res.is_ok());
+        let waited = wait_for(|snapshot| snapshot.acquire_wait.is_some()).await;
+        assert!(waited.acquire_wait >= Some(Duration::from_millis(50)), "{waited:?} should report the wait of the call");
+        assert!(!waited.prometheus_export("primary").ends_with("qa_pool_acquire_wait_seconds{pool=\"primary\"} 0\n"));
+        // Each wait is reported once
+        let released = wait_for(|snapshot| snapshot.metrics.in_use() == 0 && snapshot.acquire_wait.is_none()).await;
+        assert_eq!(released.saturated_for, None);
+        let text = released.prometheus_export("primary");
+        assert!(text.contains("qa_pool_saturated_seconds{pool=\"primary\"} 0\n"));
+        assert!(text.ends_with("qa_pool_acquire_wait_seconds{pool=\"primary\"} 0\n"));
+        monitor.shutdown().await;
+    }
+}
+
+mod cache_tests {
+    use std::sync::{Arc, OnceLock, Weak};
+    use std::time::Duration as StdDuration;
+    use chrono::{Duration, TimeZone, Utc};
+    use crate::clock::FixedClock;
+    use crate::fixtures;
+    use crate::models::{DbError, EntityId, UnavailableReason, UpdateQuestion};
+    use crate::persistence::cache::{CacheConfig, CachedQuestionDao};
+    use crate::persistence::observer::{DaoEvent, QueryObserver};
+    use crate::persistence::prelude::PgPool;
+    use crate::persistence::pool::{self, PoolConfig};
+    use crate::persistence::{QuestionDao, QuestionDaoImpl};
+    use crate::query_counter::QueryCounter;
+
+    fn stale_config() -> CacheConfig {
+        CacheConfig { serve_stale_on_error: true, ..CacheConfig::default() }
+    }
+
+    /// A pool of a single connection which acquiring times out quickly, so holding it fails reads with a transient
+    /// error.
+    async fn single_connection_pool(pool: &PgPool) -> PgPool {
+        let config = PoolConfig { max_connections: 1, acquire_timeout: StdDuration::from_millis(100), ..PoolConfig::default() };
+        pool::connect_with((*pool.connect_options()).clone(), config).await.expect("pool should connect successfully")
+    }
+
+    #[sqlx::test]
+    async fn get_question_cached_should_serve_fresh_hits_from_the_cache(pool: PgPool) {
+        let clock = Arc::new(FixedClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()));
+        let question_dao = CachedQuestionDao::new(QuestionDaoImpl::new(pool.clone())).with_clock(clock.clone());
+        let question = question_dao.create_question(fixtures::question().build()).await.unwrap();
+        let first = question_dao.get_question_cached(EntityId::uuid(question.id())).await.unwrap();
+        assert!(!first.stale);
+
+        let (res, statements) = QueryCounter::count(question_dao.get_question_cached(EntityId::uuid(question.id()))).await;
+        println!("{:?}", res);
+        let hit = res.unwrap();
+        assert!(!hit.stale);
+        assert_eq!(hit.value.id(), first.value.id());
+        assert_eq!(statements, 0);
+        // The serial finds the same entry
+        let serial = EntityId::serial(question.serial().expect("serial should be assigned"));
+        assert_eq!(QueryCounter::count(question_dao.get_question(serial)).await.1, 0);
+
+        // Changes made through the decorator are read again, as are questions older than the ttl
+        let update = UpdateQuestion { title: Some(String::from("Updated title")), ..UpdateQuestion::default() };
+        question_dao.update_question(EntityId::uuid(question.id()), update).await.unwrap();
+        let updated = question_dao.get_question(EntityId::uuid(question.id())).await.unwrap();
+        assert_eq!(updated.title(), "Updated title");
+        clock.advance(Duration::seconds(31));
+        assert_eq!(QueryCounter::count(question_dao.get_question(EntityId::uuid(question.id()))).await.1, 1);
+    }
+
+    #[sqlx::test]
+    async fn get_question_cached_should_serve_stale_questions_within_the_cap(pool: PgPool) {
+        let clock = Arc::new(FixedClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()));
+        let inner_pool = single_connection_pool(&pool).await;
+        let question_dao = CachedQuestionDao::new(QuestionDaoImpl::new(inner_pool.clone()))
+            .with_clock(clock.clone())
+            .with_config(stale_config());
+        let question_id = question_dao.create_question(fixtures::question().build()).await.unwrap().id();
+        question_dao.get_question_cached(EntityId::uuid(question_id)).await.unwrap();
+        clock.advance(Duration::minutes(10));
+
+        let held = inner_pool.acquire().await.unwrap();
+        let res = question_dao.get_question_cached(EntityId::uuid(question_id)).await;
+        println!("{:?}", res);
+        let outcome = res.unwrap();
+        assert!(outcome.stale);
+        assert_eq!(outcome.value.id(), question_id);
+        // Only the method tagging the value serves it stale
+        let res = question_dao.get_question(EntityId::uuid(question_id)).await;
+        assert!(res.unwrap_err().is_transient());
+
+        // Once reading succeeds again the question is fresh
+        drop(held);
+        let outcome = question_dao.get_question_cached(EntityId::uuid(question_id)).await.unwrap();
+        assert!(!outcome.stale);
+    }
+
+    #[sqlx::test]
+    async fn get_question_cached_should_fail_beyond_the_cap(pool: PgPool) {
+        let clock = Arc::new(FixedClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()));
+        let inner_pool = single_connection_pool(&pool).await;
+        let question_dao = CachedQuestionDao::new(QuestionDaoImpl::new(inner_pool.clone()))
+            .with_clock(clock.clone())
+            .with_config(stale_config());
+        let question_id = question_dao.create_question(fixtures::question().build()).await.unwrap().id();
+        question_dao.get_question_cached(EntityId::uuid(question_id)).await.unwrap();
+        clock.advance(Duration::minutes(10) + Duration::seconds(1));
+
+        let _held = inner_pool.acquire().await.unwrap();
+        let res = question_dao.get_question_cached(EntityId::uuid(question_id)).await;
+        println!("{:?}", res);
+        let Err(e @ DbError::Unavailable { .. }) = res else { panic!("Error should be `Unavailable` variant") };
+        assert!(e.is_transient());
+    }
+
+    #[sqlx::test]
+    async fn get_question_cached_should_fail_with_an_empty_cache(pool: PgPool) {
+        let question_id = QuestionDaoImpl::new(pool.clone()).create_question(fixtures::question().build()).await.unwrap().id();
+        let failing = QuestionDaoImpl::new(pool).with_failing_source(|| sqlx::Error::PoolTimedOut);
+        let question_dao = CachedQuestionDao::new(failing).with_config(stale_config());
+        let res = question_dao.get_question_cached(EntityId::uuid(question_id)).await;
+        println!("{:?}", res);
+        let Err(DbError::Unavailable { reason: UnavailableReason::Transient, .. }) = res else { panic!("Error should be `Unavailable` variant") };
+    }
+
+    #[sqlx::test]
+    async fn get_question_cached_should_not_serve_stale_questions_unless_configured(pool: PgPool) {
+        let clock = Arc::new(FixedClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()));
+        let inner_pool = single_connection_pool(&pool).await;
+        let question_dao = CachedQuestionDao::new(QuestionDaoImpl::new(inner_pool.clone())).with_clock(clock.clone());
+        let question_id = question_dao.create_question(fixtures::question().build()).await.unwrap().id();
+        question_dao.get_question_cached(EntityId::uuid(question_id)).await.unwrap();
+        clock.advance(Duration::minutes(1));
+
+        let _held = inner_pool.acquire().await.unwrap();
+        let res = question_dao.get_question_cached(EntityId::uuid(question_id)).await;
+        println!("{:?}", res);
+        assert!(res.unwrap_err().is_transient());
+    }
+
+    #[sqlx::test]
+    async fn cache_should_evict_the_question_cached_the_longest_ago_once_full(pool: PgPool) {
+        let config = CacheConfig { capacity: 2, ..CacheConfig::default() };
+        let question_dao = CachedQuestionDao::new(QuestionDaoImpl::new(pool)).with_config(config);
+        let ids = fixtures::seed_questions(&question_dao, 3).await;
+        for id in &ids {
+            question_dao.get_question(EntityId::uuid(*id)).await.unwrap();
+        }
+        assert_eq!(question_dao.len(), 2);
+        // The first question made room for the last, which is still served from the cache
+        assert_eq!(QueryCounter::count(question_dao.get_question(EntityId::uuid(ids[2]))).await.1, 0);
+        assert_eq!(QueryCounter::count(question_dao.get_question(EntityId::uuid(ids[0]))).await.1, 1);
+        assert_eq!(question_dao.len(), 2);
+        // Reading the first question again evicted the second, not the last
+        assert_eq!(QueryCounter::count(question_dao.get_question(EntityId::uuid(ids[2]))).await.1, 0);
+        assert_eq!(QueryCounter::count(question_dao.get_question(EntityId::uuid(ids[1]))).await.1, 1);
+    }
+
+    #[sqlx::test]
+    async fn cache_should_drop_questions_too_old_to_be_served(pool: PgPool) {
+        let clock = Arc::new(FixedClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()));
+        let question_dao = CachedQuestionDao::new(QuestionDaoImpl::new(pool))
+            .with_clock(clock.clone())
+            .with_config(stale_config());
+        let ids = fixtures::seed_questions(&question_dao, 2).await;
+        question_dao.get_question(EntityId::uuid(ids[0])).await.unwrap();
+        // Past the ttl the question can still be served stale, so it is kept
+        clock.advance(Duration::minutes(5));
+        question_dao.get_question(EntityId::uuid(ids[1])).await.unwrap();
+        assert_eq!(question_dao.len(), 2);
+        // Past the cap on staleness it can never be served again, so caching another question drops it
+        clock.advance(Duration::minutes(6));
+        question_dao.get_question(EntityId::uuid(ids[1])).await.unwrap();
+        assert_eq!(question_dao.len(), 1);
+    }
+
+    /// Invalidates a question in the cache as soon as the wrapped data access object has read it, the way a change
+    /// made concurrently through the cache would.
+    #[derive(Default)]
+    struct InvalidateAfterRead {
+        cache: OnceLock<Weak<CachedQuestionDao<QuestionDaoImpl>>>,
+    }
+
+    impl QueryObserver for InvalidateAfterRead {
+        fn on_complete(&self, event: DaoEvent) {
+            if let (Some(cache), "get_question", Some(id)) = (self.cache.get().and_then(Weak::upgrade), event.method, event.id) {
+                cache.invalidate(&EntityId::new(id));
+            }
+        }
+    }
+
+    #[sqlx::test]
+    async fn cache_should_not_keep_a_read_raced_by_an_invalidation(pool: PgPool) {
+        let observer = Arc::new(InvalidateAfterRead::default());
+        let question_dao = Arc::new(CachedQuestionDao::new(QuestionDaoImpl::new(pool).with_observer(observer.clone())));
+        let _ = observer.cache.set(Arc::downgrade(&question_dao));
+        let question_id = question_dao.create_question(fixtures::question().build()).await.unwrap().id();
+
+        let res = question_dao.get_question(EntityId::uuid(question_id)).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap().id(), question_id);
+        // The question read may predate the change, so it was returned without being cached
+        assert!(question_dao.is_empty());
+        assert_eq!(QueryCounter::count(question_dao.get_question(EntityId::uuid(question_id))).await.1, 1);
+    }
+}
+
+mod category_tests {
+    use sqlx::types::Uuid;
+    use crate::fixtures;
+    use crate::models::{Category, DbError, EntityId, NewCategory};
+    use crate::persistence::prelude::PgPool;
+    use crate::persistence::{CategoryDao, CategoryDaoImpl, QuestionDao, QuestionDaoImpl};
+
+    async fn create(category_dao: &CategoryDaoImpl, name: &str, parent: Option<&Category>) -> Category {
+        let new_category = NewCategory { name: String::from(name), parent_id: parent.map(|p| p.id().to_string()) };
+        category_dao.create_category(new_category).await.expect("category should be created successfully")
+    }
+
+    #[sqlx::test]
+    async fn get_category_tree_should_nest_children_by_name(pool: PgPool) {
+        let category_dao = CategoryDaoImpl::new(pool);
+        let programming = create(&category_dao, "Programming", None).await;
+        let rust = create(&category_dao, "Rust", Some(&programming)).await;
+        let async_rust = create(&category_dao, "Async", Some(&rust)).await;
+        let go = create(&category_dao, "Go", Some(&programming)).await;
+        let cooking = create(&category_dao, "Cooking", None).await;
+
+        let res = category_dao.get_category_tree().await;
+        println!("{:?}", res);
+        let tree = res.expect("tree should be returned");
+        let roots: Vec<Uuid> = tree.iter().map(|node| node.category.id()).collect();
+        assert_eq!(roots, vec![cooking.id(), programming.id()]);
+        let children: Vec<Uuid> = tree[1].children.iter().map(|node| node.category.id()).collect();
+        assert_eq!(children, vec![go.id(), rust.id()]);
+        assert_eq!(tree[1].children[1].children[0].category, async_rust);
+        assert!(tree[0].children.is_empty());
+    }
+
+    #[sqlx::test]
+    async fn create_category_should_fail_with_conflict_for_duplicate_sibling(pool: PgPool) {
+        let category_dao = CategoryDaoImpl::new(pool);
+        let programming = create(&category_dao, "Programming", None).await;
+        create(&category_dao, "Rust", Some(&programming)).await;
+        // The same name is allowed under a different parent
+        create(&category_dao, "Rust", None).await;
+        let res = category_dao.create_category(NewCategory { name: String::from("Rust"), parent_id: Some(programming.id().to_string()) }).await;
+        println!("{:?}", res);
+        let Err(DbError::Conflict { .. }) = res else { panic!("Error should be `Conflict` variant") };
+        let res = category_dao.create_category(NewCategory { name: String::from("Orphan"), parent_id: Some(Uuid::new_v4().to_string()) }).await;
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+    }
+
+    #[sqlx::test]
+    async fn get_questions_in_category_should_optionally_include_descendants(pool: PgPool) {
+        let category_dao = CategoryDaoImpl::new(pool.clone());
+        let question_ids = fixtures::seed_questions(&QuestionDaoImpl::new(pool), 3).await;
+        let programming = create(&category_dao, "Programming", None).await;
+        let rust = create(&category_dao, "Rust", Some(&programming)).await;
+        let async_rust = create(&category_dao, "Async", Some(&rust)).await;
+        category_dao.assign_category(EntityId::uuid(question_ids[0]), Some(EntityId::uuid(programming.id()))).await.expect("category should be assigned");
+        category_dao.assign_category(EntityId::uuid(question_ids[1]), Some(EntityId::uuid(async_rust.id()))).await.expect("category should be assigned");
+
+        let res = category_dao.get_questions_in_category(EntityId::uuid(programming.id()), false).await;
+        println!("{:?}", res);
+        let ids: Vec<Uuid> = res.unwrap().iter().map(|q| q.id()).collect();
+        assert_eq!(ids, vec![question_ids[0]]);
+        let res = category_dao.get_questions_in_category(EntityId::uuid(programming.id()), true).await;
+        println!("{:?}", res);
+        let mut ids: Vec<Uuid> = res.unwrap().iter().map(|q| q.id()).collect();
+        ids.sort();
+        let mut expected = vec![question_ids[0], question_ids[1]];
+        expected.sort();
+        assert_eq!(ids, expected);
+        let res = category_dao.get_questions_in_category(EntityId::uuid(rust.id()), false).await;
+        assert!(res.is_ok_and(|questions| questions.is_empty()));
+        let res = category_dao.get_questions_in_category(EntityId::uuid(Uuid::new_v4()), true).await;
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+    }
+
+    #[sqlx::test]
+    async fn set_category_parent_should_reject_cycles(pool: PgPool) {
+        let category_dao = CategoryDaoImpl::new(pool);
+        let programming = create(&category_dao, "Programming", None).await;
+        let rust = create(&category_dao, "Rust", Some(&programming)).await;
+        let async_rust = create(&category_dao, "Async", Some(&rust)).await;
+
+        for parent in [&programming, &rust, &async_rust] {
+            let res = category_dao.set_category_parent(EntityId::uuid(programming.id()), Some(EntityId::uuid(parent.id()))).await;
+            println!("{:?}", res);
+            let Err(DbError::Conflict { .. }) = res else { panic!("Error should be `Conflict` variant") };
+        }
+        // Moving a subtree elsewhere is allowed
+        let res = category_dao.set_category_parent(EntityId::uuid(async_rust.id()), None).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap().parent_id(), None);
+        let res = category_dao.set_category_parent(EntityId::uuid(programming.id()), Some(EntityId::uuid(async_rust.id()))).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap().parent_id(), Some(async_rust.id()));
+    }
+
+    #[sqlx::test]
+    async fn assign_category_should_replace_previous_category(pool: PgPool) {
+        let category_dao = CategoryDaoImpl::new(pool.clone());
+        let question_id = fixtures::seed_questions(&QuestionDaoImpl::new(pool), 1).await[0];
+        let rust = create(&category_dao, "Rust", None).await;
+        let go = create(&category_dao, "Go", None).await;
+
+        let question = category_dao.assign_category(EntityId::uuid(question_id), Some(EntityId::uuid(rust.id()))).await.expect("category should be assigned");
+        assert_eq!(question.category_id(), Some(rust.id()));
+        let res = category_dao.assign_category(EntityId::uuid(question_id), Some(EntityId::uuid(go.id()))).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap().category_id(), Some(go.id()));
+        let in_rust = category_dao.get_questions_in_category(EntityId::uuid(rust.id()), true).await.expect("questions should be returned");
+        assert!(in_rust.is_empty());
+        let res = category_dao.assign_category(EntityId::uuid(question_id), None).await;
+        assert_eq!(res.unwrap().category_id(), None);
+        let res = category_dao.assign_category(EntityId::uuid(question_id), Some(EntityId::uuid(Uuid::new_v4()))).await;
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+    }
+
+    #[sqlx::test]
+    async fn get_questions_in_category_should_list_pinned_questions_first(pool: PgPool) {
+        let category_dao = CategoryDaoImpl::new(pool.clone());
+        let question_dao = QuestionDaoImpl::new(pool);
+        let question_ids = fixtures::seed_questions(&question_dao, 3).await;
+        let rust = create(&category_dao, "Rust", None).await;
+        for question_id in &question_ids[..2] {
+            category_dao.assign_category(EntityId::uuid(*question_id), Some(EntityId::uuid(rust.id()))).await.expect("category should be assigned");
+        }
+        // The pinned question outside the category is still filtered out
+        for question_id in &question_ids[1..] {
+            question_dao.pin_question(EntityId::uuid(*question_id)).await.expect("question should be pinned");
+        }
+        let res = category_dao.get_questions_in_category(EntityId::uuid(rust.id()), false).await;
+        println!("{:?}", res);
+        let ids: Vec<Uuid> = res.unwrap().iter().map(|q| q.id()).collect();
+        assert_eq!(ids, vec![question_ids[1], question_ids[0]]);
+    }
+}
+
+mod scoped_tests {
+    use crate::fixtures;
+    use crate::models::{ConflictReason, DbError, EntityId, NewAnswer};
+    use crate::persistence::prelude::PgPool;
+    use crate::persistence::scoped::ScopedDao;
+    use crate::persistence::{AdminDao, AdminDaoImpl, AnswerDao, AnswerDaoImpl, QuestionDao, QuestionDaoImpl};
+
+    /// Creates and likes a question with an answer in `scope`, then fails to delete the question because it is
+    /// locked. Returns the id of the question.
+    async fn create_like_and_fail_delete(scope: &ScopedDao) -> EntityId {
+        let question = scope.create_question(fixtures::question().build()).await.expect("question should be created successfully");
+        let question_id = EntityId::uuid(question.id());
+        scope.increment_question_likes(question_id.clone()).await.expect("question should be liked successfully");
+        let new_answer = NewAnswer { question_id: question.id().to_string(), answer: String::from("Test answer"), author_id: None, content_type: None, author_token: None, parent_answer_id: None };
+        scope.create_answer(new_answer).await.expect("answer should be created successfully");
+        scope.lock_question(question_id.clone()).await.expect("question should be locked successfully");
+        let res = scope.delete_question(question_id.clone(), false).await;
+        println!("{:?}", res);
+        let Err(DbError::Conflict { reason: ConflictReason::Locked(_), .. }) = res else { panic!("Error should be `Locked` reason") };
+        question_id
+    }
+
+    #[sqlx::test]
+    async fn scoped_dao_should_roll_back_when_dropped_without_commit(pool: PgPool) {
+        let scope = ScopedDao::begin(&pool).await.expect("scope should begin successfully");
+        let question_id = create_like_and_fail_delete(&scope).await;
+        // The scope sees its own changes, while they are invisible outside of it
+        assert_eq!(scope.get_question(question_id.clone()).await.expect("question should be found").likes(), 1);
+        let res = QuestionDaoImpl::new(pool.clone()).get_question(question_id.clone()).await;
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+        drop(scope);
+
+        assert!(QuestionDaoImpl::new(pool.clone()).get_questions().await.expect("questions should be read").is_empty());
+        assert!(AnswerDaoImpl::new(pool.clone()).get_all_answers().await.expect("answers should be read").is_empty());
+        let like_events: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM like_events")
+            .fetch_one(&pool)
+            .await
+            .expect("like events should be counted");
+        assert_eq!(like_events, 0);
+    }
+
+    #[sqlx::test]
+    async fn scoped_dao_should_persist_everything_on_commit(pool: PgPool) {
+        let scope = ScopedDao::begin(&pool).await.expect("scope should begin successfully");
+        let question_id = create_like_and_fail_delete(&scope).await;
+        // The failed delete only rolled back its own changes
+        assert_eq!(scope.get_answers(question_id.clone()).await.expect("answers should be read").len(), 1);
+        scope.commit().await.expect("scope should commit successfully");
+
+        let question = QuestionDaoImpl::new(pool.clone()).get_question(question_id.clone()).await.expect("question should be found");
+        assert_eq!(question.likes(), 1);
+        assert!(question.is_locked());
+        assert_eq!(AnswerDaoImpl::new(pool.clone()).get_answers(question_id.clone()).await.expect("answers should be read").len(), 1);
+        let since = question.created_at() - chrono::Duration::seconds(1);
+        let like_events = AdminDaoImpl::new(pool.clone()).get_like_events(question_id, since).await.expect("like events should be read");
+        assert_eq!(like_events.len(), 1);
+    }
+
+    #[sqlx::test]
+    async fn scoped_dao_should_discard_changes_on_rollback(pool: PgPool) {
+        let scope = ScopedDao::begin(&pool).await.expect("scope should begin successfully");
+        scope.create_question(fixtures::question().build()).await.expect("question should be created successfully");
+        scope.rollback().await.expect("scope should roll back successfully");
+        assert!(QuestionDaoImpl::new(pool.clone()).get_questions().await.expect("questions should be read").is_empty());
+    }
+}
+
+mod integrity_tests {
+    use crate::fixtures;
+    use crate::models::{DeletePolicy, EntityId};
+    use crate::persistence::integrity::{check_integrity, repair, IntegrityReport};
+    use crate::persistence::prelude::PgPool;
+    use crate::persistence::{AnswerDao, AnswerDaoImpl, QuestionDao, QuestionDaoImpl};
+
+    /// Corrupts the database the way manual changes can, returning the report expected for it: the answers of a
+    /// question deleted without the foreign key, a question pinning and accepting an answer to another question,
+    /// negative likes, and a question updated before it was created.
+    async fn corrupt(pool: &PgPool) -> IntegrityReport {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        let (deleted_id, mut dangling) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 2).await;
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 1).await;
+        let (pointing_id, _) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 1).await;
+        for statement in [
+            "ALTER TABLE answers DROP CONSTRAINT answers_question_id_fkey",
+            "DELETE FROM questions WHERE id = $1",
+            "UPDATE questions SET likes = -2, updated_at = created_at - interval '1 day' WHERE id = $2",
+            "UPDATE answers SET likes = -1 WHERE id = $3",
+            "UPDATE questions SET pinned_answer_id = $3, accepted_answer_id = $3 WHERE id = $4",
+        ] {
+            sqlx::query(statement)
+                .bind(deleted_id)
+                .bind(question_id)
+                .bind(answer_ids[0])
+                .bind(pointing_id)
+                .execute(pool)
+                .await
+                .expect("corruption should be injected");
+        }
+        dangling.sort();
+        IntegrityReport {
+            dangling_answers: dangling,
+            foreign_answer_pointers: vec![pointing_id],
+            negative_question_likes: vec![question_id],
+            negative_answer_likes: vec![answer_ids[0]],
+            updated_before_created: vec![question_id],
+        }
+    }
+
+    #[sqlx::test]
+    async fn check_integrity_should_report_clean_database(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        fixtures::seed_question_with_answers(&question_dao, &answer_dao, 2).await;
+        let res = check_integrity(&pool).await;
+        println!("{:?}", res);
+        assert!(res.unwrap().is_clean());
+    }
+
+    #[sqlx::test]
+    async fn check_integrity_should_detect_each_corruption(pool: PgPool) {
+        let expected = corrupt(&pool).await;
+        let res = check_integrity(&pool).await;
+        println!("{:?}", res);
+        let report = res.unwrap();
+        assert_eq!(report, expected);
+        assert!(!report.is_clean());
+        assert_eq!(
+            report.to_string(),
+            "Found 2 dangling answers, 1 questions pinning or accepting answers to other questions, 1 questions and 1 \
+            answers with negative likes, and 1 questions updated before they were created"
+        );
+    }
+
+    #[sqlx::test]
+    async fn repair_should_fix_corruptions_and_orphan_dangling_answers(pool: PgPool) {
+        let report = corrupt(&pool).await;
+        let res = repair(&pool, &report, DeletePolicy::Orphan).await;
+        println!("{:?}", res);
+        let repaired = res.unwrap();
+        assert_eq!((repaired.answers_orphaned, repaired.pointers_cleared, repaired.likes_reset, repaired.timestamps_fixed), (2, 1, 2, 1));
+        assert!(check_integrity(&pool).await.unwrap().is_clean());
+        let pointing = QuestionDaoImpl::new(pool.clone()).get_question(EntityId::uuid(report.foreign_answer_pointers[0])).await.unwrap();
+        assert_eq!((pointing.pinned_answer_id(), pointing.accepted_answer_id()), (None, None));
+
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        let answer = answer_dao.get_answer(EntityId::uuid(report.dangling_answers[0])).await.expect("orphaned answer should be kept");
+        assert_eq!(answer.question_id(), None);
+        // The resets are recorded in the like audit log, so the log still sums to the counters
+        let deltas: Vec<i64> = sqlx::query_scalar("SELECT delta::bigint FROM like_events ORDER BY entity_type DESC")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(deltas, vec![2, 1]);
+        // A second repair finds nothing left to fix
+        let repaired = repair(&pool, &report, DeletePolicy::Orphan).await.unwrap();
+        assert_eq!((repaired.answers_orphaned, repaired.pointers_cleared, repaired.likes_reset, repaired.timestamps_fixed), (0, 0, 0, 0));
+    }
+
+    #[sqlx::test]
+    async fn repair_should_delete_or_leave_dangling_answers_by_policy(pool: PgPool) {
+        let report = corrupt(&pool).await;
+        let repaired = repair(&pool, &report, DeletePolicy::Restrict).await.unwrap();
+        println!("{}", repaired);
+        assert_eq!(repaired.answers_left, report.dangling_answers);
+        assert_eq!(check_integrity(&pool).await.unwrap().dangling_answers, report.dangling_answers);
+
+        let repaired = repair(&pool, &report, DeletePolicy::Cascade).await.unwrap();
+        assert_eq!(repaired.answers_deleted, 2);
+        assert!(check_integrity(&pool).await.unwrap().is_clean());
+        let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM answers WHERE id = ANY($1)")
+            .bind(&report.dangling_answers)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(remaining, 0);
+    }
+}
+
+mod links_tests {
+    use std::collections::HashSet;
+    use std::sync::Arc;
+    use chrono::{Duration, Utc};
+    use crate::clock::SteppingClock;
+    use crate::fixtures;
+    use crate::models::links::{CursorSigner, PageLinks};
+    use crate::models::{ActivityCursor, ActivityItem, DbError, Page, PageRequest, QuestionFields};
+    use crate::persistence::prelude::PgPool;
+    use crate::persistence::{QuestionDao, QuestionDaoImpl};
+
+    /// The value of a query parameter of a link.
+    fn param<'a>(link: &'a str, name: &str) -> &'a str {
+        let (_, query) = link.split_once('?').expect("link should have a query");
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix(name).and_then(|rest| rest.strip_prefix('=')))
+            .unwrap_or_else(|| panic!("link `{}` should have `{}`", link, name))
+    }
+
+    #[sqlx::test]
+    async fn offset_links_should_lead_through_every_page(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool);
+        for _ in 0..5 {
+            question_dao.create_question(fixtures::question().build()).await.unwrap();
+        }
+        let total = question_dao.get_questions().await.unwrap().len() as u64;
+
+        let mut target = String::from("/questions?fields=title&offset=0&limit=2");
+        let mut ids = Vec::new();
+        let mut pages = 0;
+        loop {
+            let request = PageRequest { offset: param(&target, "offset").parse().unwrap(), limit: param(&target, "limit").parse().unwrap() };
+            let page = question_dao.get_questions_projected(QuestionFields::TITLE, request).await.unwrap();
+            ids.extend(page.items.iter().map(|question| question.id()));
+            pages += 1;
+            let links = PageLinks::offset(&target, request, &page, total);
+            println!("{:?}", links);
+            assert_eq!(links.total_count_header().as_deref(), Some("5"));
+            assert_eq!(links.prev.is_some(), pages > 1);
+            assert_eq!(param(links.prev.as_deref().unwrap_or("?fields=title"), "fields"), "title");
+            let Some(next) = links.next else { break };
+            target = next;
+        }
+        assert_eq!(pages, 3);
+        assert_eq!(ids.iter().collect::<HashSet<_>>().len(), 5);
+    }
+
+    #[sqlx::test]
+    async fn keyset_links_should_lead_through_every_page_and_reject_tampered_cursors(pool: PgPool) {
+        let clock = Arc::new(SteppingClock::new(Utc::now(), Duration::seconds(1)));
+        let question_dao = QuestionDaoImpl::new(pool).with_clock(clock);
+        for _ in 0..6 {
+            question_dao.create_question(fixtures::question().build()).await.unwrap();
+        }
+        let signer = CursorSigner::new("secret");
+
+        let mut target = String::from("/feed?limit=2");
+        let mut ids = HashSet::new();
+        let mut pages = 0;
+        loop {
+            let before = target.contains("cursor=").then(|| signer.verify::<ActivityCursor>(param(&target, "cursor")).unwrap());
+            // One more item than requested is read to tell whether more follow the page
+            let mut items = question_dao.get_activity_feed(3, before).await.unwrap();
+            let has_more = items.len() > 2;
+            items.truncate(2);
+            ids.extend(items.iter().map(|item| item.cursor().id));
+            pages += 1;
+            let links = PageLinks::keyset(&target, &Page { items, has_more }, &signer, ActivityItem::cursor);
+            println!("{:?}", links);
+            let Some(next) = links.next else {
+                assert_eq!(links.link_header(), None);
+                break;
+            };
+            assert_eq!(param(&next, "limit"), "2");
+            // A cursor moved to another position is rejected before it reaches the query
+            let cursor: ActivityCursor = signer.verify(param(&next, "cursor")).unwrap();
+            let tampered = signer.sign(&ActivityCursor { created_at: Utc::now(), ..cursor });
+            let forged = format!("{}.{}", tampered.split_once('.').unwrap().0, param(&next, "cursor").split_once('.').unwrap().1);
+            let res = signer.verify::<ActivityCursor>(&forged);
+            println!("{:?}", res);
+            let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+            target = next;
+        }
+        assert_eq!(pages, 3);
+        assert_eq!(ids.len(), 6);
+    }
+}
+
+mod lock_tests {
+    use std::sync::Arc;
+    use chrono::{Duration, TimeZone, Utc};
+    use sqlx::types::Uuid;
+    use crate::clock::FixedClock;
+    use crate::fixtures;
+    use crate::models::{CreateOutcome, DbError};
+    use crate::persistence::lock::{with_advisory_lock, LockOutcome, MaintenanceLock};
+    use crate::persistence::prelude::PgPool;
+    use crate::persistence::{AdminDaoImpl, QuestionDao, QuestionDaoImpl};
+
+    /// The number of advisory locks held in the test's database.
+    async fn advisory_locks(pool: &PgPool) -> i64 {
+        sqlx::query_scalar(
+            "SELECT COUNT(*) FROM pg_locks WHERE locktype = 'advisory' AND granted \
+            AND database = (SELECT oid FROM pg_database WHERE datname = current_database())"
+        )
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    /// The number of sessions in the test's database waiting on a lock.
+    async fn lock_waits(pool: &PgPool) -> i64 {
+        sqlx::query_scalar(
+            "SELECT COUNT(*) FROM pg_stat_activity WHERE wait_event_type = 'Lock' AND datname = current_database()"
+        )
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn wait_for_lock_waits(pool: &PgPool, count: i64) {
+        while lock_waits(pool).await < count {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[sqlx::test]
+    async fn create_question_idempotent_should_retry_when_the_key_is_purged_after_a_conflict(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let original_id = question_dao.create_question(fixtures::question().build()).await.unwrap().id();
+        // Claim the key without committing, so the request stalls on its conflict
+        let mut holder = pool.begin().await.unwrap();
+        sqlx::query("INSERT INTO idempotency_keys (key, entity_id, request_hash) VALUES ('test-key', $1, '')")
+            .bind(original_id)
+            .execute(&mut *holder)
+            .await
+            .unwrap();
+        let created = tokio::spawn({
+            let question_dao = QuestionDaoImpl::new(pool.clone());
+            async move { question_dao.create_question_idempotent(fixtures::question().build(), "test-key").await }
+        });
+        wait_for_lock_waits(&pool, 1).await;
+        // Queue a purge that takes the table once the request has rolled back, before it reads the key
+        let purge = tokio::spawn({
+            let pool = pool.clone();
+            async move {
+                let mut tx = pool.begin().await.unwrap();
+                sqlx::query("LOCK TABLE idempotency_keys IN ACCESS EXCLUSIVE MODE").execute(&mut *tx).await.unwrap();
+                sqlx::query("DELETE FROM idempotency_keys").execute(&mut *tx).await.unwrap();
+                tx.commit().await.unwrap();
+            }
+        });
+        wait_for_lock_waits(&pool, 2).await;
+        holder.commit().await.unwrap();
+        purge.await.unwrap();
+
+        let res = created.await.unwrap();
+        println!("{:?}", res);
+        let Ok(CreateOutcome::Created(id)) = res else { panic!("Outcome should be `Created` variant") };
+        assert_ne!(id, original_id);
+        let key_id: Uuid = sqlx::query_scalar("SELECT entity_id FROM idempotency_keys WHERE key = 'test-key'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(key_id, id);
+    }
+
+    #[sqlx::test]
+    async fn concurrent_guarded_purges_should_run_once(pool: PgPool) {
+        let clock = Arc::new(FixedClock::new(Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap()));
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_clock(clock.clone());
+        for key in ["first", "second"] {
+            question_dao.create_question_idempotent(fixtures::question().build(), key).await.expect("question should be created successfully");
+        }
+        clock.advance(Duration::days(2));
+        // Hold the expired keys, so the first purge stalls while holding the maintenance lock
+        let mut blocker = pool.begin().await.unwrap();
+        sqlx::query("SELECT * FROM idempotency_keys FOR UPDATE").execute(&mut *blocker).await.unwrap();
+        let first = tokio::spawn({
+            let question_dao = QuestionDaoImpl::new(pool.clone()).with_clock(clock.clone());
+            async move { question_dao.purge_idempotency_keys_guarded(Duration::days(1)).await }
+        });
+        while advisory_locks(&pool).await == 0 {
+            tokio::task::yield_now().await;
+        }
+        let second = question_dao.purge_idempotency_keys_guarded(Duration::days(1)).await;
+        println!("{:?}", second);
+        assert!(second.unwrap().is_skipped());
+        blocker.commit().await.unwrap();
+        let first = first.await.unwrap();
+        println!("{:?}", first);
+        assert_eq!(first.unwrap(), LockOutcome::Ran(2));
+        // Once released, the next purge runs
+        assert_eq!(question_dao.purge_idempotency_keys_guarded(Duration::days(1)).await.unwrap(), LockOutcome::Ran(0));
+        assert_eq!(advisory_locks(&pool).await, 0);
+    }
+
+    #[sqlx::test]
+    async fn with_advisory_lock_should_release_the_lock_after_an_error(pool: PgPool) {
+        let res = with_advisory_lock(&pool, MaintenanceLock::Reindex, || async {
+            Err::<(), _>(DbError::validation(String::from("maintenance failed")))
+        }).await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        assert_eq!(advisory_locks(&pool).await, 0);
+        let res = AdminDaoImpl::new(pool).reindex_guarded().await;
+        println!("{:?}", res);
+        assert!(res.unwrap().ran().is_some_and(|tables| tables.contains(&"questions")));
+    }
+
+    #[sqlx::test]
+    async fn with_advisory_lock_should_skip_only_the_same_operation(pool: PgPool) {
+        let admin_dao = AdminDaoImpl::new(pool.clone());
+        let res = with_advisory_lock(&pool, MaintenanceLock::RecomputeContentStats, || async {
+            // The same operation is skipped, while others still run
+            let nested = admin_dao.recompute_content_stats_guarded().await?;
+            let other = admin_dao.reindex_guarded().await?;
+            Ok((nested.is_skipped(), other.is_skipped()))
+        }).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), LockOutcome::Ran((true, false)));
+    }
+}
+
+mod tag_tests {
+    use std::sync::Arc;
+    use chrono::{Duration, TimeZone, Utc};
+    use crate::clock::{Clock, FixedClock};
+    use crate::fixtures;
+    use crate::models::{DbError, EntityId, RetagReport, TagAcceptance, TagStats, TagSuggestion};
+    use crate::persistence::prelude::PgPool;
+    use crate::persistence::{AnswerDaoImpl, QuestionDao, QuestionDaoImpl, TagDao, TagDaoImpl};
+
+    fn suggestion(name: &str, usage_count: i64) -> TagSuggestion {
+        TagSuggestion { name: String::from(name), usage_count }
+    }
+
+    #[sqlx::test]
+    async fn suggest_tags_should_match_prefixes_regardless_of_case(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let tag_dao = TagDaoImpl::new(pool);
+        let question_id = fixtures::seed_questions(&question_dao, 1).await[0];
+        for name in ["Rust", "rust-async", "Ruby", "python", "100%", "100_percent", "1000"] {
+            tag_dao.tag_question(EntityId::uuid(question_id), name).await.expect("question should be tagged successfully");
+        }
+        let res = tag_dao.suggest_tags("RU", 10).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), [suggestion("Ruby", 1), suggestion("Rust", 1), suggestion("rust-async", 1)]);
+        assert_eq!(tag_dao.suggest_tags(" rust", 10).await.unwrap(), [suggestion("Rust", 1), suggestion("rust-async", 1)]);
+        assert_eq!(tag_dao.suggest_tags("ru", 1).await.unwrap(), [suggestion("Ruby", 1)]);
+        // Wildcards are matched literally
+        assert_eq!(tag_dao.suggest_tags("100%", 10).await.unwrap(), [suggestion("100%", 1)]);
+        assert_eq!(tag_dao.suggest_tags("100_", 10).await.unwrap(), [suggestion("100_percent", 1)]);
+        assert!(tag_dao.suggest_tags("%", 10).await.unwrap().is_empty());
+        assert!(tag_dao.suggest_tags("go", 10).await.unwrap().is_empty());
+    }
+
+    #[sqlx::test]
+    async fn suggest_tags_should_order_by_popularity(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let tag_dao = TagDaoImpl::new(pool);
+        let question_ids = fixtures::seed_questions(&question_dao, 3).await;
+        for (question_id, names) in question_ids.iter().zip([&["sql", "sqlx", "sqlite"][..], &["sqlx", "sqlite"], &["sqlx"]]) {
+            for name in names {
+                tag_dao.tag_question(EntityId::uuid(*question_id), name).await.expect("question should be tagged successfully");
+            }
+        }
+        let res = tag_dao.suggest_tags("sql", 10).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), [suggestion("sqlx", 3), suggestion("sqlite", 2), suggestion("sql", 1)]);
+    }
+
+    #[sqlx::test]
+    async fn tag_stats_should_follow_tagging_and_untagging(pool: PgPool) {
+        let clock = Arc::new(FixedClock::new(Utc.with_ymd_and_hms(2024, 1, 15, 12, 0, 0).unwrap()));
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let tag_dao = TagDaoImpl::new(pool).with_clock(clock.clone());
+        let question_ids = fixtures::seed_questions(&question_dao, 2).await;
+        let first = tag_dao.tag_question(EntityId::uuid(question_ids[0]), "Postgres").await.unwrap();
+        clock.advance(Duration::hours(1));
+        // Tags match regardless of case, and tagging twice counts once
+        let second = tag_dao.tag_question(EntityId::uuid(question_ids[1]), " postgres ").await.unwrap();
+        assert_eq!((second.id(), second.name()), (first.id(), "Postgres"));
+        tag_dao.tag_question(EntityId::uuid(question_ids[1]), "Postgres").await.unwrap();
+        let res = tag_dao.get_tag_stats().await;
+        println!("{:?}", res);
+        let last_used_at = Some(clock.now());
+        assert_eq!(res.unwrap(), [TagStats { name: String::from("Postgres"), usage_count: 2, last_used_at }]);
+
+        let res = tag_dao.untag_question(EntityId::uuid(question_ids[1]), "POSTGRES").await;
+        println!("{:?}", res);
+        assert!(res.unwrap());
+        assert!(!tag_dao.untag_question(EntityId::uuid(question_ids[1]), "postgres").await.unwrap());
+        let last_used_at = Some(clock.now() - Duration::hours(1));
+        assert_eq!(tag_dao.get_tag_stats().await.unwrap(), [TagStats { name: String::from("Postgres"), usage_count: 1, last_used_at }]);
+        // Deleting a question removes its tags from the counts
+        question_dao.delete_question(EntityId::uuid(question_ids[0]), true).await.expect("question should be deleted successfully");
+        assert_eq!(tag_dao.get_tag_stats().await.unwrap(), [TagStats { name: String::from("Postgres"), usage_count: 0, last_used_at: None }]);
+        assert_eq!(tag_dao.suggest_tags("post", 10).await.unwrap(), [suggestion("Postgres", 0)]);
+    }
+
+    #[sqlx::test]
+    async fn get_acceptance_rate_by_tag_should_rank_tags_above_the_threshold(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        let tag_dao = TagDaoImpl::new(pool);
+        // The tags of each question, and whether its author accepted an answer
+        let questions = [
+            (&["rust", "sql"][..], true),
+            (&["rust"][..], true),
+            (&["rust"][..], false),
+            (&["rust", "sql"][..], false),
+            (&["sql"][..], false),
+            (&["sparse"][..], true),
+            (&[][..], true),
+        ];
+        for (tags, accepted) in questions {
+            let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 1).await;
+            for tag in tags {
+                tag_dao.tag_question(EntityId::uuid(question_id), tag).await.expect("question should be tagged successfully");
+            }
+            if accepted {
+                question_dao.accept_answer(EntityId::uuid(question_id), EntityId::uuid(answer_ids[0])).await.expect("answer should be accepted successfully");
+            } else {
+                // Pinning is not acceptance, so a pinned summary does not raise the rate
+                question_dao.pin_answer(EntityId::uuid(question_id), EntityId::uuid(answer_ids[0])).await.expect("answer should be pinned successfully");
+            }
+        }
+        let acceptance = |name: &str, question_count, accepted_count, acceptance_rate| TagAcceptance {
+            name: String::from(name), question_count, accepted_count, acceptance_rate,
+        };
+
+        let res = tag_dao.get_acceptance_rate_by_tag(2).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), [acceptance("rust", 4, 2, 0.5), acceptance("sql", 3, 1, 1.0 / 3.0)]);
+        // Sparse tags are only included below their question count
+        assert_eq!(
+            tag_dao.get_acceptance_rate_by_tag(1).await.unwrap(),
+            [acceptance("sparse", 1, 1, 1.0), acceptance("rust", 4, 2, 0.5), acceptance("sql", 3, 1, 1.0 / 3.0)]
+        );
+        assert!(tag_dao.get_acceptance_rate_by_tag(5).await.unwrap().is_empty());
+    }
+
+    #[sqlx::test]
+    async fn suggest_tags_should_reject_blank_prefixes(pool: PgPool) {
+        let tag_dao = TagDaoImpl::new(pool);
+        for prefix in ["", "   "] {
+            let res = tag_dao.suggest_tags(prefix, 10).await;
+            println!("{:?}", res);
+            let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        }
+        let Err(DbError::Validation { .. }) = tag_dao.suggest_tags("rust", 0).await else { panic!("Error should be `Validation` variant") };
+    }
+
+    #[sqlx::test]
+    async fn tag_question_should_validate_the_name_and_question(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let tag_dao = TagDaoImpl::new(pool);
+        let question_id = fixtures::seed_questions(&question_dao, 1).await[0];
+        for name in [String::from(" "), "x".repeat(51)] {
+            let res = tag_dao.tag_question(EntityId::uuid(question_id), &name).await;
+            println!("{:?}", res);
+            let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        }
+        let res = tag_dao.tag_question(EntityId::uuid(sqlx::types::Uuid::new_v4()), "rust").await;
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+        assert!(tag_dao.get_tag_stats().await.unwrap().is_empty());
+    }
+
+    #[sqlx::test]
+    async fn rename_tag_should_rename_the_tag_on_every_question(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let tag_dao = TagDaoImpl::new(pool);
+        for question_id in fixtures::seed_questions(&question_dao, 2).await {
+            tag_dao.tag_question(EntityId::uuid(question_id), "rust-lang").await.unwrap();
+        }
+        let res = tag_dao.rename_tag("Rust-Lang", " rust ").await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), RetagReport { questions_affected: 2, already_tagged: 0 });
+        let stats = tag_dao.get_tag_stats().await.unwrap();
+        assert_eq!(stats.iter().map(|stats| (stats.name.as_str(), stats.usage_count)).collect::<Vec<_>>(), [("rust", 2)]);
+    }
+
+    #[sqlx::test]
+    async fn rename_tag_should_merge_into_an_existing_tag(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let tag_dao = TagDaoImpl::new(pool);
+        let ids = fixtures::seed_questions(&question_dao, 3).await;
+        for (question_id, names) in ids.iter().zip([&["rust-lang", "Rust"][..], &["rust-lang"], &["Rust"]]) {
+            for name in names {
+                tag_dao.tag_question(EntityId::uuid(*question_id), name).await.unwrap();
+            }
+        }
+        let res = tag_dao.rename_tag("rust-lang", "rust").await;
+        println!("{:?}", res);
+        let report = res.unwrap();
+        assert_eq!(report, RetagReport { questions_affected: 2, already_tagged: 1 });
+        assert_eq!(report.to_string(), "Retagged 2 questions, 1 of which already had the new tag");
+        let stats = tag_dao.get_tag_stats().await.unwrap();
+        assert_eq!(stats.iter().map(|stats| (stats.name.as_str(), stats.usage_count)).collect::<Vec<_>>(), [("Rust", 3)]);
+        assert!(tag_dao.suggest_tags("rust-", 10).await.unwrap().is_empty());
+    }
+
+    #[sqlx::test]
+    async fn rename_tag_should_report_missing_tags(pool: PgPool) {
+        let tag_dao = TagDaoImpl::new(pool);
+        let res = tag_dao.rename_tag("golang", "go").await;
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+        let Err(DbError::Validation { .. }) = tag_dao.rename_tag("golang", " ").await else { panic!("Error should be `Validation` variant") };
+    }
+
+    #[sqlx::test]
+    async fn rename_tag_should_ignore_case_only_renames(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let tag_dao = TagDaoImpl::new(pool);
+        let question_id = fixtures::seed_questions(&question_dao, 1).await[0];
+        tag_dao.tag_question(EntityId::uuid(question_id), "Rust").await.unwrap();
+        let res = tag_dao.rename_tag("rust", "RUST").await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap(), RetagReport::default());
+        assert_eq!(tag_dao.suggest_tags("r", 10).await.unwrap(), [suggestion("Rust", 1)]);
+    }
+}
+
+mod like_tests {
+    use std::sync::Arc;
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use sqlx::types::Uuid;
+    use crate::clock::FixedClock;
+    use crate::fixtures;
+    use crate::models::{EntityId, LikableEntity, LikeAction, LikeOp, LikeOutcome};
+    use crate::persistence::prelude::PgPool;
+    use crate::persistence::{AnswerDao, AnswerDaoImpl, LikeDao, LikeDaoImpl, QuestionDao, QuestionDaoImpl};
+
+    fn op(entity: LikableEntity, id: EntityId, op: LikeAction, client_ts: DateTime<Utc>) -> LikeOp {
+        LikeOp { entity, id, op, client_ts }
+    }
+
+    fn synced_at() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 4, 15, 12, 0, 0).unwrap()
+    }
+
+    #[sqlx::test]
+    async fn apply_like_batch_should_report_each_operation(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        let like_dao = LikeDaoImpl::new(pool.clone()).with_clock(Arc::new(FixedClock::new(synced_at())));
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 2).await;
+        let deleted_id = fixtures::seed_questions(&question_dao, 1).await[0];
+        question_dao.delete_question(EntityId::uuid(deleted_id), true).await.unwrap();
+        let draft_id = answer_dao.create_answer_draft(fixtures::answer(question_id).build()).await.unwrap().id();
+        let at = synced_at() - Duration::hours(1);
+
+        let res = like_dao.apply_like_batch("user", vec![
+            op(LikableEntity::Question, EntityId::uuid(question_id), LikeAction::Like, at),
+            op(LikableEntity::Answer, EntityId::uuid(answer_ids[0]), LikeAction::Like, at),
+            op(LikableEntity::Question, EntityId::uuid(deleted_id), LikeAction::Like, at),
+            op(LikableEntity::Answer, EntityId::uuid(Uuid::new_v4()), LikeAction::Unlike, at),
+            op(LikableEntity::Question, EntityId::new(String::from("not-an-id")), LikeAction::Like, at),
+            op(LikableEntity::Answer, EntityId::uuid(draft_id), LikeAction::Like, at),
+            // Unliking an entity the user never liked changes nothing
+            op(LikableEntity::Answer, EntityId::uuid(answer_ids[1]), LikeAction::Unlike, at),
+        ]).await;
+        println!("{:?}", res);
+        let report = res.unwrap();
+        let [applied_question, applied_answer, deleted, missing, malformed, draft, never_liked] = report.outcomes.as_slice() else {
+            panic!("there should be an outcome for each operation");
+        };
+        assert_eq!((applied_question, applied_answer), (&LikeOutcome::Applied, &LikeOutcome::Applied));
+        assert_eq!((deleted, missing), (&LikeOutcome::EntityMissing, &LikeOutcome::EntityMissing));
+        assert!(matches!(malformed, LikeOutcome::Invalid(_)));
+        assert!(matches!(draft, LikeOutcome::Invalid(_)));
+        assert_eq!(never_liked, &LikeOutcome::AlreadyApplied);
+        assert_eq!(report.applied(), 2);
+
+        assert_eq!(question_dao.get_question(EntityId::uuid(question_id)).await.unwrap().likes(), 1);
+        assert_eq!(answer_dao.get_answer(EntityId::uuid(answer_ids[0])).await.unwrap().likes(), 1);
+        // Changes are recorded in the audit log, attributed to the user and timed by the clock of the data access object
+        let recorded: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM like_events WHERE source_token = 'user' AND occurred_at = $1")
+            .bind(synced_at())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(recorded, 2);
+    }
+
+    #[sqlx::test]
+    async fn apply_like_batch_should_reject_tombstoned_answers(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        let like_dao = LikeDaoImpl::new(pool).with_clock(Arc::new(FixedClock::new(synced_at())));
+        let (_, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 1).await;
+        answer_dao.delete_answer_with_tombstone(EntityId::uuid(answer_ids[0]), None).await.expect("answer should be tombstoned");
+
+        let at = synced_at() - Duration::hours(1);
+        let res = like_dao.apply_like_batch("user", vec![op(LikableEntity::Answer, EntityId::uuid(answer_ids[0]), LikeAction::Like, at)]).await;
+        println!("{:?}", res);
+        let report = res.unwrap();
+        assert!(matches!(report.outcomes.as_slice(), [LikeOutcome::Invalid(_)]));
+        assert_eq!(answer_dao.get_answer(EntityId::uuid(answer_ids[0])).await.unwrap().likes(), 0);
+    }
+
+    #[sqlx::test]
+    async fn apply_like_batch_should_change_nothing_when_replayed(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        let like_dao = LikeDaoImpl::new(pool);
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 2).await;
+        let at = synced_at();
+        let batch = vec![
+            op(LikableEntity::Question, EntityId::uuid(question_id), LikeAction::Like, at),
+            op(LikableEntity::Answer, EntityId::uuid(answer_ids[0]), LikeAction::Like, at),
+            op(LikableEntity::Answer, EntityId::uuid(answer_ids[1]), LikeAction::Like, at),
+            op(LikableEntity::Answer, EntityId::uuid(answer_ids[1]), LikeAction::Unlike, at + Duration::seconds(1)),
+        ];
+        let first = like_dao.apply_like_batch("user", batch.clone()).await.unwrap();
+        assert_eq!(first.applied(), 4);
+
+        let res = like_dao.apply_like_batch("user", batch.clone()).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap().outcomes, vec![LikeOutcome::AlreadyApplied; 4]);
+        assert_eq!(question_dao.get_question(EntityId::uuid(question_id)).await.unwrap().likes(), 1);
+        assert_eq!(answer_dao.get_answer(EntityId::uuid(answer_ids[0])).await.unwrap().likes(), 1);
+        assert_eq!(answer_dao.get_answer(EntityId::uuid(answer_ids[1])).await.unwrap().likes(), 0);
+        // The likes of another user are counted separately
+        let other = like_dao.apply_like_batch("other user", batch).await.unwrap();
+        assert_eq!(other.applied(), 4);
+        assert_eq!(question_dao.get_question(EntityId::uuid(question_id)).await.unwrap().likes(), 2);
+    }
+
+    #[sqlx::test]
+    async fn apply_like_batch_should_keep_the_latest_change(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let like_dao = LikeDaoImpl::new(pool);
+        let question_id = fixtures::seed_questions(&question_dao, 1).await[0];
+        let id = EntityId::uuid(question_id);
+        let at = |minutes| synced_at() + Duration::minutes(minutes);
+        let likes = || async { question_dao.get_question(id.clone()).await.unwrap().likes() };
+
+        let res = like_dao.apply_like_batch("user", vec![
+            op(LikableEntity::Question, id.clone(), LikeAction::Like, at(1)),
+            op(LikableEntity::Question, id.clone(), LikeAction::Unlike, at(2)),
+            op(LikableEntity::Question, id.clone(), LikeAction::Like, at(3)),
+            op(LikableEntity::Question, id.clone(), LikeAction::Like, at(4)),
+        ]).await;
+        println!("{:?}", res);
+        let outcomes = res.unwrap().outcomes;
+        assert_eq!(outcomes, [LikeOutcome::Applied, LikeOutcome::Applied, LikeOutcome::Applied, LikeOutcome::AlreadyApplied]);
+        assert_eq!(likes().await, 1);
+
+        // A change synced late from another device does not undo a later one
+        let res = like_dao.apply_like_batch("user", vec![op(LikableEntity::Question, id.clone(), LikeAction::Unlike, at(3))]).await;
+        assert_eq!(res.unwrap().outcomes, [LikeOutcome::AlreadyApplied]);
+        assert_eq!(likes().await, 1);
+        let res = like_dao.apply_like_batch("user", vec![op(LikableEntity::Question, id.clone(), LikeAction::Unlike, at(5))]).await;
+        assert_eq!(res.unwrap().outcomes, [LikeOutcome::Applied]);
+        assert_eq!(likes().await, 0);
+    }
+}
+
+mod reputation_tests {
+    use chrono::Utc;
+    use sqlx::types::Uuid;
+    use crate::fixtures;
+    use crate::models::{DbError, EntityId, LikableEntity, LikeAction, LikeOp, ReputationConfig, UserReputation};
+    use crate::persistence::prelude::PgPool;
+    use crate::persistence::{AnswerDao, AnswerDaoImpl, AuthorDao, AuthorDaoImpl, LikeDao, LikeDaoImpl, QuestionDao, QuestionDaoImpl};
+
+    async fn create_user(pool: &PgPool, username: &str) -> Uuid {
+        sqlx::query_scalar("INSERT INTO users (username) VALUES ($1) RETURNING id")
+            .bind(username)
+            .fetch_one(pool)
+            .await
+            .expect("user should be created successfully")
+    }
+
+    async fn reputation(pool: &PgPool, user_id: Uuid) -> i32 {
+        sqlx::query_scalar("SELECT reputation FROM users WHERE id = $1").bind(user_id).fetch_one(pool).await.unwrap()
+    }
+
+    /// Creates a question asked by `author_id`, if any, and answers to it by each of `answer_authors`.
+    async fn seed_authored(pool: &PgPool, author_id: Option<Uuid>, answer_authors: &[Option<Uuid>]) -> (Uuid, Vec<Uuid>) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        let question_id = fixtures::seed_questions(&question_dao, 1).await[0];
+        sqlx::query("UPDATE questions SET author_id = $1 WHERE id = $2").bind(author_id).bind(question_id).execute(pool).await.unwrap();
+        let mut answer_ids = Vec::new();
+        for author_id in answer_authors {
+            let mut answer = fixtures::answer(question_id);
+            if let Some(author_id) = author_id {
+                answer = answer.author_id(*author_id);
+            }
+            answer_ids.push(answer_dao.create_answer(answer.build()).await.expect("answer should be created successfully").id());
+        }
+        (question_id, answer_ids)
+    }
+
+    #[sqlx::test]
+    async fn likes_should_adjust_the_reputation_of_authors(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone()).with_reputation(ReputationConfig { answer_liked: 7, ..ReputationConfig::default() });
+        let like_dao = LikeDaoImpl::new(pool.clone());
+        let asker = create_user(&pool, "asker").await;
+        let answerer = create_user(&pool, "answerer").await;
+        let (question_id, answer_ids) = seed_authored(&pool, Some(asker), &[Some(answerer)]).await;
+
+        question_dao.increment_question_likes(EntityId::uuid(question_id)).await.expect("question should be liked successfully");
+        assert_eq!(reputation(&pool, asker).await, 5);
+        answer_dao.increment_answer_likes(EntityId::uuid(answer_ids[0])).await.expect("answer should be liked successfully");
+        assert_eq!(reputation(&pool, answerer).await, 7);
+
+        // Liking and then unliking through a batch reverses the adjustment
+        let like = |entity, id, op| LikeOp { entity, id: EntityId::uuid(id), op, client_ts: Utc::now() };
+        like_dao.apply_like_batch("user-1", vec![like(LikableEntity::Question, question_id, LikeAction::Like)]).await.unwrap();
+        like_dao.apply_like_batch("user-1", vec![like(LikableEntity::Answer, answer_ids[0], LikeAction::Like)]).await.unwrap();
+        assert_eq!((reputation(&pool, asker).await, reputation(&pool, answerer).await), (10, 17));
+        let res = like_dao.apply_like_batch("user-1", vec![
+            like(LikableEntity::Question, question_id, LikeAction::Unlike),
+            like(LikableEntity::Answer, answer_ids[0], LikeAction::Unlike),
+        ]).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap().applied(), 2);
+        assert_eq!((reputation(&pool, asker).await, reputation(&pool, answerer).await), (5, 7));
+    }
+
+    #[sqlx::test]
+    async fn accepting_an_answer_should_reward_it_once(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let first = create_user(&pool, "first").await;
+        let second = create_user(&pool, "second").await;
+        let (question_id, answer_ids) = seed_authored(&pool, None, &[Some(first), Some(second)]).await;
+        let accept = |answer_id| question_dao.accept_answer(EntityId::uuid(question_id), EntityId::uuid(answer_id));
+
+        let res = accept(answer_ids[0]).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap().accepted_answer_id(), Some(answer_ids[0]));
+        assert_eq!((reputation(&pool, first).await, reputation(&pool, second).await), (15, 0));
+        // Accepting the same answer again rewards it once
+        accept(answer_ids[0]).await.expect("answer should be accepted successfully");
+        assert_eq!((reputation(&pool, first).await, reputation(&pool, second).await), (15, 0));
+        // Accepting another answer moves the reward
+        accept(answer_ids[1]).await.expect("answer should be accepted successfully");
+        assert_eq!((reputation(&pool, first).await, reputation(&pool, second).await), (0, 15));
+        let res = question_dao.unaccept_answer(EntityId::uuid(question_id)).await;
+        println!("{:?}", res);
+        assert!(res.unwrap().accepted_answer_id().is_none());
+        assert_eq!((reputation(&pool, first).await, reputation(&pool, second).await), (0, 0));
+        let question = question_dao.unaccept_answer(EntityId::uuid(question_id)).await.unwrap();
+        assert!(question.accepted_answer_id().is_none());
+        assert_eq!((reputation(&pool, first).await, reputation(&pool, second).await), (0, 0));
+    }
+
+    #[sqlx::test]
+    async fn pinning_an_answer_should_change_no_reputation(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let first = create_user(&pool, "first").await;
+        let second = create_user(&pool, "second").await;
+        let (question_id, answer_ids) = seed_authored(&pool, None, &[Some(first), Some(second)]).await;
+        question_dao.accept_answer(EntityId::uuid(question_id), EntityId::uuid(answer_ids[0])).await.unwrap();
+
+        // Pinning is separate from acceptance, so pinning and unpinning either answer leave both untouched
+        for answer_id in answer_ids.iter().chain(answer_ids.iter().rev()) {
+            let res = question_dao.pin_answer(EntityId::uuid(question_id), EntityId::uuid(*answer_id)).await;
+            println!("{:?}", res);
+            let question = res.unwrap();
+            assert_eq!((question.pinned_answer_id(), question.accepted_answer_id()), (Some(*answer_id), Some(answer_ids[0])));
+            assert_eq!((reputation(&pool, first).await, reputation(&pool, second).await), (15, 0));
+        }
+        let question = question_dao.unpin_answer(EntityId::uuid(question_id)).await.unwrap();
+        assert_eq!((question.pinned_answer_id(), question.accepted_answer_id()), (None, Some(answer_ids[0])));
+        assert_eq!((reputation(&pool, first).await, reputation(&pool, second).await), (15, 0));
+    }
+
+    #[sqlx::test]
+    async fn accept_answer_should_fail_for_answer_to_another_question(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let (question_id, _) = seed_authored(&pool, None, &[None]).await;
+        let (_, other_answer_ids) = seed_authored(&pool, None, &[None]).await;
+        let res = question_dao.accept_answer(EntityId::uuid(question_id), EntityId::uuid(other_answer_ids[0])).await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        let res = question_dao.accept_answer(EntityId::uuid(question_id), EntityId::uuid(Uuid::new_v4())).await;
+        println!("{:?}", res);
+        let Err(DbError::NotFound { .. }) = res else { panic!("Error should be `NotFound` variant") };
+    }
+
+    #[sqlx::test]
+    async fn anonymous_content_should_change_no_reputation(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone());
+        let bystander = create_user(&pool, "bystander").await;
+        let (question_id, answer_ids) = seed_authored(&pool, None, &[None]).await;
+
+        question_dao.increment_question_likes(EntityId::uuid(question_id)).await.expect("question should be liked successfully");
+        answer_dao.increment_answer_likes(EntityId::uuid(answer_ids[0])).await.expect("answer should be liked successfully");
+        question_dao.accept_answer(EntityId::uuid(question_id), EntityId::uuid(answer_ids[0])).await.expect("answer should be accepted successfully");
+        let total: i64 = sqlx::query_scalar("SELECT SUM(reputation) FROM users").fetch_one(&pool).await.unwrap();
+        assert_eq!(total, 0);
+        assert_eq!(reputation(&pool, bystander).await, 0);
+    }
+
+    #[sqlx::test]
+    async fn get_top_users_by_reputation_should_rank_users(pool: PgPool) {
+        let author_dao = AuthorDaoImpl::new(pool.clone());
+        let mut users = Vec::new();
+        for (username, reputation) in [("carol", 20), ("alice", 45), ("bob", 20), ("dave", 0)] {
+            let id = create_user(&pool, username).await;
+            sqlx::query("UPDATE users SET reputation = $1 WHERE id = $2").bind(reputation).bind(id).execute(&pool).await.unwrap();
+            users.push(UserReputation { id, username: String::from(username), reputation });
+        }
+
+        let res = author_dao.get_top_users_by_reputation(3).await;
+        println!("{:?}", res);
+        // Ties are ordered by username
+        assert_eq!(res.unwrap(), [users[1].clone(), users[2].clone(), users[0].clone()]);
+        assert_eq!(author_dao.get_top_users_by_reputation(10).await.unwrap().len(), 4);
+        let res = author_dao.get_top_users_by_reputation(0).await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+    }
+}
+
+mod policy_tests {
+    use std::sync::Arc;
+    use sqlx::types::Uuid;
+    use crate::fixtures;
+    use crate::models::{DbError, EntityId, UpdateQuestion, ValidationReason};
+    use crate::models::policy::{ContentKind, ContentPolicy, PolicyDecision};
+    use crate::persistence::prelude::PgPool;
+    use crate::persistence::{AnswerDao, AnswerDaoImpl, QuestionDao, QuestionDaoImpl};
+
+    /// Decides by the marker words in the text, an empty flag reason failing to be stored.
+    struct MarkerPolicy;
+
+    impl ContentPolicy for MarkerPolicy {
+        fn check(&self, kind: ContentKind, text: &str) -> PolicyDecision {
+            if text.contains("forbidden") {
+                PolicyDecision::Reject(format!("{kind:?} is forbidden"))
+            } else if text.contains("suspicious") {
+                PolicyDecision::Flag(format!("{kind:?} is suspicious"))
+            } else if text.contains("unflaggable") {
+                PolicyDecision::Flag(String::new())
+            } else {
+                PolicyDecision::Allow
+            }
+        }
+    }
+
+    async fn flags(pool: &PgPool) -> Vec<(Option<Uuid>, Option<Uuid>, String)> {
+        sqlx::query_as("SELECT question_id, answer_id, reason FROM moderation_flags ORDER BY created_at, reason")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn count(pool: &PgPool, table: &str) -> i64 {
+        sqlx::query_scalar(&format!("SELECT count(*) FROM {table}")).fetch_one(pool).await.unwrap()
+    }
+
+    #[sqlx::test]
+    async fn create_question_should_follow_the_policy_decision(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_policy(Arc::new(MarkerPolicy));
+        let allowed = question_dao.create_question(fixtures::question().build()).await.expect("question should be created successfully");
+        assert!(flags(&pool).await.is_empty());
+
+        let res = question_dao.create_question(fixtures::question().title("A forbidden title").build()).await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { reason: ValidationReason::PolicyViolation(reason), .. }) = res else { panic!("Error should be `PolicyViolation` reason") };
+        assert_eq!(reason, "Title is forbidden");
+        assert_eq!(count(&pool, "questions").await, 1);
+
+        let res = question_dao.create_question(fixtures::question().question("A suspicious question").build()).await;
+        println!("{:?}", res);
+        let flagged = res.expect("flagged question should be created");
+        assert_eq!(flags(&pool).await, [(Some(flagged.id()), None, String::from("Question is suspicious"))]);
+        assert_ne!(allowed.id(), flagged.id());
+    }
+
+    #[sqlx::test]
+    async fn create_answer_should_follow_the_policy_decision(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone()).with_policy(Arc::new(MarkerPolicy));
+        let question_id = fixtures::seed_questions(&question_dao, 1).await[0];
+        let res = answer_dao.create_answer(fixtures::answer(question_id).answer("A forbidden answer").build()).await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { reason: ValidationReason::PolicyViolation(_), .. }) = res else { panic!("Error should be `PolicyViolation` reason") };
+        assert_eq!(count(&pool, "answers").await, 0);
+
+        let flagged = answer_dao.create_answer(fixtures::answer(question_id).answer("A suspicious answer").build()).await.unwrap();
+        let draft = answer_dao.create_answer_draft(fixtures::answer(question_id).answer("A suspicious draft").build()).await.unwrap();
+        answer_dao.create_answer(fixtures::answer(question_id).build()).await.unwrap();
+        let reason = String::from("Answer is suspicious");
+        assert_eq!(flags(&pool).await, [(None, Some(flagged.id()), reason.clone()), (None, Some(draft.id()), reason)]);
+    }
+
+    #[sqlx::test]
+    async fn update_question_should_follow_the_policy_decision(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_policy(Arc::new(MarkerPolicy));
+        let question = question_dao.create_question(fixtures::question().title("Title").build()).await.unwrap();
+        let update = UpdateQuestion { title: Some(String::from("A forbidden title")), question: None };
+        let res = question_dao.update_question(EntityId::uuid(question.id()), update).await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { reason: ValidationReason::PolicyViolation(_), .. }) = res else { panic!("Error should be `PolicyViolation` reason") };
+        assert_eq!(question_dao.get_question(EntityId::uuid(question.id())).await.unwrap().title(), "Title");
+
+        let update = UpdateQuestion { title: None, question: Some(String::from("Now suspicious")) };
+        let res = question_dao.update_question(EntityId::uuid(question.id()), update).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap().question(), "Now suspicious");
+        assert_eq!(flags(&pool).await, [(Some(question.id()), None, String::from("Question is suspicious"))]);
+    }
+
+    #[sqlx::test]
+    async fn flags_should_be_stored_in_the_same_transaction_as_the_entity(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_policy(Arc::new(MarkerPolicy));
+        let answer_dao = AnswerDaoImpl::new(pool.clone()).with_policy(Arc::new(MarkerPolicy));
+        // The flag violates a check constraint and cannot be stored, so neither can the entity it was raised on
+        let res = question_dao.create_question(fixtures::question().question("An unflaggable question").build()).await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        assert_eq!(count(&pool, "questions").await, 0);
+
+        let question = question_dao.create_question(fixtures::question().title("Title").build()).await.unwrap();
+        let res = answer_dao.create_answer(fixtures::answer(question.id()).answer("An unflaggable answer").build()).await;
+        println!("{:?}", res);
+        let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        assert_eq!(count(&pool, "answers").await, 0);
+
+        let update = UpdateQuestion { title: Some(String::from("An unflaggable title")), question: None };
+        let Err(DbError::Validation { .. }) = question_dao.update_question(EntityId::uuid(question.id()), update).await else {
+            panic!("Error should be `Validation` variant")
+        };
+        assert_eq!(question_dao.get_question(EntityId::uuid(question.id())).await.unwrap().title(), "Title");
+        assert!(flags(&pool).await.is_empty());
+    }
+}
+
+mod budget_tests {
+    use crate::fixtures;
+    use crate::models::EntityId;
+    use crate::persistence::prelude::PgPool;
+    use crate::persistence::{AnswerDao, AnswerDaoImpl, QuestionDao, QuestionDaoImpl};
+    use crate::query_counter::QueryCounter;
+
+    #[sqlx::test]
+    async fn query_counter_should_count_each_task_separately(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool);
+        let (_, statements) = QueryCounter::count(async {}).await;
+        assert_eq!(statements, 0);
+        // Statements outside of the counted future are not counted
+        let question = question_dao.create_question(fixtures::question().build()).await.unwrap();
+        let (res, statements) = QueryCounter::count(question_dao.get_question(EntityId::uuid(question.id()))).await;
+        println!("{:?}", res);
+        assert_eq!(statements, 1);
+        let ((_, first), (_, second)) = tokio::join!(
+            QueryCounter::count(async {
+                question_dao.get_questions().await.unwrap();
+                question_dao.get_questions().await.unwrap();
+            }),
+            QueryCounter::count(question_dao.get_questions())
+        );
+        assert_eq!((first, second), (2, 1));
+    }
+
+    #[sqlx::test]
+    async fn reads_should_stay_within_their_statement_budget(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let (question_id, _) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 3).await;
+        let id = || EntityId::uuid(question_id);
+        // Each of these joins or aggregates in one query rather than making a query per answer
+        assert_eq!(QueryCounter::count(question_dao.get_question(id())).await.1, 1);
+        assert_eq!(QueryCounter::count(question_dao.get_questions()).await.1, 1);
+        assert_eq!(QueryCounter::count(question_dao.get_question_header(id())).await.1, 1);
+        assert_eq!(QueryCounter::count(answer_dao.get_answers(id())).await.1, 1);
+        assert_eq!(QueryCounter::count(answer_dao.get_answers_with_authors(id())).await.1, 1);
+        // Begins a transaction so that the question and its answers are read from the same snapshot
+        let (res, statements) = QueryCounter::count(question_dao.get_question_detail_consistent(id())).await;
+        println!("{:?}", res);
+        assert_eq!(res.unwrap().answers().len(), 3);
+        assert!(statements <= 4, "expected at most 4 statements, got {statements}");
+    }
+
+    #[sqlx::test]
+    async fn writes_should_stay_within_their_statement_budget(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone());
+        let answer_dao = AnswerDaoImpl::new(pool);
+        let (question_id, _) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 3).await;
+        let id = || EntityId::uuid(question_id);
+        let (res, statements) = QueryCounter::count(answer_dao.create_answer(fixtures::answer(question_id).build())).await;
+        println!("{:?}", res);
+        assert!(statements <= 3, "expected at most 3 statements, got {statements}");
+        let (res, statements) = QueryCounter::count(question_dao.increment_question_likes(id())).await;
+        println!("{:?}", res);
+        assert!(statements <= 2, "expected at most 2 statements, got {statements}");
+        // The answers are removed by the cascade rather than one by one
+        let (res, statements) = QueryCounter::count(question_dao.delete_question(id(), true)).await;
+        println!("{:?}", res);
+        assert!(res.is_ok());
+        assert!(statements <= 3, "expected at most 3 statements, got {statements}");
+    }
+}
+
+mod schema_tests {
+    use chrono::{Duration, Utc};
+    use crate::admin::QaAdmin;
+    use crate::fixtures;
+    use crate::models::{
+        DbError, DeletePolicy, DetailOptions, EntityId, LikableEntity, LikeAction, LikeOp, NewCategory, Totals, UpdateQuestion,
+    };
+    use crate::persistence::prelude::*;
+    use crate::persistence::{integrity, migrations};
+    use crate::persistence::schema::{SchemaName, Tables};
+
+    async fn count_rows(pool: &PgPool, table: &str) -> i64 {
+        sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table}"))
+            .fetch_one(pool)
+            .await
+            .expect("table should exist")
+    }
+
+    #[test]
+    fn schema_name_should_only_accept_lowercase_identifiers() {
+        for name in ["qa", "_qa", "qa_2", &"q".repeat(63)] {
+            assert_eq!(SchemaName::new(name).expect("name should be valid").as_str(), name);
+        }
+        for name in ["", "QA", "1qa", "qa;drop", "qa schema", "\"qa\"", &"q".repeat(64)] {
+            let res = SchemaName::new(name);
+            println!("{:?}", res);
+            let Err(DbError::Validation { .. }) = res else { panic!("Error should be `Validation` variant") };
+        }
+    }
+
+    #[sqlx::test]
+    async fn daos_should_read_and_write_within_their_schema(pool: PgPool) {
+        let schema = SchemaName::new("qa_test").unwrap();
+        let res = migrations::run_in_schema(&pool, &schema).await;
+        println!("{:?}", res);
+        assert!(res.is_ok());
+        // Migrating again is a no-op
+        assert!(migrations::run_in_schema(&pool, &schema).await.is_ok());
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_schema(schema.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone()).with_schema(schema);
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 2).await;
+        let id = || EntityId::uuid(question_id);
+
+        assert_eq!(question_dao.increment_question_likes(id()).await.unwrap(), 1);
+        let update = UpdateQuestion { title: Some(String::from("Schema title")), question: None };
+        let question = question_dao.update_question(id(), update).await.unwrap();
+        assert_eq!(question.title(), "Schema title");
+        // Serial ids are resolved within the schema as well
+        let serial = question.serial().expect("question should have a serial");
+        assert_eq!(question_dao.get_question(EntityId::serial(serial)).await.unwrap().id(), question_id);
+        let detail = question_dao.get_question_detail_with(id(), DetailOptions::default()).await.unwrap();
+        println!("{:?}", detail);
+        assert_eq!(detail.answers().len(), answer_ids.len());
+
+        assert_eq!(count_rows(&pool, "qa_test.questions").await, 1);
+        assert_eq!(count_rows(&pool, "qa_test.answers").await, 2);
+        // The revision recorded by the trigger is kept in the schema too
+        assert_eq!(count_rows(&pool, "qa_test.question_revisions").await, 1);
+        assert_eq!(count_rows(&pool, "public.questions").await, 0);
+        assert_eq!(count_rows(&pool, "public.answers").await, 0);
+    }
+
+    #[sqlx::test]
+    async fn daos_should_not_see_rows_of_other_schemas(pool: PgPool) {
+        let schema = SchemaName::new("qa_test").unwrap();
+        migrations::run_in_schema(&pool, &schema).await.unwrap();
+        let default_dao = QuestionDaoImpl::new(pool.clone());
+        let schema_dao = QuestionDaoImpl::new(pool.clone()).with_schema(schema);
+        let in_schema = schema_dao.create_question(fixtures::question().title("In schema").build()).await.unwrap();
+        let in_public = default_dao.create_question(fixtures::question().title("In public").build()).await.unwrap();
+
+        let res = default_dao.get_question(EntityId::uuid(in_schema.id())).await;
+        println!("{:?}", res);
+        assert!(res.is_err());
+        let res = schema_dao.get_question(EntityId::uuid(in_public.id())).await;
+        println!("{:?}", res);
+        assert!(res.is_err());
+        let titles = |questions: Vec<crate::models::Question>| questions.iter().map(|q| q.title().to_string()).collect::<Vec<_>>();
+        assert_eq!(titles(schema_dao.get_questions().await.unwrap()), vec!["In schema"]);
+        // Connections the schema dao returned to the pool must not leak into the default dao's reads
+        for _ in 0..4 {
+            assert_eq!(titles(default_dao.get_questions().await.unwrap()), vec!["In public"]);
+        }
+    }
+
+    /// Without the tables of the migrations in `public`, any statement naming a table outside the schema fails.
+    #[sqlx::test(migrations = false)]
+    async fn every_dao_should_only_use_the_tables_of_its_schema(pool: PgPool) {
+        let schema = SchemaName::new("qa_test").unwrap();
+        migrations::run_in_schema(&pool, &schema).await.expect("schema should be migrated successfully");
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_schema(schema.clone());
+        let answer_dao = AnswerDaoImpl::new(pool.clone()).with_schema(schema.clone());
+        let (question_id, answer_ids) = fixtures::seed_question_with_answers(&question_dao, &answer_dao, 2).await;
+        let id = || EntityId::uuid(question_id);
+        let update = UpdateQuestion { title: Some(String::from("Schema title")), question: None };
+        let serial = question_dao.update_question(id(), update).await.unwrap().serial().unwrap();
+        assert_eq!(question_dao.get_question(EntityId::serial(serial)).await.unwrap().id(), question_id);
+        assert_eq!(question_dao.increment_question_likes(id()).await.unwrap(), 1);
+        let detail = question_dao.get_question_detail_with(id(), DetailOptions::default()).await.unwrap();
+        assert_eq!(detail.answers().len(), 2);
+        answer_dao.delete_answer_with_tombstone(EntityId::uuid(answer_ids[0]), None).await.unwrap();
+
+        let subscription_dao = SubscriptionDaoImpl::new(pool.clone()).with_schema(schema.clone());
+        subscription_dao.subscribe("ops", id()).await.unwrap();
+        answer_dao.create_answer(fixtures::answer(question_id).build()).await.unwrap();
+        assert_eq!(subscription_dao.get_updates("ops").await.unwrap().len(), 1);
+        let stats_dao = StatsDaoImpl::new(pool.clone()).with_schema(schema.clone());
+        assert_eq!(stats_dao.get_totals().await.unwrap(), Totals { questions: 1, answers: 3, users: 0, subscriptions: 1 });
+        let category_dao = CategoryDaoImpl::new(pool.clone()).with_schema(schema.clone());
+        let category = category_dao.create_category(NewCategory { name: String::from("Schemas"), parent_id: None }).await.unwrap();
+        category_dao.assign_category(id(), Some(EntityId::uuid(category.id()))).await.unwrap();
+        assert_eq!(category_dao.get_questions_in_category(EntityId::uuid(category.id()), true).await.unwrap().len(), 1);
+        let tag_dao = TagDaoImpl::new(pool.clone()).with_schema(schema.clone());
+        tag_dao.tag_question(id(), "postgres").await.unwrap();
+        assert_eq!(tag_dao.suggest_tags("post", 10).await.unwrap().len(), 1);
+        let like_dao = LikeDaoImpl::new(pool.clone()).with_schema(schema.clone());
+        let op = LikeOp { entity: LikableEntity::Answer, id: EntityId::uuid(answer_ids[1]), op: LikeAction::Like, client_ts: Utc::now() };
+        assert_eq!(like_dao.apply_like_batch("user", vec![op]).await.unwrap().applied(), 1);
+        let author_dao = AuthorDaoImpl::new(pool.clone()).with_schema(schema.clone());
+        assert!(author_dao.get_top_users_by_reputation(10).await.unwrap().is_empty());
+        let admin_dao = AdminDaoImpl::new(pool.clone()).with_schema(schema.clone());
+        admin_dao.set_question_likes(id(), 5).await.unwrap();
+        assert_eq!(admin_dao.get_like_events(id(), Utc::now() - Duration::days(1)).await.unwrap().len(), 2);
+        assert_eq!(admin_dao.reindex().await.unwrap().len(), 22);
+
+        let report = integrity::check_integrity_in_schema(&pool, &schema).await.unwrap();
+        assert!(report.is_clean(), "{report:?} should be clean");
+        let repaired = integrity::repair_in_schema(&pool, &schema, &report, DeletePolicy::Cascade).await.unwrap();
+        assert_eq!(repaired.answers_deleted, 0);
+        let admin = QaAdmin::new(pool.clone()).with_schema(schema.clone());
+        assert_eq!(admin.stats().await.unwrap().questions, 1);
+        assert_eq!(admin.recount_likes().await.unwrap().questions, 0);
+        assert_eq!(admin.purge_deleted(Duration::zero()).await.unwrap().answers, 1);
+        let scope = ScopedDao::begin(&pool).await.unwrap().with_schema(schema);
+        scope.create_question(fixtures::question().title("Scoped").build()).await.unwrap();
+        scope.commit().await.unwrap();
+        assert_eq!(question_dao.get_questions().await.unwrap().len(), 2);
+        // Nothing was created outside the schema
+        let tables: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM pg_tables WHERE schemaname = 'public'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(tables, 0);
+    }
+
+    #[test]
+    fn tables_should_only_qualify_table_references() {
+        let tables = Tables::in_schema(SchemaName::new("qa").unwrap());
+        let res = tables.sql(
+            "UPDATE answers SET likes = likes + 1 FROM questions AS q JOIN likes ON likes.entity_id = q.id \
+            WHERE answers.question_id = q.id AND answers.answer <> 'FROM questions'"
+        );
+        assert_eq!(
+            res,
+            "UPDATE qa.answers SET likes = likes + 1 FROM qa.questions AS q JOIN qa.likes ON likes.entity_id = q.id \
+            WHERE answers.question_id = q.id AND answers.answer <> 'FROM questions'"
+        );
+        assert_eq!(
+            tables.sql("delete from question_tags using tags where tags.id = question_tags.tag_id"),
+            "delete from qa.question_tags using qa.tags where tags.id = question_tags.tag_id"
+        );
+        assert_eq!(tables.sql("SELECT EXTRACT(EPOCH FROM created_at) FROM answers_archive"), "SELECT EXTRACT(EPOCH FROM created_at) FROM qa.answers_archive");
+        // Without a schema the template is executed as is
+        let template = "SELECT * FROM questions";
+        assert!(matches!(Tables::default().sql(template), std::borrow::Cow::Borrowed(sql) if sql == template));
+    }
+}
+
+mod metrics_tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use crate::fixtures;
+    use crate::models::EntityId;
+    use crate::persistence::metrics::{DaoMetrics, LATENCY_BUCKETS};
+    use crate::persistence::prelude::PgPool;
+    use crate::persistence::{AnswerDao, AnswerDaoImpl, QuestionDao, QuestionDaoImpl};
+
+    /// The exposition text parsed into the types of its metrics and the values of its series.
+    struct Exposition {
+        types: HashMap<String, String>,
+        samples: HashMap<String, f64>,
+    }
+
+    impl Exposition {
+        fn parse(text: &str) -> Self {
+            let mut types = HashMap::new();
+            let mut samples = HashMap::new();
+            for line in text.lines() {
+                if let Some(declaration) = line.strip_prefix("# TYPE ") {
+                    let (name, kind) = declaration.split_once(' ').expect("type should name a metric and its type");
+                    types.insert(name.to_owned(), kind.to_owned());
+                } else if !line.starts_with('#') {
+                    let (series, value) = line.rsplit_once(' ').expect("sample should have a value");
+                    samples.insert(series.to_owned(), value.parse().expect("value should be a number"));
+                }
+            }
+            Self { types, samples }
+        }
+
+        fn value(&self, series: &str) -> f64 {
+            *self.samples.get(series).unwrap_or_else(|| panic!("series `{}` should be exported", series))
+        }
+    }
+
+    #[sqlx::test]
+    async fn dao_metrics_should_count_and_time_calls_by_method_and_outcome(pool: PgPool) {
+        let metrics = Arc::new(DaoMetrics::default());
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_observer(metrics.clone());
+        let answer_dao = AnswerDaoImpl::new(pool).with_observer(metrics.clone());
+        let empty = Exposition::parse(&metrics.prometheus_export());
+        assert!(empty.samples.is_empty());
+        assert_eq!(empty.types["qa_dao_calls_total"], "counter");
+
+        let question = question_dao.create_question(fixtures::question().build()).await.unwrap();
+        answer_dao.create_answer(fixtures::answer(question.id()).build()).await.unwrap();
+        question_dao.get_question(EntityId::uuid(question.id())).await.unwrap();
+        let res = question_dao.get_question(EntityId::new(String::from("not an id"))).await;
+        println!("{:?}", res);
+        assert!(res.is_err());
+        let text = metrics.prometheus_export();
+        println!("{}", text);
+        let first = Exposition::parse(&text);
+        assert_eq!(first.types["qa_dao_calls_total"], "counter");
+        assert_eq!(first.types["qa_dao_call_duration_seconds"], "histogram");
+        let ok = r#"entity="question",method="get_question",outcome="ok""#;
+        let failed = r#"entity="question",method="get_question",outcome="invalid_id""#;
+        assert_eq!(first.value(&format!("qa_dao_calls_total{{{ok}}}")), 1.0);
+        assert_eq!(first.value(&format!("qa_dao_calls_total{{{failed}}}")), 1.0);
+        assert_eq!(first.value(r#"qa_dao_calls_total{entity="answer",method="create_answer",outcome="ok"}"#), 1.0);
+        assert_eq!(metrics.calls("create_question", "ok"), 1);
+
+        // The buckets are cumulative, ending with every call
+        let buckets: Vec<f64> = LATENCY_BUCKETS
+            .iter()
+            .map(|bound| first.value(&format!("qa_dao_call_duration_seconds_bucket{{{ok},le=\"{bound}\"}}")))
+            .collect();
+        assert!(buckets.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(first.value(&format!("qa_dao_call_duration_seconds_bucket{{{ok},le=\"+Inf\"}}")), 1.0);
+        assert_eq!(first.value(&format!("qa_dao_call_duration_seconds_count{{{ok}}}")), 1.0);
+        assert!(first.value(&format!("qa_dao_call_duration_seconds_sum{{{ok}}}")) > 0.0);
+
+        // Every series only grows with further calls
+        for _ in 0..3 {
+            question_dao.get_question(EntityId::uuid(question.id())).await.unwrap();
+        }
+        answer_dao.get_answers(EntityId::uuid(question.id())).await.unwrap();
+        let second = Exposition::parse(&metrics.prometheus_export());
+        for (series, value) in &first.samples {
+            assert!(second.value(series) >= *value, "series `{}` should not decrease", series);
+        }
+        assert_eq!(second.value(&format!("qa_dao_calls_total{{{ok}}}")), 4.0);
+        assert_eq!(second.value(&format!("qa_dao_call_duration_seconds_count{{{ok}}}")), 4.0);
+        assert!(second.value(&format!("qa_dao_call_duration_seconds_sum{{{ok}}}")) > first.value(&format!("qa_dao_call_duration_seconds_sum{{{ok}}}")));
+        assert_eq!(second.value(r#"qa_dao_calls_total{entity="answer",method="get_answers",outcome="ok"}"#), 1.0);
+        assert_eq!(second.value(&format!("qa_dao_calls_total{{{failed}}}")), 1.0);
+    }
+}
+
+mod observer_tests {
+    use std::sync::Arc;
+    use crate::fixtures;
+    use crate::models::{DbError, DbErrorKind, EntityId};
+    use crate::persistence::observer::{DaoEvent, EntityKind, QueryObserver, VecObserver};
+    use crate::persistence::prelude::PgPool;
+    use crate::persistence::{AnswerDao, AnswerDaoImpl, QuestionDao, QuestionDaoImpl};
+    use crate::query_counter::QueryCounter;
+
+    struct PanickingObserver;
+
+    impl QueryObserver for PanickingObserver {
+        fn on_complete(&self, _: DaoEvent) {
+            panic!("observer failed");
+        }
+    }
+
+    #[sqlx::test]
+    async fn observer_should_receive_an_event_for_every_call(pool: PgPool) {
+        let observer = Arc::new(VecObserver::default());
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_observer(observer.clone());
+        let answer_dao = AnswerDaoImpl::new(pool).with_observer(observer.clone());
+        let question = question_dao.create_question(fixtures::question().build()).await.unwrap();
+        let id = question.id().to_string();
+        answer_dao.create_answer(fixtures::answer(question.id()).build()).await.unwrap();
+        answer_dao.create_answer(fixtures::answer(question.id()).build()).await.unwrap();
+        assert_eq!(answer_dao.get_answers(EntityId::new(id.clone())).await.unwrap().len(), 2);
+        let res = question_dao.get_question(EntityId::new(String::from("not an id"))).await;
+        println!("{:?}", res);
+        let Err(DbError::InvalidId { .. }) = res else { panic!("Error should be `InvalidId` variant") };
+        let res = answer_dao.get_answer(EntityId::serial(i64::MAX)).await;
+        println!("{:?}", res);
+        assert!(res.is_err());
+
+        let events = observer.events();
+        println!("{:#?}", events);
+        let summary: Vec<_> = events.iter().map(|event| (event.method, event.entity, event.rows, event.error)).collect();
+        assert_eq!(summary, vec![
+            ("create_question", EntityKind::Question, Some(1), None),
+            ("create_answer", EntityKind::Answer, Some(1), None),
+            ("create_answer", EntityKind::Answer, Some(1), None),
+            ("get_answers", EntityKind::Answer, Some(2), None),
+            ("get_question", EntityKind::Question, None, Some(DbErrorKind::InvalidId)),
+            ("get_answer", EntityKind::Answer, None, Some(DbErrorKind::NotFound)),
+        ]);
+        let ids: Vec<_> = events.iter().map(|event| event.id.as_deref()).collect();
+        assert_eq!(ids, vec![None, None, None, Some(id.as_str()), Some("not an id"), Some(i64::MAX.to_string().as_str())]);
+
+        let json = serde_json::to_value(&events[4]).unwrap();
+        assert_eq!(json["method"], "get_question");
+        assert_eq!(json["entity"], "question");
+        assert_eq!(json["error"], "invalid_id");
+        assert!(json["duration_ms"].is_f64());
+    }
+
+    #[sqlx::test]
+    async fn panicking_observer_should_not_affect_results(pool: PgPool) {
+        let question_dao = QuestionDaoImpl::new(pool.clone()).with_observer(Arc::new(PanickingObserver));
+        let question = question_dao.create_question(fixtures::question().build()).await;
+        println!("{:?}", question);
+        let question = question.expect("question should be created despite the observer panicking");
+        assert_eq!(question_dao.increment_question_likes(EntityId::uuid(question.id())).await.unwrap(), 1);
+        let res = question_dao.delete_question(EntityId::uuid(question.id()), false).await;
+        assert_eq!(res.unwrap(), question.id());
+        let res = question_dao.get_question(EntityId::uuid(question.id())).await;
+        println!("{:?}", res);
+        assert!(res.is_err());
+    }
+
+    #[sqlx::test]
+    async fn calls_without_an_observer_should_be_unchanged(pool: PgPool) {
+        let observer = Arc::new(VecObserver::default());
+        let plain_dao = QuestionDaoImpl::new(pool.clone());
+        let observed_dao = QuestionDaoImpl::new(pool).with_observer(observer.clone());
+        let question = plain_dao.create_question(fixtures::question().build()).await.unwrap();
+        let id = || EntityId::uuid(question.id());
+        let (plain, plain_statements) = QueryCounter::count(plain_dao.get_question(id())).await;
+        let (observed, observed_statements) = QueryCounter::count(observed_dao.get_question(id())).await;
+        assert_eq!(plain.unwrap().id(), observed.unwrap().id());
+        assert_eq!(plain_statements, observed_statements);
+        // Only calls made through the observed data access object are reported
+        assert_eq!(observer.events().len(), 1);
+    }
+}
+
+mod paginate_tests {
+    use std::cell::Cell;
+    use std::future::{ready, Ready};
+    use std::pin::pin;
+    use std::sync::Arc;
+    use chrono::{Duration, Utc};
+    use futures_util::StreamExt;
+    use sqlx::types::Uuid;
+    use crate::clock::{FixedClock, SteppingClock};
+    use crate::fixtures;
+    use crate::models::{ActivityItem, DbError, Page, PageRequest, QuestionFields};
+    use crate::persistence::paginate::{all_pages_of, cursor_pages, MAX_PAGES};
+    use crate::persistence::prelude::PgPool;
+    use crate::persistence::{QuestionDao, QuestionDaoImpl};
+
+    /// Serves pages of `0..len` like an offset paged listing, counting the pages fetched.
+    fn offset_listing(len: u32, fetches: &Cell<u32>) -> impl FnMut(PageRequest) -> Ready<Result<Page<u32>, DbError>> + '_ {
+        move |page| {
+            fetches.set(fetches.get() + 1);
+            let items: Vec<u32> = (page.offset..len).take(page.limit as usize).collect();
+            ready(Ok(Page { has_more: page.offset + page.limit < len, items }))
+        }
+    }
+
+    async fn collect<T>(stream: impl futures_core::Stream<Item = Result<T, DbError>>) -> Result<Vec<T>, DbError> {
+        stream.collect::<Vec<_>>().await.into_iter().collect()
+    }
+
+    #[tokio::test]
+    async fn all_pages_should_yield_every_item_once() {
+        // Empty, a single short page, an exact multiple of the page size and one more
+        for (len, expected_fetches) in [(0, 1), (1, 1), (6, 2), (7, 3)] {
+            let fetches = Cell::new(0);
+            let items = collect(all_pages_of(3, offset_listing(len, &fetches))).await.unwrap();
+            assert_eq!(items, (0..len).collect::<Vec<_>>());
+            assert_eq!(fetches.get(), expected_fetches, "pages fetched for {len} items");
+        }
+    }
+
+    #[tokio::test]
+    async fn cursor_pages_should_stop_at_a_short_page() {
+        for (len, expected_fetches) in [(0, 1), (1, 1), (6, 3), (7, 3)] {
+            let fetches = Cell::new(0);
+            let fetch = |after: Option<u32>, limit: u32| {
+                fetches.set(fetches.get() + 1);
+                let start = after.map_or(0, |after| after + 1);
+                ready(Ok::<_, DbError>((start..len).take(limit as usize).collect::<Vec<_>>()))
+            };
+            let items = collect(cursor_pages(3, fetch, |item: &u32| *item)).await.unwrap();
+            assert_eq!(items, (0..len).collect::<Vec<_>>());
+            // A full last page is only known to be last once the next one comes back empty
+            assert_eq!(fetches.get(), expected_fetches, "pages fetched for {len} items");
+        }
+    }
+
+    #[tokio::test]
+    async fn all_pages_should_end_with_the_first_error() {
+        let fetch = |page: PageRequest| match page.offset {
+            0 => ready(Ok(Page { items: vec![1, 2], has_more: true })),
+            _ => ready(Err(DbError::validation(String::from("second page failed")))),
+        };
+        let items: Vec<_> = all_pages_of(2, fetch).collect().await;
+        println!("{:?}", items);
+        assert_eq!(items.len(), 3);
+        let Some(Err(DbError::Validation { .. })) = items.last() else { panic!("Error should be `Validation` variant") };
+    }
+
+    #[tokio::test]
+    async fn all_pages_should_give_up_on_an_endless_listing() {
+        let fetches = Cell::new(0);
+        let fetch = |_| {
+            fetches.set(fetches.get() + 1);
+            ready(Ok(Page { items: vec![()], has_more: true }))
+        };
+        let items: Vec<_> = all_pages_of(1, fetch).collect().await;
+        assert_eq!(fetches.get(), MAX_PAGES);
+        assert_eq!(items.len(), MAX_PAGES as usize + 1);
+        let Some(Err(DbError::Validation { .. })) = items.last() else { panic!("Error should be `Validation` variant") };
+    }
+
+    #[sqlx::test]
+    async fn all_pages_should_finish_when_questions_are_added_while_walking(pool: PgPool) {
+        let clock = Arc::new(SteppingClock::new(Utc::now(), Duration::seconds(1)));
+        let question_dao = QuestionDaoImpl::new(pool).with_clock(clock);
+        let seeded = fixtures::seed_questions(&question_dao, 5).await;
+        let mut questions = pin!(all_pages_of(2, |page| question_dao.get_questions_projected(QuestionFields::TITLE, page)));
+        let mut ids = vec![questions.next().await.unwrap().unwrap().id()];
+        // Listed after the seeded questions, so it is reached by the walk without repeating any
+        let added = question_dao.create_question(fixtures::question().build()).await.unwrap();
+        while let Some(question) = questions.next().await {
+            ids.push(question.unwrap().id());
+        }
+        let mut expected = seeded;
+        expected.push(added.id());
+        assert_eq!(ids, expected);
+    }
+
+    #[sqlx::test]
+    async fn cursor_pages_should_walk_the_activity_feed(pool: PgPool) {
+        let clock = Arc::new(SteppingClock::new(Utc::now(), Duration::seconds(1)));
+        let question_dao = QuestionDaoImpl::new(pool).with_clock(clock);
+        fixtures::seed_questions(&question_dao, 5).await;
+        let feed = cursor_pages(2, |before, limit| question_dao.get_activity_feed(limit, before), ActivityItem::cursor);
+        let items = collect(feed).await.unwrap();
+        assert_eq!(items.len(), 5);
+        assert!(items.windows(2).all(|pair| pair[0].created_at() > pair[1].created_at()));
+    }
+
+    #[sqlx::test]
+    async fn cursor_pages_should_not_skip_items_posted_at_once(pool: PgPool) {
+        let clock = Arc::new(FixedClock::new(Utc::now()));
+        let question_dao = QuestionDaoImpl::new(pool).with_clock(clock);
+        let mut seeded = fixtures::seed_questions(&question_dao, 5).await;
+        let feed = cursor_pages(2, |before, limit| question_dao.get_activity_feed(limit, before), ActivityItem::cursor);
+        let res = collect(feed).await;
+        println!("{:?}", res);
+        let mut ids: Vec<Uuid> = res.unwrap().iter().map(|item| item.cursor().id).collect();
+        ids.sort();
+        seeded.sort();
+        assert_eq!(ids, seeded);
+    }
 }
@@ -0,0 +1,97 @@
+//! Contains `QueryCounter`, which counts the SQL statements a future executes so tests can put a budget on the
+//! number of round trips a data access method makes, or records them so tests can check what they read, available
+//! with the `testing` feature.
+//!
+//! Statements are counted from the events sqlx logs for each statement, so the counter installs itself as the
+//! `log` logger on first use. It cannot count if the process installs another logger, or a `tracing` subscriber,
+//! which would receive the events instead.
+//!
+//! # Example
+//! ```no_run
+//! # async fn example(pool: sqlx::PgPool, question_id: sqlx::types::Uuid) {
+//! use question_answer::models::EntityId;
+//! use question_answer::persistence::{QuestionDao, QuestionDaoImpl};
+//! use question_answer::query_counter::QueryCounter;
+//!
+//! let question_dao = QuestionDaoImpl::new(pool);
+//! let (_, statements) = QueryCounter::count(question_dao.get_question(EntityId::uuid(question_id))).await;
+//! assert_eq!(statements, 1);
+//! # }
+//! ```
+
+use std::cell::RefCell;
+use std::future::Future;
+use std::sync::Once;
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// The target of the events sqlx logs for every statement it executes.
+const QUERY_TARGET: &str = "sqlx::query";
+
+tokio::task_local! {
+    /// The statements executed by the task being counted, as sqlx logged them.
+    static STATEMENTS: RefCell<Vec<String>>;
+}
+
+/// Counts the SQL statements executed while a future runs.
+///
+/// Only statements executed by the task polling the future are counted, so concurrent tests do not affect each
+/// other's counts, while statements of tasks the future spawns are not counted.
+#[derive(Debug, Clone, Copy)]
+pub struct QueryCounter;
+
+impl QueryCounter {
+    /// Runs `future`, returning its output along with the number of SQL statements it executed. Beginning and
+    /// committing a transaction count as statements.
+    ///
+    /// # Panics
+    /// Panics if another `log` logger has been installed, as the statements could not be counted.
+    pub async fn count<F: Future>(future: F) -> (F::Output, usize) {
+        let (output, statements) = Self::record(future).await;
+        (output, statements.len())
+    }
+
+    /// Runs `future`, returning its output along with the SQL statements it executed, in order. Each statement is
+    /// the message sqlx logged for it, which names the first words of the statement followed by the whole statement
+    /// when it is longer, so the columns it reads can be checked.
+    ///
+    /// # Panics
+    /// Panics if another `log` logger has been installed, as the statements could not be recorded.
+    pub async fn record<F: Future>(future: F) -> (F::Output, Vec<String>) {
+        install();
+        STATEMENTS
+            .scope(RefCell::new(Vec::new()), async {
+                let output = future.await;
+                (output, STATEMENTS.with(RefCell::take))
+            })
+            .await
+    }
+}
+
+/// The logger recording the statement events of the task being counted, ignoring every other record.
+struct StatementLogger;
+
+impl Log for StatementLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target() == QUERY_TARGET
+    }
+
+    fn log(&self, record: &Record) {
+        if record.target() == QUERY_TARGET {
+            // Statements executed outside of `QueryCounter::record` are not recorded
+            let _ = STATEMENTS.try_with(|statements| statements.borrow_mut().push(record.args().to_string()));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Installs `StatementLogger` as the logger, once.
+fn install() {
+    static INSTALL: Once = Once::new();
+    static LOGGER: StatementLogger = StatementLogger;
+    INSTALL.call_once(|| {
+        log::set_logger(&LOGGER).expect("QueryCounter requires that no other logger is installed");
+        // sqlx logs statements at the debug level by default, and slow statements at the warn level
+        log::set_max_level(LevelFilter::Debug);
+    });
+}
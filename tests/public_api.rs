@@ -0,0 +1,558 @@
+//! Exercises the intended public API through the crate's preludes and public modules only, so that
+//! accidentally privatizing or renaming any part of it fails to compile.
+
+use std::path::Path;
+use std::sync::Arc;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::types::Uuid;
+use question_answer::admin::{AdminError, EngagementMode, ExportReport, ImportReport, QaAdmin, ReindexReport};
+use question_answer::clock::{FixedClock, SteppingClock};
+use question_answer::models::dto::{
+    parse_body, parse_question_patch, parse_question_patch_body, parse_request, to_json, write_ndjson, write_questions_csv, AnswerResponse, BodyError, BodyErrorCode, BodyLimits, BountyResponse, CountFormat, InputMode, ListFormat, QuestionDetailResponse,
+    QuestionHeaderResponse, QuestionResponse, RequestFields,
+};
+use question_answer::models::content_stats::ContentStats;
+use question_answer::models::diff::{diff_revisions, DiffHunk, DiffLine};
+use question_answer::models::links::{CursorSigner, PageLinks, CURSOR_PARAM, DEFAULT_CURSOR_MAX_AGE_HOURS, LIMIT_PARAM, OFFSET_PARAM};
+use question_answer::models::normalize::{normalize, normalize_title};
+use question_answer::models::period::Period;
+use question_answer::models::policy::{ContentKind, ContentPolicy, DenyListPolicy, PolicyDecision};
+use question_answer::persistence::cache::{CacheConfig, CacheOutcome, CachedQuestionDao, DEFAULT_CACHE_CAPACITY, DEFAULT_MAX_STALENESS_MINUTES};
+use question_answer::persistence::integrity::{self, IntegrityReport, RepairReport};
+use question_answer::persistence::lock::{with_advisory_lock, LockOutcome, MaintenanceLock};
+use question_answer::persistence::metrics::{DaoMetrics, LATENCY_BUCKETS};
+use question_answer::persistence::migrations::{self, MigrationError, ResetOptions};
+use question_answer::persistence::observer::{DaoEvent, EntityKind, QueryObserver};
+use question_answer::persistence::paginate::{all_pages, all_pages_of, cursor_pages, MAX_PAGES};
+use question_answer::persistence::pool::{self, PoolConfig, PoolMetrics};
+use question_answer::persistence::pool_monitor::{self, AcquireWaits, PoolMonitor, PoolSnapshot};
+use question_answer::persistence::prelude::*;
+use question_answer::persistence::schema::SchemaName;
+
+/// Uses every data access object, only needs to compile.
+#[allow(dead_code)]
+async fn use_daos(pool: PgPool) -> Result<(), DbError> {
+    let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+    let question_dao = QuestionDaoImpl::new(pool.clone())
+        .with_clock(clock.clone())
+        .with_limits(ContentLimits::default())
+        .with_delete_policy(DeletePolicy::Restrict)
+        .with_max_pinned(DEFAULT_MAX_PINNED)
+        .with_view_window(Duration::minutes(DEFAULT_VIEW_WINDOW_MINUTES))
+        .with_moderation(ModerationMode::Off)
+        .with_quota(CreationQuota { max_questions_per_hour: 5, max_answers_per_hour: 20 })
+        .with_reputation(ReputationConfig::default());
+    let answer_dao = AnswerDaoImpl::new(pool.clone())
+        .with_clock(clock.clone())
+        .with_limits(ContentLimits::default())
+        .with_moderation(ModerationMode::DelayMinutes(10))
+        .with_quota(CreationQuota { max_questions_per_hour: 5, max_answers_per_hour: 20 })
+        .with_reputation(ReputationConfig { question_liked: 5, answer_liked: 10, answer_accepted: 15 });
+    let subscription_dao = SubscriptionDaoImpl::new(pool.clone()).with_clock(clock.clone());
+    let stats_dao = StatsDaoImpl::new(pool.clone()).with_clock(clock);
+    let admin_dao = AdminDaoImpl::new(pool.clone());
+    let category_dao = CategoryDaoImpl::new(pool.clone()).with_limits(ContentLimits::default());
+
+    let new_question = NewQuestion { title: String::from("Title"), question: String::from("Question"), external_id: None, content_type: None, author_token: None };
+    let question: Question = question_dao.create_question(new_question).await?;
+    let question_id = || EntityId::new(question.id().to_string());
+    let _: Question = question_dao.get_question(question_id()).await?;
+    let _: Question = question_dao.get_question_as_of(question_id(), Utc::now()).await?;
+    let _: Vec<DiffHunk> = question_dao.get_revision_diff(question_id(), 1, 2).await?;
+    let _: ContentType = question.content_type();
+    #[cfg(feature = "render")]
+    let _: String = question.render_html();
+    let _: Vec<Question> = question_dao.get_questions().await?;
+    let page: Page<QuestionPartial> = question_dao
+        .get_questions_projected(QuestionFields::TITLE | QuestionFields::LIKES, PageRequest { offset: 0, limit: MAX_PAGE_SIZE })
+        .await?;
+    let _: Vec<Option<&str>> = page.items.iter().map(QuestionPartial::title).collect();
+    let _: i32 = question_dao.get_questions_shorter_than(10).await?.iter().map(Question::word_count).sum();
+    let _: Vec<Question> = question_dao.get_questions_in_period(Period::ThisWeek, chrono_tz::Europe::Berlin).await?;
+    let _: Vec<Question> = question_dao.get_questions_by_like_range(10, None, Some(Utc::now() - Duration::days(7))).await?;
+    for rate in question_dao.get_questions_exceeding_like_rate(20.0, Duration::days(MAX_LIKE_RATE_WINDOW_DAYS)).await? {
+        let _: (&Question, f64, bool) = (&rate.question, rate.likes_per_hour, rate.estimated);
+    }
+    let _: (DateTime<Utc>, DateTime<Utc>) = Period::Today.bounds(Utc::now(), chrono_tz::UTC);
+    let header: QuestionHeader = question_dao.get_question_header(question_id()).await?;
+    let _: i64 = header.answer_count();
+    let _: (bool, Option<Uuid>) = (header.has_accepted_answer(), header.accepted_answer_id());
+    let _: QuestionHeaderResponse = header.into();
+    let detail: QuestionDetail = question_dao.get_question_detail_consistent(question_id()).await?;
+    let token = "a".repeat(MIN_AUTHOR_TOKEN_LENGTH.max(MAX_AUTHOR_TOKEN_LENGTH / 4));
+    question_answer::models::validate_author_token(&token)?;
+    let _: QuestionDetail = question_dao.get_question_detail_for_author(question_id(), &token).await?;
+    let detail_with: QuestionDetail = question_dao.get_question_detail_with(question_id(), DetailOptions::default()).await?;
+    let answer_bodies: AnswerBodyMode = "preview".parse()?;
+    let previews: QuestionDetail = question_dao.get_question_detail_with(question_id(), DetailOptions { answer_bodies, ..DetailOptions::default() }).await?;
+    let previews = QuestionDetailResponse::from_detail(previews, answer_bodies);
+    assert!(previews.answers.iter().all(|answer| answer.truncated || answer.answer.chars().count() <= ANSWER_PREVIEW_CHARS));
+    assert!(detail_with.total_answers().is_some() && DEFAULT_EMBEDDED_ANSWERS <= MAX_PAGE_SIZE);
+    let _: Option<i64> = detail_with.archived_answers();
+    let translation: QuestionTranslation = question_dao
+        .upsert_translation(question_id(), "pt-br", NewTranslation { title: String::from("Título"), question: String::from("Pergunta") })
+        .await?;
+    assert!(SUPPORTED_LANGUAGES.contains(&translation.lang()));
+    let translated: Question = question_dao.get_translation(question_id(), translation.lang()).await?;
+    assert_eq!(translated.lang(), Some("pt-BR"));
+    assert!(question_answer::models::normalize_language("PT-br").is_ok_and(|lang| lang == "pt-BR"));
+    let mine: Vec<Question> = question_dao.get_questions_by_token(&token).await?;
+    let ranking = SearchRankingConfig { likes_weight: 0.5, debug_explain: true, ..SearchRankingConfig::default() };
+    for ranked in question_dao.search_questions("sort vec", ranking, 10).await? {
+        let _: (&Question, f64) = (&ranked.question, ranked.score);
+        let _: Option<(f64, f64, f64, f64)> = ranked.components.map(|c: RankComponents| (c.text_rank, c.likes, c.answers, c.views));
+    }
+    let feed: Vec<ActivityItem> = question_dao.get_activity_feed(MAX_PAGE_SIZE, None).await?;
+    let _: Option<DateTime<Utc>> = feed.last().map(ActivityItem::created_at);
+    let _: Option<bool> = mine.first().and_then(Question::is_mine);
+    let _: Vec<Answer> = answer_dao.get_answers_by_token(&token).await?;
+    let _: (bool, i64, &[Answer]) = (detail.is_snapshot(), detail.header().answer_count(), detail.answers());
+    let (_, _): (QuestionHeader, Vec<Answer>) = detail.into_parts();
+    let update = UpdateQuestion { title: Some(String::from("Edited")), question: None };
+    let _: Question = question_dao.update_question(question_id(), update).await?;
+    let _: Question = question_dao.lock_question(question_id()).await?;
+    let _: Question = question_dao.unlock_question(question_id()).await?;
+    let _: bool = question_dao.pin_question(question_id()).await?.is_pinned();
+    let _: Question = question_dao.unpin_question(question_id()).await?;
+    let _: i64 = question_dao.increment_question_likes(question_id()).await?;
+    let _: ViewOutcome = question_dao.record_view(question_id(), "token").await?;
+    let new_question = NewQuestion { title: String::from("Title"), question: String::from("Question"), external_id: None, content_type: None, author_token: None };
+    let outcome: CreateOutcome = question_dao.create_question_idempotent(new_question, "key").await?;
+    let _: Uuid = outcome.id();
+    let new_question = NewQuestion { title: String::from("Title"), question: String::from("Question"), external_id: Some(String::from("external")), content_type: None, author_token: None };
+    let upserted: UpsertOutcome = question_dao.upsert_question_by_external_id(new_question).await?;
+    let _: Uuid = upserted.id();
+    let _: u64 = question_dao.purge_idempotency_keys(Duration::days(1)).await?;
+    let mut on_progress = |progress: BatchProgress| { let _ = (progress.batch, progress.affected_total); };
+    let _: u64 = question_dao.delete_questions_batched(vec![], 10, false, Some(&mut on_progress)).await?;
+
+    let new_answer = NewAnswer { question_id: question.id().to_string(), answer: String::from("Answer"), author_id: None, content_type: None, author_token: None, parent_answer_id: None };
+    let answer: Answer = answer_dao.create_answer(new_answer).await?;
+    let answer_id = || EntityId::new(answer.id().to_string());
+    let pinned: Question = question_dao.pin_answer(question_id(), answer_id()).await?;
+    let _: Option<Uuid> = pinned.pinned_answer_id();
+    let _: Question = question_dao.unpin_answer(question_id()).await?;
+    let accepted: Question = question_dao.accept_answer(question_id(), answer_id()).await?;
+    let _: Option<Uuid> = accepted.accepted_answer_id();
+    let _: Question = question_dao.unaccept_answer(question_id()).await?;
+    let bountied: Question = question_dao.set_bounty(question_id(), MAX_BOUNTY.min(100), Duration::days(7)).await?;
+    let _: Option<BountyResponse> = BountyResponse::of(&bountied, Utc::now());
+    let _: Option<BountyResponse> = BountyResponse::stored(&bountied);
+    let _: QuestionResponse = QuestionResponse::at(bountied.clone(), Utc::now());
+    let _: Option<Duration> = bountied.bounty_remaining(Utc::now());
+    let _: Question = question_dao.clear_bounty(question_id()).await?;
+    let _: Vec<Uuid> = question_dao.expire_bounties().await?;
+    question_dao.link_questions(question_id(), question_id(), LinkKind::Related).await?;
+    let _: bool = question_dao.unlink_questions(question_id(), question_id(), LinkKind::DuplicateOf).await?;
+    let _: Vec<(LinkKind, Question)> = question_dao.get_linked_questions(question_id()).await?;
+    let _: bool = LinkKind::FollowUp.is_symmetric();
+    let _: Answer = answer_dao.get_answer(answer_id()).await?;
+    let _: &str = answer.content_type().as_str();
+    #[cfg(feature = "render")]
+    let _: String = question_answer::models::render::render_html(answer.answer(), ContentType::Markdown);
+    let _: Vec<Answer> = answer_dao.get_answers(question_id()).await?;
+    let _: Vec<Answer> = answer_dao.get_answers_sorted(question_id(), AnswerSort::Newest, DEFAULT_ANSWER_LIMIT).await?;
+    let first: AnswerPage = answer_dao.get_answers_paged(question_id(), 10, None).await?;
+    let _: Vec<Answer> = answer_dao.get_answers_paged(question_id(), 10, first.page_token.as_deref()).await?.answers;
+    let _: Vec<AnswerWithAuthor> = answer_dao.get_answers_with_authors(question_id()).await?;
+    let _: Vec<AnswerThread> = answer_dao.get_answer_threads(question_id()).await?;
+    let _: Vec<Answer> = answer_dao.search_answers(question_id(), "term").await?;
+    let results: Vec<AnswerWithQuestion> = answer_dao.search_all_answers("term", 20).await?;
+    let _: Option<&str> = results.first().and_then(AnswerWithQuestion::question_title);
+    let _: Vec<Answer> = answer_dao.get_all_answers().await?;
+    let page: Page<AnswerWithQuestion> = answer_dao.get_all_answers_with_question(PageRequest { offset: 0, limit: MAX_PAGE_SIZE }).await?;
+    let _: Option<&str> = page.items.first().and_then(AnswerWithQuestion::question_title);
+    let _: i64 = answer_dao.increment_answer_likes(answer_id()).await?;
+    let target = LikeTarget { entity: LikableEntity::Answer, id: answer.id().to_string() };
+    let _: i64 = like_entity(&question_dao, &answer_dao, target).await?;
+    let _: VoteOutcome = answer_dao.vote_answer(answer_id(), AnswerVote::Helpful, "token").await?;
+    let _: i64 = answer_dao.get_answer(answer_id()).await?.score();
+    let new_answer = NewAnswer { question_id: question.id().to_string(), answer: String::from("Draft"), author_id: None, content_type: None, author_token: None, parent_answer_id: None };
+    let draft: Answer = answer_dao.create_answer_draft(new_answer).await?;
+    let _: Vec<Answer> = answer_dao.get_drafts(question_id(), EntityId::uuid(Uuid::new_v4())).await?;
+    let _: bool = answer_dao.publish_answer(EntityId::uuid(draft.id())).await?.is_published();
+    let _: Option<DateTime<Utc>> = answer_dao.approve_answer(EntityId::uuid(draft.id())).await?.approved_at();
+    let _: Uuid = answer_dao.reject_answer(EntityId::uuid(draft.id())).await?;
+    let archived: ArchiveReport = answer_dao.archive_stale_answers(Duration::days(365), 100).await?;
+    let _: u64 = archived.answers();
+    let tombstone: Answer = answer_dao.delete_answer_with_tombstone(answer_id(), Some(String::from("spam"))).await?;
+    let _: (bool, Option<DateTime<Utc>>, Option<&str>, usize) =
+        (tombstone.is_deleted(), tombstone.deleted_at(), tombstone.deleted_reason(), MAX_DELETED_REASON_LENGTH);
+    let _: Uuid = answer_dao.delete_answer(answer_id()).await?;
+
+    subscription_dao.subscribe("token", question_id()).await?;
+    let _: Vec<QuestionUpdate> = subscription_dao.get_updates("token").await?;
+    subscription_dao.unsubscribe("token", question_id()).await?;
+
+    let _: Vec<DailyActivity> = stats_dao.get_daily_activity(7).await?;
+    let _: Totals = stats_dao.get_totals().await?;
+    let _: LatencyStats = stats_dao.get_time_to_first_answer(Utc::now() - Duration::days(7)).await?;
+
+    admin_dao.set_question_likes(question_id(), 1).await?;
+    admin_dao.set_answer_likes(answer_id(), 1).await?;
+    let _: BulkUpdate = admin_dao.bulk_set_question_likes(vec![(question_id(), 2)]).await?;
+    let _: BulkUpdate = admin_dao.bulk_set_answer_likes(vec![(answer_id(), 2)]).await?;
+    let _: BulkUpdate = admin_dao.bulk_set_question_views(vec![(question_id(), 10)]).await?;
+    let records: Vec<ImportRecord> = admin_dao.import_content(Vec::<QuestionDetailResponse>::new(), true).await?;
+    let _: Option<&ImportVerdict> = records.first().map(|record| &record.verdict);
+    let _: AnonymizeReport = admin_dao.anonymize_author(EntityId::uuid(Uuid::new_v4()), true).await?;
+    admin_dao.transfer_question_author(question_id(), EntityId::uuid(Uuid::new_v4())).await?;
+    admin_dao.transfer_answer_author(answer_id(), EntityId::uuid(Uuid::new_v4())).await?;
+    let _: TransferReport = admin_dao.transfer_all_content(EntityId::uuid(Uuid::new_v4()), EntityId::uuid(Uuid::new_v4())).await?;
+    let _: Option<Uuid> = question.author_id();
+    let _: MergeReport = admin_dao.merge_questions(EntityId::serial(1), question_id()).await?;
+    let _: Vec<&'static str> = admin_dao.reindex().await?;
+    let _: u64 = admin_dao.recompute_content_stats().await?;
+    let events: Vec<LikeEvent> = admin_dao.get_like_events(answer_id(), Utc::now() - Duration::days(1)).await?;
+    let _ = events.iter().map(|event| (event.entity_type(), event.delta(), event.occurred_at(), event.source_token()));
+    let purged: PurgeReport = admin_dao.purge_deleted_answers(Utc::now() - Duration::days(30)).await?;
+    let _: (DateTime<Utc>, u64, u64, String) = (purged.before, purged.answers, purged.kept_with_replies, purged.to_string());
+    let recounted: RecountReport = admin_dao.recount_likes().await?;
+    let _: (u64, u64, String) = (recounted.questions, recounted.answers, recounted.to_string());
+
+    let new_category = NewCategory { name: String::from("Programming"), parent_id: None };
+    let category: Category = category_dao.create_category(new_category).await?;
+    let category_id = || EntityId::uuid(category.id());
+    let _: Category = category_dao.set_category_parent(category_id(), None).await?;
+    let tree: Vec<CategoryNode> = category_dao.get_category_tree().await?;
+    let _: &[CategoryNode] = &tree[0].children;
+    let _: Question = category_dao.assign_category(question_id(), Some(category_id())).await?;
+    let _: Vec<Question> = category_dao.get_questions_in_category(category_id(), true).await?;
+
+    let _: Uuid = question_dao.delete_question(question_id(), true).await?;
+    let _: Uuid = question_dao.delete_question_with_policy(EntityId::serial(1), DeletePolicy::Orphan).await?;
+    let _: QuestionDetailResponse = QuestionDetailResponse::new(question, vec![answer]);
+
+    let scope = ScopedDao::begin(&pool).await?.with_clock(Arc::new(SystemClock)).with_limits(ContentLimits::default());
+    let _: Vec<Question> = scope.get_questions().await?;
+    let _: Vec<Answer> = scope.get_all_answers().await?;
+    scope.commit().await?;
+    ScopedDao::begin(&pool).await?.rollback().await?;
+    Ok(())
+}
+
+/// Uses the administrative facade, only needs to compile.
+#[allow(dead_code)]
+async fn use_admin(url: &str) -> Result<(), AdminError> {
+    let admin = QaAdmin::connect(url).await?.with_clock(Arc::new(SystemClock));
+    let reports: [Box<dyn std::fmt::Display>; 6] = [
+        Box::new(admin.stats().await?),
+        Box::new(admin.merge(EntityId::serial(1), EntityId::serial(2)).await?),
+        Box::new(admin.export("export.json").await?),
+        Box::new(admin.reindex().await?),
+        Box::new(admin.purge_deleted(Duration::days(30)).await?),
+        Box::new(admin.recount_likes().await?),
+    ];
+    let _: ExportReport = admin.export("export.json").await?;
+    let _: ReindexReport = admin.reindex().await?;
+    let _: ExportReport = admin.export_engagement("engagement.ndjson").await?;
+    let imported: ImportReport = admin.import_all("export.json", Some(Path::new("engagement.ndjson")), EngagementMode::Preserve, false).await?;
+    let _: Vec<(LikableEntity, Uuid)> = imported.missing;
+    let _ = reports.map(|report| report.to_string());
+    Ok(())
+}
+
+/// Uses the pool helpers, only needs to compile.
+#[allow(dead_code)]
+async fn use_pool(url: &str) -> Result<(), DbError> {
+    let pool = pool::connect(url, PoolConfig { warm_up: true, ..PoolConfig::default() }).await?;
+    pool::warm_up(&pool, 2).await?;
+    let metrics: PoolMetrics = pool::pool_metrics(&pool);
+    let _: u32 = metrics.in_use();
+    let _: String = metrics.prometheus_export("primary");
+    let _: PgPool = pool::connect_with((*pool.connect_options()).clone(), PoolConfig { lazy: true, ..PoolConfig::default() }).await?;
+    let _: PgPool = pool::connect(url, PoolConfig { auto_migrate: true, ..PoolConfig::default() }).await?;
+    let _: PgPool = pool::connect(url, PoolConfig { skip_schema_check: true, ..PoolConfig::default() }).await?;
+    let monitor: PoolMonitor = pool_monitor::spawn(pool.clone(), std::time::Duration::from_secs(15), pool_monitor::log_observer("primary"))?;
+    let waits: Arc<AcquireWaits> = monitor.acquire_waits();
+    waits.record(std::time::Duration::from_millis(5));
+    let _ = QuestionDaoImpl::new(pool.clone()).with_acquire_waits(waits.clone());
+    let _ = AnswerDaoImpl::new(pool.clone()).with_acquire_waits(waits);
+    monitor.shutdown().await;
+    let _: PoolMonitor = pool_monitor::spawn(pool.clone(), std::time::Duration::from_secs(15), pool_monitor::tracing_observer("primary"))?;
+    let _ = pool_monitor::spawn(pool, std::time::Duration::from_secs(15), |snapshot: PoolSnapshot| {
+        let _: (PoolMetrics, Option<std::time::Duration>, Option<std::time::Duration>, String) =
+            (snapshot.metrics, snapshot.saturated_for, snapshot.acquire_wait, snapshot.prometheus_export("primary"));
+    })?;
+    Ok(())
+}
+
+/// Uses the migration helpers, only needs to compile.
+#[allow(dead_code)]
+async fn use_migrations(pool: PgPool) -> Result<(), MigrationError> {
+    migrations::run(&pool).await?;
+    migrations::reset(&pool, ResetOptions { allow_destructive: true, force: false }).await
+}
+
+/// Walks the paged listings, only needs to compile.
+#[allow(dead_code)]
+async fn use_paginate(question_dao: QuestionDaoImpl) -> Result<(), DbError> {
+    use futures_util::StreamExt;
+    let _: Vec<Result<QuestionPartial, DbError>> = all_pages(|page| question_dao.get_questions_projected(QuestionFields::TITLE, page)).collect().await;
+    let _ = all_pages_of(10, |page| question_dao.get_questions_projected(QuestionFields::NONE, page));
+    let _ = cursor_pages(10, |before, limit| question_dao.get_activity_feed(limit, before), |item: &ActivityItem| item.cursor());
+    let _: u32 = MAX_PAGES;
+    Ok(())
+}
+
+/// Reads questions through the cache, only needs to compile.
+#[allow(dead_code)]
+async fn use_cache(pool: PgPool, id: EntityId) -> Result<(), DbError> {
+    let config = CacheConfig {
+        serve_stale_on_error: true,
+        max_staleness: Duration::minutes(DEFAULT_MAX_STALENESS_MINUTES),
+        capacity: DEFAULT_CACHE_CAPACITY,
+        ..CacheConfig::default()
+    };
+    let question_dao = CachedQuestionDao::new(QuestionDaoImpl::new(pool)).with_config(config).with_clock(Arc::new(FixedClock::new(Utc::now())));
+    let CacheOutcome { value, stale }: CacheOutcome<Question> = question_dao.get_question_cached(id.clone()).await?;
+    let _: (Question, bool) = (value, stale);
+    let _: Question = question_dao.get_question(id.clone()).await?;
+    question_dao.invalidate(&id);
+    let _: (usize, bool) = (question_dao.len(), question_dao.is_empty());
+    let _: &QuestionDaoImpl = question_dao.inner();
+    Ok(())
+}
+
+/// Links the pages around a page of a listing, only needs to compile.
+#[allow(dead_code)]
+fn use_links(page: Page<ActivityItem>, request: PageRequest) -> Result<(), DbError> {
+    let signer = CursorSigner::new("secret").with_max_age(Duration::hours(DEFAULT_CURSOR_MAX_AGE_HOURS)).with_clock(Arc::new(FixedClock::new(Utc::now())));
+    let token: String = signer.sign(&Utc::now());
+    let _: DateTime<Utc> = signer.verify(&token)?;
+    let links: PageLinks = PageLinks::keyset("/feed", &page, &signer, |item| item.created_at());
+    let PageLinks { next, prev, total_count } = PageLinks::offset("/questions", request, &page, 10);
+    let _: (Option<String>, Option<String>, Option<u64>) = (next, prev, total_count);
+    let _: (Option<String>, Option<String>) = (links.link_header(), links.total_count_header());
+    let _: [&str; 3] = [CURSOR_PARAM, OFFSET_PARAM, LIMIT_PARAM];
+    Ok(())
+}
+
+/// Observes the calls of every data access object, only needs to compile.
+#[allow(dead_code)]
+fn use_observer(pool: PgPool) {
+    struct Observer;
+
+    impl QueryObserver for Observer {
+        fn on_complete(&self, event: DaoEvent) {
+            let DaoEvent { method, entity, id, duration, rows, error } = event;
+            let _: (&str, EntityKind, Option<String>, std::time::Duration, Option<u64>, Option<DbErrorKind>) = (method, entity, id, duration, rows, error);
+        }
+    }
+
+    let observer: Arc<dyn QueryObserver> = Arc::new(Observer);
+    let _ = QuestionDaoImpl::new(pool.clone()).with_observer(observer.clone());
+    let _ = AnswerDaoImpl::new(pool.clone()).with_observer(observer);
+
+    let metrics = Arc::new(DaoMetrics::default());
+    let _ = QuestionDaoImpl::new(pool.clone()).with_observer(metrics.clone());
+    let _ = AnswerDaoImpl::new(pool).with_observer(metrics.clone());
+    let _: String = metrics.prometheus_export();
+    let _: u64 = metrics.calls("get_question", "ok");
+    let _: [f64; 12] = LATENCY_BUCKETS;
+}
+
+/// Uses the data access objects within a schema, only needs to compile.
+#[allow(dead_code)]
+async fn use_schema(pool: PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    let schema = SchemaName::new("qa")?;
+    migrations::run_in_schema(&pool, &schema).await?;
+    let _: &str = schema.as_str();
+    let _ = QuestionDaoImpl::new(pool.clone()).with_schema(schema.clone());
+    let _ = AnswerDaoImpl::new(pool.clone()).with_schema(schema.clone());
+    let _ = SubscriptionDaoImpl::new(pool.clone()).with_schema(schema.clone());
+    let _ = StatsDaoImpl::new(pool.clone()).with_schema(schema.clone());
+    let _ = AdminDaoImpl::new(pool.clone()).with_schema(schema.clone());
+    let _ = CategoryDaoImpl::new(pool.clone()).with_schema(schema.clone());
+    let _ = TagDaoImpl::new(pool.clone()).with_schema(schema.clone());
+    let _ = LikeDaoImpl::new(pool.clone()).with_schema(schema.clone());
+    let _ = AuthorDaoImpl::new(pool.clone()).with_schema(schema.clone());
+    let _ = QaAdmin::new(pool.clone()).with_schema(schema.clone());
+    let report: IntegrityReport = integrity::check_integrity_in_schema(&pool, &schema).await?;
+    let _: RepairReport = integrity::repair_in_schema(&pool, &schema, &report, DeletePolicy::Orphan).await?;
+    let _: Option<RepairReport> = integrity::repair_guarded_in_schema(&pool, &schema, &report, DeletePolicy::Orphan).await?.ran();
+    ScopedDao::begin(&pool).await?.with_schema(schema).rollback().await?;
+    Ok(())
+}
+
+/// Uses the integrity checks, only needs to compile.
+#[allow(dead_code)]
+async fn use_integrity(pool: PgPool) -> Result<(), DbError> {
+    let report: IntegrityReport = integrity::check_integrity(&pool).await?;
+    if !report.is_clean() {
+        let repaired: RepairReport = integrity::repair(&pool, &report, DeletePolicy::Orphan).await?;
+        let _: (u64, Vec<Uuid>) = (repaired.likes_reset, repaired.answers_left);
+        let _: Option<RepairReport> = integrity::repair_guarded(&pool, &report, DeletePolicy::Orphan).await?.ran();
+    }
+    Ok(())
+}
+
+/// Uses the maintenance guard, only needs to compile.
+#[allow(dead_code)]
+async fn use_lock(pool: PgPool) -> Result<(), DbError> {
+    let outcome: LockOutcome<u64> = with_advisory_lock(&pool, MaintenanceLock::PurgeIdempotencyKeys, || async { Ok(0) }).await?;
+    let _: bool = outcome.is_skipped();
+    let _: i32 = MaintenanceLock::Reindex.key();
+    let _: LockOutcome<u64> = QuestionDaoImpl::new(pool.clone()).purge_idempotency_keys_guarded(Duration::days(1)).await?;
+    let admin_dao = AdminDaoImpl::new(pool);
+    let _: LockOutcome<u64> = admin_dao.recompute_content_stats_guarded().await?;
+    let _: LockOutcome<Vec<&'static str>> = admin_dao.reindex_guarded().await?;
+    Ok(())
+}
+
+/// Applies a batch of likes, only needs to compile.
+#[allow(dead_code)]
+async fn use_like_batches(pool: PgPool, question_id: Uuid) -> Result<(), DbError> {
+    let like_dao = LikeDaoImpl::new(pool).with_clock(Arc::new(SystemClock));
+    let op = LikeOp { entity: LikableEntity::Question, id: EntityId::uuid(question_id), op: LikeAction::Like, client_ts: Utc::now() };
+    let report: LikeBatchReport = like_dao.apply_like_batch("user", vec![op]).await?;
+    for outcome in &report.outcomes {
+        let _: bool = matches!(outcome, LikeOutcome::Applied | LikeOutcome::AlreadyApplied | LikeOutcome::EntityMissing | LikeOutcome::Invalid(_));
+    }
+    let _: usize = report.applied();
+    Ok(())
+}
+
+/// Ranks users by reputation, only needs to compile.
+#[allow(dead_code)]
+async fn use_reputation(pool: PgPool) -> Result<(), DbError> {
+    let _ = LikeDaoImpl::new(pool.clone()).with_reputation(ReputationConfig::default());
+    let author_dao = AuthorDaoImpl::new(pool);
+    for user in author_dao.get_top_users_by_reputation(10).await? {
+        let _: (Uuid, String, i32) = (user.id, user.username, user.reputation);
+    }
+    Ok(())
+}
+
+/// Uses tags, only needs to compile.
+#[allow(dead_code)]
+async fn use_tags(pool: PgPool, question_id: Uuid) -> Result<(), DbError> {
+    let tag_dao = TagDaoImpl::new(pool).with_clock(Arc::new(SystemClock));
+    let tag: Tag = tag_dao.tag_question(EntityId::uuid(question_id), "rust").await?;
+    let _: (Uuid, &str, DateTime<Utc>) = (tag.id(), tag.name(), tag.created_at());
+    let _: bool = tag_dao.untag_question(EntityId::uuid(question_id), "rust").await?;
+    for suggestion in tag_dao.suggest_tags("ru", 10).await? {
+        let _: (String, i64) = (suggestion.name, suggestion.usage_count);
+    }
+    for stats in tag_dao.get_tag_stats().await? {
+        let _: Option<DateTime<Utc>> = stats.last_used_at;
+    }
+    for acceptance in tag_dao.get_acceptance_rate_by_tag(5).await? {
+        let _: (String, i64, i64, f64) = (acceptance.name, acceptance.question_count, acceptance.accepted_count, acceptance.acceptance_rate);
+    }
+    let report: RetagReport = tag_dao.rename_tag("rust-lang", "rust").await?;
+    let _: (u64, u64) = (report.questions_affected, report.already_tagged);
+    let _: usize = MAX_TAG_LENGTH;
+    Ok(())
+}
+
+/// Uses content policies, only needs to compile.
+#[allow(dead_code)]
+async fn use_policy(pool: PgPool) -> Result<(), DbError> {
+    let policy: Arc<dyn ContentPolicy> = Arc::new(DenyListPolicy::new(["spam"]).flagging());
+    let _: PolicyDecision = policy.check(ContentKind::Answer, "spam");
+    let _ = QuestionDaoImpl::new(pool.clone()).with_policy(policy.clone());
+    let _ = AnswerDaoImpl::new(pool.clone()).with_policy(policy.clone());
+    let _ = ScopedDao::begin(&pool).await?.with_policy(policy);
+    let _ = DbError::from(ValidationReason::PolicyViolation(String::from("spam")));
+    Ok(())
+}
+
+/// Uses the blocking clients, only needs to compile.
+#[cfg(feature = "blocking")]
+#[allow(dead_code)]
+fn use_blocking(url: &str) -> Result<(), DbError> {
+    use question_answer::blocking::{AnswerClient, QuestionClient};
+    let questions = QuestionClient::connect(url, PoolConfig::default())?;
+    let answers = AnswerClient::connect(url, PoolConfig::default())?;
+    let question: Question = questions.create_question(NewQuestion { title: String::from("Title"), question: String::from("Question"), external_id: None, content_type: None, author_token: None })?;
+    let _: Vec<Question> = questions.get_questions()?;
+    let _: Vec<Answer> = answers.get_answers(EntityId::new(question.id().to_string()))?;
+    Ok(())
+}
+
+#[test]
+fn models_should_be_usable_without_a_database() {
+    let limits = ContentLimits::default();
+    let new_question = NewQuestion { title: String::from("Title"), question: String::from("Question"), external_id: None, content_type: None, author_token: None };
+    assert!(new_question.validate(&limits).is_ok());
+    let new_answer = NewAnswer { question_id: Uuid::new_v4().to_string(), answer: String::from("Answer"), author_id: None, content_type: None, author_token: None, parent_answer_id: None };
+    assert!(new_answer.validate(&limits).is_ok());
+    assert!(UpdateQuestion::default().validate(&limits).is_err());
+    let parsed: NewQuestion = parse_request(r#"{"title": " Title ", "question": "Question"}"#, InputMode::Strict).unwrap();
+    assert_eq!(parsed.title, "Title");
+    assert_eq!(InputMode::default(), InputMode::Lenient);
+    assert!(NewAnswer::FIELDS.contains(&"answer"));
+    let patch: UpdateQuestion = parse_question_patch(r#"{"title": "Title"}"#, InputMode::Strict).unwrap();
+    assert!(patch.validate(&limits).is_ok());
+    let body_limits = BodyLimits::from(limits);
+    assert!(body_limits.check_question(1024).is_ok() && body_limits.check_answer(body_limits.answer + 1).is_err());
+    let parsed: NewAnswer = parse_body(br#"{"question_id": "1", "answer": "Answer"}"#, InputMode::Lenient, body_limits.answer).unwrap();
+    assert_eq!(parsed.answer, "Answer");
+    let err: BodyError = parse_question_patch_body(b"{\"title\": [", InputMode::Strict, body_limits.question).unwrap_err();
+    assert_eq!((err.code, err.path.as_str()), (BodyErrorCode::TruncatedJson, "$.title[0]"));
+    assert_eq!(normalize_title("Café"), normalize("CAFE", true));
+    assert_eq!(ContentStats::of("Two words"), ContentStats { char_count: 9, word_count: 2 });
+    let hunks: Vec<DiffHunk> = diff_revisions("a\nb", "a\nc");
+    assert_eq!(hunks[0].lines[1], DiffLine::Removed(String::from("b")));
+    assert_eq!((hunks[0].old_start, hunks[0].old_lines, hunks[0].new_start, hunks[0].new_lines), (1, 2, 1, 2));
+
+    let id = Uuid::new_v4();
+    let entity_id = EntityId::new(id.to_string());
+    assert_eq!(Uuid::try_from(&entity_id), Ok(id));
+    assert_eq!(entity_id.as_str(), id.to_string());
+    assert_eq!(EntityId::uuid(id).kind(), Ok(EntityIdKind::Uuid(id)));
+    assert_eq!(EntityId::serial(7).kind(), Ok(EntityIdKind::Serial(7)));
+
+    assert_eq!("newest".parse::<AnswerSort>().unwrap(), AnswerSort::Newest);
+    assert_eq!(AnswerSort::default(), AnswerSort::MostLiked);
+
+    let question = Question::new(id, String::from("Title"), String::from("Question"), 0, Utc::now());
+    assert!(!question.is_locked());
+    let response = QuestionResponse::from(question);
+    assert_eq!(response.id, id.to_string());
+    let _: i64 = response.likes;
+    assert!(to_json(&response, CountFormat::StringWhenUnsafe).unwrap()["likes"].is_number());
+    let _: fn(Answer) -> AnswerResponse = AnswerResponse::from;
+    let format = ListFormat::negotiate(Some("text/csv")).unwrap();
+    assert_eq!((format.media_type(), format.content_disposition("questions").is_some()), ("text/csv", true));
+    let mut csv = Vec::new();
+    write_questions_csv(&mut csv, [response.clone()]).unwrap();
+    write_ndjson(std::io::sink(), [response], CountFormat::Number).unwrap();
+
+    assert!(DbError::from(ConflictReason::HasDependents { count: 2 }).to_string().contains('2'));
+    let mismatch = DbError::from(InternalReason::SchemaMismatch { expected: 2, found: 1, missing: vec![String::from("2_add_column")] });
+    assert!(mismatch.to_string().contains("2_add_column"));
+    let error = DbError::from(ConflictReason::Locked(id));
+    assert!(error.to_string().contains(&id.to_string()));
+    let _: &dyn std::error::Error = &error;
+    assert!(error.debug_details().is_none());
+    assert!(!error.is_transient());
+    assert_eq!(error.kind(), DbErrorKind::Conflict);
+    let DbError::Conflict { reason: ConflictReason::Locked(_), source: None } = error else { unreachable!() };
+    let error = DbError::validation("title is too long").caused_by(std::io::Error::from(std::io::ErrorKind::Other)).stopped_after(2);
+    assert_eq!(error.partial_batch().map(|batch| batch.completed), Some(2));
+    let _: Option<&BoxError> = match &error {
+        DbError::NotFound { entity, id, source } => Some(entity).zip(id.as_ref()).and(source.as_ref()),
+        DbError::InvalidId { reason, source } => Some(reason).and(source.as_ref()),
+        DbError::Conflict { source, .. } | DbError::Validation { source, .. } => source.as_ref(),
+        DbError::Unavailable { reason: UnavailableReason::QuotaExceeded { retry_after }, source } => Some(retry_after).and(source.as_ref()),
+        DbError::Unavailable { source, .. } | DbError::Internal { source, .. } => source.as_ref(),
+    };
+    assert_eq!(DbError::from(sqlx::Error::RowNotFound).kind(), DbErrorKind::NotFound);
+    assert_eq!(DbError::conflict("key reused").kind(), DbErrorKind::Conflict);
+    assert_eq!(DbError::invalid_id("not a uuid").kind(), DbErrorKind::InvalidId);
+    let error: DbErrorContext = DbError::not_found("question", id).caused_by(sqlx::Error::RowNotFound).with_context("question", id);
+    assert!(error.to_string().contains("question") && std::error::Error::source(&error).is_some());
+    #[allow(deprecated)]
+    {
+        use question_answer::models::legacy::LegacyDbError;
+        let LegacyDbError::Locked(_) = LegacyDbError::from(DbError::from(LegacyDbError::Locked(id))) else { unreachable!() };
+    }
+
+    let fixed = FixedClock::new(Utc::now());
+    fixed.advance(Duration::seconds(1));
+    let stepping = SteppingClock::new(Utc::now(), Duration::seconds(1));
+    assert!(stepping.now() < stepping.now());
+    let _: Arc<dyn Clock> = Arc::new(fixed);
+}